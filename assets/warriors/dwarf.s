@@ -0,0 +1,12 @@
+; Dwarf - drops a bomb every few cells while staying alive
+.name "Dwarf"
+.comment "Bombs memory at a fixed stride"
+
+    ld %4, r4           ; stride between bombs
+    ld %0, r3           ; offset of the next bomb
+bomb:
+    live %1
+    sti r2, %64, r3     ; r2 is zero, so every bomb is an invalid opcode
+    add r3, r4, r3
+    ld %0, r16          ; loading zero sets the carry
+    zjmp %:bomb
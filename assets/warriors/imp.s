@@ -0,0 +1,8 @@
+; Imp - the smallest warrior that lasts: report alive, loop forever
+.name "Imp"
+.comment "Reports alive from a three-instruction loop"
+
+imp:
+    live %1
+    ld %0, r2           ; loading zero sets the carry
+    zjmp %:imp
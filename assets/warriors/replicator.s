@@ -0,0 +1,14 @@
+; Replicator - spreads processes across memory so one bomb cannot kill it
+.name "Replicator"
+.comment "Forks near and far, then keeps every copy alive"
+
+start:
+    live %1
+    fork %:spawn
+    ld %0, r16          ; loading zero sets the carry
+    zjmp %:start
+spawn:
+    live %1
+    lfork %1024
+    ld %0, r16
+    zjmp %:spawn
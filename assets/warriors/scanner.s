@@ -0,0 +1,15 @@
+; Scanner - reads memory ahead of itself and bombs whatever it finds
+.name "Scanner"
+.comment "Bombs only the cells that hold code"
+
+    ld %0, r3           ; offset being scanned
+    ld %8, r4           ; scan step
+scan:
+    live %1
+    ldi %48, r3, r5     ; read four bytes at the scanned offset
+    add r3, r4, r3
+    and r5, r5, r6      ; the carry is set when they were all zero
+    zjmp %:scan
+    sti r2, %40, r3     ; found code: bomb it
+    ld %0, r16
+    zjmp %:scan
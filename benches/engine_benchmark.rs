@@ -1,28 +1,109 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use corewar::{GameConfig, GameEngine};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use corewar::examples::EXAMPLES;
+use corewar::vm::DecodedInstruction;
+use corewar::{GameConfig, GameEngine, Memory};
+
+/// Start a battle that keeps every opcode the scheduler handles busy
+///
+/// Both champions are long runs of `live`, so no process runs out of code
+/// or misses a death check within the benchmarked cycles.
+fn battle() -> GameEngine {
+    let mut mixed = vec![0x01; 2000];
+    mixed[5] = 0x0C; // fork
+    mixed[10] = 0x04; // add
+    mixed[15] = 0x03; // st
+    // The builder starts the battle
+    GameEngine::builder()
+        .config(GameConfig::default())
+        .champion_bytes("Mixed", mixed)
+        .champion_bytes("Liver", vec![0x01; 2000])
+        .build()
+        .unwrap()
+}
 
 fn bench_engine_tick(c: &mut Criterion) {
     let mut group = c.benchmark_group("engine_tick");
 
-    // Benchmark setup: Create a GameEngine with a simple champion
-    let config = GameConfig::default();
-    let mut engine = GameEngine::new(config);
+    group.bench_function("tick_1000_cycles", |b| {
+        b.iter_batched(
+            battle,
+            |mut engine| {
+                // Run 1000 cycles
+                for _ in 0..1000 {
+                    assert!(engine.tick().unwrap().is_running());
+                }
+                engine
+            },
+            BatchSize::SmallInput,
+        )
+    });
 
-    // Load a dummy champion (replace with a real champion for more realistic benchmarks)
-    // For simplicity, we'll just set running to true and tick.
-    engine.set_running(true);
+    group.finish();
+}
 
-    group.bench_function("tick_1000_cycles", |b| {
+fn bench_example_battles(c: &mut Criterion) {
+    let mut group = c.benchmark_group("example_battles");
+
+    // Every pairing of the bundled warriors, from loading to the result
+    let cors: Vec<(&str, Vec<u8>)> = EXAMPLES
+        .iter()
+        .map(|example| (example.name, example.assemble().unwrap()))
+        .collect();
+    for (i, (first, first_cor)) in cors.iter().enumerate() {
+        for (second, second_cor) in &cors[i + 1..] {
+            group.bench_function(format!("{}_vs_{}", first, second), |b| {
+                b.iter(|| {
+                    let mut engine = GameEngine::builder()
+                        .champion_cor(first_cor.clone())
+                        .champion_cor(second_cor.clone())
+                        .build()
+                        .unwrap();
+                    engine.run_to_completion().unwrap()
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+
+    // A core full of `sti r1, %5, r2`, decoded at every instruction
+    const STI: [u8; 6] = [0x0B, 0x64, 0x01, 0x00, 0x05, 0x02];
+    let mut memory = Memory::new();
+    let starts: Vec<usize> = (0..memory.size() / STI.len())
+        .map(|i| i * STI.len())
+        .collect();
+    for &address in &starts {
+        memory.load_code(address, &STI, 1).unwrap();
+    }
+
+    group.bench_function("uncached_every_instruction", |b| {
+        b.iter(|| {
+            starts
+                .iter()
+                .map(|&address| DecodedInstruction::read(memory.data(), black_box(address)).size)
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("cached_every_instruction", |b| {
         b.iter(|| {
-            // Run 1000 cycles
-            for _ in 0..1000 {
-                engine.tick().unwrap();
-            }
+            starts
+                .iter()
+                .map(|&address| memory.decode(black_box(address)).size)
+                .sum::<usize>()
         })
     });
 
     group.finish();
 }
 
-criterion_group!(benches, bench_engine_tick);
+criterion_group!(
+    benches,
+    bench_engine_tick,
+    bench_example_battles,
+    bench_decode
+);
 criterion_main!(benches);
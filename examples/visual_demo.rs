@@ -6,7 +6,6 @@
 /// - Animated champion trails
 /// - Real-time battle intensity meter
 /// - Wave animations and color cycling
-
 use corewar::ui::advanced_memory::AdvancedMemoryGrid;
 use corewar::vm::{Memory, Process, Champion, ChampionColor};
 use ratatui::backend::CrosstermBackend;
@@ -44,28 +43,28 @@ fn main() -> io::Result<()> {
     // Main demo loop
     loop {
         // Handle events
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => break,
-                    KeyCode::Char(' ') => {
-                        // Simulate explosion effect
-                        advanced_grid.process_death(&processes[0]);
-                    }
-                    KeyCode::Char('w') => {
-                        // Simulate memory write
-                        advanced_grid.update_memory_access(
-                            rand::random::<u32>() as usize % 1000, 
-                            1
-                        );
-                    }
-                    KeyCode::Char('r') => {
-                        // Reset simulation
-                        advanced_grid = AdvancedMemoryGrid::new();
-                        simulate_battle_activity(&mut memory, &mut advanced_grid, &mut processes);
-                    }
-                    _ => {}
+        if event::poll(Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+        {
+            match key.code {
+                KeyCode::Char('q') => break,
+                KeyCode::Char(' ') => {
+                    // Simulate explosion effect
+                    advanced_grid.process_death(&processes[0]);
+                }
+                KeyCode::Char('w') => {
+                    // Simulate memory write
+                    advanced_grid.update_memory_access(
+                        rand::random::<u32>() as usize % 1000, 
+                        1
+                    );
+                }
+                KeyCode::Char('r') => {
+                    // Reset simulation
+                    advanced_grid = AdvancedMemoryGrid::new();
+                    simulate_battle_activity(&mut memory, &mut advanced_grid, &mut processes);
                 }
+                _ => {}
             }
         }
         
@@ -193,7 +192,7 @@ mod rand {
     use std::cell::RefCell;
     
     thread_local! {
-        static RNG: RefCell<u32> = RefCell::new(1);
+        static RNG: RefCell<u32> = const { RefCell::new(1) };
     }
     
     pub fn random<T>() -> T 
@@ -0,0 +1,82 @@
+/*
+ * C API of the corewar VM
+ *
+ * Build the library with `cargo build --release --lib --features ffi` and
+ * link against target/release/libcorewar.so (libcorewar.dylib on macOS,
+ * corewar.dll on Windows). See src/ffi.rs for the full documentation.
+ *
+ * Functions returning int report failure with -1; corewar_last_error()
+ * then describes it.
+ */
+#ifndef COREWAR_H
+#define COREWAR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A battle; create with corewar_engine_new, destroy with corewar_engine_free */
+typedef struct CorewarEngine CorewarEngine;
+
+/* A live process */
+typedef struct CorewarProcess {
+    uint32_t id;
+    uint8_t champion_id;
+    /* 0 or 1 */
+    uint8_t carry;
+    uint32_t pc;
+    /* Cycles left before the next instruction */
+    uint32_t wait_cycles;
+    /* r1 to r16 */
+    int32_t registers[16];
+} CorewarProcess;
+
+/* Message of the last error on this thread, or NULL */
+const char *corewar_last_error(void);
+
+/* Create an engine with no champions; max_cycles 0 means unlimited */
+CorewarEngine *corewar_engine_new(uint64_t seed, uint32_t max_cycles);
+
+/* Destroy an engine; NULL is ignored */
+void corewar_engine_free(CorewarEngine *engine);
+
+/* Add the contents of a .cor file for the next battle; returns its champion ID */
+int corewar_engine_load(CorewarEngine *engine, const uint8_t *cor, size_t len);
+
+/* Load the champions and start the battle from its first cycle; returns 0 */
+int corewar_engine_start(CorewarEngine *engine);
+
+/* Run up to `cycles` cycles; returns 1 while the battle runs, 0 once it is over */
+int corewar_engine_tick(CorewarEngine *engine, uint32_t cycles);
+
+/* Current cycle, 0 before the battle starts */
+uint32_t corewar_engine_cycle(const CorewarEngine *engine);
+
+/* Winner's champion ID, 0 while the battle runs or if nobody won */
+int corewar_engine_winner(const CorewarEngine *engine);
+
+/* Bytes in memory, 0 before the battle starts */
+size_t corewar_engine_memory_size(const CorewarEngine *engine);
+
+/*
+ * Copy up to `len` bytes of memory and of their owners' champion IDs (0 for
+ * none); either buffer may be NULL. Returns the bytes copied into each.
+ */
+size_t corewar_engine_read_memory(const CorewarEngine *engine, uint8_t *memory, uint8_t *owners,
+                                  size_t len);
+
+/* Live processes, 0 before the battle starts */
+size_t corewar_engine_process_count(const CorewarEngine *engine);
+
+/* Copy up to `capacity` live processes; returns the number copied */
+size_t corewar_engine_read_processes(const CorewarEngine *engine, CorewarProcess *processes,
+                                     size_t capacity);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* COREWAR_H */
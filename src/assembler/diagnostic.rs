@@ -0,0 +1,308 @@
+/// Located assembler errors
+///
+/// Every stage of the assembler records its problems as [`Diagnostic`]s and
+/// carries on, so all the errors in a warrior can be fixed in one pass.
+/// Messages never spell out where they happened: the line and column travel
+/// beside them and are attached where an error is caught, if the code that
+/// raised it did not know them. Once the assembler has mapped a diagnostic
+/// back to its file, it is rendered with the offending source line:
+///
+/// ```text
+/// Undefined label 'nowhere'
+///   --> warrior.s:3:10
+///    |
+///  3 |     live %:nowhere
+///    |          ^
+/// ```
+use crate::error::{CoreWarError, Result};
+use std::fmt;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The program cannot be assembled
+    Error,
+    /// The program assembles but is probably wrong
+    Warning,
+}
+
+/// One located assembler error or warning
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Whether this is an error or a warning
+    pub severity: Severity,
+    /// What went wrong, without the location
+    pub message: String,
+    /// Line the error is on, or 0 when it is about the whole program
+    pub line: usize,
+    /// Column the error starts at, when known
+    pub column: Option<usize>,
+    /// File the line is in, once known
+    pub file: Option<String>,
+    /// Text of the line, once known
+    pub source_line: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic about the whole program
+    pub fn general(message: impl Into<String>) -> Self {
+        Self::at_line(0, message)
+    }
+
+    /// Create a diagnostic about a line
+    pub fn at_line(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            line,
+            column: None,
+            file: None,
+            source_line: None,
+        }
+    }
+
+    /// Create a diagnostic about a position in a line
+    pub fn at(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            column: Some(column),
+            ..Self::at_line(line, message)
+        }
+    }
+
+    /// Turn this diagnostic into a warning
+    pub fn into_warning(self) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..self
+        }
+    }
+}
+
+impl From<Diagnostic> for CoreWarError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        CoreWarError::Assembly {
+            diagnostics: vec![diagnostic],
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.severity == Severity::Warning {
+            write!(f, "warning: ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if self.line == 0 {
+            return Ok(());
+        }
+
+        let file = self.file.as_deref().unwrap_or("<source>");
+        write!(f, "\n  --> {}:{}", file, self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        if let Some(source_line) = &self.source_line {
+            let gutter = " ".repeat(self.line.to_string().len());
+            write!(f, "\n {} |\n {} | {}", gutter, self.line, source_line)?;
+            if let Some(column) = self.column {
+                // Tabs keep their width so the caret lines up
+                let indent: String = source_line
+                    .chars()
+                    .take(column.saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                write!(f, "\n {} | {}^", gutter, indent)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Render diagnostics as one report
+///
+/// # Returns
+/// The single diagnostic, or a count followed by every diagnostic
+pub fn report(diagnostics: &[Diagnostic]) -> String {
+    match diagnostics {
+        [diagnostic] => diagnostic.to_string(),
+        _ => {
+            let kind = if diagnostics.iter().all(|d| d.severity == Severity::Warning) {
+                "warnings"
+            } else {
+                "errors"
+            };
+            let mut report = format!("{} {}", diagnostics.len(), kind);
+            for diagnostic in diagnostics {
+                report.push_str("\n\n");
+                report.push_str(&diagnostic.to_string());
+            }
+            report
+        }
+    }
+}
+
+/// Attach a location to an error that has none
+///
+/// Errors that already carry diagnostics keep their locations; any other
+/// error becomes a diagnostic at `line` and `column`.
+pub fn locate(error: CoreWarError, line: usize, column: Option<usize>) -> CoreWarError {
+    match error {
+        CoreWarError::Assembly { .. } => error,
+        error => Diagnostic {
+            column,
+            ..Diagnostic::at_line(line, message_of(error))
+        }
+        .into(),
+    }
+}
+
+/// Get an error's message without the assembler's prefix
+fn message_of(error: CoreWarError) -> String {
+    match error {
+        CoreWarError::Assembler { message } => message,
+        error => error.to_string(),
+    }
+}
+
+/// Diagnostics collected over an assembly
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Create an empty collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a diagnostic
+    ///
+    /// Only the first error on a line is kept: a line that failed in one
+    /// stage usually fails again in the next because of it.
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        if diagnostic.line == 0 || self.items.iter().all(|d| d.line != diagnostic.line) {
+            self.items.push(diagnostic);
+        }
+    }
+
+    /// Record an error, locating it at `line` and `column` if it has no location
+    pub fn push_error(&mut self, error: CoreWarError, line: usize, column: Option<usize>) {
+        match locate(error, line, column) {
+            CoreWarError::Assembly { diagnostics } => {
+                for diagnostic in diagnostics {
+                    self.push(diagnostic);
+                }
+            }
+            _ => unreachable!("located errors carry diagnostics"),
+        }
+    }
+
+    /// Number of diagnostics recorded
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Check whether nothing went wrong
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterate over the diagnostics in the order they were recorded
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Diagnostic> {
+        self.items.iter_mut()
+    }
+
+    /// Turn the collection into the result of a stage
+    ///
+    /// # Returns
+    /// `value` when nothing was recorded, otherwise an error carrying every diagnostic
+    pub fn into_result<T>(self, value: T) -> Result<T> {
+        if self.items.is_empty() {
+            Ok(value)
+        } else {
+            Err(CoreWarError::Assembly {
+                diagnostics: self.items,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_rendering() {
+        let mut diagnostic = Diagnostic::at(12, 8, "Undefined label 'nowhere'");
+        assert_eq!(
+            diagnostic.to_string(),
+            "Undefined label 'nowhere'\n  --> <source>:12:8"
+        );
+
+        diagnostic.file = Some("warrior.s".to_string());
+        diagnostic.source_line = Some("\tlive  %:nowhere".to_string());
+        assert_eq!(
+            diagnostic.to_string(),
+            "Undefined label 'nowhere'\n  --> warrior.s:12:8\n    |\n 12 | \tlive  %:nowhere\n    | \t      ^"
+        );
+        assert_eq!(
+            Diagnostic::general(".name directive is required").to_string(),
+            ".name directive is required"
+        );
+
+        let warnings = [
+            Diagnostic::general("Warrior never executes live").into_warning(),
+            Diagnostic::at_line(3, "Label 'x' is never used").into_warning(),
+        ];
+        assert_eq!(
+            report(&warnings),
+            "2 warnings\n\nwarning: Warrior never executes live\n\n\
+             warning: Label 'x' is never used\n  --> <source>:3"
+        );
+    }
+
+    #[test]
+    fn test_collect_and_locate_errors() {
+        let mut diagnostics = Diagnostics::new();
+        assert!(diagnostics.clone().into_result(()).is_ok());
+
+        diagnostics.push_error(
+            CoreWarError::assembler("Register out of range: r17"),
+            4,
+            Some(9),
+        );
+        diagnostics.push_error(
+            Diagnostic::at(2, 1, "Unknown instruction 'mov'").into(),
+            4,
+            None,
+        );
+        diagnostics.push_error(
+            CoreWarError::instruction("Invalid parameter count"),
+            5,
+            None,
+        );
+        // A second error on a line is taken to follow from the first
+        diagnostics.push(Diagnostic::at_line(5, "Undefined label 'x'"));
+        assert_eq!(diagnostics.len(), 3);
+
+        let error = diagnostics.into_result(()).unwrap_err();
+        let CoreWarError::Assembly { diagnostics } = &error else {
+            panic!("expected diagnostics");
+        };
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic::at(4, 9, "Register out of range: r17")
+        );
+        assert_eq!(diagnostics[1].line, 2);
+        assert_eq!(
+            diagnostics[2].message,
+            "Instruction error: Invalid parameter count"
+        );
+
+        let text = error.to_string();
+        assert!(text.starts_with(
+            "Assembler error: 3 errors\n\nRegister out of range: r17\n  --> <source>:4:9"
+        ));
+    }
+}
@@ -0,0 +1,366 @@
+/// Disassembler for Core War bytecode
+///
+/// Turns the code of a .cor file back into Redcode that assembles to the
+/// same bytes. Targets of `zjmp`, `fork` and `lfork` that start an
+/// instruction get a label, named after their address, so the control flow
+/// reads as it was written:
+///
+/// ```text
+/// .name "imp"
+///
+/// label_0:
+///     live %1
+///     zjmp %:label_0
+/// ```
+///
+/// Bytes that do not decode to an instruction the assembler could have
+/// written are shown as a comment, one byte at a time.
+///
+/// [`disassemble_memory`] decodes the instructions around an address of a
+/// running core instead, for the visualizer.
+use crate::error::Result;
+use crate::vm::Memory;
+use crate::vm::decode::MAX_INSTRUCTION_SIZE;
+use crate::vm::instruction::{Instruction, ParameterType};
+use crate::vm::loader::ChampionLoader;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// One decoded instruction
+#[derive(Debug, Clone)]
+pub(crate) struct Decoded {
+    /// Offset of the instruction in the code
+    pub address: usize,
+    /// The instruction
+    pub instruction: Instruction,
+    /// Type and value of each parameter, sign-extended from its encoded size
+    pub parameters: Vec<(ParameterType, i32)>,
+    /// Encoded size in bytes
+    pub size: usize,
+}
+
+/// One line of a disassembly of memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryLine {
+    /// Address of the instruction or data byte
+    pub address: usize,
+    /// Size in bytes
+    pub size: usize,
+    /// The instruction as Redcode, or the data byte as a comment
+    pub text: String,
+}
+
+/// Disassemble the instructions around an address in memory
+///
+/// Instructions have different sizes, so where those before `address`
+/// start is worked out by decoding forward from the furthest start that
+/// lands on `address` with no undecodable bytes on the way. If there is
+/// none, no lines come before `address`. Bytes that do not decode are shown
+/// one at a time.
+///
+/// # Arguments
+/// * `memory` - The memory to read, wrapping around its end
+/// * `address` - Address of the instruction of interest
+/// * `before` - Most lines to show before it
+/// * `after` - Lines to show after it
+///
+/// # Returns
+/// The lines in address order; the one at `address` is the first of the
+/// last `after + 1`
+pub fn disassemble_memory(
+    memory: &Memory,
+    address: usize,
+    before: usize,
+    after: usize,
+) -> Vec<MemoryLine> {
+    let size = memory.size();
+    let back = (before * MAX_INSTRUCTION_SIZE).min(size);
+    let start = (address % size + size - back) % size;
+    let bytes: Vec<u8> = (0..back + (after + 1) * MAX_INSTRUCTION_SIZE)
+        .map(|i| memory.read_byte(start + i))
+        .collect();
+    let line = |decoded: std::result::Result<Decoded, usize>| match decoded {
+        Ok(instruction) => MemoryLine {
+            address: (start + instruction.address) % size,
+            size: instruction.size,
+            text: format_instruction(&instruction, &BTreeSet::new()),
+        },
+        Err(offset) => MemoryLine {
+            address: (start + offset) % size,
+            size: 1,
+            text: format!("; data {:02x}", bytes[offset]),
+        },
+    };
+
+    let mut lines: Vec<MemoryLine> = (0..back)
+        .find_map(|lead_in| {
+            let mut chain = Vec::new();
+            let mut offset = lead_in;
+            while offset < back {
+                let instruction = decode(&bytes, offset)?;
+                offset += instruction.size;
+                chain.push(instruction);
+            }
+            (offset == back).then_some(chain)
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .take(before)
+        .rev()
+        .map(|instruction| line(Ok(instruction)))
+        .collect();
+
+    let mut offset = back;
+    for _ in 0..=after {
+        let decoded = decode(&bytes, offset).ok_or(offset);
+        offset += decoded.as_ref().map_or(1, |instruction| instruction.size);
+        lines.push(line(decoded));
+    }
+    lines
+}
+
+/// Disassemble the contents of a .cor file
+///
+/// # Arguments
+/// * `bytes` - Header followed by code, as written by the assembler
+///
+/// # Returns
+/// Redcode source for the champion, or an error if the header is invalid
+pub fn disassemble(bytes: &[u8]) -> Result<String> {
+    let champion = ChampionLoader::new(false).load_champion_from_bytes(bytes, 1, None)?;
+    Ok(disassemble_code(
+        &champion.name,
+        &champion.comment,
+        &champion.code,
+    ))
+}
+
+/// Disassemble a champion's code
+///
+/// # Arguments
+/// * `name` - Champion name, written as `.name`
+/// * `comment` - Champion comment, written as `.comment` unless empty
+/// * `code` - The code, without the header
+///
+/// # Returns
+/// Redcode source for the champion
+pub fn disassemble_code(name: &str, comment: &str, code: &[u8]) -> String {
+    let decoded = decode_all(code);
+    let starts: BTreeSet<usize> = decoded
+        .iter()
+        .filter_map(|d| d.as_ref().ok().map(|d| d.address))
+        .collect();
+    let labels: BTreeSet<usize> = decoded
+        .iter()
+        .filter_map(|d| d.as_ref().ok())
+        .filter_map(|d| jump_target(d).filter(|target| starts.contains(target)))
+        .collect();
+
+    let mut source = format!(".name \"{}\"\n", escape(name));
+    if !comment.is_empty() {
+        writeln!(source, ".comment \"{}\"", escape(comment)).unwrap();
+    }
+    source.push('\n');
+
+    for entry in &decoded {
+        match entry {
+            Ok(instruction) => {
+                if labels.contains(&instruction.address) {
+                    writeln!(source, "{}:", label(instruction.address)).unwrap();
+                }
+                writeln!(source, "    {}", format_instruction(instruction, &labels)).unwrap();
+            }
+            Err(offset) => {
+                writeln!(source, "    ; data at {}: {:02x}", offset, code[*offset]).unwrap();
+            }
+        }
+    }
+
+    source
+}
+
+/// Decode a champion's code
+///
+/// # Returns
+/// The instructions in order, with the offset of every byte that does not
+/// start one in their place
+pub(crate) fn decode_all(code: &[u8]) -> Vec<std::result::Result<Decoded, usize>> {
+    let mut decoded = Vec::new();
+    let mut offset = 0;
+    while offset < code.len() {
+        match decode(code, offset) {
+            Some(instruction) => {
+                offset += instruction.size;
+                decoded.push(Ok(instruction));
+            }
+            None => {
+                decoded.push(Err(offset));
+                offset += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Decode the instruction at `offset`, if the bytes there form one
+fn decode(code: &[u8], offset: usize) -> Option<Decoded> {
+    let instruction = Instruction::from_opcode(code[offset]).ok()?;
+    let types = if instruction.has_parameter_types() {
+        *code.get(offset + 1)?
+    } else {
+        // The single direct parameter of live, zjmp, fork and lfork
+        ParameterType::Direct.type_code() << 6
+    };
+
+    let mut parameters = Vec::new();
+    let mut end = offset + instruction.header_size();
+    for i in 0..4 {
+        let type_code = (types >> (6 - i * 2)) & 0x3;
+        if i >= instruction.parameter_count() {
+            // The assembler leaves the codes of missing parameters empty
+            if type_code != 0 {
+                return None;
+            }
+            continue;
+        }
+        if type_code == 0 {
+            return None;
+        }
+
+        let parameter_type = ParameterType::from_type_code(type_code);
+        let size = instruction.parameter_size(parameter_type);
+        let bytes = code.get(end..end + size)?;
+        let value = match size {
+            1 => bytes[0] as i32,
+            2 => i16::from_be_bytes([bytes[0], bytes[1]]) as i32,
+            _ => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        };
+        if parameter_type == ParameterType::Register && !(1..=16).contains(&value) {
+            return None;
+        }
+        parameters.push((parameter_type, value));
+        end += size;
+    }
+
+    Some(Decoded {
+        address: offset,
+        instruction,
+        parameters,
+        size: end - offset,
+    })
+}
+
+/// Get the code address a jump or fork goes to
+fn jump_target(decoded: &Decoded) -> Option<usize> {
+    match (decoded.instruction, decoded.parameters.first()) {
+        (
+            Instruction::Zjmp | Instruction::Fork | Instruction::Lfork,
+            Some((ParameterType::Direct, offset)),
+        ) => usize::try_from(decoded.address as i64 + *offset as i64).ok(),
+        _ => None,
+    }
+}
+
+/// Write an instruction as Redcode, with labels for the jump targets in `labels`
+pub(crate) fn format_instruction(decoded: &Decoded, labels: &BTreeSet<usize>) -> String {
+    let target = jump_target(decoded).filter(|target| labels.contains(target));
+    let parameters: Vec<String> = decoded
+        .parameters
+        .iter()
+        .enumerate()
+        .map(|(i, (parameter_type, value))| match parameter_type {
+            ParameterType::Register => format!("r{}", value),
+            ParameterType::Direct if i == 0 && target.is_some() => {
+                format!("%:{}", label(target.unwrap_or_default()))
+            }
+            ParameterType::Direct | ParameterType::Label => format!("%{}", value),
+            ParameterType::Indirect => value.to_string(),
+        })
+        .collect();
+
+    format!("{} {}", decoded.instruction.name(), parameters.join(", "))
+}
+
+/// Name of the label at a code address
+fn label(address: usize) -> String {
+    format!("label_{}", address)
+}
+
+/// Escape a string for a Redcode string literal
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::Assembler;
+
+    #[test]
+    fn test_disassembly_reconstructs_labels() {
+        let source = ".name \"imp \\\"2\\\"\"\n.comment \"loops\"\n\
+                      start: live %1\nld %0, r2\nfork %:start\nsti r2, %-8, 300\nzjmp %:start\n";
+        let bytecode = Assembler::new(false).assemble_source(source).unwrap();
+
+        let text = disassemble(&bytecode).unwrap();
+        assert_eq!(
+            text,
+            ".name \"imp \\\"2\\\"\"\n.comment \"loops\"\n\n\
+             label_0:\n    live %1\n    ld %0, r2\n    fork %:label_0\n\
+             \x20   sti r2, %-8, 300\n    zjmp %:label_0\n"
+        );
+        assert_eq!(
+            Assembler::new(false).assemble_source(&text).unwrap(),
+            bytecode
+        );
+    }
+
+    #[test]
+    fn test_undecodable_bytes_become_comments() {
+        // A zjmp outside the code, a bad opcode, a register out of range
+        // and a type code for a parameter add does not have
+        let code = [0x09, 0x10, 0x00, 0xff, 0x10, 0x40, 0x00, 0x04, 0x55];
+        assert_eq!(
+            disassemble_code("x", "", &code),
+            ".name \"x\"\n\n    zjmp %4096\n    ; data at 3: ff\n    \
+             ; data at 4: 10\n    ; data at 5: 40\n    ; data at 6: 00\n    \
+             ; data at 7: 04\n    ; data at 8: 55\n"
+        );
+        assert!(disassemble(&code).is_err());
+    }
+
+    #[test]
+    fn test_memory_disassembly_around_an_address() {
+        let code = Assembler::new(false)
+            .assemble_source(".name \"x\"\nlive %1\nld %0, r2\nzjmp %-13\n")
+            .unwrap();
+        let code = &code[crate::vm::loader::HEADER_SIZE..];
+        let mut memory = Memory::new();
+        let end = memory.size() - 5;
+        memory.load_code(end, code, 1).unwrap();
+
+        // The code wraps around the end of memory; where live and ld start is worked out
+        let lines = disassemble_memory(&memory, 7, 2, 1);
+        let text: Vec<(usize, &str)> = lines.iter().map(|l| (l.address, l.text.as_str())).collect();
+        assert_eq!(
+            text,
+            [
+                (end, "live %1"),
+                (0, "ld %0, r2"),
+                (7, "zjmp %-13"),
+                (10, "; data 00")
+            ]
+        );
+    }
+}
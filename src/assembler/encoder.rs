@@ -1,22 +1,25 @@
-use crate::assembler::{AstNode, InstructionNode, ParameterNode};
+use crate::assembler::diagnostic::{self, Diagnostic, Diagnostics};
+use crate::assembler::{AstNode, InstructionNode, ParameterNode, expression};
 /// Bytecode encoder for Core War
 ///
 /// This module generates bytecode from an Abstract Syntax Tree (AST)
 /// representation of a Redcode program.
 use crate::error::{CoreWarError, Result};
 use crate::vm::instruction::{CompleteInstruction, Instruction, Parameter, ParameterType};
+use crate::vm::loader::ChampionHeader;
 use std::collections::HashMap;
 
-/// Magic number for Core War executable files
-const COR_MAGIC: u32 = 0xea83f3;
-
 /// Encoder for generating Core War bytecode
 #[derive(Debug)]
 pub struct Encoder {
-    /// Symbol table for label resolution
+    /// Symbol table for label resolution: code address of each label
     symbol_table: HashMap<String, usize>,
+    /// Source line each label is defined on
+    label_lines: HashMap<String, usize>,
     /// Current code address
     current_address: usize,
+    /// Each instruction as the last encoding resolved it
+    encoded: Vec<Option<CompleteInstruction>>,
 }
 
 impl Encoder {
@@ -24,90 +27,175 @@ impl Encoder {
     pub fn new() -> Self {
         Self {
             symbol_table: HashMap::new(),
+            label_lines: HashMap::new(),
             current_address: 0,
+            encoded: Vec::new(),
         }
     }
 
+    /// Get the instructions of the last encoding, as they were resolved
+    ///
+    /// # Returns
+    /// One entry per instruction of the AST, None for those that failed
+    pub fn instructions(&self) -> &[Option<CompleteInstruction>] {
+        &self.encoded
+    }
+
     /// Encode an AST into bytecode
     ///
     /// # Arguments
     /// * `ast` - The Abstract Syntax Tree to encode
     ///
     /// # Returns
-    /// The generated bytecode as a Vec<u8>
+    /// The generated bytecode as a Vec<u8>, or an error carrying every problem found
     pub fn encode(&mut self, ast: &AstNode) -> Result<Vec<u8>> {
-        // First pass: build symbol table
-        self.build_symbol_table(&ast.instructions)?;
+        let mut diagnostics = Diagnostics::new();
+        let bytecode = self.encode_collecting(ast, &mut diagnostics);
+        diagnostics.into_result(bytecode)
+    }
 
-        // Debug: print symbol table
-        println!("[DEBUG] Symbol table: {{");
-        for (label, addr) in &self.symbol_table {
-            println!("    '{}': {}", label, addr);
-        }
-        println!("}}");
+    /// Encode an AST into bytecode, recording errors instead of stopping at them
+    ///
+    /// An instruction that fails to encode keeps the size it was given in
+    /// the first pass, so the labels after it still resolve as they would.
+    ///
+    /// # Arguments
+    /// * `ast` - The Abstract Syntax Tree to encode
+    /// * `diagnostics` - Receives an error for every instruction that failed
+    ///
+    /// # Returns
+    /// The generated bytecode, which is incomplete if any error was recorded
+    pub fn encode_collecting(&mut self, ast: &AstNode, diagnostics: &mut Diagnostics) -> Vec<u8> {
+        // First pass: build symbol table
+        let sizes = self.build_symbol_table(&ast.instructions, diagnostics);
 
-        // Second pass: generate code
-        let code = self.generate_code(&ast.instructions)?;
+        // Second pass: generate code, resolving labels against the table
+        let code = self.generate_code(&ast.instructions, &sizes, diagnostics);
 
         // Generate header and combine with code
-        let header = self.generate_header(&ast.header.name, &ast.header.comment, code.len())?;
-
         let mut bytecode = Vec::new();
-        bytecode.extend(header);
+        match self.generate_header(&ast.header.name, &ast.header.comment, code.len()) {
+            Ok(header) => bytecode.extend(header),
+            Err(error) => diagnostics.push_error(error, 0, None),
+        }
         bytecode.extend(code);
 
-        Ok(bytecode)
+        bytecode
     }
 
     /// Build the symbol table by scanning for labels
-    fn build_symbol_table(&mut self, instructions: &[InstructionNode]) -> Result<()> {
+    ///
+    /// # Returns
+    /// The size of each instruction, or None for one whose size is unknown
+    fn build_symbol_table(
+        &mut self,
+        instructions: &[InstructionNode],
+        diagnostics: &mut Diagnostics,
+    ) -> Vec<Option<usize>> {
         self.current_address = 0;
         self.symbol_table.clear();
+        self.label_lines.clear();
 
+        let mut sizes = Vec::with_capacity(instructions.len());
         for instruction in instructions {
             // Add label to symbol table if present
             if let Some(ref label) = instruction.label {
                 let normalized_label = label.trim().trim_end_matches(':');
-                if self.symbol_table.contains_key(normalized_label) {
-                    return Err(CoreWarError::assembler(format!(
-                        "Duplicate label '{}' at line {}",
-                        normalized_label, instruction.line_number
-                    )));
+                if let Some(first_line) = self.label_lines.get(normalized_label) {
+                    diagnostics.push(Diagnostic::at_line(
+                        instruction.line_number,
+                        format!(
+                            "Duplicate label '{}' (first defined at line {})",
+                            normalized_label, first_line
+                        ),
+                    ));
+                } else {
+                    self.symbol_table
+                        .insert(normalized_label.to_string(), self.current_address);
+                    self.label_lines
+                        .insert(normalized_label.to_string(), instruction.line_number);
                 }
-                self.symbol_table
-                    .insert(normalized_label.to_string(), self.current_address);
             }
 
             // Calculate instruction size
-            let instruction_enum = self.parse_instruction_mnemonic(&instruction.mnemonic)?;
-            let size =
-                self.calculate_instruction_size(&instruction_enum, &instruction.parameters)?;
-            self.current_address += size;
+            let size = self
+                .parse_instruction_mnemonic(&instruction.mnemonic)
+                .and_then(|instruction_enum| {
+                    self.calculate_instruction_size(&instruction_enum, &instruction.parameters)
+                });
+            match size {
+                Ok(size) => {
+                    self.current_address += size;
+                    sizes.push(Some(size));
+                }
+                Err(error) => {
+                    diagnostics.push_error(error, instruction.line_number, None);
+                    sizes.push(None);
+                }
+            }
         }
 
-        Ok(())
+        sizes
     }
 
     /// Generate the actual bytecode
-    fn generate_code(&mut self, instructions: &[InstructionNode]) -> Result<Vec<u8>> {
+    ///
+    /// # Arguments
+    /// * `instructions` - The instructions to encode
+    /// * `sizes` - Size of each instruction from the first pass; those
+    ///   without one already have an error and are skipped
+    /// * `diagnostics` - Receives an error for every instruction that failed
+    fn generate_code(
+        &mut self,
+        instructions: &[InstructionNode],
+        sizes: &[Option<usize>],
+        diagnostics: &mut Diagnostics,
+    ) -> Vec<u8> {
         let mut code = Vec::new();
         self.current_address = 0;
+        self.encoded.clear();
 
-        for instruction in instructions {
-            let bytecode = self.encode_instruction(instruction)?;
-            code.extend(bytecode);
+        for (instruction, size) in instructions.iter().zip(sizes) {
+            let Some(size) = size else {
+                self.encoded.push(None);
+                continue;
+            };
+            let start_address = self.current_address;
+            match self.encode_instruction(instruction) {
+                Ok(bytecode) => code.extend(bytecode),
+                Err(error) => {
+                    diagnostics.push_error(error, instruction.line_number, None);
+                    self.current_address = start_address + size;
+                    self.encoded.push(None);
+                }
+            }
         }
 
-        Ok(code)
+        code
     }
 
     /// Encode a single instruction
     fn encode_instruction(&mut self, instruction_node: &InstructionNode) -> Result<Vec<u8>> {
         let instruction = self.parse_instruction_mnemonic(&instruction_node.mnemonic)?;
-        let parameters = self.parse_parameters(&instruction_node.parameters)?;
-
-        let complete_instruction = CompleteInstruction::new(instruction, parameters)?;
         let start_address = self.current_address;
+        let parameters = self.parse_parameters(
+            &instruction_node.parameters,
+            start_address,
+            instruction_node.line_number,
+        )?;
+
+        // Without a parameter types byte, nothing but a direct value can be encoded
+        if !instruction.has_parameter_types()
+            && parameters
+                .iter()
+                .any(|p| p.param_type.type_code() != ParameterType::Direct.type_code())
+        {
+            return Err(CoreWarError::assembler(format!(
+                "{} takes a direct parameter, such as %1",
+                instruction.name()
+            )));
+        }
+        let complete_instruction = CompleteInstruction::new(instruction, parameters)?;
 
         // Update current address for next instruction
         self.current_address += complete_instruction.size();
@@ -118,24 +206,19 @@ impl Encoder {
         // Encode opcode
         bytecode.push(complete_instruction.instruction.opcode());
 
-        // Encode parameter types (2 bits per parameter, packed into bytes)
-        let param_types_byte = self.encode_parameter_types(&complete_instruction.parameters);
-        bytecode.push(param_types_byte);
+        // Encode parameter types (2 bits per parameter, packed into bytes),
+        // except for the instructions whose single parameter is always direct
+        if instruction.has_parameter_types() {
+            bytecode.push(self.encode_parameter_types(&complete_instruction.parameters));
+        }
 
         // Encode parameters
-        for (i, parameter) in complete_instruction.parameters.iter().enumerate() {
-            let param_bytes = self.encode_parameter(
-                parameter,
-                start_address
-                    + 2
-                    + complete_instruction.parameters[..i]
-                        .iter()
-                        .map(|p| p.param_type.size())
-                        .sum::<usize>(),
-            )?;
-            bytecode.extend(param_bytes);
+        for parameter in &complete_instruction.parameters {
+            let size = instruction.parameter_size(parameter.param_type);
+            bytecode.extend(self.encode_parameter(parameter, size));
         }
 
+        self.encoded.push(Some(complete_instruction));
         Ok(bytecode)
     }
 
@@ -158,6 +241,10 @@ impl Encoder {
             "lldi" => Ok(Instruction::Lldi),
             "lfork" => Ok(Instruction::Lfork),
             "aff" => Ok(Instruction::Aff),
+            #[cfg(feature = "pspace")]
+            "ldp" => Ok(Instruction::Ldp),
+            #[cfg(feature = "pspace")]
+            "stp" => Ok(Instruction::Stp),
             _ => Err(CoreWarError::assembler(format!(
                 "Unknown instruction: {}",
                 mnemonic
@@ -166,74 +253,90 @@ impl Encoder {
     }
 
     /// Parse parameter nodes into Parameter structs
-    fn parse_parameters(&self, param_nodes: &[ParameterNode]) -> Result<Vec<Parameter>> {
-        let mut parameters = Vec::new();
-
-        for param_node in param_nodes {
-            let parameter = match param_node.param_type.as_str() {
-                "register" => {
-                    let reg_num: u8 =
-                        param_node
-                            .value
-                            .trim_start_matches('r')
-                            .parse()
-                            .map_err(|_| {
-                                CoreWarError::assembler(format!(
-                                    "Invalid register: {}",
-                                    param_node.value
-                                ))
-                            })?;
-
-                    if reg_num == 0 || reg_num > 16 {
-                        return Err(CoreWarError::assembler(format!(
-                            "Register out of range: r{}",
-                            reg_num
-                        )));
-                    }
-
-                    Parameter::register(reg_num)
-                }
-                "direct" => {
-                    let value: i32 = param_node.value.parse().map_err(|_| {
-                        CoreWarError::assembler(format!(
-                            "Invalid direct value: {}",
-                            param_node.value
-                        ))
-                    })?;
-                    Parameter::direct(value)
-                }
-                "indirect" => {
-                    let value: i32 = param_node.value.parse().map_err(|_| {
-                        CoreWarError::assembler(format!(
-                            "Invalid indirect value: {}",
-                            param_node.value
-                        ))
-                    })?;
-                    Parameter::indirect(value)
-                }
-                "label" => {
-                    let normalized_label = param_node.value.trim().trim_end_matches(':');
-                    let label_address =
-                        self.symbol_table.get(normalized_label).ok_or_else(|| {
+    ///
+    /// Labels resolve to their offset from the start of the instruction
+    /// using them, so forward references are positive and backward ones
+    /// negative.
+    ///
+    /// # Arguments
+    /// * `param_nodes` - The instruction's parameters
+    /// * `start_address` - Code address of the instruction
+    /// * `line_number` - Source line of the instruction, for errors
+    fn parse_parameters(
+        &self,
+        param_nodes: &[ParameterNode],
+        start_address: usize,
+        line_number: usize,
+    ) -> Result<Vec<Parameter>> {
+        param_nodes
+            .iter()
+            .map(|param_node| {
+                self.parse_parameter(param_node, start_address)
+                    .map_err(|e| diagnostic::locate(e, line_number, Some(param_node.column)))
+            })
+            .collect()
+    }
+
+    /// Parse one parameter node into a Parameter
+    fn parse_parameter(
+        &self,
+        param_node: &ParameterNode,
+        start_address: usize,
+    ) -> Result<Parameter> {
+        let parameter = match param_node.param_type.as_str() {
+            "register" => {
+                let reg_num: u8 =
+                    param_node
+                        .value
+                        .trim_start_matches('r')
+                        .parse()
+                        .map_err(|_| {
                             CoreWarError::assembler(format!(
-                                "Undefined label: {}",
-                                normalized_label
+                                "Invalid register: {}",
+                                param_node.value
                             ))
                         })?;
-                    Parameter::label(*label_address as i32)
-                }
-                _ => {
+
+                if reg_num == 0 || reg_num > 16 {
                     return Err(CoreWarError::assembler(format!(
-                        "Unknown parameter type: {}",
-                        param_node.param_type
+                        "Register out of range: r{}",
+                        reg_num
                     )));
                 }
-            };
 
-            parameters.push(parameter);
-        }
+                Parameter::register(reg_num)
+            }
+            "direct" => Parameter::direct(self.evaluate(&param_node.value, start_address)?),
+            "indirect" => Parameter::indirect(self.evaluate(&param_node.value, start_address)?),
+            "label" => {
+                let normalized_label = param_node.value.trim().trim_end_matches(':');
+                let label_address = self.symbol_table.get(normalized_label).ok_or_else(|| {
+                    CoreWarError::assembler(format!("Undefined label '{}'", normalized_label))
+                })?;
+                Parameter::label(*label_address as i32 - start_address as i32)
+            }
+            _ => {
+                return Err(CoreWarError::assembler(format!(
+                    "Unknown parameter type: {}",
+                    param_node.param_type
+                )));
+            }
+        };
 
-        Ok(parameters)
+        Ok(parameter)
+    }
+
+    /// Evaluate a numeric parameter, which may be an expression over labels
+    ///
+    /// # Arguments
+    /// * `text` - The parameter's value, e.g. `42` or `:end-:start`
+    /// * `start_address` - Code address of the instruction, which labels are relative to
+    fn evaluate(&self, text: &str, start_address: usize) -> Result<i32> {
+        expression::evaluate(text, |label| {
+            self.symbol_table
+                .get(label)
+                .map(|&address| address as i32 - start_address as i32)
+        })
     }
 
     /// Calculate the size of an instruction in bytes
@@ -251,12 +354,14 @@ impl Encoder {
             )));
         }
 
-        let mut size = 2; // opcode + parameter types byte
+        let mut size = instruction.header_size();
 
         for param in parameters {
             size += match param.param_type.as_str() {
-                "register" => 1,
-                "direct" | "indirect" | "label" => 2,
+                "register" => instruction.parameter_size(ParameterType::Register),
+                "direct" => instruction.parameter_size(ParameterType::Direct),
+                "indirect" => instruction.parameter_size(ParameterType::Indirect),
+                "label" => instruction.parameter_size(ParameterType::Label),
                 _ => {
                     return Err(CoreWarError::assembler(format!(
                         "Unknown parameter type: {}",
@@ -286,57 +391,30 @@ impl Encoder {
     }
 
     /// Encode a single parameter
-    fn encode_parameter(&self, parameter: &Parameter, _current_pc: usize) -> Result<Vec<u8>> {
-        match parameter.param_type {
-            ParameterType::Register => Ok(vec![parameter.value as u8]),
-            ParameterType::Direct | ParameterType::Indirect | ParameterType::Label => {
-                // Encode as 16-bit little-endian
-                let value = parameter.value as u16;
-                Ok(vec![(value & 0xFF) as u8, ((value >> 8) & 0xFF) as u8])
-            }
+    ///
+    /// # Arguments
+    /// * `parameter` - The parameter to encode
+    /// * `size` - Encoded size in bytes, from [`Instruction::parameter_size`]
+    fn encode_parameter(&self, parameter: &Parameter, size: usize) -> Vec<u8> {
+        match size {
+            1 => vec![parameter.value as u8],
+            // Encode as 16-bit big-endian
+            2 => (parameter.value as u16).to_be_bytes().to_vec(),
+            // Encode as 32-bit big-endian
+            _ => parameter.value.to_be_bytes().to_vec(),
         }
     }
 
     /// Generate the champion header
     fn generate_header(&self, name: &str, comment: &str, code_size: usize) -> Result<Vec<u8>> {
         let mut header = Vec::new();
-
-        // Magic number (4 bytes, little-endian)
-        let magic_bytes = COR_MAGIC.to_le_bytes();
-        header.extend(&magic_bytes);
-
-        // Program name (128 bytes, null-terminated)
-        let mut name_bytes = [0u8; 128];
-        let name_bytes_src = name.as_bytes();
-        if name_bytes_src.len() >= 128 {
-            return Err(CoreWarError::assembler(
-                "Program name too long (max 127 characters)".to_string(),
-            ));
-        }
-        name_bytes[..name_bytes_src.len()].copy_from_slice(name_bytes_src);
-        header.extend(&name_bytes);
-
-        // Padding (4 bytes)
-        header.extend(&[0u8; 4]);
-
-        // Code size (4 bytes, little-endian)
-        let size_bytes = (code_size as u32).to_le_bytes();
-        header.extend(&size_bytes);
-
-        // Comment (128 bytes, null-terminated)
-        let mut comment_bytes = [0u8; 128];
-        let comment_bytes_src = comment.as_bytes();
-        if comment_bytes_src.len() >= 128 {
-            return Err(CoreWarError::assembler(
-                "Comment too long (max 127 characters)".to_string(),
-            ));
-        }
-        comment_bytes[..comment_bytes_src.len()].copy_from_slice(comment_bytes_src);
-        header.extend(&comment_bytes);
-
-        // Padding (4 bytes)
-        header.extend(&[0u8; 4]);
-
+        ChampionHeader::new(name, comment, code_size)
+            .write_to(&mut header)
+            .map_err(|error| match error {
+                // A name or comment that does not fit is a mistake in the source
+                CoreWarError::InvalidHeader { message } => CoreWarError::assembler(message),
+                error => error,
+            })?;
         Ok(header)
     }
 }
@@ -350,6 +428,8 @@ impl Default for Encoder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assembler::ProgramHeader;
+    use crate::vm::loader::COR_MAGIC;
 
     #[test]
     fn test_symbol_table_building() {
@@ -361,6 +441,7 @@ mod tests {
                 parameters: vec![ParameterNode {
                     param_type: "direct".to_string(),
                     value: "1".to_string(),
+                    column: 18,
                 }],
                 line_number: 1,
             },
@@ -370,15 +451,122 @@ mod tests {
                 parameters: vec![ParameterNode {
                     param_type: "label".to_string(),
                     value: "start".to_string(),
+                    column: 18,
                 }],
                 line_number: 2,
             },
         ];
 
-        encoder.build_symbol_table(&instructions).unwrap();
+        let mut diagnostics = Diagnostics::new();
+        let sizes = encoder.build_symbol_table(&instructions, &mut diagnostics);
 
+        assert!(diagnostics.is_empty());
+        assert_eq!(sizes, [Some(5), Some(3)]);
         assert_eq!(encoder.symbol_table.get("start"), Some(&0));
-        assert!(encoder.symbol_table.get("loop").is_some());
+        assert!(encoder.symbol_table.contains_key("loop"));
+    }
+
+    fn node(
+        label: Option<&str>,
+        mnemonic: &str,
+        target: &str,
+        line_number: usize,
+    ) -> InstructionNode {
+        InstructionNode {
+            label: label.map(str::to_string),
+            mnemonic: mnemonic.to_string(),
+            parameters: vec![ParameterNode {
+                param_type: "label".to_string(),
+                value: target.to_string(),
+                column: 6,
+            }],
+            line_number,
+        }
+    }
+
+    #[test]
+    fn test_labels_resolve_to_relative_offsets() {
+        let mut encoder = Encoder::new();
+        let instructions = vec![
+            node(Some("start"), "zjmp", "end", 1),
+            node(None, "fork", "start", 2),
+            node(Some("end"), "zjmp", "start", 3),
+        ];
+        let mut diagnostics = Diagnostics::new();
+        let sizes = encoder.build_symbol_table(&instructions, &mut diagnostics);
+        let code = encoder.generate_code(&instructions, &sizes, &mut diagnostics);
+        assert!(diagnostics.is_empty());
+
+        let offset = |at: usize| i16::from_be_bytes([code[at + 1], code[at + 2]]);
+        assert_eq!(offset(0), 6); // forward to `end`
+        assert_eq!(offset(3), -3); // back to `start`
+        assert_eq!(offset(6), -6);
+    }
+
+    #[test]
+    fn test_errors_are_located_and_collected() {
+        let mut encoder = Encoder::new();
+        let mut instructions = vec![
+            node(Some("loop"), "zjmp", "nowhere", 2),
+            node(Some("loop"), "zjmp", "loop", 7),
+            node(None, "live", "loop", 8),
+            node(Some("end"), "zjmp", "end", 9),
+        ];
+        instructions[2].parameters.clear();
+        let mut diagnostics = Diagnostics::new();
+        encoder.encode_collecting(
+            &AstNode {
+                header: ProgramHeader {
+                    name: "test".to_string(),
+                    comment: String::new(),
+                    author: None,
+                    version: None,
+                },
+                instructions,
+            },
+            &mut diagnostics,
+        );
+
+        // The instruction with no size is left out of the addresses
+        assert_eq!(encoder.symbol_table.get("end"), Some(&6));
+        let error = diagnostics.into_result(()).unwrap_err();
+        let CoreWarError::Assembly { diagnostics } = error else {
+            panic!("expected diagnostics");
+        };
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic::at_line(7, "Duplicate label 'loop' (first defined at line 2)"),
+                Diagnostic::at_line(8, "Invalid parameter count for live: expected 1, got 0"),
+                Diagnostic::at(2, 6, "Undefined label 'nowhere'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encoding_matches_reference_bytes() {
+        let source = ".name \"Reference\"\nlive %1\nld %-2, r3\nzjmp %-5\nsti r1, %:end, %1\nend: fork %-12\n";
+        let cor = crate::assembler::Assembler::new(false)
+            .assemble_string(source)
+            .unwrap();
+        let code = &cor[cor.len() - 25..];
+        assert_eq!(
+            *code,
+            [
+                0x01, 0x00, 0x00, 0x00, 0x01, // live %1
+                0x02, 0x90, 0xFF, 0xFF, 0xFF, 0xFE, 0x03, // ld %-2, r3
+                0x09, 0xFF, 0xFB, // zjmp %-5
+                0x0B, 0x68, 0x01, 0x00, 0x07, 0x00, 0x01, // sti r1, %:end, %1
+                0x0C, 0xFF, 0xF4, // fork %-12
+            ]
+        );
+
+        // Without a parameter types byte there is no way to encode a register
+        assert!(
+            crate::assembler::Assembler::new(false)
+                .assemble_string(".name \"x\"\nlive r1\n")
+                .is_err()
+        );
     }
 
     #[test]
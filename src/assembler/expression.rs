@@ -0,0 +1,191 @@
+/// Arithmetic expressions in instruction parameters
+///
+/// Parameters may be written as integer expressions over `+`, `-`, `*`, `/`
+/// and parentheses, e.g. `%:end+4`, `%(2*8)` or `:end-:start`. Label names,
+/// with or without a leading `:`, stand for the label's offset from the
+/// instruction using them, so the difference of two labels is the distance
+/// between them wherever it is written.
+use crate::error::{CoreWarError, Result};
+
+/// Evaluate a parameter expression
+///
+/// # Arguments
+/// * `text` - The expression, e.g. `:end-:start+4`
+/// * `resolve` - Gives the value of a label, or None if it is undefined
+///
+/// # Returns
+/// The value of the expression, or an error if it is malformed, uses an
+/// undefined label, divides by zero or overflows
+pub fn evaluate<F>(text: &str, resolve: F) -> Result<i32>
+where
+    F: Fn(&str) -> Option<i32>,
+{
+    let mut evaluator = Evaluator {
+        text,
+        chars: text.chars().filter(|c| !c.is_whitespace()).collect(),
+        position: 0,
+        resolve,
+    };
+    let value = evaluator.sum()?;
+    if evaluator.position < evaluator.chars.len() {
+        return Err(evaluator.error(format!(
+            "unexpected '{}'",
+            evaluator.chars[evaluator.position]
+        )));
+    }
+    Ok(value)
+}
+
+/// Recursive-descent evaluator over the characters of one expression
+struct Evaluator<'a, F> {
+    text: &'a str,
+    chars: Vec<char>,
+    position: usize,
+    resolve: F,
+}
+
+impl<F> Evaluator<'_, F>
+where
+    F: Fn(&str) -> Option<i32>,
+{
+    /// sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<i32> {
+        let mut value = self.product()?;
+        while let Some(op) = self.eat_any(&['+', '-']) {
+            let rhs = self.product()?;
+            value = if op == '+' {
+                value.checked_add(rhs)
+            } else {
+                value.checked_sub(rhs)
+            }
+            .ok_or_else(|| self.error("overflow"))?;
+        }
+        Ok(value)
+    }
+
+    /// product := unary (('*' | '/') unary)*
+    fn product(&mut self) -> Result<i32> {
+        let mut value = self.unary()?;
+        while let Some(op) = self.eat_any(&['*', '/']) {
+            let rhs = self.unary()?;
+            value = if op == '*' {
+                value
+                    .checked_mul(rhs)
+                    .ok_or_else(|| self.error("overflow"))?
+            } else if rhs == 0 {
+                return Err(self.error("division by zero"));
+            } else {
+                value
+                    .checked_div(rhs)
+                    .ok_or_else(|| self.error("overflow"))?
+            };
+        }
+        Ok(value)
+    }
+
+    /// unary := ('+' | '-') unary | atom
+    fn unary(&mut self) -> Result<i32> {
+        match self.eat_any(&['+', '-']) {
+            Some('-') => self
+                .unary()?
+                .checked_neg()
+                .ok_or_else(|| self.error("overflow")),
+            Some(_) => self.unary(),
+            None => self.atom(),
+        }
+    }
+
+    /// atom := number | ':'? label | '(' sum ')'
+    fn atom(&mut self) -> Result<i32> {
+        if self.eat_any(&['(']).is_some() {
+            let value = self.sum()?;
+            if self.eat_any(&[')']).is_none() {
+                return Err(self.error("missing ')'"));
+            }
+            return Ok(value);
+        }
+
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let digits = self.take_while(|c| c.is_ascii_digit());
+            return digits
+                .parse()
+                .map_err(|_| self.error(format!("number {} is too large", digits)));
+        }
+
+        self.eat_any(&[':']);
+        let label = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        if label.is_empty() {
+            return Err(match self.peek() {
+                Some(c) => self.error(format!("unexpected '{}'", c)),
+                None => self.error("expected a value"),
+            });
+        }
+        (self.resolve)(&label)
+            .ok_or_else(|| CoreWarError::assembler(format!("Undefined label '{}'", label)))
+    }
+
+    /// Consume the next character if it is one of `options`
+    fn eat_any(&mut self, options: &[char]) -> Option<char> {
+        let c = self.peek().filter(|c| options.contains(c))?;
+        self.position += 1;
+        Some(c)
+    }
+
+    /// Consume characters while they match
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(&accept) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    /// Get the next character without consuming it
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Build an error about this expression
+    fn error(&self, reason: impl std::fmt::Display) -> CoreWarError {
+        CoreWarError::assembler(format!("Invalid expression '{}': {}", self.text, reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(name: &str) -> Option<i32> {
+        match name {
+            "start" => Some(-8),
+            "end" => Some(12),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_arithmetic_and_labels() {
+        assert_eq!(evaluate("42", labels).unwrap(), 42);
+        assert_eq!(evaluate("(2*8)", labels).unwrap(), 16);
+        assert_eq!(evaluate("2+3*4", labels).unwrap(), 14);
+        assert_eq!(evaluate("(2+3)*4", labels).unwrap(), 20);
+        assert_eq!(evaluate("20/3-1", labels).unwrap(), 5);
+        assert_eq!(evaluate("-(4) + -2", labels).unwrap(), -6);
+        assert_eq!(evaluate(":end+4", labels).unwrap(), 16);
+        assert_eq!(evaluate("end-start", labels).unwrap(), 20);
+        assert_eq!(evaluate(":end-:start", labels).unwrap(), 20);
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let error = |text| evaluate(text, labels).unwrap_err().to_string();
+
+        assert!(error("nowhere+1").contains("Undefined label 'nowhere'"));
+        assert!(error("4/0").contains("Invalid expression '4/0': division by zero"));
+        assert!(error("(1+2").contains("missing ')'"));
+        assert!(error("2*").contains("expected a value"));
+        assert!(error("2)").contains("unexpected ')'"));
+        assert!(error("2147483647+1").contains("overflow"));
+        assert!(error("").contains("expected a value"));
+    }
+}
@@ -0,0 +1,312 @@
+/// Canonical formatting for Redcode source
+///
+/// The formatter re-emits a source file in one layout, so warriors read the
+/// same whoever wrote them:
+///
+/// ```text
+/// .name "imp"
+///
+/// start:
+///     live  %1      ; still here
+///     zjmp  %:start ; and again
+/// ```
+///
+/// Labels sit on lines of their own, instructions are indented by
+/// [`INDENT`] with their mnemonics padded to a common width, and
+/// parameters are separated by `", "`. Comments after code are lined up
+/// within each block of lines, and runs of blank lines shrink to one.
+/// Comments, constants, macros and includes are kept as written.
+///
+/// Only sources that parse are formatted, and the formatted source is
+/// parsed again: formatting that would change the program is an error
+/// rather than a silent change.
+use crate::assembler::diagnostic::Diagnostics;
+use crate::assembler::disassembler;
+use crate::assembler::include::{self, SourceMap};
+use crate::assembler::lexer::{Lexer, Token, TokenType};
+use crate::assembler::{AstNode, Parser};
+use crate::error::{CoreWarError, Result};
+use std::path::Path;
+
+/// Indentation of instructions
+pub const INDENT: &str = "    ";
+
+/// One output line: code and a trailing comment, both empty for a blank line
+#[derive(Debug, Default)]
+struct Line {
+    code: String,
+    comment: Option<String>,
+}
+
+/// Format Redcode source
+///
+/// # Arguments
+/// * `source` - The source to format
+/// * `path` - Path of the source, which `.include` paths are relative to
+///
+/// # Returns
+/// The formatted source, or an error if the source does not parse
+pub fn format_source(source: &str, path: Option<&Path>) -> Result<String> {
+    let program = parse(source, path)?;
+
+    let tokens = Lexer::new(source).tokenize()?;
+    let source_lines: Vec<&str> = source.lines().collect();
+    let statements: Vec<&[Token]> = tokens
+        .split(|t| matches!(t.token_type, TokenType::Newline | TokenType::Eof))
+        .collect();
+    let width = statements
+        .iter()
+        .filter_map(|tokens| mnemonic(tokens))
+        .map(|m| m.value.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (index, tokens) in statements.iter().enumerate() {
+        let text = source_lines.get(index).copied().unwrap_or_default();
+        format_line(tokens, text, width, &mut lines);
+    }
+    let formatted = render(&lines);
+
+    if shape(&parse(&formatted, path)?) != shape(&program) {
+        return Err(CoreWarError::assembler(
+            "Formatting would change the program; the source was left as it is",
+        ));
+    }
+    Ok(formatted)
+}
+
+/// Check whether a source is already formatted
+///
+/// # Returns
+/// `true` if formatting would leave the source unchanged
+pub fn is_formatted(source: &str, path: Option<&Path>) -> Result<bool> {
+    Ok(format_source(source, path)? == source)
+}
+
+/// Parse a source and the files it includes, as the assembler does
+fn parse(source: &str, path: Option<&Path>) -> Result<AstNode> {
+    let mut sources = SourceMap::new();
+    let mut diagnostics = Diagnostics::new();
+    let tokens = include::tokenize(source, path, &mut sources, &mut diagnostics);
+    let ast = Parser::new(tokens).parse_collecting(&mut diagnostics);
+    for diagnostic in diagnostics.iter_mut() {
+        sources.resolve(diagnostic);
+    }
+    diagnostics.into_result(ast)
+}
+
+/// Header, labels, mnemonics and parameters of a program, without positions
+type Shape = (
+    String,
+    String,
+    Vec<(Option<String>, String, Vec<(String, String)>)>,
+);
+
+/// Get the parts of a program that formatting must not change
+fn shape(ast: &AstNode) -> Shape {
+    let instructions = ast
+        .instructions
+        .iter()
+        .map(|i| {
+            let parameters = i
+                .parameters
+                .iter()
+                .map(|p| (p.param_type.clone(), p.value.clone()))
+                .collect();
+            (i.label.clone(), i.mnemonic.clone(), parameters)
+        })
+        .collect();
+    (
+        ast.header.name.clone(),
+        ast.header.comment.clone(),
+        instructions,
+    )
+}
+
+/// Get the mnemonic of an instruction or macro invocation, after its labels
+fn mnemonic(tokens: &[Token]) -> Option<&Token> {
+    let start = tokens
+        .iter()
+        .position(|t| t.token_type != TokenType::Label)?;
+    let first = &tokens[start];
+    let assigns = tokens
+        .get(start + 1)
+        .is_some_and(|t| t.token_type == TokenType::Operator && t.value == "=");
+    match first.token_type {
+        TokenType::Instruction => Some(first),
+        TokenType::LabelRef if !assigns => Some(first),
+        _ => None,
+    }
+}
+
+/// Format the tokens of one source line into output lines
+fn format_line(tokens: &[Token], text: &str, width: usize, lines: &mut Vec<Line>) {
+    let (tokens, comment) = match tokens.split_last() {
+        Some((last, rest)) if last.token_type == TokenType::Comment => {
+            // The lexer drops the comment marker; take the comment as written
+            let comment: String = text.chars().skip(last.column - 1).collect();
+            (rest, Some(comment.trim_end().to_string()))
+        }
+        _ => (tokens, None),
+    };
+
+    let labels = tokens
+        .iter()
+        .take_while(|t| t.token_type == TokenType::Label)
+        .count();
+    let (labels, statement) = tokens.split_at(labels);
+
+    if labels.is_empty() && statement.is_empty() {
+        // Whole-line comments keep whether they were indented
+        let indent = if text.starts_with(char::is_whitespace) {
+            INDENT
+        } else {
+            ""
+        };
+        lines.push(Line {
+            code: String::new(),
+            comment: comment.map(|comment| format!("{}{}", indent, comment)),
+        });
+        return;
+    }
+
+    for label in labels {
+        lines.push(Line {
+            code: label.value.clone(),
+            comment: None,
+        });
+    }
+    let Some(first) = statement.first() else {
+        // A label alone on its line keeps the line's comment
+        if let Some(last) = lines.last_mut() {
+            last.comment = comment;
+        }
+        return;
+    };
+
+    let code = if first.token_type == TokenType::Directive {
+        match statement {
+            [directive] => directive.value.clone(),
+            [directive, argument] => format!("{} {}", directive.value, text_of(argument)),
+            [directive, argument, rest @ ..] => format!(
+                "{} {} {}",
+                directive.value,
+                text_of(argument),
+                parameters(rest)
+            ),
+            [] => unreachable!("the statement has a first token"),
+        }
+    } else if mnemonic(statement).is_some() {
+        let rest = &statement[1..];
+        if rest.is_empty() {
+            format!("{}{}", INDENT, first.value)
+        } else {
+            format!(
+                "{}{:width$} {}",
+                INDENT,
+                first.value,
+                parameters(rest),
+                width = width
+            )
+        }
+    } else {
+        // A constant, `NAME = value`
+        format!("{} = {}", first.value, parameters(&statement[2..]))
+    };
+    lines.push(Line { code, comment });
+}
+
+/// Write parameters separated by `", "`, each without inner spaces
+fn parameters(tokens: &[Token]) -> String {
+    tokens
+        .split(|t| t.token_type == TokenType::Comma)
+        .map(|parameter| parameter.iter().map(text_of).collect::<String>())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Get a token as it is written in source
+fn text_of(token: &Token) -> String {
+    match token.token_type {
+        TokenType::String => format!("\"{}\"", disassembler::escape(&token.value)),
+        _ => token.value.clone(),
+    }
+}
+
+/// Lay out the output lines, aligning comments and dropping extra blank lines
+fn render(lines: &[Line]) -> String {
+    let mut output = String::new();
+    let blocks = lines.split(|line| line.code.is_empty() && line.comment.is_none());
+    for block in blocks.filter(|block| !block.is_empty()) {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        let column = block
+            .iter()
+            .filter(|line| line.comment.is_some())
+            .map(|line| line.code.len())
+            .max()
+            .unwrap_or(0);
+        for line in block {
+            match &line.comment {
+                Some(comment) if !line.code.is_empty() => {
+                    output.push_str(&format!("{:column$} {}", line.code, comment));
+                }
+                Some(comment) => output.push_str(comment),
+                None => output.push_str(&line.code),
+            }
+            output.push('\n');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formatting_is_canonical() {
+        let source = "\n\n.name   \"imp\"\n.comment \"a \\\"loop\\\"\"\n\n\n\
+                      SIZE=4 # bytes\n  ; setup\nstart: live %1 ; alive\n\
+                      loop:\nld %SIZE*2,r2\n   lfork   %:start;again\n\
+                      \x20     zjmp %:loop\n\n";
+        let formatted = format_source(source, None).unwrap();
+        assert_eq!(
+            formatted,
+            ".name \"imp\"\n.comment \"a \\\"loop\\\"\"\n\n\
+             SIZE = 4          # bytes\n    ; setup\nstart:\n    live  %1      ; alive\nloop:\n\
+             \x20   ld    %SIZE*2, r2\n    lfork %:start ;again\n    zjmp  %:loop\n"
+        );
+
+        // Formatting again changes nothing, and the program is the same
+        assert!(is_formatted(&formatted, None).unwrap());
+        assert_eq!(
+            crate::assembler::Assembler::new(false)
+                .assemble_source(&formatted)
+                .unwrap(),
+            crate::assembler::Assembler::new(false)
+                .assemble_source(source)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_macros_and_errors() {
+        let source = ".name \"m\"\n.macro  bomb offset,value\nld %value,r2\nst r2,offset\n.endm\n\
+                      .equ GAP 64\nbomb GAP ,0\n";
+        let formatted = format_source(source, None).unwrap();
+        assert_eq!(
+            formatted,
+            ".name \"m\"\n.macro bomb offset, value\n    ld   %value, r2\n    st   r2, offset\n\
+             .endm\n.equ GAP 64\n    bomb GAP, 0\n"
+        );
+        assert!(is_formatted(&formatted, None).unwrap());
+
+        // Sources that do not parse are left alone
+        let error = format_source(".name \"x\"\nlive %1 %2 $\n", None).unwrap_err();
+        assert!(error.to_string().contains("Unexpected character '$'"));
+        assert!(format_source("live %1\n", None).is_err());
+    }
+}
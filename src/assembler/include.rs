@@ -0,0 +1,432 @@
+/// Source file inclusion for Redcode assembly
+///
+/// `.include "common.s"` splices the tokens of another file into the
+/// including one, resolving the path relative to the including file. Every
+/// file is numbered after the lines already read, so a token's line is
+/// unique across the whole assembly; the [`SourceMap`] turns those lines
+/// back into a file and a line within it when errors are reported.
+///
+/// Source from someone else, such as a warrior uploaded to a server, is
+/// tokenized with [`tokenize_without_includes`] instead, so it cannot read
+/// the files of the machine assembling it.
+use crate::assembler::diagnostic::{Diagnostic, Diagnostics};
+use crate::assembler::lexer::{Lexer, Token, TokenType};
+use std::path::{Path, PathBuf};
+
+/// Lines of the main source and its included files, for mapping assembly
+/// lines back to them
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    /// Sources, in the order their lines were numbered; the main source first
+    files: Vec<SourceFile>,
+    /// First line number not yet given to a file
+    next_line: usize,
+}
+
+/// Where the lines of one source were numbered
+#[derive(Debug, Clone)]
+struct SourceFile {
+    /// Path the source was read from, if it came from a file
+    path: Option<PathBuf>,
+    /// Assembly line of the source's first line
+    first_line: usize,
+    /// Text of the source's lines
+    lines: Vec<String>,
+}
+
+impl SourceFile {
+    /// Number of assembly lines the source takes
+    fn len(&self) -> usize {
+        // One more for the end of the file, which the lexer also numbers
+        self.lines.len() + 1
+    }
+}
+
+impl SourceMap {
+    /// Create a map with no sources
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find the included file an assembly line belongs to
+    ///
+    /// # Returns
+    /// The file and the line within it, or None for a line of the main source
+    pub fn locate(&self, line: usize) -> Option<(&Path, usize)> {
+        // The main source is numbered first, from line 1
+        self.find(line)
+            .filter(|(file, _)| file.first_line > 1)
+            .and_then(|(file, line)| Some((file.path.as_deref()?, line)))
+    }
+
+    /// Point a diagnostic at the file and line it came from
+    ///
+    /// Sets the diagnostic's file, line and source text, and rewrites each
+    /// `line N` its message mentions from an included file as `line M of path`.
+    pub fn resolve(&self, diagnostic: &mut Diagnostic) {
+        if let Some((file, line)) = self.find(diagnostic.line) {
+            diagnostic.file = file.path.as_ref().map(|p| p.display().to_string());
+            diagnostic.source_line = file.lines.get(line - 1).cloned();
+            diagnostic.line = line;
+        }
+        diagnostic.message = self.map_lines(&diagnostic.message);
+    }
+
+    /// Iterate over the sources, the main one first
+    ///
+    /// # Returns
+    /// For each source: its path if it came from a file, the assembly line
+    /// of its first line, and the text of its lines
+    pub fn files(&self) -> impl Iterator<Item = (Option<&Path>, usize, &[String])> {
+        self.files
+            .iter()
+            .map(|file| (file.path.as_deref(), file.first_line, file.lines.as_slice()))
+    }
+
+    /// Find the source an assembly line belongs to, and the line within it
+    fn find(&self, line: usize) -> Option<(&SourceFile, usize)> {
+        self.files
+            .iter()
+            .find(|file| (file.first_line..file.first_line + file.len()).contains(&line))
+            .map(|file| (file, line - file.first_line + 1))
+    }
+
+    /// Rewrite every `line N` of an included file in a message
+    fn map_lines(&self, message: &str) -> String {
+        let mut mapped = String::with_capacity(message.len());
+        let mut rest = message;
+        while let Some(at) = rest.find("line ") {
+            let (before, after) = rest.split_at(at + "line ".len());
+            mapped.push_str(before);
+            let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            match after[..digits]
+                .parse()
+                .ok()
+                .and_then(|line| self.locate(line))
+            {
+                Some((path, line)) => mapped.push_str(&format!("{} of {}", line, path.display())),
+                None => mapped.push_str(&after[..digits]),
+            }
+            rest = &after[digits..];
+        }
+        mapped.push_str(rest);
+        mapped
+    }
+
+    /// Give the lines of a source their assembly line numbers
+    ///
+    /// # Returns
+    /// The assembly line of the source's first line
+    fn number(&mut self, source: &str, path: Option<&Path>) -> usize {
+        let file = SourceFile {
+            path: path.map(Path::to_path_buf),
+            first_line: self.next_line.max(1),
+            lines: source.lines().map(str::to_string).collect(),
+        };
+        let first_line = file.first_line;
+        self.next_line = first_line + file.len();
+        self.files.push(file);
+        first_line
+    }
+}
+
+/// Tokenize a source and every file it includes
+///
+/// # Arguments
+/// * `source` - The main source
+/// * `path` - Path of the main source, which its includes are relative to;
+///   without it they are relative to the current directory
+/// * `sources` - Receives the lines of the main source and the included files
+/// * `diagnostics` - Receives an error for every file that cannot be read or
+///   is included from itself, and every line that fails to tokenize
+///
+/// # Returns
+/// The tokens of the whole assembly, without the includes that failed
+pub fn tokenize(
+    source: &str,
+    path: Option<&Path>,
+    sources: &mut SourceMap,
+    diagnostics: &mut Diagnostics,
+) -> Vec<Token> {
+    tokenize_with(source, path, true, sources, diagnostics)
+}
+
+/// Tokenize a source that may not include other files
+///
+/// Every `.include` is an error and no file is read, so the tokens and
+/// the diagnostics hold nothing but the source itself.
+///
+/// # Arguments
+/// * `source` - The source
+/// * `sources` - Receives the lines of the source
+/// * `diagnostics` - Receives an error for every `.include` and every line
+///   that fails to tokenize
+///
+/// # Returns
+/// The tokens of the source, without its includes
+pub fn tokenize_without_includes(
+    source: &str,
+    sources: &mut SourceMap,
+    diagnostics: &mut Diagnostics,
+) -> Vec<Token> {
+    tokenize_with(source, None, false, sources, diagnostics)
+}
+
+/// Tokenize a source, splicing in its includes if they are allowed
+fn tokenize_with(
+    source: &str,
+    path: Option<&Path>,
+    includes: bool,
+    sources: &mut SourceMap,
+    diagnostics: &mut Diagnostics,
+) -> Vec<Token> {
+    let mut stack = Vec::new();
+    if let Some(path) = path {
+        stack.push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    }
+    let directory = path.and_then(Path::parent).unwrap_or(Path::new(""));
+
+    let first_line = sources.number(source, path);
+    let mut tokens = Vec::new();
+    let mut context = Splice {
+        includes,
+        stack: &mut stack,
+        sources,
+        diagnostics,
+        out: &mut tokens,
+    };
+    context.splice(source, first_line, directory);
+    tokens
+}
+
+/// State shared by the files of one assembly while they are tokenized
+struct Splice<'a> {
+    /// Whether `.include` may read files
+    includes: bool,
+    /// Canonical paths of the files being tokenized, outermost first
+    stack: &'a mut Vec<PathBuf>,
+    /// Receives the lines of every file read
+    sources: &'a mut SourceMap,
+    /// Receives the errors of every file
+    diagnostics: &'a mut Diagnostics,
+    /// Where the tokens go
+    out: &'a mut Vec<Token>,
+}
+
+impl Splice<'_> {
+    /// Tokenize one source into `out`, replacing its includes with their tokens
+    fn splice(&mut self, source: &str, first_line: usize, directory: &Path) {
+        let mut tokens = Lexer::new(source)
+            .with_first_line(first_line)
+            .tokenize_collecting(self.diagnostics)
+            .into_iter()
+            .peekable();
+
+        while let Some(token) = tokens.next() {
+            if token.token_type != TokenType::Directive || token.value != ".include" {
+                self.out.push(token);
+                continue;
+            }
+
+            let Some(name) = tokens.next_if(|t| t.token_type == TokenType::String) else {
+                self.diagnostics.push(Diagnostic::at(
+                    token.line,
+                    token.column,
+                    "Expected a file name after .include",
+                ));
+                continue;
+            };
+            if !self.includes {
+                self.diagnostics.push(Diagnostic::at(
+                    token.line,
+                    token.column,
+                    ".include is not allowed in this source",
+                ));
+                continue;
+            }
+            let path = directory.join(&name.value);
+            let contents = match std::fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    self.diagnostics.push(Diagnostic::at(
+                        name.line,
+                        name.column,
+                        format!("Cannot include '{}': {}", name.value, e),
+                    ));
+                    continue;
+                }
+            };
+
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if let Some(start) = self.stack.iter().position(|p| *p == canonical) {
+                let cycle: Vec<String> = self.stack[start..]
+                    .iter()
+                    .chain([&canonical])
+                    .map(|p| {
+                        p.file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                            .into_owned()
+                    })
+                    .collect();
+                self.diagnostics.push(Diagnostic::at(
+                    token.line,
+                    token.column,
+                    format!("Include cycle: {}", cycle.join(" -> ")),
+                ));
+                continue;
+            }
+
+            let included_first_line = self.sources.number(&contents, Some(&path));
+            self.stack.push(canonical);
+            let included_directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            self.splice(&contents, included_first_line, &included_directory);
+            self.stack.pop();
+
+            // The included file's end is not the end of the assembly
+            if self
+                .out
+                .last()
+                .is_some_and(|t| t.token_type == TokenType::Eof)
+            {
+                self.out.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(directory: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = directory.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_includes_are_spliced_and_mapped() {
+        let directory = tempfile::tempdir().unwrap();
+        let main = write(
+            directory.path(),
+            "main.s",
+            ".name \"m\"\n.include \"lib/common.s\"\nlive %1\n",
+        );
+        write(
+            directory.path(),
+            "lib/common.s",
+            ".include \"more.s\"\nst r1, 4\n",
+        );
+        write(directory.path(), "lib/more.s", "\nld %2, r1\n");
+
+        let mut sources = SourceMap::new();
+        let source = std::fs::read_to_string(&main).unwrap();
+        let tokens = tokenize(&source, Some(&main), &mut sources, &mut Diagnostics::new());
+        let values: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.token_type != TokenType::Newline)
+            .map(|t| t.value.as_str())
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ".name", "m", "ld", "%2", ",", "r1", "st", "r1", ",", "4", "live", "%1", ""
+            ]
+        );
+
+        let ld = tokens.iter().find(|t| t.value == "ld").unwrap();
+        let (path, line) = sources.locate(ld.line).unwrap();
+        assert!(path.ends_with("lib/more.s"));
+        assert_eq!(line, 2);
+        let live = tokens.iter().find(|t| t.value == "live").unwrap();
+        assert_eq!(live.line, 3);
+        assert!(sources.locate(live.line).is_none());
+
+        let mut diagnostic = Diagnostic::at(
+            ld.line,
+            1,
+            format!("Duplicate label 'x' (first defined at line {})", live.line),
+        );
+        sources.resolve(&mut diagnostic);
+        assert!(diagnostic.file.unwrap().ends_with("more.s"));
+        assert_eq!(diagnostic.line, 2);
+        assert_eq!(diagnostic.source_line.as_deref(), Some("ld %2, r1"));
+        assert_eq!(
+            diagnostic.message,
+            "Duplicate label 'x' (first defined at line 3)"
+        );
+
+        let mut diagnostic =
+            Diagnostic::at_line(live.line, format!("Label conflicts with line {}", ld.line));
+        sources.resolve(&mut diagnostic);
+        assert!(diagnostic.file.unwrap().ends_with("main.s"));
+        assert_eq!(diagnostic.source_line.as_deref(), Some("live %1"));
+        assert!(
+            diagnostic
+                .message
+                .starts_with("Label conflicts with line 2 of ")
+        );
+        assert!(
+            diagnostic.message.ends_with("more.s"),
+            "{}",
+            diagnostic.message
+        );
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let directory = tempfile::tempdir().unwrap();
+        let main = write(
+            directory.path(),
+            "a.s",
+            ".include \"b.s\"\n.include \"missing.s\"\n.include live\n",
+        );
+        write(directory.path(), "b.s", "live %1\n.include \"a.s\"\n");
+        let source = std::fs::read_to_string(&main).unwrap();
+
+        let mut sources = SourceMap::new();
+        let mut diagnostics = Diagnostics::new();
+        let tokens = tokenize(&source, Some(&main), &mut sources, &mut diagnostics);
+        assert!(tokens.iter().any(|t| t.value == "live"));
+
+        let mut messages = Vec::new();
+        for diagnostic in diagnostics.iter_mut() {
+            sources.resolve(diagnostic);
+            messages.push((diagnostic.line, diagnostic.message.clone()));
+        }
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[0],
+            (2, "Include cycle: a.s -> b.s -> a.s".to_string())
+        );
+        assert_eq!(messages[1].0, 2);
+        assert!(messages[1].1.starts_with("Cannot include 'missing.s': "));
+        assert_eq!(
+            messages[2],
+            (3, "Expected a file name after .include".to_string())
+        );
+    }
+
+    #[test]
+    fn test_includes_can_be_refused() {
+        let directory = tempfile::tempdir().unwrap();
+        let secret = write(directory.path(), "secret.s", "secret line
+");
+        let source = format!(".include \"{}\"\nlive %1\n", secret.display());
+
+        let mut sources = SourceMap::new();
+        let mut diagnostics = Diagnostics::new();
+        let tokens = tokenize_without_includes(&source, &mut sources, &mut diagnostics);
+        assert!(tokens.iter().any(|t| t.value == "live"));
+        assert!(!tokens.iter().any(|t| t.value == "secret"));
+        assert_eq!(sources.files().count(), 1);
+
+        let diagnostic = diagnostics.iter_mut().next().unwrap();
+        sources.resolve(diagnostic);
+        assert_eq!(diagnostic.line, 1);
+        assert_eq!(diagnostic.message, ".include is not allowed in this source");
+        assert!(diagnostic.file.is_none());
+    }
+}
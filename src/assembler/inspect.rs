@@ -0,0 +1,279 @@
+/// Static summaries of assembled champions
+///
+/// `corewar info` describes a champion from its code alone, without running
+/// it: how often each instruction appears and on which cycle its first
+/// process first reports `live`. Summaries of several champions line up in
+/// one table:
+///
+/// ```text
+/// File     Name  Size  Instructions  First live
+/// imp.cor  Imp     10             2           1
+/// ```
+use crate::assembler::disassembler::{self, Decoded};
+use crate::constants::IDX_MOD;
+use crate::vm::Champion;
+use crate::vm::instruction::{Instruction, ParameterType};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Widest bar of a histogram
+const BAR_WIDTH: usize = 40;
+
+/// What a champion's code holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChampionSummary {
+    /// Champion name
+    pub name: String,
+    /// Size of the code in bytes
+    pub code_size: usize,
+    /// How many times each instruction appears, in opcode order, without
+    /// instructions that do not appear
+    pub histogram: Vec<(Instruction, usize)>,
+    /// Bytes of code that do not decode to an instruction
+    pub data_bytes: usize,
+    /// Cycle the first process executes its first `live` on, if that can be
+    /// worked out without running the champion
+    pub first_live: Option<u32>,
+}
+
+impl ChampionSummary {
+    /// Summarize a champion's code
+    ///
+    /// The first `live` is found by following the first process from the
+    /// first instruction, adding up the cycles of what it executes. A `zjmp`
+    /// is followed only while the carry is known, as after `ld %0, rN`; the
+    /// estimate gives up there, at data, and when the process leaves the
+    /// code or comes back to an instruction without reporting `live`.
+    pub fn of(champion: &Champion) -> Self {
+        let decoded = disassembler::decode_all(&champion.code);
+        let histogram = Instruction::ALL
+            .into_iter()
+            .filter_map(|i| {
+                let count = decoded
+                    .iter()
+                    .flatten()
+                    .filter(|d| d.instruction == i)
+                    .count();
+                (count > 0).then_some((i, count))
+            })
+            .collect();
+
+        Self {
+            name: champion.name.clone(),
+            code_size: champion.code.len(),
+            histogram,
+            data_bytes: decoded.iter().filter(|d| d.is_err()).count(),
+            first_live: first_live(&decoded),
+        }
+    }
+
+    /// Get the number of instructions in the code
+    pub fn instruction_count(&self) -> usize {
+        self.histogram.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Get how many times an instruction appears
+    pub fn count(&self, instruction: Instruction) -> usize {
+        self.histogram
+            .iter()
+            .find(|(i, _)| *i == instruction)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Draw the histogram, one bar per instruction
+    pub fn histogram_text(&self) -> String {
+        let most = self.histogram.iter().map(|(_, c)| *c).max().unwrap_or(0);
+        let mut text = String::new();
+        for (instruction, count) in &self.histogram {
+            let bar = (count * BAR_WIDTH).div_ceil(most);
+            writeln!(
+                text,
+                "{:<5} {:>4}  {}",
+                instruction.name(),
+                count,
+                "#".repeat(bar)
+            )
+            .unwrap();
+        }
+        text
+    }
+}
+
+/// Lay out summaries of several champions as a table
+///
+/// # Arguments
+/// * `summaries` - Each champion's file and summary
+/// * `histogram` - Whether to add a column per instruction any champion has
+///
+/// # Returns
+/// The table, one row per champion; an unknown first `live` shows as `-`
+pub fn comparison_table(summaries: &[(String, ChampionSummary)], histogram: bool) -> String {
+    let instructions: Vec<Instruction> = Instruction::ALL
+        .into_iter()
+        .filter(|&i| histogram && summaries.iter().any(|(_, s)| s.count(i) > 0))
+        .collect();
+
+    let mut rows = vec![
+        ["File", "Name", "Size", "Instructions", "First live"]
+            .into_iter()
+            .map(String::from)
+            .chain(instructions.iter().map(|i| i.name().to_string()))
+            .collect::<Vec<_>>(),
+    ];
+    for (file, summary) in summaries {
+        let first_live = summary
+            .first_live
+            .map_or_else(|| "-".to_string(), |cycle| cycle.to_string());
+        rows.push(
+            [
+                file.clone(),
+                summary.name.clone(),
+                summary.code_size.to_string(),
+                summary.instruction_count().to_string(),
+                first_live,
+            ]
+            .into_iter()
+            .chain(instructions.iter().map(|&i| summary.count(i).to_string()))
+            .collect(),
+        );
+    }
+
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect();
+    let mut table = String::new();
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, &width))| match column {
+                // File and name read left to right, numbers line up on the right
+                0 | 1 => format!("{:<width$}", cell),
+                _ => format!("{:>width$}", cell),
+            })
+            .collect();
+        writeln!(table, "{}", cells.join("  ").trim_end()).unwrap();
+    }
+    table
+}
+
+/// Work out the cycle the first process first executes `live` on
+fn first_live(decoded: &[std::result::Result<Decoded, usize>]) -> Option<u32> {
+    let at: HashMap<usize, &Decoded> = decoded
+        .iter()
+        .flatten()
+        .map(|instruction| (instruction.address, instruction))
+        .collect();
+
+    // A process executes on the cycle after its previous instruction's wait
+    let mut cycle = 1;
+    let mut address = 0;
+    let mut carry = Some(false);
+    let mut visited = HashSet::new();
+    loop {
+        let decoded = at.get(&address)?;
+        if !visited.insert(address) {
+            return None;
+        }
+        let instruction = decoded.instruction;
+        if instruction == Instruction::Live {
+            return Some(cycle);
+        }
+        cycle += instruction.cycles();
+
+        // Whether a zjmp jumps is unknown once the carry is
+        address = if instruction == Instruction::Zjmp && carry? {
+            let offset = decoded.parameters[0].1 as i64 % IDX_MOD as i64;
+            usize::try_from(address as i64 + offset).ok()?
+        } else {
+            address + decoded.size
+        };
+        if instruction.sets_carry() {
+            carry = known_carry(decoded);
+        }
+    }
+}
+
+/// Get the carry an instruction sets, if it does not depend on the registers
+fn known_carry(decoded: &Decoded) -> Option<bool> {
+    match (decoded.instruction, decoded.parameters.as_slice()) {
+        (Instruction::Ld | Instruction::Lld, [(ParameterType::Direct, value), _]) => {
+            Some(*value == 0)
+        }
+        (
+            Instruction::Xor,
+            [
+                (ParameterType::Register, a),
+                (ParameterType::Register, b),
+                _,
+            ],
+        ) if a == b => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::Assembler;
+    use crate::vm::loader::ChampionLoader;
+
+    fn summary(source: &str) -> ChampionSummary {
+        let bytes = Assembler::new(false).assemble_source(source).unwrap();
+        let champion = ChampionLoader::new(false)
+            .load_champion_from_bytes(&bytes, 1, None)
+            .unwrap();
+        ChampionSummary::of(&champion)
+    }
+
+    #[test]
+    fn test_summary_counts_and_first_live() {
+        // ld (5) + zjmp past the add (20) before the live
+        let imp = summary(
+            ".name \"Imp\"\nld %0, r2\nzjmp %:go\nadd r1, r1, r1\ngo: live %1\nzjmp %:go\n",
+        );
+        assert_eq!(imp.code_size, 23);
+        assert_eq!(imp.instruction_count(), 5);
+        assert_eq!(imp.count(Instruction::Zjmp), 2);
+        assert_eq!(imp.count(Instruction::Sti), 0);
+        assert_eq!(imp.first_live, Some(26));
+        assert_eq!(
+            imp.histogram_text(),
+            format!(
+                "live     1  {}\nld       1  {}\nadd      1  {}\nzjmp     2  {}\n",
+                "#".repeat(20),
+                "#".repeat(20),
+                "#".repeat(20),
+                "#".repeat(40)
+            )
+        );
+
+        // Whether this zjmp jumps depends on r2
+        let unknown = summary(".name \"x\"\nld r2, r3\nzjmp %:go\ngo: live %1\n");
+        assert_eq!(unknown.first_live, None);
+        let looping = summary(".name \"x\"\nloop: fork %:loop\nzjmp %:loop\n");
+        assert_eq!(looping.first_live, None);
+    }
+
+    #[test]
+    fn test_comparison_table() {
+        let imp = summary(".name \"Imp\"\nlive %1\nzjmp %-5\n");
+        let bomber = summary(".name \"Bomber\"\nsti r1, %0, r2\n");
+        let summaries = [
+            ("imp.cor".to_string(), imp),
+            ("bomber.cor".to_string(), bomber),
+        ];
+
+        assert_eq!(
+            comparison_table(&summaries, false),
+            "File        Name    Size  Instructions  First live\n\
+             imp.cor     Imp        8             2           1\n\
+             bomber.cor  Bomber     6             1           -\n"
+        );
+        assert_eq!(
+            comparison_table(&summaries, true).lines().next(),
+            Some("File        Name    Size  Instructions  First live  live  zjmp  sti")
+        );
+    }
+}
@@ -2,6 +2,7 @@
 ///
 /// This module tokenizes Redcode source code into a stream of tokens
 /// for the parser to consume.
+use crate::assembler::diagnostic::{Diagnostic, Diagnostics};
 use crate::error::{CoreWarError, Result};
 
 /// Token types for Redcode assembly
@@ -29,6 +30,8 @@ pub enum TokenType {
     Number,
     /// Comma separator
     Comma,
+    /// Expression operator, parenthesis or constant assignment (e.g., "+", "(", "=")
+    Operator,
     /// Newline
     Newline,
     /// Comment (e.g., "# This is a comment")
@@ -60,6 +63,11 @@ impl Token {
             column,
         }
     }
+
+    /// Create an error located at this token
+    pub fn error(&self, message: impl Into<String>) -> CoreWarError {
+        Diagnostic::at(self.line, self.column, message).into()
+    }
 }
 
 /// Lexical analyzer for Redcode
@@ -92,16 +100,48 @@ impl Lexer {
         }
     }
 
+    /// Number lines from `line` instead of 1
+    ///
+    /// Included files are numbered after the lines already read, so every
+    /// token of an assembly has a distinct line.
+    pub fn with_first_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+
     /// Tokenize the source code
     ///
     /// # Returns
-    /// A vector of tokens, or an error if tokenization failed
+    /// A vector of tokens, or an error carrying every problem found
     pub fn tokenize(&mut self) -> Result<Vec<Token>> {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = self.tokenize_collecting(&mut diagnostics);
+        diagnostics.into_result(tokens)
+    }
+
+    /// Tokenize the source code, recording errors instead of stopping at them
+    ///
+    /// The rest of a line with an error is skipped, so the tokens of the
+    /// following lines can still be checked.
+    ///
+    /// # Arguments
+    /// * `diagnostics` - Receives an error for every line that failed
+    ///
+    /// # Returns
+    /// The tokens of the lines that could be read
+    pub fn tokenize_collecting(&mut self, diagnostics: &mut Diagnostics) -> Vec<Token> {
         let mut tokens = Vec::new();
 
         while !self.is_at_end() {
-            if let Some(token) = self.next_token()? {
-                tokens.push(token);
+            self.skip_whitespace();
+            let (line, column) = (self.line, self.column);
+            match self.next_token() {
+                Ok(Some(token)) => tokens.push(token),
+                Ok(None) => {}
+                Err(error) => {
+                    diagnostics.push_error(error, line, Some(column));
+                    self.read_comment();
+                }
             }
         }
 
@@ -111,7 +151,7 @@ impl Lexer {
             self.line,
             self.column,
         ));
-        Ok(tokens)
+        tokens
     }
 
     /// Get the next token from the source
@@ -146,9 +186,12 @@ impl Lexer {
                     self.advance(); // Consume the ':'
                     let label = self.read_identifier()?;
                     Some((TokenType::DirectLabel, format!("%:{}", label)))
-                } else {
+                } else if self.starts_number() {
                     let number = self.read_number()?;
                     Some((TokenType::Direct, format!("%{}", number)))
+                } else {
+                    // The value is an expression, read as the tokens that follow
+                    Some((TokenType::Direct, "%".to_string()))
                 }
             }
             ':' => {
@@ -166,6 +209,8 @@ impl Lexer {
                 let identifier = self.read_identifier()?;
                 self.classify_identifier(identifier)
             }
+            '+' | '*' | '/' | '(' | ')' | '=' => Some((TokenType::Operator, ch.to_string())),
+            '-' if !self.peek().is_ascii_digit() => Some((TokenType::Operator, "-".to_string())),
             _ if ch.is_ascii_digit() || ch == '-' => {
                 // Put the character back and read the full number
                 self.position -= 1;
@@ -175,8 +220,8 @@ impl Lexer {
             }
             _ => {
                 return Err(CoreWarError::assembler(format!(
-                    "Unexpected character '{}' at line {}, column {}",
-                    ch, start_line, start_column
+                    "Unexpected character '{}'",
+                    ch
                 )));
             }
         };
@@ -286,6 +331,12 @@ impl Lexer {
         Ok(number)
     }
 
+    /// Check whether a number starts at the current position
+    fn starts_number(&self) -> bool {
+        let next = self.source.get(self.position + 1).copied().unwrap_or('\0');
+        self.peek().is_ascii_digit() || (self.peek() == '-' && next.is_ascii_digit())
+    }
+
     /// Classify an identifier as instruction, register, or label
     fn classify_identifier(&self, identifier: String) -> Option<(TokenType, String)> {
         // Check if it's a label definition
@@ -294,10 +345,11 @@ impl Lexer {
         }
 
         // Check if it's a register
-        if identifier.starts_with('r') && identifier.len() > 1 {
-            if let Ok(_) = identifier[1..].parse::<u8>() {
-                return Some((TokenType::Register, identifier));
-            }
+        if identifier.starts_with('r')
+            && identifier.len() > 1
+            && identifier[1..].parse::<u8>().is_ok()
+        {
+            return Some((TokenType::Register, identifier));
         }
 
         // Check if it's an instruction
@@ -307,6 +359,8 @@ impl Lexer {
             | "sti" | "fork" | "lld" | "lldi" | "lfork" | "aff" => {
                 Some((TokenType::Instruction, instruction_name))
             }
+            #[cfg(feature = "pspace")]
+            "ldp" | "stp" => Some((TokenType::Instruction, instruction_name)),
             _ => {
                 // Assume it's a label reference if not recognized
                 Some((TokenType::LabelRef, identifier))
@@ -381,6 +435,53 @@ mod tests {
         assert_eq!(tokens[2].value, ":loop");
     }
 
+    #[test]
+    fn test_expression_tokenization() {
+        let mut lexer = Lexer::new("ld %(2*8), r1\nst r1, end-start");
+        let tokens = lexer.tokenize().unwrap();
+        let values: Vec<&str> = tokens.iter().map(|t| t.value.as_str()).collect();
+
+        assert_eq!(
+            values,
+            [
+                "ld", "%", "(", "2", "*", "8", ")", ",", "r1", "\n", "st", "r1", ",", "end", "-",
+                "start", ""
+            ]
+        );
+        assert_eq!(tokens[1].token_type, TokenType::Direct);
+        assert_eq!(tokens[2].token_type, TokenType::Operator);
+        assert_eq!(tokens[14].token_type, TokenType::Operator);
+    }
+
+    #[test]
+    fn test_errors_are_collected_per_line() {
+        let mut lexer = Lexer::new("live %1 $\nld ?2, r1\nst r1, 4\n.name \"open");
+        let mut diagnostics = Diagnostics::new();
+        let tokens = lexer.tokenize_collecting(&mut diagnostics);
+
+        // The rest of each bad line is skipped, the next line is still read
+        let values: Vec<&str> = tokens.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(
+            values,
+            [
+                "live", "%1", "\n", "ld", "\n", "st", "r1", ",", "4", "\n", ".name", ""
+            ]
+        );
+
+        let error = diagnostics.into_result(()).unwrap_err();
+        let CoreWarError::Assembly { diagnostics } = error else {
+            panic!("expected diagnostics");
+        };
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic::at(1, 9, "Unexpected character '$'"),
+                Diagnostic::at(2, 4, "Unexpected character '?'"),
+                Diagnostic::at(4, 7, "Unterminated string literal"),
+            ]
+        );
+    }
+
     #[test]
     fn test_directive_tokenization() {
         let mut lexer = Lexer::new(".name \"test\"");
@@ -0,0 +1,437 @@
+/// Static checks for warriors
+///
+/// Linting goes further than the assembler's [`warnings`]: it follows the
+/// control flow of the program from its first instruction through `zjmp`,
+/// `fork` and `lfork`, and reports
+///
+/// - loops without a `live`, whose processes die at the next cycle check
+/// - `st` and `sti` writing over the warrior's own code
+/// - offsets beyond IDX_MOD, which wrap to a nearer address
+/// - code that nothing reaches after a `zjmp` that always jumps
+/// - code larger than [`CHAMP_MAX_SIZE`]
+///
+/// Code that is too large is an error. Everything else is a warning, as a
+/// warrior may well mean it. Only constant operands are checked: where a
+/// register or a memory cell decides the address, the linter cannot know it.
+use crate::assembler::diagnostic::{Diagnostic, Diagnostics};
+use crate::assembler::include::{self, SourceMap};
+use crate::assembler::warnings::{self, NEVER_RUNS};
+use crate::assembler::{AstNode, Encoder, InstructionNode, Parser};
+use crate::constants::{CHAMP_MAX_SIZE, IDX_MOD};
+use crate::error::Result;
+use crate::vm::instruction::{CompleteInstruction, Instruction, ParameterType};
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+/// Bytes `st` and `sti` write
+const WRITE_SIZE: i64 = 4;
+
+/// Lint Redcode source
+///
+/// # Arguments
+/// * `source` - The source to lint
+/// * `path` - Path of the source, which `.include` paths are relative to
+///
+/// # Returns
+/// The assembler's warnings and the linter's findings, in line order, or
+/// an error if the source does not assemble
+pub fn lint_source(source: &str, path: Option<&Path>) -> Result<Vec<Diagnostic>> {
+    let mut sources = SourceMap::new();
+    let mut diagnostics = Diagnostics::new();
+    let tokens = include::tokenize(source, path, &mut sources, &mut diagnostics);
+    let ast = Parser::new(tokens).parse_collecting(&mut diagnostics);
+    let mut encoder = Encoder::new();
+    encoder.encode_collecting(&ast, &mut diagnostics);
+    for diagnostic in diagnostics.iter_mut() {
+        sources.resolve(diagnostic);
+    }
+    diagnostics.into_result(())?;
+
+    let mut found = warnings::check(&ast, encoder.instructions());
+    for finding in check(&ast, encoder.instructions()) {
+        // The assembler already reports some code that can never run
+        let reported = finding.message.starts_with(NEVER_RUNS)
+            && found
+                .iter()
+                .any(|w| w.line == finding.line && w.message.starts_with(NEVER_RUNS));
+        if !reported {
+            found.push(finding);
+        }
+    }
+    for diagnostic in &mut found {
+        sources.resolve(diagnostic);
+    }
+    found.sort_by_key(|diagnostic| diagnostic.line);
+    Ok(found)
+}
+
+/// Lint an assembled program
+///
+/// # Arguments
+/// * `ast` - The program
+/// * `encoded` - Each instruction of the program as the encoder resolved it
+///
+/// # Returns
+/// The findings, in line order
+pub fn check(ast: &AstNode, encoded: &[Option<CompleteInstruction>]) -> Vec<Diagnostic> {
+    let program = Program::new(&ast.instructions, encoded);
+    let mut findings = Vec::new();
+    program.loops_without_live(&mut findings);
+    program.self_overwrites(&mut findings);
+    program.offsets_beyond_reach(&mut findings);
+    program.dead_code(&mut findings);
+    if program.code_size > CHAMP_MAX_SIZE {
+        findings.push(Diagnostic::general(format!(
+            "Code is {} bytes, more than the {} a champion may have",
+            program.code_size, CHAMP_MAX_SIZE
+        )));
+    }
+
+    findings.sort_by_key(|finding| finding.line);
+    findings
+}
+
+/// A program with the control flow between its instructions
+struct Program<'a> {
+    instructions: &'a [InstructionNode],
+    encoded: &'a [Option<CompleteInstruction>],
+    /// Code address of each instruction
+    addresses: Vec<usize>,
+    code_size: usize,
+    /// Instructions each instruction may run next, in this or a new process
+    successors: Vec<Vec<usize>>,
+    /// Instructions a process can get to from the start
+    reachable: Vec<bool>,
+}
+
+impl<'a> Program<'a> {
+    fn new(
+        instructions: &'a [InstructionNode],
+        encoded: &'a [Option<CompleteInstruction>],
+    ) -> Self {
+        let mut addresses = Vec::with_capacity(encoded.len());
+        let mut code_size = 0;
+        for instruction in encoded {
+            addresses.push(code_size);
+            code_size += instruction.as_ref().map_or(0, |i| i.size());
+        }
+
+        let mut program = Self {
+            instructions,
+            encoded,
+            addresses,
+            code_size,
+            successors: Vec::new(),
+            reachable: Vec::new(),
+        };
+        let targets: HashSet<usize> = (0..encoded.len())
+            .filter_map(|i| program.jump_target(i))
+            .collect();
+        program.successors = (0..encoded.len())
+            .map(|i| program.next_instructions(i, &targets))
+            .collect();
+
+        // Labelled code that other instructions point at may be copied and run
+        let referenced: HashSet<&str> = instructions
+            .iter()
+            .zip(encoded)
+            .filter(|(_, e)| !e.as_ref().is_some_and(|e| is_control_flow(e.instruction)))
+            .flat_map(|(i, _)| &i.parameters)
+            .flat_map(warnings::label_references)
+            .collect();
+        let roots = instructions.iter().enumerate().filter(|(_, i)| {
+            i.label
+                .as_deref()
+                .is_some_and(|label| referenced.contains(label))
+        });
+        let mut reachable = vec![false; encoded.len()];
+        let starts = std::iter::once(0).chain(roots.map(|(index, _)| index));
+        for index in program.visit(starts.filter(|&i| i < encoded.len())) {
+            reachable[index] = true;
+        }
+        program.reachable = reachable;
+        program
+    }
+
+    /// Get the instruction starting at a code address
+    fn index_at(&self, address: i64) -> Option<usize> {
+        let address = usize::try_from(address).ok()?;
+        self.addresses
+            .binary_search(&address)
+            .ok()
+            .filter(|&index| self.encoded[index].is_some())
+    }
+
+    /// Get the instruction a `zjmp`, `fork` or `lfork` goes to
+    fn jump_target(&self, index: usize) -> Option<usize> {
+        let instruction = self.encoded[index].as_ref()?;
+        if !is_control_flow(instruction.instruction) {
+            return None;
+        }
+        let offset = reach(instruction.instruction, instruction.parameters[0].value);
+        self.index_at(self.addresses[index] as i64 + offset)
+    }
+
+    /// Get the instructions that may run after one
+    ///
+    /// A `zjmp` jumps unconditionally when the instruction before it always
+    /// sets the carry and nothing jumps to the `zjmp` itself.
+    fn next_instructions(&self, index: usize, targets: &HashSet<usize>) -> Vec<usize> {
+        let Some(instruction) = &self.encoded[index] else {
+            return Vec::new();
+        };
+        let always_jumps = instruction.instruction == Instruction::Zjmp
+            && index > 0
+            && self.instructions[index].label.is_none()
+            && !targets.contains(&index)
+            && self.encoded[index - 1]
+                .as_ref()
+                .is_some_and(warnings::always_sets_carry);
+
+        let mut next = Vec::new();
+        if !always_jumps && index + 1 < self.encoded.len() {
+            next.push(index + 1);
+        }
+        next.extend(self.jump_target(index));
+        next
+    }
+
+    /// Get the instructions reachable from some, including them
+    fn visit(&self, starts: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<usize> = starts.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            if seen.insert(index) {
+                stack.extend(&self.successors[index]);
+            }
+        }
+        seen
+    }
+
+    /// Flag loops a process can enter that never execute `live`
+    fn loops_without_live(&self, findings: &mut Vec<Diagnostic>) {
+        let after: Vec<BTreeSet<usize>> = self
+            .successors
+            .iter()
+            .map(|next| self.visit(next.iter().copied()))
+            .collect();
+
+        let mut in_loop = vec![false; self.encoded.len()];
+        for start in 0..self.encoded.len() {
+            if !self.reachable[start] || in_loop[start] || !after[start].contains(&start) {
+                continue;
+            }
+            // Everything that gets back to `start` is in its loop, which
+            // begins at `start` as no earlier instruction is in it
+            let members: Vec<usize> = after[start]
+                .iter()
+                .copied()
+                .filter(|&index| after[index].contains(&start))
+                .collect();
+            for &index in &members {
+                in_loop[index] = true;
+            }
+            let lives = members.iter().any(|&index| {
+                self.encoded[index]
+                    .as_ref()
+                    .is_some_and(|e| e.instruction == Instruction::Live)
+            });
+            if !lives {
+                findings.push(
+                    Diagnostic::at_line(
+                        self.instructions[start].line_number,
+                        "Loop never executes live: a process that stays in it dies at the next cycle check",
+                    )
+                    .into_warning(),
+                );
+            }
+        }
+    }
+
+    /// Flag `st` and `sti` whose constant target is inside the warrior's code
+    fn self_overwrites(&self, findings: &mut Vec<Diagnostic>) {
+        for (index, node) in self.instructions.iter().enumerate() {
+            let Some(instruction) = &self.encoded[index] else {
+                continue;
+            };
+            let parameters = &instruction.parameters;
+            let offset = match instruction.instruction {
+                Instruction::St if parameters[1].param_type == ParameterType::Indirect => {
+                    parameters[1].value as i64
+                }
+                Instruction::Sti
+                    if parameters[1..]
+                        .iter()
+                        .all(|p| p.param_type == ParameterType::Direct) =>
+                {
+                    parameters[1].value as i64 + parameters[2].value as i64
+                }
+                _ => continue,
+            };
+
+            let target = self.addresses[index] as i64 + offset % IDX_MOD as i64;
+            if target > -WRITE_SIZE && target < self.code_size as i64 {
+                let column = node.parameters.get(1).map_or(0, |p| p.column);
+                findings.push(
+                    Diagnostic::at(
+                        node.line_number,
+                        column,
+                        format!(
+                            "{} writes over the warrior's own code at offset {}",
+                            instruction.instruction.name(),
+                            target
+                        ),
+                    )
+                    .into_warning(),
+                );
+            }
+        }
+    }
+
+    /// Flag constant offsets that IDX_MOD wraps to a nearer address
+    fn offsets_beyond_reach(&self, findings: &mut Vec<Diagnostic>) {
+        for (node, instruction) in self.instructions.iter().zip(self.encoded) {
+            let Some(instruction) = instruction else {
+                continue;
+            };
+            let kind = instruction.instruction;
+            for (parameter_node, parameter) in node.parameters.iter().zip(&instruction.parameters) {
+                let is_offset = match parameter.param_type {
+                    ParameterType::Indirect => !kind.uses_long_addressing(),
+                    ParameterType::Direct | ParameterType::Label => {
+                        kind.uses_index_parameters() && !kind.uses_long_addressing()
+                    }
+                    ParameterType::Register => false,
+                };
+                if is_offset && parameter.value.unsigned_abs() as usize >= IDX_MOD {
+                    findings.push(
+                        Diagnostic::at(
+                            node.line_number,
+                            parameter_node.column,
+                            format!(
+                                "Offset {} is beyond IDX_MOD ({}) and reaches only {}",
+                                parameter.value,
+                                IDX_MOD,
+                                reach(kind, parameter.value)
+                            ),
+                        )
+                        .into_warning(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Flag the first instruction of each run of code no process gets to
+    fn dead_code(&self, findings: &mut Vec<Diagnostic>) {
+        for index in 1..self.encoded.len() {
+            if self.reachable[index] || !self.reachable[index - 1] {
+                continue;
+            }
+            findings.push(
+                Diagnostic::at_line(
+                    self.instructions[index].line_number,
+                    format!(
+                        "{}: the zjmp at line {} always jumps and nothing else gets here",
+                        NEVER_RUNS,
+                        self.instructions[index - 1].line_number
+                    ),
+                )
+                .into_warning(),
+            );
+        }
+    }
+}
+
+/// Check whether an instruction may send a process elsewhere than the next one
+fn is_control_flow(instruction: Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::Zjmp | Instruction::Fork | Instruction::Lfork
+    )
+}
+
+/// Get the offset an instruction really reaches with a constant offset
+fn reach(instruction: Instruction, offset: i32) -> i64 {
+    if instruction.uses_long_addressing() {
+        offset as i64
+    } else {
+        offset as i64 % IDX_MOD as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(source: &str) -> Vec<String> {
+        lint_source(source, None)
+            .unwrap()
+            .iter()
+            .map(|finding| format!("{}: {}", finding.line, finding.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_warrior_has_no_findings() {
+        let source = ".name \"w\"\nstart: live %1\nfork %:copy\nld %0, r2\nzjmp %:start\n\
+                      copy: sti r1, %:start, r2\nzjmp %:start\n";
+        assert!(lint(source).is_empty(), "{:?}", lint(source));
+    }
+
+    #[test]
+    fn test_each_finding() {
+        let source = ".name \"w\"\n\
+                      start: live %1\n\
+                      fork %:spin\n\
+                      st r1, 20\n\
+                      ld %0, r2\n\
+                      zjmp %:start\n\
+                      ld 600, r3\n\
+                      spin: add r1, r1, r1\n\
+                      zjmp %:spin\n\
+                      sti r1, %-20, %2\n\
+                      zjmp %:spin\n\
+                      bomb: live %1\n";
+
+        assert_eq!(
+            lint(source),
+            [
+                "4: st writes over the warrior's own code at offset 28",
+                "7: Instruction can never run: the loop at line 6 never ends",
+                "7: Offset 600 is beyond IDX_MOD (512) and reaches only 88",
+                "8: Loop never executes live: a process that stays in it dies at the next cycle check",
+                "10: sti writes over the warrior's own code at offset 18",
+                "12: Label 'bomb' is never used",
+            ]
+        );
+
+        // Forward jumps leave code behind too
+        let source = ".name \"w\"\nstart: live %1\nld %0, r2\nzjmp %:end\nadd r1, r1, r1\n\
+                      end: zjmp %:start\n";
+        assert_eq!(
+            lint(source),
+            [
+                "5: Instruction can never run: the zjmp at line 4 always jumps and nothing else gets here"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_oversized_code_is_an_error() {
+        let source = format!(
+            ".name \"w\"\n{}",
+            "live %1\n".repeat(CHAMP_MAX_SIZE / 5 + 1)
+        );
+        let findings = lint_source(&source, None).unwrap();
+        let error = findings
+            .iter()
+            .find(|f| f.severity == crate::assembler::diagnostic::Severity::Error)
+            .unwrap();
+        assert_eq!(
+            error.message,
+            "Code is 1025 bytes, more than the 1024 a champion may have"
+        );
+        assert!(lint_source(".name \"w\"\nlive %1 %2\n", None).is_err());
+    }
+}
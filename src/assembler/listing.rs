@@ -0,0 +1,326 @@
+/// Assembly listings
+///
+/// A listing shows every source line beside the code address and bytes it
+/// assembled to, followed by the symbol table and the total code size:
+///
+/// ```text
+/// ; warrior.s
+///  Line  Addr  Bytes                            Source
+///     1                                         .name "imp"
+///     2  0000  01 80 01 00 00 00                start: live %1
+///     3  0006  09 80 fa ff                             zjmp %:start
+///
+/// Symbols:
+///   0000  start
+///
+/// Code size: 10 bytes
+/// ```
+///
+/// Lines that expand to several instructions, such as macro invocations,
+/// get one row per instruction.
+///
+/// The listing doubles as the program's debug info: [`Listing::location_of`]
+/// finds the source line of a code address and [`Listing::address_of`] the
+/// code of a source line, which is how a debugger puts breakpoints in `.s`
+/// files.
+use crate::assembler::AstNode;
+use crate::assembler::include::SourceMap;
+use crate::vm::instruction::CompleteInstruction;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Width of the bytes column, enough for the longest instruction
+const BYTES_WIDTH: usize = 32;
+
+/// Listing of an assembled program
+#[derive(Debug, Clone)]
+pub struct Listing {
+    /// Each source, by name, with its rows
+    files: Vec<(String, Vec<Row>)>,
+    /// Labels and their code addresses, in address order
+    symbols: Vec<(String, usize)>,
+    /// Where each instruction came from, in address order
+    lines: Vec<LineEntry>,
+    /// Size of the code in bytes
+    code_size: usize,
+}
+
+/// The source line of one instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineEntry {
+    /// Code address of the instruction, from the start of the code
+    pub address: usize,
+    /// Size of the instruction in bytes
+    pub size: usize,
+    /// File the line is in, or None for a source that was not read from a file
+    pub file: Option<PathBuf>,
+    /// Line within the file, from 1
+    pub line: usize,
+}
+
+/// One row of a listing
+#[derive(Debug, Clone)]
+struct Row {
+    /// Line within the source, or None for a further instruction of the line above
+    line: Option<usize>,
+    /// Code address and bytes of the instruction on the row, if any
+    code: Option<(usize, Vec<u8>)>,
+    /// Source text
+    text: String,
+}
+
+impl Listing {
+    /// Build the listing of an assembled program
+    ///
+    /// # Arguments
+    /// * `ast` - The program
+    /// * `encoded` - Each instruction of the program as the encoder resolved it
+    /// * `code` - The assembled code, without the header
+    /// * `sources` - The lines of the program's sources
+    ///
+    /// # Returns
+    /// The listing
+    pub fn new(
+        ast: &AstNode,
+        encoded: &[Option<CompleteInstruction>],
+        code: &[u8],
+        sources: &SourceMap,
+    ) -> Self {
+        let mut by_line: HashMap<usize, Vec<(usize, Vec<u8>)>> = HashMap::new();
+        let mut symbols = Vec::new();
+        let mut address = 0;
+        for (node, instruction) in ast.instructions.iter().zip(encoded) {
+            let Some(instruction) = instruction else {
+                continue;
+            };
+            let end = (address + instruction.size()).min(code.len());
+            by_line
+                .entry(node.line_number)
+                .or_default()
+                .push((address, code[address.min(end)..end].to_vec()));
+            if let Some(label) = &node.label {
+                symbols.push((label.clone(), address));
+            }
+            address += instruction.size();
+        }
+        symbols.sort_by_key(|(_, address)| *address);
+
+        let mut entries = Vec::new();
+        let files = sources
+            .files()
+            .map(|(path, first_line, lines)| {
+                let name = path.map_or_else(|| "<source>".to_string(), |p| p.display().to_string());
+                let mut rows = Vec::new();
+                for (i, text) in lines.iter().enumerate() {
+                    let instructions = by_line.remove(&(first_line + i)).unwrap_or_default();
+                    if instructions.is_empty() {
+                        rows.push(Row {
+                            line: Some(i + 1),
+                            code: None,
+                            text: text.clone(),
+                        });
+                    }
+                    for (n, code) in instructions.into_iter().enumerate() {
+                        entries.push(LineEntry {
+                            address: code.0,
+                            size: code.1.len(),
+                            file: path.map(Path::to_path_buf),
+                            line: i + 1,
+                        });
+                        rows.push(Row {
+                            line: (n == 0).then_some(i + 1),
+                            code: Some(code),
+                            text: if n == 0 { text.clone() } else { String::new() },
+                        });
+                    }
+                }
+                (name, rows)
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.address);
+
+        Self {
+            files,
+            symbols,
+            lines: entries,
+            code_size: code.len(),
+        }
+    }
+
+    /// Get the labels and their code addresses, in address order
+    pub fn symbols(&self) -> &[(String, usize)] {
+        &self.symbols
+    }
+
+    /// Get the size of the code in bytes
+    pub fn code_size(&self) -> usize {
+        self.code_size
+    }
+
+    /// Get the source line of every instruction, in address order
+    pub fn lines(&self) -> &[LineEntry] {
+        &self.lines
+    }
+
+    /// Find the instruction a code address is in
+    ///
+    /// # Arguments
+    /// * `address` - Address from the start of the code
+    ///
+    /// # Returns
+    /// The instruction's entry, or None if the address is past the code
+    pub fn location_of(&self, address: usize) -> Option<&LineEntry> {
+        self.lines
+            .iter()
+            .find(|entry| (entry.address..entry.address + entry.size).contains(&address))
+    }
+
+    /// Find the first instruction of a source line
+    ///
+    /// A line without code, such as a comment or a label alone, moves to
+    /// the next line of the same file that has some.
+    ///
+    /// # Arguments
+    /// * `file` - The file, or None for a source that was not read from a file
+    /// * `line` - Line within the file, from 1
+    ///
+    /// # Returns
+    /// The entry of the instruction, whose line may be later than the one
+    /// asked for, or None if no code follows the line
+    pub fn address_of(&self, file: Option<&Path>, line: usize) -> Option<&LineEntry> {
+        self.lines
+            .iter()
+            .filter(|entry| entry.file.as_deref() == file && entry.line >= line)
+            .min_by_key(|entry| (entry.line, entry.address))
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, rows) in &self.files {
+            writeln!(f, "; {}", name)?;
+            writeln!(
+                f,
+                " Line  Addr  {:<width$} Source",
+                "Bytes",
+                width = BYTES_WIDTH
+            )?;
+            for row in rows {
+                let line = row.line.map(|line| line.to_string()).unwrap_or_default();
+                let (address, bytes) = match &row.code {
+                    Some((address, bytes)) => (
+                        format!("{:04x}", address),
+                        bytes
+                            .iter()
+                            .map(|b| format!("{:02x}", b))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    None => (String::new(), String::new()),
+                };
+                let row = format!(
+                    "{:>5}  {:<4}  {:<width$} {}",
+                    line,
+                    address,
+                    bytes,
+                    row.text,
+                    width = BYTES_WIDTH
+                );
+                writeln!(f, "{}", row.trim_end())?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "Symbols:")?;
+        for (label, address) in &self.symbols {
+            writeln!(f, "  {:04x}  {}", address, label)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Code size: {} bytes", self.code_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::diagnostic::Diagnostics;
+    use crate::assembler::{Encoder, Parser, include};
+
+    fn listing(source: &str) -> Listing {
+        let mut sources = SourceMap::new();
+        let mut diagnostics = Diagnostics::new();
+        let tokens = include::tokenize(source, None, &mut sources, &mut diagnostics);
+        let ast = Parser::new(tokens).parse().unwrap();
+        let mut encoder = Encoder::new();
+        let bytecode = encoder.encode(&ast).unwrap();
+        let code_size = encoder
+            .instructions()
+            .iter()
+            .flatten()
+            .map(CompleteInstruction::size)
+            .sum::<usize>();
+        Listing::new(
+            &ast,
+            encoder.instructions(),
+            &bytecode[bytecode.len() - code_size..],
+            &sources,
+        )
+    }
+
+    #[test]
+    fn test_listing_rows_and_symbols() {
+        let listing = listing(".name \"imp\"\nstart: live %1\n\n    zjmp %:start ; loop\n");
+        assert_eq!(
+            listing.to_string(),
+            format!(
+                "; <source>\n \
+                 Line  Addr  {:<32} Source\n    \
+                 1                                         .name \"imp\"\n    \
+                 2  0000  01 00 00 00 01                   start: live %1\n    \
+                 3\n    \
+                 4  0005  09 ff fb                             zjmp %:start ; loop\n\n\
+                 Symbols:\n  0000  start\n\nCode size: 8 bytes\n",
+                "Bytes"
+            )
+        );
+    }
+
+    #[test]
+    fn test_lines_map_addresses_to_source() {
+        // A label alone on its line leaves the instruction on the next one
+        let listing = listing(".name \"imp\"\nstart: live %1\n; loop\nagain:\n    zjmp %:start\n");
+        let lines: Vec<(usize, usize)> = listing
+            .lines()
+            .iter()
+            .map(|entry| (entry.address, entry.line))
+            .collect();
+        assert_eq!(lines, [(0, 2), (5, 5)]);
+        assert_eq!(listing.location_of(3).unwrap().line, 2);
+        assert_eq!(listing.location_of(7).unwrap().line, 5);
+        assert!(listing.location_of(8).is_none());
+
+        assert_eq!(listing.address_of(None, 1).unwrap().address, 0);
+        assert_eq!(listing.address_of(None, 3).unwrap().address, 5);
+        assert!(listing.address_of(None, 6).is_none());
+        assert!(listing.address_of(Some(Path::new("imp.s")), 2).is_none());
+    }
+
+    #[test]
+    fn test_expanded_lines_list_each_instruction() {
+        let listing = listing(
+            ".name \"m\"\n.macro twice\nlive %1\nlive %2\n.endm\nloop: twice\nzjmp %:loop\n",
+        );
+        let text = listing.to_string();
+        let rows: Vec<&str> = text.lines().filter(|l| l.contains("01 00 00 00")).collect();
+        assert_eq!(
+            rows,
+            [
+                "    6  0000  01 00 00 00 01                   loop: twice",
+                "       0005  01 00 00 00 02",
+            ]
+        );
+        assert_eq!(listing.symbols(), [("loop".to_string(), 0)]);
+        assert_eq!(listing.code_size(), 13);
+    }
+}
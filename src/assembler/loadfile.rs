@@ -0,0 +1,324 @@
+/// ICWS'94 load files
+///
+/// A load file is the textual form MARS implementations exchange warriors
+/// in: one instruction per line with numeric operands only, an `ORG` line
+/// giving the entry point and an `END` line closing the program. Metadata
+/// travels in `;name` and `;comment` lines:
+///
+/// ```text
+/// ;redcode-94
+/// ;name Imp
+/// ORG 0
+///     0  LIVE %1
+///     6  ZJMP %-6
+/// END
+/// ```
+///
+/// Each instruction may be numbered with its byte offset, which is checked
+/// when the file is read. Instructions use this VM's instruction set, so
+/// ICWS'94 opcodes such as `MOV` and addressing modes such as `#` are
+/// reported as unsupported rather than translated.
+use crate::assembler::Assembler;
+use crate::assembler::diagnostic::Diagnostic;
+use crate::assembler::disassembler::{self, Decoded};
+use crate::error::{CoreWarError, Result};
+use crate::vm::instruction::{Instruction, ParameterType};
+use crate::vm::loader::ChampionLoader;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// First line of a load file
+pub const LOAD_FILE_MARKER: &str = ";redcode-94";
+
+/// Name of warriors whose load file has no `;name` line
+pub const UNNAMED: &str = "Unnamed";
+
+/// Opcodes of ICWS'94 that this VM does not have
+const ICWS_OPCODES: [&str; 17] = [
+    "dat", "mov", "mul", "div", "mod", "jmp", "jmz", "jmn", "djn", "cmp", "seq", "sne", "slt",
+    "spl", "nop", "ldp", "stp",
+];
+
+/// Check whether a file's contents are a load file rather than a .cor file
+///
+/// # Returns
+/// `true` if the first non-blank line is the `;redcode` marker
+pub fn is_load_file(contents: &[u8]) -> bool {
+    contents
+        .split(|&b| b == b'\n')
+        .map(|line| line.trim_ascii())
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| line.to_ascii_lowercase().starts_with(b";redcode"))
+}
+
+/// Write a .cor file as a load file
+///
+/// # Arguments
+/// * `bytes` - Header followed by code, as written by the assembler
+///
+/// # Returns
+/// The load file, or an error if the header is invalid or some bytes of the
+/// code are not an instruction
+pub fn export(bytes: &[u8]) -> Result<String> {
+    let champion = ChampionLoader::new(false).load_champion_from_bytes(bytes, 1, None)?;
+
+    let mut text = format!("{}\n;name {}\n", LOAD_FILE_MARKER, one_line(&champion.name));
+    if !champion.comment.is_empty() {
+        writeln!(text, ";comment {}", one_line(&champion.comment)).unwrap();
+    }
+    text.push_str("ORG 0\n");
+    for decoded in disassembler::decode_all(&champion.code) {
+        let decoded = decoded.map_err(|offset| {
+            CoreWarError::champion(format!(
+                "Byte 0x{:02x} at offset {} is not an instruction, which load files cannot hold",
+                champion.code[offset], offset
+            ))
+        })?;
+        writeln!(text, "{:5}  {}", decoded.address, format(&decoded)).unwrap();
+    }
+    text.push_str("END\n");
+    Ok(text)
+}
+
+/// Read a load file into the contents of a .cor file
+///
+/// # Arguments
+/// * `text` - The load file
+///
+/// # Returns
+/// Header followed by code, or an error locating every bad line
+pub fn import(text: &str) -> Result<Vec<u8>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let source = to_source(&lines)?;
+
+    // A missing name is added on a first line of its own
+    let has_name = lines.iter().any(|line| directive(line, "name").is_some());
+    let prefix = if has_name {
+        String::new()
+    } else {
+        format!(".name \"{}\"\n", UNNAMED)
+    };
+    let offset = prefix.lines().count();
+
+    Assembler::new(false)
+        .assemble_source(&format!("{}{}", prefix, source))
+        .map_err(|error| match error {
+            CoreWarError::Assembly { diagnostics } => CoreWarError::Assembly {
+                diagnostics: diagnostics
+                    .into_iter()
+                    .map(|d| point_at_load_file(d, &lines, offset))
+                    .collect(),
+            },
+            error => error,
+        })
+}
+
+/// Turn load file lines into assembler source, one line for one line
+fn to_source(lines: &[&str]) -> Result<String> {
+    let mut source = String::new();
+    let mut address = 0;
+    let mut ended = false;
+    for (index, line) in lines.iter().enumerate() {
+        let number = index + 1;
+        let error = |column: usize, message: String| -> CoreWarError {
+            Diagnostic {
+                source_line: Some(line.to_string()),
+                ..Diagnostic::at(number, column, message)
+            }
+            .into()
+        };
+
+        let code = line.split(';').next().unwrap_or("");
+        let mut words = code.split_whitespace().peekable();
+        let converted = if let Some(name) = directive(line, "name") {
+            format!(".name \"{}\"", disassembler::escape(name))
+        } else if let Some(comment) = directive(line, "comment") {
+            format!(".comment \"{}\"", disassembler::escape(comment))
+        } else if ended || words.peek().is_none() {
+            String::new()
+        } else {
+            let column = code.len() - code.trim_start().len() + 1;
+            let first = words.next().unwrap_or_default();
+            match first.to_ascii_uppercase().as_str() {
+                "ORG" | "END" => {
+                    // This VM starts every champion at its first byte
+                    if let Some(start) = words.next().filter(|&start| start != "0") {
+                        return Err(error(
+                            column,
+                            format!(
+                                "{} {}: only programs starting at their first instruction are supported",
+                                first, start
+                            ),
+                        ));
+                    }
+                    ended = first.eq_ignore_ascii_case("END");
+                    String::new()
+                }
+                _ => {
+                    let (number_given, instruction) = match first.parse::<usize>() {
+                        Ok(given) => (Some(given), code.trim_start()[first.len()..].trim()),
+                        Err(_) => (None, code.trim()),
+                    };
+                    if let Some(given) = number_given.filter(|&given| given != address) {
+                        return Err(error(
+                            column,
+                            format!("Instruction numbered {} is at offset {}", given, address),
+                        ));
+                    }
+                    let (source_line, size) = convert_instruction(instruction)
+                        .map_err(|message| error(column, message))?;
+                    address += size;
+                    source_line
+                }
+            }
+        };
+        source.push_str(&converted);
+        source.push('\n');
+    }
+    Ok(source)
+}
+
+/// Convert one load file instruction to assembler syntax
+///
+/// # Returns
+/// The instruction and its encoded size, or why it cannot be read
+fn convert_instruction(text: &str) -> std::result::Result<(String, usize), String> {
+    let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let name = mnemonic.to_ascii_lowercase();
+    let Some(instruction) = Instruction::ALL.into_iter().find(|i| i.name() == name) else {
+        let base = name.split('.').next().unwrap_or_default();
+        return Err(if ICWS_OPCODES.contains(&base) || name.contains('.') {
+            format!("ICWS'94 opcode '{}' is not supported by this VM", mnemonic)
+        } else {
+            format!("Unknown instruction '{}'", mnemonic)
+        });
+    };
+
+    let operands: Vec<&str> = operands
+        .split(',')
+        .map(str::trim)
+        .filter(|operand| !operand.is_empty())
+        .collect();
+    let mut size = instruction.header_size();
+    for operand in &operands {
+        let (parameter_type, value) = match operand.as_bytes()[0] {
+            b'r' | b'R' => (ParameterType::Register, &operand[1..]),
+            b'%' => (ParameterType::Direct, &operand[1..]),
+            b'#' | b'$' | b'@' | b'*' | b'<' | b'>' | b'{' | b'}' => {
+                return Err(format!(
+                    "ICWS'94 addressing mode '{}' is not supported by this VM",
+                    &operand[..1]
+                ));
+            }
+            _ => (ParameterType::Indirect, *operand),
+        };
+        if value.trim().parse::<i64>().is_err() {
+            return Err(format!("Operand '{}' must be a number", operand));
+        }
+        size += instruction.parameter_size(parameter_type);
+    }
+
+    Ok((format!("{} {}", name, operands.join(", ")), size))
+}
+
+/// Get the text of a `;name` or `;comment` line
+fn directive<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let rest = line.trim_start().strip_prefix(';')?.trim_start();
+    let value = rest.strip_prefix(name)?;
+    (value.is_empty() || value.starts_with(char::is_whitespace)).then(|| value.trim())
+}
+
+/// Move a diagnostic about the generated source to the load file line it came from
+fn point_at_load_file(mut diagnostic: Diagnostic, lines: &[&str], offset: usize) -> Diagnostic {
+    if diagnostic.line > offset {
+        diagnostic.line -= offset;
+        diagnostic.source_line = lines.get(diagnostic.line - 1).map(|line| line.to_string());
+    }
+    diagnostic.file = None;
+    diagnostic
+}
+
+/// Write an instruction in load file style
+fn format(decoded: &Decoded) -> String {
+    let text = disassembler::format_instruction(decoded, &BTreeSet::new());
+    match text.split_once(' ') {
+        Some((mnemonic, operands)) => format!("{} {}", mnemonic.to_ascii_uppercase(), operands),
+        None => text.to_ascii_uppercase(),
+    }
+}
+
+/// Keep metadata on one line
+fn one_line(text: &str) -> String {
+    text.replace(['\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_file_round_trip() {
+        let source = ".name \"Imp\"\n.comment \"Just keeps going\"\n\
+                      start: live %1\nsti r1, %:start, r2\nzjmp %:start\n";
+        let bytecode = Assembler::new(false).assemble_source(source).unwrap();
+
+        let text = export(&bytecode).unwrap();
+        assert_eq!(
+            text,
+            ";redcode-94\n;name Imp\n;comment Just keeps going\nORG 0\n\
+             \x20   0  LIVE %1\n    5  STI r1, %-5, r2\n   11  ZJMP %-11\nEND\n"
+        );
+        assert!(is_load_file(text.as_bytes()));
+        assert!(!is_load_file(&bytecode));
+        assert_eq!(import(&text).unwrap(), bytecode);
+
+        // Numbers, metadata and case are optional; anything after END is ignored
+        let bare = "\n;REDCODE\nlive %1 ; keep going\nsti r1, %-5, r2\nzjmp %-11\nEND\nmov 0, 1\n";
+        let champion = ChampionLoader::new(false)
+            .load_champion_from_bytes(&import(bare).unwrap(), 1, None)
+            .unwrap();
+        assert_eq!(champion.name, UNNAMED);
+        assert_eq!(champion.code, &bytecode[bytecode.len() - 14..]);
+    }
+
+    #[test]
+    fn test_load_file_errors() {
+        let error = |text: &str| import(text).unwrap_err().to_string();
+
+        assert!(
+            error(";name x\nMOV.I $0, $1\n")
+                .contains("ICWS'94 opcode 'MOV.I' is not supported by this VM\n  --> <source>:2:1")
+        );
+        assert!(
+            error(";name x\n  live #1\n")
+                .contains("ICWS'94 addressing mode '#' is not supported by this VM")
+        );
+        assert!(
+            error(";name x\nORG 5\nlive %1\n")
+                .contains("ORG 5: only programs starting at their first instruction")
+        );
+        assert!(
+            error(";name x\n0 live %1\n3 live %1\n")
+                .contains("Instruction numbered 3 is at offset 5\n  --> <source>:3:1")
+        );
+        assert!(error(";name x\nlive %:start\n").contains("Operand '%:start' must be a number"));
+
+        // Errors found by the assembler point at the load file
+        let text = error("live %1\nst r1, r99\n");
+        assert!(text.contains("<source>:2"), "{}", text);
+        assert!(text.contains("st r1, r99"), "{}", text);
+
+        // Stray bytes after the code cannot be written
+        let mut bytes = Assembler::new(false)
+            .assemble_source(".name \"x\"\nlive %1\n")
+            .unwrap();
+        bytes.push(0xff);
+        bytes[136..140].copy_from_slice(&6u32.to_le_bytes());
+        assert!(
+            export(&bytes)
+                .unwrap_err()
+                .to_string()
+                .contains("Byte 0xff at offset 5 is not an instruction")
+        );
+    }
+}
@@ -0,0 +1,395 @@
+/// Macro expansion for Redcode assembly
+///
+/// A macro names a sequence of lines that is written once and expanded
+/// wherever the name is used as an instruction:
+///
+/// ```text
+/// .macro bomb offset, value
+///     ld %value, r2
+///     st r2, offset
+/// .endm
+///
+///     bomb 64, 0
+/// ```
+///
+/// Expansion works on the token stream before parsing. Parameters are
+/// replaced by the tokens of the matching argument, and macros may use other
+/// macros up to [`MAX_EXPANSION_DEPTH`] levels deep. Expanded tokens carry
+/// the line and column of the outermost invocation, so errors in expanded
+/// code point at the macro's use.
+use crate::assembler::diagnostic::{Diagnostic, Diagnostics};
+use crate::assembler::lexer::{Token, TokenType};
+use crate::error::Result;
+use std::collections::HashMap;
+
+/// Deepest nesting of macro invocations, which also stops recursive macros
+pub const MAX_EXPANSION_DEPTH: usize = 16;
+
+/// A macro definition
+#[derive(Debug, Clone)]
+struct Macro {
+    /// Parameter names, in order
+    parameters: Vec<String>,
+    /// Tokens of the body lines
+    body: Vec<Token>,
+    /// Line of the `.macro` directive
+    line: usize,
+}
+
+/// Remove macro definitions from a token stream and expand their invocations
+///
+/// # Arguments
+/// * `tokens` - Tokens from the lexer
+/// * `diagnostics` - Receives an error for every malformed definition and
+///   every invocation that does not match its macro
+///
+/// # Returns
+/// The tokens with every macro expanded; a failed invocation expands to nothing
+pub fn expand(tokens: Vec<Token>, diagnostics: &mut Diagnostics) -> Vec<Token> {
+    let (tokens, macros) = collect_definitions(tokens, diagnostics);
+    if macros.is_empty() {
+        return tokens;
+    }
+
+    let mut expanded = Vec::with_capacity(tokens.len());
+    expand_into(
+        &tokens,
+        &macros,
+        &mut Vec::new(),
+        None,
+        &mut expanded,
+        diagnostics,
+    );
+    expanded
+}
+
+/// Split the macro definitions out of the token stream
+fn collect_definitions(
+    tokens: Vec<Token>,
+    diagnostics: &mut Diagnostics,
+) -> (Vec<Token>, HashMap<String, Macro>) {
+    let mut macros: HashMap<String, Macro> = HashMap::new();
+    let mut rest = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        if token.token_type != TokenType::Directive {
+            rest.push(token);
+            continue;
+        }
+        match token.value.as_str() {
+            ".macro" => {}
+            ".endm" => {
+                diagnostics.push(Diagnostic::at(
+                    token.line,
+                    token.column,
+                    ".endm without .macro",
+                ));
+                continue;
+            }
+            _ => {
+                rest.push(token);
+                continue;
+            }
+        }
+
+        // Header: name and comma-separated parameters, up to the end of the line
+        let mut header = Vec::new();
+        while let Some(next) = tokens.next_if(|t| {
+            !matches!(
+                t.token_type,
+                TokenType::Newline | TokenType::Comment | TokenType::Eof
+            )
+        }) {
+            header.push(next);
+        }
+        tokens.next_if(|t| t.token_type == TokenType::Comment);
+        tokens.next_if(|t| t.token_type == TokenType::Newline);
+        let header = parse_header(&token, &header);
+
+        // Body: every line up to .endm
+        let mut body = Vec::new();
+        let mut closed = false;
+        while let Some(next) = tokens.next_if(|t| t.token_type != TokenType::Eof) {
+            if next.token_type == TokenType::Directive {
+                match next.value.as_str() {
+                    ".endm" => {
+                        closed = true;
+                        break;
+                    }
+                    ".macro" => diagnostics.push(Diagnostic::at(
+                        next.line,
+                        next.column,
+                        "Macro definition inside another macro",
+                    )),
+                    _ => body.push(next),
+                }
+            } else {
+                body.push(next);
+            }
+        }
+
+        let (name, parameters) = match header {
+            Ok(header) => header,
+            Err(error) => {
+                diagnostics.push_error(error, token.line, Some(token.column));
+                continue;
+            }
+        };
+        if !closed {
+            diagnostics.push(Diagnostic::at(
+                token.line,
+                token.column,
+                format!("Macro '{}' has no .endm", name),
+            ));
+        }
+        if let Some(existing) = macros.get(&name) {
+            diagnostics.push(Diagnostic::at(
+                token.line,
+                token.column,
+                format!(
+                    "Macro '{}' redefined (first defined at line {})",
+                    name, existing.line
+                ),
+            ));
+            continue;
+        }
+
+        macros.insert(
+            name,
+            Macro {
+                parameters,
+                body,
+                line: token.line,
+            },
+        );
+    }
+
+    (rest, macros)
+}
+
+/// Parse `name param, param...` after a `.macro` directive
+fn parse_header(directive: &Token, header: &[Token]) -> Result<(String, Vec<String>)> {
+    let invalid = |what: &str, token: &Token| {
+        token.error(format!("Invalid macro {} '{}'", what, token.value))
+    };
+
+    let Some((name, parameters)) = header.split_first() else {
+        return Err(directive.error("Missing macro name"));
+    };
+    if !is_name(name) {
+        return Err(invalid("name", name));
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for (i, token) in parameters.iter().enumerate() {
+        if i % 2 == 1 {
+            if token.token_type != TokenType::Comma {
+                return Err(invalid("parameter", token));
+            }
+        } else if !is_name(token) || names.contains(&token.value) {
+            return Err(invalid("parameter", token));
+        } else {
+            names.push(token.value.clone());
+        }
+    }
+    if parameters.len() % 2 == 0 && !parameters.is_empty() {
+        return Err(invalid("parameter", &parameters[parameters.len() - 1]));
+    }
+
+    Ok((name.value.clone(), names))
+}
+
+/// Check whether a token is a bare identifier
+fn is_name(token: &Token) -> bool {
+    token.token_type == TokenType::LabelRef && !token.value.starts_with(':')
+}
+
+/// Copy tokens to `out`, expanding macro invocations at the start of lines
+///
+/// # Arguments
+/// * `tokens` - Tokens to expand
+/// * `macros` - Known macros
+/// * `stack` - Names of the macros being expanded, outermost first
+/// * `site` - The outermost invocation, whose line and column expanded tokens take
+/// * `out` - Where expanded tokens go
+/// * `diagnostics` - Receives an error for every invocation that failed
+fn expand_into(
+    tokens: &[Token],
+    macros: &HashMap<String, Macro>,
+    stack: &mut Vec<String>,
+    site: Option<&Token>,
+    out: &mut Vec<Token>,
+    diagnostics: &mut Diagnostics,
+) {
+    let mut at_line_start = true;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let invoked = macros.get(&token.value).filter(|_| {
+            at_line_start
+                && is_name(token)
+                && tokens.get(i + 1).is_none_or(|next| {
+                    !(next.token_type == TokenType::Operator && next.value == "=")
+                })
+        });
+
+        let Some(definition) = invoked else {
+            // Labels may precede an invocation on its line
+            at_line_start = matches!(token.token_type, TokenType::Newline | TokenType::Label);
+            let mut token = token.clone();
+            if let Some(site) = site {
+                token.line = site.line;
+                token.column = site.column;
+            }
+            out.push(token);
+            i += 1;
+            continue;
+        };
+
+        // Arguments: comma-separated token runs up to the end of the line
+        let mut end = i + 1;
+        while tokens.get(end).is_some_and(|t| {
+            !matches!(
+                t.token_type,
+                TokenType::Newline | TokenType::Comment | TokenType::Eof
+            )
+        }) {
+            end += 1;
+        }
+        at_line_start = false;
+
+        let site = site.unwrap_or(token);
+        let error = |message: String| Diagnostic::at(site.line, site.column, message);
+        if stack.len() >= MAX_EXPANSION_DEPTH {
+            stack.push(token.value.clone());
+            diagnostics.push(error(format!(
+                "Macro expansion nested more than {} deep ({})",
+                MAX_EXPANSION_DEPTH,
+                stack.join(" -> ")
+            )));
+            stack.pop();
+            i = end;
+            continue;
+        }
+
+        let arguments: Vec<&[Token]> = if end == i + 1 {
+            Vec::new()
+        } else {
+            tokens[i + 1..end]
+                .split(|t| t.token_type == TokenType::Comma)
+                .collect()
+        };
+        if arguments.len() != definition.parameters.len() {
+            diagnostics.push(error(format!(
+                "Macro '{}' expects {} arguments, got {}",
+                token.value,
+                definition.parameters.len(),
+                arguments.len()
+            )));
+            i = end;
+            continue;
+        }
+        if let Some(empty) = arguments.iter().position(|a| a.is_empty()) {
+            diagnostics.push(error(format!(
+                "Missing argument {} to macro '{}'",
+                empty + 1,
+                token.value
+            )));
+            i = end;
+            continue;
+        }
+
+        let mut body = Vec::with_capacity(definition.body.len());
+        for body_token in &definition.body {
+            match definition
+                .parameters
+                .iter()
+                .position(|p| is_name(body_token) && *p == body_token.value)
+            {
+                Some(index) => body.extend(arguments[index].iter().cloned()),
+                None => body.push(body_token.clone()),
+            }
+        }
+
+        stack.push(token.value.clone());
+        expand_into(&body, macros, stack, Some(site), out, diagnostics);
+        stack.pop();
+
+        i = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::Lexer;
+
+    fn expand_source(source: &str) -> Result<Vec<Token>> {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = expand(Lexer::new(source).tokenize()?, &mut diagnostics);
+        diagnostics.into_result(tokens)
+    }
+
+    fn text(tokens: &[Token]) -> String {
+        tokens
+            .iter()
+            .filter(|t| t.token_type != TokenType::Newline)
+            .map(|t| t.value.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_expands_nested_macros_with_arguments() {
+        let source = ".macro keep n\nlive %n\n.endm\n\
+                      .macro bomb offset, value\nkeep value\nst r2, offset\n.endm\n\
+                      start: bomb 64, -1\nbomb :start, 2\n";
+        let tokens = expand_source(source).unwrap();
+
+        assert_eq!(
+            text(&tokens),
+            "start: live % -1 st r2 , 64 live % 2 st r2 , :start "
+        );
+        // Expanded tokens point at the invocation
+        let lines: Vec<(usize, usize)> = tokens.iter().map(|t| (t.line, t.column)).collect();
+        assert!(lines[3..13].iter().all(|&at| at == (8, 8)), "{:?}", lines);
+        assert!(lines[14..24].iter().all(|&at| at == (9, 1)), "{:?}", lines);
+    }
+
+    #[test]
+    fn test_macro_errors() {
+        let error = |source: &str| expand_source(source).unwrap_err().to_string();
+
+        assert!(
+            error(".macro m a\nlive %a\n.endm\nm\n")
+                .contains("Macro 'm' expects 1 arguments, got 0\n  --> <source>:4:1")
+        );
+        assert!(
+            error(".macro m\nlive %1\n").contains("Macro 'm' has no .endm\n  --> <source>:1:1")
+        );
+        assert!(error("live %1\n.endm\n").contains(".endm without .macro\n  --> <source>:2:1"));
+        assert!(
+            error(".macro m\n.endm\n.macro m\n.endm\n")
+                .contains("Macro 'm' redefined (first defined at line 1)\n  --> <source>:3:1")
+        );
+        assert!(
+            error(".macro m a, a\n.endm\n")
+                .contains("Invalid macro parameter 'a'\n  --> <source>:1:13")
+        );
+
+        let recursive = error(".macro m\nm\n.endm\nm\n");
+        assert!(recursive.contains(&format!(
+            "nested more than {} deep (m -> m -> ",
+            MAX_EXPANSION_DEPTH
+        )));
+        assert!(recursive.ends_with("<source>:4:1"), "{}", recursive);
+
+        // Every bad invocation is reported, and the good ones still expand
+        let mut diagnostics = Diagnostics::new();
+        let source = ".macro m a\nlive %a\n.endm\nm\nm 1\nm 2, 3\n";
+        let tokens = expand(Lexer::new(source).tokenize().unwrap(), &mut diagnostics);
+        assert_eq!(text(&tokens), "live % 1 ");
+        assert_eq!(diagnostics.len(), 2);
+    }
+}
@@ -3,15 +3,32 @@ pub mod encoder;
 ///
 /// This module provides functionality to assemble Redcode source files (.s)
 /// into Core War executable files (.cor).
+pub mod diagnostic;
+pub mod disassembler;
+pub mod expression;
+pub mod formatter;
+pub mod include;
+pub mod inspect;
 pub mod lexer;
+pub mod lint;
+pub mod listing;
+pub mod loadfile;
+pub mod macros;
 pub mod parser;
+pub mod warnings;
 
 // Re-export commonly used types
+pub use diagnostic::{Diagnostic, Diagnostics};
 pub use encoder::Encoder;
+pub use include::SourceMap;
 pub use lexer::Lexer;
+pub use listing::Listing;
 pub use parser::Parser;
 
 use crate::error::{CoreWarError, Result};
+use crate::vm::ChampionMetadata;
+use crate::vm::loader::HEADER_SIZE;
+use std::cell::RefCell;
 use std::path::Path;
 
 /// Main assembler interface
@@ -20,8 +37,28 @@ use std::path::Path;
 /// a simple interface for compiling Redcode source files.
 #[derive(Debug)]
 pub struct Assembler {
-    /// Whether to generate verbose output
+    /// Whether to report progress, on stderr so bytecode can go to stdout
     verbose: bool,
+    /// Whether warnings fail the assembly
+    deny_warnings: bool,
+    /// Whether `.include` may read files
+    includes: bool,
+    /// Date to stamp programs with, if they have an author or version
+    build_date: Option<String>,
+    /// Warnings about the last program assembled
+    warnings: RefCell<Vec<Diagnostic>>,
+    /// Listing of the last program assembled
+    listing: RefCell<Option<Listing>>,
+}
+
+/// What one assembly produced, besides its errors
+struct Output {
+    /// The bytecode, incomplete if there were errors
+    bytecode: Vec<u8>,
+    /// Warnings about the program, if it assembled
+    warnings: Vec<Diagnostic>,
+    /// Listing of the program, if it assembled
+    listing: Option<Listing>,
 }
 
 impl Assembler {
@@ -33,7 +70,55 @@ impl Assembler {
     /// # Returns
     /// A new Assembler instance
     pub fn new(verbose: bool) -> Self {
-        Self { verbose }
+        Self {
+            verbose,
+            deny_warnings: false,
+            includes: true,
+            build_date: None,
+            warnings: RefCell::new(Vec::new()),
+            listing: RefCell::new(None),
+        }
+    }
+
+    /// Fail assembly when the program has warnings, as well as errors
+    pub fn with_deny_warnings(mut self, deny_warnings: bool) -> Self {
+        self.deny_warnings = deny_warnings;
+        self
+    }
+
+    /// Allow or refuse `.include`
+    ///
+    /// Refuse it for source from someone else, such as a warrior uploaded to
+    /// a server: an include reads any file the assembler can, and errors
+    /// quote its lines back. Refused includes are errors and read nothing.
+    pub fn with_includes(mut self, includes: bool) -> Self {
+        self.includes = includes;
+        self
+    }
+
+    /// Stamp programs that have an `.author` or `.version` with a build date
+    ///
+    /// Programs without either get no metadata trailer, so their bytecode
+    /// stays the same whatever the date.
+    pub fn with_build_date(mut self, build_date: impl Into<String>) -> Self {
+        self.build_date = Some(build_date.into());
+        self
+    }
+
+    /// Get the warnings about the last program assembled
+    ///
+    /// # Returns
+    /// The warnings, located in their files; empty if the last assembly failed
+    pub fn warnings(&self) -> Vec<Diagnostic> {
+        self.warnings.borrow().clone()
+    }
+
+    /// Get the listing of the last program assembled
+    ///
+    /// # Returns
+    /// The listing, or None if the last assembly failed
+    pub fn listing(&self) -> Option<Listing> {
+        self.listing.borrow().clone()
     }
 
     /// Assemble a Redcode source file
@@ -50,10 +135,7 @@ impl Assembler {
         output_path: Option<P>,
     ) -> Result<Vec<u8>> {
         let input_path = input_path.as_ref();
-        let source = std::fs::read_to_string(input_path)
-            .map_err(|e| CoreWarError::assembler(format!("Failed to read input file: {}", e)))?;
-
-        let bytecode = self.assemble_source(&source)?;
+        let bytecode = self.assemble_path(input_path)?;
 
         // Determine output path
         let output_path = match output_path {
@@ -66,77 +148,164 @@ impl Assembler {
             .map_err(|e| CoreWarError::assembler(format!("Failed to write output file: {}", e)))?;
 
         if self.verbose {
-            println!(
+            eprintln!(
                 "Assembled {} -> {}",
                 input_path.display(),
                 output_path.display()
             );
-            println!("Generated {} bytes of bytecode", bytecode.len());
+            eprintln!("Generated {} bytes of bytecode", bytecode.len());
         }
 
         Ok(bytecode)
     }
 
+    /// Assemble a Redcode source file without writing the bytecode anywhere
+    ///
+    /// # Arguments
+    /// * `input_path` - Path to the input .s file, which `.include` paths are relative to
+    ///
+    /// # Returns
+    /// The bytecode, or an error if the file cannot be read or compilation failed
+    pub fn assemble_path<P: AsRef<Path>>(&self, input_path: P) -> Result<Vec<u8>> {
+        let input_path = input_path.as_ref();
+        let source = std::fs::read_to_string(input_path)
+            .map_err(|e| CoreWarError::assembler(format!("Failed to read input file: {}", e)))?;
+
+        self.assemble(&source, Some(input_path))
+    }
+
     /// Assemble Redcode source code from a string
     ///
+    /// The source is assembled in memory; `.include` paths are relative to
+    /// the current directory.
+    ///
     /// # Arguments
     /// * `source` - The Redcode source code
     ///
     /// # Returns
     /// The assembled bytecode, or an error if compilation failed
     pub fn assemble_string(&self, source: &str) -> Result<Vec<u8>> {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
-
-        let mut temp_file = NamedTempFile::new().map_err(|e| CoreWarError::assembler(format!("Failed to create temporary file: {}", e)))?;
-        temp_file.write_all(source.as_bytes()).map_err(|e| CoreWarError::assembler(format!("Failed to write to temporary file: {}", e)))?;
-        let path = temp_file.path();
-
-        let bytecode = self.assemble_file(path, None)?;
-
-        // The temporary file will be deleted when it goes out of scope
-        Ok(bytecode)
+        self.assemble(source, None)
     }
 
     /// Assemble Redcode source code from a string
     ///
+    /// The same as [`Self::assemble_string`], kept under its older name.
+    ///
     /// # Arguments
     /// * `source` - The Redcode source code
     ///
     /// # Returns
     /// The assembled bytecode, or an error if compilation failed
     pub fn assemble_source(&self, source: &str) -> Result<Vec<u8>> {
+        self.assemble_string(source)
+    }
+
+    /// Assemble Redcode source code, reporting every error it contains
+    ///
+    /// Each stage records its errors and carries on with what it could
+    /// read, so one run finds the problems of the whole program. The errors
+    /// are then located in the files they came from. A program without
+    /// errors is checked for warnings, which are kept for [`Self::warnings`]
+    /// or, when they are denied, returned as the error.
+    ///
+    /// # Arguments
+    /// * `source` - The Redcode source code
+    /// * `path` - Path of the source, which `.include` paths are relative to
+    ///
+    /// # Returns
+    /// The assembled bytecode, or an error carrying every diagnostic
+    fn assemble(&self, source: &str, path: Option<&Path>) -> Result<Vec<u8>> {
+        let mut sources = SourceMap::new();
+        let mut diagnostics = Diagnostics::new();
+        self.warnings.borrow_mut().clear();
+        self.listing.borrow_mut().take();
+        let mut output = self.assemble_collecting(source, path, &mut sources, &mut diagnostics);
+
+        for diagnostic in diagnostics.iter_mut().chain(&mut output.warnings) {
+            sources.resolve(diagnostic);
+        }
+        diagnostics.into_result(())?;
+        if self.deny_warnings && !output.warnings.is_empty() {
+            return Err(CoreWarError::Assembly {
+                diagnostics: output.warnings,
+            });
+        }
+
+        *self.warnings.borrow_mut() = output.warnings;
+        *self.listing.borrow_mut() = output.listing;
+        Ok(output.bytecode)
+    }
+
+    /// Run every assembly stage over a source and the files it includes
+    fn assemble_collecting(
+        &self,
+        source: &str,
+        path: Option<&Path>,
+        sources: &mut SourceMap,
+        diagnostics: &mut Diagnostics,
+    ) -> Output {
         if self.verbose {
-            println!("Lexical analysis...");
+            eprintln!("Lexical analysis...");
         }
 
-        // Tokenize the source code
-        let mut lexer = Lexer::new(source);
-        let tokens = lexer.tokenize()?;
+        // Tokenize the source code and the files it includes
+        let tokens = if self.includes {
+            include::tokenize(source, path, sources, diagnostics)
+        } else {
+            include::tokenize_without_includes(source, sources, diagnostics)
+        };
 
         if self.verbose {
-            println!("Found {} tokens", tokens.len());
-            println!("Parsing...");
+            eprintln!("Found {} tokens", tokens.len());
+            eprintln!("Parsing...");
         }
 
         // Parse the tokens into an AST
         let mut parser = Parser::new(tokens);
-        let ast = parser.parse()?;
+        let ast = parser.parse_collecting(diagnostics);
 
         if self.verbose {
-            println!("Parsed {} instructions", ast.instructions.len());
-            println!("Code generation...");
+            eprintln!("Parsed {} instructions", ast.instructions.len());
+            eprintln!("Code generation...");
         }
 
         // Generate bytecode from the AST
         let mut encoder = Encoder::new();
-        let bytecode = encoder.encode(&ast)?;
+        let bytecode = encoder.encode_collecting(&ast, diagnostics);
 
         if self.verbose {
-            println!("Generated {} bytes of bytecode", bytecode.len());
+            eprintln!("Generated {} bytes of bytecode", bytecode.len());
         }
 
-        Ok(bytecode)
+        let mut output = Output {
+            bytecode,
+            warnings: Vec::new(),
+            listing: None,
+        };
+
+        // Only a program that assembled is worth checking and listing
+        if diagnostics.is_empty() {
+            let encoded = encoder.instructions();
+            output.warnings = warnings::check(&ast, encoded);
+
+            let code = &output.bytecode[HEADER_SIZE..];
+            output.listing = Some(Listing::new(&ast, encoded, code, sources));
+
+            // An author or version goes after the code, with the build date
+            if ast.header.author.is_some() || ast.header.version.is_some() {
+                let metadata = ChampionMetadata {
+                    author: ast.header.author.clone(),
+                    version: ast.header.version.clone(),
+                    build_date: self.build_date.clone(),
+                };
+                if let Err(error) = metadata.write_to(&mut output.bytecode) {
+                    diagnostics.push_error(error, 0, None);
+                }
+            }
+        }
+
+        output
     }
 }
 
@@ -162,6 +331,10 @@ pub struct ProgramHeader {
     pub name: String,
     /// Program comment/description
     pub comment: String,
+    /// Author, from `.author`
+    pub author: Option<String>,
+    /// Version, from `.version`
+    pub version: Option<String>,
 }
 
 /// AST node for a single instruction
@@ -184,6 +357,8 @@ pub struct ParameterNode {
     pub param_type: String,
     /// Parameter value or identifier
     pub value: String,
+    /// Source column of the parameter, for error reporting
+    pub column: usize,
 }
 
 #[cfg(test)]
@@ -197,6 +372,8 @@ mod tests {
 
         let assembler = Assembler::new(true);
         assert!(assembler.verbose);
+        assert!(!assembler.deny_warnings);
+        assert!(Assembler::new(false).with_deny_warnings(true).deny_warnings);
     }
 
     #[test]
@@ -215,4 +392,31 @@ mod tests {
         // For now, we expect this to succeed since we have basic implementations
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_metadata_trailer() {
+        let source = ".name \"meta\"\n.author \"Alice\"\n.version \"1.2\"\nlive %1\n";
+        let bytes = Assembler::new(false)
+            .with_build_date("2026-10-15")
+            .assemble_source(source)
+            .unwrap();
+        let champion = crate::vm::loader::ChampionLoader::new(false)
+            .load_champion_from_bytes(&bytes, 1, None)
+            .unwrap();
+        assert_eq!(champion.code.len(), 5);
+        assert_eq!(
+            champion.metadata.to_string(),
+            "by Alice, version 1.2, built 2026-10-15"
+        );
+
+        // Without .author or .version the output is just the header and code
+        let plain = ".name \"meta\"\nlive %1\n";
+        assert_eq!(
+            Assembler::new(false)
+                .with_build_date("2026-10-15")
+                .assemble_source(plain)
+                .unwrap(),
+            Assembler::new(false).assemble_source(plain).unwrap()
+        );
+    }
 }
@@ -1,10 +1,13 @@
+use crate::assembler::diagnostic::{Diagnostic, Diagnostics};
 use crate::assembler::lexer::{Token, TokenType};
+use crate::assembler::macros;
 use crate::assembler::{AstNode, InstructionNode, ParameterNode, ProgramHeader};
 /// Parser for Redcode assembly language
 ///
 /// This module parses a stream of tokens into an Abstract Syntax Tree (AST)
 /// representing the structure of a Redcode program.
-use crate::error::{CoreWarError, Result};
+use crate::error::Result;
+use std::collections::HashMap;
 
 /// Parser for Redcode assembly
 #[derive(Debug)]
@@ -15,6 +18,8 @@ pub struct Parser {
     current: usize,
     /// Pending label for the next instruction
     pending_label: Option<String>,
+    /// Constants defined so far: value and line of definition, by name
+    constants: HashMap<String, (String, usize)>,
 }
 
 impl Parser {
@@ -30,27 +35,63 @@ impl Parser {
             tokens,
             current: 0,
             pending_label: None,
+            constants: HashMap::new(),
         }
     }
 
     /// Parse the tokens into an AST
     ///
     /// # Returns
-    /// The parsed AST, or an error if parsing failed
+    /// The parsed AST, or an error carrying every problem found
     pub fn parse(&mut self) -> Result<AstNode> {
-        let header = self.parse_header()?;
-        let instructions = self.parse_instructions()?;
+        let mut diagnostics = Diagnostics::new();
+        let ast = self.parse_collecting(&mut diagnostics);
+        diagnostics.into_result(ast)
+    }
+
+    /// Parse the tokens into an AST, recording errors instead of stopping at them
+    ///
+    /// A line with an error is left out of the AST and parsing carries on
+    /// with the next line, so later lines are still checked.
+    ///
+    /// # Arguments
+    /// * `diagnostics` - Receives an error for every line that failed
+    ///
+    /// # Returns
+    /// The AST of the lines that could be parsed
+    pub fn parse_collecting(&mut self, diagnostics: &mut Diagnostics) -> AstNode {
+        self.tokens = macros::expand(std::mem::take(&mut self.tokens), diagnostics);
+        self.current = 0;
+
+        let header = self.parse_header(diagnostics);
+        let instructions = self.parse_instructions(diagnostics);
+
+        for instruction in &instructions {
+            if let Some(label) = &instruction.label
+                && let Some((_, line)) = self.constants.get(label)
+            {
+                diagnostics.push(Diagnostic::at_line(
+                    instruction.line_number,
+                    format!(
+                        "Label '{}' conflicts with the constant defined at line {}",
+                        label, line
+                    ),
+                ));
+            }
+        }
 
-        Ok(AstNode {
+        AstNode {
             header,
             instructions,
-        })
+        }
     }
 
-    /// Parse the program header (.name and .comment directives)
-    fn parse_header(&mut self) -> Result<ProgramHeader> {
+    /// Parse the program header (.name, .comment, .author and .version directives)
+    fn parse_header(&mut self, diagnostics: &mut Diagnostics) -> ProgramHeader {
         let mut name = String::new();
         let mut comment = String::new();
+        let mut author = None;
+        let mut version = None;
 
         // Skip any initial newlines and comments
         self.skip_newlines_and_comments();
@@ -58,50 +99,52 @@ impl Parser {
         // Parse header directives
         while !self.is_at_end() && self.peek().token_type == TokenType::Directive {
             let directive = self.advance();
-
-            match directive.value.as_str() {
-                ".name" => {
-                    if self.peek().token_type == TokenType::String {
-                        name = self.advance().value;
-                    } else {
-                        return Err(CoreWarError::assembler(format!(
-                            "Expected string after .name directive at line {}",
-                            directive.line
-                        )));
-                    }
-                }
-                ".comment" => {
-                    if self.peek().token_type == TokenType::String {
-                        comment = self.advance().value;
-                    } else {
-                        return Err(CoreWarError::assembler(format!(
-                            "Expected string after .comment directive at line {}",
-                            directive.line
-                        )));
-                    }
-                }
-                _ => {
-                    return Err(CoreWarError::assembler(format!(
-                        "Unknown directive '{}' at line {}",
-                        directive.value, directive.line
-                    )));
-                }
+            let result = match directive.value.as_str() {
+                ".name" => self.parse_string(&directive).map(|value| name = value),
+                ".equ" => self.parse_constant(&directive),
+                ".comment" => self.parse_string(&directive).map(|value| comment = value),
+                ".author" => self
+                    .parse_string(&directive)
+                    .map(|value| author = Some(value)),
+                ".version" => self
+                    .parse_string(&directive)
+                    .map(|value| version = Some(value)),
+                _ => Err(directive.error(format!("Unknown directive '{}'", directive.value))),
+            };
+            if let Err(error) = result {
+                diagnostics.push_error(error, directive.line, Some(directive.column));
+                self.skip_line();
             }
 
             self.skip_newlines_and_comments();
         }
 
         if name.is_empty() {
-            return Err(CoreWarError::assembler(
-                ".name directive is required".to_string(),
-            ));
+            diagnostics.push(Diagnostic::general(".name directive is required"));
+        }
+
+        ProgramHeader {
+            name,
+            comment,
+            author,
+            version,
         }
+    }
 
-        Ok(ProgramHeader { name, comment })
+    /// Parse the string after a `.name`, `.comment`, `.author` or `.version` directive
+    fn parse_string(&mut self, directive: &Token) -> Result<String> {
+        if self.peek().token_type == TokenType::String {
+            Ok(self.advance().value)
+        } else {
+            Err(directive.error(format!(
+                "Expected string after {} directive",
+                directive.value
+            )))
+        }
     }
 
     /// Parse the program instructions
-    fn parse_instructions(&mut self) -> Result<Vec<InstructionNode>> {
+    fn parse_instructions(&mut self, diagnostics: &mut Diagnostics) -> Vec<InstructionNode> {
         let mut instructions = Vec::new();
 
         while !self.is_at_end() {
@@ -118,8 +161,10 @@ impl Parser {
                 continue;
             }
 
-            if let Some(instruction) = self.parse_instruction()? {
-                instructions.push(instruction);
+            let start = self.peek().clone();
+            if let Err(error) = self.parse_line(&mut instructions) {
+                diagnostics.push_error(error, start.line, Some(start.column));
+                self.skip_line();
             }
 
             if !self.is_at_end() && self.peek().token_type == TokenType::Newline {
@@ -135,13 +180,37 @@ impl Parser {
             }
         }
 
-        Ok(instructions)
+        instructions
+    }
+
+    /// Parse one statement: a constant definition or an instruction
+    fn parse_line(&mut self, instructions: &mut Vec<InstructionNode>) -> Result<()> {
+        // Constants: `.equ NAME value` or `NAME = value`
+        if self.peek().token_type == TokenType::Directive {
+            let directive = self.advance();
+            return match directive.value.as_str() {
+                ".equ" => self.parse_constant(&directive),
+                ".name" | ".comment" | ".author" | ".version" => Err(directive.error(format!(
+                    "Directive '{}' must come before the first instruction",
+                    directive.value
+                ))),
+                _ => Err(directive.error(format!("Unknown directive '{}'", directive.value))),
+            };
+        }
+        if self.starts_constant() {
+            let name = self.peek().clone();
+            return self.parse_constant(&name);
+        }
+
+        if let Some(instruction) = self.parse_instruction()? {
+            instructions.push(instruction);
+        }
+        Ok(())
     }
 
     /// Parse a single instruction
     fn parse_instruction(&mut self) -> Result<Option<InstructionNode>> {
         let mut label = self.pending_label.take(); // Take any pending label
-        let line_number = self.peek().line;
 
         // Check for optional label
         if self.peek().token_type == TokenType::Label {
@@ -154,17 +223,23 @@ impl Parser {
 
         // If there's no instruction after the label, save the label for next instruction
         if self.peek().token_type != TokenType::Instruction {
-            if label.is_some() {
-                // We have a label but no instruction follows, save the label for next instruction
-                self.pending_label = label;
-                return Ok(None);
-            } else {
-                // Not a label and not an instruction, skip this token
-                return Ok(None);
-            }
+            self.pending_label = label;
+            return match self.peek().token_type {
+                TokenType::Label | TokenType::Directive | TokenType::Comment | TokenType::Eof => {
+                    Ok(None)
+                }
+                _ if self.starts_constant() => Ok(None),
+                _ => {
+                    let token = self.peek();
+                    Err(token.error(format!("Unknown instruction '{}'", token.value)))
+                }
+            };
         }
 
-        let mnemonic = self.advance().value;
+        // The instruction's line, not its label's, when the label stands alone
+        let instruction = self.advance();
+        let line_number = instruction.line;
+        let mnemonic = instruction.value;
 
         // Parse parameters
         let mut parameters = Vec::new();
@@ -193,9 +268,17 @@ impl Parser {
     }
 
     /// Parse a single parameter
+    ///
+    /// A parameter followed by an operator is an expression: its tokens are
+    /// joined into the parameter's value, typed by the first token, and
+    /// evaluated by the encoder.
     fn parse_parameter(&mut self) -> Result<ParameterNode> {
         let token = self.advance();
 
+        if self.continues_expression(&token) {
+            return self.parse_expression(token);
+        }
+
         let (param_type, value) = match token.token_type {
             TokenType::Register => ("register".to_string(), token.value),
             TokenType::Direct => (
@@ -207,19 +290,145 @@ impl Parser {
                 token.value.trim_start_matches("%:").to_string(),
             ),
             TokenType::Indirect => ("indirect".to_string(), token.value),
+            TokenType::LabelRef if self.constants.contains_key(&token.value) => {
+                ("indirect".to_string(), self.substitute(&token))
+            }
             TokenType::LabelRef => (
                 "label".to_string(),
                 token.value.trim_start_matches(':').to_string(),
             ),
             _ => {
-                return Err(CoreWarError::assembler(format!(
-                    "Invalid parameter type '{}' at line {}",
-                    token.value, token.line
+                return Err(token.error(format!("Invalid parameter type '{}'", token.value)));
+            }
+        };
+
+        Ok(ParameterNode {
+            param_type,
+            value,
+            column: token.column,
+        })
+    }
+
+    /// Parse a constant definition, `.equ NAME value` or `NAME = value`
+    ///
+    /// Constants already defined are substituted into the value, so a
+    /// constant always stands for the value it had where it was defined.
+    ///
+    /// # Arguments
+    /// * `start` - The `.equ` directive, or the name of a `NAME = value` definition
+    fn parse_constant(&mut self, start: &Token) -> Result<()> {
+        let name = if start.token_type == TokenType::Directive {
+            self.advance()
+        } else {
+            let name = self.advance();
+            self.advance(); // Consume the '='
+            name
+        };
+        if name.token_type != TokenType::LabelRef || name.value.starts_with(':') {
+            return Err(name.error(format!("Invalid constant name '{}'", name.value)));
+        }
+        if let Some((_, line)) = self.constants.get(&name.value) {
+            return Err(start.error(format!(
+                "Constant '{}' redefined (first defined at line {})",
+                name.value, line
+            )));
+        }
+
+        let mut value = String::new();
+        while !matches!(
+            self.peek().token_type,
+            TokenType::Newline | TokenType::Comment | TokenType::Eof
+        ) {
+            let token = self.advance();
+            if !matches!(
+                token.token_type,
+                TokenType::Indirect | TokenType::LabelRef | TokenType::Operator
+            ) {
+                return Err(token.error(format!(
+                    "Invalid value '{}' for constant '{}'",
+                    token.value, name.value
+                )));
+            }
+            value.push_str(&self.substitute(&token));
+        }
+        if value.is_empty() {
+            return Err(start.error(format!("Missing value for constant '{}'", name.value)));
+        }
+
+        self.constants.insert(name.value, (value, start.line));
+        Ok(())
+    }
+
+    /// Check whether a `NAME = value` definition starts at the current token
+    fn starts_constant(&self) -> bool {
+        self.peek().token_type == TokenType::LabelRef
+            && self
+                .tokens
+                .get(self.current + 1)
+                .is_some_and(|t| t.token_type == TokenType::Operator && t.value == "=")
+    }
+
+    /// Get a token's text, with a constant's name replaced by its value
+    fn substitute(&self, token: &Token) -> String {
+        match self.constants.get(&token.value) {
+            Some((value, _)) if token.token_type == TokenType::LabelRef => format!("({})", value),
+            _ => token.value.clone(),
+        }
+    }
+
+    /// Parse the rest of an expression parameter starting with `first`
+    fn parse_expression(&mut self, first: Token) -> Result<ParameterNode> {
+        let param_type = match first.token_type {
+            TokenType::Direct | TokenType::DirectLabel => "direct",
+            TokenType::Indirect | TokenType::LabelRef | TokenType::Operator => "indirect",
+            _ => {
+                return Err(first.error(format!(
+                    "Invalid expression starting with '{}'",
+                    first.value
                 )));
             }
         };
 
-        Ok(ParameterNode { param_type, value })
+        let column = first.column;
+        let mut value = self.substitute(&first).trim_start_matches('%').to_string();
+        let mut last = first;
+        while self.continues_expression(&last) {
+            last = self.advance();
+            if last.token_type == TokenType::Register {
+                return Err(last.error(format!(
+                    "Register '{}' cannot be used in an expression",
+                    last.value
+                )));
+            }
+            value.push_str(&self.substitute(&last));
+        }
+
+        Ok(ParameterNode {
+            param_type: param_type.to_string(),
+            value,
+            column,
+        })
+    }
+
+    /// Check whether the token after `last` belongs to the same expression
+    fn continues_expression(&self, last: &Token) -> bool {
+        let next = self.peek();
+        if matches!(
+            next.token_type,
+            TokenType::Comma | TokenType::Newline | TokenType::Comment | TokenType::Eof
+        ) {
+            return false;
+        }
+        let open = (last.token_type == TokenType::Operator && last.value != ")")
+            || (last.token_type == TokenType::Direct && last.value == "%");
+        open || next.token_type == TokenType::Operator || next.value.starts_with('-')
+    }
+
+    /// Skip the rest of the current line, up to its newline
+    fn skip_line(&mut self) {
+        while !matches!(self.peek().token_type, TokenType::Newline | TokenType::Eof) {
+            self.advance();
+        }
     }
 
     /// Skip newline tokens
@@ -266,6 +475,7 @@ impl Parser {
 mod tests {
     use super::*;
     use crate::assembler::lexer::Lexer;
+    use crate::error::CoreWarError;
 
     #[test]
     fn test_header_parsing() {
@@ -278,7 +488,9 @@ mod tests {
         let tokens = lexer.tokenize().unwrap();
         let mut parser = Parser::new(tokens);
 
-        let header = parser.parse_header().unwrap();
+        let mut diagnostics = Diagnostics::new();
+        let header = parser.parse_header(&mut diagnostics);
+        assert!(diagnostics.is_empty());
         assert_eq!(header.name, "test");
         assert_eq!(header.comment, "A test program");
     }
@@ -317,6 +529,122 @@ mod tests {
         assert_eq!(ast.instructions[2].parameters[1].value, "loop");
     }
 
+    #[test]
+    fn test_expression_parameters() {
+        let source =
+            ".name \"test\"\nld %:end+4, r1\nsti r1, %(2 * 8), end - start\nadd r1+2, r2, r3\n";
+
+        let mut lexer = Lexer::new(source);
+        let mut parser = Parser::new(lexer.tokenize().unwrap());
+        let error = parser.parse().unwrap_err().to_string();
+        assert!(
+            error.contains("Invalid expression starting with 'r1'\n  --> <source>:4:5"),
+            "{}",
+            error
+        );
+
+        let source = source.rsplit_once("add").unwrap().0;
+        let ast = Parser::new(Lexer::new(source).tokenize().unwrap())
+            .parse()
+            .unwrap();
+        let parameters: Vec<(&str, &str)> = ast
+            .instructions
+            .iter()
+            .flat_map(|i| &i.parameters)
+            .map(|p| (p.param_type.as_str(), p.value.as_str()))
+            .collect();
+        assert_eq!(
+            parameters,
+            [
+                ("direct", ":end+4"),
+                ("register", "r1"),
+                ("register", "r1"),
+                ("direct", "(2*8)"),
+                ("indirect", "end-start"),
+            ]
+        );
+    }
+
+    fn parse(source: &str) -> Result<AstNode> {
+        Parser::new(Lexer::new(source).tokenize()?).parse()
+    }
+
+    #[test]
+    fn test_constants_are_substituted() {
+        let ast = parse(
+            ".equ STEP 4\n.name \"test\"\nSTRIDE = STEP*2 ; bombing stride\n\
+             ld STEP, r1\nsti r1, %STRIDE+1, :STEP\n",
+        )
+        .unwrap();
+
+        let ld = &ast.instructions[0].parameters[0];
+        assert_eq!(
+            (ld.param_type.as_str(), ld.value.as_str()),
+            ("indirect", "(4)")
+        );
+        let sti = &ast.instructions[1].parameters;
+        assert_eq!(sti[1].value, "((4)*2)+1");
+        // `:NAME` always refers to a label
+        assert_eq!(
+            (sti[2].param_type.as_str(), sti[2].value.as_str()),
+            ("label", "STEP")
+        );
+    }
+
+    #[test]
+    fn test_constant_errors() {
+        let error = |source: &str| parse(source).unwrap_err().to_string();
+
+        assert!(
+            error(".name \"t\"\n.equ X 1\n\nX = 2\n")
+                .contains("Constant 'X' redefined (first defined at line 2)\n  --> <source>:4:1")
+        );
+        assert!(
+            error(".name \"t\"\n.equ X 1\nX: live %1\n").contains(
+                "Label 'X' conflicts with the constant defined at line 2\n  --> <source>:3"
+            )
+        );
+        assert!(error(".name \"t\"\n.equ r1 1\n").contains("Invalid constant name 'r1'"));
+        assert!(error(".name \"t\"\n.equ X\n").contains("Missing value for constant 'X'"));
+        assert!(error(".name \"t\"\n.equ X %1\n").contains("Invalid value '%1'"));
+    }
+
+    #[test]
+    fn test_errors_are_collected_per_line() {
+        let source = ".name \"t\"\n.owner \"x\"\nstart: mov 0, 1\nld %1, ?\nst r1, %\nzjmp %:start\n\
+                      .comment \"late\"\n";
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Lexer::new(source).tokenize_collecting(&mut diagnostics);
+        let ast = Parser::new(tokens).parse_collecting(&mut diagnostics);
+
+        // The label of the bad line is kept for the next instruction
+        assert_eq!(ast.instructions.len(), 3);
+        assert_eq!(ast.instructions[0].label.as_deref(), Some("start"));
+        assert_eq!(ast.instructions[0].mnemonic, "ld");
+
+        let error = diagnostics.into_result(()).unwrap_err();
+        let CoreWarError::Assembly { diagnostics } = error else {
+            panic!("expected diagnostics");
+        };
+        let found: Vec<(usize, Option<usize>, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.line, d.column, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (4, Some(8), "Unexpected character '?'"),
+                (2, Some(1), "Unknown directive '.owner'"),
+                (3, Some(8), "Unknown instruction 'mov'"),
+                (
+                    7,
+                    Some(1),
+                    "Directive '.comment' must come before the first instruction"
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn test_missing_name_directive() {
         let source = "live %1";
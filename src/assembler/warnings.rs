@@ -0,0 +1,202 @@
+/// Warnings about programs that assemble
+///
+/// Warnings flag code that is valid but probably not what its author meant:
+/// labels nothing refers to, values too wide for the bytes they are encoded
+/// in, code that an infinite loop keeps from ever running, and warriors with
+/// no `live`, which die at the first cycle check. They do not stop assembly
+/// unless the assembler is asked to deny them.
+use crate::assembler::diagnostic::Diagnostic;
+use crate::assembler::{AstNode, InstructionNode, ParameterNode};
+use crate::vm::instruction::{CompleteInstruction, Instruction, ParameterType};
+use std::collections::HashSet;
+
+/// Start of the message of warnings about code that can never run
+pub(crate) const NEVER_RUNS: &str = "Instruction can never run";
+
+/// Check an assembled program for likely mistakes
+///
+/// # Arguments
+/// * `ast` - The program
+/// * `encoded` - Each instruction of the program as the encoder resolved it
+///
+/// # Returns
+/// The warnings, in line order
+pub fn check(ast: &AstNode, encoded: &[Option<CompleteInstruction>]) -> Vec<Diagnostic> {
+    let mut warnings = Vec::new();
+    unused_labels(&ast.instructions, &mut warnings);
+    truncated_values(&ast.instructions, encoded, &mut warnings);
+    unreachable_code(&ast.instructions, encoded, &mut warnings);
+    if !ast.instructions.iter().any(|i| i.mnemonic == "live") {
+        warnings.push(Diagnostic::general("Warrior never executes live").into_warning());
+    }
+
+    warnings.sort_by_key(|warning| warning.line);
+    warnings
+}
+
+/// Flag labels that no parameter refers to
+fn unused_labels(instructions: &[InstructionNode], warnings: &mut Vec<Diagnostic>) {
+    let used: HashSet<&str> = instructions
+        .iter()
+        .flat_map(|i| &i.parameters)
+        .flat_map(label_references)
+        .collect();
+
+    for instruction in instructions {
+        if let Some(label) = &instruction.label
+            && !used.contains(label.as_str())
+        {
+            warnings.push(
+                Diagnostic::at_line(
+                    instruction.line_number,
+                    format!("Label '{}' is never used", label),
+                )
+                .into_warning(),
+            );
+        }
+    }
+}
+
+/// Get the names a parameter may refer to as labels
+pub(crate) fn label_references(parameter: &ParameterNode) -> Vec<&str> {
+    match parameter.param_type.as_str() {
+        "label" => vec![parameter.value.trim().trim_end_matches(':')],
+        // Names in expressions, with or without their ':'
+        "direct" | "indirect" => parameter
+            .value
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|word| word.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_'))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Flag values that do not fit in the 2 bytes their parameter is encoded in
+fn truncated_values(
+    instructions: &[InstructionNode],
+    encoded: &[Option<CompleteInstruction>],
+    warnings: &mut Vec<Diagnostic>,
+) {
+    for (node, instruction) in instructions.iter().zip(encoded) {
+        let Some(instruction) = instruction else {
+            continue;
+        };
+        for (parameter_node, parameter) in node.parameters.iter().zip(&instruction.parameters) {
+            if instruction.instruction.parameter_size(parameter.param_type) == 2
+                && i16::try_from(parameter.value).is_err()
+            {
+                warnings.push(
+                    Diagnostic::at(
+                        node.line_number,
+                        parameter_node.column,
+                        format!(
+                            "Value {} does not fit in 2 bytes and is truncated to {}",
+                            parameter.value, parameter.value as i16
+                        ),
+                    )
+                    .into_warning(),
+                );
+            }
+        }
+    }
+}
+
+/// Flag the instruction after a loop that always jumps back
+///
+/// A `zjmp` jumps unconditionally when the instruction before it always
+/// sets the carry, as `ld %0, rN` does. If nothing else can jump to the
+/// `zjmp` and it jumps backwards or to itself, the loop never ends, and an
+/// unlabelled instruction after it can never run.
+fn unreachable_code(
+    instructions: &[InstructionNode],
+    encoded: &[Option<CompleteInstruction>],
+    warnings: &mut Vec<Diagnostic>,
+) {
+    for i in 1..instructions.len().saturating_sub(1) {
+        let (Some(before), Some(jump)) = (&encoded[i - 1], &encoded[i]) else {
+            continue;
+        };
+        let next = &instructions[i + 1];
+        if jump.instruction == Instruction::Zjmp
+            && jump.parameters[0].value <= 0
+            && instructions[i].label.is_none()
+            && always_sets_carry(before)
+            && next.label.is_none()
+        {
+            warnings.push(
+                Diagnostic::at_line(
+                    next.line_number,
+                    format!(
+                        "{}: the loop at line {} never ends",
+                        NEVER_RUNS, instructions[i].line_number
+                    ),
+                )
+                .into_warning(),
+            );
+        }
+    }
+}
+
+/// Check whether an instruction sets the carry whatever the registers hold
+pub(crate) fn always_sets_carry(instruction: &CompleteInstruction) -> bool {
+    let parameters = &instruction.parameters;
+    match instruction.instruction {
+        // Loading the value 0
+        Instruction::Ld | Instruction::Lld => {
+            parameters[0].param_type == ParameterType::Direct && parameters[0].value == 0
+        }
+        // A register xor itself
+        Instruction::Xor => {
+            parameters[0].param_type == ParameterType::Register && parameters[0] == parameters[1]
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::{Encoder, Lexer, Parser};
+
+    fn warnings(source: &str) -> Vec<String> {
+        let ast = Parser::new(Lexer::new(source).tokenize().unwrap())
+            .parse()
+            .unwrap();
+        let mut encoder = Encoder::new();
+        encoder.encode(&ast).unwrap();
+        check(&ast, encoder.instructions())
+            .iter()
+            .map(|warning| format!("{}: {}", warning.line, warning.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_program_has_no_warnings() {
+        let source = ".name \"w\"\nstart: live %1\nld %0, r2\nzjmp %:start\n";
+        assert!(warnings(source).is_empty(), "{:?}", warnings(source));
+    }
+
+    #[test]
+    fn test_each_warning() {
+        let source = ".name \"w\"\n\
+                      start: ld 40000, r1\n\
+                      sti r1, %:end-:start, %-70000\n\
+                      ld %0, r2\n\
+                      zjmp %:start\n\
+                      st r1, 8\n\
+                      end: xor r1, r1, r3\n\
+                      zjmp %-5\n\
+                      unused: add r1, r1, r1\n";
+
+        assert_eq!(
+            warnings(source),
+            [
+                "0: Warrior never executes live",
+                "2: Value 40000 does not fit in 2 bytes and is truncated to -25536",
+                "3: Value -70000 does not fit in 2 bytes and is truncated to -4464",
+                "6: Instruction can never run: the loop at line 5 never ends",
+                "9: Label 'unused' is never used",
+            ]
+        );
+    }
+}
@@ -30,6 +30,15 @@ pub enum CoreWarError {
     #[error("Assembler error: {message}")]
     Assembler { message: String },
 
+    /// Located assembler errors, collected from every stage of an assembly
+    #[error(
+        "Assembler error: {}",
+        crate::assembler::diagnostic::report(diagnostics)
+    )]
+    Assembly {
+        diagnostics: Vec<crate::assembler::diagnostic::Diagnostic>,
+    },
+
     /// File I/O errors
     #[error("File I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -63,6 +72,26 @@ pub enum CoreWarError {
     /// Game state errors
     #[error("Game state error: {message}")]
     GameState { message: String },
+
+    /// Battle assertion errors
+    #[error("Assertion error: {message}")]
+    Assertion { message: String },
+
+    /// Snapshot encoding, decoding or restore errors
+    #[error("Snapshot error: {message}")]
+    Snapshot { message: String },
+
+    /// Results bundle encoding, decoding or verification errors
+    #[error("Bundle error: {message}")]
+    Bundle { message: String },
+
+    /// Binary event stream encoding or decoding errors
+    #[error("Event protocol error: {message}")]
+    Protocol { message: String },
+
+    /// Configuration file errors
+    #[error("Configuration error: {message}")]
+    Config { message: String },
 }
 
 impl CoreWarError {
@@ -107,10 +136,45 @@ impl CoreWarError {
             message: message.into(),
         }
     }
+
+    /// Create a new assertion error
+    pub fn assertion(message: impl Into<String>) -> Self {
+        Self::Assertion {
+            message: message.into(),
+        }
+    }
+
+    /// Create a new snapshot error
+    pub fn snapshot(message: impl Into<String>) -> Self {
+        Self::Snapshot {
+            message: message.into(),
+        }
+    }
+
+    /// Create a new bundle error
+    pub fn bundle(message: impl Into<String>) -> Self {
+        Self::Bundle {
+            message: message.into(),
+        }
+    }
+
+    /// Create a new event protocol error
+    pub fn protocol(message: impl Into<String>) -> Self {
+        Self::Protocol {
+            message: message.into(),
+        }
+    }
+
+    /// Create a new configuration error
+    pub fn config(message: impl Into<String>) -> Self {
+        Self::Config {
+            message: message.into(),
+        }
+    }
 }
 
 impl From<CoreWarError> for std::io::Error {
     fn from(err: CoreWarError) -> Self {
-        std::io::Error::new(std::io::ErrorKind::Other, err.to_string())
+        std::io::Error::other(err.to_string())
     }
 }
@@ -0,0 +1,111 @@
+/// Example warriors bundled with the crate
+///
+/// A small corpus of classic-style warriors ships inside the library, so
+/// `corewar examples` can list, extract and run them without a checkout,
+/// and tests and benchmarks can battle real programs instead of hand-built
+/// bytes. The sources live in `assets/warriors` and are assembled on
+/// demand.
+use crate::assembler::Assembler;
+use crate::error::{CoreWarError, Result};
+
+/// A bundled warrior
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+    /// Short name, also the stem of its file name
+    pub name: &'static str,
+    /// One-line description of its strategy
+    pub description: &'static str,
+    /// Redcode source
+    pub source: &'static str,
+}
+
+/// Every bundled warrior
+pub const EXAMPLES: [Example; 4] = [
+    Example {
+        name: "imp",
+        description: "Reports alive from a tight loop and does nothing else",
+        source: include_str!("../assets/warriors/imp.s"),
+    },
+    Example {
+        name: "dwarf",
+        description: "Bomber: drops a bomb at a fixed stride while staying alive",
+        source: include_str!("../assets/warriors/dwarf.s"),
+    },
+    Example {
+        name: "scanner",
+        description: "Scanner: reads memory ahead and bombs only cells holding code",
+        source: include_str!("../assets/warriors/scanner.s"),
+    },
+    Example {
+        name: "replicator",
+        description: "Replicator: forks processes near and far so no one bomb kills it",
+        source: include_str!("../assets/warriors/replicator.s"),
+    },
+];
+
+impl Example {
+    /// Get the file name to write the source to
+    pub fn file_name(&self) -> String {
+        format!("{}.s", self.name)
+    }
+
+    /// Assemble the warrior
+    ///
+    /// # Returns
+    /// The contents of its .cor file
+    pub fn assemble(&self) -> Result<Vec<u8>> {
+        Assembler::new(false).assemble_string(self.source)
+    }
+}
+
+/// Find a bundled warrior by name
+///
+/// # Arguments
+/// * `name` - Name of the warrior, in any case, with or without `.s`
+///
+/// # Returns
+/// The warrior, or an error naming the available ones
+pub fn find(name: &str) -> Result<&'static Example> {
+    let name = name.strip_suffix(".s").unwrap_or(name);
+    EXAMPLES
+        .iter()
+        .find(|example| example.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = EXAMPLES.iter().map(|example| example.name).collect();
+            CoreWarError::config(format!(
+                "No example warrior named '{}'; try one of: {}",
+                name,
+                names.join(", ")
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::GameEngineBuilder;
+
+    #[test]
+    fn test_every_example_assembles_and_runs() {
+        for example in &EXAMPLES {
+            let cor = example.assemble().unwrap();
+            let mut engine = GameEngineBuilder::new()
+                .max_cycles(500)
+                .champion_cor(cor)
+                .build()
+                .unwrap();
+            for _ in 0..100 {
+                engine.tick().unwrap();
+            }
+            assert_eq!(engine.champions()[0].name.to_lowercase(), example.name);
+        }
+    }
+
+    #[test]
+    fn test_find_examples() {
+        assert_eq!(find("Dwarf").unwrap().name, "dwarf");
+        assert_eq!(find("imp.s").unwrap().file_name(), "imp.s");
+        let error = find("mice").unwrap_err().to_string();
+        assert!(error.contains("imp, dwarf, scanner, replicator"));
+    }
+}
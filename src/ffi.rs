@@ -0,0 +1,467 @@
+/// C API for embedding the VM
+///
+/// With the `ffi` feature the crate's cdylib exports `extern "C"` functions
+/// for C and C++ tools and GUIs, declared in `include/corewar.h`. A caller
+/// creates an engine, loads the contents of .cor files into it, starts the
+/// battle and ticks it, copying memory and processes out to draw the core,
+/// then destroys the engine:
+///
+/// ```c
+/// CorewarEngine *engine = corewar_engine_new(seed, 0);
+/// corewar_engine_load(engine, cor, cor_len);
+/// corewar_engine_start(engine);
+/// while (corewar_engine_tick(engine, 100) == 1) { ... }
+/// int winner = corewar_engine_winner(engine);
+/// corewar_engine_free(engine);
+/// ```
+///
+/// Functions that can fail return a negative number or null and leave a
+/// message for [`corewar_last_error`]. An engine may move between threads
+/// but must not be used from two at once.
+use crate::error::Result;
+use crate::vm::{GameEngine, GameEngineBuilder};
+use std::cell::RefCell;
+use std::ffi::{CString, c_char, c_int};
+use std::ptr;
+
+thread_local! {
+    /// Message of the last error on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// A battle owned by C code
+pub struct CorewarEngine {
+    /// Contents of the loaded .cor files, in load order
+    champions: Vec<Vec<u8>>,
+    /// Seed of the battle
+    seed: u64,
+    /// Maximum cycles of the battle, 0 for unlimited
+    max_cycles: u32,
+    /// The battle, once started
+    engine: Option<GameEngine>,
+}
+
+/// A process as C sees it
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorewarProcess {
+    /// Process ID
+    pub id: u32,
+    /// ID of the champion owning the process
+    pub champion_id: u8,
+    /// Carry flag, 0 or 1
+    pub carry: u8,
+    /// Address of the next instruction
+    pub pc: u32,
+    /// Cycles left before the next instruction
+    pub wait_cycles: u32,
+    /// Registers r1 to r16
+    pub registers: [i32; 16],
+}
+
+/// Remember an error for [`corewar_last_error`]
+fn set_error(message: impl Into<Vec<u8>>) {
+    let mut message = message.into();
+    message.retain(|&b| b != 0);
+    let message = CString::new(message).ok();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+/// Turn a result into a status, remembering the error
+fn status<T>(result: Result<T>, value: impl FnOnce(T) -> c_int) -> c_int {
+    match result {
+        Ok(result) => value(result),
+        Err(e) => {
+            set_error(e.to_string());
+            -1
+        }
+    }
+}
+
+/// Borrow the engine behind a pointer, remembering an error if it is null
+///
+/// # Safety
+/// `engine` must be null or come from [`corewar_engine_new`] and not be freed.
+unsafe fn engine_ref<'a>(engine: *const CorewarEngine) -> Option<&'a CorewarEngine> {
+    // SAFETY: the caller passes a live engine or null
+    let engine = unsafe { engine.as_ref() };
+    if engine.is_none() {
+        set_error("Null engine");
+    }
+    engine
+}
+
+/// Borrow the engine behind a pointer mutably, remembering an error if it is null
+///
+/// # Safety
+/// `engine` must be null or come from [`corewar_engine_new`], not be freed
+/// and not be in use elsewhere.
+unsafe fn engine_mut<'a>(engine: *mut CorewarEngine) -> Option<&'a mut CorewarEngine> {
+    // SAFETY: the caller passes a live, unshared engine or null
+    let engine = unsafe { engine.as_mut() };
+    if engine.is_none() {
+        set_error("Null engine");
+    }
+    engine
+}
+
+/// Get the battle of a started engine
+fn battle(engine: &CorewarEngine) -> Option<&GameEngine> {
+    engine.engine.as_ref()
+}
+
+/// Get the message of the last error on the calling thread
+///
+/// # Returns
+/// A NUL-terminated string owned by the library, valid until the next
+/// failing call on this thread, or null if nothing failed yet
+#[unsafe(no_mangle)]
+pub extern "C" fn corewar_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// Create an engine with no champions
+///
+/// # Arguments
+/// * `seed` - Seed of the battle
+/// * `max_cycles` - Maximum cycles of the battle, 0 for unlimited
+///
+/// # Returns
+/// The engine, to be destroyed with [`corewar_engine_free`]
+#[unsafe(no_mangle)]
+pub extern "C" fn corewar_engine_new(seed: u64, max_cycles: u32) -> *mut CorewarEngine {
+    Box::into_raw(Box::new(CorewarEngine {
+        champions: Vec::new(),
+        seed,
+        max_cycles,
+        engine: None,
+    }))
+}
+
+/// Destroy an engine
+///
+/// # Safety
+/// `engine` must be null or come from [`corewar_engine_new`], and must not
+/// be used again.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_free(engine: *mut CorewarEngine) {
+    if !engine.is_null() {
+        // SAFETY: the engine was boxed by corewar_engine_new and is freed once
+        drop(unsafe { Box::from_raw(engine) });
+    }
+}
+
+/// Add a champion for the next battle
+///
+/// The champion is checked when the battle starts; a battle already under
+/// way is not affected.
+///
+/// # Arguments
+/// * `engine` - The engine
+/// * `cor` - Contents of a .cor file: header, then code
+/// * `len` - Bytes at `cor`
+///
+/// # Returns
+/// The champion's ID, or -1 on error
+///
+/// # Safety
+/// `engine` must be a live engine and `cor` must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_load(
+    engine: *mut CorewarEngine,
+    cor: *const u8,
+    len: usize,
+) -> c_int {
+    // SAFETY: the caller passes a live engine or null
+    let Some(engine) = (unsafe { engine_mut(engine) }) else {
+        return -1;
+    };
+    if cor.is_null() {
+        set_error("Null champion");
+        return -1;
+    }
+    // SAFETY: the caller guarantees `len` readable bytes at `cor`
+    let bytes = unsafe { std::slice::from_raw_parts(cor, len) };
+    engine.champions.push(bytes.to_vec());
+    engine.champions.len() as c_int
+}
+
+/// Load the champions and start the battle, from its first cycle
+///
+/// Starting again restarts the battle with every champion loaded so far.
+///
+/// # Returns
+/// 0, or -1 if the champions are invalid
+///
+/// # Safety
+/// `engine` must be a live engine.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_start(engine: *mut CorewarEngine) -> c_int {
+    // SAFETY: the caller passes a live engine or null
+    let Some(engine) = (unsafe { engine_mut(engine) }) else {
+        return -1;
+    };
+    let builder = engine.champions.iter().fold(
+        GameEngineBuilder::new()
+            .seed(engine.seed)
+            .max_cycles(engine.max_cycles),
+        |builder, cor| builder.champion_cor(cor.as_slice()),
+    );
+    status(builder.build(), |battle| {
+        engine.engine = Some(battle);
+        0
+    })
+}
+
+/// Run cycles of the battle
+///
+/// # Arguments
+/// * `engine` - A started engine
+/// * `cycles` - Cycles to run; fewer run if the battle ends first
+///
+/// # Returns
+/// 1 while the battle runs, 0 once it is over, -1 on error
+///
+/// # Safety
+/// `engine` must be a live engine.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_tick(engine: *mut CorewarEngine, cycles: u32) -> c_int {
+    // SAFETY: the caller passes a live engine or null
+    let Some(engine) = (unsafe { engine_mut(engine) }) else {
+        return -1;
+    };
+    let Some(battle) = engine.engine.as_mut() else {
+        set_error("The battle has not been started");
+        return -1;
+    };
+    if !battle.state().running {
+        return 0;
+    }
+    for _ in 0..cycles {
+        match battle.tick() {
+            Ok(outcome) if !outcome.is_running() => return 0,
+            Ok(_) => {}
+            Err(e) => return status::<()>(Err(e), |_| 0),
+        }
+    }
+    1
+}
+
+/// Get the current cycle, 0 before the battle starts
+///
+/// # Safety
+/// `engine` must be a live engine.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_cycle(engine: *const CorewarEngine) -> u32 {
+    // SAFETY: the caller passes a live engine or null
+    unsafe { engine_ref(engine) }
+        .and_then(battle)
+        .map_or(0, |battle| battle.state().cycle)
+}
+
+/// Get the winner of a finished battle
+///
+/// # Returns
+/// The winner's champion ID, 0 while the battle runs or if nobody won
+///
+/// # Safety
+/// `engine` must be a live engine.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_winner(engine: *const CorewarEngine) -> c_int {
+    // SAFETY: the caller passes a live engine or null
+    unsafe { engine_ref(engine) }
+        .and_then(battle)
+        .and_then(|battle| battle.state().winner)
+        .map_or(0, c_int::from)
+}
+
+/// Get the number of bytes in memory, 0 before the battle starts
+///
+/// # Safety
+/// `engine` must be a live engine.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_memory_size(engine: *const CorewarEngine) -> usize {
+    // SAFETY: the caller passes a live engine or null
+    unsafe { engine_ref(engine) }
+        .and_then(battle)
+        .map_or(0, |battle| battle.memory().size())
+}
+
+/// Copy memory, and the champion owning each byte, into buffers
+///
+/// # Arguments
+/// * `engine` - A started engine
+/// * `memory` - Buffer for the bytes of memory, or null
+/// * `owners` - Buffer for the ID of each byte's owner, 0 for none, or null
+/// * `len` - Bytes each buffer holds
+///
+/// # Returns
+/// Bytes copied into each buffer: the smaller of `len` and the memory size
+///
+/// # Safety
+/// `engine` must be a live engine, and each non-null buffer must have room
+/// for `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_read_memory(
+    engine: *const CorewarEngine,
+    memory: *mut u8,
+    owners: *mut u8,
+    len: usize,
+) -> usize {
+    // SAFETY: the caller passes a live engine or null
+    let Some(battle) = (unsafe { engine_ref(engine) }).and_then(battle) else {
+        return 0;
+    };
+    let core = battle.memory();
+    let len = len.min(core.size());
+    if !memory.is_null() {
+        // SAFETY: the caller guarantees room for `len` bytes
+        unsafe { ptr::copy_nonoverlapping(core.data().as_ptr(), memory, len) };
+    }
+    if !owners.is_null() {
+        // SAFETY: the caller guarantees room for `len` bytes
+        let owners = unsafe { std::slice::from_raw_parts_mut(owners, len) };
+        for (owner, id) in owners.iter_mut().zip(core.ownership()) {
+            *owner = id.unwrap_or(0);
+        }
+    }
+    len
+}
+
+/// Get the number of live processes, 0 before the battle starts
+///
+/// # Safety
+/// `engine` must be a live engine.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_process_count(engine: *const CorewarEngine) -> usize {
+    // SAFETY: the caller passes a live engine or null
+    unsafe { engine_ref(engine) }
+        .and_then(battle)
+        .map_or(0, |battle| battle.processes().len())
+}
+
+/// Copy the live processes into an array
+///
+/// # Arguments
+/// * `engine` - A started engine
+/// * `processes` - Array for the processes
+/// * `capacity` - Processes the array holds
+///
+/// # Returns
+/// Processes copied: the smaller of `capacity` and the live processes
+///
+/// # Safety
+/// `engine` must be a live engine and `processes` must have room for
+/// `capacity` processes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn corewar_engine_read_processes(
+    engine: *const CorewarEngine,
+    processes: *mut CorewarProcess,
+    capacity: usize,
+) -> usize {
+    // SAFETY: the caller passes a live engine or null
+    let Some(battle) = (unsafe { engine_ref(engine) }).and_then(battle) else {
+        return 0;
+    };
+    if processes.is_null() {
+        return 0;
+    }
+    let live = battle.processes();
+    let count = capacity.min(live.len());
+    for (i, process) in live.iter().take(count).enumerate() {
+        let process = CorewarProcess {
+            id: process.id,
+            champion_id: process.champion_id,
+            carry: u8::from(process.carry),
+            pc: process.pc as u32,
+            wait_cycles: process.wait_cycles,
+            registers: process.registers,
+        };
+        // SAFETY: `i` is below `capacity`, which the caller has room for
+        unsafe { processes.add(i).write(process) };
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::Assembler;
+    use crate::vm::loader::HEADER_SIZE;
+    use std::ffi::CStr;
+
+    fn liver() -> Vec<u8> {
+        Assembler::new(false)
+            .assemble_string(".name \"Liver\"\n.comment \"\"\nlive %1\n")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_battle_through_the_c_api() {
+        let cor = liver();
+        unsafe {
+            let engine = corewar_engine_new(1, 30);
+            assert_eq!(corewar_engine_tick(engine, 1), -1);
+            assert_eq!(corewar_engine_load(engine, cor.as_ptr(), cor.len()), 1);
+            assert_eq!(corewar_engine_load(engine, cor.as_ptr(), cor.len()), 2);
+            assert_eq!(corewar_engine_start(engine), 0);
+
+            let size = corewar_engine_memory_size(engine);
+            let mut memory = vec![0u8; size];
+            let mut owners = vec![0u8; size];
+            let copied =
+                corewar_engine_read_memory(engine, memory.as_mut_ptr(), owners.as_mut_ptr(), size);
+            assert_eq!(copied, size);
+            assert_eq!(memory[0], cor[HEADER_SIZE]);
+            assert_eq!(owners[0], 1);
+
+            assert_eq!(corewar_engine_process_count(engine), 2);
+            let mut processes = [CorewarProcess {
+                id: 0,
+                champion_id: 0,
+                carry: 0,
+                pc: 0,
+                wait_cycles: 0,
+                registers: [0; 16],
+            }; 3];
+            assert_eq!(
+                corewar_engine_read_processes(engine, processes.as_mut_ptr(), 3),
+                2
+            );
+            let mut champion_ids: Vec<u8> = processes[..2].iter().map(|p| p.champion_id).collect();
+            champion_ids.sort();
+            assert_eq!(champion_ids, [1, 2]);
+            assert_eq!(
+                corewar_engine_read_processes(engine, processes.as_mut_ptr(), 1),
+                1
+            );
+
+            assert_eq!(corewar_engine_tick(engine, 5), 1);
+            assert_eq!(corewar_engine_cycle(engine), 5);
+            while corewar_engine_tick(engine, 100) == 1 {}
+            assert!(corewar_engine_cycle(engine) <= 30);
+            assert_eq!(corewar_engine_winner(engine), 1);
+            corewar_engine_free(engine);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            assert_eq!(corewar_engine_start(ptr::null_mut()), -1);
+            assert_eq!(
+                CStr::from_ptr(corewar_last_error()).to_str().unwrap(),
+                "Null engine"
+            );
+
+            let engine = corewar_engine_new(1, 0);
+            let junk = [0u8; 16];
+            assert_eq!(corewar_engine_load(engine, junk.as_ptr(), junk.len()), 1);
+            assert_eq!(corewar_engine_start(engine), -1);
+            assert!(!CStr::from_ptr(corewar_last_error()).is_empty());
+            assert_eq!(corewar_engine_cycle(engine), 0);
+            corewar_engine_free(engine);
+            corewar_engine_free(ptr::null_mut());
+        }
+    }
+}
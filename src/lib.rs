@@ -1,21 +1,46 @@
+//! Core War implementation in Rust
+//!
+//! This library provides a complete implementation of the Core War virtual machine,
+//! assembler, and terminal visualization system.
+//!
+//! # Architecture
+//!
+//! The library is organized into several modules:
+//! - `vm`: Virtual machine core with memory, processes, and instruction execution
+//! - `assembler`: Redcode assembler for compiling .s files to .cor binaries
+//! - `ui`: Terminal-based visualization system, with the `tui` feature
+//! - `wasm`: JavaScript bindings for a browser playground, with the `wasm` feature
+//! - `error`: Common error types used throughout the system
+//! - `examples`: Classic-style warriors bundled with the crate
+//! - `ffi`: C API for embedding the VM, with the `ffi` feature
+//!
+//! # API stability
+//!
+//! Downstream visualizers and bots should import from [`prelude`]: its
+//! items keep their names and behaviour across minor releases. The
+//! structs among them with public fields, such as [`prelude::GameConfig`]
+//! and [`prelude::Rules`], are `#[non_exhaustive]`: read and assign their
+//! fields, and build them from `Default` or a preset with their `with_*`
+//! methods, so new fields are not a breaking change. Modules hidden from
+//! the documentation, such as `vm::scheduler` and `vm::arena`, are
+//! implementation details that stay public for the CLI and benchmarks and
+//! may change in any release.
+
 pub mod assembler;
 pub mod error;
+pub mod examples;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "tui")]
 pub mod ui;
-/// Core War implementation in Rust
-///
-/// This library provides a complete implementation of the Core War virtual machine,
-/// assembler, and terminal visualization system.
-///
-/// # Architecture
-///
-/// The library is organized into several modules:
-/// - `vm`: Virtual machine core with memory, processes, and instruction execution
-/// - `assembler`: Redcode assembler for compiling .s files to .cor binaries
-/// - `ui`: Terminal-based visualization system
-/// - `error`: Common error types used throughout the system
 pub mod vm;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 /// Core War constants
+///
+/// These are the standard arena values; battles read them through
+/// [`vm::Rules`], which can override any of them.
 pub mod constants {
     /// Memory size in bytes (6KB)
     pub const MEMORY_SIZE: usize = 6144;
@@ -32,11 +57,44 @@ pub mod constants {
     /// Number of lives required before cycle reduction
     pub const NBR_LIVE: u32 = 40;
 
+    /// Number of death checks without a reduction before cycle_to_die is reduced anyway
+    pub const MAX_CHECKS: u32 = 10;
+
     /// Maximum number of champions
     pub const MAX_CHAMPIONS: usize = 4;
+
+    /// Largest code a champion should have, a sixth of memory
+    pub const CHAMP_MAX_SIZE: usize = MEMORY_SIZE / 6;
+
+    /// Core size used by pMARS
+    pub const PMARS_CORE_SIZE: usize = 8000;
+
+    /// Cycles pMARS runs before declaring a draw
+    pub const PMARS_MAX_CYCLES: u32 = 80000;
+
+    /// Processes each warrior may have in pMARS
+    pub const PMARS_MAX_PROCESSES: usize = 8000;
+
+    /// Cells of p-space each warrior has in pMARS (a sixteenth of the core)
+    pub const PMARS_PSPACE_SIZE: usize = 500;
+}
+
+/// The stable, high-level API
+///
+/// Everything needed to assemble champions, build and run battles and
+/// follow them through events. Changing or removing any of these is a
+/// breaking change; `tests/prelude_test.rs` uses them as a downstream crate
+/// would.
+pub mod prelude {
+    pub use crate::assembler::Assembler;
+    pub use crate::error::{CoreWarError, Result};
+    pub use crate::vm::{
+        Champion, ChampionOptions, GameConfig, GameEngine, GameEngineBuilder, GameEvent,
+        GameObserver, GameSnapshot, GameState, GameStats, Placement, Rules, TickOutcome,
+    };
 }
 
 pub use assembler::Assembler;
 pub use error::{CoreWarError, Result};
 /// Re-export commonly used types for convenience
-pub use vm::{Champion, ChampionLoader, GameConfig, GameEngine, Instruction, Memory, Process};
+pub use vm::{Champion, ChampionLoader, GameConfig, GameEngine, Instruction, Memory, Process, Rules};
@@ -2,19 +2,60 @@
 ///
 /// This is the main CLI interface for running Core War battles between
 /// champion programs written in Redcode assembly language.
+use clap::parser::ValueSource;
 use clap::{Arg, ArgAction, Command};
-use corewar::{Assembler, GameConfig, GameEngine};
-use log::{error, info};
-use std::path::PathBuf;
+use corewar::assembler::diagnostic::Severity;
+use corewar::assembler::inspect::{self, ChampionSummary};
+use corewar::assembler::{disassembler, formatter, lint, loadfile};
+use corewar::examples::{self, Example};
+use corewar::ui::core_image::{CoreImage, WriteHeat};
+use corewar::ui::theme::Theme;
+use corewar::vm::assertion::{self, Assertion};
+use corewar::vm::bench;
+use corewar::vm::dap::DapServer;
+use corewar::vm::debugger::{Debugger, Reply};
+use corewar::vm::evolve::{Evolution, EvolveConfig};
+use corewar::vm::instruction::{self, InstructionSpec};
+use corewar::vm::heat::{HeatFormat, HeatMap};
+use corewar::vm::hill::{DEFAULT_HILL_ROUNDS, DEFAULT_HILL_SIZE, Hill};
+use corewar::vm::hillnet::{HillClient, HillServer};
+use corewar::vm::ladder::{DEFAULT_INTERVAL, Ladder};
+use corewar::vm::loader;
+use corewar::vm::metadata;
+use corewar::vm::montecarlo;
+use corewar::vm::mutator;
+use corewar::vm::pacing::{MAX_SPEED, Throttle};
+use corewar::vm::profile;
+use corewar::vm::project::{self, FightConfig};
+#[cfg(feature = "event-protocol")]
+use corewar::vm::protocol::{EventDecoder, EventTarget};
+#[cfg(feature = "event-protocol")]
+use corewar::vm::recording::Recording;
+use corewar::vm::rematch::{self, RematchSeries};
+use corewar::vm::serve::BattleServer;
+use corewar::vm::service::{BattleService, ServiceLimits};
+use corewar::vm::stats::{self, ChampionStats, MatchupStats};
+use corewar::vm::stress::{self, StressConfig};
+use corewar::vm::tournament::{StandingsFormat, Tournament};
+use corewar::vm::validate;
+use corewar::vm::watcher::SourceWatcher;
+use corewar::vm::{
+    Breakpoint, ChampionOptions, DumpFormat, ExecutionTrace, Instruction, Placement, ResultsBundle,
+    RulesPreset, TieBreakers, TraceFilter, reference,
+};
+use corewar::{Assembler, ChampionLoader, GameConfig, GameEngine};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{EnvFilter, fmt};
 // use corewar::ui::app;
 
 fn main() {
-    // Initialize logging
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Debug)
-        .init();
-
     // Parse command line arguments
     let matches = Command::new("corewar")
         .version("0.1.0")
@@ -23,16 +64,49 @@ fn main() {
         .long_about("Core War is a programming game where players write programs in Redcode assembly \
                     language. These programs compete in a virtual machine's memory space, with the \
                     objective of stopping opposing programs while keeping their own programs alive.")
+        .arg(
+            Arg::new("trace-file")
+                .long("trace-file")
+                .help("Write every trace event, down to single instructions, to FILE as JSON lines")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true)
+        )
         .subcommand(
             Command::new("run")
                 .about("Run a Core War battle")
                 .arg(
                     Arg::new("champions")
-                        .help("Champion .cor files to load")
+                        .help("Champion .cor or load files to load; FILE:ALIAS sets a display name")
                         .value_name("FILE")
-                        .num_args(1..=4)
+                        .num_args(1..)
                         .required(true)
                 )
+                .arg(
+                    Arg::new("number")
+                        .short('n')
+                        .long("number")
+                        .help("Player number for the next champion file (repeatable)")
+                        .value_name("NUMBER")
+                        .value_parser(clap::value_parser!(u8))
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("address")
+                        .short('a')
+                        .long("address")
+                        .help("Load address for the next champion file, decimal or 0x hex (repeatable)")
+                        .value_name("ADDRESS")
+                        .value_parser(parse_address)
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .help("Display name for the champion at the same position (repeatable)")
+                        .value_name("ALIAS")
+                        .action(ArgAction::Append)
+                )
                 .arg(
                     Arg::new("visual")
                         .short('v')
@@ -40,6 +114,12 @@ fn main() {
                         .help("Enable terminal visualization")
                         .action(ArgAction::SetTrue)
                 )
+                .arg(
+                    Arg::new("theme")
+                        .long("theme")
+                        .help("Visualization theme: default, dark, light, high-contrast or a theme file (t switches at runtime)")
+                        .value_name("THEME")
+                )
                 .arg(
                     Arg::new("dump")
                         .short('d')
@@ -48,14 +128,28 @@ fn main() {
                         .value_name("CYCLES")
                         .value_parser(clap::value_parser!(u32))
                 )
+                .arg(
+                    Arg::new("dump-format")
+                        .long("dump-format")
+                        .help("Dump layout: hex, raw (core bytes), json (core and owner of every cell), or zaz (the full core once at cycle CYCLES, as the 42/zaz reference VM prints it)")
+                        .value_name("FORMAT")
+                        .value_parser(["hex", "zaz", "raw", "json"])
+                        .default_value("hex")
+                )
+                .arg(
+                    Arg::new("dump-file")
+                        .long("dump-file")
+                        .help("Write memory dumps to PATH instead of stdout; {cycle} in PATH gives each dump its own file")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                )
                 .arg(
                     Arg::new("speed")
                         .short('s')
                         .long("speed")
-                        .help("Set execution speed (1-1000)")
+                        .help("Execution speed (1-1000): cycles per frame with --visual, otherwise RATE x 100 cycles per second (default: visual 1, text unthrottled)")
                         .value_name("RATE")
                         .value_parser(clap::value_parser!(u32))
-                        .default_value("1")
                 )
                 .arg(
                     Arg::new("pause")
@@ -79,13 +173,274 @@ fn main() {
                         .help("Enable verbose logging")
                         .action(ArgAction::SetTrue)
                 )
+                .arg(
+                    Arg::new("rules")
+                        .long("rules")
+                        .help("Start from a rules preset: standard, or pmars to compare results with pMARS (8000 byte core, 80000 cycles, 8000 processes per warrior, no death checks)")
+                        .value_name("PRESET")
+                        .value_parser(["standard", "pmars"])
+                        .default_value("standard")
+                )
+                .arg(
+                    Arg::new("core-size")
+                        .long("core-size")
+                        .help("Set the memory size in bytes")
+                        .value_name("BYTES")
+                        .value_parser(clap::value_parser!(usize))
+                )
+                .arg(
+                    Arg::new("idx-mod")
+                        .long("idx-mod")
+                        .help("Set the index modulo for relative addressing")
+                        .value_name("BYTES")
+                        .value_parser(clap::value_parser!(usize))
+                )
+                .arg(
+                    Arg::new("cycle-to-die")
+                        .long("cycle-to-die")
+                        .help("Set the initial number of cycles between death checks")
+                        .value_name("CYCLES")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("cycle-delta")
+                        .long("cycle-delta")
+                        .help("Set how much cycle-to-die shrinks after each reduction")
+                        .value_name("CYCLES")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("nbr-live")
+                        .long("nbr-live")
+                        .help("Set the number of lives per period that triggers a reduction")
+                        .value_name("COUNT")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("live-reset")
+                        .long("live-reset")
+                        .help("End a period that reaches NBR_LIVE lives at the scheduled check (at-check) or right away (immediate)")
+                        .value_name("POLICY")
+                        .value_parser(["at-check", "immediate"])
+                        .default_value("at-check")
+                )
+                .arg(
+                    Arg::new("max-champions")
+                        .long("max-champions")
+                        .help("Set the maximum number of champions (default 4, at most 255)")
+                        .value_name("COUNT")
+                        .value_parser(clap::value_parser!(usize))
+                )
+                .arg(
+                    Arg::new("placement")
+                        .long("placement")
+                        .help("Place champions evenly (spread) or at seeded random addresses with a minimum gap (random)")
+                        .value_name("MODE")
+                        .value_parser(["spread", "random"])
+                        .default_value("spread")
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed for random choices, so battles can be replayed exactly")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                )
+                .arg(
+                    Arg::new("champion-coverage")
+                        .long("champion-coverage")
+                        .help("Report which bytes of each champion's code were executed")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("tie-breakers")
+                        .long("tie-breakers")
+                        .help("Break draws using these rules in order: coverage, lives, processes, first-kill")
+                        .value_name("LIST")
+                )
+                .arg(
+                    Arg::new("checkpoint-interval")
+                        .long("checkpoint-interval")
+                        .help("Cycles between rewind checkpoints (0 disables rewinding)")
+                        .value_name("CYCLES")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("checkpoints")
+                        .long("checkpoints")
+                        .help("Number of rewind checkpoints to keep")
+                        .value_name("COUNT")
+                        .value_parser(clap::value_parser!(usize))
+                )
+                .arg(
+                    Arg::new("skip-invalid")
+                        .long("skip-invalid")
+                        .help("Battle with the remaining champions when some files fail to load")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("mutate")
+                        .long("mutate")
+                        .help("Mutate champion code at load time: bitflip=N, nops=N or immediates, with @ID for one champion (repeatable)")
+                        .value_name("MUTATOR")
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("break")
+                        .long("break")
+                        .help("Pause the visualizer on pc=ADDR, cycle=N, fork=CHAMPION, death or watch=START..END")
+                        .value_name("BREAKPOINT")
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("assert")
+                        .long("assert")
+                        .help("Check a condition against the battle, e.g. \"winner==1\" or \"mem[0x10]==0x44@1000\"")
+                        .value_name("EXPR")
+                        .action(ArgAction::Append)
+                        .conflicts_with("visual")
+                )
+                .arg(
+                    Arg::new("bundle")
+                        .long("bundle")
+                        .help("Write a results bundle that `corewar verify-bundle` can re-check")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .conflicts_with("visual")
+                )
+                .arg(
+                    Arg::new("cast")
+                        .long("cast")
+                        .help("Record the visualization of the battle to FILE as an asciinema cast")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .conflicts_with_all(["visual", "json"])
+                )
+                .arg(
+                    Arg::new("cast-interval")
+                        .long("cast-interval")
+                        .help("Cycles between the frames of the --cast recording")
+                        .value_name("CYCLES")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .default_value("10")
+                        .requires("cast")
+                )
+                .arg(
+                    Arg::new("export-image")
+                        .long("export-image")
+                        .help("Save the final core, colored by owner and write heat, as a PNG image")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .conflicts_with_all(["visual", "json"])
+                )
+                .arg(
+                    Arg::new("emit-events")
+                        .long("emit-events")
+                        .help("Write the binary event stream to fd:N (e.g. a pipe to a visualizer) or file:PATH")
+                        .value_name("TARGET")
+                )
+                .arg(
+                    Arg::new("redact")
+                        .long("redact")
+                        .help("Leave written bytes and opcodes out of the event stream, for spectators")
+                        .requires("emit-events")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print one JSON object per event, then the results, instead of the text report")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["visual", "dump", "assert", "bundle", "heat", "champion-coverage"])
+                )
+                .arg(
+                    Arg::new("record")
+                        .long("record")
+                        .help("Record the battle to FILE for `corewar replay`")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                )
+                .arg(
+                    Arg::new("heat")
+                        .long("heat")
+                        .help("Add per-instruction execution counts to FILE, keeping counts from earlier battles")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .conflicts_with("visual")
+                )
+                .arg(
+                    Arg::new("heat-format")
+                        .long("heat-format")
+                        .help("Format of the --heat file: folded (flamegraph stacks) or csv")
+                        .value_name("FORMAT")
+                        .value_parser(["folded", "csv"])
+                        .default_value("folded")
+                        .requires("heat")
+                )
+                .arg(
+                    Arg::new("trace")
+                        .long("trace")
+                        .help("Trace every executed instruction to FILE: cycle, process, champion, PC, instruction and its effects")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                )
+                .arg(
+                    Arg::new("trace-format")
+                        .long("trace-format")
+                        .help("Format of the --trace file: text, or chrome for chrome://tracing and Perfetto")
+                        .value_name("FORMAT")
+                        .value_parser(["text", "chrome"])
+                        .default_value("text")
+                        .requires("trace")
+                )
+                .arg(
+                    Arg::new("trace-champion")
+                        .long("trace-champion")
+                        .help("Trace only the processes of this champion (repeatable)")
+                        .value_name("ID")
+                        .value_parser(clap::value_parser!(u8))
+                        .action(ArgAction::Append)
+                        .requires("trace")
+                )
+                .arg(
+                    Arg::new("trace-process")
+                        .long("trace-process")
+                        .help("Trace only this process (repeatable)")
+                        .value_name("ID")
+                        .value_parser(clap::value_parser!(u32))
+                        .action(ArgAction::Append)
+                        .requires("trace")
+                )
+                .arg(
+                    Arg::new("rematch-on-draw")
+                        .long("rematch-on-draw")
+                        .help("Replay a drawn battle with new seeds up to N times; the first decisive battle is reported")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32))
+                        .conflicts_with("visual")
+                )
+                .arg(
+                    Arg::new("repeat")
+                        .long("repeat")
+                        .help("Play the matchup N times with new seeds and report win and draw rates; placement defaults to random")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .conflicts_with_all(["visual", "rematch-on-draw", "json", "bundle", "record", "assert", "trace"])
+                )
+                .arg(
+                    Arg::new("summary")
+                        .long("summary")
+                        .help("With --repeat, print only the summary, not every battle")
+                        .action(ArgAction::SetTrue)
+                        .requires("repeat")
+                )
         )
         .subcommand(
             Command::new("asm")
                 .about("Assemble a Redcode source file")
                 .arg(
                     Arg::new("input")
-                        .help("Input .s file")
+                        .help("Input .s file, or - for stdin")
                         .value_name("INPUT")
                         .required(true)
                 )
@@ -93,7 +448,7 @@ fn main() {
                     Arg::new("output")
                         .short('o')
                         .long("output")
-                        .help("Output .cor file")
+                        .help("Output .cor file, or - for stdout")
                         .value_name("OUTPUT")
                 )
                 .arg(
@@ -103,112 +458,1608 @@ fn main() {
                         .help("Verbose compilation output")
                         .action(ArgAction::SetTrue)
                 )
+                .arg(
+                    Arg::new("listing")
+                        .short('l')
+                        .long("listing")
+                        .help("Write a listing of addresses, bytes and symbols to this file")
+                        .value_name("LISTING")
+                )
+                .arg(
+                    Arg::new("deny-warnings")
+                        .long("deny-warnings")
+                        .help("Fail when the program has warnings, e.g. in CI")
+                        .action(ArgAction::SetTrue)
+                )
         )
         .subcommand(
-            Command::new("info")
-                .about("Display information about a champion file")
+            Command::new("disasm")
+                .about("Disassemble a .cor file back into Redcode")
+                .arg(
+                    Arg::new("input")
+                        .help("Input .cor file")
+                        .value_name("INPUT")
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Output .s file (default: stdout)")
+                        .value_name("OUTPUT")
+                )
+                .arg(
+                    Arg::new("load-file")
+                        .long("load-file")
+                        .help("Write an ICWS'94 load file, which `run` reads like a .cor file")
+                        .action(ArgAction::SetTrue)
+                )
+        )
+        .subcommand(
+            Command::new("fmt")
+                .about("Format a Redcode source file")
+                .arg(
+                    Arg::new("input")
+                        .help("Input .s file, or - for stdin")
+                        .value_name("INPUT")
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("write")
+                        .short('w')
+                        .long("write")
+                        .help("Rewrite the file in place instead of printing it")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .help("Fail if the file is not formatted, without changing it")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("write")
+                )
+        )
+        .subcommand(
+            Command::new("lint")
+                .about("Check a Redcode source file for likely mistakes")
+                .arg(
+                    Arg::new("input")
+                        .help("Input .s file, or - for stdin")
+                        .value_name("INPUT")
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("deny-warnings")
+                        .long("deny-warnings")
+                        .help("Fail on warnings as well as errors, e.g. in CI")
+                        .action(ArgAction::SetTrue)
+                )
+        )
+        .subcommand(
+            Command::new("validate")
+                .about("Check that .cor files are well formed, e.g. in CI")
+                .after_help("Exit codes: 0 valid, 1 unreadable file, 2 wrong magic number, \
+                             3 bad header field, 4 code size mismatch, 5 undecodable code, \
+                             6 code too large")
+                .arg(
+                    Arg::new("files")
+                        .help(".cor files to check")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .num_args(1..)
+                        .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("explain")
+                .about("Explain an instruction or VM concept")
+                .arg(
+                    Arg::new("topic")
+                        .help("Instruction mnemonic (e.g. sti) or concept (e.g. IDX_MOD)")
+                        .value_name("TOPIC")
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the instruction set description as JSON")
+                        .action(ArgAction::SetTrue)
+                )
+        )
+        .subcommand(
+            Command::new("stress")
+                .about("Stress the scheduler and memory with a fork-heavy workload")
+                .arg(
+                    Arg::new("processes")
+                        .long("processes")
+                        .help("Number of processes to seed")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10000")
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .help("Number of cycles to run")
+                        .value_name("M")
+                        .value_parser(clap::value_parser!(u32))
+                        .default_value("1000")
+                )
+                .arg(
+                    Arg::new("champions")
+                        .long("champions")
+                        .help("Number of synthetic champions")
+                        .value_name("COUNT")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("4")
+                )
+                .arg(
+                    Arg::new("max-processes")
+                        .long("max-processes")
+                        .help("Stop early once this many processes exist (0 = unlimited)")
+                        .value_name("COUNT")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("1000000")
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed for the synthesized workload")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                )
+        )
+        .subcommand(
+            Command::new("verify-bundle")
+                .about("Replay a results bundle and check its report, hashes and snapshot")
                 .arg(
                     Arg::new("file")
-                        .help("Champion .cor file")
+                        .help("Bundle written with `corewar run --bundle`")
                         .value_name("FILE")
                         .required(true)
                 )
         )
-        .get_matches();
-
-    // Handle subcommands
-    match matches.subcommand() {
-        Some(("run", sub_matches)) => {
-            if let Err(e) = run_battle(sub_matches) {
-                error!("Failed to run battle: {}", e);
-                process::exit(1);
-            }
-        }
-        Some(("asm", sub_matches)) => {
-            if let Err(e) = assemble_file(sub_matches) {
-                error!("Failed to assemble file: {}", e);
-                process::exit(1);
-            }
-        }
-        Some(("info", sub_matches)) => {
-            if let Err(e) = show_champion_info(sub_matches) {
-                error!("Failed to show champion info: {}", e);
-                process::exit(1);
-            }
-        }
-        _ => {
-            // No subcommand provided, show help
-            let mut cmd = Command::new("corewar");
-            cmd.print_help().unwrap();
-            println!();
-        }
-    }
-}
-
-/// Run a Core War battle
-fn run_battle(matches: &clap::ArgMatches) -> anyhow::Result<()> {
-    let champion_files: Vec<PathBuf> = matches
-        .get_many::<String>("champions")
-        .unwrap()
-        .map(PathBuf::from)
-        .collect();
-
-    let visual = matches.get_flag("visual");
-    let dump_cycles = matches.get_one::<u32>("dump").copied().unwrap_or(0);
-    let speed = matches.get_one::<u32>("speed").copied().unwrap_or(1);
-    let start_paused = matches.get_flag("pause");
-    let max_cycles = matches.get_one::<u32>("cycles").copied().unwrap_or(0);
-    let verbose = matches.get_flag("verbose");
-
-    // Validate speed
-    if speed == 0 || speed > 1000 {
-        return Err(anyhow::anyhow!("Speed must be between 1 and 1000"));
-    }
-
-    // Create game configuration
-    let config = GameConfig {
-        max_cycles,
-        dump_cycles,
-        speed,
-        verbose,
-        start_paused,
-    };
-
-    // Create and configure game engine
-    let mut engine = GameEngine::new(config);
-
-    // Load champions
-    info!("Loading {} champions...", champion_files.len());
-    engine.load_champions(&champion_files, None)?;
-
-    // Run the battle
-    if visual {
-        // Minimal demo: launch terminal UI with real VM data
-        corewar::ui::app::run_terminal_ui_with_vm(&mut engine)?;
-        return Ok(());
-    } else {
-        run_text_mode(&mut engine)?;
-    }
-
-    Ok(())
-}
-
-/// Run battle in text mode
-fn run_text_mode(engine: &mut GameEngine) -> anyhow::Result<()> {
-    info!("Starting Core War battle...");
-
-    // Show initial state
-    engine.dump_memory()?;
-
-    // Run to completion
-    let winner = engine.run_to_completion()?;
-
-    // Show final results
-    let stats = engine.get_stats();
-    println!("\n=== Battle Results ===");
-    println!("Total cycles: {}", stats.cycle);
-    println!("Elapsed time: {:.2}s", stats.elapsed_time.as_secs_f64());
+        .subcommand(
+            Command::new("decode-events")
+                .about("Print the events of a binary event stream file")
+                .arg(
+                    Arg::new("file")
+                        .help("Stream written with `corewar run --emit-events file:PATH`")
+                        .value_name("FILE")
+                        .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("replay")
+                .about("Replay a recorded battle without executing the champions")
+                .arg(
+                    Arg::new("file")
+                        .help("Recording written with `corewar run --record`")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("visual")
+                        .short('v')
+                        .long("visual")
+                        .help("Replay in the terminal visualization")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("theme")
+                        .long("theme")
+                        .help("Visualization theme: default, dark, light, high-contrast or a theme file (t switches at runtime)")
+                        .value_name("THEME")
+                        .requires("visual")
+                )
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Aggregate results across stored battles")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("champion")
+                        .about("Win rate, survival and battle length of one champion")
+                        .arg(
+                            Arg::new("champion")
+                                .help("Champion name or a prefix of its code's SHA-256")
+                                .value_name("CHAMPION")
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("bundles")
+                                .help("Bundles written with `corewar run --bundle`, or directories of them")
+                                .value_name("BUNDLE")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .num_args(1..)
+                                .required(true)
+                        )
+                )
+                .subcommand(
+                    Command::new("matchup")
+                        .about("Head-to-head results of two champions")
+                        .arg(
+                            Arg::new("first")
+                                .help("First champion's name or SHA-256 prefix")
+                                .value_name("A")
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("second")
+                                .help("Second champion's name or SHA-256 prefix")
+                                .value_name("B")
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("bundles")
+                                .help("Bundles written with `corewar run --bundle`, or directories of them")
+                                .value_name("BUNDLE")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .num_args(1..)
+                                .required(true)
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("analyze")
+                .about("Profile a champion's behaviour")
+                .arg(
+                    Arg::new("champion")
+                        .help("Champion .cor file to profile")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("dynamic")
+                        .long("dynamic")
+                        .help("Profile the champion from battles against a suite (the only analysis so far)")
+                        .action(ArgAction::SetTrue)
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("suite")
+                        .long("suite")
+                        .help("Opponent .cor files, or directories of them")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .num_args(1..)
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .help("Maximum number of cycles per battle")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed for every battle")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                )
+        )
+        .subcommand(
+            Command::new("bench")
+                .about("Measure the virtual machine's throughput")
+                .arg(
+                    Arg::new("champions")
+                        .help("Champion .cor files to run (default: a built-in mix)")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .num_args(0..)
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .help("Number of cycles to run (default 10000)")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed for every battle")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                )
+        )
+        .subcommand(
+            Command::new("tournament")
+                .about("Play every pairing of a set of champions and rank them")
+                .arg(
+                    Arg::new("champions")
+                        .help("Champion .cor files, or directories of them")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .num_args(1..)
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("melee")
+                        .long("melee")
+                        .help("Also play every N-way melee (e.g. 3 or 4); may be repeated")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("rounds")
+                        .long("rounds")
+                        .help("Rounds per match, rotating the placement order; the most round wins takes the match")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .default_value("1")
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Format of the standings: table, csv or json")
+                        .value_name("FORMAT")
+                        .value_parser(["table", "csv", "json"])
+                        .default_value("table")
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("Write the standings to FILE instead of printing them")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .help("Maximum number of cycles per match")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed for every match")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                )
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Run battles headless and stream them to browser viewers over WebSocket, or run battles as an HTTP service")
+                .arg(
+                    Arg::new("champions")
+                        .help("Champion .cor files, or directories of them; with --http, warriors the service starts with")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .num_args(1..)
+                        .required_unless_present("http")
+                )
+                .arg(
+                    Arg::new("http")
+                        .long("http")
+                        .help("Serve an HTTP API for submitting warriors, starting battles and fetching results")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("max-running")
+                        .long("max-running")
+                        .help("With --http, the most battles running at once (default: 4)")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .requires("http")
+                )
+                .arg(
+                    Arg::new("listen")
+                        .long("listen")
+                        .help("Address to accept viewers on")
+                        .value_name("ADDR")
+                        .default_value("127.0.0.1:9020")
+                )
+                .arg(
+                    Arg::new("speed")
+                        .long("speed")
+                        .short('s')
+                        .help("Cycles per second to play at, so viewers can follow")
+                        .value_name("CPS")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .default_value("500")
+                        .conflicts_with("http")
+                )
+                .arg(
+                    Arg::new("rounds")
+                        .long("rounds")
+                        .help("Rounds to play, restarting the battle between them")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .default_value("1")
+                        .conflicts_with("http")
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .help("Maximum number of cycles per round; with --http, the most any battle runs (default: 100000)")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed for the battle")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                        .conflicts_with("http")
+                )
+                .arg(
+                    Arg::new("no-wait")
+                        .long("no-wait")
+                        .help("Start at once instead of waiting for the first viewer")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("http")
+                )
+        )
+        .subcommand(
+            Command::new("hill")
+                .about("Run a persistent king-of-the-hill")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("init")
+                        .about("Create an empty hill file")
+                        .arg(
+                            Arg::new("hill")
+                                .help("Hill file to create")
+                                .value_name("HILL")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("size")
+                                .long("size")
+                                .help("Number of champions the hill keeps (default 10)")
+                                .value_name("N")
+                                .value_parser(clap::value_parser!(usize))
+                        )
+                        .arg(
+                            Arg::new("rounds")
+                                .long("rounds")
+                                .help("Rounds per match, rotating the placement order (default 4)")
+                                .value_name("N")
+                                .value_parser(clap::value_parser!(u32))
+                        )
+                        .arg(
+                            Arg::new("cycles")
+                                .long("cycles")
+                                .help("Maximum number of cycles per round")
+                                .value_name("N")
+                                .value_parser(clap::value_parser!(u32))
+                        )
+                        .arg(
+                            Arg::new("seed")
+                                .long("seed")
+                                .help("Seed for the first round of every match")
+                                .value_name("SEED")
+                                .value_parser(clap::value_parser!(u64))
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .help("Replace an existing hill file")
+                                .action(ArgAction::SetTrue)
+                        )
+                )
+                .subcommand(
+                    Command::new("submit")
+                        .about("Challenge the hill with champions, in order")
+                        .arg(
+                            Arg::new("hill")
+                                .help("Hill file created with `corewar hill init`, or the http:// address of a hill server")
+                                .value_name("HILL")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("champions")
+                                .help("Champion .cor files")
+                                .value_name("FILE")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .num_args(1..)
+                                .required(true)
+                        )
+                )
+                .subcommand(
+                    Command::new("show")
+                        .about("Print the hill's standings")
+                        .arg(
+                            Arg::new("hill")
+                                .help("Hill file created with `corewar hill init`, or the http:// address of a hill server")
+                                .value_name("HILL")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .required(true)
+                        )
+                )
+                .subcommand(
+                    Command::new("serve")
+                        .about("Take submissions to the hill over HTTP")
+                        .arg(
+                            Arg::new("hill")
+                                .help("Hill file created with `corewar hill init`")
+                                .value_name("HILL")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("listen")
+                                .long("listen")
+                                .help("Address to listen on")
+                                .value_name("ADDR")
+                                .default_value("127.0.0.1:9030")
+                        )
+                )
+                .subcommand(
+                    Command::new("ladder")
+                        .about("Keep the hill in step with a directory of champions, replaying matches on a schedule")
+                        .arg(
+                            Arg::new("hill")
+                                .help("Hill file created with `corewar hill init`")
+                                .value_name("HILL")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("champions")
+                                .long("champions")
+                                .help("Directory of .cor files; adding or removing one enters or retires a champion")
+                                .value_name("DIR")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("interval")
+                                .long("interval")
+                                .help("Seconds between the start of two passes (default 60)")
+                                .value_name("SECS")
+                                .value_parser(clap::value_parser!(u64))
+                        )
+                        .arg(
+                            Arg::new("rematches")
+                                .long("rematches")
+                                .help("Rematches played per pass, least recently played pair first (default 10)")
+                                .value_name("N")
+                                .value_parser(clap::value_parser!(usize))
+                        )
+                        .arg(
+                            Arg::new("listen")
+                                .long("listen")
+                                .help("Also serve the standings and take submissions over HTTP on this address")
+                                .value_name("ADDR")
+                        )
+                        .arg(
+                            Arg::new("once")
+                                .long("once")
+                                .help("Run a single pass, print it and the standings, and exit")
+                                .action(ArgAction::SetTrue)
+                                .conflicts_with_all(["interval", "listen"])
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("evolve")
+                .about("Breed warriors with a genetic algorithm to beat benchmark champions")
+                .arg(
+                    Arg::new("benchmarks")
+                        .help("Benchmark .cor files, or directories of them")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .num_args(1..)
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("population")
+                        .long("population")
+                        .help("Warriors in every generation")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("32")
+                )
+                .arg(
+                    Arg::new("generations")
+                        .long("generations")
+                        .help("Generations to breed")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32))
+                        .default_value("20")
+                )
+                .arg(
+                    Arg::new("elite")
+                        .long("elite")
+                        .help("Best warriors kept unchanged in every generation")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("2")
+                )
+                .arg(
+                    Arg::new("mutation-rate")
+                        .long("mutation-rate")
+                        .help("Chance of each instruction of a child being mutated, from 0 to 1")
+                        .value_name("RATE")
+                        .value_parser(clap::value_parser!(f64))
+                        .default_value("0.1")
+                )
+                .arg(
+                    Arg::new("crossover-rate")
+                        .long("crossover-rate")
+                        .help("Chance of a child being bred from two parents, from 0 to 1")
+                        .value_name("RATE")
+                        .value_parser(clap::value_parser!(f64))
+                        .default_value("0.7")
+                )
+                .arg(
+                    Arg::new("max-length")
+                        .long("max-length")
+                        .help("Most instructions a warrior may have")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("32")
+                )
+                .arg(
+                    Arg::new("rounds")
+                        .long("rounds")
+                        .help("Rounds played against each benchmark")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .default_value("2")
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .help("Maximum number of cycles per round (default 20000)")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed for the evolution and its rounds")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                )
+                .arg(
+                    Arg::new("keep")
+                        .long("keep")
+                        .help("Number of best warriors to write out")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("3")
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("Directory to write the best warriors to, as .s files")
+                        .value_name("DIR")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .default_value("evolved")
+                )
+        )
+        .subcommand(
+            Command::new("examples")
+                .about("List, extract or run the bundled example warriors")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("List the example warriors"))
+                .subcommand(
+                    Command::new("extract")
+                        .about("Write example warriors' sources to a directory")
+                        .arg(
+                            Arg::new("names")
+                                .help("Warriors to extract (default: all)")
+                                .value_name("NAME")
+                                .num_args(1..)
+                        )
+                        .arg(
+                            Arg::new("output")
+                                .long("output")
+                                .short('o')
+                                .help("Directory to write to")
+                                .value_name("DIR")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .default_value(".")
+                        )
+                        .arg(
+                            Arg::new("cor")
+                                .long("cor")
+                                .help("Also write each warrior assembled, as a .cor file")
+                                .action(ArgAction::SetTrue)
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .help("Replace existing files")
+                                .action(ArgAction::SetTrue)
+                        )
+                )
+                .subcommand(
+                    Command::new("run")
+                        .about("Battle example warriors against each other headless")
+                        .arg(
+                            Arg::new("names")
+                                .help("Warriors to battle")
+                                .value_name("NAME")
+                                .num_args(1..)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::new("cycles")
+                                .long("cycles")
+                                .help("Maximum number of cycles")
+                                .value_name("N")
+                                .value_parser(clap::value_parser!(u32))
+                        )
+                        .arg(
+                            Arg::new("seed")
+                                .long("seed")
+                                .help("Seed for the battle")
+                                .value_name("SEED")
+                                .value_parser(clap::value_parser!(u64))
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("new")
+                .about("Create a warrior project with a template warrior and opponents to fight")
+                .arg(
+                    Arg::new("project")
+                        .help("Directory to create; its name is the warrior's")
+                        .value_name("NAME")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("fight")
+                .about("Assemble a project's warrior and battle it against the opponents in its fight.json")
+                .arg(
+                    Arg::new("paths")
+                        .help("Project directory or its fight file, or warrior files: the first fights each of the others")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .num_args(1..)
+                        .default_value(".")
+                )
+                .arg(
+                    Arg::new("watch")
+                        .short('w')
+                        .long("watch")
+                        .help("Reassemble and fight again whenever a warrior or the fight file is saved")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("visual")
+                        .long("visual")
+                        .help("Battle the warrior against all its opponents at once in the terminal visualization")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("theme")
+                        .long("theme")
+                        .help("Visualization theme: default, dark, light, high-contrast or a theme file")
+                        .value_name("THEME")
+                        .requires("visual")
+                )
+                .arg(
+                    Arg::new("rounds")
+                        .long("rounds")
+                        .help("Rounds per match (default: the fight file's, or 4)")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .conflicts_with("visual")
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .help("Maximum number of cycles per round (default: the fight file's, or 20000)")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed of the first round of every match")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                )
+        )
+        .subcommand(
+            Command::new("debug")
+                .about("Step through a battle from a command-line debugger")
+                .arg(
+                    Arg::new("champions")
+                        .help("Champion .cor files")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .num_args(1..)
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("break")
+                        .short('b')
+                        .long("break")
+                        .help("Start with a breakpoint: ADDR, pc=ADDR, cycle=N, fork=CHAMPION, death or watch=START..END (repeatable)")
+                        .value_name("BREAKPOINT")
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .help("Maximum number of cycles")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32))
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed for the battle")
+                        .value_name("SEED")
+                        .value_parser(clap::value_parser!(u64))
+                )
+        )
+        .subcommand(
+            Command::new("dap")
+                .about("Serve the Debug Adapter Protocol on stdin and stdout, for debugging warriors in an editor")
+                .arg(
+                    Arg::new("listen")
+                        .long("listen")
+                        .help("Accept editors on a TCP address instead, one session at a time")
+                        .value_name("ADDR")
+                )
+        )
+        .subcommand(
+            Command::new("info")
+                .about("Display information about champion files, side by side for several")
+                .arg(
+                    Arg::new("file")
+                        .help("Champion .cor files")
+                        .value_name("FILE")
+                        .required(true)
+                        .num_args(1..)
+                )
+                .arg(
+                    Arg::new("disasm")
+                        .short('d')
+                        .long("disasm")
+                        .help("Show each champion's code disassembled")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("histogram")
+                        .long("histogram")
+                        .help("Show how often each instruction appears")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("first-live")
+                        .long("first-live")
+                        .help("Estimate the cycle each champion first reports live on")
+                        .action(ArgAction::SetTrue)
+                )
+        )
+        .get_matches();
+
+    if let Err(e) = init_tracing(&matches) {
+        eprintln!("Failed to set up tracing: {}", e);
+        process::exit(1);
+    }
+
+    // Handle subcommands
+    match matches.subcommand() {
+        Some(("run", sub_matches)) => {
+            if let Err(e) = run_battle(sub_matches) {
+                error!("Failed to run battle: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("asm", sub_matches)) => {
+            if let Err(e) = assemble_file(sub_matches) {
+                error!("Failed to assemble file: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("disasm", sub_matches)) => {
+            if let Err(e) = disassemble_file(sub_matches) {
+                error!("Failed to disassemble file: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("fmt", sub_matches)) => {
+            if let Err(e) = format_file(sub_matches) {
+                error!("Failed to format file: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("lint", sub_matches)) => {
+            if let Err(e) = lint_file(sub_matches) {
+                error!("Failed to lint file: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("validate", sub_matches)) => match validate_files(sub_matches) {
+            Ok(0) => {}
+            Ok(code) => process::exit(code),
+            Err(e) => {
+                error!("Failed to validate files: {}", e);
+                process::exit(1);
+            }
+        },
+        Some(("explain", sub_matches)) => {
+            if let Err(e) = explain_topic(sub_matches) {
+                error!("Failed to explain topic: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("stress", sub_matches)) => {
+            if let Err(e) = run_stress(sub_matches) {
+                error!("Stress test failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("verify-bundle", sub_matches)) => {
+            if let Err(e) = verify_bundle(sub_matches) {
+                error!("Bundle verification failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("decode-events", sub_matches)) => {
+            if let Err(e) = decode_events(sub_matches) {
+                error!("Failed to decode events: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("replay", sub_matches)) => {
+            if let Err(e) = replay_battle(sub_matches) {
+                error!("Failed to replay battle: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("stats", sub_matches)) => {
+            if let Err(e) = show_stats(sub_matches) {
+                error!("Failed to compute stats: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("analyze", sub_matches)) => {
+            if let Err(e) = analyze_champion(sub_matches) {
+                error!("Failed to analyze champion: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("bench", sub_matches)) => {
+            if let Err(e) = run_bench(sub_matches) {
+                error!("Benchmark failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("tournament", sub_matches)) => {
+            if let Err(e) = run_tournament(sub_matches) {
+                error!("Tournament failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("serve", sub_matches)) => {
+            if let Err(e) = run_serve(sub_matches) {
+                error!("Server failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("hill", sub_matches)) => {
+            if let Err(e) = run_hill(sub_matches) {
+                error!("Hill command failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("evolve", sub_matches)) => {
+            if let Err(e) = run_evolve(sub_matches) {
+                error!("Evolution failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("examples", sub_matches)) => {
+            if let Err(e) = run_examples(sub_matches) {
+                error!("Examples command failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("new", sub_matches)) => {
+            if let Err(e) = create_project(sub_matches) {
+                error!("Failed to create the project: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("fight", sub_matches)) => {
+            if let Err(e) = run_fight(sub_matches) {
+                error!("Fight failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("debug", sub_matches)) => {
+            if let Err(e) = run_debugger(sub_matches) {
+                error!("Debugger failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("dap", sub_matches)) => {
+            if let Err(e) = run_dap(sub_matches) {
+                error!("Debug adapter failed: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(("info", sub_matches)) => {
+            if let Err(e) = show_champion_info(sub_matches) {
+                error!("Failed to show champion info: {}", e);
+                process::exit(1);
+            }
+        }
+        _ => {
+            // No subcommand provided, show help
+            let mut cmd = Command::new("corewar");
+            cmd.print_help().unwrap();
+            println!();
+        }
+    }
+}
+
+/// Send log messages to stderr and, with `--trace-file`, every event to a file
+///
+/// The trace file holds one JSON object per event, with its level, target,
+/// fields and enclosing spans (the battle, the cycle). Stderr shows INFO and above, or WARN and above for commands that play
+/// many battles; `RUST_LOG` overrides the level. Without a trace file the
+/// VM's per-cycle and per-instruction events are disabled where they are
+/// raised, so the hot loop neither formats nor writes anything.
+fn init_tracing(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let batch = match matches.subcommand() {
+        Some(("hill", sub_matches)) => sub_matches.subcommand_name() != Some("ladder"),
+        Some(("stress" | "analyze" | "bench" | "tournament" | "evolve" | "fight", _)) => true,
+        Some(("run", sub_matches)) => sub_matches.contains_id("repeat"),
+        _ => false,
+    };
+    let level = if batch { "warn" } else { "info" };
+    let stderr = fmt::layer().with_writer(std::io::stderr).with_filter(
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level)),
+    );
+
+    let trace_file = matches
+        .subcommand()
+        .and_then(|(_, sub_matches)| sub_matches.get_one::<PathBuf>("trace-file"))
+        .or_else(|| matches.get_one::<PathBuf>("trace-file"))
+        .map(|path| -> anyhow::Result<_> {
+            let file = std::fs::File::create(path)?;
+            Ok(fmt::layer()
+                .json()
+                .with_writer(Mutex::new(file))
+                .with_filter(LevelFilter::TRACE))
+        })
+        .transpose()?;
+
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(trace_file)
+        .init();
+    Ok(())
+}
+
+/// Run a Core War battle
+fn run_battle(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let (champion_files, mut aliases): (Vec<PathBuf>, Vec<Option<String>>) = matches
+        .get_many::<String>("champions")
+        .unwrap()
+        .map(|arg| split_champion_alias(arg))
+        .unzip();
+
+    if let Some(names) = matches.get_many::<String>("name") {
+        let names: Vec<&String> = names.collect();
+        if names.len() > champion_files.len() {
+            anyhow::bail!(
+                "{} names given for {} champions",
+                names.len(),
+                champion_files.len()
+            );
+        }
+        for (i, name) in names.into_iter().enumerate() {
+            if aliases[i].is_some() {
+                anyhow::bail!(
+                    "Champion {} is named both by --name and FILE:ALIAS",
+                    champion_files[i].display()
+                );
+            }
+            aliases[i] = Some(name.clone());
+        }
+    }
+
+    let numbers = next_champion_values::<u8>(matches, "number", "-n")?;
+    let addresses = next_champion_values::<usize>(matches, "address", "-a")?;
+    let options: Vec<ChampionOptions> = aliases
+        .into_iter()
+        .zip(numbers)
+        .zip(addresses)
+        .map(|((alias, number), load_address)| {
+            ChampionOptions::default()
+                .with_alias(alias)
+                .with_number(number)
+                .with_load_address(load_address)
+        })
+        .collect();
+
+    let visual = matches.get_flag("visual");
+    let cast = matches.get_one::<PathBuf>("cast");
+    if matches.contains_id("theme") && !visual && cast.is_none() {
+        anyhow::bail!("--theme needs --visual or --cast");
+    }
+    let theme = load_theme(matches)?;
+    let json = matches.get_flag("json");
+    let dump_cycles = matches.get_one::<u32>("dump").copied().unwrap_or(0);
+    let dump_format: DumpFormat = matches.get_one::<String>("dump-format").unwrap().parse()?;
+    if dump_format == DumpFormat::Zaz {
+        if !matches.contains_id("dump") {
+            anyhow::bail!("--dump-format zaz needs --dump CYCLES");
+        }
+        // The reference layout is meant to be diffed, so nothing else may print
+        for flag in [
+            "visual",
+            "assert",
+            "bundle",
+            "heat",
+            "export-image",
+            "cast",
+            "champion-coverage",
+            "rematch-on-draw",
+            "break",
+        ] {
+            if matches.value_source(flag) == Some(ValueSource::CommandLine) {
+                anyhow::bail!("--dump-format zaz cannot be combined with --{}", flag);
+            }
+        }
+    }
+    let speed = matches.get_one::<u32>("speed").copied();
+    let start_paused = matches.get_flag("pause");
+    let preset: RulesPreset = matches.get_one::<String>("rules").unwrap().parse()?;
+    // The preset's cycle limit applies unless one is given
+    let max_cycles = match matches.value_source("cycles") {
+        Some(ValueSource::CommandLine) => *matches.get_one::<u32>("cycles").unwrap(),
+        _ => preset.max_cycles(),
+    };
+    let verbose = matches.get_flag("verbose");
+    let show_coverage = matches.get_flag("champion-coverage");
+    let heat = matches
+        .get_one::<PathBuf>("heat")
+        .map(|path| -> anyhow::Result<(PathBuf, HeatFormat)> {
+            let format = matches.get_one::<String>("heat-format").unwrap().parse()?;
+            Ok((path.clone(), format))
+        })
+        .transpose()?;
+    // Execution counts come from the coverage tracker
+    let track_coverage = show_coverage || heat.is_some();
+    let tie_breakers = matches
+        .get_one::<String>("tie-breakers")
+        .map(|list| list.parse::<TieBreakers>())
+        .transpose()?
+        .unwrap_or_default();
+    let seed = matches
+        .get_one::<u64>("seed")
+        .copied()
+        .unwrap_or(corewar::vm::rng::DEFAULT_SEED);
+    let assertions = matches
+        .get_many::<String>("assert")
+        .map(|values| {
+            values
+                .map(|value| Assertion::parse(value))
+                .collect::<corewar::Result<Vec<_>>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let breakpoints = matches
+        .get_many::<String>("break")
+        .map(|values| {
+            values
+                .map(|value| value.parse::<Breakpoint>())
+                .collect::<corewar::Result<Vec<_>>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    // Mutators are not cloneable, so each engine parses its own from the specs
+    let mutator_specs: Vec<&String> = matches
+        .get_many::<String>("mutate")
+        .map(|values| values.collect())
+        .unwrap_or_default();
+    for spec in &mutator_specs {
+        mutator::parse(spec)?;
+    }
+    let load = |engine: &mut GameEngine| -> corewar::Result<()> {
+        for spec in &mutator_specs {
+            engine.add_mutator(mutator::parse(spec)?);
+        }
+        engine.load_champions_with_options(&champion_files, &options)
+    };
+
+    // Validate speed
+    if speed.is_some_and(|speed| speed == 0 || speed > MAX_SPEED) {
+        return Err(anyhow::anyhow!("Speed must be between 1 and {}", MAX_SPEED));
+    }
+
+    // Apply arena rule overrides
+    let defaults = preset.rules();
+    let memory_size = matches
+        .get_one::<usize>("core-size")
+        .copied()
+        .unwrap_or(defaults.memory_size);
+    let rules = defaults
+        .with_memory_size(memory_size)
+        // Keep the default IDX_MOD usable on cores smaller than it
+        .with_idx_mod(
+            matches
+                .get_one::<usize>("idx-mod")
+                .copied()
+                .unwrap_or(defaults.idx_mod.min(memory_size)),
+        )
+        .with_cycle_to_die(
+            matches
+                .get_one::<u32>("cycle-to-die")
+                .copied()
+                .unwrap_or(defaults.cycle_to_die),
+        )
+        .with_cycle_delta(
+            matches
+                .get_one::<u32>("cycle-delta")
+                .copied()
+                .unwrap_or(defaults.cycle_delta),
+        )
+        .with_nbr_live(
+            matches
+                .get_one::<u32>("nbr-live")
+                .copied()
+                .unwrap_or(defaults.nbr_live),
+        )
+        .with_live_reset(matches.get_one::<String>("live-reset").unwrap().parse()?)
+        .with_max_champions(
+            matches
+                .get_one::<usize>("max-champions")
+                .copied()
+                .unwrap_or(defaults.max_champions),
+        );
+    rules.validate()?;
+
+    // Create game configuration
+    let defaults = GameConfig::default();
+    let mut config = defaults
+        .with_max_cycles(max_cycles)
+        // The zaz dump is printed once, not by the engine every N cycles
+        .with_dump_cycles(if dump_format == DumpFormat::Zaz {
+            0
+        } else {
+            dump_cycles
+        })
+        .with_dump_format(dump_format)
+        .with_speed(speed.unwrap_or(1))
+        .with_verbose(verbose)
+        .with_start_paused(start_paused)
+        .with_rules(rules)
+        .with_seed(seed)
+        .with_track_coverage(track_coverage)
+        .with_tie_breakers(tie_breakers)
+        .with_checkpoint_interval(
+            matches
+                .get_one::<u32>("checkpoint-interval")
+                .copied()
+                .unwrap_or(defaults.checkpoint_interval),
+        )
+        .with_checkpoint_capacity(
+            matches
+                .get_one::<usize>("checkpoints")
+                .copied()
+                .unwrap_or(defaults.checkpoint_capacity),
+        )
+        .with_skip_invalid(matches.get_flag("skip-invalid"))
+        .with_placement(matches.get_one::<String>("placement").unwrap().parse()?);
+
+    // Play the matchup many times and report the win rates instead of one battle
+    if let Some(&repeats) = matches.get_one::<u32>("repeat") {
+        // Under spread placement every seed would replay the same battle
+        if matches.value_source("placement") != Some(ValueSource::CommandLine) {
+            config.placement = Placement::Random;
+        }
+        let summary = montecarlo::run(&config, repeats, &load)?;
+        if !matches.get_flag("summary") {
+            for (i, battle) in summary.battles.iter().enumerate() {
+                println!("Battle {}, {}", i + 1, battle);
+            }
+            println!();
+        }
+        println!("=== Monte Carlo Summary ===");
+        println!("Placement: {}, first seed: {}", config.placement, config.seed);
+        println!("{}", summary);
+        return Ok(());
+    }
+
+    // Play drawn battles again with new seeds, then report the deciding one
+    let rematches = matches
+        .get_one::<u32>("rematch-on-draw")
+        .map(|&limit| rematch::play_until_decisive(&config, limit, &load))
+        .transpose()?;
+    if let Some(series) = &rematches {
+        config.seed = series.deciding_match().seed;
+    }
+
+    // Create and configure game engine
+    let mut engine = GameEngine::new(config);
+    engine.set_dump_file(matches.get_one::<PathBuf>("dump-file").cloned());
+    for breakpoint in breakpoints {
+        engine.add_breakpoint(breakpoint);
+    }
+    if let Some(target) = matches.get_one::<String>("emit-events") {
+        #[cfg(feature = "event-protocol")]
+        {
+            engine.emit_events(target.parse::<EventTarget>()?.open()?)?;
+            engine.redact_events(matches.get_flag("redact"));
+        }
+        #[cfg(not(feature = "event-protocol"))]
+        anyhow::bail!(
+            "--emit-events {} needs the event-protocol feature, which this build does not include",
+            target
+        );
+    }
+    if json {
+        engine.emit_json(Box::new(std::io::stdout()));
+    }
+    if let Some(path) = matches.get_one::<PathBuf>("record") {
+        #[cfg(feature = "event-protocol")]
+        {
+            let file = std::fs::File::create(path)?;
+            engine.record(Box::new(std::io::BufWriter::new(file)))?;
+        }
+        #[cfg(not(feature = "event-protocol"))]
+        anyhow::bail!(
+            "--record {} needs the event-protocol feature, which this build does not include",
+            path.display()
+        );
+    }
+    // Writes are counted from the first cycle, for the heat in the image
+    let image = matches.get_one::<PathBuf>("export-image").map(|path| {
+        let heat = WriteHeat::new();
+        engine.subscribe(Box::new(heat.clone()));
+        (path.clone(), heat)
+    });
+
+    // Load champions
+    info!("Loading {} champions...", champion_files.len());
+    load(&mut engine)?;
+
+    // Loading resets the scheduler, so the trace starts afterwards
+    let trace = matches.get_one::<PathBuf>("trace");
+    if let Some(path) = trace {
+        let filter = TraceFilter {
+            champions: matches
+                .get_many::<u8>("trace-champion")
+                .map(|ids| ids.copied().collect())
+                .unwrap_or_default(),
+            processes: matches
+                .get_many::<u32>("trace-process")
+                .map(|ids| ids.copied().collect())
+                .unwrap_or_default(),
+        };
+        let format = matches.get_one::<String>("trace-format").unwrap().parse()?;
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        engine.trace_execution(ExecutionTrace::new(Box::new(file), filter, format)?);
+    }
+
+    // Text output is paced only when a speed was asked for
+    if !visual {
+        engine.set_throttle(speed.map(Throttle::for_speed));
+    }
+
+    // Run the battle
+    if visual {
+        engine.start()?;
+        corewar::ui::app::run_terminal_ui_with_vm(&mut engine, theme)?;
+    } else if let Some(path) = cast {
+        engine.start()?;
+        let interval = *matches.get_one::<u32>("cast-interval").unwrap();
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let frames = corewar::ui::cast::record(&mut engine, theme, interval, file)?;
+        println!("Recorded {} frames to {}", frames, path.display());
+    } else if dump_format == DumpFormat::Zaz {
+        run_zaz_dump(&mut engine, dump_cycles)?;
+    } else if json {
+        engine.run_to_completion()?;
+        engine.finish_json()?;
+    } else {
+        let outputs = BattleOutputs {
+            coverage: show_coverage,
+            bundle: matches.get_one::<PathBuf>("bundle").cloned(),
+            heat,
+            rematches,
+            image,
+        };
+        run_text_mode(&mut engine, &assertions, &outputs)?;
+    }
+
+    if let Some(path) = trace {
+        let traced = engine.finish_execution_trace()?;
+        info!("Traced {} instructions to {}", traced, path.display());
+    }
+
+    #[cfg(feature = "event-protocol")]
+    {
+        engine.finish_events()?;
+        engine.finish_recording()?;
+    }
+
+    Ok(())
+}
+
+/// Replay a recorded battle in text mode or the terminal visualization
+#[cfg(feature = "event-protocol")]
+fn replay_battle(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let path = matches.get_one::<PathBuf>("file").unwrap();
+    let recording = Recording::read(path)?;
+    info!(
+        "Replaying {} ({} cycles)",
+        path.display(),
+        recording.final_cycle()
+    );
+    let theme = load_theme(matches)?;
+    let mut engine = GameEngine::replay(recording)?;
+
+    if matches.get_flag("visual") {
+        engine.start()?;
+        corewar::ui::app::run_terminal_ui_with_vm(&mut engine, theme)?;
+    } else {
+        let outputs = BattleOutputs {
+            coverage: false,
+            bundle: None,
+            heat: None,
+            rematches: None,
+            image: None,
+        };
+        run_text_mode(&mut engine, &[], &outputs)?;
+    }
+
+    Ok(())
+}
+
+/// Replay a recorded battle in text mode or the terminal visualization
+#[cfg(not(feature = "event-protocol"))]
+fn replay_battle(_matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    anyhow::bail!("This build does not include the event-protocol feature")
+}
+
+/// Load the theme given with --theme, or the default theme
+fn load_theme(matches: &clap::ArgMatches) -> anyhow::Result<Theme> {
+    let theme = matches
+        .get_one::<String>("theme")
+        .map(|theme| Theme::load(theme))
+        .transpose()?;
+    Ok(theme.unwrap_or_default())
+}
+
+/// Match each value of a per-champion option to the champion file after it
+///
+/// Like the classic corewar CLI, `-n 2 a.cor -a 100 b.cor` numbers `a.cor`
+/// and places `b.cor`.
+///
+/// # Returns
+/// The value for each champion file, in order, or an error if a value has
+/// no file after it or two values precede the same file
+fn next_champion_values<T: Clone + Send + Sync + 'static>(
+    matches: &clap::ArgMatches,
+    id: &str,
+    flag: &str,
+) -> anyhow::Result<Vec<Option<T>>> {
+    let champion_indices: Vec<usize> = matches.indices_of("champions").unwrap().collect();
+    let mut values = vec![None; champion_indices.len()];
+
+    let (Some(option_values), Some(option_indices)) =
+        (matches.get_many::<T>(id), matches.indices_of(id))
+    else {
+        return Ok(values);
+    };
+    for (value, index) in option_values.zip(option_indices) {
+        let Some(position) = champion_indices.iter().position(|&i| i > index) else {
+            anyhow::bail!("{} must be followed by a champion file", flag);
+        };
+        if values[position].is_some() {
+            anyhow::bail!("{} is given twice for champion {}", flag, position + 1);
+        }
+        values[position] = Some(value.clone());
+    }
+    Ok(values)
+}
+
+/// Parse a decimal or `0x`-prefixed hexadecimal load address
+fn parse_address(value: &str) -> Result<usize, String> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| format!("'{}' is not a decimal or 0x hex address", value))
+}
+
+/// Split a `FILE:ALIAS` champion argument into the file and its display name
+///
+/// An argument naming an existing file is never split, so paths containing
+/// a colon still load.
+fn split_champion_alias(arg: &str) -> (PathBuf, Option<String>) {
+    if !Path::new(arg).exists()
+        && let Some((file, alias)) = arg.rsplit_once(':')
+        && !file.is_empty()
+        && !alias.is_empty()
+        && !alias.contains(['/', '\\'])
+    {
+        return (PathBuf::from(file), Some(alias.to_string()));
+    }
+    (PathBuf::from(arg), None)
+}
+
+/// Optional results printed or written after a text-mode battle
+struct BattleOutputs {
+    /// Print the champion coverage report
+    coverage: bool,
+    /// Write a results bundle to this file
+    bundle: Option<PathBuf>,
+    /// Add execution heat to this file, in this format
+    heat: Option<(PathBuf, HeatFormat)>,
+    /// Drawn battles replayed before the reported one
+    rematches: Option<RematchSeries>,
+    /// Save the final core as a PNG image to this file, heated by these writes
+    image: Option<(PathBuf, WriteHeat)>,
+}
+
+/// Run to a cycle and dump the core as the 42/zaz reference VM's `-dump` does
+///
+/// As in the reference, a battle that is won before the cycle prints the
+/// winner instead of the dump.
+fn run_zaz_dump(engine: &mut GameEngine, cycle: u32) -> anyhow::Result<()> {
+    engine.start()?;
+    let outcome = engine.run_cycles(cycle)?;
+
+    if !outcome.is_running()
+        && let Some(winner_id) = engine.state().winner
+        && let Some(winner) = engine.champions().iter().find(|c| c.id == winner_id)
+    {
+        print!("{}", engine.zaz_introduction());
+        println!("Contestant {}, \"{}\", has won !", winner.id, winner.name);
+    } else {
+        engine.dump_memory()?;
+    }
+    Ok(())
+}
+
+/// Run battle in text mode
+fn run_text_mode(
+    engine: &mut GameEngine,
+    assertions: &[Assertion],
+    outputs: &BattleOutputs,
+) -> anyhow::Result<()> {
+    info!("Starting Core War battle...");
+
+    // Show initial state
+    engine.dump_memory()?;
+
+    // Run to completion, checking assertions along the way if any were given
+    let (winner, outcomes) = if assertions.is_empty() {
+        (engine.run_to_completion()?, Vec::new())
+    } else {
+        let outcomes = assertion::run_with_assertions(engine, assertions)?;
+        (engine.get_stats().winner, outcomes)
+    };
+
+    // Show final results
+    let stats = engine.get_stats();
+    println!("\n=== Battle Results ===");
+    println!("Total cycles: {}", stats.cycle);
+    println!("Elapsed time: {:.2}s", stats.elapsed_time.as_secs_f64());
     println!("Cycles per second: {:.1}", stats.cycles_per_second);
+    println!("{}", stats.resources);
+    println!("Seed: {}", engine.config().seed);
+    println!("State digest: {:016x}", engine.state_digest());
 
     match winner {
         Some(winner_id) => {
@@ -216,9 +2067,12 @@ fn run_text_mode(engine: &mut GameEngine) -> anyhow::Result<()> {
                 .champions()
                 .iter()
                 .find(|c| c.id == winner_id)
-                .map(|c| c.name.as_str())
+                .map(|c| c.display_name())
                 .unwrap_or("Unknown");
             println!("Winner: Champion {} ({})", winner_id, winner_name);
+            if let Some(tie_break) = stats.tie_break {
+                println!("Draw broken by tie-breaker: {}", tie_break.decided_by);
+            }
         }
         None => {
             println!("Result: Draw (no winner)");
@@ -228,6 +2082,75 @@ fn run_text_mode(engine: &mut GameEngine) -> anyhow::Result<()> {
     // Final memory dump
     engine.dump_memory()?;
 
+    if let Some(series) = &outputs.rematches {
+        println!("=== Rematches ===");
+        println!("{}", series);
+        println!();
+    }
+
+    if !engine.skipped_champions().is_empty() {
+        println!("=== Skipped Champions ===");
+        for skipped in engine.skipped_champions() {
+            println!("{}: {}", skipped.path.display(), skipped.reason);
+        }
+        println!();
+    }
+
+    if outputs.coverage
+        && let Some(coverage) = engine.coverage()
+    {
+        println!("=== Champion Coverage ===");
+        for champion in &coverage {
+            println!("{}", champion);
+        }
+        println!();
+    }
+
+    if let Some((path, format)) = &outputs.heat
+        && let Some(coverage) = engine.coverage()
+    {
+        // Earlier battles' counts are kept, so a match set can share one file
+        let mut heat = if path.exists() {
+            HeatMap::read(path, *format)?
+        } else {
+            HeatMap::default()
+        };
+        heat.merge(&HeatMap::from_coverage(&coverage, engine.champions()));
+        heat.write(path, *format)?;
+        println!("Execution heat ({}) written to {}", format, path.display());
+        println!();
+    }
+
+    if let Some((path, heat)) = &outputs.image {
+        let pcs = engine.processes().iter().map(|p| p.pc).collect();
+        let image = CoreImage::render(engine.memory(), &pcs, &heat.counts(), &Theme::default());
+        image.write(path)?;
+        println!("Core image written to {}", path.display());
+        println!();
+    }
+
+    if let Some(path) = &outputs.bundle {
+        ResultsBundle::record(engine)?.write(path)?;
+        println!("Results bundle written to {}", path.display());
+        println!();
+    }
+
+    if !outcomes.is_empty() {
+        println!("=== Assertions ===");
+        for outcome in &outcomes {
+            println!("{}", outcome);
+        }
+
+        let failed = outcomes.iter().filter(|o| !o.passed).count();
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "{} of {} assertions failed",
+                failed,
+                outcomes.len()
+            ));
+        }
+    }
+
     Ok(())
 }
 
@@ -236,41 +2159,951 @@ fn assemble_file(matches: &clap::ArgMatches) -> anyhow::Result<()> {
     let input_file = matches.get_one::<String>("input").unwrap();
     let output_file = matches.get_one::<String>("output");
     let verbose = matches.get_flag("verbose");
+    let deny_warnings = matches.get_flag("deny-warnings");
 
-    let assembler = Assembler::new(verbose);
+    let assembler = Assembler::new(verbose)
+        .with_deny_warnings(deny_warnings)
+        .with_build_date(metadata::build_date());
 
     info!("Assembling {}...", input_file);
 
-    let bytecode = assembler.assemble_file(input_file, output_file)?;
+    // `-` reads the source from stdin, with includes relative to the current directory
+    let bytecode = if input_file == "-" {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source)?;
+        assembler.assemble_string(&source)?
+    } else {
+        assembler.assemble_path(input_file)?
+    };
+    for warning in assembler.warnings() {
+        warn!("{}", warning);
+    }
+    if let Some(listing_file) = matches.get_one::<String>("listing")
+        && let Some(listing) = assembler.listing()
+    {
+        std::fs::write(listing_file, listing.to_string())?;
+        info!("Wrote listing to {}", listing_file);
+    }
 
+    // Source from stdin goes to stdout unless an output file is given
     let output_name = match output_file {
         Some(output) => output.to_string(),
+        None if input_file == "-" => "-".to_string(),
         None => {
             let mut path = PathBuf::from(input_file);
             path.set_extension("cor");
             path.to_string_lossy().to_string()
         }
     };
+    if output_name == "-" {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&bytecode)?;
+        stdout.flush()?;
+    } else {
+        std::fs::write(&output_name, &bytecode)?;
+    }
 
     info!("Generated {} ({} bytes)", output_name, bytecode.len());
 
     Ok(())
 }
 
+/// Disassemble a .cor file into Redcode or a load file
+fn disassemble_file(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let input_file = matches.get_one::<String>("input").unwrap();
+    let bytes = std::fs::read(input_file)?;
+    let source = if matches.get_flag("load-file") {
+        loadfile::export(&bytes)?
+    } else {
+        disassembler::disassemble(&bytes)?
+    };
+
+    match matches.get_one::<String>("output") {
+        Some(output_file) => {
+            std::fs::write(output_file, source)?;
+            info!("Wrote {}", output_file);
+        }
+        None => print!("{}", source),
+    }
+
+    Ok(())
+}
+
+/// Format a Redcode source file, printing it, rewriting it or checking it
+fn format_file(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let input_file = matches.get_one::<String>("input").unwrap();
+
+    // `-` reads the source from stdin, with includes relative to the current directory
+    let (source, path) = if input_file == "-" {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source)?;
+        (source, None)
+    } else {
+        let path = Path::new(input_file);
+        (std::fs::read_to_string(path)?, Some(path))
+    };
+    let formatted = formatter::format_source(&source, path)?;
+
+    if matches.get_flag("check") {
+        if formatted != source {
+            anyhow::bail!("{} is not formatted", input_file);
+        }
+    } else if matches.get_flag("write") {
+        let Some(path) = path else {
+            anyhow::bail!("--write needs a file to rewrite, not stdin");
+        };
+        if formatted != source {
+            std::fs::write(path, formatted)?;
+            info!("Formatted {}", input_file);
+        }
+    } else {
+        print!("{}", formatted);
+    }
+
+    Ok(())
+}
+
+/// Lint a Redcode source file, printing what it finds
+fn lint_file(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let input_file = matches.get_one::<String>("input").unwrap();
+
+    // `-` reads the source from stdin, with includes relative to the current directory
+    let (source, path) = if input_file == "-" {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source)?;
+        (source, None)
+    } else {
+        let path = Path::new(input_file);
+        (std::fs::read_to_string(path)?, Some(path))
+    };
+    let findings = lint::lint_source(&source, path)?;
+
+    for finding in &findings {
+        match finding.severity {
+            Severity::Error => println!("error: {}\n", finding),
+            Severity::Warning => println!("{}\n", finding),
+        }
+    }
+    let errors = findings
+        .iter()
+        .filter(|finding| finding.severity == Severity::Error)
+        .count();
+    let warnings = findings.len() - errors;
+    if errors > 0 || (warnings > 0 && matches.get_flag("deny-warnings")) {
+        anyhow::bail!("{}: {} errors, {} warnings", input_file, errors, warnings);
+    }
+    info!("{}: {} warnings", input_file, warnings);
+
+    Ok(())
+}
+
+/// Validate .cor files, returning the exit code of the first failure
+fn validate_files(matches: &clap::ArgMatches) -> anyhow::Result<i32> {
+    let mut exit_code = 0;
+    for path in matches.get_many::<PathBuf>("files").unwrap() {
+        let bytes = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let problems = validate::validate(&bytes);
+        for problem in &problems {
+            println!("{}: {}", path.display(), problem);
+        }
+        match problems.first() {
+            Some(problem) if exit_code == 0 => exit_code = problem.failure.exit_code(),
+            Some(_) => {}
+            None => println!("{}: ok", path.display()),
+        }
+    }
+    Ok(exit_code)
+}
+
+/// Print built-in reference text for an instruction or concept
+fn explain_topic(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let topic = matches.get_one::<String>("topic");
+
+    if matches.get_flag("json") {
+        let json = match topic {
+            None => serde_json::to_string_pretty(&instruction::spec())?,
+            Some(topic) => {
+                let instruction = Instruction::ALL
+                    .into_iter()
+                    .find(|i| i.name().eq_ignore_ascii_case(topic.trim()))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "'{}' is not an instruction; --json describes instructions only",
+                            topic
+                        )
+                    })?;
+                serde_json::to_string_pretty(&InstructionSpec::of(instruction))?
+            }
+        };
+        println!("{}", json);
+        return Ok(());
+    }
+
+    let Some(topic) = topic else {
+        println!("Available topics:");
+        for topic in reference::topics() {
+            println!("  {}", topic);
+        }
+        return Ok(());
+    };
+
+    match reference::explain(topic) {
+        Some(text) => {
+            print!("{}", text);
+            Ok(())
+        }
+        None => Err(anyhow::anyhow!(
+            "Unknown topic '{}'. Run `corewar explain` to list topics",
+            topic
+        )),
+    }
+}
+
+/// Run the scheduler stress test
+fn run_stress(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let config = StressConfig {
+        processes: *matches.get_one::<usize>("processes").unwrap(),
+        cycles: *matches.get_one::<u32>("cycles").unwrap(),
+        champions: *matches.get_one::<usize>("champions").unwrap(),
+        max_processes: *matches.get_one::<usize>("max-processes").unwrap(),
+        seed: matches
+            .get_one::<u64>("seed")
+            .copied()
+            .unwrap_or(corewar::vm::rng::DEFAULT_SEED),
+        ..Default::default()
+    };
+
+    let report = stress::run(&config)?;
+    println!("=== Stress Test ===");
+    println!("{}", report);
+    Ok(())
+}
+
+/// Replay a results bundle and check it against its recorded results
+fn verify_bundle(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let bundle_file = matches.get_one::<String>("file").unwrap();
+
+    let bundle = ResultsBundle::read(bundle_file)?;
+    bundle.verify()?;
+
+    println!("Bundle verified: {}", bundle_file);
+    for champion in &bundle.champions {
+        println!(
+            "Champion {}: {} sha256:{}",
+            champion.id,
+            champion.alias.as_deref().unwrap_or(&champion.name),
+            champion.sha256
+        );
+    }
+    println!("Seed: {}", bundle.config.seed);
+    println!("Result: {}", bundle.report);
+
+    Ok(())
+}
+
+/// Print champion or matchup statistics over stored battles
+fn show_stats(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let (query, sub_matches) = matches.subcommand().unwrap();
+    let paths: Vec<PathBuf> = sub_matches
+        .get_many::<PathBuf>("bundles")
+        .unwrap()
+        .cloned()
+        .collect();
+    let bundles = stats::read_bundles(&paths)?;
+
+    match query {
+        "champion" => {
+            let champion = sub_matches.get_one::<String>("champion").unwrap();
+            println!("{}", ChampionStats::collect(&bundles, champion)?);
+        }
+        "matchup" => {
+            let first = sub_matches.get_one::<String>("first").unwrap();
+            let second = sub_matches.get_one::<String>("second").unwrap();
+            println!("{}", MatchupStats::collect(&bundles, first, second)?);
+        }
+        _ => unreachable!("clap only accepts the declared stats queries"),
+    }
+    println!("Searched {} stored battles", bundles.len());
+
+    Ok(())
+}
+
+/// Profile a champion from its battles against a suite of opponents
+fn analyze_champion(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let champion = matches.get_one::<PathBuf>("champion").unwrap();
+    let paths: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("suite")
+        .unwrap()
+        .cloned()
+        .collect();
+    let suite = loader::champion_files(&paths)?;
+    let config = GameConfig::default()
+        .with_max_cycles(matches.get_one::<u32>("cycles").copied().unwrap_or(0))
+        .with_seed(
+            matches
+                .get_one::<u64>("seed")
+                .copied()
+                .unwrap_or(corewar::vm::rng::DEFAULT_SEED),
+        );
+
+    let profile = profile::profile(champion, &suite, config)?;
+    println!("=== Dynamic Profile ===");
+    println!("{}", profile);
+    println!("Played against {} opponents", suite.len());
+    Ok(())
+}
+
+/// Run the VM headless for a fixed number of cycles and report its speed
+fn run_bench(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let files: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("champions")
+        .map(|files| files.cloned().collect())
+        .unwrap_or_default();
+    let cycles = matches
+        .get_one::<u32>("cycles")
+        .copied()
+        .unwrap_or(bench::DEFAULT_BENCH_CYCLES);
+    let config = GameConfig::default().with_seed(
+        matches
+            .get_one::<u64>("seed")
+            .copied()
+            .unwrap_or(corewar::vm::rng::DEFAULT_SEED),
+    );
+
+    let report = bench::run(&files, cycles, config)?;
+    println!("=== Benchmark ===");
+    println!("{}", report);
+    Ok(())
+}
+
+/// Play a round-robin tournament and print or export the standings
+fn run_tournament(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let paths: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("champions")
+        .unwrap()
+        .cloned()
+        .collect();
+    let files = loader::champion_files(&paths)?;
+    let melee_sizes: Vec<usize> = matches
+        .get_many::<usize>("melee")
+        .map(|sizes| sizes.copied().collect())
+        .unwrap_or_default();
+    let format: StandingsFormat = matches.get_one::<String>("format").unwrap().parse()?;
+    let config = GameConfig::default()
+        .with_max_cycles(matches.get_one::<u32>("cycles").copied().unwrap_or(0))
+        .with_seed(
+            matches
+                .get_one::<u64>("seed")
+                .copied()
+                .unwrap_or(corewar::vm::rng::DEFAULT_SEED),
+        );
+
+    let rounds = *matches.get_one::<u32>("rounds").unwrap();
+    let tournament = Tournament::run(&files, &melee_sizes, rounds, config)?;
+    let rendered = tournament.render(format)?;
+    match matches.get_one::<PathBuf>("output") {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            println!("Standings ({}) written to {}", format, path.display());
+        }
+        None => println!("{}", rendered.trim_end()),
+    }
+    Ok(())
+}
+
+/// Stream battles to WebSocket viewers, or run the HTTP battle service
+fn run_serve(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    if matches.get_flag("http") {
+        return run_battle_service(matches);
+    }
+    let paths: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("champions")
+        .unwrap()
+        .cloned()
+        .collect();
+    let files = loader::champion_files(&paths)?;
+    let config = GameConfig::default()
+        .with_max_cycles(matches.get_one::<u32>("cycles").copied().unwrap_or(0))
+        .with_seed(
+            matches
+                .get_one::<u64>("seed")
+                .copied()
+                .unwrap_or(corewar::vm::rng::DEFAULT_SEED),
+        );
+
+    let mut engine = GameEngine::new(config);
+    engine.load_champions(&files, None)?;
+    let speed = *matches.get_one::<u32>("speed").unwrap();
+    engine.set_throttle(Some(Throttle::new(speed)));
+
+    let mut server = BattleServer::bind(matches.get_one::<String>("listen").unwrap().as_str())?;
+    let wait = !matches.get_flag("no-wait");
+    println!("Serving battles on ws://{}", server.local_addr()?);
+    if wait {
+        println!("Waiting for a viewer to connect...");
+    }
+    let rounds = *matches.get_one::<u32>("rounds").unwrap();
+    server.run(&mut engine, rounds, wait)?;
+    Ok(())
+}
+
+/// Run battles for HTTP clients, starting with the given warriors
+fn run_battle_service(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let defaults = ServiceLimits::default();
+    let limits = ServiceLimits {
+        max_cycles: matches
+            .get_one::<u32>("cycles")
+            .copied()
+            .filter(|&cycles| cycles > 0)
+            .unwrap_or(defaults.max_cycles),
+        max_running: matches
+            .get_one::<u32>("max-running")
+            .map_or(defaults.max_running, |&n| n as usize),
+        ..defaults
+    };
+    let service = BattleService::new(limits);
+    let paths: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("champions")
+        .map(|paths| paths.cloned().collect())
+        .unwrap_or_default();
+    for path in loader::champion_files(&paths)? {
+        service.add_warrior(None, &std::fs::read(&path)?)?;
+    }
+
+    let listener = std::net::TcpListener::bind(matches.get_one::<String>("listen").unwrap())?;
+    println!(
+        "Serving the battle API on http://{}",
+        listener.local_addr()?
+    );
+    println!(
+        "Battles run for at most {} cycles, {} at a time",
+        limits.max_cycles, limits.max_running
+    );
+    service.serve(listener)?;
+    Ok(())
+}
+
+/// Create, challenge or show a king-of-the-hill file
+fn run_hill(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let (action, sub_matches) = matches.subcommand().unwrap();
+    let path = sub_matches.get_one::<PathBuf>("hill").unwrap();
+    let server = path
+        .to_str()
+        .filter(|url| url.starts_with("http://"))
+        .map(HillClient::new);
+
+    match (action, server) {
+        ("init", _) => {
+            if path.exists() && !sub_matches.get_flag("force") {
+                anyhow::bail!("{} already exists; pass --force to replace it", path.display());
+            }
+            let config = GameConfig::default()
+                .with_max_cycles(sub_matches.get_one::<u32>("cycles").copied().unwrap_or(0))
+                .with_seed(
+                    sub_matches
+                        .get_one::<u64>("seed")
+                        .copied()
+                        .unwrap_or(corewar::vm::rng::DEFAULT_SEED),
+                );
+            let hill = Hill::new(
+                sub_matches
+                    .get_one::<usize>("size")
+                    .copied()
+                    .unwrap_or(DEFAULT_HILL_SIZE),
+                sub_matches
+                    .get_one::<u32>("rounds")
+                    .copied()
+                    .unwrap_or(DEFAULT_HILL_ROUNDS),
+                config,
+            )?;
+            hill.write(path)?;
+            println!("Empty hill of {} written to {}", hill.size, path.display());
+        }
+        ("submit", Some(client)) => {
+            let mut hill = None;
+            for file in sub_matches.get_many::<PathBuf>("champions").unwrap() {
+                let submitted = client.submit(None, &std::fs::read(file)?)?;
+                println!("{}", submitted.submission);
+                println!();
+                hill = Some(submitted.hill);
+            }
+            if let Some(hill) = hill {
+                println!("{}", hill);
+            }
+        }
+        ("submit", None) => {
+            let mut hill = Hill::read(path)?;
+
+            let loader = ChampionLoader::new(false).with_rules(hill.config.rules);
+            for file in sub_matches.get_many::<PathBuf>("champions").unwrap() {
+                let champion = loader.load_champion(file, 1, Some(0))?;
+                let submission = hill.submit(&champion.name, &champion.code)?;
+                // Save after every challenger so an error later keeps earlier results
+                hill.write(path)?;
+                println!("{}", submission);
+                println!();
+            }
+            println!("{}", hill);
+        }
+        ("show", Some(client)) => println!("{}", client.standings()?),
+        ("show", None) => println!("{}", Hill::read(path)?),
+        ("serve", _) => {
+            let mut server = HillServer::open(path)?;
+            let listener =
+                std::net::TcpListener::bind(sub_matches.get_one::<String>("listen").unwrap())?;
+            println!(
+                "Serving {} ({} members) on http://{}",
+                path.display(),
+                server.hill().members.len(),
+                listener.local_addr()?
+            );
+            server.serve(listener)?;
+        }
+        ("ladder", _) => {
+            let mut ladder = Ladder::new(sub_matches.get_one::<PathBuf>("champions").unwrap());
+            if let Some(&rematches) = sub_matches.get_one::<usize>("rematches") {
+                ladder = ladder.with_rematches(rematches);
+            }
+            let mut server = HillServer::open(path)?;
+            let server = Mutex::new(&mut server);
+            if sub_matches.get_flag("once") {
+                println!("{}", ladder.pass(&server)?);
+                println!();
+                println!("{}", server.lock().unwrap().hill());
+                return Ok(());
+            }
+
+            let interval = sub_matches
+                .get_one::<u64>("interval")
+                .map_or(DEFAULT_INTERVAL, |&secs| Duration::from_secs(secs));
+            let listener = sub_matches
+                .get_one::<String>("listen")
+                .map(std::net::TcpListener::bind)
+                .transpose()?;
+            println!(
+                "Running the ladder on {} every {}s",
+                path.display(),
+                interval.as_secs()
+            );
+            if let Some(listener) = &listener {
+                println!("Serving the standings on http://{}", listener.local_addr()?);
+            }
+            std::thread::scope(|scope| {
+                if let Some(listener) = listener {
+                    scope.spawn(|| HillServer::serve_shared(&server, listener));
+                }
+                ladder.run(&server, interval)
+            })
+        }
+        _ => unreachable!("clap only accepts the declared hill actions"),
+    }
+    Ok(())
+}
+
+/// Evolve warriors against benchmarks and write out the best
+fn run_evolve(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let paths: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("benchmarks")
+        .unwrap()
+        .cloned()
+        .collect();
+    let loader = ChampionLoader::new(false);
+    let benchmarks = loader::champion_files(&paths)?
+        .iter()
+        .map(|file| {
+            let champion = loader.load_champion(file, 1, None)?;
+            Ok((champion.display_name().to_string(), champion.code))
+        })
+        .collect::<corewar::Result<Vec<_>>>()?;
+
+    let defaults = EvolveConfig::default();
+    let config = EvolveConfig {
+        population: *matches.get_one::<usize>("population").unwrap(),
+        elite: *matches.get_one::<usize>("elite").unwrap(),
+        rounds: *matches.get_one::<u32>("rounds").unwrap(),
+        max_length: *matches.get_one::<usize>("max-length").unwrap(),
+        initial_length: defaults
+            .initial_length
+            .min(*matches.get_one::<usize>("max-length").unwrap()),
+        crossover_rate: *matches.get_one::<f64>("crossover-rate").unwrap(),
+        mutation_rate: *matches.get_one::<f64>("mutation-rate").unwrap(),
+        seed: matches
+            .get_one::<u64>("seed")
+            .copied()
+            .unwrap_or(defaults.seed),
+        game: defaults.game.with_max_cycles(
+            matches
+                .get_one::<u32>("cycles")
+                .copied()
+                .unwrap_or(defaults.game.max_cycles),
+        ),
+    };
+
+    println!(
+        "Evolving {} warriors against {} benchmarks",
+        config.population,
+        benchmarks.len()
+    );
+    let mut evolution = Evolution::new(config, benchmarks)?;
+    println!("{}", evolution);
+    for _ in 0..*matches.get_one::<u32>("generations").unwrap() {
+        evolution.advance()?;
+        println!("{}", evolution);
+    }
+
+    let output = matches.get_one::<PathBuf>("output").unwrap();
+    std::fs::create_dir_all(output)?;
+    let keep = *matches.get_one::<usize>("keep").unwrap();
+    for (rank, scored) in evolution.population().iter().take(keep).enumerate() {
+        let path = output.join(format!("evolved-{}.s", rank + 1));
+        let comment = format!(
+            "Generation {}, scoring {:.1}",
+            evolution.generation(),
+            scored.score
+        );
+        let source = scored
+            .genome
+            .source(&format!("Evolved {}", rank + 1), &comment);
+        std::fs::write(&path, source)?;
+        println!(
+            "{} ({:.1}) written to {}",
+            rank + 1,
+            scored.score,
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// List, extract or battle the bundled example warriors
+fn run_examples(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let (action, sub_matches) = matches.subcommand().unwrap();
+    let chosen = || -> corewar::Result<Vec<&'static Example>> {
+        match sub_matches.get_many::<String>("names") {
+            Some(names) => names.map(|name| examples::find(name)).collect(),
+            None => Ok(examples::EXAMPLES.iter().collect()),
+        }
+    };
+
+    match action {
+        "list" => {
+            for example in &examples::EXAMPLES {
+                println!("{:<12} {}", example.name, example.description);
+            }
+        }
+        "extract" => {
+            let output = sub_matches.get_one::<PathBuf>("output").unwrap();
+            let force = sub_matches.get_flag("force");
+            std::fs::create_dir_all(output)?;
+            for example in chosen()? {
+                let mut files = vec![(
+                    output.join(example.file_name()),
+                    example.source.as_bytes().to_vec(),
+                )];
+                if sub_matches.get_flag("cor") {
+                    files.push((
+                        output.join(format!("{}.cor", example.name)),
+                        example.assemble()?,
+                    ));
+                }
+                for (path, contents) in files {
+                    if path.exists() && !force {
+                        anyhow::bail!(
+                            "{} already exists; pass --force to replace it",
+                            path.display()
+                        );
+                    }
+                    std::fs::write(&path, contents)?;
+                    println!("Wrote {}", path.display());
+                }
+            }
+        }
+        "run" => {
+            let mut builder = GameEngine::builder()
+                .max_cycles(sub_matches.get_one::<u32>("cycles").copied().unwrap_or(0))
+                .seed(
+                    sub_matches
+                        .get_one::<u64>("seed")
+                        .copied()
+                        .unwrap_or(corewar::vm::rng::DEFAULT_SEED),
+                );
+            for example in chosen()? {
+                builder = builder.champion_cor(example.assemble()?);
+            }
+            let mut engine = builder.build()?;
+            let winner = engine.run_to_completion()?;
+            println!("Battle over after {} cycles", engine.state().cycle);
+            match winner.and_then(|id| engine.champions().iter().find(|c| c.id == id)) {
+                Some(champion) => {
+                    println!(
+                        "Winner: Champion {} ({})",
+                        champion.id,
+                        champion.display_name()
+                    )
+                }
+                None => println!("Result: Draw (no winner)"),
+            }
+        }
+        _ => unreachable!("clap only accepts the declared examples actions"),
+    }
+    Ok(())
+}
+
+/// Create a warrior project and say how to use it
+fn create_project(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let dir = matches.get_one::<PathBuf>("project").unwrap();
+    for file in project::create(dir)? {
+        println!("Created {}", file.display());
+    }
+    println!();
+    println!("Edit the warrior, then fight it against the opponents with:");
+    println!("    cd {} && corewar fight", dir.display());
+    Ok(())
+}
+
+/// Fight a project's warrior or the warriors given, once or on every save
+fn run_fight(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let paths: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("paths")
+        .unwrap()
+        .cloned()
+        .collect();
+    let project = match paths.as_slice() {
+        [path] if path.is_dir() || path.extension().is_some_and(|ext| ext == "json") => {
+            Some(project::fight_file(path))
+        }
+        _ => None,
+    };
+    // Read again for every fight, as a project's fight file may change too
+    let setup = || -> anyhow::Result<FightConfig> {
+        let mut config = match &project {
+            Some(file) => FightConfig::open(file)?,
+            None => FightConfig::new(paths[0].clone(), paths[1..].to_vec()),
+        };
+        if let Some(&rounds) = matches.get_one::<u32>("rounds") {
+            config.rounds = rounds;
+        }
+        if let Some(&cycles) = matches.get_one::<u32>("cycles") {
+            config.max_cycles = cycles;
+        }
+        if let Some(&seed) = matches.get_one::<u64>("seed") {
+            config.seed = seed;
+        }
+        Ok(config)
+    };
+    let visual = matches.get_flag("visual");
+    let theme = load_theme(matches)?;
+
+    if !matches.get_flag("watch") {
+        let config = setup()?;
+        if visual {
+            corewar::ui::app::run_terminal_ui_with_vm(&mut fight_engine(&config)?, theme)?;
+        } else {
+            println!("{}", config.play()?);
+        }
+        return Ok(());
+    }
+
+    loop {
+        let config = setup();
+        let sources: Vec<PathBuf> = match &config {
+            Ok(config) => config.sources().map(Path::to_path_buf).collect(),
+            Err(_) => Vec::new(),
+        };
+        let mut watcher = SourceWatcher::new(project.iter().chain(&sources));
+        if visual {
+            match config.and_then(|config| fight_engine(&config)) {
+                Ok(mut engine) => {
+                    // The UI stops for a change, and the loop starts the new fight
+                    let changed = || !watcher.changed().is_empty();
+                    let restart = corewar::ui::app::run_terminal_ui_until(
+                        &mut engine,
+                        theme.clone(),
+                        changed,
+                    )?;
+                    if !restart {
+                        return Ok(());
+                    }
+                    continue;
+                }
+                Err(e) => eprintln!("Fight failed: {}", e),
+            }
+        } else {
+            match config.and_then(|config| Ok(config.play()?)) {
+                Ok(report) => println!("{}", report),
+                Err(e) => eprintln!("Fight failed: {}", e),
+            }
+        }
+
+        println!();
+        println!(
+            "Watching {} files for changes; press Ctrl-C to stop",
+            watcher.paths().count()
+        );
+        let changed: Vec<String> = watcher
+            .wait()
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        println!("Changed: {}", changed.join(", "));
+        println!();
+    }
+}
+
+/// Start a battle between every warrior in a fight
+fn fight_engine(config: &FightConfig) -> anyhow::Result<GameEngine> {
+    let mut builder = GameEngine::builder().config(config.game());
+    for (name, code) in config.champions()? {
+        builder = builder.champion_bytes(name, code);
+    }
+    Ok(builder.build()?)
+}
+
+/// Load a battle and hand it to the command-line debugger
+fn run_debugger(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let mut builder = GameEngine::builder()
+        .max_cycles(matches.get_one::<u32>("cycles").copied().unwrap_or(0))
+        .seed(
+            matches
+                .get_one::<u64>("seed")
+                .copied()
+                .unwrap_or(corewar::vm::rng::DEFAULT_SEED),
+        );
+    for path in matches.get_many::<PathBuf>("champions").unwrap() {
+        builder = builder.champion_file(path);
+    }
+    let mut debugger = Debugger::new(builder.build()?);
+    for spec in matches.get_many::<String>("break").into_iter().flatten() {
+        if let Reply::Continue(text) = debugger.execute(&format!("break {}", spec))? {
+            println!("{}", text);
+        }
+    }
+    let stdin = std::io::stdin();
+    debugger.run(stdin.lock(), std::io::stdout())?;
+    Ok(())
+}
+
+/// Serve the Debug Adapter Protocol to an editor
+fn run_dap(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let Some(address) = matches.get_one::<String>("listen") else {
+        // Standard output carries the protocol, so nothing else may print
+        let stdin = std::io::stdin();
+        DapServer::new().serve(stdin.lock(), std::io::stdout().lock())?;
+        return Ok(());
+    };
+
+    let listener = std::net::TcpListener::bind(address)?;
+    println!("Debug adapter listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let input = std::io::BufReader::new(stream.try_clone()?);
+        if let Err(e) = DapServer::new().serve(input, stream) {
+            warn!("Debug session failed: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Print every event of a binary event stream file
+#[cfg(feature = "event-protocol")]
+fn decode_events(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let file = matches.get_one::<String>("file").unwrap();
+    let reader = std::io::BufReader::new(std::fs::File::open(file)?);
+    let decoder = EventDecoder::new(reader)?;
+
+    let header = decoder.header();
+    println!(
+        "Event stream version {}, memory size {}",
+        header.version, header.memory_size
+    );
+    for stamped in decoder {
+        let stamped = stamped?;
+        println!("{:>8} {:?}", stamped.cycle, stamped.event);
+    }
+
+    Ok(())
+}
+
+/// Print every event of a binary event stream file
+#[cfg(not(feature = "event-protocol"))]
+fn decode_events(_matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    anyhow::bail!("This build does not include the event-protocol feature")
+}
+
 /// Show information about a champion file
 fn show_champion_info(matches: &clap::ArgMatches) -> anyhow::Result<()> {
-    let champion_file = matches.get_one::<String>("file").unwrap();
+    let files: Vec<&String> = matches.get_many::<String>("file").unwrap().collect();
+    let disasm = matches.get_flag("disasm");
+    let histogram = matches.get_flag("histogram");
+    let first_live = matches.get_flag("first-live");
 
     let loader = corewar::ChampionLoader::new(true);
-    let info = loader.get_champion_info(champion_file)?;
+    let mut champions = Vec::new();
+    for file in &files {
+        let champion = loader.load_champion(file, 1, None)?;
+        let summary = ChampionSummary::of(&champion);
+        champions.push((file.to_string(), champion, summary));
+    }
+
+    if let [(champion_file, champion, summary)] = champions.as_slice() {
+        let info = loader.get_champion_info(champion_file)?;
+        println!("Champion Information");
+        println!("===================");
+        println!("File: {}", champion_file);
+        println!("Name: {}", info.name);
+        println!("Comment: {}", info.comment);
+        let metadata = &champion.metadata;
+        if let Some(author) = &metadata.author {
+            println!("Author: {}", author);
+        }
+        if let Some(version) = &metadata.version {
+            println!("Version: {}", version);
+        }
+        if let Some(date) = &metadata.build_date {
+            println!("Built: {}", date);
+        }
+        println!("Code size: {} bytes", info.code_size);
+        println!("Magic: 0x{:08x}", info.magic);
+        if first_live {
+            match summary.first_live {
+                Some(cycle) => println!("First live: cycle {} (estimated)", cycle),
+                None => println!("First live: unknown without running the champion"),
+            }
+        }
+        if histogram {
+            println!();
+            println!(
+                "Instructions: {} ({} bytes of data)",
+                summary.instruction_count(),
+                summary.data_bytes
+            );
+            print!("{}", summary.histogram_text());
+        }
+        if disasm {
+            println!();
+            print!("{}", champion_disassembly(champion));
+        }
+        return Ok(());
+    }
 
-    println!("Champion Information");
-    println!("===================");
-    println!("File: {}", champion_file);
-    println!("Name: {}", info.name);
-    println!("Comment: {}", info.comment);
-    println!("Code size: {} bytes", info.code_size);
-    println!("Magic: 0x{:08x}", info.magic);
+    let summaries: Vec<(String, ChampionSummary)> = champions
+        .iter()
+        .map(|(file, _, summary)| (file.clone(), summary.clone()))
+        .collect();
+    print!("{}", inspect::comparison_table(&summaries, histogram));
+    if disasm {
+        for (file, champion, _) in &champions {
+            println!();
+            println!("; {}", file);
+            print!("{}", champion_disassembly(champion));
+        }
+    }
 
     Ok(())
 }
+
+/// Disassemble a loaded champion's code
+fn champion_disassembly(champion: &corewar::vm::Champion) -> String {
+    disassembler::disassemble_code(&champion.name, &champion.comment, &champion.code)
+}
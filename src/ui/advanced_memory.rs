@@ -2,15 +2,18 @@
 ///
 /// This module provides enhanced memory visualization including heat maps,
 /// particle effects for memory writes, process trails, and real-time statistics.
-
+use crate::ui::dense;
 use crate::ui::effects::{ParticleSystem, WaveAnimation, ColorCycle, AsciiArt};
-use crate::vm::{Memory, Process, Champion};
+use crate::ui::theme::Theme;
+use crate::ui::viewport::{Density, Viewport};
+use crate::vm::{Champion, GameEvent, Memory, Process};
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Widget};
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
 /// Advanced memory grid with visual effects
@@ -33,40 +36,84 @@ pub struct AdvancedMemoryGrid {
     battle_intensity: f32,
     /// Last update time
     last_update: Instant,
+    /// Part of the core shown, fitted to the grid each time it is drawn
+    viewport: Cell<Viewport>,
+    /// Colors and border style
+    theme: Theme,
 }
 
 impl AdvancedMemoryGrid {
     /// Create a new advanced memory grid
     pub fn new() -> Self {
-        let mut grid = Self {
+        let theme = Theme::default();
+        Self {
             particle_system: ParticleSystem::new(500),
             wave_animation: WaveAnimation::new(0.1, 3.0, 2.0),
-            color_cycle: ColorCycle::new(
-                vec![
-                    Color::DarkGray,
-                    Color::Gray,
-                    Color::DarkGray,
-                    Color::Black,
-                ],
-                0.5,
-            ),
+            color_cycle: border_cycle(&theme),
             heat_map: HashMap::new(),
             access_times: HashMap::new(),
             activity_levels: HashMap::new(),
             champion_trails: HashMap::new(),
             battle_intensity: 0.0,
             last_update: Instant::now(),
-        };
-        
-        // Add some initial visual test patterns to ensure effects are visible
-        grid.heat_map.insert(0, 5);
-        grid.heat_map.insert(32, 8);
-        grid.heat_map.insert(64, 12);
-        grid.activity_levels.insert(0, 0.8);
-        grid.activity_levels.insert(32, 0.6);
-        grid.activity_levels.insert(64, 0.9);
-        
-        grid
+            viewport: Cell::new(Viewport::default()),
+            theme,
+        }
+    }
+
+    /// Draw the grid in another theme
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.color_cycle = border_cycle(&theme);
+        self.theme = theme;
+    }
+
+    /// Get the part of the core the grid shows
+    pub fn viewport(&self) -> Viewport {
+        self.viewport.get()
+    }
+
+    /// Get the writes to each address seen since the start or a rewind
+    pub fn heat_map(&self) -> &HashMap<usize, u32> {
+        &self.heat_map
+    }
+
+    /// Get the viewport to scroll the grid
+    pub fn viewport_mut(&mut self) -> &mut Viewport {
+        self.viewport.get_mut()
+    }
+
+    /// Update the visualization from a game event
+    ///
+    /// # Arguments
+    /// * `event` - The event emitted by the engine
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::MemoryWrite { address, champion_id, .. } => {
+                self.update_memory_access(address, champion_id);
+            }
+            GameEvent::ProcessSpawned { champion_id, pc, .. } => {
+                if let Some((x, y)) = self.address_to_screen_coords(pc) {
+                    let color = self.champion_color(champion_id);
+                    self.particle_system
+                        .process_trail(x as f32, y as f32, color);
+                }
+            }
+            GameEvent::ProcessDied { pc, .. } => self.death_at(pc),
+            GameEvent::LiveReported { .. } => {
+                self.battle_intensity = (self.battle_intensity + 0.05).min(1.0);
+            }
+            GameEvent::ChampionEliminated { .. } => {
+                self.battle_intensity = 1.0;
+            }
+            GameEvent::CycleCompleted { .. } => {}
+            GameEvent::Rewound { .. } => {
+                // Heat and trails describe a future that no longer happened
+                self.heat_map.clear();
+                self.access_times.clear();
+                self.activity_levels.clear();
+                self.champion_trails.clear();
+            }
+        }
     }
     
     /// Update memory access patterns
@@ -84,7 +131,7 @@ impl AdvancedMemoryGrid {
         self.activity_levels.insert(address, activity);
         
         // Add to champion trail
-        let trail = self.champion_trails.entry(champion_id).or_insert_with(Vec::new);
+        let trail = self.champion_trails.entry(champion_id).or_default();
         trail.push((address, now));
         
         // Keep trail limited to last 50 positions
@@ -93,9 +140,10 @@ impl AdvancedMemoryGrid {
         }
         
         // Create particle effect for memory write
-        let (x, y) = self.address_to_screen_coords(address);
-        let color = self.champion_color(champion_id);
-        self.particle_system.memory_write(x as f32, y as f32, color);
+        if let Some((x, y)) = self.address_to_screen_coords(address) {
+            let color = self.champion_color(champion_id);
+            self.particle_system.memory_write(x as f32, y as f32, color);
+        }
         
         // Update battle intensity
         self.battle_intensity = (self.battle_intensity + 0.1).min(1.0);
@@ -103,15 +151,23 @@ impl AdvancedMemoryGrid {
     
     /// Update process position for trail effects
     pub fn update_process_position(&mut self, process: &Process) {
-        let (x, y) = self.address_to_screen_coords(process.pc);
-        let color = self.champion_color(process.champion_id);
-        self.particle_system.process_trail(x as f32, y as f32, color);
+        if let Some((x, y)) = self.address_to_screen_coords(process.pc) {
+            let color = self.champion_color(process.champion_id);
+            self.particle_system
+                .process_trail(x as f32, y as f32, color);
+        }
     }
     
     /// Handle process death with dramatic effect
     pub fn process_death(&mut self, process: &Process) {
-        let (x, y) = self.address_to_screen_coords(process.pc);
-        self.particle_system.process_death(x as f32, y as f32);
+        self.death_at(process.pc);
+    }
+
+    /// Show a death explosion at a memory address
+    fn death_at(&mut self, address: usize) {
+        if let Some((x, y)) = self.address_to_screen_coords(address) {
+            self.particle_system.process_death(x as f32, y as f32);
+        }
         
         // Boost battle intensity
         self.battle_intensity = (self.battle_intensity + 0.3).min(1.0);
@@ -147,25 +203,16 @@ impl AdvancedMemoryGrid {
         }
     }
     
-    /// Convert memory address to screen coordinates
-    fn address_to_screen_coords(&self, address: usize) -> (usize, usize) {
-        const BYTES_PER_ROW: usize = 32;
-        let row = address / BYTES_PER_ROW;
-        let col = (address % BYTES_PER_ROW) * 3; // 3 chars per byte (XX )
-        (col, row)
+    /// Convert memory address to screen coordinates, if it is in view
+    fn address_to_screen_coords(&self, address: usize) -> Option<(usize, usize)> {
+        // Inside the border
+        let (x, y) = self.viewport.get().screen_position(address)?;
+        Some((1 + x, 1 + y))
     }
     
     /// Get champion color by ID
     fn champion_color(&self, champion_id: u8) -> Color {
-        match champion_id {
-            1 => Color::Red,
-            2 => Color::Blue,
-            3 => Color::Green,
-            4 => Color::Yellow,
-            5 => Color::Magenta,
-            6 => Color::Cyan,
-            _ => Color::White,
-        }
+        self.theme.champion(champion_id)
     }
     
     /// Render the advanced memory grid
@@ -214,9 +261,6 @@ impl AdvancedMemoryGrid {
         area: Rect,
         buf: &mut Buffer,
     ) {
-        const BYTES_PER_ROW: usize = 32;
-        const DISPLAY_ROWS: usize = 20;
-        
         // Create block with enhanced animated border
         let border_color = self.color_cycle.current_color();
         let intensity_indicator = match processes.len() {
@@ -227,28 +271,57 @@ impl AdvancedMemoryGrid {
             _ => "🔴",
         };
         
-        let title = format!("🚀 Core War Memory Arena {} 🚀", intensity_indicator);
+        // Show as many bytes per row as fit, from the scrolled-to row
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let mut viewport = self.viewport.get();
+        viewport.fit(inner.width, inner.height, memory.size());
+        self.viewport.set(viewport);
+
+        let title = format!(
+            "🚀 Core War Memory Arena {} {:04X}-{:04X} 🚀",
+            intensity_indicator,
+            viewport.first_address(),
+            viewport.last_address()
+        );
         let block = Block::default()
             .title(title)
             .borders(Borders::ALL)
+            .border_type(self.theme.border_type)
             .border_style(Style::default().fg(border_color).add_modifier(Modifier::BOLD));
-        
-        let inner = block.inner(area);
         block.render(area, buf);
+
+        if viewport.density != Density::Hex {
+            let pcs: HashSet<usize> = processes.iter().map(|p| p.pc % memory.size()).collect();
+            let down = viewport.density.cell().1;
+            for row in 0..usize::from(inner.height) {
+                if (viewport.top + row * down) * viewport.columns >= memory.size() {
+                    break;
+                }
+                let line = dense::line(memory, &pcs, &viewport, &self.theme, row);
+                Paragraph::new(line).render(
+                    Rect::new(inner.x, inner.y + row as u16, inner.width, 1),
+                    buf,
+                );
+            }
+            return;
+        }
         
         // Render memory content with effects
-        for row in 0..DISPLAY_ROWS.min(inner.height as usize) {
-            let start_addr = row * BYTES_PER_ROW;
+        for row in 0..viewport.rows {
+            let start_addr = (viewport.top + row) * viewport.columns;
+            if start_addr >= memory.size() {
+                break;
+            }
             let mut line_spans = Vec::new();
             
             // Address column
             line_spans.push(Span::styled(
                 format!("{:04X}: ", start_addr),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(self.theme.dim),
             ));
             
             // Memory bytes with enhanced styling
-            for col in 0..BYTES_PER_ROW {
+            for col in 0..viewport.columns {
                 let addr = start_addr + col;
                 if addr >= memory.size() {
                     break;
@@ -265,27 +338,22 @@ impl AdvancedMemoryGrid {
                     
                     // Make heat effects MUCH more visible
                     if intensity > 0.1 {
-                        let red = (255.0 * intensity) as u8;
-                        let yellow = (180.0 * intensity) as u8;
-                        let orange = (120.0 * intensity) as u8;
-                        style = style.bg(Color::Rgb(red.min(255), yellow.min(180), orange.min(60)))
+                        style = style.bg(self.theme.heat.at(intensity))
                                    .add_modifier(Modifier::BOLD);
                     }
                 }
                 
                 // Apply dramatic activity highlighting
-                if let Some(&activity) = self.activity_levels.get(&addr) {
-                    if activity > 0.05 { // More sensitive threshold
-                        let green = (255.0 * activity) as u8;
-                        let blue = (128.0 * activity) as u8;
-                        // Make recently accessed memory much more visible
-                        style = style.fg(Color::Rgb(0, green, blue)).add_modifier(Modifier::BOLD);
-                        
-                        // Add pulsing background for very recent activity
-                        if activity > 0.7 {
-                            let pulse_bg = (50.0 * (self.last_update.elapsed().as_secs_f32() * 5.0).sin().abs()) as u8;
-                            style = style.bg(Color::Rgb(0, pulse_bg, pulse_bg / 2));
-                        }
+                if let Some(&activity) = self.activity_levels.get(&addr)
+                    && activity > 0.05 // More sensitive threshold
+                {
+                    // Make recently accessed memory much more visible
+                    style = style.fg(self.theme.activity.at(activity)).add_modifier(Modifier::BOLD);
+
+                    // Add pulsing background for very recent activity
+                    if activity > 0.7 {
+                        let pulse_bg = (50.0 * (self.last_update.elapsed().as_secs_f32() * 5.0).sin().abs()) as u8;
+                        style = style.bg(Color::Rgb(0, pulse_bg, pulse_bg / 2));
                     }
                 }
                 
@@ -351,7 +419,8 @@ impl AdvancedMemoryGrid {
         let block = Block::default()
             .title("⚡ Battle Stats ⚡")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Yellow));
+            .border_type(self.theme.border_type)
+            .border_style(Style::default().fg(self.theme.accent));
         
         let inner = block.inner(area);
         block.render(area, buf);
@@ -383,8 +452,17 @@ impl AdvancedMemoryGrid {
             
             content.push(Line::from(vec![
                 Span::styled(format!("  {} ", champion.id), Style::default().fg(color)),
-                Span::styled(&champion.name, Style::default().fg(Color::White)),
+                Span::styled(champion.display_name(), Style::default().fg(Color::White)),
             ]));
+            if !champion.metadata.is_empty() {
+                content.push(Line::from(vec![
+                    Span::raw("    "),
+                    Span::styled(
+                        champion.metadata.to_string(),
+                        Style::default().fg(self.theme.dim),
+                    ),
+                ]));
+            }
             
             content.push(Line::from(vec![
                 Span::raw("    "),
@@ -476,7 +554,7 @@ impl AdvancedMemoryGrid {
             let status_symbol = if process_count > 0 { "●" } else { "○" };
             
             champion_status.push(Span::styled(
-                format!("{} {}", status_symbol, champion.name),
+                format!("{} {}", status_symbol, champion.display_name()),
                 Style::default().fg(status_color)
             ));
             champion_status.push(Span::raw("  "));
@@ -495,8 +573,13 @@ impl AdvancedMemoryGrid {
         ]));
         
         // Render the footer
-        let paragraph = Paragraph::new(content)
-            .block(Block::default().borders(Borders::TOP).title("Battle Status"));
+        let paragraph = Paragraph::new(content).block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_type(self.theme.border_type)
+                .border_style(Style::default().fg(self.theme.border))
+                .title("Battle Status"),
+        );
         paragraph.render(area, buf);
     }
 }
@@ -505,4 +588,9 @@ impl Default for AdvancedMemoryGrid {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// Get the colors the memory grid's border pulses through in a theme
+fn border_cycle(theme: &Theme) -> ColorCycle {
+    ColorCycle::new(vec![theme.dim, theme.border, theme.dim], 0.5)
+}
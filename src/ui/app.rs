@@ -3,19 +3,49 @@
 /// This module defines the main App struct that manages the state
 /// of the Core War terminal visualization.
 use crate::error::Result;
-use crate::vm::{Memory, Process};
+use crate::vm::pacing::Speed;
+use crate::vm::reference::InstructionReference;
+use crate::vm::{
+    BreakpointHit, ChampionColor, GameEvent, Instruction, Memory, Process, TickOutcome,
+};
 use crate::ui::advanced_memory::AdvancedMemoryGrid;
+use crate::ui::charts::{SAMPLE_INTERVAL, Trends};
+use crate::ui::core_image::CoreImage;
+use crate::ui::disassembly;
+use crate::ui::editor;
+use crate::ui::event_log::EventLog;
+use crate::ui::pause::{self, PauseOn};
+use crate::ui::process_list::ProcessList;
+use crate::ui::theme::Theme;
+use crate::ui::viewport;
 use crate::GameEngine;
 use crossterm::event::{self, Event, KeyCode};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use ratatui::Terminal;
-use ratatui::backend::CrosstermBackend;
+use ratatui::backend::{Backend, CrosstermBackend};
 use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::widgets::{Gauge, Paragraph};
 use std::io::{self};
-use std::time::Duration;
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+/// Cycles a replay seek key jumps by
+pub const REPLAY_SEEK_CYCLES: u32 = 100;
+
+/// Process rows the process table shows at once
+pub const PROCESS_ROWS: u16 = 8;
+
+/// Time between rendered frames
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Lines of events the event log pane shows
+pub const EVENT_ROWS: u16 = 8;
+
+/// Most of a frame spent running cycles, leaving the rest to drawing and keys
+pub const SIMULATION_BUDGET: Duration = Duration::from_millis(25);
 
 /// Main application state
 pub struct App<'a> {
@@ -23,8 +53,13 @@ pub struct App<'a> {
     pub should_quit: bool,
     /// Whether the simulation is paused
     pub paused: bool,
-    /// Current simulation speed multiplier
-    pub speed: u32,
+    /// Cycles run per rendered frame
+    pub speed: Speed,
+    /// Frames rendered while running, for slow motion
+    frame: u64,
+    /// Cycles run in the last frame, fewer than the speed asks for when they
+    /// took longer than [`SIMULATION_BUDGET`]
+    pub frame_cycles: u32,
     /// Whether to show debug information
     pub debug_mode: bool,
     /// Selected memory address for inspection
@@ -37,6 +72,63 @@ pub struct App<'a> {
     pub engine: &'a mut GameEngine,
     /// Advanced memory visualization
     pub advanced_memory: AdvancedMemoryGrid,
+    /// Breakpoints hit by the most recent tick
+    pub breakpoint_hits: Vec<BreakpointHit>,
+    /// Result of the last champion edit, shown in the stats pane
+    pub status: Option<String>,
+    /// Address being typed to jump the memory grid to, while the prompt is open
+    pub address_input: Option<String>,
+    /// What the open prompt's input is for
+    pub prompt: Prompt,
+    /// Events that pause the battle by themselves
+    pub pause_on: PauseOn,
+    /// Samples of the battle for the trend charts
+    pub trends: Trends,
+    /// Recent events, for the event log pane
+    pub event_log: EventLog,
+    /// Sorting and cursor of the process table
+    pub process_list: ProcessList,
+    /// Pane the arrow keys act on
+    pub focus: Focus,
+    /// Process the memory grid keeps centered, while following one
+    pub following: Option<u32>,
+    /// Themes the theme key cycles through
+    themes: Vec<Theme>,
+    /// Index of the theme in use
+    theme_index: usize,
+    /// Events emitted by the engine, consumed by the visualization
+    events: Receiver<GameEvent>,
+}
+
+/// Moves along the timeline of a replayed recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaySeek {
+    /// Go back [`REPLAY_SEEK_CYCLES`] cycles
+    Back,
+    /// Go forward [`REPLAY_SEEK_CYCLES`] cycles
+    Forward,
+    /// Go to the end of the next cycle in which a process dies
+    NextDeath,
+    /// Go to the end of the recording
+    End,
+}
+
+/// What the prompt below the stats asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prompt {
+    /// An address to jump the memory grid to
+    Address,
+    /// A memory region to pause on writes to
+    WatchRegion,
+}
+
+/// Pane the arrow keys act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    /// Arrows scroll the memory grid
+    Memory,
+    /// Arrows move through the process table
+    Processes,
 }
 
 /// Different view modes for the UI
@@ -54,17 +146,42 @@ pub enum ViewMode {
 
 impl<'a> App<'a> {
     /// Create a new application instance
+    ///
+    /// The app starts paused if the engine is, as with `run --pause`. From
+    /// then on the app pauses by not ticking the engine, so the engine is
+    /// resumed to let stepping work.
     pub fn new(engine: &'a mut GameEngine) -> Self {
+        let events = engine.subscribe_channel();
+        let speed = Speed::per_frame(engine.config().speed);
+        let paused = engine.state().paused;
+        engine.resume();
+        let mut trends = Trends::new();
+        trends.record(engine);
         Self {
             should_quit: false,
-            paused: false,
-            speed: 1,
+            paused,
+            speed,
+            frame: 0,
+            frame_cycles: 0,
             debug_mode: false,
             selected_address: None,
             view_mode: ViewMode::Normal,
             selected_process_id: None,
             engine,
             advanced_memory: AdvancedMemoryGrid::new(),
+            breakpoint_hits: Vec::new(),
+            status: None,
+            address_input: None,
+            prompt: Prompt::Address,
+            pause_on: PauseOn::new(),
+            trends,
+            event_log: EventLog::new(),
+            process_list: ProcessList::new(),
+            focus: Focus::Memory,
+            following: None,
+            themes: Theme::builtins(),
+            theme_index: 0,
+            events,
         }
     }
 
@@ -78,64 +195,46 @@ impl<'a> App<'a> {
     /// # Returns
     /// `Ok(())` if successful, error otherwise
     pub fn update(&mut self) -> Result<()> {
+        self.update_within(SIMULATION_BUDGET)
+    }
+
+    /// Run a frame's share of cycles within a time budget
+    ///
+    /// At high speeds the cycles can take longer than a frame; the frame
+    /// then runs as many as fit the budget, at least one, so the UI keeps
+    /// its frame rate and answers keys instead of falling behind.
+    ///
+    /// # Arguments
+    /// * `budget` - Longest the cycles may take
+    pub fn update_within(&mut self, budget: Duration) -> Result<()> {
         if !self.paused {
-            // Store old memory state for comparison
-            let old_memory: Vec<u8> = (0..self.engine.memory().size())
-                .map(|i| self.engine.memory().read_byte(i))
-                .collect();
-            
-            // Execute VM tick
-            self.engine.tick()?;
-            
-            // Detect memory changes and update visualization
-            for addr in 0..self.engine.memory().size() {
-                let old_byte = old_memory[addr];
-                let new_byte = self.engine.memory().read_byte(addr);
-                
-                if old_byte != new_byte {
-                    // Memory changed - update heat map and create visual effects
-                    if let Some(owner_id) = self.engine.memory().get_owner(addr) {
-                        self.advanced_memory.update_memory_access(addr, owner_id);
-                        
-                        // Create particle effect for dramatic memory changes
-                        if (old_byte == 0 && new_byte != 0) || (old_byte != 0 && new_byte == 0) {
-                            // Major change - create explosion effect
-                            for process in self.engine.processes() {
-                                if process.champion_id == owner_id {
-                                    self.advanced_memory.process_death(process);
-                                    break;
-                                }
-                            }
-                        }
-                    } else {
-                        // Even if no owner, still update heat map to show activity
-                        self.advanced_memory.update_memory_access(addr, 1);
-                    }
+            // Run this frame's share of cycles, stopping at breakpoints, pause
+            // triggers and the end
+            let cycles = self.speed.cycles_in_frame(self.frame);
+            self.frame += 1;
+            self.frame_cycles = 0;
+            let was_running = self.engine.state().running;
+            let deadline = Instant::now() + budget;
+            for _ in 0..cycles {
+                let outcome = self.engine.tick()?;
+                self.frame_cycles += 1;
+                let running = outcome.is_running();
+                self.handle_outcome(outcome);
+                self.consume_events();
+                if self.paused || !running || Instant::now() >= deadline {
+                    break;
                 }
             }
-            
+            if was_running && !self.engine.state().running {
+                self.status = Some(self.battle_result());
+            }
+
             // Update advanced memory grid with real battle data
             self.advanced_memory.update();
-            
-            // Update process positions for trail visualization and force continuous visual activity
+
+            // Update process positions for trail visualization
             for process in self.engine.processes() {
                 self.advanced_memory.update_process_position(process);
-                
-                // Force heat map activity around the process position to make effects visible
-                self.advanced_memory.update_memory_access(process.pc, process.champion_id);
-                
-                // Add some activity in a larger radius around the process for more visual impact
-                for offset in 0..8 {
-                    let addr = (process.pc + offset * 10) % self.engine.memory().size();
-                    self.advanced_memory.update_memory_access(addr, process.champion_id);
-                }
-                
-                // Add some random sparkle effects around active processes
-                let cycle = self.engine.get_stats().cycle;
-                if cycle % 5 == 0 {  // Every 5 cycles
-                    let sparkle_addr = (process.pc + (cycle as usize * 7)) % self.engine.memory().size();
-                    self.advanced_memory.update_memory_access(sparkle_addr, process.champion_id);
-                }
             }
         }
         Ok(())
@@ -159,9 +258,50 @@ impl<'a> App<'a> {
             .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
             .split(frame.size());
 
-        // Render advanced memory visualization
-        let memory_area = chunks[0];
+        // Render advanced memory visualization, above the timeline when replaying
+        let mut memory_area = chunks[0];
+        if let Some((step, length)) = self.engine.replay_progress() {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)])
+                .split(memory_area);
+            memory_area = rows[0];
+            let timeline = Gauge::default()
+                .block(self.theme().block("Replay"))
+                .gauge_style(Style::default().fg(Color::Cyan))
+                .ratio(f64::from(step) / f64::from(length.max(1)))
+                .label(format!("{}/{} cycles replayed", step, length));
+            frame.render_widget(timeline, rows[1]);
+        }
+
+        // Trend charts and the event log under the memory grid, two rows
+        // for the borders
+        let champions = self.engine.champions();
+        let height = Trends::height(champions.len()).max(EVENT_ROWS) + 2;
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(memory_area);
+        memory_area = rows[0];
+        let bottom = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rows[1]);
+        let block = self.theme().block("Events");
+        let lines = self.event_log.lines(
+            champions,
+            self.theme(),
+            usize::from(block.inner(bottom[1]).height),
+        );
+        frame.render_widget(Paragraph::new(lines).block(block), bottom[1]);
+        let block = self
+            .theme()
+            .block(format!("Trends (every {} cycles)", SAMPLE_INTERVAL));
+        let charts_area = block.inner(bottom[0]);
+        frame.render_widget(block, bottom[0]);
         let buf = frame.buffer_mut();
+        self.trends
+            .render(champions, self.theme(), charts_area, buf);
         
         // Get process references for visualization
         let process_refs: Vec<&Process> = self.engine.processes().into_iter().collect();
@@ -190,29 +330,117 @@ impl<'a> App<'a> {
 
         for champ in self.engine.champions() {
             let usage = champion_memory_usage.get(&champ.id).unwrap_or(&0);
-            stats.push_str(&format!("- {} (ID: {}): {} bytes\n", champ.name, champ.id, usage));
+            stats.push_str(&format!("- {} (ID: {}): {} bytes\n", champ.display_name(), champ.id, usage));
+            if !champ.metadata.is_empty() {
+                stats.push_str(&format!("  {}\n", champ.metadata));
+            }
+        }
+        for hit in &self.breakpoint_hits {
+            stats.push_str(&format!("Breakpoint: {}\n", hit));
+        }
+        if self.engine.is_branched() {
+            stats.push_str("Branch: what-if (w to discard)\n");
+        }
+        if let Some(status) = &self.status {
+            stats.push_str(&format!("{}\n", status));
+        }
+        if let Some(input) = &self.address_input {
+            let question = match self.prompt {
+                Prompt::Address => "Go to address (0x hex or decimal)",
+                Prompt::WatchRegion => "Pause on writes to (START..END, empty to stop)",
+            };
+            stats.push_str(&format!("{}: {}_\n", question, input));
+        }
+        if let Some(id) = self.following {
+            stats.push_str(&format!("Following process {}\n", id));
+        }
+        if let Some(triggers) = self.pause_on.describe() {
+            stats.push_str(&format!("Pause on: {}\n", triggers));
+        }
+        stats.push_str(&format!("Speed: {}\n", self.speed));
+        if let Speed::Fast(cycles) = self.speed
+            && !self.paused
+            && self.engine.state().running
+            && (1..cycles).contains(&self.frame_cycles)
+        {
+            stats.push_str(&format!(
+                "  limited to {} cycles/frame by the frame budget\n",
+                self.frame_cycles
+            ));
         }
-        stats.push_str(&format!("Speed: {}x\n", self.speed));
         stats.push_str(&format!("Debug: {}\n", self.debug_mode));
-        stats.push_str("\nPress <space> to pause/resume\nPress q to quit\nPress + to increase speed\nPress - to decrease speed\nPress d to toggle debug\nPress 1 for Normal view\nPress s to step (when paused)\nPress p to cycle processes");
-
-        if let Some(selected_id) = self.selected_process_id {
-            if let Some(process) = self.engine.processes().iter().find(|p| p.id == selected_id) {
-                stats.push_str(&format!("\nSelected Process {}:\n", process.id));
-                stats.push_str(&format!("  PC: 0x{:04X}\n", process.pc));
-                stats.push_str(&format!("  Carry: {}\n", process.carry));
-                stats.push_str("  Registers:\n");
-                for i in 0..16 {
-                    stats.push_str(&format!("    r{:<2}: {:<10}\n", i + 1, process.registers[i]));
+        if self.engine.replay_progress().is_some() {
+            stats.push_str("Press [ or ] to seek 100 cycles\nPress n for the next death\nPress End to jump to the end\n");
+        }
+        stats.push_str("\nPress <space> to pause/resume\nPress q to quit\nPress + to increase speed\nPress - to decrease speed\nPress d to toggle debug\nPress 1 for Normal view\nPress s to step (when paused)\nPress Up/Down or PgUp/PgDn to scroll memory\nPress p to move the arrows to the process table\nPress t to switch theme\nPress g to go to an address\nPress f to follow the selected process, Tab for the next one\nPress z to zoom memory out to half blocks, braille and back\nPress i to save the core as a PNG image\nPress D/E to pause on deaths/eliminations\nPress W to pause on writes to a region\nPress b to step back\nPress w to branch/discard what-if\nPress e to edit the selected champion and restart\nIn a branch: k kills, x zeroes PC byte of selected process");
+
+        if let Some(selected_id) = self.selected_process_id
+            && let Some(process) = self.engine.process(selected_id)
+        {
+            stats.push_str(&format!("\nSelected Process {}:\n", process.id));
+            stats.push_str(&format!("  PC: 0x{:04X}\n", process.pc));
+            let opcode = self.engine.memory().read_byte(process.pc);
+            match Instruction::from_opcode(opcode) {
+                Ok(instruction) => {
+                    let reference = InstructionReference::of(instruction);
+                    stats.push_str(&format!(
+                        "  Next: {} {} ({})\n",
+                        instruction.name(),
+                        reference.operand_signature(),
+                        reference.summary
+                    ));
                 }
+                Err(_) => stats.push_str(&format!("  Next: invalid opcode 0x{:02X}\n", opcode)),
+            }
+            stats.push_str(&format!("  Carry: {}\n", process.carry));
+            stats.push_str("  Registers:\n");
+            for i in 0..16 {
+                stats.push_str(&format!("    r{:<2}: {:<10}\n", i + 1, process.registers[i]));
             }
         }
-        let stats =
-            Paragraph::new(stats).block(Block::default().borders(Borders::ALL).title("Stats"));
-        frame.render_widget(stats, chunks[1]);
+        // The process table and disassembly sit under the stats, two rows for each border
+        let panes = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(PROCESS_ROWS + 3),
+                Constraint::Length(
+                    (disassembly::LINES_BEFORE + disassembly::LINES_AFTER + 3) as u16,
+                ),
+            ])
+            .split(chunks[1]);
+        let stats = Paragraph::new(stats).block(self.theme().block("Stats"));
+        frame.render_widget(stats, panes[0]);
+        let (table, mut state) = self.process_list.table(
+            &self.process_rows(),
+            self.engine.champions(),
+            self.engine.get_stats().cycle,
+            self.theme(),
+            self.focus == Focus::Processes,
+        );
+        frame.render_stateful_widget(table, panes[1], &mut state);
+        frame.render_widget(self.disassembly_pane(), panes[2]);
         Ok(())
     }
 
+    /// Build the disassembly pane for the selected process
+    fn disassembly_pane(&self) -> Paragraph<'static> {
+        let process = self
+            .selected_process_id
+            .and_then(|id| self.engine.process(id));
+        let (title, lines) = match process {
+            Some(process) => (
+                format!("Disassembly (process {})", process.id),
+                disassembly::lines(self.engine.memory(), process),
+            ),
+            None => (
+                "Disassembly".to_string(),
+                vec![Line::raw("Select a process in the process table")],
+            ),
+        };
+        Paragraph::new(lines).block(self.theme().block(title))
+    }
+
     /// Toggle pause state
     pub fn toggle_pause(&mut self) {
         self.paused = !self.paused;
@@ -220,16 +448,12 @@ impl<'a> App<'a> {
 
     /// Increase simulation speed
     pub fn increase_speed(&mut self) {
-        if self.speed < 1000 {
-            self.speed *= 2;
-        }
+        self.speed = self.speed.faster();
     }
 
     /// Decrease simulation speed
     pub fn decrease_speed(&mut self) {
-        if self.speed > 1 {
-            self.speed /= 2;
-        }
+        self.speed = self.speed.slower();
     }
 
     /// Toggle debug mode
@@ -247,6 +471,281 @@ impl<'a> App<'a> {
         self.selected_address = None;
     }
 
+    /// Scroll the memory grid by rows, negative towards address 0
+    pub fn scroll_memory(&mut self, rows: isize) {
+        self.advanced_memory.viewport_mut().scroll(rows);
+    }
+
+    /// Scroll the memory grid by pages, negative towards address 0
+    pub fn page_memory(&mut self, pages: isize) {
+        self.advanced_memory.viewport_mut().page(pages);
+    }
+
+    /// Get the theme in use
+    pub fn theme(&self) -> &Theme {
+        &self.themes[self.theme_index]
+    }
+
+    /// Switch to a theme
+    ///
+    /// The theme joins the ones the theme key cycles through, replacing a
+    /// built-in theme of the same name.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme_index = match self.themes.iter().position(|t| t.name == theme.name) {
+            Some(index) => {
+                self.themes[index] = theme;
+                index
+            }
+            None => {
+                self.themes.push(theme);
+                self.themes.len() - 1
+            }
+        };
+        self.advanced_memory.set_theme(self.theme().clone());
+    }
+
+    /// Switch to the next theme
+    pub fn next_theme(&mut self) {
+        self.theme_index = (self.theme_index + 1) % self.themes.len();
+        self.advanced_memory.set_theme(self.theme().clone());
+        self.status = Some(format!("Theme: {}", self.theme().name));
+    }
+
+    /// Move the arrow keys between the memory grid and the process table
+    pub fn toggle_process_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Memory => Focus::Processes,
+            Focus::Processes => Focus::Memory,
+        };
+    }
+
+    /// Handle an arrow key, in the focused pane
+    ///
+    /// # Arguments
+    /// * `delta` - Rows to move, negative towards the top
+    pub fn navigate(&mut self, delta: isize) {
+        match self.focus {
+            Focus::Memory => self.scroll_memory(delta),
+            Focus::Processes => {
+                let cycle = self.engine.get_stats().cycle;
+                let rows = self.process_list.rows(self.engine.processes(), cycle);
+                self.process_list.move_cursor(&rows, delta);
+            }
+        }
+    }
+
+    /// Sort the process table by its next column
+    pub fn cycle_process_sort(&mut self) {
+        self.process_list.sort = self.process_list.sort.next();
+    }
+
+    /// Select the process under the table's cursor
+    ///
+    /// The process's PC is scrolled into view and selected in the memory
+    /// grid, and the detail and disassembly panes follow the process.
+    ///
+    /// # Returns
+    /// Whether the cursor was on a process
+    pub fn select_process_at_cursor(&mut self) -> bool {
+        let Some(process) = self
+            .process_list
+            .cursor
+            .and_then(|id| self.engine.process(id))
+        else {
+            return false;
+        };
+        let (id, pc) = (process.id, process.pc);
+        self.selected_process_id = Some(id);
+        self.advanced_memory.viewport_mut().jump_to(pc);
+        self.select_address(pc);
+        true
+    }
+
+    /// Follow the selected process with the memory grid, or stop following
+    ///
+    /// With no process selected the oldest live process is followed.
+    pub fn toggle_follow(&mut self) {
+        if self.following.take().is_some() {
+            self.status = Some("Stopped following".to_string());
+            return;
+        }
+        let selected = self
+            .selected_process_id
+            .filter(|&id| self.engine.process(id).is_some());
+        match selected.or_else(|| self.next_followed(0)) {
+            Some(id) => self.follow(id),
+            None => self.status = Some("No process to follow".to_string()),
+        }
+    }
+
+    /// Follow the live process after the followed one, by process ID
+    ///
+    /// Wraps to the oldest process after the newest, and starts following
+    /// if the grid was not.
+    pub fn follow_next_process(&mut self) {
+        let after = self
+            .following
+            .or(self.selected_process_id)
+            .map_or(0, |id| id + 1);
+        match self.next_followed(after) {
+            Some(id) => self.follow(id),
+            None => self.status = Some("No process to follow".to_string()),
+        }
+    }
+
+    /// Start following a process and center the grid on it
+    fn follow(&mut self, id: u32) {
+        self.following = Some(id);
+        self.process_list.cursor = Some(id);
+        self.status = Some(format!("Following process {}", id));
+        self.center_followed();
+    }
+
+    /// Get the live process with the lowest ID at or after one, wrapping
+    fn next_followed(&self, from: u32) -> Option<u32> {
+        let ids = self.engine.processes().into_iter().map(|p| p.id);
+        let (after, before): (Vec<u32>, Vec<u32>) = ids.partition(|&id| id >= from);
+        after.into_iter().min().or_else(|| before.into_iter().min())
+    }
+
+    /// Center the memory grid on the followed process and select it
+    ///
+    /// When the followed process has died the next live one is followed,
+    /// so an imp's successors are tracked as it marches on.
+    fn center_followed(&mut self) {
+        let Some(id) = self.following else {
+            return;
+        };
+        let followed = self
+            .engine
+            .process(id)
+            .or_else(|| self.engine.process(self.next_followed(id)?))
+            .map(|p| (p.id, p.pc));
+        let Some((id, pc)) = followed else {
+            self.following = None;
+            self.status = Some("Stopped following: no live processes".to_string());
+            return;
+        };
+        self.following = Some(id);
+        self.selected_process_id = Some(id);
+        self.advanced_memory.viewport_mut().center_on(pc);
+        self.select_address(pc);
+    }
+
+    /// Get the live processes in the process table's order
+    fn process_rows(&self) -> Vec<&Process> {
+        let cycle = self.engine.get_stats().cycle;
+        self.process_list.rows(self.engine.processes(), cycle)
+    }
+
+    /// Switch the memory grid to its next density, from hex to braille and back
+    pub fn cycle_density(&mut self) {
+        let viewport = self.advanced_memory.viewport_mut();
+        viewport.density = viewport.density.next();
+        self.status = Some(format!("Memory: {}", viewport.density.name()));
+    }
+
+    /// Save the core as a PNG image, named after the cycle
+    ///
+    /// The image is written to the current directory; the file written, or
+    /// why it could not be, is left in [`App::status`].
+    ///
+    /// # Returns
+    /// Whether the image was written
+    pub fn export_image(&mut self) -> bool {
+        let path = format!("corewar-cycle-{}.png", self.engine.get_stats().cycle);
+        let pcs = self.engine.processes().iter().map(|p| p.pc).collect();
+        let image = CoreImage::render(
+            self.engine.memory(),
+            &pcs,
+            self.advanced_memory.heat_map(),
+            self.theme(),
+        );
+        match image.write(&path) {
+            Ok(()) => {
+                self.status = Some(format!("Core image written to {}", path));
+                true
+            }
+            Err(e) => {
+                self.status = Some(format!("Image export failed: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Open the prompt for an address to jump the memory grid to
+    pub fn begin_address_jump(&mut self) {
+        self.prompt = Prompt::Address;
+        self.address_input = Some(String::new());
+    }
+
+    /// Open the prompt for a memory region to pause on writes to
+    pub fn begin_watch_region(&mut self) {
+        self.prompt = Prompt::WatchRegion;
+        self.address_input = Some(String::new());
+    }
+
+    /// Turn pausing when a process dies on or off
+    pub fn toggle_pause_on_death(&mut self) {
+        self.pause_on.death = !self.pause_on.death;
+    }
+
+    /// Turn pausing when a champion is eliminated on or off
+    pub fn toggle_pause_on_elimination(&mut self) {
+        self.pause_on.elimination = !self.pause_on.elimination;
+    }
+
+    /// Handle a key while the address prompt is open
+    ///
+    /// Enter jumps to the typed address and selects it, or watches the typed
+    /// region, Esc closes the prompt, and Backspace deletes a character.
+    ///
+    /// # Arguments
+    /// * `key` - The key pressed
+    ///
+    /// # Returns
+    /// Whether the prompt took the key; false when the prompt is closed
+    pub fn address_input_key(&mut self, key: KeyCode) -> bool {
+        let Some(input) = self.address_input.as_mut() else {
+            return false;
+        };
+        match key {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => self.address_input = None,
+            KeyCode::Enter if self.prompt == Prompt::WatchRegion => {
+                let input = self.address_input.take().unwrap_or_default();
+                if input.trim().is_empty() {
+                    self.pause_on.region = None;
+                    return true;
+                }
+                match pause::parse_region(&input, self.engine.memory().size()) {
+                    Some(region) => {
+                        self.pause_on.region = Some(region);
+                        self.status = None;
+                    }
+                    None => self.status = Some(format!("Not a region of the core: {}", input)),
+                }
+            }
+            KeyCode::Enter => {
+                let input = self.address_input.take().unwrap_or_default();
+                match viewport::parse_address(&input) {
+                    Some(address) => {
+                        let address = address % self.engine.memory().size();
+                        self.advanced_memory.viewport_mut().jump_to(address);
+                        self.select_address(address);
+                        self.status = None;
+                    }
+                    None => self.status = Some(format!("Not an address: {}", input)),
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
     /// Set the view mode
     pub fn set_view_mode(&mut self, mode: ViewMode) {
         self.view_mode = mode;
@@ -260,10 +759,239 @@ impl<'a> App<'a> {
     /// Step the simulation by one cycle if paused
     pub fn step(&mut self) -> Result<()> {
         if self.paused {
-            self.engine.tick()?;
+            let was_running = self.engine.state().running;
+            let outcome = self.engine.tick()?;
+            self.handle_outcome(outcome);
+            self.consume_events();
+            if was_running && !self.engine.state().running {
+                self.status = Some(self.battle_result());
+            }
         }
         Ok(())
     }
+
+    /// Describe how the battle ended
+    fn battle_result(&self) -> String {
+        let state = self.engine.state();
+        let winner = state
+            .winner
+            .and_then(|id| self.engine.champions().iter().find(|c| c.id == id));
+        match winner {
+            Some(champion) => format!(
+                "Battle over at cycle {}: {} wins",
+                state.cycle,
+                champion.display_name()
+            ),
+            None => format!("Battle over at cycle {}: draw", state.cycle),
+        }
+    }
+
+    /// Pause when a tick hits a breakpoint
+    fn handle_outcome(&mut self, outcome: TickOutcome) {
+        match outcome {
+            TickOutcome::Breakpoint(hits) => {
+                self.paused = true;
+                self.breakpoint_hits = hits;
+            }
+            _ => self.breakpoint_hits.clear(),
+        }
+    }
+
+    /// Pause and step the simulation back by one cycle
+    ///
+    /// # Returns
+    /// Whether the engine had a checkpoint old enough to rewind to
+    pub fn rewind(&mut self) -> bool {
+        self.paused = true;
+        let rewound = self.engine.rewind(1).is_ok();
+        self.consume_events();
+        rewound
+    }
+
+    /// Move along the timeline of a replayed recording
+    ///
+    /// Forward seeks replay the skipped cycles, so the visualization sees
+    /// their events; backward seeks restore a replay checkpoint. A failed
+    /// seek, such as looking for a death when no process dies anymore, is
+    /// reported in [`App::status`].
+    ///
+    /// # Arguments
+    /// * `seek` - Where to go
+    ///
+    /// # Returns
+    /// Whether the replay moved
+    pub fn seek(&mut self, seek: ReplaySeek) -> bool {
+        if self.engine.replay_progress().is_none() {
+            self.status = Some("Seeking is only possible in a replay".to_string());
+            return false;
+        }
+        let outcome = match seek {
+            ReplaySeek::Back => self
+                .engine
+                .rewind(REPLAY_SEEK_CYCLES)
+                .map(|_| TickOutcome::Running),
+            ReplaySeek::Forward => self.engine.run_cycles(REPLAY_SEEK_CYCLES),
+            ReplaySeek::NextDeath => self.engine.seek_next_death(),
+            ReplaySeek::End => self.engine.step_until(|_| false),
+        };
+        let moved = match outcome {
+            Ok(outcome) => {
+                self.handle_outcome(outcome);
+                self.status = None;
+                true
+            }
+            Err(e) => {
+                self.status = Some(format!("Seek failed: {}", e));
+                false
+            }
+        };
+        self.consume_events();
+        moved
+    }
+
+    /// Enter branch mode, or leave it and return to the branch point
+    ///
+    /// Entering pauses the simulation so the battle can be edited first.
+    ///
+    /// # Returns
+    /// Whether the engine entered or left the branch
+    pub fn toggle_branch(&mut self) -> bool {
+        let toggled = if self.engine.is_branched() {
+            self.engine.discard_branch().is_ok()
+        } else {
+            self.paused = true;
+            self.engine.branch().is_ok()
+        };
+        self.consume_events();
+        toggled
+    }
+
+    /// Kill the selected process while in branch mode
+    ///
+    /// # Returns
+    /// Whether a process was killed
+    pub fn kill_selected_process(&mut self) -> bool {
+        let Some(process_id) = self.selected_process_id else {
+            return false;
+        };
+        let killed = self.engine.kill_process(process_id).is_ok();
+        if killed {
+            self.selected_process_id = None;
+        }
+        self.consume_events();
+        killed
+    }
+
+    /// Zero the byte under the selected process's PC while in branch mode
+    ///
+    /// # Returns
+    /// Whether memory was changed
+    pub fn poke_selected_process(&mut self) -> bool {
+        let pc = self
+            .selected_process_id
+            .and_then(|id| self.engine.process(id).map(|p| p.pc));
+        pc.is_some_and(|pc| self.engine.poke_memory(pc, 0x00).is_ok())
+    }
+
+    /// Edit the selected process's champion, or the first one, and reload it
+    ///
+    /// Runs the user's editor on the champion's source and blocks until it
+    /// exits, so the caller must release the terminal first. If the source
+    /// changed, it is reassembled and the round restarts. The outcome is
+    /// left in [`App::status`].
+    ///
+    /// # Returns
+    /// Whether the round restarted with the edited champion
+    pub fn edit_champion(&mut self) -> bool {
+        let selected_champion = self.selected_process_id.and_then(|id| {
+            self.engine
+                .processes()
+                .iter()
+                .find(|p| p.id == id)
+                .map(|p| p.champion_id)
+        });
+        let champion = self
+            .engine
+            .champions()
+            .iter()
+            .find(|c| selected_champion.is_none_or(|id| c.id == id));
+        let Some(champion) = champion else {
+            self.status = Some("No champion to edit".to_string());
+            return false;
+        };
+        let name = champion.display_name().to_string();
+        let (Some(source), Some(output)) = (editor::source_path(champion), champion.source.clone())
+        else {
+            self.status = Some(format!("No .s source found for {}", name));
+            return false;
+        };
+
+        match editor::edit_file(&source) {
+            Ok(true) => self.reload_champion(&source, &output),
+            Ok(false) => {
+                self.status = Some(format!("No changes to {}", name));
+                false
+            }
+            Err(e) => {
+                self.status = Some(format!("Edit failed: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Reassemble a champion's source and restart the round with it
+    ///
+    /// # Arguments
+    /// * `source` - Redcode source of a loaded champion
+    /// * `output` - The .cor file the champion was loaded from
+    ///
+    /// # Returns
+    /// Whether the round restarted; on failure the battle is left as it was
+    /// if the source did not assemble
+    pub fn reload_champion(&mut self, source: &Path, output: &Path) -> bool {
+        let size = match editor::assemble(source, output) {
+            Ok(size) => size,
+            Err(e) => {
+                self.status = Some(format!("Assembly failed: {}", e));
+                return false;
+            }
+        };
+        if let Err(e) = self.engine.restart().and_then(|()| self.engine.start()) {
+            self.status = Some(format!("Reload failed: {}", e));
+            return false;
+        }
+
+        self.advanced_memory = AdvancedMemoryGrid::new();
+        self.selected_process_id = None;
+        self.breakpoint_hits.clear();
+        self.consume_events();
+        self.status = Some(format!(
+            "Reloaded {} ({} bytes), round restarted",
+            source.display(),
+            size
+        ));
+        true
+    }
+
+    /// Feed the events emitted since the last tick to the visualization
+    ///
+    /// An event that trips one of [`App::pause_on`]'s triggers pauses the
+    /// battle, with the reason in [`App::status`]. The trend charts are
+    /// sampled too, when a sample is due, the events are logged, and the
+    /// grid keeps centered on the followed process.
+    fn consume_events(&mut self) {
+        self.trends.record(self.engine);
+        for event in self.events.try_iter() {
+            self.advanced_memory.handle_event(&event);
+            self.event_log.record(&event);
+            if let Some(reason) = self.pause_on.reason(&event) {
+                self.paused = true;
+                self.status = Some(format!("Paused: {}", reason));
+            }
+        }
+        self.event_log.flush(self.engine.get_stats().cycle);
+        self.center_followed();
+    }
 }
 
 impl Default for App<'_> {
@@ -276,11 +1004,8 @@ impl Default for App<'_> {
 #[allow(dead_code)]
 fn champion_color(id: Option<u8>) -> Color {
     match id {
-        Some(1) => Color::Red,
-        Some(2) => Color::Blue,
-        Some(3) => Color::Green,
-        Some(4) => Color::Yellow,
-        _ => Color::DarkGray,
+        Some(id) => ChampionColor::for_id(id).into(),
+        None => Color::DarkGray,
     }
 }
 
@@ -322,11 +1047,12 @@ fn render_memory_grid(
             } else if is_trail {
                 // Lighter shade of champion color for trail
                 match trail_map[idx] {
-                    Some(1) => Color::Rgb(100, 0, 0),
-                    Some(2) => Color::Rgb(0, 0, 100),
-                    Some(3) => Color::Rgb(0, 100, 0),
-                    Some(4) => Color::Rgb(100, 100, 0),
-                    _ => Color::DarkGray,
+                    Some(id) => {
+                        let (r, g, b) = ChampionColor::for_id(id).rgb();
+                        let dim = |c: u8| (u16::from(c) * 100 / 255) as u8;
+                        Color::Rgb(dim(r), dim(g), dim(b))
+                    }
+                    None => Color::DarkGray,
                 }
             } else {
                 champion_color(owner)
@@ -344,75 +1070,197 @@ fn render_memory_grid(
     lines
 }
 
-pub fn run_terminal_ui_with_vm(
+/// Run the terminal UI on an engine until the user quits
+///
+/// The engine should be started; the battle runs at the configured speed
+/// and stops at its cycle limit. The terminal is restored when the UI
+/// exits, on errors as well as on panics.
+///
+/// # Arguments
+/// * `engine` - The engine to visualize and control
+/// * `theme` - Theme to start in; the theme key also cycles the built-in ones
+///
+/// # Returns
+/// `Ok(())` when the user quits, or an error if the terminal or engine fails
+pub fn run_terminal_ui_with_vm(engine: &mut GameEngine, theme: Theme) -> io::Result<()> {
+    run_terminal_ui_until(engine, theme, || false).map(|_| ())
+}
+
+/// Run the terminal UI on an engine until the user quits or the caller
+/// wants a new battle
+///
+/// Like [`run_terminal_ui_with_vm`], but asks `restart` once a frame
+/// whether to stop, so the caller can replace the battle, as
+/// `corewar fight --watch` does when a warrior is saved.
+///
+/// # Arguments
+/// * `engine` - The engine to visualize and control
+/// * `theme` - Theme to start in; the theme key also cycles the built-in ones
+/// * `restart` - Returns true when the UI should stop for a new battle
+///
+/// # Returns
+/// `Ok(true)` when `restart` stopped the UI, `Ok(false)` when the user
+/// quit, or an error if the terminal or engine fails
+pub fn run_terminal_ui_until(
+    engine: &mut GameEngine,
+    theme: Theme,
+    restart: impl FnMut() -> bool,
+) -> io::Result<bool> {
+    crate::ui::initialize()?;
+    let result = run_event_loop(engine, theme, restart);
+    crate::ui::cleanup()?;
+    result
+}
+
+/// Render frames and handle keys until the user quits or `restart` says so
+fn run_event_loop(
     engine: &mut GameEngine,
-) -> io::Result<()> {
-    enable_raw_mode()?;
+    theme: Theme,
+    mut restart: impl FnMut() -> bool,
+) -> io::Result<bool> {
     let mut stdout = io::stdout();
     let backend = CrosstermBackend::new(&mut stdout);
     let mut terminal = Terminal::new(backend)?;
     let mut app = App::new(engine);
+    app.set_theme(theme);
 
-    loop {
+    while !app.should_quit {
+        if restart() {
+            return Ok(true);
+        }
+        let frame_start = Instant::now();
         terminal.draw(|f| {
             app.render(f).unwrap();
         })?;
+        if !app.paused {
+            app.update()?;
+        }
 
-        // Input handling
-        if event::poll(Duration::from_millis(0))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => {
-                        app.quit();
-                    }
-                    KeyCode::Char(' ') => {
-                        app.toggle_pause();
-                    }
-                    KeyCode::Char('+') => {
-                        app.increase_speed();
-                    }
-                    KeyCode::Char('-') => {
-                        app.decrease_speed();
-                    }
-                    KeyCode::Char('d') => {
-                        app.toggle_debug();
-                    }
-                    KeyCode::Char('1') => {
-                        app.set_view_mode(ViewMode::Normal);
-                    }
-                    KeyCode::Char('s') => {
-                        app.step()?;
-                    }
-                    KeyCode::Char('p') => {
-                        // Cycle through processes
-                        let processes = app.engine.processes();
-                        if processes.is_empty() {
-                            app.selected_process_id = None;
-                        } else {
-                            let current_idx = app.selected_process_id
-                                .and_then(|id| processes.iter().position(|p| p.id == id))
-                                .unwrap_or(processes.len() - 1);
-                            let next_idx = (current_idx + 1) % processes.len();
-                            app.selected_process_id = Some(processes[next_idx].id);
-                        }
-                    }
-                    _ => {}
-                }
+        // Wait out the rest of the frame for keys, handling each as it comes
+        while !app.should_quit {
+            let rest = FRAME_INTERVAL.saturating_sub(frame_start.elapsed());
+            if !event::poll(rest)? {
+                break;
+            }
+            if let Event::Key(key) = event::read()?
+                && !app.address_input_key(key.code)
+            {
+                handle_key(&mut app, &mut terminal, key.code)?;
             }
         }
-        if !app.paused {
-            app.update()?;
+    }
+    Ok(false)
+}
+
+/// Act on a key pressed outside the address prompt
+fn handle_key<B: Backend>(
+    app: &mut App,
+    terminal: &mut Terminal<B>,
+    code: KeyCode,
+) -> io::Result<()> {
+    match code {
+        KeyCode::Char('q') => {
+            app.quit();
+        }
+        KeyCode::Char(' ') => {
+            app.toggle_pause();
+        }
+        KeyCode::Char('+') => {
+            app.increase_speed();
+        }
+        KeyCode::Char('-') => {
+            app.decrease_speed();
+        }
+        KeyCode::Char('d') => {
+            app.toggle_debug();
+        }
+        KeyCode::Char('1') => {
+            app.set_view_mode(ViewMode::Normal);
+        }
+        KeyCode::Char('s') => {
+            app.step()?;
+        }
+        KeyCode::Up => {
+            app.navigate(-1);
+        }
+        KeyCode::Down => {
+            app.navigate(1);
+        }
+        KeyCode::Char('o') => {
+            app.cycle_process_sort();
+        }
+        KeyCode::Enter => {
+            app.select_process_at_cursor();
+        }
+        KeyCode::PageUp => {
+            app.page_memory(-1);
+        }
+        KeyCode::PageDown => {
+            app.page_memory(1);
+        }
+        KeyCode::Char('g') => {
+            app.begin_address_jump();
+        }
+        KeyCode::Char('b') => {
+            app.rewind();
+        }
+        KeyCode::Char('w') => {
+            app.toggle_branch();
+        }
+        KeyCode::Char('[') => {
+            app.seek(ReplaySeek::Back);
+        }
+        KeyCode::Char(']') => {
+            app.seek(ReplaySeek::Forward);
+        }
+        KeyCode::Char('n') => {
+            app.seek(ReplaySeek::NextDeath);
+        }
+        KeyCode::End => {
+            app.seek(ReplaySeek::End);
         }
-        if app.should_quit {
-            break;
+        KeyCode::Char('k') => {
+            app.kill_selected_process();
         }
-        // Apply speed control for visual mode
-        if app.speed > 0 {
-            let delay = Duration::from_millis(1000 / app.speed as u64);
-            std::thread::sleep(delay);
+        KeyCode::Char('x') => {
+            app.poke_selected_process();
         }
+        KeyCode::Char('e') => {
+            // Hand the terminal to the editor until it exits
+            disable_raw_mode()?;
+            app.edit_champion();
+            enable_raw_mode()?;
+            terminal.clear()?;
+        }
+        KeyCode::Char('p') => {
+            app.toggle_process_focus();
+        }
+        KeyCode::Char('t') => {
+            app.next_theme();
+        }
+        KeyCode::Char('D') => {
+            app.toggle_pause_on_death();
+        }
+        KeyCode::Char('E') => {
+            app.toggle_pause_on_elimination();
+        }
+        KeyCode::Char('W') => {
+            app.begin_watch_region();
+        }
+        KeyCode::Char('z') => {
+            app.cycle_density();
+        }
+        KeyCode::Char('i') => {
+            app.export_image();
+        }
+        KeyCode::Char('f') => {
+            app.toggle_follow();
+        }
+        KeyCode::Tab => {
+            app.follow_next_process();
+        }
+        _ => {}
     }
-    disable_raw_mode()?;
     Ok(())
 }
 
@@ -426,7 +1274,7 @@ mod tests {
         let app = App::new(&mut engine);
         assert!(!app.should_quit);
         assert!(!app.paused);
-        assert_eq!(app.speed, 1);
+        assert_eq!(app.speed, Speed::Fast(1));
         assert!(!app.debug_mode);
         assert_eq!(app.view_mode, ViewMode::Normal);
     }
@@ -444,11 +1292,14 @@ mod tests {
 
         // Test speed controls
         app.increase_speed();
-        assert_eq!(app.speed, 2);
+        assert_eq!(app.speed, Speed::Fast(2));
         app.increase_speed();
-        assert_eq!(app.speed, 4);
+        assert_eq!(app.speed, Speed::Fast(4));
+        app.decrease_speed();
+        assert_eq!(app.speed, Speed::Fast(2));
         app.decrease_speed();
-        assert_eq!(app.speed, 2);
+        app.decrease_speed();
+        assert_eq!(app.speed, Speed::Slow(2));
 
         // Test debug toggle
         app.toggle_debug();
@@ -473,6 +1324,121 @@ mod tests {
         assert_eq!(app.selected_address, None);
     }
 
+    #[test]
+    fn test_memory_scrolling_and_address_jump() {
+        let mut engine = GameEngine::new(Default::default());
+        let mut app = App::new(&mut engine);
+
+        app.page_memory(1);
+        app.scroll_memory(2);
+        assert_eq!(app.advanced_memory.viewport().top, 22);
+
+        // Keys go to the prompt while it is open
+        assert!(!app.address_input_key(KeyCode::Char('1')));
+        app.begin_address_jump();
+        for c in "0x4009".chars() {
+            assert!(app.address_input_key(KeyCode::Char(c)));
+        }
+        app.address_input_key(KeyCode::Backspace);
+        app.address_input_key(KeyCode::Enter);
+        assert_eq!(app.address_input, None);
+        assert_eq!(app.selected_address, Some(0x400));
+        assert_eq!(app.advanced_memory.viewport().first_address(), 0x400);
+
+        app.begin_address_jump();
+        app.address_input_key(KeyCode::Char('z'));
+        app.address_input_key(KeyCode::Enter);
+        assert_eq!(app.status.as_deref(), Some("Not an address: z"));
+
+        app.cycle_density();
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Memory: half blocks, 2 bytes per cell")
+        );
+        app.cycle_density();
+        app.cycle_density();
+        assert_eq!(
+            app.advanced_memory.viewport().density,
+            viewport::Density::Hex
+        );
+    }
+
+    #[test]
+    fn test_process_table_selects_a_process() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .build()
+            .unwrap();
+        let mut app = App::new(&mut engine);
+        assert!(!app.select_process_at_cursor());
+
+        app.toggle_process_focus();
+        assert_eq!(app.focus, Focus::Processes);
+        app.navigate(1);
+        app.navigate(1);
+        let second = app.engine.process(2).unwrap().pc;
+        assert!(app.select_process_at_cursor());
+        assert_eq!(app.selected_process_id, Some(2));
+        assert_eq!(app.selected_address, Some(second));
+        assert_eq!(app.advanced_memory.viewport().first_address(), second);
+
+        // Arrows scroll memory again once the focus moves back
+        app.toggle_process_focus();
+        app.scroll_memory(-100);
+        app.navigate(1);
+        assert_eq!(app.advanced_memory.viewport().top, 1);
+        assert_eq!(app.process_list.cursor, Some(2));
+    }
+
+    #[test]
+    fn test_app_follows_a_process() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .build()
+            .unwrap();
+        let mut app = App::new(&mut engine);
+        app.toggle_follow();
+        assert_eq!(app.following, Some(1));
+        assert_eq!(app.selected_process_id, Some(1));
+
+        // The second process's PC lands on the middle row
+        app.follow_next_process();
+        let second = app.engine.process(2).unwrap().pc;
+        let viewport = app.advanced_memory.viewport();
+        assert_eq!(app.following, Some(2));
+        assert_eq!(app.selected_address, Some(second));
+        assert_eq!(viewport.position(second).unwrap().1, viewport.rows / 2);
+
+        app.follow_next_process();
+        assert_eq!(app.following, Some(1));
+        app.toggle_follow();
+        assert_eq!(app.following, None);
+        assert_eq!(app.status.as_deref(), Some("Stopped following"));
+    }
+
+    #[test]
+    fn test_app_switches_themes() {
+        let mut engine = GameEngine::new(Default::default());
+        let mut app = App::new(&mut engine);
+        assert_eq!(app.theme().name, "default");
+
+        app.next_theme();
+        assert_eq!(app.theme().name, "dark");
+        assert_eq!(app.status.as_deref(), Some("Theme: dark"));
+
+        // A theme file's theme joins the cycle after the built-in ones
+        let custom = Theme::from_json(r#"{"name": "mine", "base": "light"}"#).unwrap();
+        app.set_theme(custom);
+        assert_eq!(app.theme().name, "mine");
+        app.next_theme();
+        assert_eq!(app.theme().name, "default");
+        app.set_theme(Theme::builtin("high-contrast").unwrap());
+        app.next_theme();
+        assert_eq!(app.theme().name, "mine");
+    }
+
     #[test]
     fn test_app_update_calls_engine_tick() {
         let mut engine = GameEngine::new(Default::default());
@@ -489,4 +1455,234 @@ mod tests {
         app.update().unwrap();
         assert_eq!(app.engine.get_stats().cycle, initial_cycles + 1);
     }
+
+    #[test]
+    fn test_app_update_runs_speed_cycles_per_frame() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .build()
+            .unwrap();
+        engine.start().unwrap();
+        let mut app = App::new(&mut engine);
+
+        app.speed = Speed::Fast(8);
+        app.update().unwrap();
+        assert_eq!(app.engine.get_stats().cycle, 8);
+
+        // Slow motion runs one cycle, then waits out the other frames
+        app.speed = Speed::Slow(3);
+        app.frame = 0;
+        for _ in 0..6 {
+            app.update().unwrap();
+        }
+        assert_eq!(app.engine.get_stats().cycle, 10);
+    }
+
+    #[test]
+    fn test_app_update_keeps_to_the_frame_budget() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .build()
+            .unwrap();
+        engine.start().unwrap();
+        let mut app = App::new(&mut engine);
+
+        // With no time to spare a frame still makes progress
+        app.speed = Speed::Fast(100);
+        app.update_within(Duration::ZERO).unwrap();
+        assert_eq!(app.frame_cycles, 1);
+        assert_eq!(app.engine.get_stats().cycle, 1);
+
+        app.update_within(Duration::from_secs(60)).unwrap();
+        assert_eq!(app.frame_cycles, 100);
+        assert_eq!(app.engine.get_stats().cycle, 101);
+    }
+
+    #[test]
+    fn test_app_pauses_on_events() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Broken", [0xFF; 100])
+            // Keeps the battle going once the broken champion is out
+            .champion_bytes("Third", [0x01; 100])
+            .build()
+            .unwrap();
+        engine.start().unwrap();
+        let mut app = App::new(&mut engine);
+        app.speed = Speed::Fast(100);
+
+        app.toggle_pause_on_death();
+        app.begin_watch_region();
+        for c in "0x10..0x20".chars() {
+            app.address_input_key(KeyCode::Char(c));
+        }
+        app.address_input_key(KeyCode::Enter);
+        assert_eq!(app.pause_on.region, Some(0x10..0x20));
+
+        // The broken champion's process dies long before the frame ends
+        app.update_within(Duration::from_secs(60)).unwrap();
+        assert!(app.paused);
+        assert!(app.frame_cycles < 100);
+        let status = app.status.as_deref().unwrap();
+        assert!(status.starts_with("Paused: process 2 died"));
+        let cycle = app.engine.get_stats().cycle;
+        let death = app
+            .event_log
+            .entries()
+            .iter()
+            .find(|e| e.text.contains("died"));
+        assert_eq!(death.map(|e| (e.cycle, e.champion_id)), Some((cycle, 2)));
+
+        app.begin_watch_region();
+        app.address_input_key(KeyCode::Enter);
+        assert_eq!(app.pause_on.region, None);
+        app.begin_watch_region();
+        app.address_input_key(KeyCode::Char('z'));
+        app.address_input_key(KeyCode::Enter);
+        assert_eq!(app.status.as_deref(), Some("Not a region of the core: z"));
+    }
+
+    #[test]
+    fn test_app_honours_pause_and_cycle_limit() {
+        let config = crate::GameConfig {
+            start_paused: true,
+            max_cycles: 5,
+            speed: 4,
+            ..Default::default()
+        };
+        let mut engine = GameEngine::builder()
+            .config(config)
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .build()
+            .unwrap();
+        engine.start().unwrap();
+        let mut app = App::new(&mut engine);
+        assert!(app.paused);
+        assert_eq!(app.speed, Speed::Fast(4));
+
+        // Paused, the app steps one cycle at a time
+        app.update().unwrap();
+        app.step().unwrap();
+        assert_eq!(app.engine.get_stats().cycle, 1);
+
+        app.toggle_pause();
+        app.update().unwrap();
+        app.update().unwrap();
+        assert_eq!(app.engine.get_stats().cycle, 5);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Battle over at cycle 5: First wins")
+        );
+        assert_eq!(app.engine.state().winner, Some(1));
+    }
+
+    #[test]
+    fn test_app_rewind_pauses_and_steps_back() {
+        let mut engine = GameEngine::new(Default::default());
+        let snapshot = engine.snapshot();
+        engine.restore(snapshot).unwrap(); // Records a checkpoint at cycle 0
+        engine.set_running(true);
+        let mut app = App::new(&mut engine);
+
+        app.update().unwrap();
+        assert_eq!(app.engine.get_stats().cycle, 1);
+
+        assert!(app.rewind());
+        assert!(app.paused);
+        assert_eq!(app.engine.get_stats().cycle, 0);
+    }
+
+    #[test]
+    fn test_app_branch_mode_kills_and_discards() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .build()
+            .unwrap();
+        let mut app = App::new(&mut engine);
+        app.selected_process_id = Some(1);
+        assert!(!app.kill_selected_process());
+
+        assert!(app.toggle_branch());
+        assert!(app.paused);
+        assert!(app.poke_selected_process());
+        assert!(app.kill_selected_process());
+        assert_eq!(app.engine.processes().len(), 1);
+
+        assert!(app.toggle_branch());
+        assert!(!app.engine.is_branched());
+        assert_eq!(app.engine.processes().len(), 2);
+        assert_eq!(app.engine.memory().read_byte(0), 0x01);
+    }
+
+    #[cfg(feature = "event-protocol")]
+    #[test]
+    fn test_app_seeks_along_a_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("battle.cwrec");
+        let mut engine = GameEngine::builder()
+            .max_cycles(250)
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .build()
+            .unwrap();
+        engine.record(Box::new(std::fs::File::create(&path).unwrap())).unwrap();
+        engine.run_to_completion().unwrap();
+        engine.finish_recording().unwrap();
+        let length = engine.get_stats().cycle;
+
+        let recording = crate::vm::Recording::read(&path).unwrap();
+        let mut replay = GameEngine::replay(recording).unwrap();
+        replay.start().unwrap();
+        let mut app = App::new(&mut replay);
+
+        assert!(app.seek(ReplaySeek::Forward));
+        assert_eq!(app.engine.replay_progress(), Some((100, length)));
+        assert!(app.seek(ReplaySeek::End));
+        assert_eq!(app.engine.replay_progress(), Some((length, length)));
+        assert!(app.seek(ReplaySeek::Back));
+        assert_eq!(app.engine.get_stats().cycle, length - 100);
+        // The death check at the end kills the processes
+        assert!(app.seek(ReplaySeek::NextDeath));
+        assert_eq!(app.engine.replay_progress(), Some((length, length)));
+        assert!(!app.seek(ReplaySeek::NextDeath));
+        assert!(app.status.as_ref().unwrap().starts_with("Seek failed"));
+
+        let mut engine = GameEngine::new(Default::default());
+        assert!(!App::new(&mut engine).seek(ReplaySeek::Forward));
+    }
+
+    #[test]
+    fn test_app_reload_champion_restarts_round() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("looper.s");
+        let output = dir.path().join("looper.cor");
+        std::fs::write(&source, ".name \"Looper\"\n.comment \"\"\nstart: live %1\n").unwrap();
+        editor::assemble(&source, &output).unwrap();
+
+        let mut engine = GameEngine::new(Default::default());
+        engine.load_champions(&[&output], None).unwrap();
+        let original = engine.champions()[0].code.clone();
+        let mut app = App::new(&mut engine);
+        app.engine.start().unwrap();
+        app.update().unwrap();
+
+        std::fs::write(&source, "start: bogus\n").unwrap();
+        assert!(!app.reload_champion(&source, &output));
+        assert!(app.status.as_ref().unwrap().starts_with("Assembly failed"));
+        assert_eq!(app.engine.get_stats().cycle, 1);
+
+        std::fs::write(
+            &source,
+            ".name \"Looper\"\n.comment \"\"\nstart: live %1\nzjmp %:start\n",
+        )
+        .unwrap();
+        assert!(app.reload_champion(&source, &output));
+        assert_eq!(app.engine.get_stats().cycle, 0);
+        assert!(app.engine.get_stats().running);
+        assert_ne!(app.engine.champions()[0].code, original);
+    }
 }
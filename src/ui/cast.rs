@@ -0,0 +1,254 @@
+/// Battle recordings as asciinema casts
+///
+/// A battle runs without a terminal while the visualization is drawn to an
+/// off-screen buffer every few cycles. Each drawing becomes one frame of an
+/// asciinema v2 `.cast` file: the buffer's cells as text with ANSI color
+/// codes, played back [`FRAME_DELAY`] seconds apart. Anyone with `asciinema
+/// play`, or the web player, can then watch the battle as it looked in the
+/// terminal UI.
+use crate::GameEngine;
+use crate::error::Result;
+use crate::ui::app::App;
+use crate::ui::theme::Theme;
+use crate::vm::pacing::Speed;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use std::io::Write;
+use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
+
+/// Seconds between frames when the cast plays
+pub const FRAME_DELAY: f64 = 0.1;
+
+/// Columns and rows of the terminal a cast is drawn for
+pub const CAST_SIZE: (u16, u16) = (160, 50);
+
+/// Writes frames to an asciinema v2 cast
+pub struct CastWriter<W: Write> {
+    /// Where the cast goes
+    out: W,
+    /// Frames written so far
+    frames: u32,
+}
+
+impl<W: Write> CastWriter<W> {
+    /// Start a cast by writing its header
+    ///
+    /// # Arguments
+    /// * `out` - Where the cast goes
+    /// * `width` - Columns of the recorded terminal
+    /// * `height` - Rows of the recorded terminal
+    pub fn new(mut out: W, width: u16, height: u16) -> Result<Self> {
+        let header = serde_json::json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "title": "Core War battle",
+        });
+        writeln!(out, "{}", header)?;
+        Ok(Self { out, frames: 0 })
+    }
+
+    /// Write a drawing of the screen as the next frame
+    pub fn frame(&mut self, buffer: &Buffer) -> Result<()> {
+        let time = f64::from(self.frames) * FRAME_DELAY;
+        let event = serde_json::json!([time, "o", ansi(buffer)]);
+        writeln!(self.out, "{}", event)?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Get the number of frames written
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Flush the cast and give back where it went
+    pub fn finish(mut self) -> Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Run a battle to its end, recording the visualization as a cast
+///
+/// The engine should be started. Breakpoints and the UI's pause triggers do
+/// not stop the recording.
+///
+/// # Arguments
+/// * `engine` - The battle to record
+/// * `theme` - Colors of the visualization
+/// * `interval` - Cycles between frames, at least 1
+/// * `out` - Where the cast goes
+///
+/// # Returns
+/// The number of frames recorded
+pub fn record<W: Write>(
+    engine: &mut GameEngine,
+    theme: Theme,
+    interval: u32,
+    out: W,
+) -> Result<u32> {
+    let (width, height) = CAST_SIZE;
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    let mut cast = CastWriter::new(out, width, height)?;
+    let mut app = App::new(engine);
+    app.set_theme(theme);
+    app.paused = false;
+    app.speed = Speed::Fast(interval.max(1));
+
+    loop {
+        let frame = terminal.draw(|f| {
+            // Rendering only fails on a broken engine, which the tick reports
+            let _ = app.render(f);
+        })?;
+        cast.frame(frame.buffer)?;
+        if !app.engine.state().running {
+            break;
+        }
+        // No frame budget: every frame covers the whole interval
+        app.update_within(Duration::from_secs(3600))?;
+        app.paused = false;
+    }
+    let frames = cast.frames();
+    cast.finish()?;
+    Ok(frames)
+}
+
+/// Turn a drawing of the screen into text with ANSI color codes
+///
+/// The text homes the cursor first, so each frame draws over the last.
+pub fn ansi(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut text = String::from("\x1b[H");
+    for y in area.top()..area.bottom() {
+        let mut style = None;
+        // Cells hidden under the wide character before them
+        let mut hidden = 0;
+        for x in area.left()..area.right() {
+            let cell = buffer.get(x, y);
+            if hidden > 0 {
+                hidden -= 1;
+                continue;
+            }
+            if cell.skip {
+                continue;
+            }
+            hidden = cell.symbol().width().saturating_sub(1);
+            let cell_style = (cell.fg, cell.bg, cell.modifier);
+            if style != Some(cell_style) {
+                text.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+                style = Some(cell_style);
+            }
+            text.push_str(cell.symbol());
+        }
+        text.push_str("\x1b[0m");
+        if y + 1 < area.bottom() {
+            text.push_str("\r\n");
+        }
+    }
+    text
+}
+
+/// Build the escape sequence that sets a cell's colors and weight
+fn sgr(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let mut codes = vec!["0".to_string()];
+    if modifier.contains(Modifier::BOLD) {
+        codes.push("1".to_string());
+    }
+    if modifier.contains(Modifier::REVERSED) {
+        codes.push("7".to_string());
+    }
+    codes.push(color_code(fg, false));
+    codes.push(color_code(bg, true));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// Get the SGR parameters of a foreground or background color
+fn color_code(color: Color, background: bool) -> String {
+    let base = if background { 40 } else { 30 };
+    let named = |index: u8| {
+        if index < 8 {
+            (base + index).to_string()
+        } else {
+            (base + 60 + index - 8).to_string()
+        }
+    };
+    match color {
+        Color::Reset => (base + 9).to_string(),
+        Color::Black => named(0),
+        Color::Red => named(1),
+        Color::Green => named(2),
+        Color::Yellow => named(3),
+        Color::Blue => named(4),
+        Color::Magenta => named(5),
+        Color::Cyan => named(6),
+        Color::Gray => named(7),
+        Color::DarkGray => named(8),
+        Color::LightRed => named(9),
+        Color::LightGreen => named(10),
+        Color::LightYellow => named(11),
+        Color::LightBlue => named(12),
+        Color::LightMagenta => named(13),
+        Color::LightCyan => named(14),
+        Color::White => named(15),
+        Color::Indexed(index) => format!("{};5;{}", base + 8, index),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    #[test]
+    fn test_ansi_frames() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 2));
+        buffer.set_string(0, 0, "ab", Style::default().fg(Color::Red));
+        buffer.set_string(0, 1, "🚀", Style::default().bg(Color::Rgb(1, 2, 3)));
+        assert_eq!(
+            ansi(&buffer),
+            "\x1b[H\x1b[0;31;49mab\x1b[0;39;49m \x1b[0m\r\n\
+             \x1b[0;39;48;2;1;2;3m🚀\x1b[0;39;49m \x1b[0m"
+        );
+        assert_eq!(color_code(Color::LightBlue, false), "94");
+        assert_eq!(color_code(Color::Indexed(200), true), "48;5;200");
+    }
+
+    #[test]
+    fn test_record_a_battle() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .max_cycles(250)
+            .build()
+            .unwrap();
+        engine.start().unwrap();
+        let cast = Vec::new();
+        let frames = record(&mut engine, Theme::default(), 100, cast).unwrap();
+        assert_eq!(frames, 4);
+        assert!(!engine.state().running);
+    }
+
+    #[test]
+    fn test_cast_layout() {
+        let mut cast = CastWriter::new(Vec::new(), 3, 1).unwrap();
+        let buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+        cast.frame(&buffer).unwrap();
+        cast.frame(&buffer).unwrap();
+        let text = String::from_utf8(cast.finish().unwrap()).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 3);
+        assert_eq!(lines[1][0], 0.0);
+        assert_eq!(lines[2][0], FRAME_DELAY);
+        assert_eq!(lines[2][1], "o");
+    }
+}
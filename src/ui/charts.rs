@@ -0,0 +1,236 @@
+/// Trend charts for the dashboard
+///
+/// The engine is sampled every few cycles for each champion's process count
+/// and owned memory, and for `cycle_to_die`. The samples are drawn as
+/// sparklines, one row per series, so the ebb and flow of a battle shows at
+/// a glance. Rewinding drops the samples of the cycles undone.
+use crate::GameEngine;
+use crate::ui::theme::Theme;
+use crate::vm::Champion;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::widgets::{Paragraph, Sparkline, Widget};
+use std::collections::VecDeque;
+
+/// Cycles between samples
+pub const SAMPLE_INTERVAL: u32 = 25;
+
+/// Samples kept; older ones scroll off the left of the charts
+pub const MAX_SAMPLES: usize = 256;
+
+/// Width of the labels before the sparklines
+const LABEL_WIDTH: u16 = 22;
+
+/// The engine's state at one cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    /// Cycle the sample was taken at
+    pub cycle: u32,
+    /// Live processes of each champion, by champion ID
+    pub processes: Vec<(u8, u64)>,
+    /// Bytes of memory each champion owns, by champion ID
+    pub owned: Vec<(u8, u64)>,
+    /// Cycles between death checks
+    pub cycle_to_die: u64,
+}
+
+impl Sample {
+    /// Sample an engine
+    pub fn take(engine: &GameEngine) -> Self {
+        let champions = engine.champions();
+        let mut processes: Vec<(u8, u64)> = champions.iter().map(|c| (c.id, 0)).collect();
+        for process in engine.processes() {
+            if let Some((_, count)) = processes
+                .iter_mut()
+                .find(|(id, _)| *id == process.champion_id)
+            {
+                *count += 1;
+            }
+        }
+        let mut owned: Vec<(u8, u64)> = champions.iter().map(|c| (c.id, 0)).collect();
+        for owner in engine.memory().ownership().iter().flatten() {
+            if let Some((_, count)) = owned.iter_mut().find(|(id, _)| id == owner) {
+                *count += 1;
+            }
+        }
+        Self {
+            cycle: engine.get_stats().cycle,
+            processes,
+            owned,
+            cycle_to_die: u64::from(engine.scheduler_stats().cycle_to_die),
+        }
+    }
+}
+
+/// Samples of a battle over time
+#[derive(Debug, Clone, Default)]
+pub struct Trends {
+    /// Samples, oldest first
+    samples: VecDeque<Sample>,
+}
+
+impl Trends {
+    /// Create an empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sample the engine if a sample is due
+    ///
+    /// Samples from cycles after the engine's, left by a rewind, are dropped
+    /// first.
+    ///
+    /// # Returns
+    /// Whether a sample was taken
+    pub fn record(&mut self, engine: &GameEngine) -> bool {
+        let cycle = engine.get_stats().cycle;
+        while self.samples.back().is_some_and(|s| s.cycle > cycle) {
+            self.samples.pop_back();
+        }
+        if self
+            .samples
+            .back()
+            .is_some_and(|s| cycle < s.cycle + SAMPLE_INTERVAL)
+        {
+            return false;
+        }
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample::take(engine));
+        true
+    }
+
+    /// Get the samples, oldest first
+    pub fn samples(&self) -> &VecDeque<Sample> {
+        &self.samples
+    }
+
+    /// Get the process counts of a champion, oldest first
+    pub fn processes(&self, champion_id: u8) -> Vec<u64> {
+        self.series(|s| value_of(&s.processes, champion_id))
+    }
+
+    /// Get the memory owned by a champion, oldest first
+    pub fn owned(&self, champion_id: u8) -> Vec<u64> {
+        self.series(|s| value_of(&s.owned, champion_id))
+    }
+
+    /// Get `cycle_to_die`, oldest first
+    pub fn cycle_to_die(&self) -> Vec<u64> {
+        self.series(|s| s.cycle_to_die)
+    }
+
+    /// Get the rows the charts take for a number of champions
+    pub fn height(champions: usize) -> u16 {
+        (champions * 2 + 1) as u16
+    }
+
+    /// Render a sparkline per series, each labelled with its latest value
+    ///
+    /// # Arguments
+    /// * `champions` - Champions to chart
+    /// * `theme` - Champion colors
+    /// * `area` - Area to draw in, [`Trends::height`] rows high
+    /// * `buf` - Buffer to draw to
+    pub fn render(&self, champions: &[Champion], theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let mut rows: Vec<(String, Vec<u64>, Style)> = Vec::new();
+        for champion in champions {
+            let style = Style::default().fg(theme.champion(champion.id));
+            let name: String = champion.display_name().chars().take(10).collect();
+            for (label, series) in [
+                ("procs", self.processes(champion.id)),
+                ("owned", self.owned(champion.id)),
+            ] {
+                let latest = series.last().copied().unwrap_or(0);
+                rows.push((format!("{:<10} {} {}", name, label, latest), series, style));
+            }
+        }
+        let series = self.cycle_to_die();
+        let latest = series.last().copied().unwrap_or(0);
+        let style = Style::default().fg(theme.accent);
+        rows.push((format!("cycle_to_die {}", latest), series, style));
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); rows.len()])
+            .split(area);
+        for ((label, series, style), row) in rows.iter().zip(chunks.iter()) {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(LABEL_WIDTH), Constraint::Min(0)])
+                .split(*row);
+            Paragraph::new(label.as_str())
+                .style(*style)
+                .render(columns[0], buf);
+            // The newest samples, as many as fit
+            let shown = &series[series.len().saturating_sub(usize::from(columns[1].width))..];
+            Sparkline::default()
+                .data(shown)
+                .style(*style)
+                .render(columns[1], buf);
+        }
+    }
+
+    /// Get one value from every sample, oldest first
+    fn series(&self, value: impl Fn(&Sample) -> u64) -> Vec<u64> {
+        self.samples.iter().map(value).collect()
+    }
+}
+
+/// Get a champion's value from a sample's per-champion values
+fn value_of(values: &[(u8, u64)], champion_id: u8) -> u64 {
+    values
+        .iter()
+        .find(|(id, _)| *id == champion_id)
+        .map_or(0, |&(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine() -> GameEngine {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 60])
+            .build()
+            .unwrap();
+        engine.start().unwrap();
+        engine
+    }
+
+    #[test]
+    fn test_samples() {
+        let engine = engine();
+        let sample = Sample::take(&engine);
+        assert_eq!(sample.cycle, 0);
+        assert_eq!(sample.processes, [(1, 1), (2, 1)]);
+        assert_eq!(sample.owned, [(1, 100), (2, 60)]);
+        assert_eq!(
+            sample.cycle_to_die,
+            u64::from(crate::constants::CYCLE_TO_DIE)
+        );
+    }
+
+    #[test]
+    fn test_record_every_interval() {
+        let mut engine = engine();
+        let mut trends = Trends::new();
+        for _ in 0..SAMPLE_INTERVAL * 2 {
+            trends.record(&engine);
+            engine.tick().unwrap();
+        }
+        assert!(trends.record(&engine));
+        let cycles: Vec<u32> = trends.samples().iter().map(|s| s.cycle).collect();
+        assert_eq!(cycles, [0, SAMPLE_INTERVAL, SAMPLE_INTERVAL * 2]);
+        assert_eq!(trends.processes(2), [1, 1, 1]);
+        assert_eq!(trends.owned(3), [0, 0, 0]);
+
+        // Samples of undone cycles go
+        engine.rewind(SAMPLE_INTERVAL + 1).unwrap();
+        assert!(!trends.record(&engine));
+        assert_eq!(trends.samples().len(), 1);
+    }
+}
@@ -137,7 +137,7 @@ impl Dashboard {
         println!("Champions: {}", champions.len());
 
         for champion in champions {
-            println!("  {}: {} processes", champion.name, champion.process_count);
+            println!("  {}: {} processes", champion.display_name(), champion.process_count);
         }
 
         Ok(())
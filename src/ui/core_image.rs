@@ -0,0 +1,265 @@
+/// PNG images of the core
+///
+/// Draws the whole core as a grid of square cells, one per byte, colored by
+/// the champion owning it and warmed towards the theme's heat colors by how
+/// often it was written. Bytes under a process's PC are white, as in the
+/// memory grid.
+use crate::error::{CoreWarError, Result};
+use crate::ui::theme::Theme;
+use crate::vm::{GameEvent, GameObserver, Memory};
+use image::{ImageError, Rgb, RgbImage};
+use ratatui::style::Color;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Bytes per row of the image
+pub const COLUMNS: usize = 64;
+
+/// Pixels along each side of a byte's cell
+pub const CELL_SIZE: usize = 8;
+
+/// Writes to a byte at which its cell takes the hottest heat color
+const HOTTEST: f32 = 16.0;
+
+/// Color of bytes nobody owns
+const UNOWNED: [u8; 3] = [24, 24, 24];
+
+/// An RGB image of the core
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreImage {
+    /// Width in pixels
+    pub width: usize,
+    /// Height in pixels
+    pub height: usize,
+    /// The pixels
+    pixels: RgbImage,
+}
+
+impl CoreImage {
+    /// Draw the core
+    ///
+    /// # Arguments
+    /// * `memory` - Memory to draw
+    /// * `pcs` - Addresses processes are at
+    /// * `heat` - Writes to each address
+    /// * `theme` - Champion and heat colors
+    pub fn render(
+        memory: &Memory,
+        pcs: &HashSet<usize>,
+        heat: &HashMap<usize, u32>,
+        theme: &Theme,
+    ) -> Self {
+        let width = COLUMNS * CELL_SIZE;
+        let height = memory.size().div_ceil(COLUMNS) * CELL_SIZE;
+        let mut image = Self {
+            width,
+            height,
+            pixels: RgbImage::new(width as u32, height as u32),
+        };
+        for address in 0..memory.size() {
+            let color = if pcs.contains(&address) {
+                [255, 255, 255]
+            } else {
+                let owner = memory
+                    .get_owner(address)
+                    .map_or(UNOWNED, |id| rgb(theme.champion(id)));
+                let writes = heat.get(&address).copied().unwrap_or(0);
+                let t = (writes as f32 / HOTTEST).min(1.0);
+                mix(owner, rgb(theme.heat.at(t)), t / 2.0)
+            };
+            image.fill(address, color);
+        }
+        image
+    }
+
+    /// Get the color of a pixel
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels.get_pixel(x as u32, y as u32).0
+    }
+
+    /// Write the image to a PNG file
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.pixels.save(path).map_err(|e| match e {
+            ImageError::IoError(e) => CoreWarError::Io(e),
+            e => CoreWarError::Io(io::Error::other(e)),
+        })
+    }
+
+    /// Color the cell of a byte
+    fn fill(&mut self, address: usize, color: [u8; 3]) {
+        let left = address % COLUMNS * CELL_SIZE;
+        let top = address / COLUMNS * CELL_SIZE;
+        for y in top..top + CELL_SIZE {
+            for x in left..left + CELL_SIZE {
+                self.pixels.put_pixel(x as u32, y as u32, Rgb(color));
+            }
+        }
+    }
+}
+
+/// Counts of writes to each address, shared with the engine as an observer
+///
+/// Subscribe a clone to the engine before the battle and read the counts
+/// from the original afterwards, for a headless [`CoreImage`].
+#[derive(Debug, Clone, Default)]
+pub struct WriteHeat {
+    counts: Arc<Mutex<HashMap<usize, u32>>>,
+}
+
+impl WriteHeat {
+    /// Create a counter with no writes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the writes counted so far, by address
+    pub fn counts(&self) -> HashMap<usize, u32> {
+        self.counts.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+}
+
+impl GameObserver for WriteHeat {
+    fn on_event(&mut self, event: &GameEvent) {
+        let Ok(mut counts) = self.counts.lock() else {
+            return;
+        };
+        match *event {
+            GameEvent::MemoryWrite { address, .. } => *counts.entry(address).or_insert(0) += 1,
+            // Heat describes a future that no longer happened
+            GameEvent::Rewound { .. } => counts.clear(),
+            _ => {}
+        }
+    }
+}
+
+/// Get the RGB value of a terminal color
+///
+/// Named colors take the usual xterm values; indexed colors follow the
+/// xterm 256-color palette.
+pub fn rgb(color: Color) -> [u8; 3] {
+    const BASIC: [[u8; 3]; 16] = [
+        [0, 0, 0],
+        [205, 0, 0],
+        [0, 205, 0],
+        [205, 205, 0],
+        [0, 0, 238],
+        [205, 0, 205],
+        [0, 205, 205],
+        [229, 229, 229],
+        [127, 127, 127],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [92, 92, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+    let index = match color {
+        Color::Rgb(r, g, b) => return [r, g, b],
+        Color::Reset | Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    };
+    match index {
+        0..=15 => BASIC[usize::from(index)],
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            [level(n / 36), level(n / 6 % 6), level(n % 6)]
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            [gray, gray, gray]
+        }
+    }
+}
+
+/// Blend two colors
+fn mix(from: [u8; 3], to: [u8; 3], t: f32) -> [u8; 3] {
+    let channel =
+        |i: usize| (f32::from(from[i]) + (f32::from(to[i]) - f32::from(from[i])) * t) as u8;
+    [channel(0), channel(1), channel(2)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_colors_owners_heat_and_pcs() {
+        let mut memory = Memory::new();
+        memory.load_code(0, &[1, 2, 3], 1).unwrap();
+        let theme = Theme::default();
+        let heat = HashMap::from([(1, 1000)]);
+        let image = CoreImage::render(&memory, &HashSet::from([2]), &heat, &theme);
+        assert_eq!(image.width, COLUMNS * CELL_SIZE);
+        assert_eq!(image.height, memory.size() / COLUMNS * CELL_SIZE);
+
+        let owner = rgb(theme.champion(1));
+        assert_eq!(image.pixel(0, 0), owner);
+        assert_eq!(image.pixel(CELL_SIZE - 1, CELL_SIZE - 1), owner);
+        let hottest = rgb(theme.heat.at(1.0));
+        assert_eq!(image.pixel(CELL_SIZE, 0), mix(owner, hottest, 0.5));
+        assert_eq!(image.pixel(2 * CELL_SIZE, 0), [255, 255, 255]);
+        assert_eq!(image.pixel(3 * CELL_SIZE, 0), UNOWNED);
+    }
+
+    #[test]
+    fn test_write_saves_a_png() {
+        let mut memory = Memory::new();
+        memory.load_code(0, &[1], 1).unwrap();
+        let core = CoreImage::render(&memory, &HashSet::new(), &HashMap::new(), &Theme::default());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("core.png");
+        core.write(&path).unwrap();
+
+        let png = std::fs::read(&path).unwrap();
+        assert_eq!(
+            png[..8],
+            [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']
+        );
+        let decoded = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(
+            decoded.dimensions(),
+            (core.width as u32, core.height as u32)
+        );
+        assert_eq!(decoded.get_pixel(0, 0).0, core.pixel(0, 0));
+        assert_eq!(decoded.get_pixel(CELL_SIZE as u32, 0).0, UNOWNED);
+    }
+
+    #[test]
+    fn test_write_heat_counts_writes() {
+        let mut heat = WriteHeat::new();
+        let write = GameEvent::MemoryWrite {
+            address: 9,
+            value: 0,
+            champion_id: 1,
+            process_id: 1,
+            opcode: 3,
+            pc: 0,
+        };
+        let mut observer = heat.clone();
+        observer.on_event(&write);
+        observer.on_event(&write);
+        assert_eq!(heat.counts(), HashMap::from([(9, 2)]));
+        heat.on_event(&GameEvent::Rewound { cycle: 0 });
+        assert!(heat.counts().is_empty());
+    }
+}
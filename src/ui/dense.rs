@@ -0,0 +1,158 @@
+/// Dense memory grid rows
+///
+/// Draws the rows of the memory grid in the dense modes of
+/// [`Density`]: half blocks color the upper and lower half of a character
+/// by the owners of two bytes, and braille lights a dot for each owned byte
+/// of eight, colored by the champion owning most of them. A byte under a
+/// process's PC is drawn in white in either mode.
+use crate::ui::theme::Theme;
+use crate::ui::viewport::{Density, Viewport};
+use crate::vm::Memory;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use std::collections::HashSet;
+
+/// Bit of the braille dot for each byte of a cell, by row then column
+const BRAILLE_DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Build one row of a dense memory grid
+///
+/// # Arguments
+/// * `memory` - Memory to draw
+/// * `pcs` - Addresses processes are at
+/// * `viewport` - Part of the core shown, fitted to a dense [`Density`]
+/// * `theme` - Champion colors
+/// * `row` - Row of characters, from the top of the grid
+///
+/// # Returns
+/// The address of the row's first byte followed by its cells
+pub fn line(
+    memory: &Memory,
+    pcs: &HashSet<usize>,
+    viewport: &Viewport,
+    theme: &Theme,
+    row: usize,
+) -> Line<'static> {
+    let (across, down) = viewport.density.cell();
+    let first_row = viewport.top + row * down;
+    let color = |col: usize, byte_row: usize| -> Option<Color> {
+        let address = byte_row * viewport.columns + col;
+        if col >= viewport.columns || address >= memory.size() {
+            return None;
+        }
+        if pcs.contains(&address) {
+            return Some(Color::White);
+        }
+        memory.get_owner(address).map(|owner| theme.champion(owner))
+    };
+
+    let mut spans = vec![Span::styled(
+        format!("{:04X}: ", first_row * viewport.columns),
+        Style::default().fg(theme.dim),
+    )];
+    for col in (0..viewport.columns).step_by(across) {
+        let span = match viewport.density {
+            Density::HalfBlock => half_block(color(col, first_row), color(col, first_row + 1)),
+            Density::Braille | Density::Hex => {
+                let colors: Vec<[Option<Color>; 2]> = (0..4)
+                    .map(|dy| [color(col, first_row + dy), color(col + 1, first_row + dy)])
+                    .collect();
+                braille(&colors)
+            }
+        };
+        spans.push(span);
+    }
+    Line::from(spans)
+}
+
+/// Draw two bytes, one above the other, in one character
+///
+/// # Arguments
+/// * `top` - Color of the upper byte, or None if it is not owned
+/// * `bottom` - Color of the lower byte, or None if it is not owned
+pub fn half_block(top: Option<Color>, bottom: Option<Color>) -> Span<'static> {
+    match (top, bottom) {
+        (Some(top), Some(bottom)) => Span::styled("▀", Style::default().fg(top).bg(bottom)),
+        (Some(top), None) => Span::styled("▀", Style::default().fg(top)),
+        (None, Some(bottom)) => Span::styled("▄", Style::default().fg(bottom)),
+        (None, None) => Span::raw(" "),
+    }
+}
+
+/// Draw up to eight bytes, two across by four down, in one character
+///
+/// # Arguments
+/// * `colors` - Colors of the bytes by row then column, None where not owned
+pub fn braille(colors: &[[Option<Color>; 2]]) -> Span<'static> {
+    let mut dots = 0u32;
+    let mut counts: Vec<(Color, usize)> = Vec::new();
+    for (row, pair) in colors.iter().take(4).enumerate() {
+        for (col, color) in pair.iter().enumerate() {
+            let Some(color) = *color else {
+                continue;
+            };
+            dots |= u32::from(BRAILLE_DOTS[row][col]);
+            match counts.iter_mut().find(|(c, _)| *c == color) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((color, 1)),
+            }
+        }
+    }
+    // A PC stands out over any owner; otherwise the first of the largest wins
+    let color = if counts.iter().any(|&(c, _)| c == Color::White) {
+        Some(Color::White)
+    } else {
+        counts
+            .iter()
+            .rev()
+            .max_by_key(|&&(_, count)| count)
+            .map(|&(c, _)| c)
+    };
+    let symbol = char::from_u32(0x2800 + dots).unwrap_or(' ').to_string();
+    match color {
+        Some(color) => Span::styled(symbol, Style::default().fg(color)),
+        None => Span::raw(symbol),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells() {
+        assert_eq!(half_block(None, None).content, " ");
+        let span = half_block(None, Some(Color::Blue));
+        assert_eq!(
+            (span.content.as_ref(), span.style.fg),
+            ("▄", Some(Color::Blue))
+        );
+        let span = half_block(Some(Color::Red), Some(Color::Blue));
+        assert_eq!(span.style.bg, Some(Color::Blue));
+
+        let (red, blue) = (Some(Color::Red), Some(Color::Blue));
+        let span = braille(&[[red, None], [None, blue], [None, None], [blue, None]]);
+        assert_eq!(span.content, "⡑");
+        assert_eq!(span.style.fg, Some(Color::Blue));
+        let span = braille(&[[red, Some(Color::White)]]);
+        assert_eq!(span.style.fg, Some(Color::White));
+        assert_eq!(braille(&[]).content, "⠀");
+    }
+
+    #[test]
+    fn test_line() {
+        let mut memory = Memory::new();
+        memory.load_code(4, &[1, 2, 3], 2).unwrap();
+        let mut viewport = Viewport::new(memory.size());
+        viewport.density = Density::Braille;
+        viewport.fit(10, 10, memory.size());
+        assert_eq!(viewport.columns, 8);
+
+        // Bytes 4-6 on the first row of bytes, with a process at 5
+        let pcs = HashSet::from([5]);
+        let line = line(&memory, &pcs, &viewport, &Theme::default(), 0);
+        assert_eq!(line.to_string(), "0000: ⠀⠀⠉⠁");
+        assert_eq!(line.spans[3].style.fg, Some(Color::White));
+        assert_eq!(line.spans[4].style.fg, Some(Color::Blue));
+    }
+}
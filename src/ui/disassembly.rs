@@ -0,0 +1,96 @@
+/// Disassembly pane following the selected process
+///
+/// The pane decodes the memory around the selected process's PC every
+/// frame, so it shows the code as it is now, including whatever other
+/// champions have written over it. The instruction at the PC is highlighted,
+/// with the cycles left before the process executes it.
+use crate::assembler::disassembler;
+use crate::vm::{Memory, Process};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+/// Most instructions shown before the PC
+pub const LINES_BEFORE: usize = 4;
+
+/// Instructions shown after the PC
+pub const LINES_AFTER: usize = 8;
+
+/// Get the pane's lines for a process
+///
+/// # Arguments
+/// * `memory` - Memory the process runs in
+/// * `process` - The process to follow
+///
+/// # Returns
+/// One line per instruction or undecodable byte, the one at the PC highlighted
+pub fn lines(memory: &Memory, process: &Process) -> Vec<Line<'static>> {
+    let pc = process.pc % memory.size();
+    disassembler::disassemble_memory(memory, pc, LINES_BEFORE, LINES_AFTER)
+        .into_iter()
+        .map(|line| {
+            let text = format!("{:04X}  {}", line.address, line.text);
+            if line.address == pc {
+                let current = Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD);
+                Line::from(vec![
+                    Span::styled(format!("> {}", text), current),
+                    Span::styled(
+                        format!("  {}", countdown(process.wait_cycles)),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                ])
+            } else {
+                Line::from(Span::styled(
+                    format!("  {}", text),
+                    Style::default().fg(Color::Gray),
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Describe when a process executes the instruction at its PC
+///
+/// Wait cycles count down at the start of each cycle, and the process
+/// executes once they reach zero.
+pub fn countdown(wait_cycles: u32) -> String {
+    match wait_cycles {
+        0 | 1 => "runs next cycle".to_string(),
+        cycles => format!("runs in {} cycles", cycles),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::Assembler;
+    use crate::vm::ChampionColor;
+    use crate::vm::loader::HEADER_SIZE;
+
+    #[test]
+    fn test_lines_highlight_the_pc() {
+        let bytecode = Assembler::new(false)
+            .assemble_source(".name \"x\"\nlive %1\nld %0, r2\nzjmp %-13\n")
+            .unwrap();
+        let mut memory = Memory::new();
+        memory.load_code(100, &bytecode[HEADER_SIZE..], 1).unwrap();
+        let mut process = Process::new(1, 1, 105, ChampionColor::for_id(1));
+        process.wait_cycles = 4;
+
+        let lines = lines(&memory, &process);
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(text[0], "  0064  live %1");
+        assert_eq!(text[1], "> 0069  ld %0, r2  runs in 4 cycles");
+        assert_eq!(text[2], "  0070  zjmp %-13");
+        assert_eq!(lines.len(), 2 + LINES_AFTER);
+    }
+
+    #[test]
+    fn test_countdown() {
+        assert_eq!(countdown(0), "runs next cycle");
+        assert_eq!(countdown(1), "runs next cycle");
+        assert_eq!(countdown(20), "runs in 20 cycles");
+    }
+}
@@ -0,0 +1,125 @@
+/// Champion editing from visual mode
+///
+/// Visual mode hands the terminal to the user's editor to change a
+/// champion's Redcode source, then reassembles it next to the loaded .cor
+/// file so the battle can restart with the new code. The source is the
+/// `.s` file beside the `.cor`, the layout the assembler produces by default.
+use crate::Assembler;
+use crate::error::{CoreWarError, Result};
+use crate::vm::Champion;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Find the Redcode source of a loaded champion
+///
+/// # Arguments
+/// * `champion` - A champion loaded from a .cor file
+///
+/// # Returns
+/// The `.s` file beside the champion's .cor file, if the champion came
+/// from a file and the source exists
+pub fn source_path(champion: &Champion) -> Option<PathBuf> {
+    let source = champion.source.as_ref()?.with_extension("s");
+    source.is_file().then_some(source)
+}
+
+/// Get the command that opens the user's editor
+///
+/// `$VISUAL` is preferred over `$EDITOR`, as most tools do. The value may
+/// carry arguments, e.g. `code --wait`.
+///
+/// # Returns
+/// The program followed by its arguments
+pub fn editor_command() -> Vec<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .map(|value| {
+            value
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .find(|command| !command.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_EDITOR.to_string()])
+}
+
+/// Open a file in the user's editor and wait for it to exit
+///
+/// The terminal must be out of raw mode while the editor runs.
+///
+/// # Arguments
+/// * `path` - File to edit
+///
+/// # Returns
+/// Whether the file's contents changed, or an error if the editor could
+/// not be run or failed
+pub fn edit_file(path: &Path) -> Result<bool> {
+    let before = fs::read(path)?;
+    let command = editor_command();
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .arg(path)
+        .status()
+        .map_err(|e| CoreWarError::game_state(format!("Cannot run {}: {}", command[0], e)))?;
+    if !status.success() {
+        return Err(CoreWarError::game_state(format!(
+            "{} exited with {}",
+            command[0], status
+        )));
+    }
+    Ok(fs::read(path)? != before)
+}
+
+/// Reassemble a champion's source over its .cor file
+///
+/// # Arguments
+/// * `source` - Redcode source file
+/// * `output` - The .cor file the champion is loaded from
+///
+/// # Returns
+/// The size of the new bytecode, or the assembler's error; the .cor file
+/// is left untouched when assembly fails
+pub fn assemble(source: &Path, output: &Path) -> Result<usize> {
+    let bytecode = Assembler::new(false).assemble_file(source, Some(output))?;
+    Ok(bytecode.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_path_needs_an_existing_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut champion = Champion::new(1, "Imp".to_string(), String::new(), vec![0x01], 0);
+        assert_eq!(source_path(&champion), None);
+
+        champion.source = Some(dir.path().join("imp.cor"));
+        assert_eq!(source_path(&champion), None);
+
+        fs::write(dir.path().join("imp.s"), ".name \"Imp\"\n").unwrap();
+        assert_eq!(source_path(&champion), Some(dir.path().join("imp.s")));
+    }
+
+    #[test]
+    fn test_assemble_replaces_the_cor_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("imp.s");
+        let output = dir.path().join("imp.cor");
+        fs::write(&source, ".name \"Imp\"\n.comment \"\"\nstart: live %1\n").unwrap();
+
+        let size = assemble(&source, &output).unwrap();
+        assert!(size > 0);
+        assert_eq!(fs::read(&output).unwrap().len(), size);
+
+        fs::write(&source, "start: bogus %1\n").unwrap();
+        assert!(assemble(&source, &output).is_err());
+        assert_eq!(fs::read(&output).unwrap().len(), size);
+    }
+}
@@ -2,7 +2,6 @@
 ///
 /// This module provides enhanced visualization effects like particle systems,
 /// animations, and dynamic color schemes for the Core War terminal interface.
-
 use ratatui::style::Color;
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
@@ -91,6 +90,11 @@ impl Particle {
                 (255.0 * self.intensity) as u8,
                 (255.0 * self.intensity) as u8
             ),
+            Color::Rgb(r, g, b) => Color::Rgb(
+                (r as f32 * self.intensity) as u8,
+                (g as f32 * self.intensity) as u8,
+                (b as f32 * self.intensity) as u8
+            ),
             _ => self.color
         }
     }
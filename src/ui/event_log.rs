@@ -0,0 +1,228 @@
+/// Event log pane
+///
+/// Lists the battle's recent events — `live` reports, forks, deaths and
+/// eliminations — newest at the bottom, each with its cycle and in its
+/// champion's color. Events arrive from the engine's event bus before the
+/// `CycleCompleted` event of their cycle, so they are held until then and
+/// stamped with that cycle. Rewinding drops the entries of the cycles undone.
+use crate::ui::theme::Theme;
+use crate::vm::{Champion, GameEvent};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use std::collections::VecDeque;
+
+/// Entries kept; older ones scroll off the top
+pub const MAX_ENTRIES: usize = 200;
+
+/// One event in the log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Cycle the event happened in
+    pub cycle: u32,
+    /// Champion the event concerns
+    pub champion_id: u8,
+    /// What happened
+    pub text: String,
+}
+
+/// Recent events of a battle
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    /// Logged entries, oldest first
+    entries: VecDeque<Entry>,
+    /// Events of the cycle in progress, waiting for its number
+    pending: Vec<(u8, String)>,
+}
+
+impl EventLog {
+    /// Create an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log an event, if it is one the log shows
+    ///
+    /// # Arguments
+    /// * `event` - An event emitted by the engine
+    pub fn record(&mut self, event: &GameEvent) {
+        let (champion_id, text) = match *event {
+            GameEvent::LiveReported {
+                process_id,
+                champion_id,
+                ..
+            } => (champion_id, format!("process {} reported live", process_id)),
+            GameEvent::ProcessSpawned {
+                process_id,
+                parent_id: Some(parent_id),
+                champion_id,
+                pc,
+            } => (
+                champion_id,
+                format!(
+                    "process {} forked process {} at {:04X}",
+                    parent_id, process_id, pc
+                ),
+            ),
+            GameEvent::ProcessSpawned {
+                process_id,
+                parent_id: None,
+                champion_id,
+                pc,
+            } => (
+                champion_id,
+                format!("process {} started at {:04X}", process_id, pc),
+            ),
+            GameEvent::ProcessDied {
+                process_id,
+                champion_id,
+                pc,
+            } => (
+                champion_id,
+                format!("process {} died at {:04X}", process_id, pc),
+            ),
+            GameEvent::ChampionEliminated { champion_id, .. } => {
+                (champion_id, "eliminated".to_string())
+            }
+            GameEvent::CycleCompleted { cycle } => return self.flush(cycle),
+            GameEvent::Rewound { cycle } => {
+                self.pending.clear();
+                while self.entries.back().is_some_and(|e| e.cycle > cycle) {
+                    self.entries.pop_back();
+                }
+                return;
+            }
+            GameEvent::MemoryWrite { .. } => return,
+        };
+        self.pending.push((champion_id, text));
+    }
+
+    /// Log the events waiting for their cycle
+    ///
+    /// Events outside a cycle, such as the first processes starting, are
+    /// not followed by a `CycleCompleted` event; flushing stamps them with
+    /// the current cycle.
+    ///
+    /// # Arguments
+    /// * `cycle` - Cycle the waiting events happened in
+    pub fn flush(&mut self, cycle: u32) {
+        for (champion_id, text) in self.pending.drain(..) {
+            if self.entries.len() == MAX_ENTRIES {
+                self.entries.pop_front();
+            }
+            self.entries.push_back(Entry {
+                cycle,
+                champion_id,
+                text,
+            });
+        }
+    }
+
+    /// Get the logged entries, oldest first
+    pub fn entries(&self) -> &VecDeque<Entry> {
+        &self.entries
+    }
+
+    /// Build the lines of the newest entries
+    ///
+    /// # Arguments
+    /// * `champions` - Champions, for their names
+    /// * `theme` - Champion colors
+    /// * `rows` - Lines that fit the pane
+    ///
+    /// # Returns
+    /// Up to `rows` lines, the newest last
+    pub fn lines(&self, champions: &[Champion], theme: &Theme, rows: usize) -> Vec<Line<'static>> {
+        self.entries
+            .iter()
+            .skip(self.entries.len().saturating_sub(rows))
+            .map(|entry| {
+                let name = champions
+                    .iter()
+                    .find(|c| c.id == entry.champion_id)
+                    .map_or_else(
+                        || entry.champion_id.to_string(),
+                        |c| c.display_name().to_string(),
+                    );
+                Line::styled(
+                    format!("{:>6} {}: {}", entry.cycle, name, entry.text),
+                    Style::default().fg(theme.champion(entry.champion_id)),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn died(process_id: u32) -> GameEvent {
+        GameEvent::ProcessDied {
+            process_id,
+            champion_id: 1,
+            pc: 0x10,
+        }
+    }
+
+    #[test]
+    fn test_events_are_stamped_with_their_cycle() {
+        let mut log = EventLog::new();
+        log.record(&GameEvent::ProcessSpawned {
+            process_id: 1,
+            parent_id: None,
+            champion_id: 1,
+            pc: 0,
+        });
+        log.flush(0);
+        log.record(&GameEvent::MemoryWrite {
+            address: 0,
+            value: 0,
+            champion_id: 1,
+            process_id: 1,
+            opcode: 3,
+            pc: 0,
+        });
+        log.record(&GameEvent::ProcessSpawned {
+            process_id: 2,
+            parent_id: Some(1),
+            champion_id: 1,
+            pc: 0x20,
+        });
+        log.record(&GameEvent::CycleCompleted { cycle: 1 });
+        log.record(&died(2));
+        log.record(&GameEvent::CycleCompleted { cycle: 2 });
+
+        let entries: Vec<(u32, &str)> = log
+            .entries()
+            .iter()
+            .map(|e| (e.cycle, e.text.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (0, "process 1 started at 0000"),
+                (1, "process 1 forked process 2 at 0020"),
+                (2, "process 2 died at 0010"),
+            ]
+        );
+
+        // Undone cycles leave the log
+        log.record(&GameEvent::Rewound { cycle: 1 });
+        assert_eq!(log.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_lines_show_the_newest_entries() {
+        let mut log = EventLog::new();
+        for cycle in 0..MAX_ENTRIES as u32 + 10 {
+            log.record(&died(cycle));
+            log.flush(cycle);
+        }
+        assert_eq!(log.entries().len(), MAX_ENTRIES);
+        assert_eq!(log.entries()[0].cycle, 10);
+
+        let lines = log.lines(&[], &Theme::default(), 2);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].to_string(), "   209 1: process 209 died at 0010");
+    }
+}
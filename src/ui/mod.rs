@@ -4,9 +4,20 @@
 /// Core War battles in real-time.
 pub mod app;
 pub mod components;
+pub mod editor;
 pub mod input;
 pub mod effects;
 pub mod advanced_memory;
+pub mod viewport;
+pub mod disassembly;
+pub mod process_list;
+pub mod theme;
+pub mod pause;
+pub mod dense;
+pub mod charts;
+pub mod event_log;
+pub mod core_image;
+pub mod cast;
 
 // Re-export commonly used types
 pub use app::App;
@@ -14,24 +25,66 @@ pub use components::{Controls, Dashboard, MemoryGrid};
 pub use input::InputHandler;
 
 use crate::error::Result;
+use crate::vm::ChampionColor;
+use crossterm::cursor::{Hide, Show};
+use crossterm::execute;
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use ratatui::style::Color;
+use std::io;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+impl From<ChampionColor> for Color {
+    fn from(color: ChampionColor) -> Self {
+        match color {
+            ChampionColor::Red => Color::Red,
+            ChampionColor::Blue => Color::Blue,
+            ChampionColor::Green => Color::Green,
+            ChampionColor::Yellow => Color::Yellow,
+            ChampionColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+        }
+    }
+}
+
+/// Whether the terminal is set up for the UI and needs restoring
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Makes sure the panic hook is installed once
+static PANIC_HOOK: Once = Once::new();
 
 /// Initialize the terminal UI system
 ///
-/// This function sets up the terminal for TUI rendering and returns
-/// the necessary components for running the visualization.
+/// Puts the terminal in raw mode on the alternate screen with the cursor
+/// hidden. A panic while the UI runs restores the terminal before the panic
+/// message is printed, so a crash does not leave the shell unusable.
 pub fn initialize() -> Result<()> {
-    // TODO: Initialize ratatui and crossterm
-    // This is a placeholder for the actual TUI initialization
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = cleanup();
+            previous(info);
+        }));
+    });
+
+    enable_raw_mode()?;
+    TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
+    execute!(io::stdout(), EnterAlternateScreen, Hide)?;
     Ok(())
 }
 
 /// Clean up and restore the terminal
 ///
-/// This function restores the terminal to its original state
-/// after the TUI has finished running.
+/// Leaves raw mode and the alternate screen and shows the cursor again.
+/// Does nothing if the terminal is not set up for the UI, so it is safe to
+/// call more than once.
 pub fn cleanup() -> Result<()> {
-    // TODO: Restore terminal state
-    // This is a placeholder for terminal cleanup
+    if !TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, Show)?;
     Ok(())
 }
 
@@ -66,7 +119,7 @@ pub fn run_ui(
     for champion in champions {
         println!(
             "Champion {}: {} ({})",
-            champion.id, champion.name, champion.comment
+            champion.id, champion.display_name(), champion.comment
         );
         println!("  Load address: 0x{:04X}", champion.load_address);
         println!("  Code size: {} bytes", champion.code_size());
@@ -0,0 +1,151 @@
+/// Automatic pauses on battle events
+///
+/// The terminal UI can pause by itself when a process dies, a champion is
+/// eliminated, or a process writes to a watched region of memory. The
+/// triggers are checked against the engine's events after every cycle, so
+/// the battle stops on the cycle the event happened.
+use crate::ui::viewport;
+use crate::vm::GameEvent;
+use std::ops::Range;
+
+/// Events that pause the terminal UI
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PauseOn {
+    /// Pause when any process dies
+    pub death: bool,
+    /// Pause when a champion loses its last process
+    pub elimination: bool,
+    /// Pause when a process writes to these addresses
+    pub region: Option<Range<usize>>,
+}
+
+impl PauseOn {
+    /// Create triggers with every one off
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether an event trips a trigger
+    ///
+    /// # Arguments
+    /// * `event` - An event emitted by the engine
+    ///
+    /// # Returns
+    /// Why the UI pauses, or None if the event trips no trigger
+    pub fn reason(&self, event: &GameEvent) -> Option<String> {
+        match *event {
+            GameEvent::ProcessDied { process_id, pc, .. } if self.death => {
+                Some(format!("process {} died at {:04X}", process_id, pc))
+            }
+            GameEvent::ChampionEliminated { champion_id, cycle } if self.elimination => Some(
+                format!("champion {} eliminated at cycle {}", champion_id, cycle),
+            ),
+            GameEvent::MemoryWrite {
+                address,
+                process_id,
+                ..
+            } if self.region.as_ref().is_some_and(|r| r.contains(&address)) => {
+                Some(format!("process {} wrote to {:04X}", process_id, address))
+            }
+            _ => None,
+        }
+    }
+
+    /// Describe the triggers that are on, for the stats pane
+    ///
+    /// # Returns
+    /// The triggers separated by commas, or None if all are off
+    pub fn describe(&self) -> Option<String> {
+        let mut triggers = Vec::new();
+        if self.death {
+            triggers.push("death".to_string());
+        }
+        if self.elimination {
+            triggers.push("elimination".to_string());
+        }
+        if let Some(region) = &self.region {
+            triggers.push(format!(
+                "writes to {:04X}..{:04X}",
+                region.start, region.end
+            ));
+        }
+        (!triggers.is_empty()).then(|| triggers.join(", "))
+    }
+}
+
+/// Parse a memory region typed to watch
+///
+/// # Arguments
+/// * `text` - `START..END` with the end exclusive, or a single address; each
+///   address in `0x` hexadecimal or decimal
+/// * `memory_size` - Size of the core; the region must lie within it
+///
+/// # Returns
+/// The region, or None if the text is not a non-empty region of the core
+pub fn parse_region(text: &str, memory_size: usize) -> Option<Range<usize>> {
+    let region = match text.split_once("..") {
+        Some((start, end)) => viewport::parse_address(start)?..viewport::parse_address(end)?,
+        None => {
+            let address = viewport::parse_address(text)?;
+            address..address + 1
+        }
+    };
+    (region.start < region.end && region.end <= memory_size).then_some(region)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triggers() {
+        let died = GameEvent::ProcessDied {
+            process_id: 3,
+            champion_id: 1,
+            pc: 0x40,
+        };
+        let eliminated = GameEvent::ChampionEliminated {
+            champion_id: 2,
+            cycle: 900,
+        };
+        let write = |address| GameEvent::MemoryWrite {
+            address,
+            value: 0,
+            champion_id: 1,
+            process_id: 5,
+            opcode: 3,
+            pc: 0,
+        };
+
+        let mut pause_on = PauseOn::new();
+        assert_eq!(pause_on.reason(&died), None);
+        assert_eq!(pause_on.describe(), None);
+
+        pause_on.death = true;
+        pause_on.elimination = true;
+        pause_on.region = Some(0x100..0x110);
+        assert_eq!(pause_on.reason(&died).unwrap(), "process 3 died at 0040");
+        assert_eq!(
+            pause_on.reason(&eliminated).unwrap(),
+            "champion 2 eliminated at cycle 900"
+        );
+        assert_eq!(
+            pause_on.reason(&write(0x10F)).unwrap(),
+            "process 5 wrote to 010F"
+        );
+        assert_eq!(pause_on.reason(&write(0x110)), None);
+        assert_eq!(
+            pause_on.describe().unwrap(),
+            "death, elimination, writes to 0100..0110"
+        );
+    }
+
+    #[test]
+    fn test_parse_region() {
+        assert_eq!(parse_region("0x100..0x110", 4096), Some(0x100..0x110));
+        assert_eq!(parse_region("16", 4096), Some(16..17));
+        assert_eq!(parse_region("0x110..0x100", 4096), None);
+        assert_eq!(parse_region("4000..5000", 4096), None);
+        assert_eq!(parse_region("x..y", 4096), None);
+    }
+}
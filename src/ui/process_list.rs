@@ -0,0 +1,275 @@
+/// Process table pane
+///
+/// Lists every process with its champion, PC, wait cycles, cycles since it
+/// last executed `live`, and state. The table sorts by any column and keeps
+/// a cursor, which follows its process as the rows reorder; choosing a row
+/// selects that process for the memory view and the detail panes.
+use crate::ui::theme::Theme;
+use crate::vm::{Champion, Process};
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Row, Table, TableState};
+use std::cmp::Reverse;
+
+/// Column the process table is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Process ID, oldest first
+    Id,
+    /// Champion, then process ID
+    Champion,
+    /// Program counter
+    Pc,
+    /// Cycles before the next instruction executes
+    Wait,
+    /// Cycles since the last `live`, most recent first; never last
+    Live,
+    /// Ready processes first, then waiting ones
+    State,
+}
+
+impl SortKey {
+    /// Every key, in the order of the table's columns
+    pub const ALL: [SortKey; 6] = [
+        SortKey::Id,
+        SortKey::Champion,
+        SortKey::Pc,
+        SortKey::Wait,
+        SortKey::Live,
+        SortKey::State,
+    ];
+
+    /// Get the key of the next column, wrapping to the first
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&key| key == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Get the heading of the key's column
+    pub fn heading(self) -> &'static str {
+        match self {
+            SortKey::Id => "ID",
+            SortKey::Champion => "Champion",
+            SortKey::Pc => "PC",
+            SortKey::Wait => "Wait",
+            SortKey::Live => "Live",
+            SortKey::State => "State",
+        }
+    }
+}
+
+/// Sorting and cursor of the process table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessList {
+    /// Column the rows are sorted by
+    pub sort: SortKey,
+    /// ID of the process under the cursor
+    pub cursor: Option<u32>,
+}
+
+impl ProcessList {
+    /// Create a table sorted by process ID with no cursor
+    pub fn new() -> Self {
+        Self {
+            sort: SortKey::Id,
+            cursor: None,
+        }
+    }
+
+    /// Sort processes as the table shows them
+    ///
+    /// # Arguments
+    /// * `processes` - The live processes
+    /// * `cycle` - The current cycle, for the cycles since `live`
+    ///
+    /// # Returns
+    /// The processes in table order; ties keep process ID order
+    pub fn rows<'p>(&self, mut processes: Vec<&'p Process>, cycle: u32) -> Vec<&'p Process> {
+        processes.sort_by_key(|p| p.id);
+        match self.sort {
+            SortKey::Id => {}
+            SortKey::Champion => processes.sort_by_key(|p| p.champion_id),
+            SortKey::Pc => processes.sort_by_key(|p| p.pc),
+            SortKey::Wait => processes.sort_by_key(|p| p.wait_cycles),
+            SortKey::Live => processes.sort_by_key(|p| since_live(p, cycle).unwrap_or(u32::MAX)),
+            SortKey::State => processes.sort_by_key(|p| Reverse(state(p) == "ready")),
+        }
+        processes
+    }
+
+    /// Move the cursor through the rows
+    ///
+    /// With no cursor, or its process gone, the cursor starts at the first row.
+    ///
+    /// # Arguments
+    /// * `rows` - The rows as [`ProcessList::rows`] orders them
+    /// * `delta` - Rows to move, negative towards the top; stops at either end
+    pub fn move_cursor(&mut self, rows: &[&Process], delta: isize) {
+        let index = match self.cursor_index(rows) {
+            Some(index) => index.saturating_add_signed(delta).min(rows.len() - 1),
+            None if rows.is_empty() => return,
+            None => 0,
+        };
+        self.cursor = Some(rows[index].id);
+    }
+
+    /// Get the row the cursor is on, if its process is among the rows
+    pub fn cursor_index(&self, rows: &[&Process]) -> Option<usize> {
+        let cursor = self.cursor?;
+        rows.iter().position(|p| p.id == cursor)
+    }
+
+    /// Build the table widget and the state that scrolls it to the cursor
+    ///
+    /// # Arguments
+    /// * `rows` - The rows as [`ProcessList::rows`] orders them
+    /// * `champions` - Champions, for their names
+    /// * `cycle` - The current cycle, for the cycles since `live`
+    /// * `theme` - Colors of the champions and the border
+    /// * `focused` - Whether arrow keys move the cursor, shown in the border
+    pub fn table(
+        &self,
+        rows: &[&Process],
+        champions: &[Champion],
+        cycle: u32,
+        theme: &Theme,
+        focused: bool,
+    ) -> (Table<'static>, TableState) {
+        let header = Row::new(SortKey::ALL.map(|key| {
+            if key == self.sort {
+                format!("{}▼", key.heading())
+            } else {
+                key.heading().to_string()
+            }
+        }))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+        let table_rows = rows.iter().map(|process| {
+            let champion = champions
+                .iter()
+                .find(|c| c.id == process.champion_id)
+                .map_or_else(
+                    || process.champion_id.to_string(),
+                    |c| c.display_name().to_string(),
+                );
+            let since_live =
+                since_live(process, cycle).map_or_else(|| "-".to_string(), |c| c.to_string());
+            Row::new([
+                process.id.to_string(),
+                champion,
+                format!("{:04X}", process.pc),
+                process.wait_cycles.to_string(),
+                since_live,
+                state(process).to_string(),
+            ])
+            .style(Style::default().fg(theme.champion(process.champion_id)))
+        });
+
+        let block = if focused {
+            theme
+                .block("Processes (o to sort, Enter to select)")
+                .border_style(Style::default().fg(theme.accent))
+        } else {
+            theme.block("Processes (p to focus)")
+        };
+        let widths = [
+            Constraint::Length(5),
+            Constraint::Min(8),
+            Constraint::Length(5),
+            Constraint::Length(5),
+            Constraint::Length(5),
+            Constraint::Length(8),
+        ];
+        let table = Table::new(table_rows, widths)
+            .header(header)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let state = TableState::default().with_selected(self.cursor_index(rows));
+        (table, state)
+    }
+}
+
+impl Default for ProcessList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the cycles since a process last executed `live`, if it ever has
+fn since_live(process: &Process, cycle: u32) -> Option<u32> {
+    process
+        .last_live_cycle
+        .map(|last| cycle.saturating_sub(last))
+}
+
+/// Describe whether a process executes on the next cycle
+fn state(process: &Process) -> &'static str {
+    if !process.alive {
+        "dead"
+    } else if process.wait_cycles > 1 {
+        "waiting"
+    } else {
+        "ready"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::ChampionColor;
+
+    fn process(id: u32, champion_id: u8, pc: usize, wait_cycles: u32) -> Process {
+        let mut process = Process::new(id, champion_id, pc, ChampionColor::for_id(champion_id));
+        process.wait_cycles = wait_cycles;
+        process
+    }
+
+    #[test]
+    fn test_sorting() {
+        let processes = [
+            process(1, 2, 300, 0),
+            process(2, 1, 100, 20),
+            process(3, 1, 200, 5),
+        ];
+        let refs: Vec<&Process> = processes.iter().collect();
+        let ids = |list: &ProcessList| -> Vec<u32> {
+            list.rows(refs.clone(), 10).iter().map(|p| p.id).collect()
+        };
+
+        let mut list = ProcessList::new();
+        assert_eq!(ids(&list), [1, 2, 3]);
+        list.sort = SortKey::Champion;
+        assert_eq!(ids(&list), [2, 3, 1]);
+        list.sort = list.sort.next();
+        assert_eq!(list.sort, SortKey::Pc);
+        assert_eq!(ids(&list), [2, 3, 1]);
+        list.sort = SortKey::Wait;
+        assert_eq!(ids(&list), [1, 3, 2]);
+        list.sort = SortKey::State;
+        assert_eq!(ids(&list), [1, 2, 3]);
+        assert_eq!(SortKey::State.next(), SortKey::Id);
+    }
+
+    #[test]
+    fn test_cursor_follows_its_process() {
+        let processes = [
+            process(1, 1, 300, 0),
+            process(2, 1, 100, 0),
+            process(3, 1, 200, 0),
+        ];
+        let mut list = ProcessList::new();
+        let rows = list.rows(processes.iter().collect(), 0);
+
+        list.move_cursor(&rows, 1);
+        assert_eq!(list.cursor, Some(1));
+        list.move_cursor(&rows, 5);
+        assert_eq!(list.cursor, Some(3));
+
+        // Sorted by PC the cursor's process moves to the middle row
+        list.sort = SortKey::Pc;
+        let rows = list.rows(processes.iter().collect(), 0);
+        assert_eq!(list.cursor_index(&rows), Some(1));
+        list.move_cursor(&rows, -5);
+        assert_eq!(list.cursor, Some(2));
+    }
+}
@@ -0,0 +1,379 @@
+/// Color themes for the terminal UI
+///
+/// A theme sets the champion colors, the gradients of the memory heat map
+/// and activity highlight, and the color and line style of pane borders.
+/// Four themes are built in; others are read from a JSON file that names a
+/// built-in theme to start from and overrides any of its fields:
+///
+/// ```json
+/// {
+///   "name": "solarized",
+///   "base": "dark",
+///   "champions": ["#dc322f", "#268bd2", "#859900", "#b58900"],
+///   "heat": ["#002b36", "#cb4b16"],
+///   "border_type": "rounded"
+/// }
+/// ```
+///
+/// Colors are names such as `red` or `dark-gray`, `#rrggbb`, or a palette
+/// index. The ends of a gradient must be `#rrggbb` colors.
+use crate::error::{CoreWarError, Result};
+use crate::vm::ChampionColor;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, BorderType, Borders};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Names of the built-in themes, in the order the theme key cycles them
+pub const BUILTIN_THEMES: [&str; 4] = ["default", "dark", "light", "high-contrast"];
+
+/// A color ramp between two RGB colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gradient {
+    /// Color at intensity 0
+    pub from: (u8, u8, u8),
+    /// Color at intensity 1
+    pub to: (u8, u8, u8),
+}
+
+impl Gradient {
+    /// Get the color at an intensity
+    ///
+    /// # Arguments
+    /// * `intensity` - Position along the ramp, clamped to 0.0-1.0
+    pub fn at(&self, intensity: f32) -> Color {
+        let t = intensity.clamp(0.0, 1.0);
+        let mix =
+            |from: u8, to: u8| (f32::from(from) + (f32::from(to) - f32::from(from)) * t) as u8;
+        Color::Rgb(
+            mix(self.from.0, self.to.0),
+            mix(self.from.1, self.to.1),
+            mix(self.from.2, self.to.2),
+        )
+    }
+}
+
+/// Colors and border style of the terminal UI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Name shown when the theme is switched to
+    pub name: String,
+    /// Colors of champions 1-4; later champions keep their generated colors
+    pub champions: [Color; 4],
+    /// Background of memory cells by how often they were written
+    pub heat: Gradient,
+    /// Foreground of memory cells by recent activity
+    pub activity: Gradient,
+    /// Pane borders
+    pub border: Color,
+    /// Secondary text and the dim phase of the memory grid's border
+    pub dim: Color,
+    /// Focused pane borders and highlights
+    pub accent: Color,
+    /// Line style of pane borders
+    pub border_type: BorderType,
+}
+
+impl Theme {
+    /// Get a built-in theme by name
+    ///
+    /// # Arguments
+    /// * `name` - One of [`BUILTIN_THEMES`]
+    ///
+    /// # Returns
+    /// The theme, or None if no built-in theme has the name
+    pub fn builtin(name: &str) -> Option<Self> {
+        let theme = match name {
+            "default" => Self {
+                name: name.to_string(),
+                champions: [Color::Red, Color::Blue, Color::Green, Color::Yellow],
+                heat: Gradient {
+                    from: (0, 0, 0),
+                    to: (255, 180, 60),
+                },
+                activity: Gradient {
+                    from: (0, 0, 0),
+                    to: (0, 255, 128),
+                },
+                border: Color::Gray,
+                dim: Color::DarkGray,
+                accent: Color::Yellow,
+                border_type: BorderType::Plain,
+            },
+            "dark" => Self {
+                name: name.to_string(),
+                champions: [
+                    Color::Rgb(200, 70, 70),
+                    Color::Rgb(80, 120, 210),
+                    Color::Rgb(80, 170, 90),
+                    Color::Rgb(200, 170, 60),
+                ],
+                heat: Gradient {
+                    from: (0, 0, 0),
+                    to: (150, 60, 20),
+                },
+                activity: Gradient {
+                    from: (40, 40, 40),
+                    to: (90, 190, 150),
+                },
+                border: Color::DarkGray,
+                dim: Color::Black,
+                accent: Color::Rgb(200, 170, 60),
+                border_type: BorderType::Rounded,
+            },
+            "light" => Self {
+                name: name.to_string(),
+                champions: [
+                    Color::Rgb(180, 0, 0),
+                    Color::Rgb(0, 60, 180),
+                    Color::Rgb(0, 120, 0),
+                    Color::Rgb(150, 100, 0),
+                ],
+                heat: Gradient {
+                    from: (255, 255, 255),
+                    to: (250, 160, 80),
+                },
+                activity: Gradient {
+                    from: (120, 120, 120),
+                    to: (0, 110, 60),
+                },
+                border: Color::Black,
+                dim: Color::Gray,
+                accent: Color::Blue,
+                border_type: BorderType::Plain,
+            },
+            "high-contrast" => Self {
+                name: name.to_string(),
+                champions: [
+                    Color::LightRed,
+                    Color::LightCyan,
+                    Color::LightGreen,
+                    Color::LightYellow,
+                ],
+                heat: Gradient {
+                    from: (0, 0, 0),
+                    to: (255, 0, 255),
+                },
+                activity: Gradient {
+                    from: (255, 255, 255),
+                    to: (255, 255, 0),
+                },
+                border: Color::White,
+                dim: Color::White,
+                accent: Color::LightYellow,
+                border_type: BorderType::Thick,
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Get every built-in theme, in the order of [`BUILTIN_THEMES`]
+    pub fn builtins() -> Vec<Self> {
+        BUILTIN_THEMES
+            .iter()
+            .filter_map(|name| Self::builtin(name))
+            .collect()
+    }
+
+    /// Read a theme from a JSON file
+    ///
+    /// # Arguments
+    /// * `path` - Theme file, as described in the module documentation
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parse a theme from JSON
+    ///
+    /// # Arguments
+    /// * `json` - Theme, as described in the module documentation
+    ///
+    /// # Returns
+    /// The base theme with the given fields replaced, or an error naming the
+    /// first field that is not valid
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: ThemeFile = serde_json::from_str(json)
+            .map_err(|e| CoreWarError::config(format!("Invalid theme file: {}", e)))?;
+        let base = file.base.as_deref().unwrap_or("default");
+        let mut theme = Self::builtin(base)
+            .ok_or_else(|| CoreWarError::config(format!("Unknown base theme '{}'", base)))?;
+
+        theme.name = file.name;
+        if let Some(champions) = file.champions {
+            for (slot, color) in theme.champions.iter_mut().zip(&champions) {
+                *slot = parse_color("champions", color)?;
+            }
+        }
+        if let Some([from, to]) = &file.heat {
+            theme.heat = parse_gradient("heat", from, to)?;
+        }
+        if let Some([from, to]) = &file.activity {
+            theme.activity = parse_gradient("activity", from, to)?;
+        }
+        if let Some(border) = &file.border {
+            theme.border = parse_color("border", border)?;
+        }
+        if let Some(dim) = &file.dim {
+            theme.dim = parse_color("dim", dim)?;
+        }
+        if let Some(accent) = &file.accent {
+            theme.accent = parse_color("accent", accent)?;
+        }
+        if let Some(border_type) = &file.border_type {
+            theme.border_type = parse_border_type(border_type)?;
+        }
+        Ok(theme)
+    }
+
+    /// Get a built-in theme by name, or read a theme file
+    ///
+    /// # Arguments
+    /// * `name_or_path` - One of [`BUILTIN_THEMES`], or the path of a theme file
+    pub fn load(name_or_path: &str) -> Result<Self> {
+        if let Some(theme) = Self::builtin(name_or_path) {
+            return Ok(theme);
+        }
+        if !Path::new(name_or_path).exists() {
+            return Err(CoreWarError::config(format!(
+                "'{}' is neither a built-in theme ({}) nor a theme file",
+                name_or_path,
+                BUILTIN_THEMES.join(", ")
+            )));
+        }
+        Self::read(name_or_path)
+    }
+
+    /// Get the color of a champion
+    ///
+    /// # Arguments
+    /// * `champion_id` - Champion ID
+    pub fn champion(&self, champion_id: u8) -> Color {
+        match champion_id {
+            0 => self.champions[0],
+            1..=4 => self.champions[usize::from(champion_id) - 1],
+            _ => ChampionColor::for_id(champion_id).into(),
+        }
+    }
+
+    /// Get a bordered pane in the theme's border style
+    ///
+    /// # Arguments
+    /// * `title` - Title shown in the top border
+    pub fn block<'t>(&self, title: impl Into<ratatui::text::Line<'t>>) -> Block<'t> {
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(self.border_type)
+            .border_style(Style::default().fg(self.border))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin("default").unwrap()
+    }
+}
+
+/// Theme file contents; every field but the name is optional
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: String,
+    base: Option<String>,
+    champions: Option<Vec<String>>,
+    heat: Option<[String; 2]>,
+    activity: Option<[String; 2]>,
+    border: Option<String>,
+    dim: Option<String>,
+    accent: Option<String>,
+    border_type: Option<String>,
+}
+
+/// Parse a color from a theme file field
+fn parse_color(field: &str, text: &str) -> Result<Color> {
+    text.parse()
+        .map_err(|_| CoreWarError::config(format!("{}: '{}' is not a color", field, text)))
+}
+
+/// Parse the ends of a gradient from a theme file field
+fn parse_gradient(field: &str, from: &str, to: &str) -> Result<Gradient> {
+    let rgb = |text: &str| match parse_color(field, text)? {
+        Color::Rgb(r, g, b) => Ok((r, g, b)),
+        _ => Err(CoreWarError::config(format!(
+            "{}: gradient ends must be #rrggbb colors, not '{}'",
+            field, text
+        ))),
+    };
+    Ok(Gradient {
+        from: rgb(from)?,
+        to: rgb(to)?,
+    })
+}
+
+/// Parse a border line style from a theme file
+fn parse_border_type(text: &str) -> Result<BorderType> {
+    match text {
+        "plain" => Ok(BorderType::Plain),
+        "rounded" => Ok(BorderType::Rounded),
+        "double" => Ok(BorderType::Double),
+        "thick" => Ok(BorderType::Thick),
+        _ => Err(CoreWarError::config(format!(
+            "border_type: '{}' is not plain, rounded, double or thick",
+            text
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        let themes = Theme::builtins();
+        assert_eq!(themes.len(), BUILTIN_THEMES.len());
+        assert_eq!(themes[0], Theme::default());
+        assert!(Theme::builtin("neon").is_none());
+        assert!(
+            Theme::load("neon")
+                .unwrap_err()
+                .to_string()
+                .contains("nor a theme file")
+        );
+
+        let theme = Theme::default();
+        assert_eq!(theme.champion(1), Color::Red);
+        assert_eq!(theme.champion(4), Color::Yellow);
+        assert_eq!(theme.champion(5), ChampionColor::for_id(5).into());
+        assert_eq!(theme.heat.at(0.0), Color::Rgb(0, 0, 0));
+        assert_eq!(theme.heat.at(2.0), Color::Rgb(255, 180, 60));
+    }
+
+    #[test]
+    fn test_theme_file() {
+        let theme = Theme::from_json(
+            r##"{"name": "mine", "base": "light", "champions": ["#010203", "cyan"],
+                "heat": ["#000000", "#ff0000"], "border_type": "double"}"##,
+        )
+        .unwrap();
+        let light = Theme::builtin("light").unwrap();
+        assert_eq!(theme.name, "mine");
+        assert_eq!(theme.champion(1), Color::Rgb(1, 2, 3));
+        assert_eq!(theme.champion(2), Color::Cyan);
+        assert_eq!(theme.champion(3), light.champion(3));
+        assert_eq!(theme.heat.at(0.5), Color::Rgb(127, 0, 0));
+        assert_eq!(theme.activity, light.activity);
+        assert_eq!(theme.border_type, BorderType::Double);
+    }
+
+    #[test]
+    fn test_invalid_theme_files() {
+        let error = |json: &str| Theme::from_json(json).unwrap_err().to_string();
+        assert!(error(r#"{"name": "x", "base": "neon"}"#).ends_with("Unknown base theme 'neon'"));
+        assert!(error(r#"{"name": "x", "border": "plaid"}"#).ends_with("'plaid' is not a color"));
+        assert!(error(r##"{"name": "x", "heat": ["red", "#ffffff"]}"##).contains("#rrggbb"));
+        assert!(error(r#"{"name": "x", "colour": "red"}"#).contains("unknown field"));
+    }
+}
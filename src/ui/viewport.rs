@@ -0,0 +1,300 @@
+/// The part of the core the memory grid shows
+///
+/// The grid lays the core out in rows of as many bytes as fit its width, so
+/// a wider terminal shows more of the core at once. The viewport is the run
+/// of rows that fits its height; it scrolls by rows and pages and can jump
+/// to an address or center one. It never scrolls past the last row of the
+/// core.
+///
+/// In hex mode every byte takes a cell of its own. The dense modes pack
+/// several bytes into each character, so the whole core usually fits on
+/// screen: half blocks stack two rows of bytes in a character, and braille
+/// packs two bytes across by four rows down.
+use crate::constants::MEMORY_SIZE;
+
+/// Characters taken by the address at the start of each row, `0000: `
+pub const ADDRESS_WIDTH: usize = 6;
+
+/// Characters taken by one byte, `XX `, in hex mode
+pub const CELL_WIDTH: usize = 3;
+
+/// How many bytes of the core each character of the grid shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Density {
+    /// One byte per cell, as hex
+    #[default]
+    Hex,
+    /// Two bytes per character, one above the other
+    HalfBlock,
+    /// Eight bytes per character, two across by four down
+    Braille,
+}
+
+impl Density {
+    /// Get the next density, from hex to braille and back to hex
+    pub fn next(self) -> Self {
+        match self {
+            Density::Hex => Density::HalfBlock,
+            Density::HalfBlock => Density::Braille,
+            Density::Braille => Density::Hex,
+        }
+    }
+
+    /// Get the bytes one cell shows
+    ///
+    /// # Returns
+    /// Bytes side by side, and rows of bytes stacked, in one cell
+    pub fn cell(self) -> (usize, usize) {
+        match self {
+            Density::Hex => (1, 1),
+            Density::HalfBlock => (1, 2),
+            Density::Braille => (2, 4),
+        }
+    }
+
+    /// Get the characters one cell takes
+    pub fn cell_width(self) -> usize {
+        match self {
+            Density::Hex => CELL_WIDTH,
+            Density::HalfBlock | Density::Braille => 1,
+        }
+    }
+
+    /// Get the name of the density, for the status line
+    pub fn name(self) -> &'static str {
+        match self {
+            Density::Hex => "hex",
+            Density::HalfBlock => "half blocks, 2 bytes per cell",
+            Density::Braille => "braille, 8 bytes per cell",
+        }
+    }
+}
+
+/// Rows of the core shown in the memory grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    /// Bytes per row
+    pub columns: usize,
+    /// Rows that fit the grid
+    pub rows: usize,
+    /// First row shown
+    pub top: usize,
+    /// Size of the core in bytes
+    pub memory_size: usize,
+    /// Bytes shown per character
+    pub density: Density,
+}
+
+impl Viewport {
+    /// Create a viewport at the start of a core
+    ///
+    /// # Arguments
+    /// * `memory_size` - Size of the core in bytes
+    ///
+    /// # Returns
+    /// A viewport of 32 columns and 20 rows until it is fitted to a grid
+    pub fn new(memory_size: usize) -> Self {
+        Self {
+            columns: 32,
+            rows: 20,
+            top: 0,
+            memory_size,
+            density: Density::Hex,
+        }
+    }
+
+    /// Fit the viewport to the inside of the memory grid
+    ///
+    /// The first address shown stays in view when the number of columns
+    /// changes. Columns and rows count bytes, so a dense grid has more of
+    /// them than it has characters.
+    ///
+    /// # Arguments
+    /// * `width` - Width of the grid in characters
+    /// * `height` - Height of the grid in rows
+    /// * `memory_size` - Size of the core in bytes
+    pub fn fit(&mut self, width: u16, height: u16, memory_size: usize) {
+        let first = self.first_address();
+        let (across, down) = self.density.cell();
+        let cells = usize::from(width).saturating_sub(ADDRESS_WIDTH) / self.density.cell_width();
+        self.memory_size = memory_size;
+        self.columns = (cells * across).clamp(1, memory_size.max(1));
+        self.rows = usize::from(height).max(1) * down;
+        self.top = first / self.columns;
+        self.clamp();
+    }
+
+    /// Get the number of rows the whole core takes
+    pub fn total_rows(&self) -> usize {
+        self.memory_size.div_ceil(self.columns)
+    }
+
+    /// Get the address at the start of the first row shown
+    pub fn first_address(&self) -> usize {
+        self.top * self.columns
+    }
+
+    /// Get the last address shown
+    pub fn last_address(&self) -> usize {
+        ((self.top + self.rows) * self.columns).min(self.memory_size) - 1
+    }
+
+    /// Scroll by rows, negative towards address 0
+    pub fn scroll(&mut self, rows: isize) {
+        self.top = self.top.saturating_add_signed(rows);
+        self.clamp();
+    }
+
+    /// Scroll by pages of [`Viewport::rows`] rows, negative towards address 0
+    pub fn page(&mut self, pages: isize) {
+        self.scroll(pages * self.rows as isize);
+    }
+
+    /// Scroll so that an address is shown, on the top row if possible
+    ///
+    /// # Arguments
+    /// * `address` - Address to show, wrapped around the core
+    pub fn jump_to(&mut self, address: usize) {
+        self.top = (address % self.memory_size.max(1)) / self.columns;
+        self.clamp();
+    }
+
+    /// Scroll so that an address is on the middle row, if the core allows
+    ///
+    /// # Arguments
+    /// * `address` - Address to center, wrapped around the core
+    pub fn center_on(&mut self, address: usize) {
+        let row = (address % self.memory_size.max(1)) / self.columns;
+        self.top = row.saturating_sub(self.rows / 2);
+        self.clamp();
+    }
+
+    /// Get where an address is shown
+    ///
+    /// # Returns
+    /// The column and row of the address within the viewport, or None if it
+    /// is scrolled out of view
+    pub fn position(&self, address: usize) -> Option<(usize, usize)> {
+        let row = address / self.columns;
+        (self.top..self.top + self.rows)
+            .contains(&row)
+            .then(|| (address % self.columns, row - self.top))
+    }
+
+    /// Get where an address is drawn inside the grid
+    ///
+    /// # Returns
+    /// The character column, counting the address column, and the row of
+    /// the cell showing the address, or None if it is scrolled out of view
+    pub fn screen_position(&self, address: usize) -> Option<(usize, usize)> {
+        let (across, down) = self.density.cell();
+        let (col, row) = self.position(address)?;
+        Some((
+            ADDRESS_WIDTH + col / across * self.density.cell_width(),
+            row / down,
+        ))
+    }
+
+    /// Keep the last row of the core at or below the bottom of the viewport
+    fn clamp(&mut self) {
+        self.top = self.top.min(self.total_rows().saturating_sub(self.rows));
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::new(MEMORY_SIZE)
+    }
+}
+
+/// Parse an address typed to jump to
+///
+/// # Arguments
+/// * `text` - Hexadecimal with a `0x` prefix, as the grid shows addresses, or decimal
+///
+/// # Returns
+/// The address, or None if the text is not a number
+pub fn parse_address(text: &str) -> Option<usize> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_to_width() {
+        let mut viewport = Viewport::new(MEMORY_SIZE);
+        // 6 for the address and 3 per byte: 64 bytes in 198 characters
+        viewport.fit(198, 30, MEMORY_SIZE);
+        assert_eq!(viewport.columns, 64);
+        assert_eq!(viewport.rows, 30);
+        assert_eq!(viewport.total_rows(), 96);
+        assert_eq!(viewport.last_address(), 64 * 30 - 1);
+
+        // The first address shown stays in view on a narrower grid
+        viewport.jump_to(0x400);
+        viewport.fit(102, 30, MEMORY_SIZE);
+        assert_eq!(viewport.columns, 32);
+        assert_eq!(viewport.first_address(), 0x400);
+
+        viewport.fit(0, 0, MEMORY_SIZE);
+        assert_eq!((viewport.columns, viewport.rows), (1, 1));
+    }
+
+    #[test]
+    fn test_scrolling_stays_in_the_core() {
+        let mut viewport = Viewport::new(MEMORY_SIZE);
+        viewport.fit(102, 20, MEMORY_SIZE);
+        viewport.scroll(-1);
+        assert_eq!(viewport.top, 0);
+        viewport.page(1);
+        assert_eq!(viewport.top, 20);
+        assert_eq!(viewport.position(20 * 32 + 5), Some((5, 0)));
+        assert_eq!(viewport.position(5), None);
+
+        // 192 rows of 32 bytes, the last 20 on screen
+        viewport.page(100);
+        assert_eq!(viewport.top, 172);
+        assert_eq!(viewport.last_address(), MEMORY_SIZE - 1);
+        viewport.jump_to(MEMORY_SIZE + 64);
+        assert_eq!(viewport.top, 2);
+
+        // Centering stops at either end of the core
+        viewport.center_on(64 * 32);
+        assert_eq!(viewport.top, 54);
+        viewport.center_on(3 * 32);
+        assert_eq!(viewport.top, 0);
+        viewport.center_on(MEMORY_SIZE - 1);
+        assert_eq!(viewport.top, 172);
+    }
+
+    #[test]
+    fn test_dense_grids_fit_the_core() {
+        let mut viewport = Viewport::new(MEMORY_SIZE);
+        viewport.density = Density::HalfBlock;
+        viewport.fit(102, 20, MEMORY_SIZE);
+        assert_eq!((viewport.columns, viewport.rows), (96, 40));
+        assert_eq!(viewport.screen_position(96 * 3 + 5), Some((11, 1)));
+
+        // 192 bytes across by 80 down holds the whole core
+        viewport.density = viewport.density.next();
+        viewport.fit(102, 20, MEMORY_SIZE);
+        assert_eq!((viewport.columns, viewport.rows), (192, 80));
+        assert_eq!(viewport.last_address(), MEMORY_SIZE - 1);
+        assert_eq!(viewport.screen_position(192 * 5 + 3), Some((7, 1)));
+        assert_eq!(viewport.density.next(), Density::Hex);
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("0x1A0"), Some(0x1A0));
+        assert_eq!(parse_address(" 416 "), Some(416));
+        assert_eq!(parse_address("1A0"), None);
+        assert_eq!(parse_address(""), None);
+    }
+}
@@ -0,0 +1,193 @@
+/// Slab storage for the scheduler's processes
+///
+/// Processes live in slots that do not move while they are alive, and the
+/// run queue only holds slot indices. Reordering, spawning and killing
+/// processes therefore shuffle indices instead of whole processes with their
+/// registers and trails. Process IDs are never reused, so an ID found once
+/// keeps naming the same process until it dies, which is what the UI and
+/// debugger hold on to.
+use crate::vm::Process;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+/// Processes in run-queue order, stored in reusable slots
+#[derive(Debug, Clone, Default)]
+pub struct ProcessArena {
+    /// Process storage; `None` marks a free slot
+    slots: Vec<Option<Process>>,
+    /// Free slots, reused before the storage grows
+    free: Vec<usize>,
+    /// Slot of each process, by process ID
+    index: HashMap<u32, usize>,
+    /// Slots in execution order, front first
+    queue: VecDeque<usize>,
+}
+
+impl ProcessArena {
+    /// Create an empty arena
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of processes
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check whether the arena holds no process
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Add a process at the front of the run queue
+    pub fn push_front(&mut self, process: Process) {
+        let slot = self.store(process);
+        self.queue.push_front(slot);
+    }
+
+    /// Add a process at the back of the run queue
+    pub fn push_back(&mut self, process: Process) {
+        let slot = self.store(process);
+        self.queue.push_back(slot);
+    }
+
+    /// Get a process by ID
+    pub fn get(&self, id: u32) -> Option<&Process> {
+        self.index
+            .get(&id)
+            .and_then(|&slot| self.slots[slot].as_ref())
+    }
+
+    /// Get a process by ID, mutably
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut Process> {
+        self.index
+            .get(&id)
+            .and_then(|&slot| self.slots[slot].as_mut())
+    }
+
+    /// Get the process at a position of the run queue, mutably
+    ///
+    /// # Arguments
+    /// * `position` - Position in the run queue, 0 for the front
+    pub fn get_at_mut(&mut self, position: usize) -> Option<&mut Process> {
+        let slot = *self.queue.get(position)?;
+        self.slots[slot].as_mut()
+    }
+
+    /// Remove a process by ID
+    ///
+    /// # Returns
+    /// The removed process, or None if no process has the ID
+    pub fn remove(&mut self, id: u32) -> Option<Process> {
+        let slot = self.index.remove(&id)?;
+        self.queue.retain(|&queued| queued != slot);
+        self.free.push(slot);
+        self.slots[slot].take()
+    }
+
+    /// Keep only the processes the predicate accepts, in run-queue order
+    pub fn retain_mut(&mut self, mut keep: impl FnMut(&mut Process) -> bool) {
+        let slots = &mut self.slots;
+        let free = &mut self.free;
+        let index = &mut self.index;
+        self.queue.retain(|&slot| {
+            let process = slots[slot].as_mut().expect("queued slots hold a process");
+            if keep(process) {
+                return true;
+            }
+            index.remove(&process.id);
+            slots[slot] = None;
+            free.push(slot);
+            false
+        });
+    }
+
+    /// Iterate over the processes in run-queue order
+    pub fn iter(&self) -> impl Iterator<Item = &Process> {
+        self.queue.iter().map(|&slot| {
+            self.slots[slot]
+                .as_ref()
+                .expect("queued slots hold a process")
+        })
+    }
+
+    /// Put a process into a free slot
+    fn store(&mut self, process: Process) -> usize {
+        let id = process.id;
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot] = Some(process);
+                slot
+            }
+            None => {
+                self.slots.push(Some(process));
+                self.slots.len() - 1
+            }
+        };
+        self.index.insert(id, slot);
+        slot
+    }
+}
+
+impl FromIterator<Process> for ProcessArena {
+    fn from_iter<I: IntoIterator<Item = Process>>(processes: I) -> Self {
+        let mut arena = Self::new();
+        for process in processes {
+            arena.push_back(process);
+        }
+        arena
+    }
+}
+
+/// Hashes like the run queue it replaces: the length, then each process in order
+impl Hash for ProcessArena {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for process in self.iter() {
+            process.hash(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::ChampionColor;
+
+    fn process(id: u32) -> Process {
+        Process::new(id, 1, id as usize * 10, ChampionColor::Red)
+    }
+
+    fn ids(arena: &ProcessArena) -> Vec<u32> {
+        arena.iter().map(|p| p.id).collect()
+    }
+
+    #[test]
+    fn test_queue_order_and_lookup() {
+        let mut arena: ProcessArena = [process(1), process(2)].into_iter().collect();
+        arena.push_front(process(3));
+        assert_eq!(ids(&arena), [3, 1, 2]);
+        assert_eq!(arena.get(2).unwrap().pc, 20);
+
+        arena.get_mut(2).unwrap().pc = 99;
+        arena.get_at_mut(0).unwrap().pc = 77;
+        assert_eq!(arena.get(2).unwrap().pc, 99);
+        assert_eq!(arena.get(3).unwrap().pc, 77);
+        assert!(arena.get(4).is_none());
+    }
+
+    #[test]
+    fn test_removed_slots_are_reused() {
+        let mut arena: ProcessArena = (1..=4).map(process).collect();
+        assert_eq!(arena.remove(2).unwrap().id, 2);
+        assert!(arena.remove(2).is_none());
+        arena.retain_mut(|p| p.id != 3);
+        assert_eq!(ids(&arena), [1, 4]);
+
+        arena.push_back(process(5));
+        arena.push_back(process(6));
+        assert_eq!(ids(&arena), [1, 4, 5, 6]);
+        assert_eq!(arena.slots.len(), 4);
+        assert_eq!(arena.get(6).unwrap().pc, 60);
+    }
+}
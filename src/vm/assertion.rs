@@ -0,0 +1,330 @@
+/// Scriptable battle assertions
+///
+/// This module implements a tiny assertion language evaluated against a
+/// running [`GameEngine`], so warrior authors can write behavioral regression
+/// tests such as `winner==1`, `cycle<5000` or `mem[0x10]==0x44@1000`.
+use crate::error::{CoreWarError, Result};
+use crate::vm::GameEngine;
+use std::fmt;
+
+/// Quantity an assertion inspects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subject {
+    /// Winning champion ID (0 when there is no winner)
+    Winner,
+    /// Current cycle number
+    Cycle,
+    /// Number of live processes
+    Processes,
+    /// Number of champions with live processes
+    Champions,
+    /// Byte value at a memory address
+    Memory(usize),
+}
+
+/// Comparison operator of an assertion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Operator tokens, longest first so `<=` wins over `<`
+    const TOKENS: [(&'static str, Comparison); 6] = [
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    /// Apply the comparison to two values
+    pub fn holds(&self, actual: i64, expected: i64) -> bool {
+        match self {
+            Self::Equal => actual == expected,
+            Self::NotEqual => actual != expected,
+            Self::Less => actual < expected,
+            Self::LessOrEqual => actual <= expected,
+            Self::Greater => actual > expected,
+            Self::GreaterOrEqual => actual >= expected,
+        }
+    }
+
+    /// Get the operator token
+    pub fn symbol(&self) -> &'static str {
+        Self::TOKENS
+            .iter()
+            .find(|(_, op)| op == self)
+            .map(|(token, _)| *token)
+            .unwrap_or("==")
+    }
+}
+
+/// A single parsed assertion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assertion {
+    /// What is being checked
+    pub subject: Subject,
+    /// How the actual value is compared
+    pub comparison: Comparison,
+    /// Expected value
+    pub expected: i64,
+    /// Cycle at which to evaluate (None = at the end of the battle)
+    pub at_cycle: Option<u32>,
+    /// Original assertion text
+    pub source: String,
+}
+
+impl Assertion {
+    /// Parse an assertion expression
+    ///
+    /// The grammar is `SUBJECT OP VALUE [@CYCLE]` where `SUBJECT` is one of
+    /// `winner`, `cycle`, `processes`, `champions` or `mem[ADDR]`, `OP` is a
+    /// comparison operator and numbers may be decimal or `0x` hexadecimal.
+    ///
+    /// # Arguments
+    /// * `expression` - The assertion text, e.g. `mem[0x10]==0x44@1000`
+    ///
+    /// # Returns
+    /// The parsed assertion, or an error describing the syntax problem
+    pub fn parse(expression: &str) -> Result<Self> {
+        let compact: String = expression.chars().filter(|c| !c.is_whitespace()).collect();
+
+        let (body, at_cycle) = match compact.split_once('@') {
+            Some((body, cycle)) => {
+                let cycle = parse_number(cycle, expression)?;
+                let cycle = u32::try_from(cycle).map_err(|_| {
+                    CoreWarError::assertion(format!("Invalid cycle in '{}'", expression))
+                })?;
+                (body, Some(cycle))
+            }
+            None => (compact.as_str(), None),
+        };
+
+        let (position, token, comparison) = Comparison::TOKENS
+            .iter()
+            .filter_map(|(token, op)| body.find(token).map(|pos| (pos, *token, *op)))
+            .min_by_key(|(pos, token, _)| (*pos, std::cmp::Reverse(token.len())))
+            .ok_or_else(|| {
+                CoreWarError::assertion(format!("Missing comparison operator in '{}'", expression))
+            })?;
+
+        let subject = parse_subject(&body[..position], expression)?;
+        let expected = parse_number(&body[position + token.len()..], expression)?;
+
+        Ok(Self {
+            subject,
+            comparison,
+            expected,
+            at_cycle,
+            source: expression.trim().to_string(),
+        })
+    }
+
+    /// Read the current value of this assertion's subject from the engine
+    pub fn actual(&self, engine: &GameEngine) -> i64 {
+        let stats = engine.get_stats();
+        match self.subject {
+            Subject::Winner => stats.winner.map(i64::from).unwrap_or(0),
+            Subject::Cycle => i64::from(stats.cycle),
+            Subject::Processes => stats.active_processes as i64,
+            Subject::Champions => stats.active_champions as i64,
+            Subject::Memory(address) => i64::from(engine.memory().read_byte(address)),
+        }
+    }
+
+    /// Evaluate this assertion against the engine's current state
+    pub fn evaluate(&self, engine: &GameEngine) -> AssertionOutcome {
+        let actual = self.actual(engine);
+        AssertionOutcome {
+            assertion: self.clone(),
+            actual: Some(actual),
+            passed: self.comparison.holds(actual, self.expected),
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// Result of evaluating one assertion
+#[derive(Debug, Clone)]
+pub struct AssertionOutcome {
+    /// The assertion that was evaluated
+    pub assertion: Assertion,
+    /// Observed value (None if the assertion's cycle was never reached)
+    pub actual: Option<i64>,
+    /// Whether the assertion held
+    pub passed: bool,
+}
+
+impl fmt::Display for AssertionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.passed { "PASS" } else { "FAIL" };
+        match self.actual {
+            Some(actual) => write!(f, "{} {} (actual: {})", status, self.assertion, actual),
+            None => write!(
+                f,
+                "{} {} (battle ended before cycle {})",
+                status,
+                self.assertion,
+                self.assertion.at_cycle.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// Run a battle to completion while evaluating assertions
+///
+/// Assertions with an `@CYCLE` suffix are checked right after that cycle
+/// executes; the rest are checked once the battle has finished and the winner
+/// has been determined.
+///
+/// # Arguments
+/// * `engine` - An engine with champions already loaded
+/// * `assertions` - The assertions to evaluate
+///
+/// # Returns
+/// One outcome per assertion, in the order they were given
+pub fn run_with_assertions(
+    engine: &mut GameEngine,
+    assertions: &[Assertion],
+) -> Result<Vec<AssertionOutcome>> {
+    let mut outcomes: Vec<Option<AssertionOutcome>> = vec![None; assertions.len()];
+
+    engine.start()?;
+    loop {
+        let running = engine.tick()?.is_running();
+        let cycle = engine.get_stats().cycle;
+
+        for (assertion, outcome) in assertions.iter().zip(outcomes.iter_mut()) {
+            if assertion.at_cycle == Some(cycle) {
+                *outcome = Some(assertion.evaluate(engine));
+            }
+        }
+
+        if !running {
+            break;
+        }
+    }
+
+    Ok(assertions
+        .iter()
+        .zip(outcomes)
+        .map(|(assertion, outcome)| match (outcome, assertion.at_cycle) {
+            (Some(outcome), _) => outcome,
+            (None, None) => assertion.evaluate(engine),
+            (None, Some(_)) => AssertionOutcome {
+                assertion: assertion.clone(),
+                actual: None,
+                passed: false,
+            },
+        })
+        .collect())
+}
+
+/// Parse the left-hand side of an assertion
+fn parse_subject(text: &str, expression: &str) -> Result<Subject> {
+    let lowered = text.to_lowercase();
+    match lowered.as_str() {
+        "winner" => Ok(Subject::Winner),
+        "cycle" | "cycles" => Ok(Subject::Cycle),
+        "processes" => Ok(Subject::Processes),
+        "champions" => Ok(Subject::Champions),
+        _ => {
+            let address = lowered
+                .strip_prefix("mem[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .ok_or_else(|| {
+                    CoreWarError::assertion(format!(
+                        "Unknown subject '{}' in '{}'",
+                        text, expression
+                    ))
+                })?;
+            let address = parse_number(address, expression)?;
+            let address = usize::try_from(address).map_err(|_| {
+                CoreWarError::assertion(format!("Invalid address in '{}'", expression))
+            })?;
+            Ok(Subject::Memory(address))
+        }
+    }
+}
+
+/// Parse a decimal or `0x` hexadecimal integer
+fn parse_number(text: &str, expression: &str) -> Result<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => digits.parse::<i64>(),
+    }
+    .map_err(|_| {
+        CoreWarError::assertion(format!("Invalid number '{}' in '{}'", text, expression))
+    })?;
+
+    Ok(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_assertions() {
+        let assertion = Assertion::parse("winner==1").unwrap();
+        assert_eq!(assertion.subject, Subject::Winner);
+        assert_eq!(assertion.comparison, Comparison::Equal);
+        assert_eq!(assertion.expected, 1);
+        assert_eq!(assertion.at_cycle, None);
+
+        let assertion = Assertion::parse("cycle < 5000").unwrap();
+        assert_eq!(assertion.subject, Subject::Cycle);
+        assert_eq!(assertion.comparison, Comparison::Less);
+        assert_eq!(assertion.expected, 5000);
+
+        let assertion = Assertion::parse("processes>=2").unwrap();
+        assert_eq!(assertion.comparison, Comparison::GreaterOrEqual);
+    }
+
+    #[test]
+    fn test_parse_memory_assertion_with_cycle() {
+        let assertion = Assertion::parse("mem[0x10]==0x44@1000").unwrap();
+        assert_eq!(assertion.subject, Subject::Memory(0x10));
+        assert_eq!(assertion.expected, 0x44);
+        assert_eq!(assertion.at_cycle, Some(1000));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Assertion::parse("winner").is_err());
+        assert!(Assertion::parse("speed==1").is_err());
+        assert!(Assertion::parse("mem[zz]==1").is_err());
+        assert!(Assertion::parse("cycle<5000@soon").is_err());
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert!(Comparison::Equal.holds(1, 1));
+        assert!(Comparison::NotEqual.holds(1, 2));
+        assert!(Comparison::Less.holds(1, 2));
+        assert!(Comparison::LessOrEqual.holds(2, 2));
+        assert!(Comparison::Greater.holds(3, 2));
+        assert!(Comparison::GreaterOrEqual.holds(2, 2));
+        assert_eq!(Comparison::LessOrEqual.symbol(), "<=");
+    }
+}
@@ -0,0 +1,307 @@
+/// VM throughput benchmarks
+///
+/// Runs a fixed number of cycles headless and measures how fast the virtual
+/// machine gets through them. Battles that end before the cycle budget is
+/// spent are replayed from the start until it is, so short battles still
+/// give a stable measurement. Per-opcode timings are taken in a separate
+/// pass, since reading the clock around every instruction slows the
+/// scheduler down.
+use crate::error::{CoreWarError, Result};
+use crate::vm::{GameConfig, GameEngine, Instruction};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Cycles run by a benchmark unless told otherwise
+pub const DEFAULT_BENCH_CYCLES: u32 = 10_000;
+
+/// Time spent executing one opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OpcodeTiming {
+    /// Instructions executed with this opcode
+    pub count: u64,
+    /// Total time spent executing them
+    pub total: Duration,
+}
+
+impl OpcodeTiming {
+    /// Average time per instruction
+    pub fn average(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.count.min(u64::from(u32::MAX)) as u32
+        }
+    }
+}
+
+/// Time spent executing instructions, by opcode
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InstructionTimings {
+    by_opcode: BTreeMap<u8, OpcodeTiming>,
+}
+
+impl InstructionTimings {
+    /// Record one executed instruction
+    ///
+    /// # Arguments
+    /// * `opcode` - Byte at the process's program counter
+    /// * `elapsed` - Time the instruction took
+    pub fn record(&mut self, opcode: u8, elapsed: Duration) {
+        let timing = self.by_opcode.entry(opcode).or_default();
+        timing.count += 1;
+        timing.total += elapsed;
+    }
+
+    /// Add another set of timings to this one
+    pub fn merge(&mut self, other: &InstructionTimings) {
+        for (&opcode, timing) in &other.by_opcode {
+            let entry = self.by_opcode.entry(opcode).or_default();
+            entry.count += timing.count;
+            entry.total += timing.total;
+        }
+    }
+
+    /// Timings of each executed opcode, in opcode order
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &OpcodeTiming)> {
+        self.by_opcode
+            .iter()
+            .map(|(&opcode, timing)| (opcode, timing))
+    }
+
+    /// Instructions recorded
+    pub fn count(&self) -> u64 {
+        self.by_opcode.values().map(|timing| timing.count).sum()
+    }
+
+    /// Time spent in all recorded instructions
+    pub fn total(&self) -> Duration {
+        self.by_opcode.values().map(|timing| timing.total).sum()
+    }
+}
+
+/// Result of a benchmark
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    /// Names of the benchmarked champions
+    pub champions: Vec<String>,
+    /// Cycles simulated in the throughput pass
+    pub cycles: u64,
+    /// Battles started to simulate them
+    pub battles: usize,
+    /// Wall-clock time of the throughput pass
+    pub elapsed: Duration,
+    /// Instructions executed in the throughput pass
+    pub instructions: u64,
+    /// Per-opcode timings from the timed pass
+    pub timings: InstructionTimings,
+}
+
+impl BenchReport {
+    /// Simulated cycles per second of wall-clock time
+    pub fn cycles_per_second(&self) -> f64 {
+        per_second(self.cycles, self.elapsed)
+    }
+
+    /// Executed instructions per second of wall-clock time
+    pub fn instructions_per_second(&self) -> f64 {
+        per_second(self.instructions, self.elapsed)
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Champions: {}", self.champions.join(", "))?;
+        writeln!(
+            f,
+            "Cycles: {} in {} battles ({:.3}s)",
+            self.cycles,
+            self.battles,
+            self.elapsed.as_secs_f64()
+        )?;
+        writeln!(f, "Cycles/sec: {:.0}", self.cycles_per_second())?;
+        writeln!(
+            f,
+            "Instructions/sec: {:.0} ({} instructions)",
+            self.instructions_per_second(),
+            self.instructions
+        )?;
+        writeln!(f)?;
+        write!(
+            f,
+            "{:<6} {:<8} {:>12} {:>12} {:>10} {:>7}",
+            "Opcode", "Name", "Count", "Total (us)", "Avg (ns)", "Share"
+        )?;
+        let total = self.timings.total().as_secs_f64();
+        for (opcode, timing) in self.timings.iter() {
+            let name = Instruction::from_opcode(opcode).map_or("data", |i| i.name());
+            let share = if total > 0.0 {
+                timing.total.as_secs_f64() / total * 100.0
+            } else {
+                0.0
+            };
+            write!(
+                f,
+                "\n0x{:02X}   {:<8} {:>12} {:>12.1} {:>10} {:>6.1}%",
+                opcode,
+                name,
+                timing.count,
+                timing.total.as_secs_f64() * 1e6,
+                timing.average().as_nanos(),
+                share
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Benchmark the virtual machine
+///
+/// Without files, a built-in mix of a liver, a bomber and a forker is run.
+///
+/// # Arguments
+/// * `files` - Champion .cor files to load
+/// * `cycles` - Cycles to simulate in each pass
+/// * `config` - Configuration the battles run with; its cycle limit is ignored
+///
+/// # Returns
+/// The measurements, or an error if a champion fails to load or the
+/// battles end without running a cycle
+pub fn run(files: &[PathBuf], cycles: u32, config: GameConfig) -> Result<BenchReport> {
+    if cycles == 0 {
+        return Err(CoreWarError::game_state(
+            "A benchmark needs at least 1 cycle",
+        ));
+    }
+    let config = GameConfig {
+        max_cycles: 0,
+        ..config
+    };
+
+    let throughput = measure(files, cycles, config, false)?;
+    let timed = measure(files, cycles, config, true)?;
+    Ok(BenchReport {
+        champions: throughput.champions,
+        cycles: throughput.cycles,
+        battles: throughput.battles,
+        elapsed: throughput.elapsed,
+        instructions: throughput.instructions,
+        timings: timed.timings,
+    })
+}
+
+/// Measurements of one pass over the cycle budget
+#[derive(Default)]
+struct Pass {
+    champions: Vec<String>,
+    cycles: u64,
+    battles: usize,
+    elapsed: Duration,
+    instructions: u64,
+    timings: InstructionTimings,
+}
+
+/// Simulate `cycles` cycles, restarting battles that end early
+fn measure(files: &[PathBuf], cycles: u32, config: GameConfig, timed: bool) -> Result<Pass> {
+    let mut pass = Pass::default();
+    while pass.cycles < u64::from(cycles) {
+        let mut engine = build(files, config)?;
+        if timed {
+            engine.enable_instruction_timing();
+        }
+        if pass.champions.is_empty() {
+            pass.champions = engine
+                .champions()
+                .iter()
+                .map(|c| c.display_name().to_string())
+                .collect();
+        }
+
+        let remaining = u32::try_from(u64::from(cycles) - pass.cycles).unwrap_or(u32::MAX);
+        let started = Instant::now();
+        engine.start()?;
+        engine.run_cycles(remaining)?;
+        pass.elapsed += started.elapsed();
+
+        let ran = engine.state().cycle;
+        if ran == 0 {
+            return Err(CoreWarError::game_state(
+                "The battle ended without running a cycle",
+            ));
+        }
+        pass.cycles += u64::from(ran);
+        pass.battles += 1;
+        pass.instructions += engine.scheduler_stats().instructions_executed;
+        if let Some(timings) = engine.instruction_timings() {
+            pass.timings.merge(timings);
+        }
+    }
+    Ok(pass)
+}
+
+/// Build an engine with the benchmarked champions loaded
+fn build(files: &[PathBuf], config: GameConfig) -> Result<GameEngine> {
+    let mut builder = GameEngine::builder().config(config);
+    if files.is_empty() {
+        let mut bomber = vec![0x04; 64];
+        bomber[0] = 0x01;
+        let mut forker = vec![0x01; 64];
+        forker[5] = 0x0C;
+        builder = builder
+            .champion_bytes("Liver", [0x01; 64])
+            .champion_bytes("Bomber", bomber)
+            .champion_bytes("Forker", forker);
+    } else {
+        for file in files {
+            builder = builder.champion_file(file);
+        }
+    }
+    builder.build()
+}
+
+/// Rate of `count` over `elapsed`, 0 when no time passed
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        count as f64 / seconds
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_merge_by_opcode() {
+        let mut timings = InstructionTimings::default();
+        timings.record(0x01, Duration::from_nanos(100));
+        timings.record(0x01, Duration::from_nanos(300));
+        let mut other = InstructionTimings::default();
+        other.record(0x04, Duration::from_nanos(50));
+        other.record(0x01, Duration::from_nanos(200));
+        timings.merge(&other);
+
+        let entries: Vec<_> = timings.iter().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, 0x01);
+        assert_eq!(entries[0].1.count, 3);
+        assert_eq!(entries[0].1.average(), Duration::from_nanos(200));
+        assert_eq!(timings.count(), 4);
+        assert_eq!(timings.total(), Duration::from_nanos(650));
+    }
+
+    #[test]
+    fn test_bench_runs_the_requested_cycles() {
+        let report = run(&[], 2_000, GameConfig::default()).unwrap();
+        assert_eq!(report.cycles, 2_000);
+        assert_eq!(report.champions, ["Liver", "Bomber", "Forker"]);
+        assert!(report.battles >= 1);
+        assert!(report.instructions > 0);
+        assert!(report.timings.count() > 0);
+        assert!(report.to_string().contains("live"));
+        assert!(run(&[], 0, GameConfig::default()).is_err());
+    }
+}
@@ -0,0 +1,412 @@
+/// Engine breakpoints
+///
+/// Breakpoints let a debugger or the terminal UI stop a battle when
+/// something interesting happens: a process reaching an address, a given
+/// cycle, a champion forking, a process dying or a write landing in a watched
+/// memory range. The engine checks them after every cycle and reports hits
+/// through its tick outcome.
+use crate::error::{CoreWarError, Result};
+use crate::vm::{GameEvent, Instruction};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// A condition that interrupts the battle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// A process's PC moves to this address
+    Address(usize),
+    /// The battle reaches this cycle
+    Cycle(u32),
+    /// A process of this champion forks
+    Fork { champion_id: u8 },
+    /// Any process dies
+    ProcessDeath,
+    /// A process writes a byte in `start..end` (a watchpoint)
+    Write { start: usize, end: usize },
+}
+
+impl Breakpoint {
+    /// Create a watchpoint on a memory range
+    ///
+    /// # Arguments
+    /// * `range` - Addresses to watch, end exclusive
+    ///
+    /// # Returns
+    /// A breakpoint triggered by any write in the range
+    pub fn watch(range: Range<usize>) -> Self {
+        Self::Write {
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = CoreWarError;
+
+    /// Parse `pc=ADDR`, `cycle=N`, `fork=CHAMPION`, `death` or `watch=START..END`
+    fn from_str(s: &str) -> Result<Self> {
+        let spec = s.trim().to_lowercase();
+        if spec == "death" {
+            return Ok(Self::ProcessDeath);
+        }
+
+        let invalid = || {
+            CoreWarError::game_state(format!(
+                "Invalid breakpoint '{}' (expected pc=ADDR, cycle=N, fork=CHAMPION, death or watch=START..END)",
+                s
+            ))
+        };
+        let (kind, value) = spec.split_once('=').ok_or_else(invalid)?;
+        if kind == "watch" {
+            let (start, end) = value.split_once("..").ok_or_else(invalid)?;
+            let start = parse_number(start).ok_or_else(invalid)?;
+            let end = parse_number(end).ok_or_else(invalid)?;
+            return match (usize::try_from(start), usize::try_from(end)) {
+                (Ok(start), Ok(end)) if start < end => Ok(Self::Write { start, end }),
+                _ => Err(invalid()),
+            };
+        }
+        let value = parse_number(value).ok_or_else(invalid)?;
+
+        match kind {
+            "pc" => usize::try_from(value)
+                .map(Self::Address)
+                .map_err(|_| invalid()),
+            "cycle" => u32::try_from(value).map(Self::Cycle).map_err(|_| invalid()),
+            "fork" => u8::try_from(value)
+                .map(|champion_id| Self::Fork { champion_id })
+                .map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "pc=0x{:04X}", address),
+            Self::Cycle(cycle) => write!(f, "cycle={}", cycle),
+            Self::Fork { champion_id } => write!(f, "fork={}", champion_id),
+            Self::ProcessDeath => write!(f, "death"),
+            Self::Write { start, end } => write!(f, "watch=0x{:04X}..0x{:04X}", start, end),
+        }
+    }
+}
+
+/// Parse a decimal or `0x`-prefixed hexadecimal number
+pub(crate) fn parse_number(value: &str) -> Option<u64> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// The write that triggered a watchpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchedWrite {
+    /// Address written
+    pub address: usize,
+    /// Byte written
+    pub value: u8,
+    /// Champion that owns the writing process
+    pub champion_id: u8,
+    /// Opcode of the writing instruction
+    pub opcode: u8,
+    /// Address of the writing instruction
+    pub pc: usize,
+}
+
+impl fmt::Display for WatchedWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "champion {} wrote 0x{:02X} to 0x{:04X} with ",
+            self.champion_id, self.value, self.address
+        )?;
+        match Instruction::from_opcode(self.opcode) {
+            Ok(instruction) => write!(f, "{}", instruction.name())?,
+            Err(_) => write!(f, "opcode 0x{:02X}", self.opcode)?,
+        }
+        write!(f, " at 0x{:04X}", self.pc)
+    }
+}
+
+/// A breakpoint that triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakpointHit {
+    /// The breakpoint that triggered
+    pub breakpoint: Breakpoint,
+    /// Cycle it triggered on
+    pub cycle: u32,
+    /// Process that triggered it, if any
+    pub process_id: Option<u32>,
+    /// The write that triggered a watchpoint
+    pub write: Option<WatchedWrite>,
+}
+
+impl fmt::Display for BreakpointHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at cycle {}", self.breakpoint, self.cycle)?;
+        if let Some(process_id) = self.process_id {
+            write!(f, " (process {})", process_id)?;
+        }
+        if let Some(write) = &self.write {
+            write!(f, ": {}", write)?;
+        }
+        Ok(())
+    }
+}
+
+/// The set of breakpoints installed on an engine
+#[derive(Debug, Clone, Default)]
+pub struct Breakpoints {
+    list: Vec<Breakpoint>,
+}
+
+impl Breakpoints {
+    /// Add a breakpoint, ignoring duplicates
+    ///
+    /// # Arguments
+    /// * `breakpoint` - The breakpoint to add
+    pub fn add(&mut self, breakpoint: Breakpoint) {
+        if !self.list.contains(&breakpoint) {
+            self.list.push(breakpoint);
+        }
+    }
+
+    /// Remove a breakpoint
+    ///
+    /// # Arguments
+    /// * `breakpoint` - The breakpoint to remove
+    ///
+    /// # Returns
+    /// Whether the breakpoint was installed
+    pub fn remove(&mut self, breakpoint: &Breakpoint) -> bool {
+        let before = self.list.len();
+        self.list.retain(|b| b != breakpoint);
+        self.list.len() != before
+    }
+
+    /// Remove every breakpoint
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Get the installed breakpoints, in the order they were added
+    pub fn as_slice(&self) -> &[Breakpoint] {
+        &self.list
+    }
+
+    /// Check whether no breakpoints are installed
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Check whether any breakpoint needs process PCs tracked
+    pub fn watches_addresses(&self) -> bool {
+        self.list
+            .iter()
+            .any(|b| matches!(b, Breakpoint::Address(_)))
+    }
+
+    /// Find the breakpoints triggered by a cycle
+    ///
+    /// # Arguments
+    /// * `cycle` - The cycle that just completed
+    /// * `events` - Events raised during the cycle
+    /// * `moved` - Processes whose PC changed during the cycle, with their new PC
+    ///
+    /// # Returns
+    /// Every hit, in breakpoint order
+    pub fn hits(
+        &self,
+        cycle: u32,
+        events: &[GameEvent],
+        moved: &[(u32, usize)],
+    ) -> Vec<BreakpointHit> {
+        let mut hits = Vec::new();
+        let mut hit = |breakpoint: Breakpoint, process_id: Option<u32>, write| {
+            hits.push(BreakpointHit {
+                breakpoint,
+                cycle,
+                process_id,
+                write,
+            })
+        };
+
+        for &breakpoint in &self.list {
+            match breakpoint {
+                Breakpoint::Address(address) => moved
+                    .iter()
+                    .filter(|(_, pc)| *pc == address)
+                    .for_each(|(id, _)| hit(breakpoint, Some(*id), None)),
+                Breakpoint::Cycle(at) => {
+                    if at == cycle {
+                        hit(breakpoint, None, None);
+                    }
+                }
+                Breakpoint::Fork { champion_id } => {
+                    for event in events {
+                        if let GameEvent::ProcessSpawned {
+                            process_id,
+                            parent_id: Some(_),
+                            champion_id: owner,
+                            ..
+                        } = *event
+                            && owner == champion_id
+                        {
+                            hit(breakpoint, Some(process_id), None);
+                        }
+                    }
+                }
+                Breakpoint::ProcessDeath => {
+                    for event in events {
+                        if let GameEvent::ProcessDied { process_id, .. } = *event {
+                            hit(breakpoint, Some(process_id), None);
+                        }
+                    }
+                }
+                Breakpoint::Write { start, end } => {
+                    for event in events {
+                        if let GameEvent::MemoryWrite {
+                            address,
+                            value,
+                            champion_id,
+                            process_id,
+                            opcode,
+                            pc,
+                        } = *event
+                            && (start..end).contains(&address)
+                        {
+                            let write = WatchedWrite {
+                                address,
+                                value,
+                                champion_id,
+                                opcode,
+                                pc,
+                            };
+                            hit(breakpoint, Some(process_id), Some(write));
+                        }
+                    }
+                }
+            }
+        }
+
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_breakpoints() {
+        assert_eq!(
+            "pc=0x100".parse::<Breakpoint>().unwrap(),
+            Breakpoint::Address(0x100)
+        );
+        assert_eq!(
+            "cycle=500".parse::<Breakpoint>().unwrap(),
+            Breakpoint::Cycle(500)
+        );
+        assert_eq!(
+            "FORK=2".parse::<Breakpoint>().unwrap(),
+            Breakpoint::Fork { champion_id: 2 }
+        );
+        assert_eq!(
+            "death".parse::<Breakpoint>().unwrap(),
+            Breakpoint::ProcessDeath
+        );
+
+        assert!("pc".parse::<Breakpoint>().is_err());
+        assert!("fork=300".parse::<Breakpoint>().is_err());
+        assert!("jump=1".parse::<Breakpoint>().is_err());
+        assert_eq!(
+            "watch=0x100..0x140".parse::<Breakpoint>().unwrap(),
+            Breakpoint::watch(0x100..0x140)
+        );
+        assert!("watch=0x140..0x100".parse::<Breakpoint>().is_err());
+        assert!("watch=0x100".parse::<Breakpoint>().is_err());
+
+        for spec in [
+            "pc=0x0100",
+            "cycle=7",
+            "fork=1",
+            "death",
+            "watch=0x0100..0x0140",
+        ] {
+            assert_eq!(spec.parse::<Breakpoint>().unwrap().to_string(), spec);
+        }
+    }
+
+    #[test]
+    fn test_hits_match_events_and_moves() {
+        let mut breakpoints = Breakpoints::default();
+        breakpoints.add(Breakpoint::Address(10));
+        breakpoints.add(Breakpoint::Cycle(3));
+        breakpoints.add(Breakpoint::Fork { champion_id: 1 });
+        breakpoints.add(Breakpoint::ProcessDeath);
+        breakpoints.add(Breakpoint::ProcessDeath);
+        assert_eq!(breakpoints.as_slice().len(), 4);
+
+        let events = [
+            GameEvent::ProcessSpawned {
+                process_id: 5,
+                parent_id: Some(1),
+                champion_id: 1,
+                pc: 20,
+            },
+            GameEvent::ProcessSpawned {
+                process_id: 6,
+                parent_id: Some(2),
+                champion_id: 2,
+                pc: 30,
+            },
+            GameEvent::ProcessDied {
+                process_id: 2,
+                champion_id: 2,
+                pc: 40,
+            },
+        ];
+        let hits = breakpoints.hits(2, &events, &[(1, 10), (3, 11)]);
+        let triggered: Vec<_> = hits.iter().map(|h| (h.breakpoint, h.process_id)).collect();
+        assert_eq!(
+            triggered,
+            vec![
+                (Breakpoint::Address(10), Some(1)),
+                (Breakpoint::Fork { champion_id: 1 }, Some(5)),
+                (Breakpoint::ProcessDeath, Some(2)),
+            ]
+        );
+
+        assert_eq!(breakpoints.hits(3, &[], &[]).len(), 1);
+        assert!(breakpoints.remove(&Breakpoint::Cycle(3)));
+        assert!(breakpoints.hits(3, &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_watch_hits_writes_in_range() {
+        let mut breakpoints = Breakpoints::default();
+        breakpoints.add(Breakpoint::watch(0x100..0x140));
+
+        let write = |address| GameEvent::MemoryWrite {
+            address,
+            value: 0xAA,
+            champion_id: 2,
+            process_id: 7,
+            opcode: 0x04,
+            pc: 0xF0,
+        };
+        let hits = breakpoints.hits(4, &[write(0xFF), write(0x100), write(0x140)], &[]);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].process_id, Some(7));
+        assert_eq!(hits[0].write.unwrap().address, 0x100);
+        assert_eq!(
+            hits[0].to_string(),
+            "watch=0x0100..0x0140 at cycle 4 (process 7): champion 2 wrote 0xAA to 0x0100 with add at 0x00F0"
+        );
+    }
+}
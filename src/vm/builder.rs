@@ -0,0 +1,424 @@
+/// Builder for ready-to-run game engines
+///
+/// Setting up an engine by hand means creating a [`GameConfig`], loading
+/// champions and starting the battle, in that order. [`GameEngineBuilder`]
+/// collects the same information in any order and checks it all at once in
+/// [`GameEngineBuilder::build`], which returns a started engine.
+use crate::error::{CoreWarError, Result};
+use crate::vm::{
+    Champion, ChampionLoader, GameConfig, GameEngine, Memory, Placement, Rules, RulesPreset,
+};
+use std::path::PathBuf;
+
+/// Where a champion's code comes from
+#[derive(Debug, Clone)]
+pub(crate) enum ChampionSource {
+    /// A compiled .cor file
+    File(PathBuf),
+    /// The contents of a compiled .cor file
+    Cor(Vec<u8>),
+    /// Raw bytecode with a display name
+    Bytes { name: String, code: Vec<u8> },
+}
+
+/// A champion waiting to be loaded
+#[derive(Debug, Clone)]
+struct PendingChampion {
+    source: ChampionSource,
+    load_address: Option<usize>,
+}
+
+/// Builder for a [`GameEngine`] with its champions loaded and the battle started
+#[derive(Debug, Clone, Default)]
+pub struct GameEngineBuilder {
+    /// Configuration for the engine
+    config: GameConfig,
+    /// Champions in load order
+    champions: Vec<PendingChampion>,
+    /// P-space carried over for champions, by load order
+    #[cfg(feature = "pspace")]
+    pspace: Vec<(usize, Vec<i32>)>,
+    /// First misuse of the builder, reported by `build`
+    error: Option<String>,
+}
+
+impl GameEngineBuilder {
+    /// Create a builder with the default configuration and no champions
+    ///
+    /// # Returns
+    /// A new GameEngineBuilder instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the whole configuration
+    ///
+    /// # Arguments
+    /// * `config` - Game configuration
+    pub fn config(mut self, config: GameConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set the arena rules
+    ///
+    /// # Arguments
+    /// * `rules` - Arena rules for memory, scheduling and loading
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.config.rules = rules;
+        self
+    }
+
+    /// Set the rules and cycle limit of a preset
+    ///
+    /// # Arguments
+    /// * `preset` - Named set of rules, e.g. [`RulesPreset::Pmars`]
+    pub fn preset(mut self, preset: RulesPreset) -> Self {
+        self.config.rules = preset.rules();
+        self.config.max_cycles = preset.max_cycles();
+        self
+    }
+
+    /// Set the seed for random choices
+    ///
+    /// # Arguments
+    /// * `seed` - Seed for the engine's random number generator
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+
+    /// Set how champions without a load address are placed
+    ///
+    /// # Arguments
+    /// * `placement` - Evenly spaced or scattered at random
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.config.placement = placement;
+        self
+    }
+
+    /// Set the cycle limit
+    ///
+    /// # Arguments
+    /// * `max_cycles` - Maximum cycles to run (0 = unlimited)
+    pub fn max_cycles(mut self, max_cycles: u32) -> Self {
+        self.config.max_cycles = max_cycles;
+        self
+    }
+
+    /// Add a champion from a compiled .cor file
+    ///
+    /// # Arguments
+    /// * `path` - Path to the .cor file
+    pub fn champion_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.champions.push(PendingChampion {
+            source: ChampionSource::File(path.into()),
+            load_address: None,
+        });
+        self
+    }
+
+    /// Add a champion from the contents of a compiled .cor file
+    ///
+    /// For champions that never touch the file system, such as ones
+    /// assembled in a browser.
+    ///
+    /// # Arguments
+    /// * `bytes` - Header followed by code, as written by the assembler
+    pub fn champion_cor(mut self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.champions.push(PendingChampion {
+            source: ChampionSource::Cor(bytes.into()),
+            load_address: None,
+        });
+        self
+    }
+
+    /// Add a champion from raw bytecode, without a .cor header
+    ///
+    /// # Arguments
+    /// * `name` - Champion name
+    /// * `code` - Bytecode loaded into memory as is
+    pub fn champion_bytes(mut self, name: impl Into<String>, code: impl Into<Vec<u8>>) -> Self {
+        self.champions.push(PendingChampion {
+            source: ChampionSource::Bytes {
+                name: name.into(),
+                code: code.into(),
+            },
+            load_address: None,
+        });
+        self
+    }
+
+    /// Add a champion from either kind of source
+    pub(crate) fn champion_source(mut self, source: ChampionSource) -> Self {
+        self.champions.push(PendingChampion {
+            source,
+            load_address: None,
+        });
+        self
+    }
+
+    /// Set the load address of the most recently added champion
+    ///
+    /// Champions without an address are placed according to the configured
+    /// placement. Random placement cannot be combined with load addresses.
+    ///
+    /// # Arguments
+    /// * `address` - Address the champion's code starts at
+    pub fn load_address(mut self, address: usize) -> Self {
+        match self.champions.last_mut() {
+            Some(champion) => champion.load_address = Some(address),
+            None => {
+                self.error.get_or_insert_with(|| {
+                    format!("Load address 0x{:04X} given before any champion", address)
+                });
+            }
+        }
+        self
+    }
+
+    /// Give the most recently added champion the p-space of an earlier round
+    ///
+    /// Champions without one start with the p-space of a first round.
+    ///
+    /// # Arguments
+    /// * `cells` - The champion's p-space, resized to the rules' p-space size
+    #[cfg(feature = "pspace")]
+    pub fn pspace(mut self, cells: Vec<i32>) -> Self {
+        match self.champions.len().checked_sub(1) {
+            Some(index) => self.pspace.push((index, cells)),
+            None => {
+                self.error
+                    .get_or_insert_with(|| "P-space given before any champion".to_string());
+            }
+        }
+        self
+    }
+
+    /// Load the champions and start the battle
+    ///
+    /// # Returns
+    /// A started engine, or an error if the rules, champions or placement are invalid
+    pub fn build(self) -> Result<GameEngine> {
+        if let Some(message) = self.error {
+            return Err(CoreWarError::game_state(message));
+        }
+
+        let rules = self.config.rules;
+        rules.validate()?;
+
+        let loader = ChampionLoader::new(true).with_rules(rules);
+        loader.check_champion_count(self.champions.len())?;
+
+        let scatter = self.config.placement == Placement::Random;
+        if scatter && self.champions.iter().any(|c| c.load_address.is_some()) {
+            return Err(CoreWarError::game_state(
+                "Random placement cannot be combined with load addresses",
+            ));
+        }
+
+        let default_addresses =
+            Memory::calculate_placement_addresses(self.champions.len(), rules.memory_size);
+        let mut champions = self
+            .champions
+            .into_iter()
+            .zip(default_addresses)
+            .enumerate()
+            .map(|(i, (pending, default_address))| {
+                let id = (i + 1) as u8;
+                let address = pending.load_address.unwrap_or(default_address);
+                match pending.source {
+                    ChampionSource::File(path) => loader.load_champion(path, id, Some(address)),
+                    ChampionSource::Cor(bytes) => {
+                        loader.load_champion_from_bytes(&bytes, id, Some(address))
+                    }
+                    ChampionSource::Bytes { name, code } => {
+                        champion_from_bytes(&rules, id, name, code, address)
+                    }
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if scatter {
+            loader.scatter_champions(&mut champions, self.config.seed)?;
+        }
+        loader.validate_champion_placement(&champions)?;
+        #[cfg(feature = "pspace")]
+        for (index, cells) in self.pspace {
+            champions[index].pspace = cells;
+        }
+
+        let mut engine = GameEngine::new(self.config);
+        engine.install_champions(champions)?;
+        engine.start()?;
+        Ok(engine)
+    }
+}
+
+/// Create a champion from raw bytecode, checking it fits the arena
+fn champion_from_bytes(
+    rules: &Rules,
+    id: u8,
+    name: String,
+    code: Vec<u8>,
+    address: usize,
+) -> Result<Champion> {
+    if code.is_empty() || code.len() > rules.memory_size {
+        return Err(CoreWarError::champion(format!(
+            "Champion {} has {} bytes of code (must be 1-{})",
+            name,
+            code.len(),
+            rules.memory_size
+        )));
+    }
+    if address >= rules.memory_size {
+        return Err(CoreWarError::champion(format!(
+            "Load address {} is outside memory bounds ({})",
+            address, rules.memory_size
+        )));
+    }
+
+    Ok(Champion::new(id, name, String::new(), code, address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_starts_engine_with_placed_champions() {
+        let mut engine = GameEngine::builder()
+            .seed(7)
+            .max_cycles(50)
+            .champion_bytes("First", [0x01; 100])
+            .champion_bytes("Second", [0x01; 100])
+            .load_address(3000)
+            .build()
+            .unwrap();
+
+        assert!(engine.state().running);
+        assert_eq!(engine.config().seed, 7);
+        let addresses: Vec<usize> = engine.champions().iter().map(|c| c.load_address).collect();
+        assert_eq!(addresses, vec![0, 3000]);
+        assert_eq!(engine.memory().get_owner(3000), Some(2));
+        assert_eq!(engine.processes().len(), 2);
+
+        engine.run_to_completion().unwrap();
+        assert_eq!(engine.state().cycle, 50);
+    }
+
+    #[test]
+    fn test_build_supports_more_than_four_champions() {
+        let rules = Rules {
+            max_champions: 6,
+            ..Default::default()
+        };
+        let mut builder = GameEngine::builder().max_cycles(100).rules(rules);
+        for i in 0..6 {
+            builder = builder.champion_bytes(format!("C{}", i + 1), [0x01; 100]);
+        }
+        let mut engine = builder.build().unwrap();
+
+        let addresses: Vec<usize> = engine.champions().iter().map(|c| c.load_address).collect();
+        assert_eq!(addresses, vec![0, 1024, 2048, 3072, 4096, 5120]);
+        let mut colors: Vec<(u8, u8, u8)> =
+            engine.champions().iter().map(|c| c.color.rgb()).collect();
+        colors.sort();
+        colors.dedup();
+        assert_eq!(colors.len(), 6);
+
+        engine.run_to_completion().unwrap();
+        assert!(engine.champions().iter().all(|c| c.live_count > 0));
+
+        let too_many = (0..7).fold(GameEngine::builder().rules(rules), |builder, i| {
+            builder.champion_bytes(format!("C{}", i + 1), [0x01; 10])
+        });
+        assert!(too_many.build().is_err());
+    }
+
+    #[test]
+    fn test_build_scatters_champions_with_random_placement() {
+        let build = |seed| {
+            GameEngine::builder()
+                .seed(seed)
+                .placement(Placement::Random)
+                .champion_bytes("First", [0x01; 100])
+                .champion_bytes("Second", [0x01; 100])
+                .champion_bytes("Third", [0x01; 100])
+                .build()
+                .unwrap()
+        };
+        let addresses = |engine: &GameEngine| -> Vec<usize> {
+            engine.champions().iter().map(|c| c.load_address).collect()
+        };
+
+        let engine = build(1);
+        assert_eq!(addresses(&build(1)), addresses(&engine));
+        assert_ne!(addresses(&build(2)), addresses(&engine));
+        for champion in engine.champions() {
+            let memory = engine.memory();
+            assert_eq!(memory.get_owner(champion.load_address), Some(champion.id));
+            assert_eq!(
+                memory.get_owner(champion.load_address + 99),
+                Some(champion.id)
+            );
+        }
+
+        assert!(
+            GameEngine::builder()
+                .placement(Placement::Random)
+                .champion_bytes("Placed", [0x01; 10])
+                .load_address(100)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_setups() {
+        assert!(GameEngine::builder().build().is_err());
+        assert!(
+            GameEngine::builder()
+                .load_address(10)
+                .champion_bytes("Late", [0x01])
+                .build()
+                .is_err()
+        );
+        assert!(
+            GameEngine::builder()
+                .champion_bytes("Empty", [])
+                .build()
+                .is_err()
+        );
+        assert!(
+            GameEngine::builder()
+                .champion_bytes("First", [0x01; 10])
+                .load_address(100)
+                .champion_bytes("Overlap", [0x01; 10])
+                .load_address(105)
+                .build()
+                .is_err()
+        );
+        assert!(
+            GameEngine::builder()
+                .champion_file("/nonexistent/champion.cor")
+                .build()
+                .is_err()
+        );
+        assert!(
+            GameEngine::builder()
+                .champion_cor([0x00; 16])
+                .build()
+                .is_err()
+        );
+        assert!(
+            GameEngine::builder()
+                .rules(Rules {
+                    memory_size: 0,
+                    ..Default::default()
+                })
+                .champion_bytes("Champ", [0x01])
+                .build()
+                .is_err()
+        );
+    }
+}
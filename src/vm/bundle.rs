@@ -0,0 +1,474 @@
+/// Shareable, re-verifiable battle results
+///
+/// A [`ResultsBundle`] is a single JSON document holding everything needed
+/// to check a battle's outcome independently: the configuration, each
+/// champion's code with its SHA-256 hash, the results report, a replay trail
+/// of state digests and the final snapshot. [`ResultsBundle::verify`] replays
+/// the battle from scratch and checks every part against the replay.
+use crate::error::{CoreWarError, Result};
+use crate::vm::{Champion, GameConfig, GameEngine, GameSnapshot, TieBreak};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Bundle format version, bumped whenever the layout changes
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Cycles between state digests in the replay trail
+pub const REPLAY_INTERVAL: u32 = 100;
+
+/// Everything needed to share and re-verify a finished battle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultsBundle {
+    /// Bundle format version
+    pub version: u32,
+    /// Configuration the battle ran with
+    pub config: GameConfig,
+    /// Champions as they were loaded, before the battle started
+    pub champions: Vec<BundledChampion>,
+    /// Outcome of the battle
+    pub report: BattleReport,
+    /// State digests every [`REPLAY_INTERVAL`] cycles and at the last cycle
+    pub replay: Vec<ReplayFrame>,
+    /// Simulation state at the end of the battle
+    pub snapshot: GameSnapshot,
+}
+
+/// A champion's code and placement, with a hash of the code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledChampion {
+    /// Champion ID
+    pub id: u8,
+    /// Champion name from header
+    pub name: String,
+    /// Display name chosen by the user
+    pub alias: Option<String>,
+    /// Champion comment from header
+    pub comment: String,
+    /// Loading address in memory
+    pub load_address: usize,
+    /// Champion bytecode
+    pub code: Vec<u8>,
+    /// Lowercase hex SHA-256 of the bytecode
+    pub sha256: String,
+}
+
+/// Outcome of a battle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BattleReport {
+    /// Cycles run
+    pub cycles: u32,
+    /// Winner champion ID, or None for a draw
+    pub winner: Option<u8>,
+    /// How a drawn battle was decided, if a tie-breaker picked the winner
+    pub tie_break: Option<TieBreak>,
+    /// State digest after the last cycle
+    pub final_digest: u64,
+    /// Per-champion results
+    pub champions: Vec<ChampionResult>,
+}
+
+/// How one champion finished the battle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChampionResult {
+    /// Champion ID
+    pub id: u8,
+    /// Name shown in reports
+    pub name: String,
+    /// Number of live instructions executed
+    pub live_count: u32,
+    /// Processes alive at the end
+    pub process_count: usize,
+}
+
+/// State digest at one cycle of the battle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Cycle the digest was taken after
+    pub cycle: u32,
+    /// [`GameEngine::state_digest`] at that cycle
+    pub digest: u64,
+}
+
+impl BundledChampion {
+    /// Capture a champion as it was loaded
+    ///
+    /// # Arguments
+    /// * `champion` - Loaded champion
+    pub fn from_champion(champion: &Champion) -> Self {
+        Self {
+            id: champion.id,
+            name: champion.name.clone(),
+            alias: champion.alias.clone(),
+            comment: champion.comment.clone(),
+            load_address: champion.load_address,
+            code: champion.code.clone(),
+            sha256: sha256_hex(&champion.code),
+        }
+    }
+
+    /// Create a fresh champion ready to be installed
+    fn to_champion(&self) -> Champion {
+        let mut champion = Champion::new(
+            self.id,
+            self.name.clone(),
+            self.comment.clone(),
+            self.code.clone(),
+            self.load_address,
+        );
+        champion.alias = self.alias.clone();
+        champion
+    }
+}
+
+impl ResultsBundle {
+    /// Bundle the results of a finished battle
+    ///
+    /// The battle is replayed from its initial state to build the replay
+    /// trail, so the engine must not have been rewound, restored or edited
+    /// in a what-if branch.
+    ///
+    /// # Arguments
+    /// * `engine` - Engine whose battle has ended
+    ///
+    /// # Returns
+    /// The bundle, or an error if the battle is still running or cannot be
+    /// reproduced from its initial state
+    pub fn record(engine: &GameEngine) -> Result<Self> {
+        if engine.state().running {
+            return Err(CoreWarError::bundle("Battle is still running"));
+        }
+
+        let config = *engine.config();
+        let champions: Vec<BundledChampion> = engine
+            .champions()
+            .iter()
+            .map(BundledChampion::from_champion)
+            .collect();
+        let (replayed, replay) = replay(&config, &champions)?;
+        let report = BattleReport::of(&replayed);
+
+        if report.final_digest != engine.state_digest() {
+            return Err(CoreWarError::bundle(format!(
+                "Battle cannot be reproduced from its initial state (cycle {} digest {:016x}, replay reached cycle {} digest {:016x})",
+                engine.state().cycle,
+                engine.state_digest(),
+                report.cycles,
+                report.final_digest
+            )));
+        }
+
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            config,
+            champions,
+            report,
+            replay,
+            snapshot: engine.snapshot(),
+        })
+    }
+
+    /// Replay the battle and check every part of the bundle against it
+    ///
+    /// # Returns
+    /// `Ok(())` if the bundle is consistent, or an error naming the first
+    /// part that does not match
+    pub fn verify(&self) -> Result<()> {
+        self.check_version()?;
+
+        for champion in &self.champions {
+            let actual = sha256_hex(&champion.code);
+            if actual != champion.sha256 {
+                return Err(CoreWarError::bundle(format!(
+                    "Champion {} ({}) code hash is {}, bundle says {}",
+                    champion.id, champion.name, actual, champion.sha256
+                )));
+            }
+        }
+
+        let (replayed, replay) = replay(&self.config, &self.champions)?;
+
+        if let Some((expected, actual)) = self
+            .replay
+            .iter()
+            .zip(&replay)
+            .find(|(expected, actual)| expected != actual)
+        {
+            return Err(CoreWarError::bundle(format!(
+                "Replay diverged at cycle {}: digest {:016x}, bundle says {:016x} at cycle {}",
+                actual.cycle, actual.digest, expected.digest, expected.cycle
+            )));
+        }
+        if self.replay.len() != replay.len() {
+            return Err(CoreWarError::bundle(format!(
+                "Replay trail has {} frames, bundle has {}",
+                replay.len(),
+                self.replay.len()
+            )));
+        }
+
+        let report = BattleReport::of(&replayed);
+        if report != self.report {
+            return Err(CoreWarError::bundle(format!(
+                "Report does not match the replay: replay ended at cycle {} with winner {:?}, bundle says cycle {} with winner {:?}",
+                report.cycles, report.winner, self.report.cycles, self.report.winner
+            )));
+        }
+
+        let mut restored = GameEngine::new(replay_config(&self.config));
+        restored.restore(self.snapshot.clone())?;
+        if restored.state_digest() != report.final_digest {
+            return Err(CoreWarError::bundle(format!(
+                "Final snapshot digest {:016x} does not match the replay ({:016x})",
+                restored.state_digest(),
+                report.final_digest
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Encode the bundle as JSON
+    ///
+    /// # Returns
+    /// The JSON text, or an error if encoding fails
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| CoreWarError::bundle(e.to_string()))
+    }
+
+    /// Decode a bundle from JSON
+    ///
+    /// # Arguments
+    /// * `json` - JSON produced by [`ResultsBundle::to_json`]
+    ///
+    /// # Returns
+    /// The bundle, or an error if the JSON is invalid or from another version
+    pub fn from_json(json: &str) -> Result<Self> {
+        let bundle: Self =
+            serde_json::from_str(json).map_err(|e| CoreWarError::bundle(e.to_string()))?;
+        bundle.check_version()?;
+        Ok(bundle)
+    }
+
+    /// Write the bundle to a file
+    ///
+    /// # Arguments
+    /// * `path` - Destination file, conventionally with a `.cwb` extension
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Read a bundle from a file
+    ///
+    /// # Arguments
+    /// * `path` - File written by [`ResultsBundle::write`]
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Check that the bundle was written by this format version
+    pub fn check_version(&self) -> Result<()> {
+        if self.version != BUNDLE_VERSION {
+            return Err(CoreWarError::bundle(format!(
+                "Unsupported bundle version {} (expected {})",
+                self.version, BUNDLE_VERSION
+            )));
+        }
+        self.snapshot.check_version()
+    }
+}
+
+impl BattleReport {
+    /// Summarize the battle an engine has finished
+    pub fn of(engine: &GameEngine) -> Self {
+        let processes = engine.processes();
+        Self {
+            cycles: engine.state().cycle,
+            winner: engine.state().winner,
+            tie_break: engine.state().tie_break,
+            final_digest: engine.state_digest(),
+            champions: engine
+                .champions()
+                .iter()
+                .map(|champion| ChampionResult {
+                    id: champion.id,
+                    name: champion.display_name().to_string(),
+                    live_count: champion.live_count,
+                    process_count: processes
+                        .iter()
+                        .filter(|p| p.champion_id == champion.id)
+                        .count(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for BattleReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} cycles, ", self.cycles)?;
+        match self.winner {
+            Some(id) => {
+                let name = self
+                    .champions
+                    .iter()
+                    .find(|c| c.id == id)
+                    .map(|c| c.name.as_str())
+                    .unwrap_or("Unknown");
+                write!(f, "winner Champion {} ({})", id, name)?;
+            }
+            None => write!(f, "draw")?,
+        }
+        write!(f, ", final digest {:016x}", self.final_digest)
+    }
+}
+
+/// Configuration for replaying a battle without output or pauses
+fn replay_config(config: &GameConfig) -> GameConfig {
+    GameConfig {
+        dump_cycles: 0,
+        verbose: false,
+        start_paused: false,
+        track_coverage: false,
+        checkpoint_interval: 0,
+        ..*config
+    }
+}
+
+/// Run a battle from its initial state, recording the replay trail
+fn replay(
+    config: &GameConfig,
+    champions: &[BundledChampion],
+) -> Result<(GameEngine, Vec<ReplayFrame>)> {
+    let mut engine = GameEngine::new(replay_config(config));
+    engine.install_champions(champions.iter().map(BundledChampion::to_champion).collect())?;
+    engine.start()?;
+
+    let mut frames = Vec::new();
+    engine.step_until(|engine| {
+        let cycle = engine.state().cycle;
+        if cycle.is_multiple_of(REPLAY_INTERVAL) {
+            frames.push(ReplayFrame {
+                cycle,
+                digest: engine.state_digest(),
+            });
+        }
+        false
+    })?;
+
+    let cycle = engine.state().cycle;
+    if frames.last().is_none_or(|frame| frame.cycle != cycle) {
+        frames.push(ReplayFrame {
+            cycle,
+            digest: engine.state_digest(),
+        });
+    }
+    Ok((engine, frames))
+}
+
+/// Hash bytes with SHA-256 as lowercase hex
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished_battle() -> GameEngine {
+        let mut engine = GameEngine::builder()
+            .seed(11)
+            .max_cycles(450)
+            .champion_bytes("Living", [0x01, 0x02, 0x02, 0x02, 0x02].repeat(60))
+            .champion_bytes("Forker", [0x0C, 0x02, 0x02, 0x02, 0x02, 0x01].repeat(50))
+            .build()
+            .unwrap();
+        engine.run_to_completion().unwrap();
+        engine
+    }
+
+    #[test]
+    fn test_recorded_bundle_verifies() {
+        let engine = finished_battle();
+        let bundle = ResultsBundle::record(&engine).unwrap();
+
+        assert_eq!(bundle.report.cycles, 450);
+        assert_eq!(bundle.report.winner, engine.state().winner);
+        assert_eq!(bundle.report.final_digest, engine.state_digest());
+        let cycles: Vec<u32> = bundle.replay.iter().map(|frame| frame.cycle).collect();
+        assert_eq!(cycles, vec![100, 200, 300, 400, 450]);
+        assert_eq!(bundle.champions[0].sha256.len(), 64);
+
+        let decoded = ResultsBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        decoded.verify().unwrap();
+    }
+
+    #[test]
+    fn test_tampered_bundle_fails_verification() {
+        let bundle = ResultsBundle::record(&finished_battle()).unwrap();
+
+        let mut tampered = bundle.clone();
+        tampered.champions[1].code[0] = 0x00;
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = bundle.clone();
+        tampered.champions[1].code[0] = 0x00;
+        tampered.champions[1].sha256 = sha256_hex(&tampered.champions[1].code);
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = bundle.clone();
+        tampered.report.winner = Some(3);
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = bundle.clone();
+        tampered.replay[2].digest ^= 1;
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = bundle.clone();
+        tampered.snapshot.memory[0] ^= 0xFF;
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = bundle;
+        tampered.version = BUNDLE_VERSION + 1;
+        assert!(tampered.verify().is_err());
+    }
+
+    #[test]
+    fn test_record_rejects_running_or_edited_battles() {
+        let mut engine = GameEngine::builder()
+            .max_cycles(200)
+            .champion_bytes("Live", [0x01; 100])
+            .champion_bytes("Live", [0x01; 100])
+            .build()
+            .unwrap();
+        assert!(ResultsBundle::record(&engine).is_err());
+
+        engine.run_cycles(10).unwrap();
+        engine.branch().unwrap();
+        engine.poke_memory(500, 0x0C).unwrap();
+        engine.run_cycles(200).unwrap();
+        assert!(!engine.state().running);
+        assert!(ResultsBundle::record(&engine).is_err());
+    }
+
+    #[test]
+    fn test_report_counts_processes_left_at_the_end() {
+        // Every process faults on its first instruction, mid check period
+        let mut engine = GameEngine::builder()
+            .champion_bytes("Fault", [0xEE; 10])
+            .champion_bytes("Fault", [0xEE; 10])
+            .build()
+            .unwrap();
+        engine.run_to_completion().unwrap();
+        assert!(engine.state().cycle < engine.scheduler_stats().cycle_to_die);
+
+        let report = BattleReport::of(&engine);
+        assert!(report.champions.iter().all(|c| c.process_count == 0));
+    }
+}
@@ -0,0 +1,56 @@
+/// Clock for timing battles, in native builds and in browsers
+///
+/// `std::time::Instant` panics on `wasm32-unknown-unknown`, whose standard
+/// library has no clock. The engine, scheduler and throttle take their
+/// [`Instant`] from here instead: the standard one everywhere it works, and
+/// with the `wasm` feature in a browser, one counting the milliseconds of
+/// JavaScript's `Date.now()`.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown", feature = "wasm")))]
+pub use std::time::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "wasm"))]
+pub use browser::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "wasm"))]
+mod browser {
+    use std::ops::Add;
+    use std::time::Duration;
+
+    /// A point in time, as time since the Unix epoch
+    ///
+    /// Offers the part of `std::time::Instant` the crate uses.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Instant(Duration);
+
+    impl Instant {
+        /// Get the current time
+        pub fn now() -> Self {
+            Self(Duration::from_secs_f64(
+                js_sys::Date::now().max(0.0) / 1000.0,
+            ))
+        }
+
+        /// Get the time elapsed since this instant, zero if the clock went back
+        pub fn elapsed(&self) -> Duration {
+            Self::now().saturating_duration_since(*self)
+        }
+
+        /// Get the time from an earlier instant to this one, zero if it is later
+        pub fn saturating_duration_since(&self, earlier: Self) -> Duration {
+            self.0.saturating_sub(earlier.0)
+        }
+
+        /// Get the time from an earlier instant to this one, zero if it is later
+        pub fn duration_since(&self, earlier: Self) -> Duration {
+            self.saturating_duration_since(earlier)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Self;
+
+        fn add(self, duration: Duration) -> Self {
+            Self(self.0 + duration)
+        }
+    }
+}
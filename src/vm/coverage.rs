@@ -0,0 +1,224 @@
+/// Instruction-level code coverage for champions
+///
+/// This module records which bytes of each champion's original code were
+/// executed during a battle, so warrior authors can find dead code paths, and
+/// how often an instruction at each offset ran, so they can find hot loops.
+use crate::vm::Champion;
+use std::fmt;
+use std::ops::Range;
+
+/// Code region of one champion and the bytes executed within it
+#[derive(Debug, Clone)]
+struct CoverageRegion {
+    champion_id: u8,
+    name: String,
+    load_address: usize,
+    executed: Vec<bool>,
+    executions: Vec<u64>,
+}
+
+/// Tracks executed bytes across every champion's original code
+#[derive(Debug, Clone, Default)]
+pub struct CoverageTracker {
+    regions: Vec<CoverageRegion>,
+    memory_size: usize,
+}
+
+impl CoverageTracker {
+    /// Create a tracker for the champions' code as loaded
+    ///
+    /// # Arguments
+    /// * `champions` - The loaded champions
+    /// * `memory_size` - Size of the memory the champions are loaded in
+    ///
+    /// # Returns
+    /// A new CoverageTracker with nothing executed yet
+    pub fn new(champions: &[Champion], memory_size: usize) -> Self {
+        let regions = champions
+            .iter()
+            .map(|champion| CoverageRegion {
+                champion_id: champion.id,
+                name: champion.display_name().to_string(),
+                load_address: champion.load_address,
+                executed: vec![false; champion.code_size()],
+                executions: vec![0; champion.code_size()],
+            })
+            .collect();
+
+        Self {
+            regions,
+            memory_size,
+        }
+    }
+
+    /// Record that an instruction was executed
+    ///
+    /// # Arguments
+    /// * `address` - Address of the instruction's opcode
+    /// * `size` - Number of bytes the instruction occupies
+    pub fn record(&mut self, address: usize, size: usize) {
+        for region in &mut self.regions {
+            let relative = (address + self.memory_size - region.load_address) % self.memory_size;
+            if let Some(executions) = region.executions.get_mut(relative) {
+                *executions += 1;
+            }
+        }
+
+        for offset in 0..size.max(1) {
+            let address = (address + offset) % self.memory_size;
+            for region in &mut self.regions {
+                let relative =
+                    (address + self.memory_size - region.load_address) % self.memory_size;
+                if let Some(executed) = region.executed.get_mut(relative) {
+                    *executed = true;
+                }
+            }
+        }
+    }
+
+    /// Build the coverage report for every champion
+    ///
+    /// # Returns
+    /// One entry per champion, in load order
+    pub fn report(&self) -> Vec<ChampionCoverage> {
+        self.regions
+            .iter()
+            .map(|region| ChampionCoverage {
+                champion_id: region.champion_id,
+                name: region.name.clone(),
+                executed: region.executed.clone(),
+                executions: region.executions.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Coverage of a single champion's code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChampionCoverage {
+    /// Champion ID
+    pub champion_id: u8,
+    /// Champion name
+    pub name: String,
+    /// Whether each byte of the original code was executed
+    pub executed: Vec<bool>,
+    /// How many times an instruction starting at each byte was executed
+    pub executions: Vec<u64>,
+}
+
+impl ChampionCoverage {
+    /// Number of code bytes that were executed
+    pub fn executed_bytes(&self) -> usize {
+        self.executed.iter().filter(|&&executed| executed).count()
+    }
+
+    /// Total number of code bytes
+    pub fn total_bytes(&self) -> usize {
+        self.executed.len()
+    }
+
+    /// Percentage of code bytes that were executed
+    pub fn percentage(&self) -> f64 {
+        if self.executed.is_empty() {
+            return 0.0;
+        }
+        self.executed_bytes() as f64 * 100.0 / self.total_bytes() as f64
+    }
+
+    /// Offsets of code that never executed, merged into contiguous ranges
+    pub fn unexecuted_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (offset, &executed) in self.executed.iter().enumerate() {
+            if executed {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(range) if range.end == offset => range.end += 1,
+                _ => ranges.push(offset..offset + 1),
+            }
+        }
+        ranges
+    }
+}
+
+impl fmt::Display for ChampionCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Champion {} ({}): {}/{} bytes executed ({:.1}%)",
+            self.champion_id,
+            self.name,
+            self.executed_bytes(),
+            self.total_bytes(),
+            self.percentage()
+        )?;
+
+        let ranges = self.unexecuted_ranges();
+        if !ranges.is_empty() {
+            let ranges: Vec<String> = ranges
+                .iter()
+                .map(|range| format!("0x{:04X}-0x{:04X}", range.start, range.end - 1))
+                .collect();
+            write!(f, "\n  never executed: {}", ranges.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn champion(id: u8, size: usize, load_address: usize) -> Champion {
+        Champion::new(
+            id,
+            format!("C{}", id),
+            String::new(),
+            vec![0; size],
+            load_address,
+        )
+    }
+
+    #[test]
+    fn test_record_marks_champion_bytes() {
+        let champions = vec![champion(1, 10, 0), champion(2, 10, 100)];
+        let mut tracker = CoverageTracker::new(&champions, 1024);
+
+        tracker.record(0, 5);
+        tracker.record(105, 2);
+        tracker.record(500, 5); // Outside every champion
+
+        let report = tracker.report();
+        assert_eq!(report[0].executed_bytes(), 5);
+        assert_eq!(report[0].percentage(), 50.0);
+        assert_eq!(report[0].unexecuted_ranges(), vec![5..10]);
+        assert_eq!(report[1].executed_bytes(), 2);
+        assert_eq!(report[1].unexecuted_ranges(), vec![0..5, 7..10]);
+    }
+
+    #[test]
+    fn test_record_counts_executions_at_opcode() {
+        let champions = vec![champion(1, 10, 0)];
+        let mut tracker = CoverageTracker::new(&champions, 1024);
+
+        tracker.record(0, 5);
+        tracker.record(5, 5);
+        tracker.record(0, 5);
+
+        let executions = &tracker.report()[0].executions;
+        assert_eq!(executions[0], 2);
+        assert_eq!(executions[5], 1);
+        assert_eq!(executions.iter().sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn test_record_wraps_around_memory() {
+        let champions = vec![champion(1, 8, 1020)];
+        let mut tracker = CoverageTracker::new(&champions, 1024);
+
+        tracker.record(1022, 4); // Covers 1022, 1023, 0, 1
+
+        let report = tracker.report();
+        assert_eq!(report[0].unexecuted_ranges(), vec![0..2, 6..8]);
+    }
+}
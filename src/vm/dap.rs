@@ -0,0 +1,938 @@
+/// Debug Adapter Protocol server
+///
+/// `corewar dap` lets VS Code and other editors debug warriors: set
+/// breakpoints in `.s` files, step a process and look at its registers and
+/// at the core. The editor starts the adapter and talks to it over stdin and
+/// stdout, or connects to `corewar dap --listen ADDR`, with the JSON
+/// messages of the [Debug Adapter Protocol], each after a `Content-Length`
+/// header.
+///
+/// A launch configuration names the warrior to debug and the ones it
+/// fights:
+///
+/// ```json
+/// {
+///     "type": "corewar",
+///     "request": "launch",
+///     "program": "${file}",
+///     "opponents": ["opponents/imp.s", "dwarf.cor"],
+///     "maxCycles": 20000,
+///     "seed": 0,
+///     "stopOnEntry": true
+/// }
+/// ```
+///
+/// Warriors given as sources are assembled with their [`Listing`], which
+/// maps source lines to code addresses, so breakpoints may go in any of them
+/// and every process shows the line it is at. Each live process is a
+/// thread, with one stack frame whose only scope holds its registers, PC and
+/// carry. Stepping a thread runs the battle until that process moves to
+/// another instruction; every other process runs meanwhile, as in the
+/// battle itself.
+///
+/// [Debug Adapter Protocol]: https://microsoft.github.io/debug-adapter-protocol/
+use crate::assembler::disassembler;
+use crate::assembler::{Assembler, Listing};
+use crate::error::{CoreWarError, Result};
+use crate::vm::{Breakpoint, BreakpointHit, GameConfig, GameEngine, Process, TickOutcome};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Largest message accepted from the editor, in bytes
+pub const MAX_MESSAGE: usize = 1024 * 1024;
+
+/// A warrior in the battle being debugged
+struct Warrior {
+    /// Address its code was loaded at
+    load_address: usize,
+    /// Size of its code in bytes
+    code_size: usize,
+    /// Its debug info, if it was assembled from source
+    listing: Option<Listing>,
+}
+
+/// A breakpoint the editor set in a source file
+#[derive(Debug, Clone)]
+struct SourceBreakpoint {
+    /// ID the editor knows it by
+    id: u64,
+    /// File it was set in
+    file: PathBuf,
+    /// Line it was set on
+    line: usize,
+    /// Core address it stops at, once a warrior's code holds the line
+    address: Option<usize>,
+    /// Line of that code, which may follow the one set
+    verified_line: Option<usize>,
+}
+
+/// The battle being debugged
+struct Session {
+    /// The battle
+    engine: GameEngine,
+    /// Its warriors, in champion order
+    warriors: Vec<Warrior>,
+    /// Whether to stop before the first cycle rather than run
+    stop_on_entry: bool,
+}
+
+/// Answers one editor's debugging requests
+pub struct DapServer {
+    /// Sequence number of the last message sent
+    seq: u64,
+    /// The battle, once launched
+    session: Option<Session>,
+    /// Breakpoints the editor set, in every file
+    breakpoints: Vec<SourceBreakpoint>,
+    /// ID of the last breakpoint created
+    last_breakpoint_id: u64,
+    /// Whether the editor finished configuring the session
+    configured: bool,
+    /// Whether the battle has been started or stopped on entry
+    started: bool,
+    /// Whether the editor asked to end the session
+    done: bool,
+}
+
+impl Default for DapServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DapServer {
+    /// Create a server waiting for an editor to initialize it
+    pub fn new() -> Self {
+        Self {
+            seq: 0,
+            session: None,
+            breakpoints: Vec::new(),
+            last_breakpoint_id: 0,
+            configured: false,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Answer messages until the editor disconnects or closes the input
+    ///
+    /// # Arguments
+    /// * `input` - Where the editor's messages are read from
+    /// * `output` - Where responses and events are written
+    ///
+    /// # Returns
+    /// `Ok(())` when the session ends, or an error if a message cannot be
+    /// read or written
+    pub fn serve<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> Result<()> {
+        while !self.done {
+            let Some(request) = read_message(&mut input)? else {
+                break;
+            };
+            for message in self.handle(&request) {
+                write_message(&mut output, &message)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer one request
+    ///
+    /// # Arguments
+    /// * `request` - A request from the editor
+    ///
+    /// # Returns
+    /// The response, followed by any events it raised
+    pub fn handle(&mut self, request: &Value) -> Vec<Value> {
+        let command = request["command"].as_str().unwrap_or_default();
+        let mut events = Vec::new();
+        let result = self.dispatch(command, &request["arguments"], &mut events);
+        if let Err(e) = &result {
+            warn!("{} request failed: {}", command, e);
+        }
+
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(e) => response["message"] = json!(e.to_string()),
+        }
+        let mut messages = vec![self.stamp(response)];
+        for (event, body) in events {
+            let event = json!({ "type": "event", "event": event, "body": body });
+            messages.push(self.stamp(event));
+        }
+        messages
+    }
+
+    /// Give a message the next sequence number
+    fn stamp(&mut self, mut message: Value) -> Value {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        message
+    }
+
+    /// Carry out a request
+    fn dispatch(
+        &mut self,
+        command: &str,
+        args: &Value,
+        events: &mut Vec<(&'static str, Value)>,
+    ) -> Result<Value> {
+        match command {
+            "initialize" => {
+                events.push(("initialized", json!({})));
+                Ok(json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsReadMemoryRequest": true,
+                    "supportsEvaluateForHovers": true,
+                    "supportsTerminateRequest": true,
+                }))
+            }
+            "launch" => {
+                self.launch(args, events)?;
+                self.start(events)?;
+                Ok(Value::Null)
+            }
+            "setBreakpoints" => self.set_breakpoints(args),
+            "setExceptionBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "configurationDone" => {
+                self.configured = true;
+                self.start(events)?;
+                Ok(Value::Null)
+            }
+            "threads" => Ok(self.threads()),
+            "stackTrace" => self.stack_trace(args),
+            "scopes" => {
+                let frame = process_id(&args["frameId"])?;
+                Ok(json!({
+                    "scopes": [{
+                        "name": "Registers",
+                        "presentationHint": "registers",
+                        "variablesReference": frame,
+                        "expensive": false,
+                    }]
+                }))
+            }
+            "variables" => self.variables(args),
+            "evaluate" => self.evaluate(args),
+            "readMemory" => self.read_memory(args),
+            "continue" => {
+                let outcome = self.session()?.engine.step_until(|_| false)?;
+                self.stopped(outcome, "breakpoint", None, events);
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "next" | "stepIn" | "stepOut" => {
+                let thread = process_id(&args["threadId"])?;
+                let engine = &mut self.session()?.engine;
+                let pc = engine
+                    .process(thread)
+                    .ok_or_else(|| CoreWarError::config(format!("No live process {}", thread)))?
+                    .pc;
+                let outcome =
+                    engine.step_until(|e| e.process(thread).is_none_or(|p| p.pc != pc))?;
+                self.stopped(outcome, "step", Some(thread), events);
+                Ok(Value::Null)
+            }
+            "pause" => {
+                // Battles only run while a request is answered, so they are
+                // already paused by the time this one is read
+                let thread = self.session()?.engine.processes().first().map(|p| p.id);
+                events.push(("stopped", stopped_body("pause", thread, &[])));
+                Ok(Value::Null)
+            }
+            "terminate" => {
+                events.push(("terminated", json!({})));
+                Ok(Value::Null)
+            }
+            "disconnect" => {
+                self.done = true;
+                Ok(Value::Null)
+            }
+            _ => Err(CoreWarError::protocol(format!(
+                "Unsupported request '{}'",
+                command
+            ))),
+        }
+    }
+
+    /// Get the launched battle
+    fn session(&mut self) -> Result<&mut Session> {
+        self.session
+            .as_mut()
+            .ok_or_else(|| CoreWarError::config("No battle has been launched"))
+    }
+
+    /// Load the warriors of a launch request and start their battle
+    fn launch(&mut self, args: &Value, events: &mut Vec<(&'static str, Value)>) -> Result<()> {
+        if self.session.is_some() {
+            return Err(CoreWarError::config("A battle is already launched"));
+        }
+        let program = args["program"]
+            .as_str()
+            .ok_or_else(|| CoreWarError::config("launch needs the program to debug"))?;
+        let mut paths = vec![PathBuf::from(program)];
+        for opponent in args["opponents"].as_array().into_iter().flatten() {
+            let opponent = opponent
+                .as_str()
+                .ok_or_else(|| CoreWarError::config("Opponents must be file paths"))?;
+            paths.push(PathBuf::from(opponent));
+        }
+
+        let config = GameConfig {
+            max_cycles: u32_arg(&args["maxCycles"])?.unwrap_or(0),
+            seed: args["seed"]
+                .as_u64()
+                .unwrap_or(crate::vm::rng::DEFAULT_SEED),
+            ..GameConfig::default()
+        };
+        let mut builder = GameEngine::builder().config(config);
+        let mut listings = Vec::new();
+        for path in &paths {
+            let (cor, listing) = assemble(&canonical(path))?;
+            builder = builder.champion_cor(cor);
+            listings.push(listing);
+        }
+        let engine = builder.build()?;
+        let warriors = engine
+            .champions()
+            .iter()
+            .zip(listings)
+            .map(|(champion, listing)| Warrior {
+                load_address: champion.load_address,
+                code_size: champion.code.len(),
+                listing,
+            })
+            .collect();
+        info!(
+            "Debugging {} against {} opponents",
+            program,
+            paths.len() - 1
+        );
+        let names: Vec<&str> = engine
+            .champions()
+            .iter()
+            .map(|c| c.display_name())
+            .collect();
+        events.push((
+            "output",
+            json!({
+                "category": "console",
+                "output": format!("Loaded {}\n", names.join(", ")),
+            }),
+        ));
+
+        self.session = Some(Session {
+            engine,
+            warriors,
+            stop_on_entry: args["stopOnEntry"].as_bool().unwrap_or(false),
+        });
+        self.install_breakpoints(events);
+        Ok(())
+    }
+
+    /// Stop on entry or run, once the battle is launched and configured
+    fn start(&mut self, events: &mut Vec<(&'static str, Value)>) -> Result<()> {
+        if self.started || !self.configured {
+            return Ok(());
+        }
+        let Some(session) = self.session.as_mut() else {
+            return Ok(());
+        };
+        self.started = true;
+        if session.stop_on_entry {
+            let thread = session.engine.processes().first().map(|p| p.id);
+            events.push(("stopped", stopped_body("entry", thread, &[])));
+        } else {
+            let outcome = session.engine.step_until(|_| false)?;
+            self.stopped(outcome, "breakpoint", None, events);
+        }
+        Ok(())
+    }
+
+    /// Replace the breakpoints of a source file
+    fn set_breakpoints(&mut self, args: &Value) -> Result<Value> {
+        let file = args["source"]["path"]
+            .as_str()
+            .map(|path| canonical(Path::new(path)))
+            .ok_or_else(|| CoreWarError::config("setBreakpoints needs a source path"))?;
+        let lines: Vec<usize> = match args["breakpoints"].as_array() {
+            Some(breakpoints) => breakpoints
+                .iter()
+                .filter_map(|b| b["line"].as_u64())
+                .map(|line| line as usize)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let (kept, removed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.breakpoints)
+            .into_iter()
+            .partition(|b| b.file != file);
+        self.breakpoints = kept;
+        if let Some(session) = self.session.as_mut() {
+            for address in removed.iter().filter_map(|b| b.address) {
+                session
+                    .engine
+                    .remove_breakpoint(&Breakpoint::Address(address));
+            }
+        }
+
+        let mut answered = Vec::new();
+        for line in lines {
+            self.last_breakpoint_id += 1;
+            let mut breakpoint = SourceBreakpoint {
+                id: self.last_breakpoint_id,
+                file: file.clone(),
+                line,
+                address: None,
+                verified_line: None,
+            };
+            if let Some(session) = self.session.as_mut() {
+                session.resolve(&mut breakpoint);
+            }
+            answered.push(breakpoint_body(&breakpoint));
+            self.breakpoints.push(breakpoint);
+        }
+        Ok(json!({ "breakpoints": answered }))
+    }
+
+    /// Place the breakpoints set before the launch, and tell the editor
+    fn install_breakpoints(&mut self, events: &mut Vec<(&'static str, Value)>) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        for breakpoint in &mut self.breakpoints {
+            session.resolve(breakpoint);
+            events.push((
+                "breakpoint",
+                json!({ "reason": "changed", "breakpoint": breakpoint_body(breakpoint) }),
+            ));
+        }
+    }
+
+    /// Tell the editor where a run stopped
+    fn stopped(
+        &mut self,
+        outcome: TickOutcome,
+        reason: &'static str,
+        thread: Option<u32>,
+        events: &mut Vec<(&'static str, Value)>,
+    ) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        match outcome {
+            TickOutcome::Finished => {
+                let engine = &session.engine;
+                let result = match engine.state().winner {
+                    Some(id) => engine
+                        .champions()
+                        .iter()
+                        .find(|c| c.id == id)
+                        .map(|c| format!("{} won", c.display_name()))
+                        .unwrap_or_else(|| format!("Champion {} won", id)),
+                    None => "No winner".to_string(),
+                };
+                let cycle = engine.state().cycle;
+                events.push((
+                    "output",
+                    json!({
+                        "category": "console",
+                        "output": format!("Battle over after {} cycles: {}\n", cycle, result),
+                    }),
+                ));
+                events.push(("exited", json!({ "exitCode": 0 })));
+                events.push(("terminated", json!({})));
+            }
+            TickOutcome::Breakpoint(hits) => {
+                let ids: Vec<u64> = self
+                    .breakpoints
+                    .iter()
+                    .filter(|b| hits.iter().any(|hit| hits_address(hit, b.address)))
+                    .map(|b| b.id)
+                    .collect();
+                let thread = hits.iter().find_map(|hit| hit.process_id).or(thread);
+                events.push(("stopped", stopped_body("breakpoint", thread, &ids)));
+            }
+            TickOutcome::Running => {
+                let engine = &session.engine;
+                let thread = thread
+                    .filter(|&id| engine.process(id).is_some())
+                    .or_else(|| engine.processes().first().map(|p| p.id));
+                events.push(("stopped", stopped_body(reason, thread, &[])));
+            }
+        }
+    }
+
+    /// List the live processes as threads
+    fn threads(&self) -> Value {
+        let Some(session) = &self.session else {
+            return json!({ "threads": [] });
+        };
+        let threads: Vec<Value> = session
+            .engine
+            .processes()
+            .iter()
+            .map(|process| {
+                let champion = session
+                    .engine
+                    .champions()
+                    .iter()
+                    .find(|c| c.id == process.champion_id)
+                    .map_or("Champion", |c| c.display_name());
+                json!({
+                    "id": process.id,
+                    "name": format!("{} process {}", champion, process.id),
+                })
+            })
+            .collect();
+        json!({ "threads": threads })
+    }
+
+    /// Show where a process is, in its source when it has one
+    fn stack_trace(&mut self, args: &Value) -> Result<Value> {
+        let thread = process_id(&args["threadId"])?;
+        let session = self.session()?;
+        let process = session.process(thread)?;
+        let memory = session.engine.memory();
+        let pc = process.pc % memory.size();
+        let instruction = disassembler::disassemble_memory(memory, pc, 0, 0)
+            .into_iter()
+            .next()
+            .map_or_else(String::new, |line| line.text);
+
+        let mut frame = json!({
+            "id": thread,
+            "name": format!("{:04X}  {}", pc, instruction),
+            "line": 0,
+            "column": 0,
+            "instructionPointerReference": format!("0x{:04X}", pc),
+        });
+        match session.locate(pc) {
+            Some((file, line)) => {
+                let name = file
+                    .file_name()
+                    .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+                frame["source"] = json!({ "name": name, "path": file });
+                frame["line"] = json!(line);
+                frame["column"] = json!(1);
+            }
+            None => frame["presentationHint"] = json!("subtle"),
+        }
+        Ok(json!({ "stackFrames": [frame], "totalFrames": 1 }))
+    }
+
+    /// List the registers of a process
+    fn variables(&mut self, args: &Value) -> Result<Value> {
+        let thread = process_id(&args["variablesReference"])?;
+        let process = self.session()?.process(thread)?;
+        let mut variables: Vec<Value> = process
+            .registers
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                json!({
+                    "name": format!("r{}", i + 1),
+                    "value": value.to_string(),
+                    "type": "i32",
+                    "variablesReference": 0,
+                })
+            })
+            .collect();
+        variables.push(json!({
+            "name": "pc",
+            "value": format!("0x{:04X}", process.pc),
+            "memoryReference": format!("0x{:04X}", process.pc),
+            "variablesReference": 0,
+        }));
+        variables.push(json!({
+            "name": "carry",
+            "value": process.carry.to_string(),
+            "type": "bool",
+            "variablesReference": 0,
+        }));
+        Ok(json!({ "variables": variables }))
+    }
+
+    /// Show a register, the PC or the carry of the selected process
+    fn evaluate(&mut self, args: &Value) -> Result<Value> {
+        let expression = args["expression"].as_str().unwrap_or_default().trim();
+        let session = self.session()?;
+        let thread = match args.get("frameId") {
+            Some(frame) if !frame.is_null() => process_id(frame)?,
+            _ => session
+                .engine
+                .processes()
+                .first()
+                .map(|p| p.id)
+                .ok_or_else(|| CoreWarError::config("No live process"))?,
+        };
+        let process = session.process(thread)?;
+        let result = match expression.to_lowercase().as_str() {
+            "pc" => format!("0x{:04X}", process.pc),
+            "carry" => process.carry.to_string(),
+            register => register
+                .strip_prefix('r')
+                .and_then(|n| n.parse::<usize>().ok())
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| process.registers.get(i))
+                .map(i32::to_string)
+                .ok_or_else(|| {
+                    CoreWarError::config(format!(
+                        "Cannot evaluate '{}': expected r1 to r16, pc or carry",
+                        expression
+                    ))
+                })?,
+        };
+        Ok(json!({ "result": result, "variablesReference": 0 }))
+    }
+
+    /// Read bytes of the core
+    fn read_memory(&mut self, args: &Value) -> Result<Value> {
+        let reference = args["memoryReference"].as_str().unwrap_or_default();
+        let base = match reference.strip_prefix("0x") {
+            Some(hex) => i64::from_str_radix(hex, 16).ok(),
+            None => reference.parse().ok(),
+        }
+        .ok_or_else(|| CoreWarError::config(format!("Invalid memory reference '{}'", reference)))?;
+        let memory = self.session()?.engine.memory();
+        let size = memory.size();
+        let start = (base + args["offset"].as_i64().unwrap_or(0)).rem_euclid(size as i64) as usize;
+        let count = (args["count"].as_u64().unwrap_or(0) as usize).min(size);
+        let data: Vec<u8> = (0..count).map(|i| memory.read_byte(start + i)).collect();
+        Ok(json!({
+            "address": format!("0x{:04X}", start),
+            "data": STANDARD.encode(&data),
+        }))
+    }
+}
+
+impl Session {
+    /// Get a live process
+    fn process(&self, id: u32) -> Result<&Process> {
+        self.engine
+            .process(id)
+            .ok_or_else(|| CoreWarError::config(format!("No live process {}", id)))
+    }
+
+    /// Find the source line a core address was assembled from
+    fn locate(&self, address: usize) -> Option<(&Path, usize)> {
+        let size = self.engine.memory().size();
+        self.warriors.iter().find_map(|warrior| {
+            let offset = (address + size - warrior.load_address % size) % size;
+            if offset >= warrior.code_size {
+                return None;
+            }
+            let entry = warrior.listing.as_ref()?.location_of(offset)?;
+            Some((entry.file.as_deref()?, entry.line))
+        })
+    }
+
+    /// Find where a source breakpoint stops, and install it
+    fn resolve(&mut self, breakpoint: &mut SourceBreakpoint) {
+        let size = self.engine.memory().size();
+        let found = self.warriors.iter().find_map(|warrior| {
+            let entry = warrior
+                .listing
+                .as_ref()?
+                .address_of(Some(&breakpoint.file), breakpoint.line)?;
+            Some(((warrior.load_address + entry.address) % size, entry.line))
+        });
+        if let Some((address, line)) = found {
+            breakpoint.address = Some(address);
+            breakpoint.verified_line = Some(line);
+            self.engine.add_breakpoint(Breakpoint::Address(address));
+        }
+    }
+}
+
+/// Assemble a source, keeping its debug info, or read a .cor file
+fn assemble(path: &Path) -> Result<(Vec<u8>, Option<Listing>)> {
+    if path.extension().is_some_and(|ext| ext == "cor") {
+        return Ok((fs::read(path)?, None));
+    }
+    let assembler = Assembler::new(false);
+    let cor = assembler.assemble_path(path)?;
+    Ok((cor, assembler.listing()))
+}
+
+/// Resolve a path the way both the editor and the listing may name it
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Check whether a breakpoint hit is the address of a source breakpoint
+fn hits_address(hit: &BreakpointHit, address: Option<usize>) -> bool {
+    matches!(hit.breakpoint, Breakpoint::Address(a) if Some(a) == address)
+}
+
+/// Describe a source breakpoint to the editor
+fn breakpoint_body(breakpoint: &SourceBreakpoint) -> Value {
+    let mut body = json!({
+        "id": breakpoint.id,
+        "verified": breakpoint.address.is_some(),
+        "line": breakpoint.verified_line.unwrap_or(breakpoint.line),
+        "source": { "path": breakpoint.file },
+    });
+    if breakpoint.address.is_none() {
+        body["message"] = json!("No warrior code at or after this line");
+    }
+    body
+}
+
+/// Build the body of a stopped event
+fn stopped_body(reason: &str, thread: Option<u32>, breakpoints: &[u64]) -> Value {
+    let mut body = json!({ "reason": reason, "allThreadsStopped": true });
+    if let Some(thread) = thread {
+        body["threadId"] = json!(thread);
+    }
+    if !breakpoints.is_empty() {
+        body["hitBreakpointIds"] = json!(breakpoints);
+    }
+    body
+}
+
+/// Read a thread, frame or variables reference, which are all process IDs
+fn process_id(value: &Value) -> Result<u32> {
+    value
+        .as_u64()
+        .and_then(|id| u32::try_from(id).ok())
+        .ok_or_else(|| CoreWarError::config(format!("Invalid process reference {}", value)))
+}
+
+/// Read an optional number argument
+fn u32_arg(value: &Value) -> Result<Option<u32>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    value
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .map(Some)
+        .ok_or_else(|| CoreWarError::config(format!("Invalid number {}", value)))
+}
+
+/// Read one message, or None at the end of the input
+///
+/// # Arguments
+/// * `input` - Stream of `Content-Length`-framed JSON messages
+///
+/// # Returns
+/// The message, or an error if its header or JSON is invalid
+pub fn read_message<R: BufRead>(input: &mut R) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return match length {
+                None => Ok(None),
+                Some(_) => Err(CoreWarError::protocol("Message ends in its header")),
+            };
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            length = Some(value.trim().parse::<usize>().map_err(|_| {
+                CoreWarError::protocol(format!("Invalid Content-Length '{}'", value.trim()))
+            })?);
+        }
+    }
+
+    let length = length.unwrap_or_default();
+    if length > MAX_MESSAGE {
+        return Err(CoreWarError::protocol(format!(
+            "Message of {} bytes is larger than {}",
+            length, MAX_MESSAGE
+        )));
+    }
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| CoreWarError::protocol(format!("Invalid message: {}", e)))
+}
+
+/// Write one message with its `Content-Length` header
+pub fn write_message<W: Write>(output: &mut W, message: &Value) -> Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WARRIOR: &str =
+        ".name \"Looper\"\n.comment \"\"\n\nloop: live %1\n    ld %0, r2\n    zjmp %:loop\n";
+
+    fn request(server: &mut DapServer, command: &str, arguments: Value) -> Vec<Value> {
+        server.handle(&json!({
+            "seq": 1,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        }))
+    }
+
+    fn events<'a>(messages: &'a [Value], name: &str) -> Vec<&'a Value> {
+        messages.iter().filter(|m| m["event"] == name).collect()
+    }
+
+    #[test]
+    fn test_breakpoints_stop_in_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("looper.s");
+        fs::write(&program, WARRIOR).unwrap();
+        let program = canonical(&program);
+
+        let mut server = DapServer::new();
+        let messages = request(&mut server, "initialize", json!({ "adapterID": "corewar" }));
+        assert_eq!(
+            messages[0]["body"]["supportsConfigurationDoneRequest"],
+            true
+        );
+        assert_eq!(events(&messages, "initialized").len(), 1);
+
+        // Set before the launch, so verified once the warrior is assembled
+        let messages = request(
+            &mut server,
+            "setBreakpoints",
+            json!({ "source": { "path": program }, "breakpoints": [{ "line": 3 }] }),
+        );
+        assert_eq!(messages[0]["body"]["breakpoints"][0]["verified"], false);
+        let messages = request(
+            &mut server,
+            "launch",
+            json!({ "program": program, "maxCycles": 200, "stopOnEntry": true }),
+        );
+        assert_eq!(messages[0]["success"], true, "{}", messages[0]);
+        let changed = events(&messages, "breakpoint");
+        assert_eq!(changed[0]["body"]["breakpoint"]["verified"], true);
+        assert_eq!(changed[0]["body"]["breakpoint"]["line"], 4);
+
+        let messages = request(&mut server, "configurationDone", json!({}));
+        assert_eq!(events(&messages, "stopped")[0]["body"]["reason"], "entry");
+        let thread = events(&messages, "stopped")[0]["body"]["threadId"].clone();
+
+        let messages = request(&mut server, "stackTrace", json!({ "threadId": thread }));
+        let frame = &messages[0]["body"]["stackFrames"][0];
+        assert_eq!(frame["line"], 4);
+        assert_eq!(frame["source"]["path"], json!(program));
+
+        let messages = request(
+            &mut server,
+            "variables",
+            json!({ "variablesReference": thread }),
+        );
+        let variables = messages[0]["body"]["variables"].as_array().unwrap();
+        assert_eq!(variables.len(), 18);
+        assert_eq!(variables[16]["name"], "pc");
+
+        let messages = request(&mut server, "next", json!({ "threadId": thread }));
+        assert_eq!(messages[0]["success"], true);
+        assert_eq!(
+            events(&messages, "stopped").len() + events(&messages, "terminated").len(),
+            1
+        );
+
+        let messages = request(&mut server, "continue", json!({ "threadId": thread }));
+        let finished = events(&messages, "terminated").len() == 1;
+        let stopped = events(&messages, "stopped");
+        assert!(
+            finished
+                || stopped[0]["body"]["hitBreakpointIds"][0]
+                    == changed[0]["body"]["breakpoint"]["id"]
+        );
+
+        let messages = request(&mut server, "disconnect", json!({}));
+        assert_eq!(messages[0]["success"], true);
+        assert!(server.done);
+    }
+
+    #[test]
+    fn test_memory_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("looper.s");
+        fs::write(&program, WARRIOR).unwrap();
+
+        let mut server = DapServer::new();
+        let messages = request(&mut server, "threads", json!({}));
+        assert_eq!(messages[0]["body"]["threads"], json!([]));
+        assert_eq!(
+            request(&mut server, "continue", json!({}))[0]["success"],
+            false
+        );
+        assert_eq!(
+            request(&mut server, "launch", json!({}))[0]["success"],
+            false
+        );
+        assert_eq!(
+            request(&mut server, "gotoTargets", json!({}))[0]["success"],
+            false
+        );
+
+        request(
+            &mut server,
+            "launch",
+            json!({ "program": program, "stopOnEntry": true }),
+        );
+        request(&mut server, "configurationDone", json!({}));
+        let messages = request(&mut server, "threads", json!({}));
+        assert_eq!(
+            messages[0]["body"]["threads"][0]["name"],
+            "Looper process 1"
+        );
+
+        let messages = request(
+            &mut server,
+            "readMemory",
+            json!({ "memoryReference": "0x0000", "count": 3 }),
+        );
+        assert_eq!(messages[0]["body"]["data"], "AQAA");
+        let messages = request(&mut server, "evaluate", json!({ "expression": "r2" }));
+        assert_eq!(messages[0]["body"]["result"], "0");
+        assert_eq!(
+            request(&mut server, "evaluate", json!({ "expression": "r0" }))[0]["success"],
+            false
+        );
+    }
+
+    #[test]
+    fn test_messages_are_framed() {
+        let mut input = Vec::new();
+        write_message(&mut input, &json!({ "seq": 1 })).unwrap();
+        assert_eq!(input, b"Content-Length: 9\r\n\r\n{\"seq\":1}".to_vec());
+        write_message(&mut input, &json!({ "seq": 2, "command": "disconnect" })).unwrap();
+        write_message(&mut input, &json!({ "seq": 3, "command": "threads" })).unwrap();
+
+        // The session ends at the disconnect, leaving the last request unread
+        let mut server = DapServer::new();
+        let mut replies = Vec::new();
+        server.serve(&input[..], &mut replies).unwrap();
+        let mut replies = &replies[..];
+        let first = read_message(&mut replies).unwrap().unwrap();
+        assert_eq!(first["success"], false);
+        let second = read_message(&mut replies).unwrap().unwrap();
+        assert_eq!(second["command"], "disconnect");
+        assert_eq!(second["request_seq"], 2);
+        assert!(read_message(&mut replies).unwrap().is_none());
+        assert!(read_message(&mut &b"Content-Length: x\r\n\r\n"[..]).is_err());
+    }
+}
@@ -0,0 +1,478 @@
+/// Command-line debugger
+///
+/// `corewar debug` loads a battle and reads commands from the terminal, in
+/// the spirit of gdb: run a cycle or a few, stop on breakpoints and
+/// watchpoints, and look at the registers of a process and at memory.
+/// [`Debugger`] holds the battle and the selected process and runs one
+/// command line at a time, so the same commands can be scripted or tested
+/// without a terminal.
+///
+/// Numbers may be decimal or `0x`-prefixed hexadecimal. An empty line at
+/// the prompt repeats the previous command, so pressing enter keeps
+/// stepping.
+use crate::assembler::disassembler;
+use crate::error::{CoreWarError, Result};
+use crate::vm::breakpoint::parse_number;
+use crate::vm::{Breakpoint, GameEngine, Process, TickOutcome};
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+
+/// Text shown before each command
+pub const PROMPT: &str = "(corewar) ";
+
+/// Bytes `mem` shows when no length is given
+const DEFAULT_MEM_LENGTH: usize = 64;
+
+/// Instructions `list` shows before and after the PC
+const LIST_CONTEXT: usize = 4;
+
+/// Summary of the commands, shown by `help`
+const HELP: &str = "\
+step [N]             run N cycles (default 1), stopping at breakpoints
+continue             run until a breakpoint triggers or the battle ends
+break ADDR|SPEC      stop when a process reaches ADDR, or on a breakpoint
+                     spec: cycle=N, fork=CHAMPION, death or watch=START..END
+watch START..END     stop when a process writes in the range, end exclusive
+delete SPEC|all      remove a breakpoint, or all of them
+breakpoints          list the breakpoints
+print REG|pc|carry   show registers of the selected process: r3, r1..r16
+mem ADDR [LEN]       dump LEN bytes of memory from ADDR (default 64)
+list                 disassemble around the selected process's PC
+process [ID]         select a process, or list the live processes
+quit                 leave the debugger";
+
+/// What the debugger does after a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// Show the text and read the next command
+    Continue(String),
+    /// Leave the debugger
+    Quit,
+}
+
+/// Interactive control of a battle
+pub struct Debugger {
+    /// The battle being debugged
+    engine: GameEngine,
+    /// Process the process commands act on, while it lives
+    selected: Option<u32>,
+}
+
+impl Debugger {
+    /// Debug a battle
+    ///
+    /// # Arguments
+    /// * `engine` - A started engine; its breakpoints are kept
+    pub fn new(engine: GameEngine) -> Self {
+        Self {
+            engine,
+            selected: None,
+        }
+    }
+
+    /// Get the battle being debugged
+    pub fn engine(&self) -> &GameEngine {
+        &self.engine
+    }
+
+    /// Read commands and print their results until `quit` or end of input
+    ///
+    /// Errors in a command are printed and the debugger carries on; only
+    /// failing to read or write the terminal ends the session with an error.
+    ///
+    /// # Arguments
+    /// * `input` - Where commands are read from, one per line
+    /// * `output` - Where the prompt and results are written
+    pub fn run<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> Result<()> {
+        writeln!(output, "{}", self.summary())?;
+        writeln!(output, "Type help for the commands.")?;
+        let mut previous = String::new();
+        loop {
+            write!(output, "{}", PROMPT)?;
+            output.flush()?;
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                writeln!(output)?;
+                return Ok(());
+            }
+            let line = match line.trim() {
+                "" => previous.clone(),
+                line => line.to_string(),
+            };
+            if line.is_empty() {
+                continue;
+            }
+            match self.execute(&line) {
+                Ok(Reply::Continue(text)) => {
+                    if !text.is_empty() {
+                        writeln!(output, "{}", text)?;
+                    }
+                }
+                Ok(Reply::Quit) => return Ok(()),
+                Err(e) => writeln!(output, "{}", e)?,
+            }
+            previous = line;
+        }
+    }
+
+    /// Run one command line
+    ///
+    /// # Arguments
+    /// * `line` - A command and its arguments
+    ///
+    /// # Returns
+    /// What to show and whether to go on, or an error if the command is
+    /// unknown or its arguments are invalid
+    pub fn execute(&mut self, line: &str) -> Result<Reply> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        let text = match (command, args.as_slice()) {
+            ("step" | "s", []) => self.step(1)?,
+            ("step" | "s", [cycles]) => self.step(number(cycles)?)?,
+            ("continue" | "c", []) => self.resume()?,
+            ("break" | "b", [spec]) => self.add_breakpoint(breakpoint(spec)?),
+            ("watch" | "w", [range]) => {
+                self.add_breakpoint(format!("watch={}", range).parse::<Breakpoint>()?)
+            }
+            ("delete" | "d", ["all"]) => {
+                self.engine.clear_breakpoints();
+                "Deleted every breakpoint".to_string()
+            }
+            ("delete" | "d", [spec]) => {
+                let breakpoint = breakpoint(spec)?;
+                if !self.engine.remove_breakpoint(&breakpoint) {
+                    return Err(CoreWarError::config(format!(
+                        "No breakpoint {}",
+                        breakpoint
+                    )));
+                }
+                format!("Deleted {}", breakpoint)
+            }
+            ("breakpoints", []) => self.list_breakpoints(),
+            ("print" | "p", [register]) => self.print(register)?,
+            ("mem" | "x", [address]) => self.mem(address, DEFAULT_MEM_LENGTH)?,
+            ("mem" | "x", [address, length]) => self.mem(address, number(length)?)?,
+            ("list" | "l", []) => self.list()?,
+            ("process", []) => self.list_processes(),
+            ("process", [id]) => {
+                let id = u32::try_from(number(id)?)
+                    .ok()
+                    .filter(|&id| self.engine.process(id).is_some())
+                    .ok_or_else(|| CoreWarError::config(format!("No live process {}", id)))?;
+                self.selected = Some(id);
+                describe(self.engine.process(id).expect("checked above"))
+            }
+            ("help" | "h", []) => HELP.to_string(),
+            ("quit" | "q", []) => return Ok(Reply::Quit),
+            _ => {
+                return Err(CoreWarError::config(format!(
+                    "Invalid command '{}'; type help for the commands",
+                    line.trim()
+                )));
+            }
+        };
+        Ok(Reply::Continue(text))
+    }
+
+    /// Run cycles and say where the battle stopped
+    fn step(&mut self, cycles: usize) -> Result<String> {
+        let cycles = u32::try_from(cycles).unwrap_or(u32::MAX);
+        let outcome = self.engine.run_cycles(cycles)?;
+        Ok(self.stopped(outcome))
+    }
+
+    /// Run until a breakpoint or the end of the battle
+    fn resume(&mut self) -> Result<String> {
+        let outcome = self.engine.step_until(|_| false)?;
+        Ok(self.stopped(outcome))
+    }
+
+    /// Describe the state a run stopped in
+    fn stopped(&mut self, outcome: TickOutcome) -> String {
+        let mut text = String::new();
+        match outcome {
+            TickOutcome::Breakpoint(hits) => {
+                for hit in &hits {
+                    let _ = writeln!(text, "Breakpoint: {}", hit);
+                }
+                // Follow the process that stopped the battle
+                if let Some(id) = hits.iter().find_map(|hit| hit.process_id)
+                    && self.engine.process(id).is_some()
+                {
+                    self.selected = Some(id);
+                }
+            }
+            TickOutcome::Finished => {
+                let winner = self
+                    .engine
+                    .state()
+                    .winner
+                    .and_then(|id| self.engine.champions().iter().find(|c| c.id == id));
+                let _ = match winner {
+                    Some(champion) => writeln!(
+                        text,
+                        "Battle over: champion {} ({}) won",
+                        champion.id,
+                        champion.display_name()
+                    ),
+                    None => writeln!(text, "Battle over: no winner"),
+                };
+            }
+            TickOutcome::Running => {}
+        }
+        text.push_str(&self.summary());
+        text
+    }
+
+    /// Describe the cycle and the selected process
+    fn summary(&mut self) -> String {
+        let cycle = self.engine.state().cycle;
+        match self.current() {
+            Some(process) => format!("Cycle {}: {}", cycle, describe(process)),
+            None => format!("Cycle {}: no live processes", cycle),
+        }
+    }
+
+    /// Install a breakpoint and confirm it
+    fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> String {
+        self.engine.add_breakpoint(breakpoint);
+        format!("Breakpoint {} set", breakpoint)
+    }
+
+    /// List the installed breakpoints
+    fn list_breakpoints(&self) -> String {
+        let breakpoints = self.engine.breakpoints();
+        if breakpoints.is_empty() {
+            return "No breakpoints".to_string();
+        }
+        breakpoints
+            .iter()
+            .enumerate()
+            .map(|(i, breakpoint)| format!("{}: {}", i + 1, breakpoint))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Show registers, the PC or the carry of the selected process
+    fn print(&mut self, register: &str) -> Result<String> {
+        let process = self
+            .current()
+            .ok_or_else(|| CoreWarError::config("No live process to print"))?;
+        let register = register.to_lowercase();
+        match register.as_str() {
+            "pc" => return Ok(format!("pc = 0x{:04X}", process.pc)),
+            "carry" => return Ok(format!("carry = {}", process.carry)),
+            _ => {}
+        }
+
+        let invalid = || {
+            CoreWarError::config(format!(
+                "Invalid register '{}' (expected r1 to r16, a range such as r1..r16, pc or carry)",
+                register
+            ))
+        };
+        let index = |name: &str| {
+            name.strip_prefix('r')
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|n| (1..=process.registers.len()).contains(n))
+                .ok_or_else(invalid)
+        };
+        let (first, last) = match register.split_once("..") {
+            Some((first, last)) => (index(first)?, index(last)?),
+            None => (index(&register)?, index(&register)?),
+        };
+        if first > last {
+            return Err(invalid());
+        }
+        Ok((first..=last)
+            .map(|n| {
+                let value = process.registers[n - 1];
+                format!("r{:<2} = {:<11} (0x{:08X})", n, value, value as u32)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Dump a block of memory
+    fn mem(&self, address: &str, length: usize) -> Result<String> {
+        let memory = self.engine.memory();
+        if length == 0 || length > memory.size() {
+            return Err(CoreWarError::config(format!(
+                "Length must be between 1 and the core size, {}",
+                memory.size()
+            )));
+        }
+        let start = number(address)? % memory.size();
+        Ok(memory.dump_hex(start, length).trim_end().to_string())
+    }
+
+    /// Disassemble the code around the selected process's PC
+    fn list(&mut self) -> Result<String> {
+        let pc = self
+            .current()
+            .ok_or_else(|| CoreWarError::config("No live process to list"))?
+            .pc;
+        let memory = self.engine.memory();
+        let pc = pc % memory.size();
+        Ok(
+            disassembler::disassemble_memory(memory, pc, LIST_CONTEXT, LIST_CONTEXT)
+                .into_iter()
+                .map(|line| {
+                    let marker = if line.address == pc { '>' } else { ' ' };
+                    format!("{} {:04X}  {}", marker, line.address, line.text)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
+    /// List the live processes, marking the selected one
+    fn list_processes(&mut self) -> String {
+        let selected = self.current().map(|process| process.id);
+        let processes = self.engine.processes();
+        if processes.is_empty() {
+            return "No live processes".to_string();
+        }
+        processes
+            .iter()
+            .map(|process| {
+                let marker = if Some(process.id) == selected {
+                    '*'
+                } else {
+                    ' '
+                };
+                format!("{} {}", marker, describe(process))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Get the selected process, or select the first live one if it died
+    fn current(&mut self) -> Option<&Process> {
+        if self
+            .selected
+            .is_none_or(|id| self.engine.process(id).is_none())
+        {
+            self.selected = self.engine.processes().first().map(|process| process.id);
+        }
+        self.engine.process(self.selected?)
+    }
+}
+
+/// Describe a process in one line
+fn describe(process: &Process) -> String {
+    format!(
+        "process {} (champion {}) at pc=0x{:04X}, carry={}",
+        process.id, process.champion_id, process.pc, process.carry
+    )
+}
+
+/// Parse a count or address
+fn number(value: &str) -> Result<usize> {
+    parse_number(value)
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or_else(|| CoreWarError::config(format!("Invalid number '{}'", value)))
+}
+
+/// Parse a bare address as a PC breakpoint, or any breakpoint spec
+fn breakpoint(spec: &str) -> Result<Breakpoint> {
+    match parse_number(spec) {
+        Some(address) => Ok(Breakpoint::Address(address as usize)),
+        None => spec.parse(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples;
+
+    fn debugger() -> Debugger {
+        let engine = GameEngine::builder()
+            .champion_cor(examples::find("imp").unwrap().assemble().unwrap())
+            .champion_cor(examples::find("dwarf").unwrap().assemble().unwrap())
+            .build()
+            .unwrap();
+        Debugger::new(engine)
+    }
+
+    fn run(debugger: &mut Debugger, line: &str) -> String {
+        match debugger.execute(line).unwrap() {
+            Reply::Continue(text) => text,
+            Reply::Quit => panic!("{} quit the debugger", line),
+        }
+    }
+
+    #[test]
+    fn test_stepping_and_breakpoints() {
+        let mut debugger = debugger();
+        assert!(run(&mut debugger, "step").starts_with("Cycle 1:"));
+        assert!(run(&mut debugger, "step 0x2").starts_with("Cycle 3:"));
+
+        assert_eq!(
+            run(&mut debugger, "break cycle=10"),
+            "Breakpoint cycle=10 set"
+        );
+        assert!(run(&mut debugger, "watch 0..4").contains("watch=0x0000..0x0004"));
+        assert!(run(&mut debugger, "breakpoints").starts_with("1: cycle=10\n2: watch="));
+        let stopped = run(&mut debugger, "continue");
+        assert!(
+            stopped.contains("Breakpoint: cycle=10 at cycle 10"),
+            "{}",
+            stopped
+        );
+
+        assert_eq!(run(&mut debugger, "delete all"), "Deleted every breakpoint");
+        assert!(debugger.execute("delete cycle=10").is_err());
+        let over = run(&mut debugger, "continue");
+        let winner = debugger.engine().state().winner;
+        assert!(over.contains("Battle over"));
+        assert_eq!(winner.is_some(), over.contains(") won"), "{}", over);
+        assert!(!debugger.engine().state().running);
+    }
+
+    #[test]
+    fn test_inspecting_processes_and_memory() {
+        let mut debugger = debugger();
+        let registers = run(&mut debugger, "print r1..r16");
+        assert_eq!(registers.lines().count(), 16);
+        assert!(registers.starts_with("r1  = "), "{}", registers);
+        assert!(registers.ends_with(')') && registers.contains("\nr16 = "));
+        assert_eq!(run(&mut debugger, "print r2").lines().count(), 1);
+        assert!(run(&mut debugger, "print pc").starts_with("pc = 0x"));
+        assert!(debugger.execute("print r17").is_err());
+        assert!(debugger.execute("print r3..r2").is_err());
+
+        assert_eq!(run(&mut debugger, "mem 0 32").lines().count(), 2);
+        assert!(run(&mut debugger, "mem 0x10").starts_with("0010:"));
+        assert!(run(&mut debugger, "list").contains("> "));
+
+        let processes = run(&mut debugger, "process");
+        assert_eq!(processes.lines().count(), 2);
+        let second = debugger.engine().processes()[1].id;
+        let selected = run(&mut debugger, &format!("process {}", second));
+        assert!(selected.starts_with(&format!("process {} ", second)));
+        assert!(run(&mut debugger, "process").contains(&format!("* {}", selected)));
+        assert!(debugger.execute("process 999").is_err());
+        assert!(debugger.execute("jump 3").is_err());
+        assert_eq!(debugger.execute("quit").unwrap(), Reply::Quit);
+    }
+
+    #[test]
+    fn test_session_repeats_empty_lines() {
+        let mut debugger = debugger();
+        let mut output = Vec::new();
+        debugger
+            .run(
+                "step\n\nbogus\nprint r1\nquit\nstep\n".as_bytes(),
+                &mut output,
+            )
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Cycle 2:"));
+        assert!(output.contains("Invalid command 'bogus'"));
+        assert!(output.contains("r1  ="));
+        assert_eq!(debugger.engine().state().cycle, 2);
+    }
+}
@@ -0,0 +1,222 @@
+/// Decoding instructions straight from memory
+///
+/// Processes run the same code over and over, so [`crate::vm::Memory`]
+/// keeps a [`DecodeCache`] of the instruction decoded at each address.
+/// A byte written to memory drops every cached instruction it could be a
+/// part of: the ones starting at the byte and up to
+/// [`MAX_INSTRUCTION_SIZE`] - 1 bytes before it whose encoding reaches it.
+/// The cache is derived from the memory contents, so a cloned memory starts
+/// with an empty one instead of copying it.
+use crate::vm::instruction::{Instruction, ParameterType};
+use std::fmt;
+
+/// Longest encoding of an instruction: opcode, type byte and three 4-byte parameters
+pub const MAX_INSTRUCTION_SIZE: usize = 14;
+
+/// Most parameters an instruction takes
+const MAX_PARAMETERS: usize = 3;
+
+/// An instruction as read from memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    /// The opcode byte
+    pub opcode: u8,
+    /// The instruction the opcode names, if any
+    pub instruction: Option<Instruction>,
+    /// The parameter-type byte, implied for instructions without one
+    pub types: u8,
+    /// Type and raw value of each parameter, up to the first one whose type
+    /// code is empty: a register number, an offset or a direct value
+    parameters: [Option<(ParameterType, i32)>; MAX_PARAMETERS],
+    /// Encoded size in bytes, 1 for an unknown opcode
+    pub size: usize,
+}
+
+impl DecodedInstruction {
+    /// Decode the instruction at an address without the cache
+    ///
+    /// # Arguments
+    /// * `bytes` - The memory contents
+    /// * `address` - Address of the opcode; the encoding wraps around the end
+    ///
+    /// # Returns
+    /// The decoded instruction
+    pub fn read(bytes: &[u8], address: usize) -> Self {
+        let byte = |offset: usize| bytes[(address + offset) % bytes.len()];
+        let opcode = byte(0);
+        let Ok(instruction) = Instruction::from_opcode(opcode) else {
+            return Self {
+                opcode,
+                instruction: None,
+                types: 0,
+                parameters: [None; MAX_PARAMETERS],
+                size: 1,
+            };
+        };
+
+        let types = if instruction.has_parameter_types() {
+            byte(1)
+        } else {
+            // The single direct parameter of live, zjmp, fork and lfork
+            ParameterType::Direct.type_code() << 6
+        };
+        let mut parameters = [None; MAX_PARAMETERS];
+        let mut size = instruction.header_size();
+        for (i, parameter) in parameters
+            .iter_mut()
+            .enumerate()
+            .take(instruction.parameter_count())
+        {
+            let type_code = (types >> (6 - i * 2)) & 0x3;
+            if type_code == 0 {
+                break;
+            }
+            let parameter_type = ParameterType::from_type_code(type_code);
+            let value = match instruction.parameter_size(parameter_type) {
+                1 => byte(size) as i32,
+                2 => i16::from_be_bytes([byte(size), byte(size + 1)]) as i32,
+                _ => {
+                    i32::from_be_bytes([byte(size), byte(size + 1), byte(size + 2), byte(size + 3)])
+                }
+            };
+            *parameter = Some((parameter_type, value));
+            size += instruction.parameter_size(parameter_type);
+        }
+
+        Self {
+            opcode,
+            instruction: Some(instruction),
+            types,
+            parameters,
+            size,
+        }
+    }
+
+    /// Get the decoded parameters, in order
+    ///
+    /// Decoding stops at the first empty type code, so there may be fewer
+    /// than the instruction takes.
+    pub fn parameters(&self) -> impl Iterator<Item = (ParameterType, i32)> + '_ {
+        self.parameters.iter().map_while(|parameter| *parameter)
+    }
+}
+
+/// Instructions decoded at each memory address since the bytes under them
+/// last changed
+#[derive(Default)]
+pub struct DecodeCache {
+    /// Decoded instruction per address; empty until the first decode
+    entries: Vec<Option<DecodedInstruction>>,
+}
+
+impl DecodeCache {
+    /// Get the instruction at an address, decoding it on a miss
+    ///
+    /// # Arguments
+    /// * `bytes` - The memory contents the cache belongs to
+    /// * `address` - The normalized address of the opcode
+    ///
+    /// # Returns
+    /// The decoded instruction
+    pub fn get(&mut self, bytes: &[u8], address: usize) -> DecodedInstruction {
+        if self.entries.len() != bytes.len() {
+            self.entries = vec![None; bytes.len()];
+        }
+        *self.entries[address].get_or_insert_with(|| DecodedInstruction::read(bytes, address))
+    }
+
+    /// Drop the cached instructions whose encoding covers an address
+    ///
+    /// # Arguments
+    /// * `address` - The normalized address of a byte that changed
+    pub fn invalidate(&mut self, address: usize) {
+        let len = self.entries.len();
+        if len == 0 {
+            return;
+        }
+        for back in 0..MAX_INSTRUCTION_SIZE.min(len) {
+            let entry = &mut self.entries[(address + len - back) % len];
+            if entry.is_some_and(|decoded| decoded.size > back) {
+                *entry = None;
+            }
+        }
+    }
+
+    /// Drop every cached instruction
+    pub fn clear(&mut self) {
+        self.entries = Vec::new();
+    }
+}
+
+impl Clone for DecodeCache {
+    /// Start the copy empty; it fills again as the copy is executed
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for DecodeCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DecodeCache")
+            .field("cached", &self.entries.iter().flatten().count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::Memory;
+
+    #[test]
+    fn test_read_decodes_parameters_and_wraps() {
+        // sti r1, %5, r2 across the end of memory
+        let mut bytes = vec![0; 8];
+        bytes[6..].copy_from_slice(&[0x0B, 0x64]);
+        bytes[..5].copy_from_slice(&[0x01, 0x00, 0x05, 0x02, 0x00]);
+        let decoded = DecodedInstruction::read(&bytes, 6);
+        assert_eq!(decoded.instruction, Some(Instruction::Sti));
+        assert_eq!(decoded.size, 2 + 1 + 2 + 1);
+        assert_eq!(
+            decoded.parameters().collect::<Vec<_>>(),
+            [
+                (ParameterType::Register, 1),
+                (ParameterType::Direct, 5),
+                (ParameterType::Register, 2)
+            ]
+        );
+
+        // live has no types byte; an unknown opcode is a single byte
+        let live = DecodedInstruction::read(&[0x01, 0x00, 0x00, 0x00, 0x2A], 0);
+        assert_eq!(
+            live.parameters().collect::<Vec<_>>(),
+            [(ParameterType::Direct, 42)]
+        );
+        assert_eq!(live.size, 5);
+        assert_eq!(DecodedInstruction::read(&[0xEE], 0).size, 1);
+    }
+
+    #[test]
+    fn test_writes_invalidate_the_instructions_they_touch() {
+        let mut memory = Memory::new();
+        memory
+            .load_code(100, &[0x01, 0x00, 0x00, 0x00, 0x01], 1)
+            .unwrap();
+        memory.load_code(200, &[0x0C, 0x00, 0x10], 1).unwrap();
+        assert_eq!(memory.decode(100).parameters().next().unwrap().1, 1);
+        assert_eq!(memory.decode(200).instruction, Some(Instruction::Fork));
+
+        // The last byte of live's parameter, and a byte right after fork
+        memory.write_byte(104, 0x07, None);
+        memory.write_byte(203, 0xFF, None);
+        assert_eq!(memory.decode(100).parameters().next().unwrap().1, 7);
+        assert_eq!(
+            memory.decode(200),
+            DecodedInstruction::read(memory.data(), 200)
+        );
+
+        memory.write_word(97, 0x0000_000C, None);
+        assert_eq!(memory.decode(100).instruction, Some(Instruction::Fork));
+        assert_eq!(memory.clone().decode(100), memory.decode(100));
+    }
+}
@@ -1,26 +1,80 @@
-use crate::constants::{MAX_CHAMPIONS, MEMORY_SIZE};
 /// Core War game engine
 ///
 /// This module implements the main game engine that coordinates all components
 /// of the Core War virtual machine to run complete battles.
 use crate::error::{CoreWarError, Result};
-use crate::vm::{Champion, ChampionLoader, Memory, Scheduler};
-use log::{debug, info};
-use std::time::{Duration, Instant};
+use crate::vm::bench::InstructionTimings;
+use crate::vm::breakpoint::{Breakpoint, BreakpointHit, Breakpoints};
+use crate::vm::builder::GameEngineBuilder;
+use crate::vm::bundle::BattleReport;
+use crate::vm::clock::Instant;
+use crate::vm::coverage::{ChampionCoverage, CoverageTracker};
+use crate::vm::events::{EventBus, GameEvent, GameObserver};
+use crate::vm::exectrace::ExecutionTrace;
+use crate::vm::history::{
+    CheckpointHistory, DEFAULT_CHECKPOINT_CAPACITY, DEFAULT_CHECKPOINT_INTERVAL,
+};
+use crate::vm::mutator::TargetedMutator;
+use crate::vm::ndjson::JsonLinesWriter;
+use crate::vm::pacing::Throttle;
+#[cfg(feature = "event-protocol")]
+use crate::vm::protocol::EventEncoder;
+#[cfg(feature = "pspace")]
+use crate::vm::pspace;
+#[cfg(feature = "event-protocol")]
+use crate::vm::recording::{Recorder, Recording, Replay, ReplayCursor};
+use crate::vm::resources::{ResourceSnapshot, ResourceUsage};
+use crate::vm::rng::{DEFAULT_SEED, SeededRng, StateHasher};
+use crate::vm::snapshot::{GameSnapshot, SNAPSHOT_VERSION};
+use crate::vm::tiebreak::{self, TieBreak, TieBreakScores, TieBreakers};
+use crate::vm::{
+    Champion, ChampionLoader, ChampionOptions, DumpFormat, MemDelta, Memory, Placement, Rules,
+    Scheduler, SkippedChampion,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+use tracing::{Span, debug, info, info_span, trace, warn};
 
 /// Game engine configuration
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
 pub struct GameConfig {
     /// Maximum number of cycles to run (0 = unlimited)
     pub max_cycles: u32,
     /// Dump memory every N cycles (0 = no dumping)
     pub dump_cycles: u32,
-    /// Execution speed multiplier
+    /// Layout of memory dumps
+    pub dump_format: DumpFormat,
+    /// Cycles per frame in the terminal UI (see [`crate::vm::pacing`])
     pub speed: u32,
     /// Whether to enable verbose logging
     pub verbose: bool,
     /// Whether to pause at start
     pub start_paused: bool,
+    /// Arena rules (memory size, death check parameters, champion limit)
+    pub rules: Rules,
+    /// Seed for every random choice made during the battle
+    pub seed: u64,
+    /// Whether to record which bytes of champion code are executed
+    pub track_coverage: bool,
+    /// Tie-breakers applied, in order, when no champion wins outright
+    pub tie_breakers: TieBreakers,
+    /// Cycles between rewind checkpoints (0 = no checkpoints)
+    pub checkpoint_interval: u32,
+    /// Number of rewind checkpoints kept (0 = no checkpoints)
+    pub checkpoint_capacity: usize,
+    /// Whether to battle with the remaining champions when some fail to load
+    pub skip_invalid: bool,
+    /// How champions without custom addresses are placed in memory
+    pub placement: Placement,
 }
 
 impl Default for GameConfig {
@@ -28,15 +82,111 @@ impl Default for GameConfig {
         Self {
             max_cycles: 0,
             dump_cycles: 0,
+            dump_format: DumpFormat::default(),
             speed: 1,
             verbose: false,
             start_paused: false,
+            rules: Rules::default(),
+            seed: DEFAULT_SEED,
+            track_coverage: false,
+            tie_breakers: TieBreakers::default(),
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            checkpoint_capacity: DEFAULT_CHECKPOINT_CAPACITY,
+            skip_invalid: false,
+            placement: Placement::default(),
         }
     }
 }
 
+impl GameConfig {
+    /// Set the maximum number of cycles to run (0 = unlimited)
+    pub fn with_max_cycles(mut self, max_cycles: u32) -> Self {
+        self.max_cycles = max_cycles;
+        self
+    }
+
+    /// Set how often memory is dumped, in cycles (0 = no dumping)
+    pub fn with_dump_cycles(mut self, dump_cycles: u32) -> Self {
+        self.dump_cycles = dump_cycles;
+        self
+    }
+
+    /// Set the layout of memory dumps
+    pub fn with_dump_format(mut self, dump_format: DumpFormat) -> Self {
+        self.dump_format = dump_format;
+        self
+    }
+
+    /// Set the cycles per frame in the terminal UI
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Set whether to enable verbose logging
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Set whether to pause at start
+    pub fn with_start_paused(mut self, start_paused: bool) -> Self {
+        self.start_paused = start_paused;
+        self
+    }
+
+    /// Set the arena rules
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set the seed for every random choice made during the battle
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set whether to record which bytes of champion code are executed
+    pub fn with_track_coverage(mut self, track_coverage: bool) -> Self {
+        self.track_coverage = track_coverage;
+        self
+    }
+
+    /// Set the tie-breakers applied when no champion wins outright
+    pub fn with_tie_breakers(mut self, tie_breakers: TieBreakers) -> Self {
+        self.tie_breakers = tie_breakers;
+        self
+    }
+
+    /// Set the cycles between rewind checkpoints (0 = no checkpoints)
+    pub fn with_checkpoint_interval(mut self, checkpoint_interval: u32) -> Self {
+        self.checkpoint_interval = checkpoint_interval;
+        self
+    }
+
+    /// Set the number of rewind checkpoints kept (0 = no checkpoints)
+    pub fn with_checkpoint_capacity(mut self, checkpoint_capacity: usize) -> Self {
+        self.checkpoint_capacity = checkpoint_capacity;
+        self
+    }
+
+    /// Set whether to battle with the remaining champions when some fail to load
+    pub fn with_skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.skip_invalid = skip_invalid;
+        self
+    }
+
+    /// Set how champions without custom addresses are placed in memory
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+}
+
 /// Game state information
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct GameState {
     /// Current cycle number
     pub cycle: u32,
@@ -46,6 +196,8 @@ pub struct GameState {
     pub paused: bool,
     /// Winner champion ID (None if game ongoing)
     pub winner: Option<u8>,
+    /// How a drawn battle was decided, if a tie-breaker picked the winner
+    pub tie_break: Option<TieBreak>,
     /// Game start time
     pub start_time: Instant,
     /// Last cycle execution time
@@ -68,6 +220,77 @@ pub struct GameEngine {
     config: GameConfig,
     /// Current game state
     state: GameState,
+    /// Resource counters sampled when the battle started
+    resource_baseline: ResourceSnapshot,
+    /// Source of all random choices, seeded from the configuration
+    rng: SeededRng,
+    /// Observers notified of game events
+    events: EventBus,
+    /// Periodic checkpoints used to rewind
+    history: CheckpointHistory,
+    /// Conditions that interrupt the battle
+    breakpoints: Breakpoints,
+    /// Champion files left out because they failed to load
+    skipped_champions: Vec<SkippedChampion>,
+    /// State to return to when the current what-if branch is discarded
+    branch: Option<Box<BranchOrigin>>,
+    /// Transformations applied to champion code at load time
+    mutators: Vec<TargetedMutator>,
+    /// Binary encoding of every published event, for external visualizers
+    #[cfg(feature = "event-protocol")]
+    event_stream: Option<EventEncoder<Box<dyn Write + Send>>>,
+    /// Whether the event stream hides champion code from spectators
+    #[cfg(feature = "event-protocol")]
+    redact_stream: bool,
+    /// JSON lines of every published event, for tools that read JSON
+    json_stream: Option<JsonLinesWriter<Box<dyn Write + Send>>>,
+    /// File memory dumps are written to instead of stdout
+    dump_file: Option<PathBuf>,
+    /// Champion files and options of the last load, for restarting
+    loaded_from: Option<(Vec<PathBuf>, Vec<ChampionOptions>)>,
+    /// Memory captured to report later changes against
+    memory_baseline: Option<Memory>,
+    /// Recording of the battle, for replaying it later
+    #[cfg(feature = "event-protocol")]
+    recorder: Option<Recorder<Box<dyn Write + Send>>>,
+    /// Recording being replayed instead of executing champions
+    #[cfg(feature = "event-protocol")]
+    replay: Option<Box<Replay>>,
+    /// Span every cycle of the battle is traced in
+    span: Span,
+    /// Pace `tick` holds the battle to, if any
+    throttle: Option<Throttle>,
+}
+
+/// Engine state saved when a what-if branch starts
+#[derive(Debug, Clone)]
+struct BranchOrigin {
+    /// Simulation state at the branch point
+    snapshot: GameSnapshot,
+    /// Checkpoints taken before the branch
+    history: CheckpointHistory,
+    /// Coverage recorded before the branch
+    coverage: Option<CoverageTracker>,
+    /// Whether the battle was still running
+    running: bool,
+}
+
+/// Result of a single tick
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TickOutcome {
+    /// The battle continues
+    Running,
+    /// The battle is over or has not been started
+    Finished,
+    /// The cycle triggered breakpoints; the battle continues
+    Breakpoint(Vec<BreakpointHit>),
+}
+
+impl TickOutcome {
+    /// Check whether the battle is still running
+    pub fn is_running(&self) -> bool {
+        !matches!(self, Self::Finished)
+    }
 }
 
 impl GameEngine {
@@ -82,8 +305,8 @@ impl GameEngine {
         let now = Instant::now();
 
         Self {
-            memory: Memory::new(),
-            scheduler: Scheduler::new(),
+            memory: Memory::with_rules(&config.rules),
+            scheduler: Scheduler::with_rules(config.rules),
             champions: Vec::new(),
             config,
             state: GameState {
@@ -91,17 +314,106 @@ impl GameEngine {
                 running: false,
                 paused: config.start_paused,
                 winner: None,
+                tie_break: None,
                 start_time: now,
                 last_cycle_time: now,
             },
+            resource_baseline: ResourceSnapshot::capture(),
+            rng: SeededRng::new(config.seed),
+            events: EventBus::new(),
+            history: CheckpointHistory::new(config.checkpoint_interval, config.checkpoint_capacity),
+            breakpoints: Breakpoints::default(),
+            skipped_champions: Vec::new(),
+            branch: None,
+            mutators: Vec::new(),
+            #[cfg(feature = "event-protocol")]
+            event_stream: None,
+            #[cfg(feature = "event-protocol")]
+            redact_stream: false,
+            json_stream: None,
+            dump_file: None,
+            loaded_from: None,
+            memory_baseline: None,
+            #[cfg(feature = "event-protocol")]
+            recorder: None,
+            #[cfg(feature = "event-protocol")]
+            replay: None,
+            span: Span::none(),
+            throttle: None,
+        }
+    }
+
+    /// Create an engine that replays a recorded battle
+    ///
+    /// The engine starts from the recording's first keyframe with the
+    /// recorded configuration. Ticking applies the recorded events of each
+    /// cycle instead of executing the champions, and the battle ends where
+    /// the recording ends. Rewinding seeks back along the recording;
+    /// branching and stepping single processes are not available while
+    /// replaying.
+    ///
+    /// # Arguments
+    /// * `recording` - A recording read with [`Recording::read`]
+    ///
+    /// # Returns
+    /// The engine, ready for [`GameEngine::start`], or an error if the first
+    /// keyframe is inconsistent
+    #[cfg(feature = "event-protocol")]
+    pub fn replay(recording: Recording) -> Result<Self> {
+        let mut engine = Self::new(recording.config);
+        let (keyframe, cursor) = ReplayCursor::start(recording);
+        engine.apply_snapshot(keyframe)?;
+        engine.replay = Some(Box::new(Replay::new(cursor, engine.snapshot())));
+        Ok(engine)
+    }
+
+    /// Check whether the engine is replaying a recording
+    #[cfg(feature = "event-protocol")]
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Get how far a replay has got
+    ///
+    /// # Returns
+    /// The number of cycles replayed and the number the whole recording
+    /// replays, or None if the engine is not replaying
+    pub fn replay_progress(&self) -> Option<(u32, u32)> {
+        #[cfg(feature = "event-protocol")]
+        if let Some(replay) = &self.replay {
+            return Some((replay.cursor.step(), replay.length));
+        }
+        None
+    }
+
+    /// Replay up to and including the next cycle in which a process dies
+    ///
+    /// Events are published as for ticks, and a breakpoint stops the replay
+    /// early.
+    ///
+    /// # Returns
+    /// The outcome of the last cycle replayed, or an error if the engine is
+    /// not replaying or no process dies in the rest of the recording
+    pub fn seek_next_death(&mut self) -> Result<TickOutcome> {
+        #[cfg(feature = "event-protocol")]
+        if let Some(replay) = &self.replay {
+            return match replay.cursor.steps_to_next_death(self.state.cycle) {
+                Some(steps) => self.run_cycles(steps),
+                None => Err(CoreWarError::game_state(
+                    "No process dies in the rest of the recording".to_string(),
+                )),
+            };
         }
+        Err(CoreWarError::game_state(
+            "Seeking is only possible while replaying a recording".to_string(),
+        ))
     }
 
     /// Load champions into the game
     ///
     /// # Arguments
     /// * `champion_files` - Paths to .cor files
-    /// * `custom_addresses` - Optional custom load addresses
+    /// * `custom_addresses` - Optional custom load addresses, overriding the configured placement
     ///
     /// # Returns
     /// `Ok(())` if successful, error otherwise
@@ -109,6 +421,60 @@ impl GameEngine {
         &mut self,
         champion_files: &[P],
         custom_addresses: Option<&[usize]>,
+    ) -> Result<()> {
+        self.load_aliased_champions(champion_files, &[], custom_addresses)
+    }
+
+    /// Load champions into the game under display names of the user's choosing
+    ///
+    /// # Arguments
+    /// * `champion_files` - Paths to .cor files
+    /// * `aliases` - Display name for the file at the same position, if any
+    /// * `custom_addresses` - Optional custom load addresses, overriding the configured placement
+    ///
+    /// # Returns
+    /// `Ok(())` if successful, error otherwise
+    pub fn load_aliased_champions<P: AsRef<std::path::Path>>(
+        &mut self,
+        champion_files: &[P],
+        aliases: &[Option<String>],
+        custom_addresses: Option<&[usize]>,
+    ) -> Result<()> {
+        if let Some(addresses) = custom_addresses
+            && addresses.len() != champion_files.len()
+        {
+            return Err(CoreWarError::champion(
+                "Number of custom addresses must match number of champion files",
+            ));
+        }
+
+        let options: Vec<ChampionOptions> = (0..champion_files.len())
+            .map(|i| ChampionOptions {
+                alias: aliases.get(i).cloned().flatten(),
+                number: None,
+                load_address: custom_addresses.map(|addresses| addresses[i]),
+            })
+            .collect();
+        self.load_champions_with_options(champion_files, &options)
+    }
+
+    /// Load champions into the game with per-file names, numbers and addresses
+    ///
+    /// Champions given a player number use it as their ID; the rest take the
+    /// lowest free numbers in order. When any champion has a load address,
+    /// the others get their evenly spaced address instead of the configured
+    /// placement.
+    ///
+    /// # Arguments
+    /// * `champion_files` - Paths to .cor files
+    /// * `options` - Overrides for the file at the same position
+    ///
+    /// # Returns
+    /// `Ok(())` if successful, error otherwise
+    pub fn load_champions_with_options<P: AsRef<std::path::Path>>(
+        &mut self,
+        champion_files: &[P],
+        options: &[ChampionOptions],
     ) -> Result<()> {
         if champion_files.is_empty() {
             return Err(CoreWarError::game_state(
@@ -116,17 +482,142 @@ impl GameEngine {
             ));
         }
 
-        if champion_files.len() > MAX_CHAMPIONS {
+        let rules = self.config.rules;
+        rules.validate()?;
+
+        if champion_files.len() > rules.max_champions {
             return Err(CoreWarError::game_state(format!(
                 "Too many champions: {} (max {})",
                 champion_files.len(),
-                MAX_CHAMPIONS
+                rules.max_champions
             )));
         }
 
+        let custom_addresses = options
+            .iter()
+            .any(|option| option.load_address.is_some())
+            .then(|| {
+                let spread =
+                    Memory::calculate_placement_addresses(champion_files.len(), rules.memory_size);
+                spread
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, address)| {
+                        options
+                            .get(i)
+                            .and_then(|option| option.load_address)
+                            .unwrap_or(address)
+                    })
+                    .collect::<Vec<_>>()
+            });
+        let custom_addresses = custom_addresses.as_deref();
+
         // Load champions
-        let loader = ChampionLoader::new(true);
-        self.champions = loader.load_champions(champion_files, custom_addresses)?;
+        let loader = ChampionLoader::new(true).with_rules(rules);
+        let mut champions = if self.config.skip_invalid {
+            let (champions, skipped) =
+                loader.load_valid_champions(champion_files, custom_addresses)?;
+            for skip in &skipped {
+                info!("Skipping champion {}: {}", skip.path.display(), skip.reason);
+            }
+            self.skipped_champions = skipped;
+            champions
+        } else {
+            loader.load_champions(champion_files, custom_addresses)?
+        };
+        if custom_addresses.is_none() && self.config.placement == Placement::Random {
+            loader.scatter_champions(&mut champions, self.config.seed)?;
+        }
+
+        // Skipped files keep their position in the list, so match options by file
+        let mut numbers = Vec::new();
+        let mut loaded = champions.iter_mut();
+        let mut skipped = self.skipped_champions.iter().peekable();
+        for (i, path) in champion_files.iter().enumerate() {
+            if skipped.next_if(|s| s.path == path.as_ref()).is_some() {
+                continue;
+            }
+            if let Some(champion) = loaded.next() {
+                let option = options.get(i).cloned().unwrap_or_default();
+                champion.alias = option.alias;
+                numbers.push(option.number);
+            }
+        }
+        loader.assign_champion_numbers(&mut champions, &numbers)?;
+
+        self.loaded_from = Some((
+            champion_files.iter().map(|f| f.as_ref().to_path_buf()).collect(),
+            options.to_vec(),
+        ));
+        self.install_champions(champions)
+    }
+
+    /// Restart the battle from cycle 0, reloading the champion files from disk
+    ///
+    /// Champions are loaded again with the files and options of the last
+    /// load, so a champion that was reassembled in the meantime comes back
+    /// with its new code. Configuration, observers, breakpoints, mutators
+    /// and the event stream are kept; observers see a
+    /// [`GameEvent::Rewound`] to cycle 0 followed by the new spawns. The
+    /// battle is left stopped, ready for [`GameEngine::start`].
+    ///
+    /// # Returns
+    /// `Ok(())` if successful, error if the champions were not loaded from
+    /// files or no longer load
+    pub fn restart(&mut self) -> Result<()> {
+        let Some((files, options)) = self.loaded_from.clone() else {
+            return Err(CoreWarError::game_state(
+                "Champions were not loaded from files, so they cannot be reloaded".to_string(),
+            ));
+        };
+
+        let now = Instant::now();
+        self.memory = Memory::with_rules(&self.config.rules);
+        self.scheduler = Scheduler::with_rules(self.config.rules);
+        self.champions.clear();
+        self.state = GameState {
+            cycle: 0,
+            running: false,
+            paused: self.state.paused,
+            winner: None,
+            tie_break: None,
+            start_time: now,
+            last_cycle_time: now,
+        };
+        self.rng = SeededRng::new(self.config.seed);
+        self.history = CheckpointHistory::new(
+            self.config.checkpoint_interval,
+            self.config.checkpoint_capacity,
+        );
+        self.skipped_champions.clear();
+        self.branch = None;
+        self.publish(&GameEvent::Rewound { cycle: 0 });
+
+        info!("Restarting battle with {} champion files", files.len());
+        self.load_champions_with_options(&files, &options)
+    }
+
+    /// Start building an engine that is ready to run
+    ///
+    /// # Returns
+    /// A builder with the default configuration and no champions
+    pub fn builder() -> GameEngineBuilder {
+        GameEngineBuilder::new()
+    }
+
+    /// Place loaded champions in memory and create their initial processes
+    ///
+    /// # Arguments
+    /// * `champions` - Champions with IDs and load addresses already assigned
+    ///
+    /// # Returns
+    /// `Ok(())` if successful, error if a champion's code does not fit
+    pub(crate) fn install_champions(&mut self, champions: Vec<Champion>) -> Result<()> {
+        self.champions = self.mutate_champions(champions)?;
+        #[cfg(feature = "pspace")]
+        for champion in &mut self.champions {
+            pspace::prepare(&mut champion.pspace, self.config.rules.pspace_size);
+        }
 
         // Load champion code into memory and create initial processes
         for champion in &self.champions {
@@ -142,17 +633,71 @@ impl GameEngine {
                 info!(
                     "Loaded champion {}: {} at address 0x{:04X} ({} bytes)",
                     champion.id,
-                    champion.name,
+                    champion.display_name(),
                     champion.load_address,
                     champion.code.len()
                 );
             }
         }
 
+        if self.config.track_coverage {
+            self.scheduler
+                .enable_coverage(CoverageTracker::new(&self.champions, self.memory.size()));
+        }
+
+        self.dispatch_events();
+        self.history.record(self.snapshot());
+
         info!("Loaded {} champions", self.champions.len());
         Ok(())
     }
 
+    /// Add a transformation applied to champion code at load time
+    ///
+    /// Mutators run in the order they were added, when champions are
+    /// loaded, so they must be added before loading.
+    ///
+    /// # Arguments
+    /// * `mutator` - The mutator and the champion it is limited to, if any
+    pub fn add_mutator(&mut self, mutator: TargetedMutator) {
+        self.mutators.push(mutator);
+    }
+
+    /// Apply the mutators to freshly loaded champions
+    fn mutate_champions(&self, mut champions: Vec<Champion>) -> Result<Vec<Champion>> {
+        if self.mutators.is_empty() {
+            return Ok(champions);
+        }
+
+        // A generator of its own keeps mutation from shifting the battle's random choices
+        let mut rng = SeededRng::new(self.config.seed);
+        for champion in &mut champions {
+            for mutator in self.mutators.iter().filter(|m| m.applies_to(champion.id)) {
+                mutator.mutator.mutate(&mut champion.code, &mut rng);
+                info!(
+                    "Mutated champion {} ({}) with {}",
+                    champion.id,
+                    champion.display_name(),
+                    mutator
+                );
+            }
+
+            if champion.code.is_empty() || champion.code.len() > self.memory.size() {
+                return Err(CoreWarError::champion(format!(
+                    "Mutated champion {} has {} bytes of code (must be 1-{})",
+                    champion.display_name(),
+                    champion.code.len(),
+                    self.memory.size()
+                )));
+            }
+        }
+
+        ChampionLoader::new(false)
+            .with_rules(self.config.rules)
+            .validate_champion_placement(&champions)?;
+        Ok(champions)
+    }
+
     /// Start the game
     pub fn start(&mut self) -> Result<()> {
         if self.champions.is_empty() {
@@ -162,7 +707,16 @@ impl GameEngine {
         self.state.running = true;
         self.state.start_time = Instant::now();
         self.state.last_cycle_time = Instant::now();
-        eprintln!("GameEngine::start: self.state.running set to {}", self.state.running);
+        self.resource_baseline = ResourceSnapshot::capture();
+        self.span = info_span!(
+            "battle",
+            champions = self.champions.len(),
+            seed = self.config.seed
+        );
+        let _battle = self.span.enter();
+        if let Some(throttle) = self.throttle.as_mut() {
+            throttle.restart(self.state.cycle);
+        }
 
         info!(
             "Starting Core War battle with {} champions",
@@ -171,7 +725,7 @@ impl GameEngine {
 
         // Print champion summary
         for champion in &self.champions {
-            info!("  {}: {}", champion.name, champion.comment);
+            info!("  {}: {}", champion.display_name(), champion.comment);
         }
 
         Ok(())
@@ -184,45 +738,145 @@ impl GameEngine {
     pub fn run_to_completion(&mut self) -> Result<Option<u8>> {
         self.start()?;
 
-        while self.tick()? {
-            // Loop continues as long as tick() returns true
+        while self.tick()?.is_running() {
+            // Loop continues as long as the battle is running
         }
 
-        self.determine_winner()
+        Ok(self.state.winner)
     }
 
     /// Execute a single game tick (cycle)
     ///
     /// # Returns
-    /// `Ok(true)` if the game is still running, `Ok(false)` if it has finished.
-    pub fn tick(&mut self) -> Result<bool> {
-        if !self.state.running || self.state.paused {
-            return Ok(self.state.running);
+    /// Whether the game is still running, and any breakpoints the cycle hit
+    pub fn tick(&mut self) -> Result<TickOutcome> {
+        if self.state.paused {
+            return Ok(self.outcome(Vec::new()));
+        }
+        let outcome = self.step_cycle()?;
+        if let Some(throttle) = self.throttle.as_mut()
+            && outcome.is_running()
+        {
+            throttle.wait(self.state.cycle);
         }
+        Ok(outcome)
+    }
 
-        self.state.cycle += 1;
+    /// Hold `tick` to a target pace, or let it run at full speed
+    ///
+    /// Only `tick` and the loops built on it, such as `run_to_completion`,
+    /// are paced; stepping, seeking and `run_cycles` stay immediate.
+    ///
+    /// # Arguments
+    /// * `throttle` - The pace to keep, or None for full speed
+    pub fn set_throttle(&mut self, throttle: Option<Throttle>) {
+        self.throttle = throttle;
+    }
+
+    /// Run up to `cycles` cycles, even while paused
+    ///
+    /// Stops early when the battle ends or a breakpoint triggers.
+    ///
+    /// # Arguments
+    /// * `cycles` - Maximum number of cycles to run
+    ///
+    /// # Returns
+    /// The outcome of the last cycle run
+    pub fn run_cycles(&mut self, cycles: u32) -> Result<TickOutcome> {
+        let mut outcome = self.outcome(Vec::new());
+        for _ in 0..cycles {
+            outcome = self.step_cycle()?;
+            if outcome != TickOutcome::Running {
+                break;
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Run cycles, even while paused, until a condition holds
+    ///
+    /// The condition is checked after every cycle. Stepping also stops when
+    /// the battle ends or a breakpoint triggers, so a condition that never
+    /// holds cannot hang a battle with a cycle limit or a death check.
+    ///
+    /// # Arguments
+    /// * `condition` - Called with the engine after each cycle
+    ///
+    /// # Returns
+    /// The outcome of the last cycle run
+    pub fn step_until<F>(&mut self, mut condition: F) -> Result<TickOutcome>
+    where
+        F: FnMut(&GameEngine) -> bool,
+    {
+        loop {
+            let outcome = self.step_cycle()?;
+            if outcome != TickOutcome::Running || condition(self) {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    /// Execute the next instruction of one process while every other process stays frozen
+    ///
+    /// The process skips its remaining wait cycles and the cycle counter does
+    /// not move. Works while paused; breakpoints are checked as for a tick.
+    ///
+    /// # Arguments
+    /// * `process_id` - The process to step
+    ///
+    /// # Returns
+    /// The resulting outcome, or an error if no such process is scheduled
+    pub fn step_process(&mut self, process_id: u32) -> Result<TickOutcome> {
+        if !self.state.running {
+            return Ok(self.outcome(Vec::new()));
+        }
+        self.require_live("Stepping a single process")?;
+
+        self.write_due_keyframe();
+        let pcs_before = self.pcs_before_step();
+        self.scheduler
+            .step_process(process_id, &mut self.memory, &mut self.champions)?;
+
+        // Keep rewinding to this cycle consistent with the stepped state
+        self.history.record(self.snapshot());
+
+        let hits = self.publish_step_events(pcs_before);
+        Ok(self.outcome(hits))
+    }
+
+    /// Execute one cycle regardless of the pause state
+    fn step_cycle(&mut self) -> Result<TickOutcome> {
+        if !self.state.running {
+            return Ok(self.outcome(Vec::new()));
+        }
+
+        let span = self.span.clone();
+        let _battle = span.enter();
         self.state.last_cycle_time = Instant::now();
-        debug!("Engine ticked. Current cycle: {}", self.state.cycle);
+        self.write_due_keyframe();
+        let pcs_before = self.pcs_before_step();
 
-        // Execute one cycle of the scheduler
-        let should_continue =
-            self.scheduler.execute_cycle(&mut self.memory, &mut self.champions)?;
+        let should_continue = self.advance_cycle()?;
+
+        let hits = self.publish_step_events(pcs_before);
+        self.publish(&GameEvent::CycleCompleted {
+            cycle: self.state.cycle,
+        });
 
         if !should_continue {
             self.state.running = false;
             if self.config.verbose {
                 info!("Game ended at cycle {}", self.state.cycle);
             }
-            debug!("GameEngine: self.state.running set to false because scheduler returned false.");
         }
 
         // Dump memory if requested
-        if self.config.dump_cycles > 0 && self.state.cycle % self.config.dump_cycles == 0 {
+        if self.config.dump_cycles > 0 && self.state.cycle.is_multiple_of(self.config.dump_cycles) {
             self.dump_memory()?;
         }
 
         // Log progress periodically
-        if self.config.verbose && self.state.cycle % 1000 == 0 {
+        if self.config.verbose && self.state.cycle.is_multiple_of(1000) {
             debug!(
                 "Cycle {}: {} processes active",
                 self.state.cycle,
@@ -234,93 +888,711 @@ impl GameEngine {
         if self.config.max_cycles > 0 && self.state.cycle >= self.config.max_cycles {
             info!("Reached maximum cycles limit: {}", self.config.max_cycles);
             self.state.running = false;
-            debug!("GameEngine: self.state.running set to false due to max_cycles.");
         }
 
-        debug!("tick: Returning running: {}", self.state.running);
-        Ok(self.state.running)
-    }
+        // Every caller sees the winner as soon as the battle is over
+        if !self.state.running {
+            self.determine_winner()?;
+        }
 
-    
+        Ok(self.outcome(hits))
+    }
 
-    /// Pause the game
-    pub fn pause(&mut self) {
-        self.state.paused = true;
-        if self.config.verbose {
-            info!("Game paused at cycle {}", self.state.cycle);
-        }
+    /// Record process PCs before a step if address breakpoints need them
+    fn pcs_before_step(&self) -> Option<HashMap<u32, usize>> {
+        self.breakpoints
+            .watches_addresses()
+            .then(|| self.scheduler.processes().iter().map(|p| (p.id, p.pc)).collect())
     }
 
-    /// Resume the game
-    pub fn resume(&mut self) {
-        self.state.paused = false;
-        if self.config.verbose {
-            info!("Game resumed at cycle {}", self.state.cycle);
+    /// Publish the scheduler's events from a step and check them against the breakpoints
+    ///
+    /// # Arguments
+    /// * `pcs_before` - Process PCs recorded by [`GameEngine::pcs_before_step`]
+    ///
+    /// # Returns
+    /// The breakpoints the step hit
+    fn publish_step_events(
+        &mut self,
+        pcs_before: Option<HashMap<u32, usize>>,
+    ) -> Vec<BreakpointHit> {
+        let events = self.scheduler.take_events();
+        let hits = if self.breakpoints.is_empty() {
+            Vec::new()
+        } else {
+            let moved: Vec<(u32, usize)> = pcs_before
+                .map(|before| {
+                    self.scheduler
+                        .processes()
+                        .iter()
+                        .filter(|p| before.get(&p.id) != Some(&p.pc))
+                        .map(|p| (p.id, p.pc))
+                        .collect()
+                })
+                .unwrap_or_default();
+            self.breakpoints.hits(self.state.cycle, &events, &moved)
+        };
+        for event in &events {
+            self.publish(event);
         }
+        hits
     }
 
-    /// Toggle pause state
-    pub fn toggle_pause(&mut self) {
-        if self.state.paused {
-            self.resume();
+    /// Build the outcome of a tick from the running state
+    fn outcome(&self, hits: Vec<BreakpointHit>) -> TickOutcome {
+        if !self.state.running {
+            TickOutcome::Finished
+        } else if hits.is_empty() {
+            TickOutcome::Running
         } else {
-            self.pause();
+            TickOutcome::Breakpoint(hits)
         }
     }
 
-    /// Determine the winner based on current game state
-    fn determine_winner(&mut self) -> Result<Option<u8>> {
-        // Count active processes per champion
-        let mut active_champions = Vec::new();
+    /// Install a breakpoint
+    ///
+    /// # Arguments
+    /// * `breakpoint` - The condition that should interrupt the battle
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.add(breakpoint);
+    }
 
-        for champion in &self.champions {
-            if champion.process_count > 0 {
-                active_champions.push(champion.id);
-            }
-        }
+    /// Remove a breakpoint
+    ///
+    /// # Arguments
+    /// * `breakpoint` - The breakpoint to remove
+    ///
+    /// # Returns
+    /// Whether the breakpoint was installed
+    pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+        self.breakpoints.remove(breakpoint)
+    }
 
-        match active_champions.len() {
-            0 => {
-                info!("No active champions remaining - it's a draw!");
-                self.state.winner = None;
-                Ok(None)
-            }
-            1 => {
-                let winner_id = active_champions[0];
-                let winner = self.champions.iter().find(|c| c.id == winner_id).unwrap();
-                info!("Champion {} ({}) wins!", winner_id, winner.name);
-                self.state.winner = Some(winner_id);
-                Ok(Some(winner_id))
-            }
-            _ => {
-                // Multiple champions still active
-                Ok(None)
-            }
-        }
+    /// Remove every breakpoint
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
     }
 
-    /// Dump current memory state
-    pub fn dump_memory(&self) -> Result<()> {
-        println!("\n=== Memory Dump (Cycle {}) ===", self.state.cycle);
-        println!("{}", self.memory.dump_hex(0, MEMORY_SIZE.min(512))); // Limit to first 512 bytes
+    /// Get the installed breakpoints
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        self.breakpoints.as_slice()
+    }
 
-        // Show process information
-        println!("=== Process Information ===");
-        println!("Active processes: {}", self.scheduler.process_count());
+    /// Watch a memory range for writes
+    ///
+    /// Any process writing a byte in the range makes the tick report a
+    /// breakpoint hit carrying the writer and the instruction it executed.
+    ///
+    /// # Arguments
+    /// * `range` - Addresses to watch, end exclusive
+    pub fn watch(&mut self, range: Range<usize>) {
+        self.breakpoints.add(Breakpoint::watch(range));
+    }
 
-        for champion in &self.champions {
-            println!(
-                "Champion {}: {} ({} processes)",
-                champion.id, champion.name, champion.process_count
-            );
+    /// Stop watching a memory range
+    ///
+    /// # Arguments
+    /// * `range` - A range previously passed to [`GameEngine::watch`]
+    ///
+    /// # Returns
+    /// Whether the range was watched
+    pub fn unwatch(&mut self, range: Range<usize>) -> bool {
+        self.breakpoints.remove(&Breakpoint::watch(range))
+    }
+
+    
+
+    /// Execute one scheduler cycle, taking a checkpoint when one is due
+    ///
+    /// # Returns
+    /// Whether the scheduler wants the game to continue
+    fn advance_cycle(&mut self) -> Result<bool> {
+        #[cfg(feature = "event-protocol")]
+        if self.replay.is_some() {
+            return self.replay_cycle();
         }
 
-        println!();
-        Ok(())
-    }
+        self.state.cycle += 1;
+        trace!(cycle = self.state.cycle, "engine ticked");
 
-    /// Get current game statistics
-    pub fn get_stats(&self) -> GameStats {
+        // Execute one cycle of the scheduler
+        let should_continue =
+            self.scheduler.execute_cycle(&mut self.memory, &mut self.champions)?;
+
+        if self.history.is_due(self.state.cycle) {
+            self.history.record(self.snapshot());
+        }
+
+        Ok(should_continue)
+    }
+
+    /// Apply the recorded events of the next cycle while replaying
+    ///
+    /// A keyframe that moves the battle to another cycle, such as one
+    /// recorded after a rewind, is applied before the cycle runs, and
+    /// observers see a [`GameEvent::Rewound`] if it moves the battle back.
+    /// A keyframe at the cycle just replayed, such as the final state, is
+    /// applied right after it.
+    ///
+    /// # Returns
+    /// Whether the recording goes on
+    #[cfg(feature = "event-protocol")]
+    fn replay_cycle(&mut self) -> Result<bool> {
+        let replay = self.replay.as_mut().expect("only called while replaying");
+        let step = replay.cursor.advance(self.state.cycle);
+        if let Some(keyframe) = step.before {
+            let jumped_back = keyframe.cycle < self.state.cycle;
+            self.apply_snapshot(keyframe)?;
+            if jumped_back {
+                self.publish(&GameEvent::Rewound {
+                    cycle: self.state.cycle,
+                });
+            }
+        }
+
+        self.state.cycle = step.cycle;
+        trace!(cycle = self.state.cycle, "engine replayed");
+        self.scheduler
+            .replay_cycle(&step.events, &mut self.memory, &mut self.champions);
+        if let Some(keyframe) = step.after {
+            // Restoring drops the scheduler's events, so publish them first
+            self.dispatch_events();
+            self.apply_snapshot(keyframe)?;
+        }
+
+        let replay = self.replay.as_ref().expect("only called while replaying");
+        let finished = replay.cursor.is_finished();
+        if replay.checkpoint_due() {
+            let snapshot = self.snapshot();
+            if let Some(replay) = &mut self.replay {
+                replay.checkpoint(snapshot);
+            }
+        }
+        Ok(!finished)
+    }
+
+    /// Seek a replay back along the recording
+    ///
+    /// # Arguments
+    /// * `cycles` - Number of replayed cycles to go back
+    ///
+    /// # Returns
+    /// The cycle the battle is now at
+    #[cfg(feature = "event-protocol")]
+    fn rewind_replay(&mut self, cycles: u32) -> Result<u32> {
+        let replay = self.replay.as_mut().expect("only called while replaying");
+        let previous_step = replay.cursor.step();
+        let target = previous_step.saturating_sub(cycles);
+        let (checkpoint, cursor) = replay.checkpoint_at_or_before(target);
+        replay.cursor = cursor;
+
+        self.apply_snapshot(checkpoint)?;
+        while self
+            .replay_progress()
+            .is_some_and(|(step, _)| step < target)
+        {
+            self.replay_cycle()?;
+        }
+        // Observers already saw the replayed cycles the first time around
+        self.scheduler.take_events();
+
+        if target < previous_step {
+            self.state.running = true;
+        }
+        self.publish(&GameEvent::Rewound {
+            cycle: self.state.cycle,
+        });
+        Ok(self.state.cycle)
+    }
+
+    /// Step the battle backwards
+    ///
+    /// Restores the newest checkpoint at or before the target cycle and
+    /// replays the cycles in between. Replayed cycles are not published to
+    /// observers; a single [`GameEvent::Rewound`] is published instead.
+    /// While replaying a recording, cycles are counted along the recording
+    /// and the replay's own checkpoints are used, so it can always go back
+    /// to its start.
+    ///
+    /// # Arguments
+    /// * `cycles` - Number of cycles to go back
+    ///
+    /// # Returns
+    /// The cycle the battle is now at, or an error if no checkpoint is old enough
+    pub fn rewind(&mut self, cycles: u32) -> Result<u32> {
+        #[cfg(feature = "event-protocol")]
+        if self.replay.is_some() {
+            return self.rewind_replay(cycles);
+        }
+        let previous_cycle = self.state.cycle;
+        let target = previous_cycle.saturating_sub(cycles);
+
+        let checkpoint = match self.history.latest_at_or_before(target) {
+            Some(checkpoint) => checkpoint.clone(),
+            None => {
+                return Err(CoreWarError::game_state(match self.history.oldest_cycle() {
+                    Some(oldest) => format!(
+                        "Cannot rewind to cycle {}: oldest checkpoint is cycle {}",
+                        target, oldest
+                    ),
+                    None => format!("Cannot rewind to cycle {}: no checkpoints kept", target),
+                }));
+            }
+        };
+
+        self.apply_snapshot(checkpoint)?;
+        self.history.discard_after(target);
+        while self.state.cycle < target {
+            self.advance_cycle()?;
+        }
+        // Observers already saw the replayed cycles the first time around
+        self.scheduler.take_events();
+
+        if target < previous_cycle {
+            self.state.running = true;
+        }
+        self.publish(&GameEvent::Rewound {
+            cycle: self.state.cycle,
+        });
+
+        debug!("Rewound from cycle {} to {}", previous_cycle, self.state.cycle);
+        Ok(self.state.cycle)
+    }
+
+    /// Subscribe an observer to game events
+    ///
+    /// # Arguments
+    /// * `observer` - The observer to notify of every event from now on
+    pub fn subscribe(&mut self, observer: Box<dyn GameObserver>) {
+        self.events.subscribe(observer);
+    }
+
+    /// Subscribe to game events through a channel
+    ///
+    /// # Returns
+    /// A receiver that yields every event from now on
+    pub fn subscribe_channel(&mut self) -> Receiver<GameEvent> {
+        self.events.subscribe_channel()
+    }
+
+    /// Write every event from now on to a binary event stream
+    ///
+    /// The stream header is written immediately; see [`crate::vm::protocol`]
+    /// for the layout. Records are flushed after every cycle so a visualizer
+    /// reading a pipe stays in step with the battle. If writing fails, for
+    /// example because the reader went away, the stream is dropped and the
+    /// battle carries on.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the stream
+    ///
+    /// # Returns
+    /// `Ok(())` if the header was written, error otherwise
+    #[cfg(feature = "event-protocol")]
+    pub fn emit_events(&mut self, writer: Box<dyn Write + Send>) -> Result<()> {
+        self.event_stream = Some(EventEncoder::new(writer, self.memory.size())?);
+        Ok(())
+    }
+
+    /// Hide champion code from the binary event stream
+    ///
+    /// For streaming a live battle to spectators: memory writes in the
+    /// stream show which champion wrote where, but not the bytes written or
+    /// the opcode that wrote them (see [`GameEvent::redacted`]), so opponents
+    /// cannot rebuild a submission from the stream while the match runs.
+    /// Observers and recordings still see every event in full, so the code
+    /// can be published from a bundle or recording once the match is over.
+    ///
+    /// # Arguments
+    /// * `redact` - Whether to redact the stream from now on
+    #[cfg(feature = "event-protocol")]
+    pub fn redact_events(&mut self, redact: bool) {
+        self.redact_stream = redact;
+    }
+
+    /// Flush the binary event stream, if any, and stop writing to it
+    ///
+    /// # Returns
+    /// `Ok(())` if the buffered records were written, error otherwise
+    #[cfg(feature = "event-protocol")]
+    pub fn finish_events(&mut self) -> Result<()> {
+        match self.event_stream.take() {
+            Some(mut stream) => stream.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Write every event from now on as a line of JSON
+    ///
+    /// See [`crate::vm::ndjson`] for the objects written. Lines are flushed
+    /// after every cycle; if writing fails, the output is dropped and the
+    /// battle carries on.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the lines
+    pub fn emit_json(&mut self, writer: Box<dyn Write + Send>) {
+        self.json_stream = Some(JsonLinesWriter::new(writer));
+    }
+
+    /// Close the JSON output, if any, with the battle's results
+    ///
+    /// # Returns
+    /// `Ok(())` if the results were written, error otherwise
+    pub fn finish_json(&mut self) -> Result<()> {
+        match self.json_stream.take() {
+            Some(mut stream) => {
+                stream.write_result(&BattleReport::of(self))?;
+                stream.flush()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Record the battle for replaying it later
+    ///
+    /// The header and configuration are written immediately. The battle's
+    /// state is written as a keyframe before the next cycle runs and again
+    /// whenever it is replaced rather than evolved, by a rewind, a restored
+    /// snapshot or a restart; every event in between is recorded. See
+    /// [`crate::vm::recording`] for the layout. If writing fails, the
+    /// recording is dropped and the battle carries on.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the recording
+    ///
+    /// # Returns
+    /// `Ok(())` if the header was written, error otherwise
+    #[cfg(feature = "event-protocol")]
+    pub fn record(&mut self, writer: Box<dyn Write + Send>) -> Result<()> {
+        self.recorder = Some(Recorder::new(writer, &self.config)?);
+        Ok(())
+    }
+
+    /// Close the recording, if any, with the battle's current state
+    ///
+    /// # Returns
+    /// `Ok(())` if the recording was written, error otherwise
+    #[cfg(feature = "event-protocol")]
+    pub fn finish_recording(&mut self) -> Result<()> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(&self.snapshot()).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Write a keyframe if the recording needs one before the next step
+    fn write_due_keyframe(&mut self) {
+        #[cfg(feature = "event-protocol")]
+        if self.recorder.as_ref().is_some_and(|r| r.keyframe_due()) {
+            let snapshot = self.snapshot();
+            if let Some(recorder) = &mut self.recorder
+                && let Err(e) = recorder.keyframe(&snapshot)
+            {
+                warn!("Recording stopped: {}", e);
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Note that the battle's state was replaced outside of events
+    fn invalidate_recording(&mut self) {
+        #[cfg(feature = "event-protocol")]
+        if let Some(recorder) = &mut self.recorder {
+            recorder.invalidate();
+        }
+    }
+
+    /// Forward the scheduler's pending events to observers
+    fn dispatch_events(&mut self) {
+        for event in self.scheduler.take_events() {
+            self.publish(&event);
+        }
+    }
+
+    /// Send an event to the observers and the event outputs
+    fn publish(&mut self, event: &GameEvent) {
+        self.events.publish(event);
+
+        if let Some(stream) = &mut self.json_stream {
+            let written = stream.write_event(self.state.cycle, event).and_then(|()| {
+                if matches!(event, GameEvent::CycleCompleted { .. }) {
+                    stream.flush()
+                } else {
+                    Ok(())
+                }
+            });
+            if let Err(e) = written {
+                warn!("JSON output closed: {}", e);
+                self.json_stream = None;
+            }
+        }
+
+        #[cfg(feature = "event-protocol")]
+        if let Some(stream) = &mut self.event_stream {
+            let encoded = if self.redact_stream {
+                stream.encode(self.state.cycle, &event.redacted())
+            } else {
+                stream.encode(self.state.cycle, event)
+            };
+            let written = encoded.and_then(|()| {
+                if matches!(event, GameEvent::CycleCompleted { .. }) {
+                    stream.flush()
+                } else {
+                    Ok(())
+                }
+            });
+            if let Err(e) = written {
+                warn!("Event stream closed: {}", e);
+                self.event_stream = None;
+            }
+        }
+
+        #[cfg(feature = "event-protocol")]
+        if let Some(recorder) = &mut self.recorder {
+            let recorded = match event {
+                // The state is replaced, so a keyframe describes it instead
+                GameEvent::Rewound { .. } => {
+                    recorder.invalidate();
+                    Ok(())
+                }
+                _ => recorder.record(self.state.cycle, event),
+            };
+            if let Err(e) = recorded {
+                warn!("Recording stopped: {}", e);
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Pause the game
+    pub fn pause(&mut self) {
+        self.state.paused = true;
+        if self.config.verbose {
+            info!("Game paused at cycle {}", self.state.cycle);
+        }
+    }
+
+    /// Resume the game
+    pub fn resume(&mut self) {
+        self.state.paused = false;
+        if self.config.verbose {
+            info!("Game resumed at cycle {}", self.state.cycle);
+        }
+    }
+
+    /// Toggle pause state
+    pub fn toggle_pause(&mut self) {
+        if self.state.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// Determine the winner based on current game state
+    ///
+    /// A battle that ends by running its course decides its winner by
+    /// itself, into [`GameState::winner`]; call this to judge a battle that
+    /// is still running, such as one cut short by the caller.
+    ///
+    /// The winner is the last champion to report alive via `live`, even if
+    /// none of its processes survived. If no champion ever reported alive the
+    /// battle is a draw, which the configured tie-breakers may still decide.
+    /// Without [`Rules::require_live`] the winner is instead the only champion
+    /// with processes left, and a battle with several survivors is a draw.
+    ///
+    /// # Returns
+    /// The winner champion ID, or None if no winner
+    pub fn determine_winner(&mut self) -> Result<Option<u8>> {
+        self.state.tie_break = None;
+        let mut winner_id = if self.config.rules.require_live {
+            self.scheduler.last_live_champion()
+        } else {
+            // Without death checks, as in pMARS, only the last champion standing wins
+            let survivors: BTreeSet<u8> = self
+                .scheduler
+                .processes()
+                .iter()
+                .map(|p| p.champion_id)
+                .collect();
+            match survivors.len() {
+                1 => survivors.first().copied(),
+                _ => None,
+            }
+        };
+
+        if winner_id.is_none() && !self.config.tie_breakers.is_empty() {
+            let scores = self.tie_break_scores();
+            self.state.tie_break = tiebreak::break_tie(&self.config.tie_breakers, &scores);
+            winner_id = self.state.tie_break.map(|tie_break| tie_break.winner);
+        }
+
+        let winner = winner_id.and_then(|id| self.champions.iter().find(|c| c.id == id));
+        match (winner, self.state.tie_break) {
+            (Some(winner), Some(tie_break)) => {
+                info!(
+                    "Draw broken by {}: Champion {} ({}) wins!",
+                    tie_break.decided_by, winner.id, winner.display_name()
+                );
+                self.state.winner = Some(winner.id);
+            }
+            (Some(winner), None) => {
+                info!("Champion {} ({}) wins!", winner.id, winner.display_name());
+                self.state.winner = Some(winner.id);
+            }
+            (None, _) if !self.config.rules.require_live => {
+                info!("No single champion survived - it's a draw!");
+                self.state.winner = None;
+            }
+            (None, _) => {
+                info!("No champion reported alive - it's a draw!");
+                self.state.winner = None;
+            }
+        }
+
+        Ok(self.state.winner)
+    }
+
+    /// Measure every champion for tie-breaking
+    fn tie_break_scores(&self) -> Vec<TieBreakScores> {
+        let processes = self.scheduler.processes();
+        self.champions
+            .iter()
+            .map(|champion| TieBreakScores {
+                champion_id: champion.id,
+                memory_owned: (0..self.memory.size())
+                    .filter(|&addr| self.memory.get_owner(addr) == Some(champion.id))
+                    .count(),
+                total_lives: champion.live_count,
+                processes_alive: processes
+                    .iter()
+                    .filter(|p| p.champion_id == champion.id)
+                    .count(),
+                first_kill_cycle: self.scheduler.first_kill_cycle(champion.id),
+            })
+            .collect()
+    }
+
+    /// Dump current memory state in the configured format
+    ///
+    /// Dumps go to the dump file if one is set, replacing its contents, and
+    /// to stdout otherwise. On the terminal the hex format is limited to the
+    /// first 512 bytes; every other dump holds the full core.
+    pub fn dump_memory(&self) -> Result<()> {
+        let format = self.config.dump_format;
+        match &self.dump_file {
+            Some(path) => fs::write(dump_path(path, self.state.cycle), self.render_dump(format)?)?,
+            None if format == DumpFormat::Hex => print!("{}", self.hex_dump(512)),
+            None => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(&self.render_dump(format)?)?;
+                stdout.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Render a dump of the full core
+    ///
+    /// # Arguments
+    /// * `format` - Layout of the dump
+    ///
+    /// # Returns
+    /// The dump's bytes: text for hex, zaz and JSON, the core itself for raw
+    pub fn render_dump(&self, format: DumpFormat) -> Result<Vec<u8>> {
+        Ok(match format {
+            DumpFormat::Hex => self.hex_dump(self.memory.size()).into_bytes(),
+            DumpFormat::Zaz => (self.zaz_introduction() + &self.memory.dump_zaz()).into_bytes(),
+            DumpFormat::Raw => self.memory.data().to_vec(),
+            DumpFormat::Json => {
+                let dump = MemoryDump {
+                    cycle: self.state.cycle,
+                    memory_size: self.memory.size(),
+                    champions: self
+                        .champions
+                        .iter()
+                        .map(|c| DumpedChampion {
+                            id: c.id,
+                            name: c.display_name().to_string(),
+                            process_count: self
+                                .scheduler
+                                .processes()
+                                .iter()
+                                .filter(|p| p.champion_id == c.id)
+                                .count(),
+                        })
+                        .collect(),
+                    bytes: self.memory.data().to_vec(),
+                    owners: self.memory.ownership().to_vec(),
+                };
+                let mut json = serde_json::to_vec(&dump)
+                    .map_err(|e| CoreWarError::game_state(format!("Cannot encode dump: {}", e)))?;
+                json.push(b'\n');
+                json
+            }
+        })
+    }
+
+    /// Write memory dumps to a file instead of stdout
+    ///
+    /// A `{cycle}` in the path is replaced by the cycle of each dump, so
+    /// every dump gets its own file; otherwise each dump replaces the last.
+    ///
+    /// # Arguments
+    /// * `path` - Destination file, or None to dump to stdout
+    pub fn set_dump_file(&mut self, path: Option<PathBuf>) {
+        self.dump_file = path;
+    }
+
+    /// Render the annotated hex dump of the start of memory
+    ///
+    /// # Arguments
+    /// * `limit` - Maximum number of bytes to show
+    fn hex_dump(&self, limit: usize) -> String {
+        let mut dump = format!("\n=== Memory Dump (Cycle {}) ===\n", self.state.cycle);
+        dump.push_str(&self.memory.dump_hex(0, self.memory.size().min(limit)));
+
+        // Show process information
+        dump.push_str("\n=== Process Information ===\n");
+        dump.push_str(&format!(
+            "Active processes: {}\n",
+            self.scheduler.process_count()
+        ));
+        for champion in &self.champions {
+            dump.push_str(&format!(
+                "Champion {}: {} ({} processes)\n",
+                champion.id,
+                champion.display_name(),
+                champion.process_count
+            ));
+        }
+        dump.push('\n');
+        dump
+    }
+
+    /// Render the contestant introduction the 42/zaz reference VM prints
+    ///
+    /// The reference prints this before a `-dump` of the core and uses the
+    /// header names, so aliases are ignored here.
+    ///
+    /// # Returns
+    /// The introduction, every line newline-terminated
+    pub fn zaz_introduction(&self) -> String {
+        let mut introduction = String::from("Introducing contestants...\n");
+        for champion in &self.champions {
+            introduction.push_str(&format!(
+                "* Player {}, weighing {} bytes, \"{}\" (\"{}\") !\n",
+                champion.id,
+                champion.code.len(),
+                champion.name,
+                champion.comment
+            ));
+        }
+        introduction
+    }
+
+    /// Get current game statistics
+    pub fn get_stats(&self) -> GameStats {
         let elapsed = self.state.start_time.elapsed();
         let cycles_per_second = if elapsed.as_secs() > 0 {
             self.state.cycle as f64 / elapsed.as_secs_f64()
@@ -341,7 +1613,267 @@ impl GameEngine {
                 .filter(|c| c.process_count > 0)
                 .count(),
             winner: self.state.winner,
+            tie_break: self.state.tie_break,
+            last_live_champion: self.scheduler.last_live_champion(),
+            resources: self.resource_usage(),
+        }
+    }
+
+    /// Get the code coverage of each champion
+    ///
+    /// # Returns
+    /// One entry per champion, or None if coverage tracking is disabled
+    pub fn coverage(&self) -> Option<Vec<ChampionCoverage>> {
+        self.scheduler.coverage().map(CoverageTracker::report)
+    }
+
+    /// Start timing each executed instruction by opcode
+    ///
+    /// Loading champions resets the scheduler, so enable timing after loading.
+    pub fn enable_instruction_timing(&mut self) {
+        self.scheduler.enable_timing();
+    }
+
+    /// Write a line for every traced instruction from now on
+    ///
+    /// See [`crate::vm::exectrace`] for the lines written. Loading champions
+    /// resets the scheduler, so start the trace after loading.
+    ///
+    /// # Arguments
+    /// * `trace` - Destination and filter of the trace
+    pub fn trace_execution(&mut self, mut trace: ExecutionTrace) {
+        trace.name_champions(&self.champions);
+        self.scheduler.enable_trace(trace);
+    }
+
+    /// Flush the execution trace, if any, and stop writing to it
+    ///
+    /// # Returns
+    /// The number of instructions traced, or an error if the trace could not
+    /// be written
+    pub fn finish_execution_trace(&mut self) -> Result<u64> {
+        match self.scheduler.take_trace() {
+            Some(trace) => trace.finish(),
+            None => Ok(0),
+        }
+    }
+
+    /// Get the time spent per opcode, if instruction timing is enabled
+    pub fn instruction_timings(&self) -> Option<&InstructionTimings> {
+        self.scheduler.timings()
+    }
+
+    /// Compute a digest of the simulation state
+    ///
+    /// The digest covers the cycle counter, memory contents and ownership, and
+    /// every process and scheduler counter. Wall-clock timings and
+    /// visualization state are excluded, so two runs with the same champions,
+    /// configuration and seed produce the same digest at the same cycle, on
+    /// every platform and pointer width.
+    ///
+    /// # Returns
+    /// A 64-bit hash of the current state
+    pub fn state_digest(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        self.state.cycle.hash(&mut hasher);
+        self.memory.hash(&mut hasher);
+        self.scheduler.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Capture the full simulation state
+    ///
+    /// The snapshot covers memory and ownership, processes, scheduler
+    /// counters, champions and the random number generator, so restoring it
+    /// resumes the battle exactly where it was taken.
+    ///
+    /// # Returns
+    /// A serializable snapshot of the current state
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            version: SNAPSHOT_VERSION,
+            rules: self.config.rules,
+            cycle: self.state.cycle,
+            winner: self.state.winner,
+            tie_break: self.state.tie_break,
+            memory: self.memory.data().to_vec(),
+            ownership: self.memory.ownership().to_vec(),
+            scheduler: self.scheduler.snapshot(),
+            champions: self.champions.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Replace the simulation state with a snapshot
+    ///
+    /// The snapshot's rules replace the configured ones. Running and paused
+    /// flags, observers and the rest of the configuration are kept. Coverage
+    /// is not part of a snapshot; when enabled, it restarts from the
+    /// restored state. Rewind checkpoints from before the restore are dropped.
+    ///
+    /// # Arguments
+    /// * `snapshot` - A snapshot taken with [`GameEngine::snapshot`]
+    ///
+    /// # Returns
+    /// `Ok(())` if successful, error if the snapshot is inconsistent
+    pub fn restore(&mut self, snapshot: GameSnapshot) -> Result<()> {
+        self.apply_snapshot(snapshot)?;
+        self.branch = None;
+
+        if self.config.track_coverage {
+            self.scheduler
+                .enable_coverage(CoverageTracker::new(&self.champions, self.memory.size()));
+        }
+
+        self.history = CheckpointHistory::new(
+            self.config.checkpoint_interval,
+            self.config.checkpoint_capacity,
+        );
+        self.history.record(self.snapshot());
+        self.invalidate_recording();
+
+        info!("Restored snapshot at cycle {}", self.state.cycle);
+        Ok(())
+    }
+
+    /// Fork the battle into a what-if branch
+    ///
+    /// Everything that happens until [`GameEngine::discard_branch`] can be
+    /// thrown away, including memory pokes and killed processes, which are
+    /// only allowed inside a branch.
+    ///
+    /// # Returns
+    /// `Ok(())` if the branch started, error if one is already active
+    pub fn branch(&mut self) -> Result<()> {
+        self.require_live("Branching")?;
+        if self.branch.is_some() {
+            return Err(CoreWarError::game_state(
+                "Already in a what-if branch".to_string(),
+            ));
+        }
+
+        self.branch = Some(Box::new(BranchOrigin {
+            snapshot: self.snapshot(),
+            history: self.history.clone(),
+            coverage: self.scheduler.coverage().cloned(),
+            running: self.state.running,
+        }));
+        info!("Branched at cycle {}", self.state.cycle);
+        Ok(())
+    }
+
+    /// Check whether a what-if branch is active
+    pub fn is_branched(&self) -> bool {
+        self.branch.is_some()
+    }
+
+    /// Throw away the what-if branch and return to where it started
+    ///
+    /// # Returns
+    /// The cycle the battle returned to, or an error if no branch is active
+    pub fn discard_branch(&mut self) -> Result<u32> {
+        let origin = self.branch.take().ok_or_else(|| {
+            CoreWarError::game_state("Not in a what-if branch".to_string())
+        })?;
+        let origin = *origin;
+
+        self.apply_snapshot(origin.snapshot)?;
+        self.history = origin.history;
+        if let Some(coverage) = origin.coverage {
+            self.scheduler.enable_coverage(coverage);
+        }
+        self.state.running = origin.running;
+
+        self.publish(&GameEvent::Rewound {
+            cycle: self.state.cycle,
+        });
+        info!("Discarded branch, back at cycle {}", self.state.cycle);
+        Ok(self.state.cycle)
+    }
+
+    /// Overwrite a byte of memory inside a what-if branch
+    ///
+    /// The byte keeps its owner.
+    ///
+    /// # Arguments
+    /// * `address` - Address to write, wrapped to the memory size
+    /// * `value` - Byte to write
+    ///
+    /// # Returns
+    /// `Ok(())` if written, error if no branch is active
+    pub fn poke_memory(&mut self, address: usize, value: u8) -> Result<()> {
+        self.require_branch("Memory")?;
+        self.memory.write_byte(address, value, None);
+        self.invalidate_recording();
+        Ok(())
+    }
+
+    /// Kill a process inside a what-if branch
+    ///
+    /// # Arguments
+    /// * `process_id` - The process to kill
+    ///
+    /// # Returns
+    /// `Ok(())` if killed, error if no branch is active or no such process exists
+    pub fn kill_process(&mut self, process_id: u32) -> Result<()> {
+        self.require_branch("Processes")?;
+        self.scheduler.kill_process(process_id)?;
+        self.dispatch_events();
+        Ok(())
+    }
+
+    /// Refuse to change the course of a replayed battle
+    #[cfg_attr(not(feature = "event-protocol"), allow(unused_variables))]
+    fn require_live(&self, what: &str) -> Result<()> {
+        #[cfg(feature = "event-protocol")]
+        if self.replay.is_some() {
+            return Err(CoreWarError::game_state(format!(
+                "{} is not possible while replaying a recording",
+                what
+            )));
+        }
+        Ok(())
+    }
+
+    /// Refuse to edit the battle outside a what-if branch
+    fn require_branch(&self, what: &str) -> Result<()> {
+        if self.branch.is_none() {
+            return Err(CoreWarError::game_state(format!(
+                "{} can only be edited in a what-if branch",
+                what
+            )));
         }
+        Ok(())
+    }
+
+    /// Replace the simulation state with a snapshot, keeping coverage and history
+    fn apply_snapshot(&mut self, snapshot: GameSnapshot) -> Result<()> {
+        snapshot.check_version()?;
+        snapshot.rules.validate()?;
+        let memory = Memory::from_parts(&snapshot.rules, snapshot.memory, snapshot.ownership)?;
+
+        self.memory = memory;
+        self.scheduler.restore(snapshot.rules, snapshot.scheduler);
+        self.champions = snapshot.champions;
+        self.config.rules = snapshot.rules;
+        self.rng = snapshot.rng;
+        self.state.cycle = snapshot.cycle;
+        self.state.winner = snapshot.winner;
+        self.state.tie_break = snapshot.tie_break;
+        Ok(())
+    }
+
+    /// Get the random number generator used for battle decisions
+    pub fn rng_mut(&mut self) -> &mut SeededRng {
+        &mut self.rng
+    }
+
+    /// Measure the resources used since the battle started
+    ///
+    /// # Returns
+    /// CPU time, peak RSS and allocation counts where available
+    pub fn resource_usage(&self) -> ResourceUsage {
+        self.resource_baseline.usage_since()
     }
 
     /// Get reference to memory (for UI)
@@ -349,11 +1881,41 @@ impl GameEngine {
         &self.memory
     }
 
+    /// Capture the current memory to report later changes against
+    ///
+    /// Replaces any baseline captured before.
+    pub fn capture_memory_baseline(&mut self) {
+        self.memory_baseline = Some(self.memory.clone());
+    }
+
+    /// Report what changed in memory since the baseline was captured
+    ///
+    /// # Returns
+    /// The changed ranges with their old and new bytes and owners, or an
+    /// error if no baseline was captured
+    pub fn memory_changes(&self) -> Result<Vec<MemDelta>> {
+        let baseline = self
+            .memory_baseline
+            .as_ref()
+            .ok_or_else(|| CoreWarError::game_state("No memory baseline captured"))?;
+        Ok(baseline.diff(&self.memory))
+    }
+
+    /// Get the champion files left out because they failed to load
+    pub fn skipped_champions(&self) -> &[SkippedChampion] {
+        &self.skipped_champions
+    }
+
     /// Get reference to champions (for UI)
     pub fn champions(&self) -> &[Champion] {
         &self.champions
     }
 
+    /// Get the game configuration
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
     /// Get current game state
     pub fn state(&self) -> &GameState {
         &self.state
@@ -373,10 +1935,55 @@ impl GameEngine {
     pub fn processes(&self) -> Vec<&crate::vm::Process> {
         self.scheduler.processes()
     }
+
+    /// Get an active process by ID
+    ///
+    /// Process IDs are never reused, so the UI and debugger can hold on to
+    /// one across cycles.
+    pub fn process(&self, process_id: u32) -> Option<&crate::vm::Process> {
+        self.scheduler.process(process_id)
+    }
+}
+
+/// Full core written by a JSON memory dump
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryDump {
+    /// Cycle the dump was taken at
+    pub cycle: u32,
+    /// Number of bytes in the core
+    pub memory_size: usize,
+    /// Champions in the battle, for naming the owners
+    pub champions: Vec<DumpedChampion>,
+    /// Every byte of the core
+    pub bytes: Vec<u8>,
+    /// Champion that last wrote each byte, if any
+    pub owners: Vec<Option<u8>>,
+}
+
+/// A champion listed in a JSON memory dump
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpedChampion {
+    /// Champion ID
+    pub id: u8,
+    /// Name shown in reports
+    pub name: String,
+    /// Processes alive when the dump was taken
+    pub process_count: usize,
+}
+
+/// Replace `{cycle}` in a dump file path with the dump's cycle
+fn dump_path(path: &Path, cycle: u32) -> PathBuf {
+    match path.to_str() {
+        Some(text) if text.contains("{cycle}") => {
+            PathBuf::from(text.replace("{cycle}", &cycle.to_string()))
+        }
+        _ => path.to_path_buf(),
+    }
 }
 
 /// Game statistics
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct GameStats {
     pub cycle: u32,
     pub running: bool,
@@ -386,47 +1993,33 @@ pub struct GameStats {
     pub active_processes: usize,
     pub active_champions: usize,
     pub winner: Option<u8>,
+    /// How a drawn battle was decided, if a tie-breaker picked the winner
+    pub tie_break: Option<TieBreak>,
+    /// Champion that most recently reported alive
+    pub last_live_champion: Option<u8>,
+    /// Resources used since the battle started
+    pub resources: ResourceUsage,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::vm::ChampionColor;
+    use crate::vm::loader::write_champion;
+    use crate::vm::mutator;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
     /// Create a simple test champion that just executes live instructions
     fn create_live_champion(name: &str) -> NamedTempFile {
-        let mut file = NamedTempFile::new().unwrap();
-
-        // Write header
-        let magic = 0xea83f3u32;
-        file.write_all(&magic.to_le_bytes()).unwrap();
-
-        // Write name (128 bytes)
-        let mut name_bytes = [0u8; 128];
-        let name_src = name.as_bytes();
-        name_bytes[..name_src.len()].copy_from_slice(name_src);
-        file.write_all(&name_bytes).unwrap();
-
-        // Padding
-        file.write_all(&[0u8; 4]).unwrap();
-
-        // Code: live %1 (simple instruction)
-        let code = vec![0x01, 0x40, 0x01, 0x00]; // live %1 in bytecode
-        file.write_all(&(code.len() as u32).to_le_bytes()).unwrap();
+        create_champion(name, &[0x01, 0x40, 0x01, 0x00]) // live %1 in bytecode
+    }
 
-        // Comment
+    /// Create a test champion with the given bytecode
+    fn create_champion(name: &str, code: &[u8]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
         let comment = format!("{} - test champion", name);
-        let mut comment_bytes = [0u8; 128];
-        comment_bytes[..comment.len().min(127)].copy_from_slice(comment.as_bytes());
-        file.write_all(&comment_bytes).unwrap();
-
-        // Final padding
-        file.write_all(&[0u8; 4]).unwrap();
-
-        // Write code
-        file.write_all(&code).unwrap();
-
+        write_champion(&mut file, name, &comment, code).unwrap();
         file.flush().unwrap();
         file
     }
@@ -474,7 +2067,7 @@ mod tests {
 
         // Execute a few cycles
         for _ in 0..5 {
-            if !engine.tick().unwrap() {
+            if !engine.tick().unwrap().is_running() {
                 break;
             }
         }
@@ -483,6 +2076,910 @@ mod tests {
         assert!(engine.state.cycle > 0);
     }
 
+    #[test]
+    fn test_custom_rules() {
+        let config = GameConfig {
+            rules: Rules {
+                memory_size: 1024,
+                idx_mod: 128,
+                cycle_to_die: 100,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = GameEngine::new(config);
+
+        let champion1 = create_live_champion("TestChamp1");
+        let champion2 = create_live_champion("TestChamp2");
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+
+        assert_eq!(engine.memory().size(), 1024);
+        assert_eq!(engine.champions[1].load_address, 512);
+        assert_eq!(engine.scheduler_stats().cycle_to_die, 100);
+
+        // Invalid rules are rejected before anything is loaded
+        let mut engine = GameEngine::new(GameConfig {
+            rules: Rules {
+                cycle_to_die: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        assert!(engine.load_champions(&[champion1.path()], None).is_err());
+    }
+
+    #[test]
+    fn test_state_digest_is_reproducible() {
+        let champion1 = create_live_champion("TestChamp1");
+        let champion2 = create_live_champion("TestChamp2");
+        let config = GameConfig {
+            max_cycles: 50,
+            seed: 1234,
+            ..Default::default()
+        };
+
+        let run = || {
+            let mut engine = GameEngine::new(config);
+            engine
+                .load_champions(&[champion1.path(), champion2.path()], None)
+                .unwrap();
+            engine.run_to_completion().unwrap();
+            engine.state_digest()
+        };
+        assert_eq!(run(), run());
+
+        // The digest changes as the battle progresses
+        let mut engine = GameEngine::new(config);
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        let before = engine.state_digest();
+        engine.start().unwrap();
+        engine.tick().unwrap();
+        assert_ne!(before, engine.state_digest());
+    }
+
+    #[test]
+    fn test_snapshot_restore_resumes_battle() {
+        // Long enough that both champions are still running after 60 cycles
+        let champion1 = create_champion("TestChamp1", &[0x01; 100]);
+        let champion2 = create_champion("TestChamp2", &[0x01; 100]);
+        let config = GameConfig {
+            seed: 99,
+            ..Default::default()
+        };
+
+        let mut original = GameEngine::new(config);
+        original
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        original.start().unwrap();
+        for _ in 0..30 {
+            original.tick().unwrap();
+        }
+
+        let json = original.snapshot().to_json().unwrap();
+        let mut restored = GameEngine::new(GameConfig::default());
+        restored
+            .restore(GameSnapshot::from_json(&json).unwrap())
+            .unwrap();
+
+        assert_eq!(restored.state().cycle, 30);
+        assert_eq!(restored.champions().len(), 2);
+        assert_eq!(restored.rng_mut().next_u64(), original.rng_mut().next_u64());
+        assert_eq!(restored.state_digest(), original.state_digest());
+
+        // Both engines continue identically
+        restored.start().unwrap();
+        for _ in 0..30 {
+            original.tick().unwrap();
+            restored.tick().unwrap();
+        }
+        assert_eq!(restored.state_digest(), original.state_digest());
+    }
+
+    #[test]
+    fn test_restore_rejects_inconsistent_snapshot() {
+        let champion = create_live_champion("TestChamp");
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine.load_champions(&[champion.path()], None).unwrap();
+
+        let mut snapshot = engine.snapshot();
+        snapshot.memory.truncate(100);
+        assert!(engine.restore(snapshot).is_err());
+
+        let mut snapshot = engine.snapshot();
+        snapshot.version += 1;
+        assert!(engine.restore(snapshot.clone()).is_err());
+        assert!(GameSnapshot::from_json(&snapshot.to_json().unwrap()).is_err());
+
+        assert!(GameSnapshot::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_rewind_replays_to_earlier_cycle() {
+        let champion1 = create_champion("TestChamp1", &[0x01; 600]);
+        let champion2 = create_champion("TestChamp2", &[0x01; 600]);
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        let events = engine.subscribe_channel();
+        engine.start().unwrap();
+
+        let mut digests = vec![engine.state_digest()];
+        for _ in 0..250 {
+            engine.tick().unwrap();
+            digests.push(engine.state_digest());
+        }
+        events.try_iter().for_each(drop);
+
+        assert_eq!(engine.rewind(70).unwrap(), 180);
+        assert_eq!(engine.state_digest(), digests[180]);
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            vec![GameEvent::Rewound { cycle: 180 }]
+        );
+
+        assert_eq!(engine.rewind(150).unwrap(), 30);
+        assert_eq!(engine.state_digest(), digests[30]);
+
+        // Running forward again reproduces the original battle
+        for _ in 30..250 {
+            engine.tick().unwrap();
+        }
+        assert_eq!(engine.state_digest(), digests[250]);
+    }
+
+    #[test]
+    fn test_rewind_limited_by_kept_checkpoints() {
+        let champion1 = create_champion("TestChamp1", &[0x01; 600]);
+        let champion2 = create_champion("TestChamp2", &[0x01; 600]);
+        let champions = [champion1.path(), champion2.path()];
+        let config = GameConfig {
+            checkpoint_interval: 100,
+            checkpoint_capacity: 1,
+            ..Default::default()
+        };
+        let mut engine = GameEngine::new(config);
+        engine.load_champions(&champions, None).unwrap();
+        engine.start().unwrap();
+        for _ in 0..250 {
+            engine.tick().unwrap();
+        }
+
+        assert!(engine.rewind(100).is_err());
+        assert_eq!(engine.state().cycle, 250);
+        assert_eq!(engine.rewind(50).unwrap(), 200);
+
+        let mut engine = GameEngine::new(GameConfig {
+            checkpoint_interval: 0,
+            ..Default::default()
+        });
+        engine.load_champions(&champions, None).unwrap();
+        assert!(engine.rewind(0).is_err());
+    }
+
+    #[test]
+    fn test_breakpoints_interrupt_ticks() {
+        let forker = create_champion("Forker", &[0x0C; 10]);
+        let liver = create_live_champion("Liver");
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_champions(&[forker.path(), liver.path()], None)
+            .unwrap();
+        let liver_start = engine.champions()[1].load_address;
+
+        engine.add_breakpoint(Breakpoint::Fork { champion_id: 1 });
+        engine.add_breakpoint(Breakpoint::Address(liver_start + 5));
+        engine.add_breakpoint(Breakpoint::Cycle(3));
+        engine.add_breakpoint(Breakpoint::ProcessDeath);
+        engine.start().unwrap();
+
+        // Cycle 1: champion 1 forks and champion 2's live moves it 5 bytes on
+        let TickOutcome::Breakpoint(hits) = engine.tick().unwrap() else {
+            panic!("expected breakpoints on cycle 1");
+        };
+        let triggered: Vec<Breakpoint> = hits.iter().map(|h| h.breakpoint).collect();
+        assert_eq!(
+            triggered,
+            vec![
+                Breakpoint::Fork { champion_id: 1 },
+                Breakpoint::Address(liver_start + 5)
+            ]
+        );
+        assert!(hits.iter().all(|h| h.cycle == 1));
+
+        // Cycle 2: the child lands on empty memory and dies
+        let TickOutcome::Breakpoint(hits) = engine.tick().unwrap() else {
+            panic!("expected a death on cycle 2");
+        };
+        assert_eq!(hits[0].breakpoint, Breakpoint::ProcessDeath);
+        assert_eq!(hits[0].process_id, Some(3));
+
+        let TickOutcome::Breakpoint(hits) = engine.tick().unwrap() else {
+            panic!("expected the cycle breakpoint");
+        };
+        assert_eq!(hits[0].breakpoint, Breakpoint::Cycle(3));
+        assert_eq!(engine.tick().unwrap(), TickOutcome::Running);
+
+        engine.clear_breakpoints();
+        assert!(engine.breakpoints().is_empty());
+    }
+
+    #[test]
+    fn test_run_cycles_and_step_until_ignore_pause() {
+        let first = create_champion("First", &[0x01; 100]);
+        let second = create_champion("Second", &[0x01; 100]);
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_champions(&[first.path(), second.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.pause();
+
+        assert_eq!(engine.tick().unwrap(), TickOutcome::Running);
+        assert_eq!(engine.state().cycle, 0);
+
+        assert_eq!(engine.run_cycles(5).unwrap(), TickOutcome::Running);
+        assert_eq!(engine.state().cycle, 5);
+
+        engine.add_breakpoint(Breakpoint::Cycle(8));
+        assert!(matches!(
+            engine.run_cycles(10).unwrap(),
+            TickOutcome::Breakpoint(_)
+        ));
+        assert_eq!(engine.state().cycle, 8);
+
+        let outcome = engine.step_until(|e| e.state().cycle == 20).unwrap();
+        assert_eq!(outcome, TickOutcome::Running);
+        assert_eq!(engine.state().cycle, 20);
+    }
+
+    #[test]
+    fn test_step_process_freezes_others() {
+        let first = create_champion("First", &[0x01; 100]);
+        let second = create_champion("Second", &[0x01; 100]);
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_champions(&[first.path(), second.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.pause();
+
+        let pc_of = |engine: &GameEngine, id: u32| {
+            engine.processes().iter().find(|p| p.id == id).unwrap().pc
+        };
+        let (start1, start2) = (pc_of(&engine, 1), pc_of(&engine, 2));
+
+        // The second step skips the wait the first live left behind
+        engine.step_process(1).unwrap();
+        assert_eq!(engine.step_process(1).unwrap(), TickOutcome::Running);
+        assert_eq!(pc_of(&engine, 1), start1 + 10);
+        assert_eq!(pc_of(&engine, 2), start2);
+        assert_eq!(engine.state().cycle, 0);
+        assert_eq!(engine.champions()[0].live_count, 2);
+
+        assert!(engine.step_process(99).is_err());
+    }
+
+    #[test]
+    fn test_branch_edits_are_discarded() {
+        let first = create_champion("First", &[0x01; 100]);
+        let second = create_champion("Second", &[0x01; 100]);
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_champions(&[first.path(), second.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.run_cycles(20).unwrap();
+
+        assert!(engine.poke_memory(0, 0).is_err());
+        assert!(engine.kill_process(1).is_err());
+        assert!(engine.discard_branch().is_err());
+
+        let mut control = GameEngine::new(GameConfig::default());
+        control
+            .load_champions(&[first.path(), second.path()], None)
+            .unwrap();
+        control.start().unwrap();
+        control.run_cycles(20).unwrap();
+        let digest = engine.state_digest();
+
+        engine.branch().unwrap();
+        assert!(engine.branch().is_err());
+        let pc = engine.processes().iter().find(|p| p.id == 1).unwrap().pc;
+        engine.poke_memory(pc, 0x00).unwrap();
+        engine.kill_process(2).unwrap();
+        assert!(engine.kill_process(2).is_err());
+        engine.run_cycles(30).unwrap();
+        assert_eq!(engine.processes().len(), 0);
+        assert!(!engine.state().running);
+
+        assert_eq!(engine.discard_branch().unwrap(), 20);
+        assert!(!engine.is_branched());
+        assert!(engine.state().running);
+        assert_eq!(engine.state_digest(), digest);
+
+        // The original battle carries on as if the branch never happened
+        engine.run_cycles(30).unwrap();
+        control.run_cycles(30).unwrap();
+        assert_eq!(engine.state_digest(), control.state_digest());
+    }
+
+    #[test]
+    fn test_watch_reports_writer() {
+        let first = create_champion("First", &[0x01; 100]);
+        let second = create_champion("Second", &[0x01; 100]);
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_champions(&[first.path(), second.path()], None)
+            .unwrap();
+        let start = engine.champions()[1].load_address;
+
+        engine.watch(start..start + 4);
+        engine.start().unwrap();
+
+        // Champion 2's live marks the byte after its opcode
+        let TickOutcome::Breakpoint(hits) = engine.tick().unwrap() else {
+            panic!("expected the watchpoint on cycle 1");
+        };
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].breakpoint, Breakpoint::watch(start..start + 4));
+        let write = hits[0].write.unwrap();
+        assert_eq!(write.address, start + 1);
+        assert_eq!(write.value, 0xFF);
+        assert_eq!(write.champion_id, 2);
+        assert_eq!(write.opcode, 0x01);
+        assert_eq!(write.pc, start);
+        assert!(hits[0].to_string().contains("with live at"));
+
+        assert!(engine.unwatch(start..start + 4));
+        assert!(!engine.unwatch(start..start + 4));
+        assert!(engine.breakpoints().is_empty());
+    }
+
+    #[test]
+    fn test_skip_invalid_champions() {
+        let champion1 = create_live_champion("TestChamp1");
+        let mut broken = NamedTempFile::new().unwrap();
+        broken.write_all(b"not a champion").unwrap();
+        let champion3 = create_live_champion("TestChamp3");
+        let files = [champion1.path(), broken.path(), champion3.path()];
+
+        let mut engine = GameEngine::new(GameConfig::default());
+        assert!(engine.load_champions(&files, None).is_err());
+
+        let mut engine = GameEngine::new(GameConfig {
+            skip_invalid: true,
+            ..Default::default()
+        });
+        engine.load_champions(&files, None).unwrap();
+        assert_eq!(engine.champions().len(), 2);
+        assert_eq!(engine.processes().len(), 2);
+        assert_eq!(engine.skipped_champions().len(), 1);
+        assert_eq!(engine.skipped_champions()[0].path, broken.path());
+    }
+
+    #[test]
+    fn test_aliases_follow_loaded_files() {
+        let champion1 = create_live_champion("Imp");
+        let mut broken = NamedTempFile::new().unwrap();
+        broken.write_all(b"not a champion").unwrap();
+        let champion3 = create_live_champion("Imp");
+        let files = [champion1.path(), broken.path(), champion3.path()];
+        let aliases = [None, Some("Broken".to_string()), Some("Imp v2".to_string())];
+
+        let mut engine = GameEngine::new(GameConfig {
+            skip_invalid: true,
+            track_coverage: true,
+            ..Default::default()
+        });
+        engine
+            .load_aliased_champions(&files, &aliases, None)
+            .unwrap();
+
+        let names: Vec<&str> = engine.champions().iter().map(|c| c.display_name()).collect();
+        assert_eq!(names, vec!["Imp", "Imp v2"]);
+        assert_eq!(engine.champions()[1].name, "Imp");
+        assert_eq!(engine.coverage().unwrap()[1].name, "Imp v2");
+        assert_eq!(engine.snapshot().champions[1].display_name(), "Imp v2");
+    }
+
+    #[test]
+    fn test_champion_options_set_numbers_and_addresses() {
+        let first = create_live_champion("First");
+        let second = create_live_champion("Second");
+        let third = create_live_champion("Third");
+        let options = [
+            ChampionOptions {
+                number: Some(3),
+                ..Default::default()
+            },
+            ChampionOptions {
+                load_address: Some(0x200),
+                ..Default::default()
+            },
+            ChampionOptions {
+                alias: Some("Last".to_string()),
+                number: Some(1),
+                ..Default::default()
+            },
+        ];
+
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_champions_with_options(&[first.path(), second.path(), third.path()], &options)
+            .unwrap();
+
+        let loaded: Vec<(u8, &str, usize)> = engine
+            .champions()
+            .iter()
+            .map(|c| (c.id, c.display_name(), c.load_address))
+            .collect();
+        assert_eq!(
+            loaded,
+            vec![(1, "Last", 4096), (2, "Second", 0x200), (3, "First", 0)]
+        );
+        assert_eq!(engine.memory().get_owner(0), Some(3));
+        assert_eq!(engine.champions()[2].color, ChampionColor::Green);
+
+        let repeated = [options[0].clone(), options[0].clone()];
+        let mut engine = GameEngine::new(GameConfig::default());
+        assert!(
+            engine
+                .load_champions_with_options(&[first.path(), second.path()], &repeated)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_memory_changes_since_baseline() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("Adder", [0x04])
+            .build()
+            .unwrap();
+        assert!(engine.memory_changes().is_err());
+
+        engine.capture_memory_baseline();
+        assert!(engine.memory_changes().unwrap().is_empty());
+        engine.start().unwrap();
+        engine.run_cycles(1).unwrap();
+
+        let changes = engine.memory_changes().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].old_bytes, vec![0x00]);
+        assert_eq!(changes[0].new_bytes, vec![0xAA]);
+        assert_eq!(changes[0].old_owners, vec![None]);
+        assert_eq!(changes[0].new_owners, vec![Some(1)]);
+        assert_eq!(engine.memory().read_byte(changes[0].start), 0xAA);
+    }
+
+    #[test]
+    fn test_restart_reloads_champion_files() {
+        let mut champion1 = create_champion("Liver", &[0x01; 100]);
+        let champion2 = create_champion("Other", &[0x01; 100]);
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_aliased_champions(
+                &[champion1.path(), champion2.path()],
+                &[Some("Mine".to_string())],
+                None,
+            )
+            .unwrap();
+        let events = engine.subscribe_channel();
+        engine.start().unwrap();
+        engine.run_cycles(20).unwrap();
+        assert_eq!(
+            engine.champions()[0].source.as_deref(),
+            Some(champion1.path())
+        );
+
+        // Reassemble the first champion on disk
+        let reassembled = create_champion("Liver", &[0x0C; 50]);
+        std::fs::copy(reassembled.path(), champion1.path()).unwrap();
+        champion1.flush().unwrap();
+
+        engine.restart().unwrap();
+        assert_eq!(engine.state().cycle, 0);
+        assert!(!engine.state().running);
+        assert_eq!(engine.champions()[0].code, vec![0x0C; 50]);
+        assert_eq!(engine.champions()[0].display_name(), "Mine");
+        assert_eq!(engine.memory().read_byte(0), 0x0C);
+        assert_eq!(engine.processes().len(), 2);
+        assert!(
+            events
+                .try_iter()
+                .any(|event| event == GameEvent::Rewound { cycle: 0 })
+        );
+
+        let mut unloaded = GameEngine::builder()
+            .champion_bytes("Bytes", [0x01; 10])
+            .build()
+            .unwrap();
+        assert!(unloaded.restart().is_err());
+    }
+
+    #[test]
+    fn test_dumps_cover_the_full_core() {
+        let champion = create_champion("Bomber", &[0x04, 0x02, 0x02, 0x02, 0x02, 0x01]);
+        let faulting = create_champion("Fault", &[0x00; 10]);
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = GameEngine::new(GameConfig {
+            dump_format: DumpFormat::Json,
+            ..Default::default()
+        });
+        engine
+            .load_champions(&[champion.path(), faulting.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.run_cycles(1).unwrap();
+
+        let raw = engine.render_dump(DumpFormat::Raw).unwrap();
+        assert_eq!(raw, engine.memory().data());
+
+        engine.set_dump_file(Some(dir.path().join("core-{cycle}.json")));
+        engine.dump_memory().unwrap();
+        let json = fs::read_to_string(dir.path().join("core-1.json")).unwrap();
+        let dump: MemoryDump = serde_json::from_str(&json).unwrap();
+        assert_eq!(dump.cycle, 1);
+        assert_eq!(dump.bytes, raw);
+        assert_eq!(dump.owners, engine.memory().ownership());
+        assert_eq!(dump.owners[10], Some(1));
+        assert_eq!(dump.champions[0].name, "Bomber");
+        // The faulted process is gone before any death check
+        assert_eq!(dump.champions[0].process_count, 1);
+        assert_eq!(dump.champions[1].process_count, 0);
+
+        let hex = String::from_utf8(engine.render_dump(DumpFormat::Hex).unwrap()).unwrap();
+        assert!(hex.contains(&format!("{:04X}: ", engine.memory().size() - 16)));
+    }
+
+    #[cfg(feature = "event-protocol")]
+    #[test]
+    fn test_replay_follows_the_recorded_battle() {
+        let champion1 = create_champion("Liver", &[0x01; 100]);
+        let champion2 = create_champion("Forker", &[0x0C, 0x04, 0x04, 0x04, 0x04, 0x01]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("battle.cwrec");
+
+        let mut engine = GameEngine::new(GameConfig {
+            max_cycles: 120,
+            checkpoint_interval: 10,
+            ..Default::default()
+        });
+        engine
+            .record(Box::new(fs::File::create(&path).unwrap()))
+            .unwrap();
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.run_cycles(40).unwrap();
+        let memory_at_40 = engine.memory().clone();
+        let processes_at_40 = engine.processes().len();
+        assert_eq!(engine.rewind(15).unwrap(), 25);
+        let winner = engine.run_to_completion().unwrap();
+        engine.finish_recording().unwrap();
+
+        let mut replay = GameEngine::replay(Recording::read(&path).unwrap()).unwrap();
+        assert!(replay.is_replaying());
+        let events = replay.subscribe_channel();
+        replay.start().unwrap();
+        replay.run_cycles(40).unwrap();
+        assert!(memory_at_40.diff(replay.memory()).is_empty());
+        assert_eq!(replay.processes().len(), processes_at_40);
+        assert!(replay.branch().is_err());
+
+        assert_eq!(replay.run_to_completion().unwrap(), winner);
+        assert_eq!(replay.state().cycle, engine.state().cycle);
+        assert_eq!(replay.state_digest(), engine.state_digest());
+        assert!(
+            events
+                .try_iter()
+                .any(|event| event == GameEvent::Rewound { cycle: 25 })
+        );
+    }
+
+    #[cfg(feature = "event-protocol")]
+    #[test]
+    fn test_replay_seeks_along_the_recording() {
+        let liver = create_champion("Liver", &[0x01; 100]);
+        let dier = create_champion("Dier", &[0x02]);
+        // Keeps the battle going once the dier is out
+        let survivor = create_champion("Survivor", &[0x01; 100]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("battle.cwrec");
+
+        let mut engine = GameEngine::new(GameConfig {
+            max_cycles: 250,
+            ..Default::default()
+        });
+        engine
+            .record(Box::new(fs::File::create(&path).unwrap()))
+            .unwrap();
+        engine
+            .load_champions(&[liver.path(), dier.path(), survivor.path()], None)
+            .unwrap();
+        engine.run_to_completion().unwrap();
+        engine.finish_recording().unwrap();
+
+        let mut replay = GameEngine::replay(Recording::read(&path).unwrap()).unwrap();
+        let length = engine.state().cycle;
+        assert_eq!(replay.replay_progress(), Some((0, length)));
+        replay.start().unwrap();
+        let events = replay.subscribe_channel();
+        replay.seek_next_death().unwrap();
+        let (step, _) = replay.replay_progress().unwrap();
+        assert!(step > 0);
+        assert!(
+            events
+                .try_iter()
+                .any(|event| matches!(event, GameEvent::ProcessDied { .. }))
+        );
+
+        let mut digests = vec![(replay.state().cycle, replay.state_digest())];
+        while replay.tick().unwrap().is_running() {
+            digests.push((replay.state().cycle, replay.state_digest()));
+        }
+        digests.push((replay.state().cycle, replay.state_digest()));
+        assert_eq!(replay.replay_progress(), Some((length, length)));
+        assert_eq!(replay.state_digest(), engine.state_digest());
+        assert!(replay.seek_next_death().is_err());
+
+        // Back to a checkpoint's successor, then to the start and forward again
+        let at = |cycle: u32| digests.iter().find(|(c, _)| *c == cycle).unwrap().1;
+        assert_eq!(replay.rewind(length - 101).unwrap(), 101);
+        assert!(replay.state().running);
+        assert_eq!(replay.state_digest(), at(101));
+        assert_eq!(replay.rewind(1000).unwrap(), 0);
+        replay.run_cycles(130).unwrap();
+        assert_eq!(replay.state_digest(), at(130));
+        assert!(
+            GameEngine::new(GameConfig::default())
+                .seek_next_death()
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "event-protocol")]
+    #[test]
+    fn test_event_stream_matches_published_events() {
+        use crate::vm::protocol;
+        use std::sync::{Arc, Mutex};
+
+        struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for SharedBuffer {
+            fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(bytes)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let champion1 = create_champion("Liver", &[0x01; 100]);
+        let champion2 = create_champion("Forker", &[0x0C, 0x03, 0x03, 0x03, 0x03, 0x01]);
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+
+        let mut engine = GameEngine::new(GameConfig::default());
+        let published = engine.subscribe_channel();
+        engine
+            .emit_events(Box::new(SharedBuffer(buffer.clone())))
+            .unwrap();
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.run_cycles(30).unwrap();
+        engine.finish_events().unwrap();
+
+        let (header, stamped) = protocol::decode(&buffer.lock().unwrap()).unwrap();
+        assert_eq!(header.memory_size as usize, engine.memory().size());
+        let decoded: Vec<GameEvent> = stamped.iter().map(|s| s.event.clone()).collect();
+        assert_eq!(decoded, published.try_iter().collect::<Vec<_>>());
+
+        // Load-time spawns come first, then every cycle closes with its marker
+        assert!(matches!(stamped[0].event, GameEvent::ProcessSpawned { .. }));
+        assert_eq!(stamped[0].cycle, 0);
+        assert_eq!(
+            stamped.last().unwrap().event,
+            GameEvent::CycleCompleted { cycle: 30 }
+        );
+        assert!(
+            stamped
+                .iter()
+                .any(|s| matches!(s.event, GameEvent::MemoryWrite { .. }))
+        );
+    }
+
+    #[cfg(feature = "event-protocol")]
+    #[test]
+    fn test_redacted_event_stream_hides_written_bytes() {
+        use crate::vm::protocol;
+
+        let champion1 = create_champion("Liver", &[0x01; 100]);
+        let champion2 = create_champion("Writer", &[0x04; 100]);
+        let stream = NamedTempFile::new().unwrap();
+
+        let mut engine = GameEngine::new(GameConfig::default());
+        let published = engine.subscribe_channel();
+        engine
+            .emit_events(Box::new(stream.reopen().unwrap()))
+            .unwrap();
+        engine.redact_events(true);
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.run_cycles(30).unwrap();
+        engine.finish_events().unwrap();
+
+        let (_, stamped) = protocol::decode(&fs::read(stream.path()).unwrap()).unwrap();
+        let published: Vec<GameEvent> = published.try_iter().collect();
+        assert_eq!(stamped.len(), published.len());
+        assert!(
+            published
+                .iter()
+                .any(|e| matches!(e, GameEvent::MemoryWrite { value, .. } if *value != 0))
+        );
+        for (streamed, event) in stamped.iter().zip(&published) {
+            assert_eq!(streamed.event, event.redacted());
+        }
+    }
+
+    #[test]
+    fn test_json_output_ends_with_the_results() {
+        let champion1 = create_champion("Liver", &[0x01; 100]);
+        let champion2 = create_champion("Writer", &[0x04; 100]);
+        let output = NamedTempFile::new().unwrap();
+
+        let mut engine = GameEngine::new(GameConfig {
+            max_cycles: 50,
+            ..GameConfig::default()
+        });
+        let published = engine.subscribe_channel();
+        engine.emit_json(Box::new(output.reopen().unwrap()));
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.run_to_completion().unwrap();
+        engine.finish_json().unwrap();
+
+        let text = fs::read_to_string(output.path()).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let published: Vec<GameEvent> = published.try_iter().collect();
+        assert_eq!(lines.len(), published.len() + 1);
+        for (line, event) in lines.iter().zip(&published) {
+            let mut line = line.clone();
+            line.as_object_mut().unwrap().remove("cycle");
+            let mut expected = serde_json::to_value(event).unwrap();
+            expected.as_object_mut().unwrap().remove("cycle");
+            assert_eq!(line, expected);
+        }
+        let result = lines.last().unwrap();
+        assert_eq!(result["type"], "result");
+        assert_eq!(result["cycles"], engine.state().cycle);
+    }
+
+    #[test]
+    fn test_mutators_rewrite_targeted_champions_at_load() {
+        let code = [0x01, 0x00, 0x00, 0x00, 0x01].repeat(8);
+        let champion1 = create_champion("Original", &code);
+        let champion2 = create_champion("Mutant", &code);
+        let load = || {
+            let mut engine = GameEngine::new(GameConfig {
+                seed: 5,
+                ..Default::default()
+            });
+            engine.add_mutator(mutator::parse("nops=2@2").unwrap());
+            engine.add_mutator(mutator::parse("bitflip=4@2").unwrap());
+            engine
+                .load_champions(&[champion1.path(), champion2.path()], None)
+                .unwrap();
+            engine
+        };
+
+        let engine = load();
+        assert_eq!(engine.champions()[0].code, code);
+        let mutant = &engine.champions()[1];
+        assert_eq!(mutant.code.len(), code.len() + 2 * mutator::NOP.len());
+        assert_ne!(mutant.code, code);
+        let address = mutant.load_address;
+        assert_eq!(engine.memory().read_byte(address), mutant.code[0]);
+
+        // The mutation is drawn from the seed, so it repeats exactly
+        assert_eq!(load().champions()[1].code, mutant.code);
+        assert_eq!(load().state_digest(), engine.state_digest());
+    }
+
+    #[test]
+    fn test_champion_coverage() {
+        let champion = create_live_champion("TestChamp");
+
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine.load_champions(&[champion.path()], None).unwrap();
+        assert!(engine.coverage().is_none());
+
+        let config = GameConfig {
+            max_cycles: 10,
+            track_coverage: true,
+            ..Default::default()
+        };
+        let mut engine = GameEngine::new(config);
+        engine.load_champions(&[champion.path()], None).unwrap();
+        engine.run_to_completion().unwrap();
+
+        // The single live instruction covers the whole 4-byte program
+        let coverage = engine.coverage().unwrap();
+        assert_eq!(coverage.len(), 1);
+        assert_eq!(coverage[0].total_bytes(), 4);
+        assert_eq!(coverage[0].executed_bytes(), 4);
+    }
+
+    #[test]
+    fn test_event_subscription() {
+        let mut engine = GameEngine::new(GameConfig::default());
+        let events = engine.subscribe_channel();
+
+        let champion1 = create_live_champion("TestChamp1");
+        let champion2 = create_live_champion("TestChamp2");
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        engine.start().unwrap();
+        engine.tick().unwrap();
+
+        let events: Vec<GameEvent> = events.try_iter().collect();
+        let spawned = events
+            .iter()
+            .filter(|e| matches!(e, GameEvent::ProcessSpawned { .. }))
+            .count();
+        let lives = events
+            .iter()
+            .filter(|e| matches!(e, GameEvent::LiveReported { .. }))
+            .count();
+        assert_eq!(spawned, 2);
+        assert_eq!(lives, 2);
+        assert_eq!(events.last(), Some(&GameEvent::CycleCompleted { cycle: 1 }));
+    }
+
+    #[test]
+    fn test_draw_broken_by_tie_breakers() {
+        // Neither champion reports alive; the second one owns more memory
+        let champion1 = create_champion("Small", &[0x00]);
+        let champion2 = create_champion("Large", &[0x00, 0x00, 0x00, 0x00]);
+
+        let mut engine = GameEngine::new(GameConfig::default());
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        assert_eq!(engine.run_to_completion().unwrap(), None);
+        assert_eq!(engine.get_stats().tie_break, None);
+
+        let config = GameConfig {
+            tie_breakers: "lives,coverage".parse().unwrap(),
+            ..Default::default()
+        };
+        let mut engine = GameEngine::new(config);
+        engine
+            .load_champions(&[champion1.path(), champion2.path()], None)
+            .unwrap();
+        assert_eq!(engine.run_to_completion().unwrap(), Some(2));
+
+        let tie_break = engine.get_stats().tie_break.unwrap();
+        assert_eq!(tie_break.decided_by, crate::vm::TieBreaker::MemoryCoverage);
+        assert_eq!(tie_break.winner, 2);
+    }
+
     #[test]
     fn test_pause_resume() {
         let mut engine = GameEngine::new(GameConfig::default());
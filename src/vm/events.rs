@@ -0,0 +1,219 @@
+/// Event bus for engine observers
+///
+/// The scheduler and engine describe everything that happens in a battle as
+/// typed [`GameEvent`]s. Observers subscribe to the engine either by
+/// implementing [`GameObserver`] or by taking a channel receiver, so UIs,
+/// loggers and analysis tools can follow a battle without polling memory.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Something that happened during a battle
+///
+/// Serialized as an object tagged with the snake_case event name in `type`,
+/// as [`crate::vm::ndjson`] writes it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// A byte of memory was written by a process
+    MemoryWrite {
+        address: usize,
+        value: u8,
+        champion_id: u8,
+        process_id: u32,
+        /// Opcode of the instruction that wrote the byte
+        opcode: u8,
+        /// Address of the instruction that wrote the byte
+        pc: usize,
+    },
+    /// A process was created (initial process or fork)
+    ProcessSpawned {
+        process_id: u32,
+        parent_id: Option<u32>,
+        champion_id: u8,
+        pc: usize,
+    },
+    /// A process died (invalid instruction or missed death check)
+    ProcessDied {
+        process_id: u32,
+        champion_id: u8,
+        pc: usize,
+    },
+    /// A process executed `live`, reporting its champion alive
+    LiveReported {
+        process_id: u32,
+        champion_id: u8,
+        cycle: u32,
+    },
+    /// A cycle finished executing
+    CycleCompleted { cycle: u32 },
+    /// A champion lost its last process
+    ChampionEliminated { champion_id: u8, cycle: u32 },
+    /// The battle was rewound to an earlier cycle
+    Rewound { cycle: u32 },
+}
+
+impl GameEvent {
+    /// Get a copy of the event that does not reveal champion code
+    ///
+    /// Memory writes keep where, when and by whom the core was written, but
+    /// their value and the writing instruction's opcode are zeroed. Other
+    /// events carry no code and are returned unchanged.
+    pub fn redacted(&self) -> GameEvent {
+        match *self {
+            GameEvent::MemoryWrite {
+                address,
+                champion_id,
+                process_id,
+                pc,
+                ..
+            } => GameEvent::MemoryWrite {
+                address,
+                value: 0,
+                champion_id,
+                process_id,
+                opcode: 0,
+                pc,
+            },
+            ref event => event.clone(),
+        }
+    }
+}
+
+/// Receiver of game events
+pub trait GameObserver: Send {
+    /// Handle a single event
+    ///
+    /// # Arguments
+    /// * `event` - The event that occurred
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+impl GameObserver for Sender<GameEvent> {
+    fn on_event(&mut self, event: &GameEvent) {
+        // A dropped receiver just means nobody is listening anymore
+        let _ = self.send(event.clone());
+    }
+}
+
+/// Dispatches events to every subscribed observer
+#[derive(Default)]
+pub struct EventBus {
+    observers: Vec<Box<dyn GameObserver>>,
+}
+
+impl EventBus {
+    /// Create an event bus with no observers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an observer
+    ///
+    /// # Arguments
+    /// * `observer` - The observer to notify of every event
+    pub fn subscribe(&mut self, observer: Box<dyn GameObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Subscribe through a channel
+    ///
+    /// # Returns
+    /// A receiver that yields every event published from now on
+    pub fn subscribe_channel(&mut self) -> Receiver<GameEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribe(Box::new(sender));
+        receiver
+    }
+
+    /// Check whether anyone is listening
+    pub fn has_observers(&self) -> bool {
+        !self.observers.is_empty()
+    }
+
+    /// Send an event to every observer
+    ///
+    /// # Arguments
+    /// * `event` - The event to publish
+    pub fn publish(&mut self, event: &GameEvent) {
+        for observer in &mut self.observers {
+            observer.on_event(event);
+        }
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("observers", &self.observers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<GameEvent>>>);
+
+    impl GameObserver for Recorder {
+        fn on_event(&mut self, event: &GameEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_publish_reaches_all_observers() {
+        let mut bus = EventBus::new();
+        assert!(!bus.has_observers());
+
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        bus.subscribe(Box::new(Recorder(recorded.clone())));
+        let receiver = bus.subscribe_channel();
+        assert!(bus.has_observers());
+
+        let event = GameEvent::CycleCompleted { cycle: 1 };
+        bus.publish(&event);
+
+        assert_eq!(*recorded.lock().unwrap(), vec![event.clone()]);
+        assert_eq!(receiver.try_recv().unwrap(), event);
+    }
+
+    #[test]
+    fn test_redacted_hides_written_code() {
+        let write = GameEvent::MemoryWrite {
+            address: 12,
+            value: 0x0C,
+            champion_id: 2,
+            process_id: 5,
+            opcode: 0x03,
+            pc: 8,
+        };
+        assert_eq!(
+            write.redacted(),
+            GameEvent::MemoryWrite {
+                address: 12,
+                value: 0,
+                champion_id: 2,
+                process_id: 5,
+                opcode: 0,
+                pc: 8,
+            }
+        );
+
+        let live = GameEvent::LiveReported {
+            process_id: 5,
+            champion_id: 2,
+            cycle: 3,
+        };
+        assert_eq!(live.redacted(), live);
+    }
+
+    #[test]
+    fn test_dropped_receiver_is_ignored() {
+        let mut bus = EventBus::new();
+        drop(bus.subscribe_channel());
+        bus.publish(&GameEvent::CycleCompleted { cycle: 1 });
+    }
+}
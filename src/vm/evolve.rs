@@ -0,0 +1,445 @@
+/// Genetic evolution of warriors
+///
+/// An [`Evolution`] breeds warriors to beat a set of benchmark champions. It
+/// starts from a population of random programs and scores each one by
+/// playing a [`Match`] against every benchmark: the points it earns per
+/// round (see [`WIN_POINTS`] and [`DRAW_POINTS`]) scaled to 100 rounds, as
+/// on a hill. Every generation keeps its best warriors unchanged and fills
+/// the rest of the next with children: two parents picked by tournament
+/// selection are crossed at an instruction boundary, then mutated by
+/// replacing, inserting or deleting instructions and changing operands.
+///
+/// Genomes are lists of instructions built from [`Instruction::operand_types`],
+/// so every warrior assembles, and the best can be written out as Redcode
+/// with [`Genome::source`]. All randomness, including the placement of
+/// every scoring round, comes from the configured seed, so a run replays
+/// exactly.
+use crate::assembler::Assembler;
+use crate::constants::IDX_MOD;
+use crate::error::{CoreWarError, Result};
+use crate::vm::instruction::{CompleteInstruction, Parameter};
+use crate::vm::loader::ChampionLoader;
+use crate::vm::tournament::{DRAW_POINTS, Match, WIN_POINTS};
+use crate::vm::{GameConfig, Instruction, ParameterType, SeededRng};
+use std::fmt;
+
+/// Number of registers operands may name
+const REGISTERS: usize = 16;
+
+/// Warriors drawn at random for each parent, the fittest of which is picked
+const TOURNAMENT_SIZE: usize = 3;
+
+/// Settings of an evolution
+#[derive(Debug, Clone, Copy)]
+pub struct EvolveConfig {
+    /// Warriors in every generation
+    pub population: usize,
+    /// Best warriors copied unchanged into the next generation
+    pub elite: usize,
+    /// Rounds played against each benchmark
+    pub rounds: u32,
+    /// Instructions of a random warrior, at most
+    pub initial_length: usize,
+    /// Instructions a warrior may grow to
+    pub max_length: usize,
+    /// Chance that a child is bred from two parents rather than copied from one
+    pub crossover_rate: f64,
+    /// Chance of each instruction of a child being mutated
+    pub mutation_rate: f64,
+    /// Seed of the evolution and of the first scoring round
+    pub seed: u64,
+    /// Configuration every scoring round runs with
+    pub game: GameConfig,
+}
+
+impl Default for EvolveConfig {
+    fn default() -> Self {
+        Self {
+            population: 32,
+            elite: 2,
+            rounds: 2,
+            initial_length: 8,
+            max_length: 32,
+            crossover_rate: 0.7,
+            mutation_rate: 0.1,
+            seed: crate::vm::rng::DEFAULT_SEED,
+            game: GameConfig {
+                max_cycles: 20_000,
+                ..GameConfig::default()
+            },
+        }
+    }
+}
+
+/// A warrior's code, as instructions
+#[derive(Debug, Clone)]
+pub struct Genome {
+    /// The instructions, in order
+    pub instructions: Vec<CompleteInstruction>,
+}
+
+impl Genome {
+    /// Write the warrior as Redcode
+    ///
+    /// # Arguments
+    /// * `name` - Name for the `.name` directive
+    /// * `comment` - Text for the `.comment` directive
+    pub fn source(&self, name: &str, comment: &str) -> String {
+        let mut source = format!(".name \"{}\"\n.comment \"{}\"\n\n", name, comment);
+        for instruction in &self.instructions {
+            source.push_str(&format!("    {}\n", instruction));
+        }
+        source
+    }
+
+    /// Assemble the warrior into bytecode
+    pub fn assemble(&self) -> Result<Vec<u8>> {
+        let cor = Assembler::new(false).assemble_string(&self.source("Evolved", ""))?;
+        Ok(ChampionLoader::new(false)
+            .load_champion_from_bytes(&cor, 1, None)?
+            .code)
+    }
+}
+
+/// A warrior with its score against the benchmarks
+#[derive(Debug, Clone)]
+pub struct Scored {
+    /// The warrior
+    pub genome: Genome,
+    /// Points per 100 rounds against the benchmarks
+    pub score: f64,
+}
+
+/// A population evolving against benchmarks
+#[derive(Debug)]
+pub struct Evolution {
+    /// Settings
+    config: EvolveConfig,
+    /// Name and code of every benchmark
+    benchmarks: Vec<(String, Vec<u8>)>,
+    /// Source of every random choice
+    rng: SeededRng,
+    /// Generations bred so far, 0 for the random one
+    generation: u32,
+    /// The current generation, best first
+    population: Vec<Scored>,
+}
+
+impl Evolution {
+    /// Create and score a random first generation
+    ///
+    /// # Arguments
+    /// * `config` - Settings of the evolution
+    /// * `benchmarks` - Name and bytecode of every champion to beat
+    ///
+    /// # Returns
+    /// The evolution, or an error if the settings are invalid or there are
+    /// no benchmarks
+    pub fn new(config: EvolveConfig, benchmarks: Vec<(String, Vec<u8>)>) -> Result<Self> {
+        if benchmarks.is_empty() {
+            return Err(CoreWarError::config(
+                "Evolution needs at least one benchmark",
+            ));
+        }
+        if config.population < 2 || config.elite >= config.population {
+            return Err(CoreWarError::config(
+                "The population needs at least 2 warriors, and more than the elite",
+            ));
+        }
+        if config.rounds == 0 || config.initial_length == 0 {
+            return Err(CoreWarError::config(
+                "Warriors need at least one instruction and one round per benchmark",
+            ));
+        }
+        if config.initial_length > config.max_length {
+            return Err(CoreWarError::config(
+                "The initial length cannot exceed the maximum length",
+            ));
+        }
+
+        let mut evolution = Self {
+            config,
+            benchmarks,
+            rng: SeededRng::new(config.seed),
+            generation: 0,
+            population: Vec::new(),
+        };
+        let genomes = (0..config.population)
+            .map(|_| {
+                let length = 1 + evolution.rng.next_below(config.initial_length);
+                Genome {
+                    instructions: (0..length)
+                        .map(|_| random_instruction(&mut evolution.rng))
+                        .collect(),
+                }
+            })
+            .collect();
+        evolution.population = evolution.score_all(genomes)?;
+        Ok(evolution)
+    }
+
+    /// Get the number of generations bred so far
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Get the current generation, best first
+    pub fn population(&self) -> &[Scored] {
+        &self.population
+    }
+
+    /// Get the best warrior so far
+    pub fn best(&self) -> &Scored {
+        &self.population[0]
+    }
+
+    /// Get the mean score of the current generation
+    pub fn mean_score(&self) -> f64 {
+        self.population.iter().map(|s| s.score).sum::<f64>() / self.population.len() as f64
+    }
+
+    /// Breed and score the next generation
+    pub fn advance(&mut self) -> Result<()> {
+        let elite = self.population[..self.config.elite].to_vec();
+        let mut children = Vec::with_capacity(self.config.population - elite.len());
+        while elite.len() + children.len() < self.config.population {
+            let first = self.select();
+            let mut child = if self.chance(self.config.crossover_rate) {
+                let second = self.select();
+                self.crossover(first, second)
+            } else {
+                self.population[first].genome.clone()
+            };
+            self.mutate(&mut child);
+            children.push(child);
+        }
+
+        let mut next = elite;
+        next.extend(self.score_all(children)?);
+        sort(&mut next);
+        self.population = next;
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Score warriors and sort them best first
+    fn score_all(&self, genomes: Vec<Genome>) -> Result<Vec<Scored>> {
+        let mut scored = genomes
+            .into_iter()
+            .map(|genome| {
+                let score = self.score(&genome)?;
+                Ok(Scored { genome, score })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        sort(&mut scored);
+        Ok(scored)
+    }
+
+    /// Play a warrior against every benchmark
+    fn score(&self, genome: &Genome) -> Result<f64> {
+        let code = genome.assemble()?;
+        let (mut points, mut rounds) = (0, 0);
+        for (name, benchmark) in &self.benchmarks {
+            let result = Match::with_code(
+                vec![
+                    ("Evolved".to_string(), code.clone()),
+                    (name.clone(), benchmark.clone()),
+                ],
+                self.config.game,
+            )
+            .best_of(self.config.rounds)
+            .play()?;
+            points += result.wins(0) as u32 * WIN_POINTS + result.draws() as u32 * DRAW_POINTS;
+            rounds += result.rounds.len();
+        }
+        Ok(f64::from(points) * 100.0 / rounds as f64)
+    }
+
+    /// Pick a parent by tournament selection, returning its index
+    fn select(&mut self) -> usize {
+        // The population is sorted, so the lowest index drawn is the fittest
+        (0..TOURNAMENT_SIZE)
+            .map(|_| self.rng.next_below(self.population.len()))
+            .min()
+            .expect("the tournament is not empty")
+    }
+
+    /// Join the start of one parent to the end of another
+    fn crossover(&mut self, first: usize, second: usize) -> Genome {
+        let first = &self.population[first].genome.instructions;
+        let second = &self.population[second].genome.instructions;
+        let head = self.rng.next_below(first.len() + 1);
+        let tail = self.rng.next_below(second.len() + 1);
+        let mut instructions: Vec<CompleteInstruction> = first[..head]
+            .iter()
+            .chain(&second[tail..])
+            .cloned()
+            .collect();
+        if instructions.is_empty() {
+            instructions.push(random_instruction(&mut self.rng));
+        }
+        instructions.truncate(self.config.max_length);
+        Genome { instructions }
+    }
+
+    /// Change a child's instructions at random
+    fn mutate(&mut self, genome: &mut Genome) {
+        let rate = self.config.mutation_rate;
+        for i in 0..genome.instructions.len() {
+            if !self.chance(rate) {
+                continue;
+            }
+            if self.chance(0.5) {
+                genome.instructions[i] = random_instruction(&mut self.rng);
+            } else {
+                let instruction = &mut genome.instructions[i];
+                let operand = self.rng.next_below(instruction.parameters.len());
+                let types = instruction.instruction.operand_types()[operand];
+                instruction.parameters[operand] = random_parameter(&mut self.rng, types);
+            }
+        }
+        if genome.instructions.len() < self.config.max_length && self.chance(rate) {
+            let at = self.rng.next_below(genome.instructions.len() + 1);
+            let instruction = random_instruction(&mut self.rng);
+            genome.instructions.insert(at, instruction);
+        }
+        if genome.instructions.len() > 1 && self.chance(rate) {
+            let at = self.rng.next_below(genome.instructions.len());
+            genome.instructions.remove(at);
+        }
+    }
+
+    /// Draw true with a probability
+    fn chance(&mut self, probability: f64) -> bool {
+        ((self.rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
+
+impl fmt::Display for Evolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let best = self.best();
+        write!(
+            f,
+            "Generation {}: best {:.1} ({} instructions), mean {:.1}",
+            self.generation,
+            best.score,
+            best.genome.instructions.len(),
+            self.mean_score()
+        )
+    }
+}
+
+/// Sort warriors best first, preferring shorter code between equal scores
+fn sort(scored: &mut [Scored]) {
+    scored.sort_by(|a, b| {
+        b.score.total_cmp(&a.score).then(
+            a.genome
+                .instructions
+                .len()
+                .cmp(&b.genome.instructions.len()),
+        )
+    });
+}
+
+/// Draw an instruction with operands of types it accepts
+fn random_instruction(rng: &mut SeededRng) -> CompleteInstruction {
+    let instruction = Instruction::ALL[rng.next_below(Instruction::ALL.len())];
+    let parameters = instruction
+        .operand_types()
+        .iter()
+        .map(|types| random_parameter(rng, types))
+        .collect();
+    CompleteInstruction {
+        instruction,
+        parameters,
+    }
+}
+
+/// Draw an operand of one of the given types
+fn random_parameter(rng: &mut SeededRng, types: &[ParameterType]) -> Parameter {
+    let param_type = types[rng.next_below(types.len())];
+    let offset = rng.next_below(2 * IDX_MOD) as i32 - IDX_MOD as i32;
+    match param_type {
+        ParameterType::Register => Parameter::register(1 + rng.next_below(REGISTERS) as u8),
+        _ => Parameter::new(param_type, offset),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `live %1` then `zjmp %-5`: keeps reporting alive
+    fn liver() -> (String, Vec<u8>) {
+        let code = vec![0x01, 0x00, 0x00, 0x00, 0x01, 0x09, 0xFF, 0xFB];
+        ("Liver".to_string(), code)
+    }
+
+    fn config() -> EvolveConfig {
+        EvolveConfig {
+            population: 8,
+            rounds: 1,
+            game: GameConfig {
+                max_cycles: 2_000,
+                ..GameConfig::default()
+            },
+            ..EvolveConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_random_warriors_assemble() {
+        let mut rng = SeededRng::new(5);
+        for _ in 0..200 {
+            let genome = Genome {
+                instructions: (0..4).map(|_| random_instruction(&mut rng)).collect(),
+            };
+            let code = genome.assemble().unwrap();
+            let size: usize = genome.instructions.iter().map(|i| i.size()).sum();
+            assert_eq!(code.len(), size);
+        }
+    }
+
+    #[test]
+    fn test_evolution_keeps_the_best_and_replays() {
+        let mut evolution = Evolution::new(config(), vec![liver()]).unwrap();
+        let first_best = evolution.best().score;
+        for _ in 0..3 {
+            evolution.advance().unwrap();
+            assert_eq!(evolution.population().len(), 8);
+            assert!(
+                evolution
+                    .population()
+                    .iter()
+                    .all(|s| s.genome.instructions.len() <= 32)
+            );
+        }
+        assert_eq!(evolution.generation(), 3);
+        assert!(evolution.best().score >= first_best);
+        assert!(evolution.to_string().starts_with("Generation 3: best"));
+
+        let mut replay = Evolution::new(config(), vec![liver()]).unwrap();
+        for _ in 0..3 {
+            replay.advance().unwrap();
+        }
+        assert_eq!(
+            replay.best().genome.source("A", ""),
+            evolution.best().genome.source("A", "")
+        );
+    }
+
+    #[test]
+    fn test_invalid_settings_are_refused() {
+        assert!(Evolution::new(config(), Vec::new()).is_err());
+        let elite = EvolveConfig {
+            elite: 8,
+            ..config()
+        };
+        assert!(Evolution::new(elite, vec![liver()]).is_err());
+        let length = EvolveConfig {
+            initial_length: 40,
+            ..config()
+        };
+        assert!(Evolution::new(length, vec![liver()]).is_err());
+    }
+}
@@ -0,0 +1,551 @@
+/// Per-instruction execution trace
+///
+/// `corewar run --trace FILE` writes one line for every instruction a
+/// process executes: the cycle, the process and its champion, the PC, the
+/// instruction decoded before it ran, and what it did. The effects list the
+/// registers and carry it changed, the bytes it wrote, the process it
+/// forked, a reported live, the new PC, or the fault that killed it:
+///
+/// ```text
+/// #  cycle process champion pc      instruction          effects
+///        1       1        1 0x0000  live %1              live mem[0x0001]=FF pc=0x0005
+/// ```
+///
+/// Diffing the traces of two runs shows where they part, which a final
+/// state or an event log only hints at. A [`TraceFilter`] narrows the trace
+/// to some champions or processes, since a battle executes far more
+/// instructions than anyone reads.
+///
+/// With `--trace-format chrome` the same instructions are written as Chrome
+/// trace events instead, for chrome://tracing or the Perfetto UI. Each
+/// champion is a process and each of its processes a thread, and every
+/// instruction is a slice lasting the cycles its process then waits, with
+/// one cycle shown as one microsecond. The timeline shows at a glance which
+/// processes wait on slow instructions and when forks fill the queue.
+use crate::assembler::disassembler::disassemble_memory;
+use crate::error::{CoreWarError, Result};
+use crate::vm::{Champion, GameEvent, Memory, Process};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::str::FromStr;
+use tracing::warn;
+
+/// Layout of a trace file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One aligned line per instruction under a header line
+    #[default]
+    Text,
+    /// A Chrome trace event JSON document
+    Chrome,
+}
+
+impl FromStr for TraceFormat {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(TraceFormat::Text),
+            "chrome" => Ok(TraceFormat::Chrome),
+            _ => Err(CoreWarError::game_state(format!(
+                "Unknown trace format '{}' (expected text or chrome)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Which processes a trace covers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraceFilter {
+    /// Champions whose processes are traced
+    pub champions: Vec<u8>,
+    /// Processes traced whatever their champion
+    pub processes: Vec<u32>,
+}
+
+impl TraceFilter {
+    /// Check whether a process is traced
+    ///
+    /// A filter naming no champions and no processes traces everything;
+    /// otherwise a process is traced if its champion or its own ID is named.
+    pub fn matches(&self, process: &Process) -> bool {
+        (self.champions.is_empty() && self.processes.is_empty())
+            || self.champions.contains(&process.champion_id)
+            || self.processes.contains(&process.id)
+    }
+}
+
+/// A process as it was before executing a traced instruction
+#[derive(Debug, Clone)]
+pub(crate) struct TracedStep {
+    pc: usize,
+    registers: [i32; 16],
+    carry: bool,
+    instruction: String,
+    /// Number of scheduler events raised before the instruction ran
+    first_event: usize,
+}
+
+/// Writes a line for every traced instruction
+pub struct ExecutionTrace {
+    /// Destination of the lines, dropped once writing failed
+    writer: Option<Box<dyn Write + Send>>,
+    filter: TraceFilter,
+    format: TraceFormat,
+    lines: u64,
+    /// Champion names, for naming Chrome trace tracks
+    champions: BTreeMap<u8, String>,
+    /// Every traced champion and process, for naming Chrome trace tracks
+    tracks: BTreeSet<(u8, u32)>,
+}
+
+impl ExecutionTrace {
+    /// Start a trace and write its header
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the trace
+    /// * `filter` - Processes to trace
+    /// * `format` - Layout of the trace
+    ///
+    /// # Returns
+    /// The trace, or an error if the header could not be written
+    pub fn new(
+        mut writer: Box<dyn Write + Send>,
+        filter: TraceFilter,
+        format: TraceFormat,
+    ) -> Result<Self> {
+        match format {
+            TraceFormat::Text => writeln!(
+                writer,
+                "# {:>6} {:>7} {:>8} {:<7} {:<20} effects",
+                "cycle", "process", "champion", "pc", "instruction"
+            )?,
+            TraceFormat::Chrome => write!(writer, "{{\"traceEvents\":[")?,
+        }
+        Ok(Self {
+            writer: Some(writer),
+            filter,
+            format,
+            lines: 0,
+            champions: BTreeMap::new(),
+            tracks: BTreeSet::new(),
+        })
+    }
+
+    /// Name the tracks of a Chrome trace after the champions
+    ///
+    /// # Arguments
+    /// * `champions` - The loaded champions
+    pub fn name_champions(&mut self, champions: &[Champion]) {
+        self.champions = champions
+            .iter()
+            .map(|champion| (champion.id, champion.display_name().to_string()))
+            .collect();
+    }
+
+    /// Get the layout of the trace
+    pub fn format(&self) -> TraceFormat {
+        self.format
+    }
+
+    /// Get the processes the trace covers
+    pub fn filter(&self) -> &TraceFilter {
+        &self.filter
+    }
+
+    /// Get the number of instructions traced so far
+    pub fn lines(&self) -> u64 {
+        self.lines
+    }
+
+    /// Note a process's state before it executes an instruction
+    ///
+    /// # Arguments
+    /// * `process` - The process about to execute
+    /// * `memory` - Memory holding the instruction
+    /// * `events` - Number of events the scheduler holds so far
+    ///
+    /// # Returns
+    /// The step to pass to [`Self::record`], or None if the process is not
+    /// traced or writing already failed
+    pub(crate) fn begin(
+        &self,
+        process: &Process,
+        memory: &Memory,
+        events: usize,
+    ) -> Option<TracedStep> {
+        if self.writer.is_none() || !self.filter.matches(process) {
+            return None;
+        }
+        let instruction = disassemble_memory(memory, process.pc, 0, 0)
+            .into_iter()
+            .next()
+            .map(|line| line.text)
+            .unwrap_or_default();
+        Some(TracedStep {
+            pc: process.pc,
+            registers: process.registers,
+            carry: process.carry,
+            instruction,
+            first_event: events,
+        })
+    }
+
+    /// Write the line of an executed instruction
+    ///
+    /// If writing fails, the trace is closed and the battle carries on.
+    ///
+    /// # Arguments
+    /// * `cycle` - Cycle the instruction ran in
+    /// * `step` - The process before the instruction, from [`Self::begin`]
+    /// * `process` - The process after the instruction
+    /// * `events` - Every event the scheduler holds, including those the
+    ///   instruction raised
+    /// * `fault` - The error that kills the process, if the instruction failed
+    pub(crate) fn record(
+        &mut self,
+        cycle: u32,
+        step: TracedStep,
+        process: &Process,
+        events: &[GameEvent],
+        fault: Option<&CoreWarError>,
+    ) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        let effects = effects(
+            &step,
+            process,
+            &events[step.first_event.min(events.len())..],
+            fault,
+        );
+        let written = match self.format {
+            TraceFormat::Text => writeln!(
+                writer,
+                "{:>8} {:>7} {:>8} 0x{:04X}  {:<20} {}",
+                cycle, process.id, process.champion_id, step.pc, step.instruction, effects
+            ),
+            TraceFormat::Chrome => {
+                self.tracks.insert((process.champion_id, process.id));
+                let name = match step.instruction.split_whitespace().next() {
+                    Some(";") | None => "data",
+                    Some(mnemonic) => mnemonic,
+                };
+                let event = json!({
+                    "name": name,
+                    "cat": "instruction",
+                    "ph": "X",
+                    "ts": cycle,
+                    "dur": process.wait_cycles.max(1),
+                    "pid": process.champion_id,
+                    "tid": process.id,
+                    "args": {
+                        "pc": format!("0x{:04X}", step.pc),
+                        "instruction": step.instruction,
+                        "effects": effects,
+                    },
+                });
+                let separator = if self.lines == 0 { "" } else { "," };
+                write!(writer, "{}\n{}", separator, event)
+            }
+        };
+        match written {
+            Ok(()) => self.lines += 1,
+            Err(e) => {
+                warn!("Execution trace closed: {}", e);
+                self.writer = None;
+            }
+        }
+    }
+
+    /// Flush the trace and stop writing to it
+    ///
+    /// # Returns
+    /// The number of instructions traced, or an error if the lines could not
+    /// be written
+    pub fn finish(mut self) -> Result<u64> {
+        match self.writer.take() {
+            Some(mut writer) => {
+                if self.format == TraceFormat::Chrome {
+                    self.write_track_names(&mut writer)?;
+                }
+                writer.flush()?
+            }
+            None if self.lines > 0 => {
+                return Err(CoreWarError::game_state(format!(
+                    "Execution trace closed after {} lines",
+                    self.lines
+                )));
+            }
+            None => {}
+        }
+        Ok(self.lines)
+    }
+}
+
+impl ExecutionTrace {
+    /// Close a Chrome trace with the names of its tracks
+    fn write_track_names(&self, writer: &mut Box<dyn Write + Send>) -> Result<()> {
+        let champions: BTreeSet<u8> = self.tracks.iter().map(|&(champion, _)| champion).collect();
+        let names = champions
+            .into_iter()
+            .map(|champion| {
+                let name = self
+                    .champions
+                    .get(&champion)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Champion {}", champion));
+                json!({
+                    "name": "process_name",
+                    "ph": "M",
+                    "pid": champion,
+                    "args": { "name": name },
+                })
+            })
+            .chain(self.tracks.iter().map(|&(champion, process)| {
+                json!({
+                    "name": "thread_name",
+                    "ph": "M",
+                    "pid": champion,
+                    "tid": process,
+                    "args": { "name": format!("Process {}", process) },
+                })
+            }));
+        let mut separator = if self.lines == 0 { "" } else { "," };
+        for name in names {
+            write!(writer, "{}\n{}", separator, name)?;
+            separator = ",";
+        }
+        writeln!(writer, "\n]}}")?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ExecutionTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecutionTrace")
+            .field("filter", &self.filter)
+            .field("format", &self.format)
+            .field("lines", &self.lines)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Describe what an instruction did
+fn effects(
+    step: &TracedStep,
+    process: &Process,
+    events: &[GameEvent],
+    fault: Option<&CoreWarError>,
+) -> String {
+    let mut text = String::new();
+    for (i, (before, after)) in step.registers.iter().zip(&process.registers).enumerate() {
+        if before != after {
+            let _ = write!(text, "r{}={} ", i + 1, after);
+        }
+    }
+    if step.carry != process.carry {
+        let _ = write!(text, "carry={} ", u8::from(process.carry));
+    }
+
+    // Consecutive bytes written by this process read as one run
+    let mut run: Option<(usize, Vec<u8>)> = None;
+    for event in events {
+        match *event {
+            GameEvent::MemoryWrite {
+                address,
+                value,
+                process_id,
+                ..
+            } if process_id == process.id => match &mut run {
+                Some((start, bytes)) if *start + bytes.len() == address => bytes.push(value),
+                _ => {
+                    write_run(&mut text, run.take());
+                    run = Some((address, vec![value]));
+                }
+            },
+            GameEvent::ProcessSpawned {
+                process_id,
+                parent_id: Some(parent),
+                pc,
+                ..
+            } if parent == process.id => {
+                let _ = write!(text, "fork={}@0x{:04X} ", process_id, pc);
+            }
+            GameEvent::LiveReported { process_id, .. } if process_id == process.id => {
+                text.push_str("live ");
+            }
+            _ => {}
+        }
+    }
+    write_run(&mut text, run);
+
+    match fault {
+        Some(error) => {
+            let _ = write!(text, "fault: {}", error);
+        }
+        None => {
+            let _ = write!(text, "pc=0x{:04X}", process.pc);
+        }
+    }
+    text
+}
+
+/// Describe a run of written bytes
+fn write_run(text: &mut String, run: Option<(usize, Vec<u8>)>) {
+    if let Some((start, bytes)) = run {
+        let _ = write!(text, "mem[0x{:04X}]=", start);
+        for byte in bytes {
+            let _ = write!(text, "{:02X}", byte);
+        }
+        text.push(' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{ChampionColor, GameEngine};
+    use std::sync::{Arc, Mutex};
+
+    /// A writer whose bytes the test can read back
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_filter_selects_champions_and_processes() {
+        let process = Process::new(7, 2, 0, ChampionColor::Blue);
+        assert!(TraceFilter::default().matches(&process));
+        let champion = TraceFilter {
+            champions: vec![2],
+            processes: vec![],
+        };
+        assert!(champion.matches(&process));
+        let other = TraceFilter {
+            champions: vec![1],
+            processes: vec![3],
+        };
+        assert!(!other.matches(&process));
+        let own = TraceFilter {
+            champions: vec![1],
+            processes: vec![7],
+        };
+        assert!(own.matches(&process));
+    }
+
+    #[test]
+    fn test_effects_list_registers_writes_and_pc() {
+        let mut process = Process::new(1, 1, 0, ChampionColor::Red);
+        let step = TracedStep {
+            pc: 0,
+            registers: process.registers,
+            carry: false,
+            instruction: "st r1, 5".to_string(),
+            first_event: 0,
+        };
+        process.registers[2] = -4;
+        process.carry = true;
+        process.pc = 5;
+        let write = |address, value| GameEvent::MemoryWrite {
+            address,
+            value,
+            champion_id: 1,
+            process_id: 1,
+            opcode: 0x03,
+            pc: 0,
+        };
+        let events = [write(8, 0x12), write(9, 0x34), write(20, 0xFF)];
+        assert_eq!(
+            effects(&step, &process, &events, None),
+            "r3=-4 carry=1 mem[0x0008]=1234 mem[0x0014]=FF pc=0x0005"
+        );
+        let fault = CoreWarError::InvalidOpcode { opcode: 0 };
+        assert!(effects(&step, &process, &[], Some(&fault)).starts_with("r3=-4 carry=1 fault: "));
+    }
+
+    #[test]
+    fn test_battle_trace_has_a_line_per_traced_instruction() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("first", vec![0x01, 0, 0, 0, 1, 0x01, 0, 0, 0, 1])
+            .champion_bytes("second", vec![0x01, 0, 0, 0, 2, 0x01, 0, 0, 0, 2])
+            .build()
+            .unwrap();
+        let output = Shared::default();
+        let filter = TraceFilter {
+            champions: vec![2],
+            processes: vec![],
+        };
+        engine.trace_execution(
+            ExecutionTrace::new(Box::new(output.clone()), filter, TraceFormat::Text).unwrap(),
+        );
+        engine.run_cycles(30).unwrap();
+        let traced = engine.finish_execution_trace().unwrap();
+
+        let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with('#'));
+        assert!(traced > 0);
+        assert_eq!(lines.len() as u64, traced + 1);
+        for line in &lines[1..] {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(columns[2], "2", "{}", line);
+        }
+        assert!(lines[1].contains("live"));
+    }
+
+    #[test]
+    fn test_chrome_trace_has_a_track_per_process() {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("first", vec![0x01, 0, 0, 0, 1, 0x0C, 0, 0, 0, 0])
+            .champion_bytes("second", vec![0x01, 0, 0, 0, 2, 0x01, 0, 0, 0, 2])
+            .build()
+            .unwrap();
+        let output = Shared::default();
+        let trace = ExecutionTrace::new(
+            Box::new(output.clone()),
+            TraceFilter::default(),
+            TraceFormat::Chrome,
+        )
+        .unwrap();
+        engine.trace_execution(trace);
+        engine.run_cycles(30).unwrap();
+        let traced = engine.finish_execution_trace().unwrap();
+
+        let document: serde_json::Value =
+            serde_json::from_slice(&output.0.lock().unwrap()).unwrap();
+        let events = document["traceEvents"].as_array().unwrap();
+        let slices: Vec<_> = events.iter().filter(|e| e["ph"] == "X").collect();
+        assert_eq!(slices.len() as u64, traced);
+        assert!(slices[0]["name"].is_string());
+        assert!(
+            slices[0]["args"]["effects"]
+                .as_str()
+                .unwrap()
+                .starts_with("live")
+        );
+        assert_eq!(slices[0]["dur"], 10);
+        assert!(
+            events
+                .iter()
+                .any(|e| e["ph"] == "M" && e["args"]["name"] == "first")
+        );
+        for slice in &slices {
+            assert!(events.iter().any(|e| e["name"] == "thread_name"
+                && e["pid"] == slice["pid"]
+                && e["tid"] == slice["tid"]));
+        }
+    }
+}
@@ -0,0 +1,318 @@
+/// Execution heat export for flamegraph-style visualization
+///
+/// A [`HeatMap`] counts how many times the instruction at each offset of a
+/// champion's code was executed, built from the coverage tracker. Heat maps
+/// from several battles merge by champion name and offset, so a whole match
+/// set can be profiled at once, and export as CSV or as folded stacks that
+/// flamegraph tools read directly.
+use crate::error::{CoreWarError, Result};
+use crate::vm::{Champion, ChampionCoverage, Instruction};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Header line of the CSV format
+const CSV_HEADER: &str = "champion,offset,instruction,count";
+
+/// File format of a heat export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeatFormat {
+    /// `champion;instruction@0xOFFSET count` lines, as read by flamegraph tools
+    #[default]
+    Folded,
+    /// `champion,offset,instruction,count` rows with a header
+    Csv,
+}
+
+impl FromStr for HeatFormat {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "folded" => Ok(HeatFormat::Folded),
+            "csv" => Ok(HeatFormat::Csv),
+            _ => Err(CoreWarError::game_state(format!(
+                "Unknown heat format '{}' (expected folded or csv)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for HeatFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeatFormat::Folded => write!(f, "folded"),
+            HeatFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// Executions of the instruction at one offset of a champion's code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeatEntry {
+    /// Champion name, with separators used by the export formats replaced
+    pub champion: String,
+    /// Offset of the instruction in the champion's code
+    pub offset: usize,
+    /// Instruction name, or the opcode byte for data
+    pub instruction: String,
+    /// Number of times the instruction was executed
+    pub count: u64,
+}
+
+/// Execution counts by champion and code offset
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeatMap {
+    entries: BTreeMap<(String, usize), HeatEntry>,
+}
+
+impl HeatMap {
+    /// Build a heat map from a battle's coverage
+    ///
+    /// Offsets that never started an instruction are left out.
+    ///
+    /// # Arguments
+    /// * `coverage` - Coverage report from [`crate::GameEngine::coverage`]
+    /// * `champions` - The battle's champions, for instruction names
+    ///
+    /// # Returns
+    /// The heat map of the battle
+    pub fn from_coverage(coverage: &[ChampionCoverage], champions: &[Champion]) -> Self {
+        let mut heat = Self::default();
+        for champion_coverage in coverage {
+            let code = champions
+                .iter()
+                .find(|c| c.id == champion_coverage.champion_id)
+                .map(|c| c.code.as_slice())
+                .unwrap_or_default();
+
+            for (offset, &count) in champion_coverage.executions.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+                heat.add(HeatEntry {
+                    champion: sanitize(&champion_coverage.name),
+                    offset,
+                    instruction: code.get(offset).map(|&b| label(b)).unwrap_or_default(),
+                    count,
+                });
+            }
+        }
+        heat
+    }
+
+    /// Add another heat map's counts to this one
+    ///
+    /// # Arguments
+    /// * `other` - Heat map whose counts are added
+    pub fn merge(&mut self, other: &HeatMap) {
+        for entry in other.entries.values() {
+            self.add(entry.clone());
+        }
+    }
+
+    /// Get every entry, ordered by champion and offset
+    pub fn entries(&self) -> impl Iterator<Item = &HeatEntry> {
+        self.entries.values()
+    }
+
+    /// Total number of executions counted
+    pub fn total(&self) -> u64 {
+        self.entries.values().map(|entry| entry.count).sum()
+    }
+
+    /// Render the heat map in a format
+    ///
+    /// # Arguments
+    /// * `format` - Export format
+    ///
+    /// # Returns
+    /// The exported text
+    pub fn render(&self, format: HeatFormat) -> String {
+        let mut out = String::new();
+        if format == HeatFormat::Csv {
+            out.push_str(CSV_HEADER);
+            out.push('\n');
+        }
+        for entry in self.entries.values() {
+            let line = match format {
+                HeatFormat::Folded => format!(
+                    "{};{}@0x{:04X} {}",
+                    entry.champion, entry.instruction, entry.offset, entry.count
+                ),
+                HeatFormat::Csv => format!(
+                    "{},{},{},{}",
+                    entry.champion, entry.offset, entry.instruction, entry.count
+                ),
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Parse a heat map previously rendered in a format
+    ///
+    /// # Arguments
+    /// * `text` - Text produced by [`HeatMap::render`]
+    /// * `format` - Format the text is in
+    ///
+    /// # Returns
+    /// The heat map, or an error naming the first malformed line
+    pub fn parse(text: &str, format: HeatFormat) -> Result<Self> {
+        let mut heat = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || (format == HeatFormat::Csv && line == CSV_HEADER) {
+                continue;
+            }
+            let entry = match format {
+                HeatFormat::Folded => parse_folded(line),
+                HeatFormat::Csv => parse_csv(line),
+            }
+            .ok_or_else(|| {
+                CoreWarError::game_state(format!(
+                    "Invalid {} heat line {}: {}",
+                    format,
+                    number + 1,
+                    line
+                ))
+            })?;
+            heat.add(entry);
+        }
+        Ok(heat)
+    }
+
+    /// Read a heat map from a file
+    ///
+    /// # Arguments
+    /// * `path` - File written by [`HeatMap::write`]
+    /// * `format` - Format the file is in
+    pub fn read<P: AsRef<Path>>(path: P, format: HeatFormat) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?, format)
+    }
+
+    /// Write the heat map to a file
+    ///
+    /// # Arguments
+    /// * `path` - Destination file
+    /// * `format` - Export format
+    pub fn write<P: AsRef<Path>>(&self, path: P, format: HeatFormat) -> Result<()> {
+        fs::write(path, self.render(format))?;
+        Ok(())
+    }
+
+    /// Add an entry's count to the entry at the same champion and offset
+    fn add(&mut self, entry: HeatEntry) {
+        self.entries
+            .entry((entry.champion.clone(), entry.offset))
+            .and_modify(|existing| existing.count += entry.count)
+            .or_insert(entry);
+    }
+}
+
+/// Name the instruction an opcode byte starts, or the byte itself for data
+fn label(opcode: u8) -> String {
+    match Instruction::from_opcode(opcode) {
+        Ok(instruction) => instruction.name().to_string(),
+        Err(_) => format!("0x{:02X}", opcode),
+    }
+}
+
+/// Replace characters that separate fields in either export format
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            ';' | ',' | '@' | '\n' | '\r' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Parse a `champion;instruction@0xOFFSET count` line
+fn parse_folded(line: &str) -> Option<HeatEntry> {
+    let (stack, count) = line.rsplit_once(' ')?;
+    let (champion, frame) = stack.split_once(';')?;
+    let (instruction, offset) = frame.rsplit_once('@')?;
+    Some(HeatEntry {
+        champion: champion.to_string(),
+        offset: usize::from_str_radix(offset.strip_prefix("0x")?, 16).ok()?,
+        instruction: instruction.to_string(),
+        count: count.parse().ok()?,
+    })
+}
+
+/// Parse a `champion,offset,instruction,count` row
+fn parse_csv(line: &str) -> Option<HeatEntry> {
+    let mut fields = line.split(',');
+    let entry = HeatEntry {
+        champion: fields.next()?.to_string(),
+        offset: fields.next()?.parse().ok()?,
+        instruction: fields.next()?.to_string(),
+        count: fields.next()?.parse().ok()?,
+    };
+    fields.next().is_none().then_some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn battle_heat(name: &str, executions: Vec<u64>) -> HeatMap {
+        let code = vec![0x01, 0x00, 0x00, 0x00, 0x01, 0x09, 0xFF, 0xFB, 0xAA];
+        let champion = Champion::new(1, name.to_string(), String::new(), code, 0);
+        let coverage = ChampionCoverage {
+            champion_id: 1,
+            name: name.to_string(),
+            executed: vec![true; executions.len()],
+            executions,
+        };
+        HeatMap::from_coverage(&[coverage], &[champion])
+    }
+
+    #[test]
+    fn test_heat_from_coverage_names_instructions() {
+        let heat = battle_heat("Loop;er", vec![7, 0, 0, 0, 0, 6, 0, 0, 1]);
+
+        assert_eq!(heat.total(), 14);
+        assert_eq!(
+            heat.render(HeatFormat::Folded),
+            "Loop_er;live@0x0000 7\nLoop_er;zjmp@0x0005 6\nLoop_er;0xAA@0x0008 1\n"
+        );
+        assert_eq!(
+            heat.render(HeatFormat::Csv),
+            "champion,offset,instruction,count\n\
+             Loop_er,0,live,7\nLoop_er,5,zjmp,6\nLoop_er,8,0xAA,1\n"
+        );
+    }
+
+    #[test]
+    fn test_heat_merges_and_round_trips() {
+        let mut heat = battle_heat("Looper", vec![7, 0, 0, 0, 0, 6, 0, 0, 0]);
+        heat.merge(&battle_heat("Looper", vec![3, 0, 0, 0, 0, 0, 0, 0, 2]));
+        heat.merge(&battle_heat("Other", vec![1, 0, 0, 0, 0, 0, 0, 0, 0]));
+
+        let counts: Vec<(&str, usize, u64)> = heat
+            .entries()
+            .map(|e| (e.champion.as_str(), e.offset, e.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("Looper", 0, 10),
+                ("Looper", 5, 6),
+                ("Looper", 8, 2),
+                ("Other", 0, 1)
+            ]
+        );
+
+        for format in [HeatFormat::Folded, HeatFormat::Csv] {
+            assert_eq!(HeatMap::parse(&heat.render(format), format).unwrap(), heat);
+        }
+        assert!(HeatMap::parse("Looper;live 3\n", HeatFormat::Folded).is_err());
+        assert!(HeatMap::parse("Looper,0,live\n", HeatFormat::Csv).is_err());
+    }
+}
@@ -0,0 +1,681 @@
+/// Persistent king-of-the-hill competitions
+///
+/// A hill keeps a fixed number of champions. A submitted challenger plays a
+/// [`Match`] against every current member; then everyone is ranked by score,
+/// the points earned per round against the other members (see
+/// [`WIN_POINTS`] and [`DRAW_POINTS`]) scaled to 100 rounds. If the hill
+/// is over its size, the last-ranked champion is pushed off, which may be
+/// the challenger itself. Every match also updates the players' Elo ratings,
+/// which carry the champions' history across submissions.
+///
+/// A match can be replayed later with [`Hill::rematch`], which plays the
+/// pair that went longest without one on a fresh seed and updates the
+/// ratings again, and a member can be withdrawn with [`Hill::retire`];
+/// [`crate::vm::ladder`] does both on a schedule.
+///
+/// The hill, including every member's code and the results between current
+/// members, is stored as one JSON file, so it survives the submitted .cor
+/// files being moved or rebuilt. [`HillReport`] and [`SubmissionReport`]
+/// are the standings and challenge outcomes without the code, as
+/// [`crate::vm::hillnet`] sends them to remote submitters.
+use crate::error::{CoreWarError, Result};
+use crate::vm::GameConfig;
+use crate::vm::bundle::sha256_hex;
+use crate::vm::tournament::{DRAW_POINTS, Match, MatchResult, WIN_POINTS};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Hill file format version, bumped whenever the layout changes
+pub const HILL_VERSION: u32 = 1;
+
+/// Number of champions a hill keeps unless configured otherwise
+pub const DEFAULT_HILL_SIZE: usize = 10;
+
+/// Rounds per match unless configured otherwise
+pub const DEFAULT_HILL_ROUNDS: u32 = 4;
+
+/// Elo rating of a newly submitted champion
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// Largest rating change a single match can cause
+pub const RATING_K: f64 = 32.0;
+
+/// A king-of-the-hill and everything needed to challenge it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hill {
+    /// Hill file format version
+    pub version: u32,
+    /// Number of champions kept
+    pub size: usize,
+    /// Rounds per match
+    pub rounds: u32,
+    /// Configuration every match runs with
+    pub config: GameConfig,
+    /// Challengers submitted so far, accepted or not
+    pub submissions: u32,
+    /// Champions on the hill, in the order they entered
+    pub members: Vec<HillMember>,
+    /// Results of every match between current members, least recently played first
+    pub results: Vec<PairResult>,
+    /// Rematches played so far, which picks each rematch's seed
+    #[serde(default)]
+    pub rematches: u64,
+    /// SHA-256 of every champion pushed off the hill
+    #[serde(default)]
+    pub pushed_off: Vec<String>,
+}
+
+/// A champion on the hill
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HillMember {
+    /// Champion name
+    pub name: String,
+    /// Lowercase hex SHA-256 of the code, identifying the champion
+    pub sha256: String,
+    /// Champion bytecode
+    pub code: Vec<u8>,
+    /// Elo rating
+    pub rating: f64,
+    /// Challengers that entered the hill since this champion did
+    pub age: u32,
+}
+
+/// Round results of the match between two members
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairResult {
+    /// SHA-256 of the challenger of the match
+    pub first: String,
+    /// SHA-256 of the member it challenged
+    pub second: String,
+    /// Rounds the challenger won
+    pub first_wins: usize,
+    /// Rounds the member won
+    pub second_wins: usize,
+    /// Rounds that ended in a draw
+    pub draws: usize,
+}
+
+/// A member's line in the hill's standings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HillStanding {
+    /// Champion name
+    pub name: String,
+    /// SHA-256 of the code
+    pub sha256: String,
+    /// Points per 100 rounds against the other members
+    pub score: f64,
+    /// Rounds won against the other members
+    pub wins: usize,
+    /// Rounds lost against the other members
+    pub losses: usize,
+    /// Rounds drawn against the other members
+    pub draws: usize,
+    /// Elo rating
+    pub rating: f64,
+    /// Challengers that entered since the champion did
+    pub age: u32,
+}
+
+/// Outcome of challenging the hill
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submission {
+    /// Name of the challenger
+    pub name: String,
+    /// Each member challenged, with the match result; the challenger is entrant 0
+    pub matches: Vec<(String, MatchResult)>,
+    /// The challenger's rank from 1, or `None` if it did not make the hill
+    pub rank: Option<usize>,
+    /// Name of the champion pushed off the hill, if any
+    pub pushed_off: Option<String>,
+}
+
+/// Outcome of replaying the match between two members
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rematch {
+    /// Name of the member that challenged the other in the first match
+    pub challenger: String,
+    /// Result of the replay, from the challenger's side
+    pub report: MatchReport,
+}
+
+/// The hill's standings and settings, without the members' code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HillReport {
+    /// Number of champions kept
+    pub size: usize,
+    /// Rounds per match
+    pub rounds: u32,
+    /// Challengers submitted so far, accepted or not
+    pub submissions: u32,
+    /// Members, best first
+    pub standings: Vec<HillStanding>,
+}
+
+/// Result of one match of a challenge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchReport {
+    /// Name of the member challenged
+    pub opponent: String,
+    /// Rounds the challenger won
+    pub wins: usize,
+    /// Rounds the member won
+    pub losses: usize,
+    /// Rounds that ended in a draw
+    pub draws: usize,
+}
+
+/// Outcome of challenging the hill, as plain data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmissionReport {
+    /// Name of the challenger
+    pub name: String,
+    /// Each match the challenger played
+    pub matches: Vec<MatchReport>,
+    /// The challenger's rank from 1, or `None` if it did not make the hill
+    pub rank: Option<usize>,
+    /// Name of the champion pushed off the hill, if any
+    pub pushed_off: Option<String>,
+}
+
+impl Hill {
+    /// Create an empty hill
+    ///
+    /// # Arguments
+    /// * `size` - Number of champions kept
+    /// * `rounds` - Rounds per match
+    /// * `config` - Configuration every match runs with
+    ///
+    /// # Returns
+    /// The hill, or an error if the size or rounds are 0 or the rules are invalid
+    pub fn new(size: usize, rounds: u32, config: GameConfig) -> Result<Self> {
+        if size == 0 || rounds == 0 {
+            return Err(CoreWarError::game_state(
+                "A hill needs a size and rounds of at least 1",
+            ));
+        }
+        config.rules.validate()?;
+        Ok(Self {
+            version: HILL_VERSION,
+            size,
+            rounds,
+            config,
+            submissions: 0,
+            members: Vec::new(),
+            results: Vec::new(),
+            rematches: 0,
+            pushed_off: Vec::new(),
+        })
+    }
+
+    /// Read a hill from a file
+    ///
+    /// # Arguments
+    /// * `path` - File written by [`Hill::write`]
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let hill: Self = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| CoreWarError::game_state(format!("Invalid hill file: {}", e)))?;
+        if hill.version != HILL_VERSION {
+            return Err(CoreWarError::game_state(format!(
+                "Unsupported hill version {} (expected {})",
+                hill.version, HILL_VERSION
+            )));
+        }
+        Ok(hill)
+    }
+
+    /// Write the hill to a file
+    ///
+    /// # Arguments
+    /// * `path` - Destination file
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| CoreWarError::game_state(e.to_string()))?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Challenge the hill with a champion
+    ///
+    /// # Arguments
+    /// * `name` - Champion name
+    /// * `code` - Champion bytecode
+    ///
+    /// # Returns
+    /// How the challenge went, or an error if the champion has no code, is
+    /// already on the hill or a match cannot be played
+    pub fn submit(&mut self, name: &str, code: &[u8]) -> Result<Submission> {
+        if code.is_empty() {
+            return Err(CoreWarError::champion(format!(
+                "Champion {} has no code",
+                name
+            )));
+        }
+        let sha256 = sha256_hex(code);
+        if let Some(member) = self.members.iter().find(|m| m.sha256 == sha256) {
+            return Err(CoreWarError::game_state(format!(
+                "The same code is already on the hill as '{}'",
+                member.name
+            )));
+        }
+
+        let mut challenger = HillMember {
+            name: name.to_string(),
+            sha256,
+            code: code.to_vec(),
+            rating: INITIAL_RATING,
+            age: 0,
+        };
+        let mut matches = Vec::new();
+        for member in &mut self.members {
+            let result = Match::with_code(
+                vec![
+                    (challenger.name.clone(), challenger.code.clone()),
+                    (member.name.clone(), member.code.clone()),
+                ],
+                self.config,
+            )
+            .best_of(self.rounds)
+            .play()?;
+
+            let pair = PairResult {
+                first: challenger.sha256.clone(),
+                second: member.sha256.clone(),
+                first_wins: result.wins(0),
+                second_wins: result.wins(1),
+                draws: result.draws(),
+            };
+            let change = rating_change(challenger.rating, member.rating, &pair);
+            challenger.rating += change;
+            member.rating -= change;
+            self.results.push(pair);
+            matches.push((member.name.clone(), result));
+        }
+
+        self.submissions += 1;
+        let challenger_sha = challenger.sha256.clone();
+        self.members.push(challenger);
+
+        let mut pushed_off = None;
+        if self.members.len() > self.size {
+            let last = self.standings().pop().expect("the hill is not empty");
+            self.members.retain(|m| m.sha256 != last.sha256);
+            self.results
+                .retain(|r| r.first != last.sha256 && r.second != last.sha256);
+            self.pushed_off.push(last.sha256);
+            pushed_off = Some(last.name);
+        }
+
+        let rank = self
+            .standings()
+            .iter()
+            .position(|s| s.sha256 == challenger_sha)
+            .map(|position| position + 1);
+        if rank.is_some() {
+            for member in &mut self.members {
+                if member.sha256 != challenger_sha {
+                    member.age += 1;
+                }
+            }
+        }
+
+        Ok(Submission {
+            name: name.to_string(),
+            matches,
+            rank,
+            pushed_off,
+        })
+    }
+
+    /// Replay the match between the two members that went longest without one
+    ///
+    /// The replay runs on a seed of its own, so a rematch is not a copy of
+    /// the last match. Its result replaces the last one, and the ratings
+    /// change as if it were a new challenge.
+    ///
+    /// # Returns
+    /// The rematch, `None` if the hill has fewer than two members, or an
+    /// error if the match cannot be played
+    pub fn rematch(&mut self) -> Result<Option<Rematch>> {
+        let Some(oldest) = self.results.first() else {
+            return Ok(None);
+        };
+        let position = |sha256: &str| self.members.iter().position(|m| m.sha256 == sha256);
+        let (Some(first), Some(second)) = (position(&oldest.first), position(&oldest.second))
+        else {
+            return Err(CoreWarError::game_state(
+                "The hill holds a result for a champion that is not on it",
+            ));
+        };
+
+        self.rematches += 1;
+        let config = GameConfig {
+            seed: self.config.seed.wrapping_add(self.rematches),
+            ..self.config
+        };
+        let (challenger, member) = (&self.members[first], &self.members[second]);
+        let result = Match::with_code(
+            vec![
+                (challenger.name.clone(), challenger.code.clone()),
+                (member.name.clone(), member.code.clone()),
+            ],
+            config,
+        )
+        .best_of(self.rounds)
+        .play()?;
+
+        let mut pair = self.results.remove(0);
+        pair.first_wins = result.wins(0);
+        pair.second_wins = result.wins(1);
+        pair.draws = result.draws();
+        let change = rating_change(challenger.rating, member.rating, &pair);
+        let rematch = Rematch {
+            challenger: challenger.name.clone(),
+            report: MatchReport {
+                opponent: member.name.clone(),
+                wins: pair.first_wins,
+                losses: pair.second_wins,
+                draws: pair.draws,
+            },
+        };
+        self.members[first].rating += change;
+        self.members[second].rating -= change;
+        self.results.push(pair);
+        Ok(Some(rematch))
+    }
+
+    /// Take a member off the hill, with its results
+    ///
+    /// The ratings it won or lost against the others stay as they are.
+    ///
+    /// # Arguments
+    /// * `sha256` - SHA-256 of the member's code
+    ///
+    /// # Returns
+    /// The member, or `None` if no member has that code
+    pub fn retire(&mut self, sha256: &str) -> Option<HillMember> {
+        let position = self.members.iter().position(|m| m.sha256 == sha256)?;
+        self.results
+            .retain(|r| r.first != sha256 && r.second != sha256);
+        Some(self.members.remove(position))
+    }
+
+    /// Rank the members by score, then rating
+    pub fn standings(&self) -> Vec<HillStanding> {
+        let mut standings: Vec<HillStanding> = self
+            .members
+            .iter()
+            .map(|member| {
+                let (mut wins, mut losses, mut draws) = (0, 0, 0);
+                for result in &self.results {
+                    if result.first == member.sha256 {
+                        wins += result.first_wins;
+                        losses += result.second_wins;
+                    } else if result.second == member.sha256 {
+                        wins += result.second_wins;
+                        losses += result.first_wins;
+                    } else {
+                        continue;
+                    }
+                    draws += result.draws;
+                }
+                let rounds = wins + losses + draws;
+                let points = wins as u32 * WIN_POINTS + draws as u32 * DRAW_POINTS;
+                HillStanding {
+                    name: member.name.clone(),
+                    sha256: member.sha256.clone(),
+                    score: if rounds == 0 {
+                        0.0
+                    } else {
+                        f64::from(points) * 100.0 / rounds as f64
+                    },
+                    wins,
+                    losses,
+                    draws,
+                    rating: member.rating,
+                    age: member.age,
+                }
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.rating.total_cmp(&a.rating))
+                .then(b.age.cmp(&a.age))
+        });
+        standings
+    }
+}
+
+impl HillReport {
+    /// Report a hill's current standings
+    pub fn of(hill: &Hill) -> Self {
+        Self {
+            size: hill.size,
+            rounds: hill.rounds,
+            submissions: hill.submissions,
+            standings: hill.standings(),
+        }
+    }
+}
+
+impl From<&Submission> for SubmissionReport {
+    fn from(submission: &Submission) -> Self {
+        Self {
+            name: submission.name.clone(),
+            matches: submission
+                .matches
+                .iter()
+                .map(|(opponent, result)| MatchReport {
+                    opponent: opponent.clone(),
+                    wins: result.wins(0),
+                    losses: result.wins(1),
+                    draws: result.draws(),
+                })
+                .collect(),
+            rank: submission.rank,
+            pushed_off: submission.pushed_off.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Hill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        HillReport::of(self).fmt(f)
+    }
+}
+
+impl fmt::Display for HillReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let standings = &self.standings;
+        let width = standings
+            .iter()
+            .map(|s| s.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Champion".len());
+        writeln!(
+            f,
+            "Hill of {} ({} rounds per match, {} submissions)",
+            self.size, self.rounds, self.submissions
+        )?;
+        write!(
+            f,
+            "{:>4}  {:<width$}  {:>6}  {:>5}  {:>5}  {:>5}  {:>6}  {:>3}",
+            "Rank", "Champion", "Score", "Won", "Lost", "Drawn", "Rating", "Age"
+        )?;
+        for (rank, s) in standings.iter().enumerate() {
+            write!(
+                f,
+                "\n{:>4}  {:<width$}  {:>6.1}  {:>5}  {:>5}  {:>5}  {:>6.0}  {:>3}",
+                rank + 1,
+                s.name,
+                s.score,
+                s.wins,
+                s.losses,
+                s.draws,
+                s.rating,
+                s.age
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Rematch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} vs {}: {}-{} ({} drawn)",
+            self.challenger,
+            self.report.opponent,
+            self.report.wins,
+            self.report.losses,
+            self.report.draws
+        )
+    }
+}
+
+impl fmt::Display for Submission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        SubmissionReport::from(self).fmt(f)
+    }
+}
+
+impl fmt::Display for SubmissionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for m in &self.matches {
+            writeln!(
+                f,
+                "{} vs {}: {}-{} ({} drawn)",
+                self.name, m.opponent, m.wins, m.losses, m.draws
+            )?;
+        }
+        match self.rank {
+            Some(rank) => write!(f, "{} entered the hill at rank {}", self.name, rank)?,
+            None => write!(f, "{} did not make the hill", self.name)?,
+        }
+        if let Some(name) = &self.pushed_off
+            && *name != self.name
+        {
+            write!(f, "; {} was pushed off", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rating points the challenger of a match gains, and its opponent loses
+fn rating_change(challenger: f64, opponent: f64, result: &PairResult) -> f64 {
+    let rounds = result.first_wins + result.second_wins + result.draws;
+    if rounds == 0 {
+        return 0.0;
+    }
+    let expected = 1.0 / (1.0 + 10f64.powf((opponent - challenger) / 400.0));
+    let actual = (result.first_wins as f64 + 0.5 * result.draws as f64) / rounds as f64;
+    RATING_K * (actual - expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIVER: [u8; 100] = [0x01; 100];
+    const IDLE: [u8; 100] = [0x02; 100];
+
+    fn hill(size: usize) -> Hill {
+        let config = GameConfig {
+            max_cycles: 50,
+            ..GameConfig::default()
+        };
+        Hill::new(size, 2, config).unwrap()
+    }
+
+    #[test]
+    fn test_submissions_rank_and_push_off() {
+        let mut hill = hill(2);
+        let first = hill.submit("Idle", &IDLE).unwrap();
+        assert_eq!(first.rank, Some(1));
+        assert!(first.matches.is_empty());
+
+        let second = hill.submit("Liver", &LIVER).unwrap();
+        assert_eq!(second.rank, Some(1));
+        assert_eq!(second.matches[0].1.wins(0), 2);
+        assert!(hill.members.iter().any(|m| m.rating > INITIAL_RATING));
+        assert!(hill.submit("Copy", &LIVER).is_err());
+        assert!(hill.submit("Empty", &[]).is_err());
+
+        // Draws against the idler but loses to the liver, so ranks last
+        let third = hill.submit("Idle2", &[0x02; 90]).unwrap();
+        assert_eq!(third.rank, None);
+        assert_eq!(third.pushed_off.as_deref(), Some("Idle2"));
+        assert_eq!(hill.members.len(), 2);
+        assert_eq!(hill.results.len(), 1);
+        assert_eq!(hill.submissions, 3);
+        assert!(third.to_string().contains("did not make the hill"));
+
+        let standings = hill.standings();
+        assert_eq!(standings[0].name, "Liver");
+        assert_eq!(standings[0].score, f64::from(WIN_POINTS) * 100.0);
+        assert_eq!(standings[1].age, 1);
+    }
+
+    #[test]
+    fn test_hill_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hill.json");
+        let mut hill = hill(3);
+        hill.submit("Liver", &LIVER).unwrap();
+        hill.submit("Idle", &IDLE).unwrap();
+        hill.write(&path).unwrap();
+
+        let read = Hill::read(&path).unwrap();
+        assert_eq!(read.members, hill.members);
+        assert_eq!(read.results, hill.results);
+        assert_eq!(read.config.max_cycles, 50);
+        assert!(read.to_string().contains("Liver"));
+
+        fs::write(&path, "{}").unwrap();
+        assert!(Hill::read(&path).is_err());
+        assert!(Hill::new(0, 1, GameConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_rematches_replay_the_oldest_pair_and_retire_drops_results() {
+        let mut hill = hill(3);
+        assert_eq!(hill.rematch().unwrap(), None);
+        hill.submit("Liver", &LIVER).unwrap();
+        hill.submit("Idle", &IDLE).unwrap();
+        hill.submit("Idle2", &[0x02; 90]).unwrap();
+        let oldest = hill.results[0].clone();
+        let ratings: Vec<f64> = hill.members.iter().map(|m| m.rating).collect();
+
+        let rematch = hill.rematch().unwrap().unwrap();
+        assert_eq!(rematch.to_string(), "Idle vs Liver: 0-2 (0 drawn)");
+        assert_eq!(hill.results.last(), Some(&oldest));
+        assert_eq!(hill.results.len(), 3);
+        assert_eq!(hill.rematches, 1);
+        assert!(hill.members[0].rating > ratings[0]);
+        assert!(hill.members[1].rating < ratings[1]);
+
+        let liver = hill.members[0].sha256.clone();
+        assert_eq!(hill.retire(&liver).unwrap().name, "Liver");
+        assert_eq!(hill.retire(&liver), None);
+        assert_eq!(hill.members.len(), 2);
+        assert_eq!(hill.results.len(), 1);
+    }
+
+    #[test]
+    fn test_rating_change_is_zero_sum_and_expected() {
+        let even = PairResult {
+            first: String::new(),
+            second: String::new(),
+            first_wins: 1,
+            second_wins: 1,
+            draws: 0,
+        };
+        assert_eq!(rating_change(1500.0, 1500.0, &even), 0.0);
+        assert!(rating_change(1400.0, 1600.0, &even) > 0.0);
+        assert!(rating_change(1600.0, 1400.0, &even) < 0.0);
+    }
+}
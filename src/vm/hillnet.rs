@@ -0,0 +1,364 @@
+/// Hill submission protocol over HTTP
+///
+/// `corewar hill serve` puts a hill file behind a small HTTP API, so a
+/// community can run a continuous king-of-the-hill on this engine: anyone
+/// may submit a warrior and gets its match results and the new ranking
+/// back. `corewar hill submit` and `corewar hill show` talk to such a
+/// server, through [`HillClient`], when given its `http://` address in
+/// place of a hill file.
+///
+/// # Endpoints
+///
+/// | Request | Body | Answer |
+/// |---------|------|--------|
+/// | `GET /hill` | | The [`HillReport`] |
+/// | `POST /hill/submit?name=NAME` | Redcode source, or a .cor file | `submission`, the [`SubmissionReport`], and `hill`, the [`HillReport`] after it |
+///
+/// A `name` overrides the one in the warrior's source or header. A rejected
+/// warrior, such as one already on the hill, answers `422`; other errors
+/// answer with a 4xx or 5xx status too, and every error holds its message
+/// in a JSON `error` field.
+///
+/// Requests are read on several threads, so a slow client holds up no one
+/// else, but are answered one at a time: challengers play in the order
+/// their requests arrive in full. The hill file is rewritten after every accepted challenge,
+/// and a challenge that fails part way leaves both the file and the served
+/// hill as they were.
+use crate::error::{CoreWarError, Result};
+use crate::vm::hill::{Hill, HillReport, SubmissionReport};
+use crate::vm::http::{self, Request, Response};
+use crate::vm::loader::ChampionLoader;
+use crate::vm::service::upload_cor;
+use serde::{Deserialize, Serialize};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Largest warrior upload accepted, in bytes
+pub const MAX_UPLOAD: usize = 64 * 1024;
+
+/// Longest a client may take to send its request or take the answer
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Answer to a submission
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Submitted {
+    /// How the challenge went
+    pub submission: SubmissionReport,
+    /// The hill after the challenge
+    pub hill: HillReport,
+}
+
+/// Serves a hill file to remote submitters
+#[derive(Debug)]
+pub struct HillServer {
+    /// The hill, as last written
+    hill: Hill,
+    /// File the hill is kept in
+    path: PathBuf,
+}
+
+impl HillServer {
+    /// Serve the hill kept in a file
+    ///
+    /// # Arguments
+    /// * `path` - Hill file created with `corewar hill init`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        Ok(Self {
+            hill: Hill::read(&path)?,
+            path,
+        })
+    }
+
+    /// Get the hill being served
+    pub fn hill(&self) -> &Hill {
+        &self.hill
+    }
+
+    /// Answer connections until the listener fails
+    ///
+    /// # Arguments
+    /// * `listener` - Bound listener to accept clients on
+    pub fn serve(&mut self, listener: TcpListener) -> Result<()> {
+        Self::serve_shared(&Mutex::new(self), listener)
+    }
+
+    /// Answer connections until the listener fails, sharing the server
+    ///
+    /// Whoever else holds the lock, such as a [`crate::vm::ladder::Ladder`]
+    /// replaying matches, can change the hill in between requests.
+    ///
+    /// # Arguments
+    /// * `server` - The server, locked only while a request is answered
+    /// * `listener` - Bound listener to accept clients on
+    pub fn serve_shared(server: &Mutex<&mut Self>, listener: TcpListener) -> Result<()> {
+        http::serve_connections(listener, http::WORKERS, |stream| {
+            Self::answer(server, stream)
+        });
+        Ok(())
+    }
+
+    /// Change the hill and save it
+    ///
+    /// The change is made to a copy, so one that fails, or cannot be saved,
+    /// leaves both the file and the served hill as they were.
+    ///
+    /// # Arguments
+    /// * `change` - Changes the hill, returning a value or an error
+    pub fn update<T, F>(&mut self, change: F) -> Result<T>
+    where
+        F: FnOnce(&mut Hill) -> Result<T>,
+    {
+        let mut hill = self.hill.clone();
+        let value = change(&mut hill)?;
+        hill.write(&self.path)?;
+        self.hill = hill;
+        Ok(value)
+    }
+
+    /// Read a request from a connection and answer it
+    ///
+    /// Only answering holds the server, so reading a slow request does not
+    /// keep other clients waiting.
+    fn answer(server: &Mutex<&mut Self>, mut stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let response = match http::read_request(&mut stream, MAX_UPLOAD) {
+            Ok(request) => server
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .handle(&request),
+            Err(e @ CoreWarError::Io(_)) => return Err(e),
+            Err(e) => Response::error(400, e),
+        };
+        response.write_to(&mut stream)
+    }
+
+    /// Answer a request
+    ///
+    /// # Arguments
+    /// * `request` - A request from a client
+    ///
+    /// # Returns
+    /// The response to send back
+    pub fn handle(&mut self, request: &Request) -> Response {
+        match (request.method.as_str(), request.segments().as_slice()) {
+            ("GET", ["hill"]) => json_response(200, &HillReport::of(&self.hill)),
+            ("POST", ["hill", "submit"]) => self.submit(request),
+            (_, ["hill"] | ["hill", "submit"]) => Response::error(405, "Method not allowed"),
+            _ => Response::error(404, format!("No such endpoint: {}", request.path)),
+        }
+    }
+
+    /// Challenge the hill with an uploaded warrior
+    fn submit(&mut self, request: &Request) -> Response {
+        let challenge = upload_cor(&request.body).and_then(|cor| {
+            ChampionLoader::new(false)
+                .with_rules(self.hill.config.rules)
+                .load_champion_from_bytes(&cor, 1, Some(0))
+        });
+        let champion = match challenge {
+            Ok(champion) => champion,
+            Err(e) => return Response::error(422, e),
+        };
+        let name = request.query("name").unwrap_or(&champion.name);
+
+        // Challenge a copy, so a failed match or write keeps the served hill
+        let mut hill = self.hill.clone();
+        let submission = match hill.submit(name, &champion.code) {
+            Ok(submission) => submission,
+            Err(e) => return Response::error(422, e),
+        };
+        if let Err(e) = hill.write(&self.path) {
+            warn!("Could not save the hill: {}", e);
+            return Response::error(500, format!("Could not save the hill: {}", e));
+        }
+        self.hill = hill;
+        info!("{}", submission.to_string().replace('\n', "; "));
+
+        json_response(
+            200,
+            &Submitted {
+                submission: SubmissionReport::from(&submission),
+                hill: HillReport::of(&self.hill),
+            },
+        )
+    }
+}
+
+/// Talks to a [`HillServer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HillClient {
+    /// Server address, as `host:port`
+    address: String,
+}
+
+impl HillClient {
+    /// Create a client for a server
+    ///
+    /// # Arguments
+    /// * `url` - Server address, as `host:port` or `http://host:port`
+    pub fn new(url: &str) -> Self {
+        let address = url.strip_prefix("http://").unwrap_or(url);
+        Self {
+            address: address.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Get the hill's standings
+    pub fn standings(&self) -> Result<HillReport> {
+        self.call("GET", "/hill", &[])
+    }
+
+    /// Challenge the hill
+    ///
+    /// # Arguments
+    /// * `name` - Name overriding the warrior's own, if any
+    /// * `warrior` - Redcode source, or the contents of a .cor file
+    ///
+    /// # Returns
+    /// How the challenge went and the hill after it, or an error if the
+    /// server rejected the warrior
+    pub fn submit(&self, name: Option<&str>, warrior: &[u8]) -> Result<Submitted> {
+        let target = match name {
+            Some(name) => format!("/hill/submit?name={}", http::percent_encode(name)),
+            None => "/hill/submit".to_string(),
+        };
+        self.call("POST", &target, warrior)
+    }
+
+    /// Send a request and decode the JSON answer
+    fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        target: &str,
+        body: &[u8],
+    ) -> Result<T> {
+        // Answers are held to the server's own limit on uploads
+        let (status, body) = http::send(&self.address, method, target, body, MAX_UPLOAD)?;
+        if !(200..300).contains(&status) {
+            let message = serde_json::from_slice::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|error| error["error"].as_str().map(str::to_string))
+                .unwrap_or_else(|| format!("status {}", status));
+            return Err(CoreWarError::protocol(format!(
+                "Hill server refused the request: {}",
+                message
+            )));
+        }
+        serde_json::from_slice(&body).map_err(|e| {
+            CoreWarError::protocol(format!("Invalid answer from the hill server: {}", e))
+        })
+    }
+}
+
+/// Create a response with a serialized body
+fn json_response<T: Serialize>(status: u16, body: &T) -> Response {
+    match serde_json::to_value(body) {
+        Ok(body) => Response::json(status, &body),
+        Err(e) => Response::error(500, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::GameConfig;
+
+    const LIVER: &str = ".name \"Liver\"\n.comment \"\"\nloop: live %1\nzjmp %:loop\n";
+    const IDLER: &str = ".name \"Idler\"\n.comment \"\"\nloop: zjmp %:loop\n";
+
+    fn hill_file(dir: &Path) -> PathBuf {
+        let path = dir.join("hill.json");
+        let config = GameConfig {
+            max_cycles: 200,
+            ..GameConfig::default()
+        };
+        Hill::new(2, 2, config).unwrap().write(&path).unwrap();
+        path
+    }
+
+    fn request(method: &str, target: &str, body: &str) -> Request {
+        let raw = format!(
+            "{} {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            target,
+            body.len(),
+            body
+        );
+        http::read_request(raw.as_bytes(), MAX_UPLOAD).unwrap()
+    }
+
+    #[test]
+    fn test_submissions_update_the_hill_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = hill_file(dir.path());
+        let mut server = HillServer::open(&path).unwrap();
+
+        let response = server.handle(&request("POST", "/hill/submit?name=First", IDLER));
+        assert_eq!(response.status, 200);
+        let response = server.handle(&request("POST", "/hill/submit", LIVER));
+        assert_eq!(response.status, 200);
+        let submitted: Submitted = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(submitted.submission.name, "Liver");
+        assert_eq!(submitted.submission.matches[0].opponent, "First");
+        assert_eq!(submitted.hill.standings.len(), 2);
+        assert_eq!(Hill::read(&path).unwrap().submissions, 2);
+
+        assert_eq!(
+            server
+                .handle(&request("POST", "/hill/submit", LIVER))
+                .status,
+            422
+        );
+        assert_eq!(
+            server
+                .handle(&request("POST", "/hill/submit", "nop"))
+                .status,
+            422
+        );
+        assert_eq!(server.handle(&request("PUT", "/hill", "")).status, 405);
+        assert_eq!(server.handle(&request("GET", "/battles", "")).status, 404);
+        assert_eq!(server.hill().submissions, 2);
+    }
+
+    #[test]
+    fn test_submissions_cannot_include_server_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("secret.s");
+        std::fs::write(&secret, "server secret %\n").unwrap();
+        let source = format!(".include \"{}\"\nlive %1\n", secret.display());
+        let mut server = HillServer::open(hill_file(dir.path())).unwrap();
+
+        let refused = server.handle(&request("POST", "/hill/submit", &source));
+        assert_eq!(refused.status, 422);
+        let error = String::from_utf8(refused.body).unwrap();
+        assert!(error.contains(".include is not allowed"), "{}", error);
+        assert!(!error.contains("server secret"), "{}", error);
+        assert_eq!(server.hill().submissions, 0);
+    }
+
+    #[test]
+    fn test_client_talks_to_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = HillServer::open(hill_file(dir.path())).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || server.serve(listener));
+
+        let client = HillClient::new(&url);
+        let submitted = client
+            .submit(Some("Little Liver"), LIVER.as_bytes())
+            .unwrap();
+        assert_eq!(submitted.submission.rank, Some(1));
+        let report = client.standings().unwrap();
+        assert_eq!(report.standings[0].name, "Little Liver");
+        assert_eq!(report.to_string(), submitted.hill.to_string());
+
+        let error = client.submit(None, LIVER.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("already on the hill"));
+    }
+}
@@ -0,0 +1,153 @@
+/// Checkpoint history for stepping backwards through a battle
+///
+/// The engine keeps a ring buffer of periodic [`GameSnapshot`]s. Rewinding
+/// restores the newest checkpoint at or before the target cycle and replays
+/// the remaining cycles, which is exact because battles are deterministic.
+use crate::vm::GameSnapshot;
+use std::collections::VecDeque;
+
+/// Cycles between checkpoints when none is configured
+pub const DEFAULT_CHECKPOINT_INTERVAL: u32 = 100;
+
+/// Checkpoints kept when none is configured
+pub const DEFAULT_CHECKPOINT_CAPACITY: usize = 32;
+
+/// Ring buffer of periodic checkpoints
+#[derive(Debug, Clone)]
+pub struct CheckpointHistory {
+    /// Cycles between checkpoints
+    interval: u32,
+    /// Maximum number of checkpoints kept
+    capacity: usize,
+    /// Checkpoints, oldest first
+    checkpoints: VecDeque<GameSnapshot>,
+}
+
+impl CheckpointHistory {
+    /// Create an empty history
+    ///
+    /// # Arguments
+    /// * `interval` - Cycles between checkpoints (0 disables checkpoints)
+    /// * `capacity` - Maximum number of checkpoints kept (0 disables checkpoints)
+    ///
+    /// # Returns
+    /// A new CheckpointHistory instance
+    pub fn new(interval: u32, capacity: usize) -> Self {
+        Self {
+            interval,
+            capacity,
+            checkpoints: VecDeque::new(),
+        }
+    }
+
+    /// Check whether checkpoints are being recorded
+    pub fn is_enabled(&self) -> bool {
+        self.interval > 0 && self.capacity > 0
+    }
+
+    /// Check whether a checkpoint should be taken at a cycle
+    ///
+    /// # Arguments
+    /// * `cycle` - The cycle that just completed
+    pub fn is_due(&self, cycle: u32) -> bool {
+        self.is_enabled() && cycle.is_multiple_of(self.interval)
+    }
+
+    /// Store a checkpoint, evicting the oldest one when full
+    ///
+    /// A checkpoint replaces any existing checkpoint for the same cycle.
+    ///
+    /// # Arguments
+    /// * `snapshot` - The state to keep
+    pub fn record(&mut self, snapshot: GameSnapshot) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        while self
+            .checkpoints
+            .back()
+            .is_some_and(|c| c.cycle >= snapshot.cycle)
+        {
+            self.checkpoints.pop_back();
+        }
+        self.checkpoints.push_back(snapshot);
+        while self.checkpoints.len() > self.capacity {
+            self.checkpoints.pop_front();
+        }
+    }
+
+    /// Find the newest checkpoint taken at or before a cycle
+    ///
+    /// # Arguments
+    /// * `cycle` - The cycle to rewind to
+    ///
+    /// # Returns
+    /// The checkpoint, or None if every kept checkpoint is newer
+    pub fn latest_at_or_before(&self, cycle: u32) -> Option<&GameSnapshot> {
+        self.checkpoints.iter().rev().find(|c| c.cycle <= cycle)
+    }
+
+    /// Drop every checkpoint taken after a cycle
+    ///
+    /// # Arguments
+    /// * `cycle` - The last cycle to keep
+    pub fn discard_after(&mut self, cycle: u32) {
+        while self.checkpoints.back().is_some_and(|c| c.cycle > cycle) {
+            self.checkpoints.pop_back();
+        }
+    }
+
+    /// Get the cycle of the oldest checkpoint kept
+    pub fn oldest_cycle(&self) -> Option<u32> {
+        self.checkpoints.front().map(|c| c.cycle)
+    }
+
+    /// Get the number of checkpoints kept
+    pub fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// Check whether no checkpoints are kept
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameEngine;
+
+    fn checkpoint(cycle: u32) -> GameSnapshot {
+        let mut snapshot = GameEngine::new(Default::default()).snapshot();
+        snapshot.cycle = cycle;
+        snapshot
+    }
+
+    #[test]
+    fn test_ring_buffer_evicts_oldest() {
+        let mut history = CheckpointHistory::new(10, 3);
+        for cycle in [0, 10, 20, 30] {
+            assert!(history.is_due(cycle));
+            history.record(checkpoint(cycle));
+        }
+        assert!(!history.is_due(15));
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.oldest_cycle(), Some(10));
+        assert_eq!(history.latest_at_or_before(25).unwrap().cycle, 20);
+        assert!(history.latest_at_or_before(5).is_none());
+
+        history.discard_after(15);
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_history_records_nothing() {
+        let mut history = CheckpointHistory::new(0, 3);
+        assert!(!history.is_due(0));
+        history.record(checkpoint(0));
+        assert!(history.is_empty());
+    }
+}
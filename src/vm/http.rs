@@ -0,0 +1,453 @@
+/// Minimal HTTP/1.1
+///
+/// Just enough of the protocol for [`crate::vm::service`] and
+/// [`crate::vm::hillnet`] to answer API calls: reading one request with a
+/// `Content-Length` body, and writing one response, after which the
+/// connection closes. [`send`] is the matching client. Chunked bodies and
+/// keep-alive are not supported.
+///
+/// [`serve_connections`] answers connections on a pool of [`WORKERS`]
+/// threads, so a client that is slow to send its request holds up only the
+/// thread reading it.
+use crate::error::{CoreWarError, Result};
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Mutex, mpsc};
+use std::thread;
+use tracing::warn;
+
+/// Longest request line and headers read, to bound a hostile client
+const MAX_HEADER: usize = 8192;
+
+/// Threads a server answers connections on
+pub const WORKERS: usize = 8;
+
+/// A request from a client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// Method, such as `GET` or `POST`
+    pub method: String,
+    /// Path, without the query string
+    pub path: String,
+    /// Decoded query parameters, in order
+    pub query: Vec<(String, String)>,
+    /// Headers, with names in lowercase
+    pub headers: Vec<(String, String)>,
+    /// Body, empty if the request has none
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Get the value of a header
+    ///
+    /// # Arguments
+    /// * `name` - Header name, in lowercase
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Get the value of a query parameter
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Get the path's segments, without empty ones
+    pub fn segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+}
+
+/// A response to send
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// Status code
+    pub status: u16,
+    /// Media type of the body
+    pub content_type: &'static str,
+    /// Body
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Create a response with a JSON body
+    pub fn json(status: u16, body: &Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    /// Create an error response, with the message in a JSON `error` field
+    pub fn error(status: u16, message: impl std::fmt::Display) -> Self {
+        Self::json(status, &json!({ "error": message.to_string() }))
+    }
+
+    /// Create a response with a binary body
+    pub fn bytes(status: u16, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            content_type: "application/octet-stream",
+            body,
+        }
+    }
+
+    /// Send the response
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\n\
+             Content-Type: {}\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n",
+            self.status,
+            reason(self.status),
+            self.content_type,
+            self.body.len()
+        )?;
+        writer.write_all(&self.body)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Read a request
+///
+/// # Arguments
+/// * `reader` - Connection from the client
+/// * `max_body` - Largest body accepted, in bytes
+///
+/// # Returns
+/// The request, or an error if it is malformed or its body too large
+pub fn read_request<R: Read>(reader: R, max_body: usize) -> Result<Request> {
+    let mut reader = BufReader::new(reader);
+    let mut read = 0;
+    let mut next_line = |reader: &mut BufReader<R>| -> Result<String> {
+        let mut line = String::new();
+        let n = reader.read_line(&mut line)?;
+        read += n;
+        if n == 0 || read > MAX_HEADER {
+            return Err(CoreWarError::protocol("Incomplete HTTP request"));
+        }
+        Ok(line.trim_end().to_string())
+    };
+
+    let request_line = next_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(CoreWarError::protocol(format!(
+            "Malformed HTTP request line: {}",
+            request_line
+        )));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect();
+
+    let mut headers = Vec::new();
+    loop {
+        let line = next_line(&mut reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let mut request = Request {
+        method: method.to_string(),
+        path: percent_decode(path),
+        query,
+        headers,
+        body: Vec::new(),
+    };
+
+    if request.header("transfer-encoding").is_some() {
+        return Err(CoreWarError::protocol(
+            "Chunked request bodies are not supported; send a Content-Length",
+        ));
+    }
+    let length = match request.header("content-length") {
+        Some(length) => length
+            .parse::<usize>()
+            .map_err(|_| CoreWarError::protocol(format!("Invalid Content-Length: {}", length)))?,
+        None => 0,
+    };
+    if length > max_body {
+        return Err(CoreWarError::protocol(format!(
+            "Request body of {} bytes is larger than the {} allowed",
+            length, max_body
+        )));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+    Ok(request)
+}
+
+/// Send a request and wait for the response
+///
+/// # Arguments
+/// * `address` - Server address, as `host:port`
+/// * `method` - Method, such as `GET` or `POST`
+/// * `target` - Path, with any query string already encoded
+/// * `body` - Body, empty for none
+/// * `max_body` - Largest response body accepted, in bytes
+///
+/// # Returns
+/// The response's status code and body, or an error if the body is too large
+pub fn send(
+    address: &str,
+    method: &str,
+    target: &str,
+    body: &[u8],
+    max_body: usize,
+) -> Result<(u16, Vec<u8>)> {
+    let mut stream = TcpStream::connect(address)?;
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\n\
+         Host: {}\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        method,
+        target,
+        address,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()?;
+    read_response(stream, max_body)
+}
+
+/// Read a response, as [`Response::write_to`] sends it
+///
+/// # Arguments
+/// * `reader` - Connection from the server
+/// * `max_body` - Largest body accepted, in bytes
+///
+/// # Returns
+/// The status code and body, or an error if the body is too large
+fn read_response<R: Read>(reader: R, max_body: usize) -> Result<(u16, Vec<u8>)> {
+    let mut reader = BufReader::new(reader);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(|| {
+            CoreWarError::protocol(format!(
+                "Malformed HTTP status line: {}",
+                status_line.trim_end()
+            ))
+        })?;
+
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(CoreWarError::protocol("Incomplete HTTP response"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let too_large = |length: usize| {
+        CoreWarError::protocol(format!(
+            "Response body of {} bytes is larger than the {} allowed",
+            length, max_body
+        ))
+    };
+    let mut body = Vec::new();
+    match length {
+        Some(length) if length > max_body => return Err(too_large(length)),
+        Some(length) => {
+            body.resize(length, 0);
+            reader.read_exact(&mut body)?;
+        }
+        None => {
+            // Read one byte past the limit to tell a full body from a larger one
+            reader.take(max_body as u64 + 1).read_to_end(&mut body)?;
+            if body.len() > max_body {
+                return Err(too_large(body.len()));
+            }
+        }
+    }
+    Ok((status, body))
+}
+
+/// Answer connections on a pool of threads until the listener fails
+///
+/// Each accepted connection goes to the next idle thread. While every
+/// thread is busy, up to as many connections again wait their turn and
+/// the listener stops accepting more.
+///
+/// # Arguments
+/// * `listener` - Bound listener to accept clients on
+/// * `workers` - Threads answering connections
+/// * `answer` - Answers one connection; its errors are logged
+pub fn serve_connections<F>(listener: TcpListener, workers: usize, answer: F)
+where
+    F: Fn(TcpStream) -> Result<()> + Sync,
+{
+    let workers = workers.max(1);
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(workers);
+    let receiver = Mutex::new(receiver);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    // The lock is only held while waiting for a connection
+                    let next = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                    let Ok(stream) = next else {
+                        return;
+                    };
+                    if let Err(e) = answer(stream) {
+                        warn!("Request failed: {}", e);
+                    }
+                }
+            });
+        }
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if sender.send(stream).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => warn!("Connection failed: {}", e),
+            }
+        }
+        drop(sender);
+    });
+}
+
+/// Encode text for a query string, escaping all but unreserved characters
+pub fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes, and `+` as a space
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                i += 2;
+            }
+            (b'+', None) => decoded.push(b' '),
+            (byte, None) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Get the reason phrase of a status code
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /warriors?name=Little%20Imp&x HTTP/1.1\r\nHost: localhost\r\n\
+                   Content-Length: 5\r\n\r\nhello and more";
+        let request = read_request(raw.as_bytes(), 1024).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.segments(), ["warriors"]);
+        assert_eq!(request.query("name"), Some("Little Imp"));
+        assert_eq!(request.query("x"), Some(""));
+        assert_eq!(request.header("host"), Some("localhost"));
+        assert_eq!(request.body, b"hello");
+
+        assert!(read_request(raw.as_bytes(), 4).is_err());
+        assert!(read_request("GET /\r\n\r\n".as_bytes(), 4).is_err());
+        assert!(read_request("GET / HTTP/1.1\r\n".as_bytes(), 4).is_err());
+        assert_eq!(percent_decode("a+b%2Fc%zz%4"), "a b/c%zz%4");
+    }
+
+    #[test]
+    fn test_write_response() {
+        let mut output = Vec::new();
+        Response::error(404, "No battle 3")
+            .write_to(&mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(output.contains("Content-Type: application/json\r\n"));
+        assert!(output.ends_with("\r\n\r\n{\"error\":\"No battle 3\"}"));
+
+        let (status, body) = read_response(output.as_bytes(), 1024).unwrap();
+        assert_eq!(status, 404);
+        assert_eq!(body, b"{\"error\":\"No battle 3\"}");
+        assert!(read_response("HTTP/1.1 200 OK\r\n".as_bytes(), 1024).is_err());
+
+        // Bodies past the limit, announced or not
+        assert!(matches!(
+            read_response(output.as_bytes(), 4),
+            Err(CoreWarError::Protocol { .. })
+        ));
+        let unsized_body = "HTTP/1.1 200 OK\r\n\r\nhello";
+        assert_eq!(
+            read_response(unsized_body.as_bytes(), 5).unwrap().1,
+            b"hello"
+        );
+        assert!(matches!(
+            read_response(unsized_body.as_bytes(), 4),
+            Err(CoreWarError::Protocol { .. })
+        ));
+        assert_eq!(percent_decode(&percent_encode("Imp & co/2")), "Imp & co/2");
+    }
+}
@@ -3,11 +3,14 @@
 /// This module defines the 16-instruction Core War instruction set
 /// with proper parameter types and validation.
 use crate::error::{CoreWarError, Result};
+use crate::vm::reference::InstructionReference;
+use serde::Serialize;
 
 /// Core War instruction set
 ///
 /// The instruction set consists of 16 instructions, each with a specific opcode
-/// and parameter requirements.
+/// and parameter requirements. The `pspace` feature adds `ldp` and `stp`,
+/// which reach the champion's private storage.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Instruction {
@@ -43,9 +46,59 @@ pub enum Instruction {
     Lfork = 0x0F,
     /// Display character to stdout - opcode 0x10
     Aff = 0x10,
+    /// Load from private storage - opcode 0x11
+    #[cfg(feature = "pspace")]
+    Ldp = 0x11,
+    /// Store to private storage - opcode 0x12
+    #[cfg(feature = "pspace")]
+    Stp = 0x12,
 }
 
 impl Instruction {
+    /// Every instruction, in opcode order
+    #[cfg(not(feature = "pspace"))]
+    pub const ALL: [Instruction; 16] = [
+        Self::Live,
+        Self::Ld,
+        Self::St,
+        Self::Add,
+        Self::Sub,
+        Self::And,
+        Self::Or,
+        Self::Xor,
+        Self::Zjmp,
+        Self::Ldi,
+        Self::Sti,
+        Self::Fork,
+        Self::Lld,
+        Self::Lldi,
+        Self::Lfork,
+        Self::Aff,
+    ];
+
+    /// Every instruction, in opcode order
+    #[cfg(feature = "pspace")]
+    pub const ALL: [Instruction; 18] = [
+        Self::Live,
+        Self::Ld,
+        Self::St,
+        Self::Add,
+        Self::Sub,
+        Self::And,
+        Self::Or,
+        Self::Xor,
+        Self::Zjmp,
+        Self::Ldi,
+        Self::Sti,
+        Self::Fork,
+        Self::Lld,
+        Self::Lldi,
+        Self::Lfork,
+        Self::Aff,
+        Self::Ldp,
+        Self::Stp,
+    ];
+
     /// Convert an opcode byte to an instruction
     ///
     /// # Arguments
@@ -71,6 +124,10 @@ impl Instruction {
             0x0E => Ok(Self::Lldi),
             0x0F => Ok(Self::Lfork),
             0x10 => Ok(Self::Aff),
+            #[cfg(feature = "pspace")]
+            0x11 => Ok(Self::Ldp),
+            #[cfg(feature = "pspace")]
+            0x12 => Ok(Self::Stp),
             _ => Err(CoreWarError::InvalidOpcode { opcode }),
         }
     }
@@ -99,6 +156,30 @@ impl Instruction {
             Self::Lldi => 3,
             Self::Lfork => 1,
             Self::Aff => 1,
+            #[cfg(feature = "pspace")]
+            Self::Ldp | Self::Stp => 2,
+        }
+    }
+
+    /// Get the parameter types accepted by each operand
+    ///
+    /// # Returns
+    /// One slice of accepted types per operand, in operand order
+    pub fn operand_types(&self) -> &'static [&'static [ParameterType]] {
+        use ParameterType::{Direct as D, Indirect as I, Register as R};
+        match self {
+            Self::Live | Self::Zjmp | Self::Fork | Self::Lfork => &[&[D]],
+            Self::Ld | Self::Lld => &[&[D, I], &[R]],
+            Self::St => &[&[R], &[I, R]],
+            Self::Add | Self::Sub => &[&[R], &[R], &[R]],
+            Self::And | Self::Or | Self::Xor => &[&[R, D, I], &[R, D, I], &[R]],
+            Self::Ldi | Self::Lldi => &[&[R, D, I], &[D, R], &[R]],
+            Self::Sti => &[&[R], &[R, D, I], &[D, R]],
+            Self::Aff => &[&[R]],
+            #[cfg(feature = "pspace")]
+            Self::Ldp => &[&[D, I, R], &[R]],
+            #[cfg(feature = "pspace")]
+            Self::Stp => &[&[R], &[D, I, R]],
         }
     }
 
@@ -121,6 +202,8 @@ impl Instruction {
             Self::Lldi => 50,
             Self::Lfork => 1000,
             Self::Aff => 2,
+            #[cfg(feature = "pspace")]
+            Self::Ldp | Self::Stp => 5,
         }
     }
 
@@ -143,11 +226,19 @@ impl Instruction {
             Self::Lldi => "lldi",
             Self::Lfork => "lfork",
             Self::Aff => "aff",
+            #[cfg(feature = "pspace")]
+            Self::Ldp => "ldp",
+            #[cfg(feature = "pspace")]
+            Self::Stp => "stp",
         }
     }
 
     /// Check if this instruction sets the carry flag
     pub fn sets_carry(&self) -> bool {
+        #[cfg(feature = "pspace")]
+        if *self == Self::Ldp {
+            return true;
+        }
         matches!(
             self,
             Self::Ld | Self::Lld | Self::Ldi | Self::Lldi | Self::And | Self::Or | Self::Xor
@@ -158,10 +249,50 @@ impl Instruction {
     pub fn uses_long_addressing(&self) -> bool {
         matches!(self, Self::Lld | Self::Lldi | Self::Lfork)
     }
+
+    /// Check if this instruction's direct parameters are 2-byte indices
+    ///
+    /// Jumps, forks and the indexed loads and stores take addresses rather
+    /// than values, so their direct parameters are as wide as an indirect one.
+    pub fn uses_index_parameters(&self) -> bool {
+        matches!(
+            self,
+            Self::Zjmp | Self::Ldi | Self::Sti | Self::Fork | Self::Lldi | Self::Lfork
+        )
+    }
+
+    /// Check if this instruction is encoded with a parameter types byte
+    ///
+    /// `live`, `zjmp`, `fork` and `lfork` take a single direct parameter,
+    /// so their opcode is followed by the parameter itself.
+    pub fn has_parameter_types(&self) -> bool {
+        !matches!(self, Self::Live | Self::Zjmp | Self::Fork | Self::Lfork)
+    }
+
+    /// Get the encoded size in bytes of the opcode and any parameter types byte
+    pub fn header_size(&self) -> usize {
+        if self.has_parameter_types() { 2 } else { 1 }
+    }
+
+    /// Get the encoded size in bytes of a parameter of this instruction
+    ///
+    /// # Arguments
+    /// * `param_type` - The parameter type
+    ///
+    /// # Returns
+    /// 4 for direct values outside the index instructions, otherwise the
+    /// type's own [`ParameterType::size`]
+    pub fn parameter_size(&self, param_type: ParameterType) -> usize {
+        match param_type {
+            ParameterType::Direct | ParameterType::Label if !self.uses_index_parameters() => 4,
+            _ => param_type.size(),
+        }
+    }
 }
 
 /// Parameter types for Core War instructions
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ParameterType {
     /// Register parameter (r1-r16)
     Register,
@@ -200,7 +331,20 @@ impl ParameterType {
         }
     }
 
-    /// Get the size in bytes of this parameter type
+    /// Get the Redcode syntax used to write this parameter type
+    pub fn syntax(&self) -> &'static str {
+        match self {
+            Self::Register => "rN",
+            Self::Direct => "%N",
+            Self::Indirect => "N",
+            Self::Label => ":label",
+        }
+    }
+
+    /// Get the size in bytes of this parameter type as an index
+    ///
+    /// Direct values take 4 bytes outside the index instructions; use
+    /// [`Instruction::parameter_size`] when the instruction is known.
     pub fn size(&self) -> usize {
         match self {
             Self::Register => 1,
@@ -314,31 +458,156 @@ impl CompleteInstruction {
 
     /// Get the total size of this instruction in bytes
     pub fn size(&self) -> usize {
-        1 + // Opcode byte
-        1 + // Parameter types byte
-        self.parameters.iter().map(|p| p.param_type.size()).sum::<usize>()
+        self.instruction.header_size()
+            + self
+                .parameters
+                .iter()
+                .map(|p| self.instruction.parameter_size(p.param_type))
+                .sum::<usize>()
+    }
+}
+
+/// Machine-readable description of the instruction set
+///
+/// This is the single source of truth exported by `corewar explain --json`,
+/// so editors, fuzzers and documentation can follow the VM's semantics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstructionSetSpec {
+    /// Encoding of each operand type
+    pub parameter_types: Vec<ParameterTypeSpec>,
+    /// Every instruction, in opcode order
+    pub instructions: Vec<InstructionSpec>,
+}
+
+/// Encoding of one operand type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ParameterTypeSpec {
+    /// The operand type
+    pub kind: ParameterType,
+    /// 2-bit code used in the parameter types byte
+    pub type_code: u8,
+    /// Encoded size in bytes as an index; direct values are 4 bytes
+    /// outside the index instructions
+    pub size: usize,
+    /// Redcode syntax
+    pub syntax: &'static str,
+}
+
+/// Machine-readable description of one instruction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstructionSpec {
+    /// Mnemonic
+    pub name: &'static str,
+    /// Opcode byte
+    pub opcode: u8,
+    /// Cycles the instruction takes to execute
+    pub cycles: u32,
+    /// Accepted operand types, one list per operand
+    pub operands: Vec<Vec<ParameterType>>,
+    /// Smallest encoded size in bytes, including opcode and any types byte
+    pub min_size: usize,
+    /// Largest encoded size in bytes, including opcode and any types byte
+    pub max_size: usize,
+    /// Whether the instruction updates the carry flag
+    pub sets_carry: bool,
+    /// Whether offsets skip the IDX_MOD reduction
+    pub long_addressing: bool,
+    /// One-line summary
+    pub summary: &'static str,
+}
+
+impl InstructionSpec {
+    /// Describe a single instruction
+    ///
+    /// # Arguments
+    /// * `instruction` - The instruction to describe
+    ///
+    /// # Returns
+    /// The instruction's description
+    pub fn of(instruction: Instruction) -> Self {
+        let operand_types = instruction.operand_types();
+        let header = instruction.header_size();
+
+        Self {
+            name: instruction.name(),
+            opcode: instruction.opcode(),
+            cycles: instruction.cycles(),
+            operands: operand_types.iter().map(|types| types.to_vec()).collect(),
+            min_size: header
+                + operand_types
+                    .iter()
+                    .map(|types| {
+                        types
+                            .iter()
+                            .map(|&t| instruction.parameter_size(t))
+                            .min()
+                            .unwrap_or(0)
+                    })
+                    .sum::<usize>(),
+            max_size: header
+                + operand_types
+                    .iter()
+                    .map(|types| {
+                        types
+                            .iter()
+                            .map(|&t| instruction.parameter_size(t))
+                            .max()
+                            .unwrap_or(0)
+                    })
+                    .sum::<usize>(),
+            sets_carry: instruction.sets_carry(),
+            long_addressing: instruction.uses_long_addressing(),
+            summary: InstructionReference::of(instruction).summary,
+        }
     }
+}
+
+/// Describe the full instruction set
+///
+/// # Returns
+/// Operand encodings and every instruction, in opcode order
+pub fn spec() -> InstructionSetSpec {
+    let parameter_types = [
+        ParameterType::Register,
+        ParameterType::Direct,
+        ParameterType::Indirect,
+    ]
+    .into_iter()
+    .map(|kind| ParameterTypeSpec {
+        kind,
+        type_code: kind.type_code(),
+        size: kind.size(),
+        syntax: kind.syntax(),
+    })
+    .collect();
+
+    InstructionSetSpec {
+        parameter_types,
+        instructions: Instruction::ALL.into_iter().map(InstructionSpec::of).collect(),
+    }
+}
 
-    /// Get a string representation of this instruction
-    pub fn to_string(&self) -> String {
-        let mut result = self.instruction.name().to_string();
+impl std::fmt::Display for CompleteInstruction {
+    /// Format this instruction as Redcode source text
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.instruction.name())?;
 
         for (i, param) in self.parameters.iter().enumerate() {
             if i > 0 {
-                result.push_str(", ");
+                write!(f, ", ")?;
             } else {
-                result.push(' ');
+                write!(f, " ")?;
             }
 
             match param.param_type {
-                ParameterType::Register => result.push_str(&format!("r{}", param.value)),
-                ParameterType::Direct => result.push_str(&format!("%{}", param.value)),
-                ParameterType::Indirect => result.push_str(&format!("{}", param.value)),
-                ParameterType::Label => result.push_str(&format!(":{}", param.value)),
+                ParameterType::Register => write!(f, "r{}", param.value)?,
+                ParameterType::Direct => write!(f, "%{}", param.value)?,
+                ParameterType::Indirect => write!(f, "{}", param.value)?,
+                ParameterType::Label => write!(f, ":{}", param.value)?,
             }
         }
 
-        result
+        Ok(())
     }
 }
 
@@ -353,7 +622,11 @@ mod tests {
         assert_eq!(Instruction::from_opcode(0x10).unwrap(), Instruction::Aff);
 
         assert!(Instruction::from_opcode(0x00).is_err());
+        assert!(Instruction::from_opcode(0x13).is_err());
+        #[cfg(not(feature = "pspace"))]
         assert!(Instruction::from_opcode(0x11).is_err());
+        #[cfg(feature = "pspace")]
+        assert_eq!(Instruction::from_opcode(0x12).unwrap(), Instruction::Stp);
     }
 
     #[test]
@@ -366,8 +639,40 @@ mod tests {
         assert!(!Instruction::Live.uses_long_addressing());
 
         assert_eq!(Instruction::Add.parameter_count(), 3);
-        assert_eq!(Instruction::Ld.sets_carry(), true);
-        assert_eq!(Instruction::Lld.uses_long_addressing(), true);
+        assert!(Instruction::Ld.sets_carry());
+        assert!(Instruction::Lld.uses_long_addressing());
+    }
+
+    #[test]
+    fn test_operand_types_match_parameter_count() {
+        for instruction in Instruction::ALL {
+            assert_eq!(
+                instruction.operand_types().len(),
+                instruction.parameter_count(),
+                "{}",
+                instruction.name()
+            );
+            assert_eq!(Instruction::from_opcode(instruction.opcode()).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_spec_matches_instruction_table() {
+        let spec = spec();
+        let count = if cfg!(feature = "pspace") { 18 } else { 16 };
+        assert_eq!(spec.instructions.len(), count);
+        assert_eq!(spec.parameter_types.len(), 3);
+
+        let sti = &spec.instructions[10];
+        assert_eq!(sti.name, "sti");
+        assert_eq!(sti.opcode, 0x0B);
+        assert_eq!(sti.cycles, 25);
+        assert_eq!(sti.min_size, 2 + 1 + 1 + 1);
+        assert_eq!(sti.max_size, 2 + 1 + 2 + 2);
+
+        let json = serde_json::to_value(InstructionSpec::of(Instruction::Ld)).unwrap();
+        assert_eq!(json["operands"], serde_json::json!([["direct", "indirect"], ["register"]]));
+        assert_eq!(json["sets_carry"], true);
     }
 
     #[test]
@@ -381,6 +686,19 @@ mod tests {
         assert_eq!(ParameterType::Register.size(), 1);
     }
 
+    #[test]
+    fn test_direct_size_depends_on_instruction() {
+        assert_eq!(Instruction::Ld.parameter_size(ParameterType::Direct), 4);
+        assert_eq!(Instruction::Live.parameter_size(ParameterType::Label), 4);
+        assert_eq!(Instruction::Ld.parameter_size(ParameterType::Indirect), 2);
+        assert_eq!(Instruction::Sti.parameter_size(ParameterType::Direct), 2);
+        assert_eq!(Instruction::Zjmp.parameter_size(ParameterType::Label), 2);
+        assert_eq!(Instruction::And.parameter_size(ParameterType::Register), 1);
+
+        let ld = InstructionSpec::of(Instruction::Ld);
+        assert_eq!((ld.min_size, ld.max_size), (2 + 2 + 1, 2 + 4 + 1));
+    }
+
     #[test]
     fn test_parameter_creation() {
         let reg_param = Parameter::register(5);
@@ -403,7 +721,7 @@ mod tests {
 
         assert_eq!(inst.instruction, Instruction::Ld);
         assert_eq!(inst.parameters.len(), 2);
-        assert_eq!(inst.size(), 1 + 1 + 1 + 2); // opcode + param types + reg + direct
+        assert_eq!(inst.size(), 1 + 1 + 1 + 4); // opcode + param types + reg + direct
 
         let inst_str = inst.to_string();
         assert_eq!(inst_str, "ld r1, %42");
@@ -0,0 +1,253 @@
+/// Continuous ladders on a hill
+///
+/// `corewar hill ladder` keeps a hill in step with a directory of .cor
+/// files. Every pass of a [`Ladder`]:
+///
+/// 1. retires the members whose file was removed or rebuilt with other
+///    code, with their results,
+/// 2. submits every new champion in the directory, in name order, skipping
+///    the ones that were pushed off before, and
+/// 3. plays up to [`Ladder::with_rematches`] rematches, least recently
+///    played pair first, on fresh seeds, so the ratings keep converging as
+///    the field changes.
+///
+/// Every step is saved to the hill file as it is made, through
+/// [`HillServer::update`], so ratings survive a restart and a step that
+/// fails leaves the hill as it was. [`Ladder::run`] repeats passes on an
+/// interval while [`HillServer::serve_shared`] answers `GET /hill` with the
+/// standings as JSON, and takes submissions too, in between steps.
+use crate::error::Result;
+use crate::vm::bundle::sha256_hex;
+use crate::vm::hill::{Rematch, Submission};
+use crate::vm::hillnet::HillServer;
+use crate::vm::loader::{self, ChampionLoader};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Rematches per pass unless configured otherwise
+pub const DEFAULT_REMATCHES: usize = 10;
+
+/// Time between the start of two passes unless configured otherwise
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Keeps a hill in step with a directory of champions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ladder {
+    /// Directory of .cor files entered in the ladder
+    champions: PathBuf,
+    /// Rematches played per pass
+    rematches: usize,
+}
+
+/// What one pass of a ladder did
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LadderPass {
+    /// Names of the members retired because their file is gone
+    pub retired: Vec<String>,
+    /// Each new champion's challenge
+    pub submissions: Vec<Submission>,
+    /// Each rematch played
+    pub rematches: Vec<Rematch>,
+}
+
+impl Ladder {
+    /// Create a ladder over a directory
+    ///
+    /// # Arguments
+    /// * `champions` - Directory of .cor files; adding or removing a file
+    ///   enters or retires a champion
+    pub fn new<P: AsRef<Path>>(champions: P) -> Self {
+        Self {
+            champions: champions.as_ref().to_path_buf(),
+            rematches: DEFAULT_REMATCHES,
+        }
+    }
+
+    /// Set the number of rematches played per pass
+    pub fn with_rematches(mut self, rematches: usize) -> Self {
+        self.rematches = rematches;
+        self
+    }
+
+    /// Bring the hill in step with the directory and play rematches
+    ///
+    /// The server is locked for one step at a time, so requests are
+    /// answered in between.
+    ///
+    /// # Arguments
+    /// * `server` - Server of the hill
+    ///
+    /// # Returns
+    /// What the pass did, or an error if the directory cannot be read or a
+    /// step fails; the steps before it are kept
+    pub fn pass(&self, server: &Mutex<&mut HillServer>) -> Result<LadderPass> {
+        let lock = || server.lock().unwrap_or_else(|e| e.into_inner());
+        let rules = lock().hill().config.rules;
+        let loader = ChampionLoader::new(false).with_rules(rules);
+        let mut champions = Vec::new();
+        for file in loader::champion_files(std::slice::from_ref(&self.champions))? {
+            match loader.load_champion(&file, 1, Some(0)) {
+                Ok(champion) if champion.code.is_empty() => {
+                    warn!("Skipping {}: it has no code", file.display())
+                }
+                Ok(champion) => champions.push(champion),
+                Err(e) => warn!("Skipping {}: {}", file.display(), e),
+            }
+        }
+        let entered: HashSet<String> = champions.iter().map(|c| sha256_hex(&c.code)).collect();
+
+        let retired = lock().update(|hill| {
+            let gone: Vec<String> = hill
+                .members
+                .iter()
+                .filter(|m| !entered.contains(&m.sha256))
+                .map(|m| m.sha256.clone())
+                .collect();
+            Ok(gone
+                .iter()
+                .filter_map(|sha256| hill.retire(sha256))
+                .map(|member| member.name)
+                .collect())
+        })?;
+        let mut pass = LadderPass {
+            retired,
+            ..LadderPass::default()
+        };
+
+        for champion in &champions {
+            let sha256 = sha256_hex(&champion.code);
+            let mut guard = lock();
+            let hill = guard.hill();
+            if hill.members.iter().any(|m| m.sha256 == sha256) || hill.pushed_off.contains(&sha256)
+            {
+                continue;
+            }
+            let submission = guard.update(|hill| hill.submit(&champion.name, &champion.code))?;
+            pass.submissions.push(submission);
+        }
+
+        for _ in 0..self.rematches {
+            match lock().update(|hill| hill.rematch())? {
+                Some(rematch) => pass.rematches.push(rematch),
+                None => break,
+            }
+        }
+        Ok(pass)
+    }
+
+    /// Run passes forever, one every interval
+    ///
+    /// A pass that fails is logged and tried again at the next interval.
+    ///
+    /// # Arguments
+    /// * `server` - Server of the hill
+    /// * `interval` - Time between the start of two passes
+    pub fn run(&self, server: &Mutex<&mut HillServer>, interval: Duration) -> ! {
+        loop {
+            let started = std::time::Instant::now();
+            match self.pass(server) {
+                Ok(pass) => info!("{}", pass.to_string().replace('\n', "; ")),
+                Err(e) => warn!("Ladder pass failed: {}", e),
+            }
+            thread::sleep(interval.saturating_sub(started.elapsed()));
+        }
+    }
+}
+
+impl fmt::Display for LadderPass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = Vec::new();
+        for name in &self.retired {
+            lines.push(format!("{} retired", name));
+        }
+        for submission in &self.submissions {
+            lines.push(submission.to_string());
+        }
+        for rematch in &self.rematches {
+            lines.push(format!("Rematch {}", rematch));
+        }
+        if lines.is_empty() {
+            lines.push("Nothing to play".to_string());
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::GameConfig;
+    use crate::vm::hill::Hill;
+    use crate::vm::loader::write_champion;
+    use std::fs;
+
+    /// Write a .cor file with the given code into a directory
+    fn enter(dir: &Path, name: &str, code: &[u8]) {
+        let mut file = fs::File::create(dir.join(format!("{}.cor", name))).unwrap();
+        write_champion(&mut file, name, "", code).unwrap();
+    }
+
+    /// Create a hill file of the given size in a directory and open it
+    fn server(dir: &Path, size: usize) -> HillServer {
+        let config = GameConfig {
+            max_cycles: 50,
+            ..GameConfig::default()
+        };
+        let path = dir.join("hill.json");
+        Hill::new(size, 2, config).unwrap().write(&path).unwrap();
+        HillServer::open(path).unwrap()
+    }
+
+    #[test]
+    fn test_passes_enter_new_champions_and_rematch() {
+        let dir = tempfile::tempdir().unwrap();
+        let champions = dir.path().join("champions");
+        fs::create_dir(&champions).unwrap();
+        enter(&champions, "Idle", &[0x02; 100]);
+        enter(&champions, "Liver", &[0x01; 100]);
+        let mut server = server(dir.path(), 3);
+        let server = Mutex::new(&mut server);
+        let ladder = Ladder::new(&champions).with_rematches(2);
+
+        let first = ladder.pass(&server).unwrap();
+        assert_eq!(first.submissions.len(), 2);
+        assert_eq!(first.rematches.len(), 2);
+        assert!(first.to_string().contains("Rematch Liver vs Idle: 2-0"));
+
+        let second = ladder.pass(&server).unwrap();
+        assert!(second.submissions.is_empty());
+        assert_eq!(second.rematches.len(), 2);
+
+        // Every step was saved
+        let saved = Hill::read(dir.path().join("hill.json")).unwrap();
+        assert_eq!(saved.rematches, 4);
+        assert_eq!(saved.members, server.lock().unwrap().hill().members);
+    }
+
+    #[test]
+    fn test_passes_prune_retired_and_skip_pushed_off_champions() {
+        let dir = tempfile::tempdir().unwrap();
+        let champions = dir.path().join("champions");
+        fs::create_dir(&champions).unwrap();
+        enter(&champions, "Liver", &[0x01; 100]);
+        enter(&champions, "Idle", &[0x02; 100]);
+        let mut server = server(dir.path(), 1);
+        let server = Mutex::new(&mut server);
+        let ladder = Ladder::new(&champions).with_rematches(0);
+
+        let first = ladder.pass(&server).unwrap();
+        assert_eq!(first.submissions[1].pushed_off.as_deref(), Some("Idle"));
+        assert_eq!(ladder.pass(&server).unwrap().to_string(), "Nothing to play");
+
+        fs::remove_file(champions.join("Liver.cor")).unwrap();
+        let pruned = ladder.pass(&server).unwrap();
+        assert_eq!(pruned.retired, vec!["Liver".to_string()]);
+        assert!(pruned.submissions.is_empty());
+        assert!(server.lock().unwrap().hill().members.is_empty());
+    }
+}
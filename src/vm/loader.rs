@@ -1,16 +1,26 @@
-use crate::constants::MEMORY_SIZE;
 /// Champion loader for Core War .cor files
 ///
 /// This module handles loading and validation of Core War champion files,
-/// including header parsing and memory placement.
+/// including header parsing and memory placement. Champion files may also be
+/// ICWS'94 load files (see [`crate::assembler::loadfile`]).
+use crate::assembler::loadfile;
 use crate::error::{CoreWarError, Result};
-use crate::vm::{Champion, Memory};
+use crate::vm::{Champion, ChampionColor, ChampionMetadata, Memory, Rules, SeededRng};
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
 /// Magic number for Core War executable files
-const COR_MAGIC: u32 = 0xea83f3;
+pub const COR_MAGIC: u32 = 0xea83f3;
+
+/// Bytes the header keeps for the champion name, including its terminating zero
+pub const PROG_NAME_LENGTH: usize = 128;
+
+/// Bytes the header keeps for the champion comment, including its terminating zero
+pub const COMMENT_LENGTH: usize = 128;
+
+/// Size of a .cor header: magic, name, padding, code size, comment, padding
+pub const HEADER_SIZE: usize = 4 + PROG_NAME_LENGTH + 4 + 4 + COMMENT_LENGTH + 4;
 
 /// Core War champion file header structure
 #[derive(Debug, Clone)]
@@ -25,11 +35,130 @@ pub struct ChampionHeader {
     pub comment: String,
 }
 
+impl ChampionHeader {
+    /// Create the header of a champion's code
+    ///
+    /// # Arguments
+    /// * `name` - Champion name
+    /// * `comment` - Champion comment
+    /// * `code_size` - Size of the code in bytes
+    ///
+    /// # Returns
+    /// A header with the magic number set
+    pub fn new(name: &str, comment: &str, code_size: usize) -> Self {
+        Self {
+            magic: COR_MAGIC,
+            name: name.to_string(),
+            code_size: code_size as u32,
+            comment: comment.to_string(),
+        }
+    }
+
+    /// Write the header as it starts a .cor file
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the [`HEADER_SIZE`] bytes
+    ///
+    /// # Returns
+    /// Nothing, or an error if the name or comment is too long or writing fails
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE);
+        bytes.extend_from_slice(&self.magic.to_le_bytes());
+        bytes.extend(padded("Program name", &self.name, PROG_NAME_LENGTH)?);
+        bytes.extend_from_slice(&[0u8; 4]);
+        bytes.extend_from_slice(&self.code_size.to_le_bytes());
+        bytes.extend(padded("Comment", &self.comment, COMMENT_LENGTH)?);
+        bytes.extend_from_slice(&[0u8; 4]);
+
+        writer
+            .write_all(&bytes)
+            .map_err(|e| CoreWarError::champion(format!("Failed to write header: {}", e)))
+    }
+}
+
+/// Write a champion as a .cor file
+///
+/// # Arguments
+/// * `writer` - Destination of the file's contents
+/// * `name` - Champion name
+/// * `comment` - Champion comment
+/// * `code` - The champion's code
+///
+/// # Returns
+/// Nothing, or an error if the name or comment is too long or writing fails
+pub fn write_champion<W: Write>(
+    writer: &mut W,
+    name: &str,
+    comment: &str,
+    code: &[u8],
+) -> Result<()> {
+    ChampionHeader::new(name, comment, code.len()).write_to(writer)?;
+    writer
+        .write_all(code)
+        .map_err(|e| CoreWarError::champion(format!("Failed to write code: {}", e)))
+}
+
+/// Pad a header string with zeros to its field's length
+fn padded(field: &str, text: &str, length: usize) -> Result<Vec<u8>> {
+    // The last byte always stays zero, terminating the string
+    if text.len() >= length {
+        return Err(CoreWarError::InvalidHeader {
+            message: format!("{} too long (max {} characters)", field, length - 1),
+        });
+    }
+    let mut bytes = text.as_bytes().to_vec();
+    bytes.resize(length, 0);
+    Ok(bytes)
+}
+
+/// A champion file left out of a battle because it failed to load
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedChampion {
+    /// Path of the champion file
+    pub path: PathBuf,
+    /// Why the file could not be loaded
+    pub reason: String,
+}
+
+/// Overrides for loading one champion file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChampionOptions {
+    /// Display name overriding the header name
+    pub alias: Option<String>,
+    /// Player number to use as the champion ID
+    pub number: Option<u8>,
+    /// Address to load the champion's code at
+    pub load_address: Option<usize>,
+}
+
+impl ChampionOptions {
+    /// Set the display name overriding the header name
+    pub fn with_alias(mut self, alias: Option<String>) -> Self {
+        self.alias = alias;
+        self
+    }
+
+    /// Set the player number to use as the champion ID
+    pub fn with_number(mut self, number: Option<u8>) -> Self {
+        self.number = number;
+        self
+    }
+
+    /// Set the address to load the champion's code at
+    pub fn with_load_address(mut self, load_address: Option<usize>) -> Self {
+        self.load_address = load_address;
+        self
+    }
+}
+
 /// Champion loader for .cor files
 #[derive(Debug)]
 pub struct ChampionLoader {
     /// Whether to perform strict validation
     strict_validation: bool,
+    /// Arena rules champions are loaded for
+    rules: Rules,
 }
 
 impl ChampionLoader {
@@ -41,14 +170,29 @@ impl ChampionLoader {
     /// # Returns
     /// A new ChampionLoader instance
     pub fn new(strict_validation: bool) -> Self {
-        Self { strict_validation }
+        Self {
+            strict_validation,
+            rules: Rules::default(),
+        }
     }
 
-    /// Load a champion from a .cor file
+    /// Load champions for a non-standard arena
     ///
     /// # Arguments
-    /// * `path` - Path to the .cor file
-    /// * `champion_id` - ID to assign to the champion (1-4)
+    /// * `rules` - Arena rules bounding memory size and champion count
+    ///
+    /// # Returns
+    /// The loader configured with the given rules
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Load a champion from a .cor file or a load file
+    ///
+    /// # Arguments
+    /// * `path` - Path to the .cor file or load file
+    /// * `champion_id` - ID to assign to the champion (1 to the champion limit)
     /// * `load_address` - Optional custom load address
     ///
     /// # Returns
@@ -60,25 +204,84 @@ impl ChampionLoader {
         load_address: Option<usize>,
     ) -> Result<Champion> {
         let path = path.as_ref();
+        self.check_champion_id(champion_id)?;
 
-        // Validate champion ID
-        if champion_id == 0 || champion_id > 4 {
-            return Err(CoreWarError::champion(format!(
-                "Invalid champion ID: {} (must be 1-4)",
-                champion_id
-            )));
+        // Open and read the file
+        let mut bytes = Vec::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut bytes))
+            .map_err(|e| {
+                CoreWarError::champion(format!("Failed to open {}: {}", path.display(), e))
+            })?;
+
+        // Load files are read as if they had been assembled
+        if loadfile::is_load_file(&bytes) {
+            let text = String::from_utf8_lossy(&bytes).into_owned();
+            bytes = match loadfile::import(&text) {
+                Ok(bytes) => bytes,
+                Err(CoreWarError::Assembly { mut diagnostics }) => {
+                    for diagnostic in &mut diagnostics {
+                        diagnostic.file = Some(path.display().to_string());
+                    }
+                    return Err(CoreWarError::Assembly { diagnostics });
+                }
+                Err(error) => return Err(error),
+            };
         }
 
-        // Open and read the file
-        let mut file = File::open(path).map_err(|e| {
-            CoreWarError::champion(format!("Failed to open {}: {}", path.display(), e))
-        })?;
+        let mut champion = self.load_champion_from_bytes(&bytes, champion_id, load_address)?;
+        champion.source = Some(path.to_path_buf());
+        Ok(champion)
+    }
+
+    /// Load a champion from the contents of a .cor file
+    ///
+    /// Metadata in a trailer after the code is read too; see
+    /// [`crate::vm::metadata`].
+    ///
+    /// # Arguments
+    /// * `bytes` - Header followed by code, as written by the assembler
+    /// * `champion_id` - ID to assign to the champion (1 to the champion limit)
+    /// * `load_address` - Optional custom load address
+    ///
+    /// # Returns
+    /// A loaded Champion instance
+    pub fn load_champion_from_bytes(
+        &self,
+        bytes: &[u8],
+        champion_id: u8,
+        load_address: Option<usize>,
+    ) -> Result<Champion> {
+        let mut champion = self.load_champion_from_reader(bytes, champion_id, load_address)?;
+        let trailer = &bytes[HEADER_SIZE + champion.code_size()..];
+        champion.metadata = ChampionMetadata::from_trailer(trailer);
+        Ok(champion)
+    }
+
+    /// Load a champion from a reader producing a .cor file
+    ///
+    /// Reading stops after the code, so trailing data is left in the reader.
+    ///
+    /// # Arguments
+    /// * `reader` - Source of the header followed by code
+    /// * `champion_id` - ID to assign to the champion (1 to the champion limit)
+    /// * `load_address` - Optional custom load address
+    ///
+    /// # Returns
+    /// A loaded Champion instance
+    pub fn load_champion_from_reader<R: Read>(
+        &self,
+        mut reader: R,
+        champion_id: u8,
+        load_address: Option<usize>,
+    ) -> Result<Champion> {
+        self.check_champion_id(champion_id)?;
 
         // Parse the header
-        let header = self.parse_header(&mut file)?;
+        let header = self.parse_header(&mut reader)?;
 
         // Read the code
-        let code = self.read_code(&mut file, header.code_size)?;
+        let code = self.read_code(&mut reader, header.code_size)?;
 
         // Validate code size
         if code.len() != header.code_size as usize {
@@ -92,17 +295,20 @@ impl ChampionLoader {
         // Determine load address
         let final_load_address = match load_address {
             Some(addr) => {
-                if addr >= MEMORY_SIZE {
+                if addr >= self.rules.memory_size {
                     return Err(CoreWarError::champion(format!(
                         "Load address {} is outside memory bounds ({})",
-                        addr, MEMORY_SIZE
+                        addr, self.rules.memory_size
                     )));
                 }
                 addr
             }
             None => {
                 // Use default placement
-                let addresses = Memory::calculate_placement_addresses(4);
+                let addresses = Memory::calculate_placement_addresses(
+                    self.rules.max_champions,
+                    self.rules.memory_size,
+                );
                 addresses[(champion_id - 1) as usize]
             }
         };
@@ -119,6 +325,17 @@ impl ChampionLoader {
         Ok(champion)
     }
 
+    /// Check that a champion ID is within the arena's champion slots
+    fn check_champion_id(&self, champion_id: u8) -> Result<()> {
+        if champion_id == 0 || champion_id as usize > self.rules.max_champions {
+            return Err(CoreWarError::champion(format!(
+                "Invalid champion ID: {} (must be 1-{})",
+                champion_id, self.rules.max_champions
+            )));
+        }
+        Ok(())
+    }
+
     /// Load multiple champions from files
     ///
     /// # Arguments
@@ -132,54 +349,129 @@ impl ChampionLoader {
         file_paths: &[P],
         custom_addresses: Option<&[usize]>,
     ) -> Result<Vec<Champion>> {
-        if file_paths.is_empty() {
+        self.check_champion_count(file_paths.len())?;
+        let addresses = self.placement_addresses(file_paths.len(), custom_addresses)?;
+
+        let mut champions = Vec::new();
+
+        // Load each champion
+        for (i, path) in file_paths.iter().enumerate() {
+            let champion_id = (i + 1) as u8;
+            let load_address = addresses[i];
+
+            let champion = self.load_champion(path, champion_id, Some(load_address))?;
+            champions.push(champion);
+        }
+
+        // Validate that champions don't overlap in memory
+        self.validate_champion_placement(&champions)?;
+
+        Ok(champions)
+    }
+
+    /// Load multiple champions, leaving out files that fail to load
+    ///
+    /// The remaining champions are numbered from 1 in the order given and
+    /// placed as if only they had been provided. Custom addresses still apply
+    /// to the file at the same position.
+    ///
+    /// # Arguments
+    /// * `file_paths` - Paths to the .cor files
+    /// * `custom_addresses` - Optional custom load addresses
+    ///
+    /// # Returns
+    /// The loaded champions and the skipped files, or an error if none loaded
+    pub fn load_valid_champions<P: AsRef<Path>>(
+        &self,
+        file_paths: &[P],
+        custom_addresses: Option<&[usize]>,
+    ) -> Result<(Vec<Champion>, Vec<SkippedChampion>)> {
+        self.check_champion_count(file_paths.len())?;
+        if let Some(addrs) = custom_addresses {
+            self.placement_addresses(file_paths.len(), Some(addrs))?;
+        }
+
+        let mut loaded = Vec::new();
+        let mut skipped = Vec::new();
+
+        for (i, path) in file_paths.iter().enumerate() {
+            let champion_id = (loaded.len() + 1) as u8;
+            let load_address = custom_addresses.map(|addrs| addrs[i]);
+
+            match self.load_champion(path, champion_id, load_address) {
+                Ok(champion) => loaded.push(champion),
+                Err(e) => skipped.push(SkippedChampion {
+                    path: path.as_ref().to_path_buf(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        if loaded.is_empty() {
+            return Err(CoreWarError::champion(format!(
+                "None of the {} champion files could be loaded",
+                file_paths.len()
+            )));
+        }
+
+        if custom_addresses.is_none() {
+            let addresses = self.placement_addresses(loaded.len(), None)?;
+            for (champion, address) in loaded.iter_mut().zip(addresses) {
+                champion.load_address = address;
+            }
+        }
+
+        self.validate_champion_placement(&loaded)?;
+
+        Ok((loaded, skipped))
+    }
+
+    /// Check that a battle has between one and the maximum number of champions
+    pub(crate) fn check_champion_count(&self, count: usize) -> Result<()> {
+        if count == 0 {
             return Err(CoreWarError::champion(
                 "No champion files provided".to_string(),
             ));
         }
 
-        if file_paths.len() > 4 {
+        if count > self.rules.max_champions {
             return Err(CoreWarError::champion(format!(
-                "Too many champions: {} (maximum is 4)",
-                file_paths.len()
+                "Too many champions: {} (maximum is {})",
+                count, self.rules.max_champions
             )));
         }
 
-        let mut champions = Vec::new();
+        Ok(())
+    }
 
+    /// Get the load address of each champion
+    fn placement_addresses(
+        &self,
+        count: usize,
+        custom_addresses: Option<&[usize]>,
+    ) -> Result<Vec<usize>> {
         // Calculate optimal placement addresses if not provided
-        let addresses = match custom_addresses {
+        match custom_addresses {
             Some(addrs) => {
-                if addrs.len() != file_paths.len() {
+                if addrs.len() != count {
                     return Err(CoreWarError::champion(
                         "Number of custom addresses must match number of champion files"
                             .to_string(),
                     ));
                 }
-                addrs.to_vec()
+                Ok(addrs.to_vec())
             }
-            None => Memory::calculate_placement_addresses(file_paths.len()),
-        };
-
-        // Load each champion
-        for (i, path) in file_paths.iter().enumerate() {
-            let champion_id = (i + 1) as u8;
-            let load_address = addresses[i];
-
-            let champion = self.load_champion(path, champion_id, Some(load_address))?;
-            champions.push(champion);
+            None => Ok(Memory::calculate_placement_addresses(
+                count,
+                self.rules.memory_size,
+            )),
         }
-
-        // Validate that champions don't overlap in memory
-        self.validate_champion_placement(&champions)?;
-
-        Ok(champions)
     }
 
-    /// Parse the champion header from a file
-    fn parse_header(&self, file: &mut File) -> Result<ChampionHeader> {
+    /// Parse the champion header from a reader
+    fn parse_header<R: Read>(&self, reader: &mut R) -> Result<ChampionHeader> {
         // Read magic number (4 bytes)
-        let magic = self.read_u32_le(file)?;
+        let magic = self.read_u32_le(reader)?;
         if magic != COR_MAGIC {
             return Err(CoreWarError::InvalidHeader {
                 message: format!(
@@ -190,30 +482,30 @@ impl ChampionLoader {
         }
 
         // Read program name (128 bytes)
-        let name = self.read_string(file, 128)?;
+        let name = self.read_string(reader, PROG_NAME_LENGTH)?;
 
         // Skip padding (4 bytes)
-        file.seek(SeekFrom::Current(4))
+        reader.read_exact(&mut [0u8; 4])
             .map_err(|e| CoreWarError::champion(format!("Failed to skip padding: {}", e)))?;
 
         // Read code size (4 bytes)
-        let code_size = self.read_u32_le(file)?;
+        let code_size = self.read_u32_le(reader)?;
 
         // Validate code size
-        if self.strict_validation && code_size > MEMORY_SIZE as u32 {
+        if self.strict_validation && code_size as usize > self.rules.memory_size {
             return Err(CoreWarError::InvalidHeader {
                 message: format!(
                     "Code size {} exceeds memory size {}",
-                    code_size, MEMORY_SIZE
+                    code_size, self.rules.memory_size
                 ),
             });
         }
 
         // Read comment (128 bytes)
-        let comment = self.read_string(file, 128)?;
+        let comment = self.read_string(reader, COMMENT_LENGTH)?;
 
         // Skip final padding (4 bytes)
-        file.seek(SeekFrom::Current(4))
+        reader.read_exact(&mut [0u8; 4])
             .map_err(|e| CoreWarError::champion(format!("Failed to skip final padding: {}", e)))?;
 
         Ok(ChampionHeader {
@@ -224,26 +516,26 @@ impl ChampionLoader {
         })
     }
 
-    /// Read the champion code from a file
-    fn read_code(&self, file: &mut File, code_size: u32) -> Result<Vec<u8>> {
+    /// Read the champion code from a reader
+    fn read_code<R: Read>(&self, reader: &mut R, code_size: u32) -> Result<Vec<u8>> {
         let mut code = vec![0u8; code_size as usize];
-        file.read_exact(&mut code)
+        reader.read_exact(&mut code)
             .map_err(|e| CoreWarError::champion(format!("Failed to read champion code: {}", e)))?;
         Ok(code)
     }
 
-    /// Read a 32-bit little-endian integer from file
-    fn read_u32_le(&self, file: &mut File) -> Result<u32> {
+    /// Read a 32-bit little-endian integer from a reader
+    fn read_u32_le<R: Read>(&self, reader: &mut R) -> Result<u32> {
         let mut buffer = [0u8; 4];
-        file.read_exact(&mut buffer)
+        reader.read_exact(&mut buffer)
             .map_err(|e| CoreWarError::champion(format!("Failed to read u32: {}", e)))?;
         Ok(u32::from_le_bytes(buffer))
     }
 
-    /// Read a null-terminated string from file
-    fn read_string(&self, file: &mut File, max_length: usize) -> Result<String> {
+    /// Read a null-terminated string from a reader
+    fn read_string<R: Read>(&self, reader: &mut R, max_length: usize) -> Result<String> {
         let mut buffer = vec![0u8; max_length];
-        file.read_exact(&mut buffer)
+        reader.read_exact(&mut buffer)
             .map_err(|e| CoreWarError::champion(format!("Failed to read string: {}", e)))?;
 
         // Find null terminator
@@ -255,20 +547,19 @@ impl ChampionLoader {
     }
 
     /// Validate that champions don't overlap in memory
-    fn validate_champion_placement(&self, champions: &[Champion]) -> Result<()> {
+    pub(crate) fn validate_champion_placement(&self, champions: &[Champion]) -> Result<()> {
         for (i, champion1) in champions.iter().enumerate() {
             for (j, champion2) in champions.iter().enumerate() {
                 if i >= j {
                     continue; // Skip self and already checked pairs
                 }
 
-                let start1 = champion1.load_address;
-                let end1 = start1 + champion1.code_size();
-                let start2 = champion2.load_address;
-                let end2 = start2 + champion2.code_size();
+                let (start1, len1) = (champion1.load_address, champion1.code_size());
+                let (start2, len2) = (champion2.load_address, champion2.code_size());
+                let (end1, end2) = (start1 + len1, start2 + len2);
 
                 // Check for overlap (considering circular memory)
-                if self.ranges_overlap(start1, end1, start2, end2) {
+                if self.ranges_overlap(start1, len1, start2, len2) {
                     return Err(CoreWarError::champion(format!(
                         "Champions {} and {} overlap in memory: [{}-{}] and [{}-{}]",
                         champion1.name, champion2.name, start1, end1, start2, end2
@@ -280,10 +571,74 @@ impl ChampionLoader {
     }
 
     /// Check if two memory ranges overlap (considering circular addressing)
-    fn ranges_overlap(&self, start1: usize, end1: usize, start2: usize, end2: usize) -> bool {
-        // Simple linear overlap check
-        // TODO: Handle circular memory overlap properly
-        !(end1 <= start2 || end2 <= start1)
+    fn ranges_overlap(&self, start1: usize, len1: usize, start2: usize, len2: usize) -> bool {
+        let size = self.rules.memory_size;
+        // Either range starts inside the other, measured forward around the core
+        (start2 + size - start1 % size) % size < len1
+            || (start1 + size - start2 % size) % size < len2
+    }
+
+    /// Give champions the player numbers requested for them
+    ///
+    /// Champions without a requested number take the lowest free numbers, in
+    /// order. The champions are then sorted by number.
+    ///
+    /// # Arguments
+    /// * `champions` - Loaded champions, whose IDs are replaced
+    /// * `numbers` - Requested number of the champion at the same position, if any
+    ///
+    /// # Returns
+    /// `Ok(())` if successful, error if a number is out of range or repeated
+    pub(crate) fn assign_champion_numbers(
+        &self,
+        champions: &mut [Champion],
+        numbers: &[Option<u8>],
+    ) -> Result<()> {
+        let requested: Vec<u8> = numbers.iter().flatten().copied().collect();
+        for (i, &number) in requested.iter().enumerate() {
+            self.check_champion_id(number)?;
+            if requested[..i].contains(&number) {
+                return Err(CoreWarError::champion(format!(
+                    "Player number {} is given to more than one champion",
+                    number
+                )));
+            }
+        }
+
+        let mut free = (1..=self.rules.max_champions as u8).filter(|n| !requested.contains(n));
+        for (i, champion) in champions.iter_mut().enumerate() {
+            let number = match numbers.get(i).copied().flatten() {
+                Some(number) => number,
+                None => free.next().ok_or_else(|| {
+                    CoreWarError::champion("No player numbers left for the remaining champions")
+                })?,
+            };
+            champion.id = number;
+            champion.color = ChampionColor::for_id(number);
+        }
+        champions.sort_by_key(|champion| champion.id);
+        Ok(())
+    }
+
+    /// Move champions to seeded random addresses with a minimum gap between them
+    ///
+    /// # Arguments
+    /// * `champions` - Loaded champions, whose load addresses are replaced
+    /// * `seed` - Seed for the placement
+    ///
+    /// # Returns
+    /// `Ok(())` if successful, error if the champions do not fit with the gaps
+    pub(crate) fn scatter_champions(&self, champions: &mut [Champion], seed: u64) -> Result<()> {
+        let sizes: Vec<usize> = champions.iter().map(Champion::code_size).collect();
+        let addresses = Memory::random_placement_addresses(
+            &sizes,
+            self.rules.memory_size,
+            &mut SeededRng::new(seed),
+        )?;
+        for (champion, address) in champions.iter_mut().zip(addresses) {
+            champion.load_address = address;
+        }
+        self.validate_champion_placement(champions)
     }
 
     /// Get information about a .cor file without fully loading it
@@ -308,43 +663,50 @@ impl Default for ChampionLoader {
     }
 }
 
+/// List champion files, expanding directories
+///
+/// Directories are searched for `.cor` files, not recursively.
+///
+/// # Arguments
+/// * `paths` - Champion files, or directories holding them
+///
+/// # Returns
+/// Every champion file, directories' contents in name order
+pub fn champion_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(path)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<_>>()?;
+            entries.retain(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "cor"));
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::MEMORY_SIZE;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    /// Build the contents of a test .cor file
+    fn cor_bytes(name: &str, comment: &str, code: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_champion(&mut bytes, name, comment, code).unwrap();
+        bytes
+    }
+
     /// Create a test .cor file
     fn create_test_cor_file(name: &str, comment: &str, code: &[u8]) -> NamedTempFile {
         let mut file = NamedTempFile::new().unwrap();
-
-        // Write header
-        file.write_all(&COR_MAGIC.to_le_bytes()).unwrap(); // Magic
-
-        // Write name (128 bytes)
-        let mut name_bytes = [0u8; 128];
-        let name_src = name.as_bytes();
-        name_bytes[..name_src.len()].copy_from_slice(name_src);
-        file.write_all(&name_bytes).unwrap();
-
-        // Write padding (4 bytes)
-        file.write_all(&[0u8; 4]).unwrap();
-
-        // Write code size
-        file.write_all(&(code.len() as u32).to_le_bytes()).unwrap();
-
-        // Write comment (128 bytes)
-        let mut comment_bytes = [0u8; 128];
-        let comment_src = comment.as_bytes();
-        comment_bytes[..comment_src.len()].copy_from_slice(comment_src);
-        file.write_all(&comment_bytes).unwrap();
-
-        // Write final padding (4 bytes)
-        file.write_all(&[0u8; 4]).unwrap();
-
-        // Write code
-        file.write_all(code).unwrap();
-
+        file.write_all(&cor_bytes(name, comment, code)).unwrap();
         file.flush().unwrap();
         file
     }
@@ -431,4 +793,141 @@ mod tests {
         // Champions should be placed at different addresses
         assert_ne!(champions[0].load_address, champions[1].load_address);
     }
+
+    #[test]
+    fn test_load_valid_champions_skips_invalid_files() {
+        let loader = ChampionLoader::new(true);
+        let code = vec![0x01, 0x02, 0x03, 0x04];
+        let file1 = create_test_cor_file("Champ1", "First champion", &code);
+        let mut broken = NamedTempFile::new().unwrap();
+        broken.write_all(b"not a champion").unwrap();
+        let file3 = create_test_cor_file("Champ3", "Third champion", &code);
+
+        let paths = [file1.path(), broken.path(), file3.path()];
+        assert!(loader.load_champions(&paths, None).is_err());
+
+        let (champions, skipped) = loader.load_valid_champions(&paths, None).unwrap();
+        assert_eq!(champions.len(), 2);
+        assert_eq!(champions[1].name, "Champ3");
+        assert_eq!(champions[1].id, 2);
+        assert_eq!(champions[1].load_address, MEMORY_SIZE / 2);
+
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, broken.path());
+        assert!(!skipped[0].reason.is_empty());
+
+        // Nothing left to battle with
+        assert!(loader.load_valid_champions(&[broken.path()], None).is_err());
+    }
+
+    #[test]
+    fn test_loader_with_custom_rules() {
+        let rules = Rules {
+            memory_size: 1024,
+            idx_mod: 128,
+            max_champions: 2,
+            ..Default::default()
+        };
+        let loader = ChampionLoader::new(true).with_rules(rules);
+        let code = vec![0x01, 0x02, 0x03, 0x04];
+        let file1 = create_test_cor_file("Champ1", "First champion", &code);
+        let file2 = create_test_cor_file("Champ2", "Second champion", &code);
+        let file3 = create_test_cor_file("Champ3", "Third champion", &code);
+
+        // Load addresses are bounded by the smaller core
+        assert!(loader.load_champion(file1.path(), 1, Some(2000)).is_err());
+
+        let champions = loader
+            .load_champions(&[file1.path(), file2.path()], None)
+            .unwrap();
+        assert_eq!(champions[1].load_address, 512);
+
+        // Only two champions are allowed by these rules
+        assert!(
+            loader
+                .load_champions(&[file1.path(), file2.path(), file3.path()], None)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_placement_overlap_wraps_around_memory() {
+        let loader = ChampionLoader::new(false);
+        let size = loader.rules.memory_size;
+        let champion = |id, address| {
+            Champion::new(id, format!("C{}", id), String::new(), vec![0x01; 10], address)
+        };
+
+        // The first champion wraps past the end into the second one
+        assert!(
+            loader
+                .validate_champion_placement(&[champion(1, size - 5), champion(2, 3)])
+                .is_err()
+        );
+        assert!(
+            loader
+                .validate_champion_placement(&[champion(1, size - 5), champion(2, 5)])
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_load_champion_from_bytes_and_reader() {
+        let loader = ChampionLoader::new(true);
+        let bytes = cor_bytes("InMemory", "No temp file", &[0x01, 0x02, 0x03]);
+
+        let champion = loader.load_champion_from_bytes(&bytes, 2, Some(64)).unwrap();
+        assert_eq!(champion.id, 2);
+        assert_eq!(champion.name, "InMemory");
+        assert_eq!(champion.comment, "No temp file");
+        assert_eq!(champion.code, vec![0x01, 0x02, 0x03]);
+        assert_eq!(champion.load_address, 64);
+
+        // Reading stops after the code, leaving the rest of the stream
+        let mut stream = bytes.clone();
+        stream.extend_from_slice(&[0xAA, 0xBB]);
+        let mut reader = std::io::Cursor::new(stream);
+        let champion = loader.load_champion_from_reader(&mut reader, 1, None).unwrap();
+        assert_eq!(champion.code, vec![0x01, 0x02, 0x03]);
+        assert_eq!(reader.position() as usize, bytes.len());
+
+        assert!(loader.load_champion_from_bytes(&bytes[..bytes.len() - 1], 1, None).is_err());
+        assert!(loader.load_champion_from_bytes(&bytes[..100], 1, None).is_err());
+        assert!(loader.load_champion_from_bytes(&bytes, 0, None).is_err());
+    }
+
+    #[test]
+    fn test_written_header_reads_back() {
+        let bytes = cor_bytes("Writer", "Round trip", &[0x01, 0x40]);
+        assert_eq!(bytes.len(), HEADER_SIZE + 2);
+        assert_eq!(bytes[..4], COR_MAGIC.to_le_bytes());
+
+        let header = ChampionLoader::new(true)
+            .parse_header(&mut bytes.as_slice())
+            .unwrap();
+        assert_eq!(header.name, "Writer");
+        assert_eq!(header.comment, "Round trip");
+        assert_eq!(header.code_size, 2);
+
+        // The terminating zero must fit
+        let name = "x".repeat(PROG_NAME_LENGTH);
+        let error = write_champion(&mut Vec::new(), &name, "", &[]).unwrap_err();
+        assert!(error.to_string().contains("Program name too long"));
+        assert!(write_champion(&mut Vec::new(), &name[1..], "", &[]).is_ok());
+    }
+
+    #[test]
+    fn test_champion_files_expand_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("b.cor"), []).unwrap();
+        std::fs::write(dir.path().join("a.cor"), []).unwrap();
+        std::fs::write(dir.path().join("a.s"), "").unwrap();
+
+        let extra = PathBuf::from("extra.cor");
+        let files = champion_files(&[dir.path().to_path_buf(), extra.clone()]).unwrap();
+        assert_eq!(
+            files,
+            vec![dir.path().join("a.cor"), dir.path().join("b.cor"), extra]
+        );
+    }
 }
@@ -3,12 +3,163 @@
 /// This module implements the 6KB circular memory space with modulo addressing
 /// as specified in the Core War standard. All memory operations are bounds-checked
 /// and use modulo arithmetic for circular addressing.
-use crate::constants::{IDX_MOD, MEMORY_SIZE};
 use crate::error::{CoreWarError, Result};
+use crate::vm::decode::{DecodeCache, DecodedInstruction};
+use crate::vm::{Rules, SeededRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// Bytes per line of a zaz-layout dump
+pub const ZAZ_DUMP_WIDTH: usize = 32;
+
+/// How champions without a custom address are placed in memory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    /// Evenly spaced from address 0, in load order
+    #[default]
+    Spread,
+    /// Scattered at seeded random addresses with a minimum gap between champions
+    Random,
+}
+
+impl FromStr for Placement {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "spread" => Ok(Placement::Spread),
+            "random" => Ok(Placement::Random),
+            _ => Err(CoreWarError::game_state(format!(
+                "Unknown placement '{}' (expected spread or random)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Placement::Spread => write!(f, "spread"),
+            Placement::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// Layout of a memory dump
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DumpFormat {
+    /// Annotated hex with process information; the start of memory on the
+    /// terminal, the full core in a file
+    #[default]
+    Hex,
+    /// The full core in the layout of the 42/zaz reference VM's `-dump`
+    Zaz,
+    /// The full core as raw bytes
+    Raw,
+    /// The full core with the owner of every cell, as JSON
+    Json,
+}
+
+impl FromStr for DumpFormat {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "hex" => Ok(DumpFormat::Hex),
+            "zaz" => Ok(DumpFormat::Zaz),
+            "raw" => Ok(DumpFormat::Raw),
+            "json" => Ok(DumpFormat::Json),
+            _ => Err(CoreWarError::game_state(format!(
+                "Unknown dump format '{}' (expected hex, zaz, raw or json)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for DumpFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DumpFormat::Hex => write!(f, "hex"),
+            DumpFormat::Zaz => write!(f, "zaz"),
+            DumpFormat::Raw => write!(f, "raw"),
+            DumpFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// A contiguous range of memory that differs between two points in time
+///
+/// Produced by [`Memory::diff`]; a cell counts as changed when its byte or
+/// its owner differs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemDelta {
+    /// Address of the first changed cell
+    pub start: usize,
+    /// Bytes of the range before the change
+    pub old_bytes: Vec<u8>,
+    /// Bytes of the range after the change
+    pub new_bytes: Vec<u8>,
+    /// Owners of the range before the change
+    pub old_owners: Vec<Option<u8>>,
+    /// Owners of the range after the change
+    pub new_owners: Vec<Option<u8>>,
+}
+
+impl MemDelta {
+    /// Number of cells in the range
+    pub fn len(&self) -> usize {
+        self.new_bytes.len()
+    }
+
+    /// Check whether the range is empty; ranges built by a diff never are
+    pub fn is_empty(&self) -> bool {
+        self.new_bytes.is_empty()
+    }
+
+    /// Address one past the last changed cell
+    pub fn end(&self) -> usize {
+        self.start + self.len()
+    }
+}
+
+impl fmt::Display for MemDelta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let owners = |owners: &[Option<u8>]| {
+            owners
+                .iter()
+                .map(|o| o.map_or("-".to_string(), |id| id.to_string()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        write!(
+            f,
+            "{:04X}..{:04X}: {} -> {} (owners {} -> {})",
+            self.start,
+            self.end(),
+            hex(&self.old_bytes),
+            hex(&self.new_bytes),
+            owners(&self.old_owners),
+            owners(&self.new_owners)
+        )
+    }
+}
 
 /// Core War virtual machine memory
 ///
-/// The memory is a circular buffer of 6KB (6144 bytes) with modulo addressing.
+/// The memory is a circular buffer (6KB by default) with modulo addressing.
 /// All memory operations are performed using modulo arithmetic to ensure
 /// circular behavior.
 #[derive(Debug, Clone)]
@@ -17,27 +168,81 @@ pub struct Memory {
     data: Vec<u8>,
     /// Track ownership of memory locations for visualization
     ownership: Vec<Option<u8>>, // Champion ID that owns this memory location
+    /// Index modulo for indirect addressing
+    idx_mod: usize,
+    /// Instructions decoded since the bytes under them last changed
+    decoded: DecodeCache,
 }
 
 impl Memory {
     /// Create a new memory instance with all bytes initialized to zero
     pub fn new() -> Self {
+        Self::with_rules(&Rules::default())
+    }
+
+    /// Create a zeroed memory instance sized for the given rules
+    ///
+    /// # Arguments
+    /// * `rules` - The arena rules providing the memory size and IDX_MOD
+    ///
+    /// # Returns
+    /// A new Memory instance
+    pub fn with_rules(rules: &Rules) -> Self {
         Self {
-            data: vec![0; MEMORY_SIZE],
-            ownership: vec![None; MEMORY_SIZE],
+            data: vec![0; rules.memory_size],
+            ownership: vec![None; rules.memory_size],
+            idx_mod: rules.idx_mod,
+            decoded: DecodeCache::default(),
+        }
+    }
+
+    /// Rebuild memory from saved contents and ownership
+    ///
+    /// # Arguments
+    /// * `rules` - The arena rules the memory was saved under
+    /// * `data` - Memory contents
+    /// * `ownership` - Owner of each memory location
+    ///
+    /// # Returns
+    /// The Memory instance, or an error if the buffers do not match the rules
+    pub fn from_parts(rules: &Rules, data: Vec<u8>, ownership: Vec<Option<u8>>) -> Result<Self> {
+        if data.len() != rules.memory_size || ownership.len() != rules.memory_size {
+            return Err(CoreWarError::memory(format!(
+                "Expected {} bytes of memory and ownership, got {} and {}",
+                rules.memory_size,
+                data.len(),
+                ownership.len()
+            )));
         }
+
+        Ok(Self {
+            data,
+            ownership,
+            idx_mod: rules.idx_mod,
+            decoded: DecodeCache::default(),
+        })
     }
 
     /// Get the size of the memory
     pub fn size(&self) -> usize {
-        MEMORY_SIZE
+        self.data.len()
+    }
+
+    /// Get the raw memory contents
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get the owner of every memory location
+    pub fn ownership(&self) -> &[Option<u8>] {
+        &self.ownership
     }
 
     /// Normalize an address using modulo arithmetic
     ///
     /// This ensures all memory addresses wrap around the circular memory space.
     fn normalize_address(&self, address: usize) -> usize {
-        address % MEMORY_SIZE
+        address % self.size()
     }
 
     /// Normalize an index using IDX_MOD
@@ -45,7 +250,7 @@ impl Memory {
     /// This is used for indirect addressing calculations.
     #[allow(dead_code)]
     fn normalize_index(&self, index: usize) -> usize {
-        index % IDX_MOD
+        index % self.idx_mod
     }
 
     /// Read a single byte from memory
@@ -68,13 +273,31 @@ impl Memory {
     /// * `owner` - Optional champion ID that owns this memory location
     pub fn write_byte(&mut self, address: usize, value: u8, owner: Option<u8>) {
         let normalized = self.normalize_address(address);
+        if self.data[normalized] != value {
+            self.decoded.invalidate(normalized);
+        }
         self.data[normalized] = value;
         if let Some(owner_id) = owner {
             self.ownership[normalized] = Some(owner_id);
         }
     }
 
-    /// Read a 32-bit word from memory (4 bytes, little-endian)
+    /// Decode the instruction at an address
+    ///
+    /// Decoded instructions are cached until a write changes one of their
+    /// bytes, so executing the same code again skips decoding it.
+    ///
+    /// # Arguments
+    /// * `address` - The memory address of the opcode
+    ///
+    /// # Returns
+    /// The decoded instruction
+    pub fn decode(&mut self, address: usize) -> DecodedInstruction {
+        let normalized = self.normalize_address(address);
+        self.decoded.get(&self.data, normalized)
+    }
+
+    /// Read a 32-bit word from memory (4 bytes, big-endian, as the assembler encodes them)
     ///
     /// # Arguments
     /// * `address` - The memory address to read from
@@ -87,25 +310,25 @@ impl Memory {
         let b2 = self.read_byte(address + 2) as u32;
         let b3 = self.read_byte(address + 3) as u32;
 
-        // Little-endian byte order
-        b0 | (b1 << 8) | (b2 << 16) | (b3 << 24)
+        // Big-endian byte order
+        (b0 << 24) | (b1 << 16) | (b2 << 8) | b3
     }
 
-    /// Write a 32-bit word to memory (4 bytes, little-endian)
+    /// Write a 32-bit word to memory (4 bytes, big-endian)
     ///
     /// # Arguments
     /// * `address` - The memory address to write to
     /// * `value` - The 32-bit word value to write
     /// * `owner` - Optional champion ID that owns this memory location
     pub fn write_word(&mut self, address: usize, value: u32, owner: Option<u8>) {
-        // Little-endian byte order
-        self.write_byte(address, (value & 0xFF) as u8, owner);
-        self.write_byte(address + 1, ((value >> 8) & 0xFF) as u8, owner);
-        self.write_byte(address + 2, ((value >> 16) & 0xFF) as u8, owner);
-        self.write_byte(address + 3, ((value >> 24) & 0xFF) as u8, owner);
+        // Big-endian byte order
+        self.write_byte(address, ((value >> 24) & 0xFF) as u8, owner);
+        self.write_byte(address + 1, ((value >> 16) & 0xFF) as u8, owner);
+        self.write_byte(address + 2, ((value >> 8) & 0xFF) as u8, owner);
+        self.write_byte(address + 3, (value & 0xFF) as u8, owner);
     }
 
-    /// Read a 16-bit halfword from memory (2 bytes, little-endian)
+    /// Read a 16-bit halfword from memory (2 bytes, big-endian)
     ///
     /// # Arguments
     /// * `address` - The memory address to read from
@@ -116,20 +339,20 @@ impl Memory {
         let b0 = self.read_byte(address) as u16;
         let b1 = self.read_byte(address + 1) as u16;
 
-        // Little-endian byte order
-        b0 | (b1 << 8)
+        // Big-endian byte order
+        (b0 << 8) | b1
     }
 
-    /// Write a 16-bit halfword to memory (2 bytes, little-endian)
+    /// Write a 16-bit halfword to memory (2 bytes, big-endian)
     ///
     /// # Arguments
     /// * `address` - The memory address to write to
     /// * `value` - The 16-bit halfword value to write
     /// * `owner` - Optional champion ID that owns this memory location
     pub fn write_halfword(&mut self, address: usize, value: u16, owner: Option<u8>) {
-        // Little-endian byte order
-        self.write_byte(address, (value & 0xFF) as u8, owner);
-        self.write_byte(address + 1, ((value >> 8) & 0xFF) as u8, owner);
+        // Big-endian byte order
+        self.write_byte(address, ((value >> 8) & 0xFF) as u8, owner);
+        self.write_byte(address + 1, (value & 0xFF) as u8, owner);
     }
 
     /// Load champion code into memory at the specified address
@@ -142,11 +365,11 @@ impl Memory {
     /// # Returns
     /// `Ok(())` if successful, or an error if the code doesn't fit
     pub fn load_code(&mut self, address: usize, code: &[u8], champion_id: u8) -> Result<()> {
-        if code.len() > MEMORY_SIZE {
+        if code.len() > self.size() {
             return Err(CoreWarError::memory(format!(
                 "Code size {} exceeds memory size {}",
                 code.len(),
-                MEMORY_SIZE
+                self.size()
             )));
         }
 
@@ -193,29 +416,96 @@ impl Memory {
             }
         }
 
-        if length % 16 != 0 {
+        if !length.is_multiple_of(16) {
             result.push('\n');
         }
 
         result
     }
 
+    /// Dump the whole core in the layout of the 42/zaz reference VM
+    ///
+    /// Each line holds 32 bytes as `0x0000 : 0B 68 01 ... ` in uppercase hex,
+    /// every byte followed by a space as the reference prints it.
+    ///
+    /// # Returns
+    /// The dump, one newline-terminated line per 32 bytes
+    pub fn dump_zaz(&self) -> String {
+        let mut result = String::new();
+        for (line, bytes) in self.data.chunks(ZAZ_DUMP_WIDTH).enumerate() {
+            result.push_str(&format!("0x{:04X} : ", line * ZAZ_DUMP_WIDTH));
+            for byte in bytes {
+                result.push_str(&format!("{:02X} ", byte));
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Compare this memory with a later state of it
+    ///
+    /// Adjacent changed cells are merged into one range. Ranges do not wrap
+    /// around the end of memory. If the sizes differ, cells past the end of
+    /// the smaller memory compare as zero and unowned.
+    ///
+    /// # Arguments
+    /// * `other` - The memory to compare against, taken as the newer state
+    ///
+    /// # Returns
+    /// The changed ranges in address order, empty if nothing changed
+    pub fn diff(&self, other: &Memory) -> Vec<MemDelta> {
+        let cell = |memory: &Memory, address: usize| {
+            (
+                memory.data.get(address).copied().unwrap_or(0),
+                memory.ownership.get(address).copied().flatten(),
+            )
+        };
+
+        let mut deltas: Vec<MemDelta> = Vec::new();
+        for address in 0..self.size().max(other.size()) {
+            let (old_byte, old_owner) = cell(self, address);
+            let (new_byte, new_owner) = cell(other, address);
+            if old_byte == new_byte && old_owner == new_owner {
+                continue;
+            }
+
+            match deltas.last_mut() {
+                Some(delta) if delta.end() == address => {
+                    delta.old_bytes.push(old_byte);
+                    delta.new_bytes.push(new_byte);
+                    delta.old_owners.push(old_owner);
+                    delta.new_owners.push(new_owner);
+                }
+                _ => deltas.push(MemDelta {
+                    start: address,
+                    old_bytes: vec![old_byte],
+                    new_bytes: vec![new_byte],
+                    old_owners: vec![old_owner],
+                    new_owners: vec![new_owner],
+                }),
+            }
+        }
+        deltas
+    }
+
     /// Clear all memory and ownership information
     pub fn clear(&mut self) {
         self.data.fill(0);
         self.ownership.fill(None);
+        self.decoded.clear();
     }
 
     /// Calculate the optimal placement addresses for multiple champions
     ///
     /// # Arguments
     /// * `champion_count` - Number of champions to place
+    /// * `memory_size` - Size of the memory the champions are placed in
     ///
     /// # Returns
     /// Vector of starting addresses for each champion
-    pub fn calculate_placement_addresses(champion_count: usize) -> Vec<usize> {
+    pub fn calculate_placement_addresses(champion_count: usize, memory_size: usize) -> Vec<usize> {
         let mut addresses = Vec::new();
-        let spacing = MEMORY_SIZE / champion_count;
+        let spacing = memory_size / champion_count;
 
         for i in 0..champion_count {
             addresses.push(i * spacing);
@@ -223,6 +513,79 @@ impl Memory {
 
         addresses
     }
+
+    /// Get the smallest gap random placement leaves between two champions
+    ///
+    /// Each champion is guaranteed a quarter of its even share of memory as
+    /// empty space before the next champion's code.
+    ///
+    /// # Arguments
+    /// * `champion_count` - Number of champions to place
+    /// * `memory_size` - Size of the memory the champions are placed in
+    pub fn min_placement_gap(champion_count: usize, memory_size: usize) -> usize {
+        memory_size / (champion_count.max(1) * 4)
+    }
+
+    /// Choose random, non-overlapping addresses for champions
+    ///
+    /// Champions are put in a random order around the circular memory,
+    /// starting at a random address, and the space left over after their code
+    /// and the minimum gaps is shared out randomly between the gaps. Code may
+    /// wrap past the end of memory.
+    ///
+    /// # Arguments
+    /// * `code_sizes` - Code size of each champion, in load order
+    /// * `memory_size` - Size of the memory the champions are placed in
+    /// * `rng` - Generator to draw every random choice from
+    ///
+    /// # Returns
+    /// The starting address of each champion, in load order, or an error if
+    /// the champions and gaps do not fit
+    pub fn random_placement_addresses(
+        code_sizes: &[usize],
+        memory_size: usize,
+        rng: &mut SeededRng,
+    ) -> Result<Vec<usize>> {
+        let count = code_sizes.len();
+        let min_gap = Self::min_placement_gap(count, memory_size);
+        let needed = code_sizes.iter().sum::<usize>() + count * min_gap;
+        if needed > memory_size {
+            return Err(CoreWarError::memory(format!(
+                "{} champions need {} bytes with gaps of {} but memory has {}",
+                count, needed, min_gap, memory_size
+            )));
+        }
+        let spare = memory_size - needed;
+
+        // Fisher-Yates shuffle of the order champions appear in memory
+        let mut order: Vec<usize> = (0..count).collect();
+        for i in (1..count).rev() {
+            order.swap(i, rng.next_below(i + 1));
+        }
+
+        // Cut the spare space at random points; champion k gets the slice before it
+        let mut cuts: Vec<usize> = (0..count).map(|_| rng.next_below(spare + 1)).collect();
+        cuts.sort_unstable();
+
+        let mut addresses = vec![0; count];
+        let mut address = rng.next_below(memory_size);
+        let mut previous_cut = 0;
+        for (&champion, &cut) in order.iter().zip(&cuts) {
+            address += cut - previous_cut;
+            previous_cut = cut;
+            addresses[champion] = address % memory_size;
+            address += code_sizes[champion] + min_gap;
+        }
+
+        Ok(addresses)
+    }
+}
+
+impl Hash for Memory {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.ownership.hash(state);
+    }
 }
 
 impl Default for Memory {
@@ -234,6 +597,7 @@ impl Default for Memory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::MEMORY_SIZE;
 
     #[test]
     fn test_memory_creation() {
@@ -257,11 +621,11 @@ mod tests {
         memory.write_word(100, 0x12345678, Some(1));
         assert_eq!(memory.read_word(100), 0x12345678);
 
-        // Test individual bytes (little-endian)
-        assert_eq!(memory.read_byte(100), 0x78);
-        assert_eq!(memory.read_byte(101), 0x56);
-        assert_eq!(memory.read_byte(102), 0x34);
-        assert_eq!(memory.read_byte(103), 0x12);
+        // Test individual bytes (big-endian)
+        assert_eq!(memory.read_byte(100), 0x12);
+        assert_eq!(memory.read_byte(101), 0x34);
+        assert_eq!(memory.read_byte(102), 0x56);
+        assert_eq!(memory.read_byte(103), 0x78);
     }
 
     #[test]
@@ -295,11 +659,100 @@ mod tests {
 
     #[test]
     fn test_placement_addresses() {
-        let addresses = Memory::calculate_placement_addresses(4);
+        let addresses = Memory::calculate_placement_addresses(4, MEMORY_SIZE);
         assert_eq!(addresses.len(), 4);
         assert_eq!(addresses[0], 0);
         assert_eq!(addresses[1], MEMORY_SIZE / 4);
         assert_eq!(addresses[2], MEMORY_SIZE / 2);
         assert_eq!(addresses[3], 3 * MEMORY_SIZE / 4);
     }
+
+    #[test]
+    fn test_random_placement_keeps_minimum_gap() {
+        let sizes = [100, 682, 50, 1000];
+        let min_gap = Memory::min_placement_gap(sizes.len(), MEMORY_SIZE);
+        assert_eq!(min_gap, 384);
+
+        for seed in 0..50 {
+            let addresses =
+                Memory::random_placement_addresses(&sizes, MEMORY_SIZE, &mut SeededRng::new(seed))
+                    .unwrap();
+            assert_eq!(
+                Memory::random_placement_addresses(&sizes, MEMORY_SIZE, &mut SeededRng::new(seed))
+                    .unwrap(),
+                addresses
+            );
+
+            let mut placed: Vec<(usize, usize)> = addresses.iter().copied().zip(sizes).collect();
+            placed.sort_unstable();
+            for (i, &(start, size)) in placed.iter().enumerate() {
+                let next_start = placed[(i + 1) % placed.len()].0;
+                let gap = (next_start + MEMORY_SIZE - (start + size) % MEMORY_SIZE) % MEMORY_SIZE;
+                assert!(gap >= min_gap, "seed {} leaves a gap of {}", seed, gap);
+            }
+        }
+
+        assert!(
+            Memory::random_placement_addresses(&[3000, 3000], MEMORY_SIZE, &mut SeededRng::new(0))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_custom_core_size() {
+        let rules = Rules {
+            memory_size: 1024,
+            idx_mod: 128,
+            ..Default::default()
+        };
+        let mut memory = Memory::with_rules(&rules);
+        assert_eq!(memory.size(), 1024);
+
+        memory.write_byte(1024, 0x42, Some(1)); // Should wrap to 0
+        assert_eq!(memory.read_byte(0), 0x42);
+        assert!(memory.load_code(0, &[0; 2048], 1).is_err());
+    }
+
+    #[test]
+    fn test_zaz_dump_covers_the_whole_core() {
+        let rules = Rules {
+            memory_size: 64,
+            idx_mod: 32,
+            ..Default::default()
+        };
+        let mut memory = Memory::with_rules(&rules);
+        memory.load_code(30, &[0x0B, 0x68, 0xAB], 1).unwrap();
+
+        let dump = memory.dump_zaz();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], format!("0x0000 : {}0B 68 ", "00 ".repeat(30)));
+        assert_eq!(lines[1], format!("0x0020 : AB {}", "00 ".repeat(31)));
+        assert!(dump.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_diff_merges_adjacent_changes() {
+        let mut before = Memory::new();
+        before.write_byte(12, 0x07, Some(2));
+        let mut after = before.clone();
+        assert!(before.diff(&after).is_empty());
+
+        after.write_halfword(10, 0xAABB, Some(1));
+        after.write_byte(12, 0x07, Some(1));
+        after.write_byte(100, 0xCC, None);
+
+        let deltas = before.diff(&after);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].start, 10);
+        assert_eq!(deltas[0].end(), 13);
+        assert_eq!(deltas[0].old_bytes, vec![0x00, 0x00, 0x07]);
+        assert_eq!(deltas[0].new_bytes, vec![0xAA, 0xBB, 0x07]);
+        assert_eq!(deltas[0].old_owners, vec![None, None, Some(2)]);
+        assert_eq!(deltas[0].new_owners, vec![Some(1); 3]);
+        assert_eq!(
+            deltas[1].to_string(),
+            "0064..0065: 00 -> CC (owners - -> -)"
+        );
+    }
 }
@@ -0,0 +1,216 @@
+/// Champion metadata beyond the .cor header
+///
+/// The header has room for a name and a comment only. A champion's author,
+/// version and build date travel in a trailer after its code, which readers
+/// that predate it never see, as they stop at the end of the code:
+///
+/// ```text
+/// "META"  length (4 bytes, little-endian)  "author=Alice\nversion=1.2\n..."
+/// ```
+///
+/// The trailer holds one `key=value` line per field that is set. Unknown
+/// keys are skipped and a damaged trailer reads as no metadata, so whatever
+/// follows the code never stops a champion from loading.
+use crate::error::{CoreWarError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// First bytes of the trailer
+pub const TRAILER_MARKER: &[u8; 4] = b"META";
+
+/// Environment variable fixing the build date, for reproducible builds
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// Optional facts about a champion, set with `.author` and `.version`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChampionMetadata {
+    /// Who wrote the champion
+    pub author: Option<String>,
+    /// Version of the champion, in any format
+    pub version: Option<String>,
+    /// Day the champion was assembled, as YYYY-MM-DD
+    pub build_date: Option<String>,
+}
+
+impl ChampionMetadata {
+    /// Check whether no field is set
+    pub fn is_empty(&self) -> bool {
+        self.fields().all(|(_, value)| value.is_none())
+    }
+
+    /// Write the metadata as the trailer of a .cor file
+    ///
+    /// Nothing is written when no field is set. Line breaks in values
+    /// become spaces.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination, positioned after the champion's code
+    ///
+    /// # Returns
+    /// Nothing, or an error if writing fails
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut body = String::new();
+        for (key, value) in self.fields() {
+            if let Some(value) = value {
+                body.push_str(&format!("{}={}\n", key, value.replace(['\n', '\r'], " ")));
+            }
+        }
+
+        let mut bytes = TRAILER_MARKER.to_vec();
+        bytes.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(body.as_bytes());
+        writer
+            .write_all(&bytes)
+            .map_err(|e| CoreWarError::champion(format!("Failed to write metadata: {}", e)))
+    }
+
+    /// Read the metadata trailer that follows a champion's code
+    ///
+    /// # Arguments
+    /// * `bytes` - Everything after the code
+    ///
+    /// # Returns
+    /// The metadata, empty if there is no trailer or it is damaged
+    pub fn from_trailer(bytes: &[u8]) -> Self {
+        let mut metadata = Self::default();
+        let Some(length) = trailer_length(bytes) else {
+            return metadata;
+        };
+        let Ok(body) = std::str::from_utf8(&bytes[8..length]) else {
+            return metadata;
+        };
+
+        for (key, value) in body.lines().filter_map(|line| line.split_once('=')) {
+            let value = Some(value.to_string());
+            match key {
+                "author" => metadata.author = value,
+                "version" => metadata.version = value,
+                "built" => metadata.build_date = value,
+                _ => {}
+            }
+        }
+        metadata
+    }
+
+    /// Get each field with its trailer key
+    fn fields(&self) -> impl Iterator<Item = (&'static str, Option<&String>)> {
+        [
+            ("author", self.author.as_ref()),
+            ("version", self.version.as_ref()),
+            ("built", self.build_date.as_ref()),
+        ]
+        .into_iter()
+    }
+}
+
+impl fmt::Display for ChampionMetadata {
+    /// Describe the metadata in one line, e.g. `by Alice, version 1.2, built 2026-10-15`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(author) = &self.author {
+            parts.push(format!("by {}", author));
+        }
+        if let Some(version) = &self.version {
+            parts.push(format!("version {}", version));
+        }
+        if let Some(date) = &self.build_date {
+            parts.push(format!("built {}", date));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Get the size of the trailer at the start of some bytes
+///
+/// # Arguments
+/// * `bytes` - Everything after a champion's code
+///
+/// # Returns
+/// The size of the trailer including its marker and length, or None if the
+/// bytes do not start with a complete trailer
+pub fn trailer_length(bytes: &[u8]) -> Option<usize> {
+    let rest = bytes.strip_prefix(TRAILER_MARKER)?;
+    let (length, body) = rest.split_first_chunk::<4>()?;
+    let length = u32::from_le_bytes(*length) as usize;
+    (length <= body.len()).then_some(8 + length)
+}
+
+/// Get the date to stamp assembled champions with
+///
+/// # Returns
+/// Today's date in UTC as YYYY-MM-DD, or the date of `SOURCE_DATE_EPOCH`
+/// when it is set, so rebuilding a champion gives the same bytes
+pub fn build_date() -> String {
+    let seconds = std::env::var(SOURCE_DATE_EPOCH)
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        });
+    date_of(seconds)
+}
+
+/// Format the UTC date of a Unix time as YYYY-MM-DD
+pub fn date_of(seconds: u64) -> String {
+    // Civil date from days since 1970-01-01, counting in 400-year eras
+    // that start on March 1st so leap days fall at the end of a year
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailer_round_trip() {
+        let metadata = ChampionMetadata {
+            author: Some("Alice\nSmith".to_string()),
+            version: Some("1.2".to_string()),
+            build_date: None,
+        };
+        let mut bytes = Vec::new();
+        metadata.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes[..4], *TRAILER_MARKER);
+        assert_eq!(trailer_length(&bytes), Some(bytes.len()));
+
+        let read = ChampionMetadata::from_trailer(&bytes);
+        assert_eq!(read.author.as_deref(), Some("Alice Smith"));
+        assert_eq!(read.version.as_deref(), Some("1.2"));
+        assert_eq!(read.to_string(), "by Alice Smith, version 1.2");
+
+        // No trailer, a cut-off one, and nothing to write
+        assert!(ChampionMetadata::from_trailer(&[0xAA, 0xBB]).is_empty());
+        assert!(ChampionMetadata::from_trailer(&bytes[..bytes.len() - 1]).is_empty());
+        let mut empty = Vec::new();
+        ChampionMetadata::default().write_to(&mut empty).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(date_of(0), "1970-01-01");
+        assert_eq!(date_of(11_016 * 86_400), "2000-02-29");
+        assert_eq!(date_of(20_741 * 86_400 + 86_399), "2026-10-15");
+    }
+}
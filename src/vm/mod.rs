@@ -1,5 +1,27 @@
+#[doc(hidden)]
+pub mod arena;
+pub mod assertion;
+pub mod bench;
+pub mod breakpoint;
+pub mod builder;
+pub mod bundle;
+pub mod clock;
+pub mod coverage;
+pub mod dap;
+pub mod debugger;
+pub mod decode;
 pub mod engine;
+pub mod events;
+pub mod evolve;
+pub mod exectrace;
+pub mod heat;
+pub mod hill;
+pub mod hillnet;
+#[doc(hidden)]
+pub mod history;
+pub mod http;
 pub mod instruction;
+pub mod ladder;
 pub mod loader;
 /// Virtual Machine implementation for Core War
 ///
@@ -9,26 +31,89 @@ pub mod loader;
 /// - Instruction set and execution
 /// - Champion loading and management
 pub mod memory;
+pub mod metadata;
+pub mod montecarlo;
+pub mod mutator;
+pub mod ndjson;
+pub mod pacing;
 pub mod process;
+pub mod profile;
+pub mod project;
+#[cfg(feature = "event-protocol")]
+pub mod protocol;
+#[cfg(feature = "pspace")]
+pub mod pspace;
+#[cfg(feature = "event-protocol")]
+pub mod recording;
+pub mod reference;
+pub mod rematch;
+pub(crate) mod resources;
+pub mod rng;
+pub mod rules;
+#[doc(hidden)]
 pub mod scheduler;
+pub mod serve;
+pub mod service;
+pub mod snapshot;
+pub mod stats;
+#[doc(hidden)]
+pub mod stress;
+pub mod tiebreak;
+pub mod tournament;
+pub mod validate;
+pub mod watcher;
+pub mod websocket;
 
 // Re-export commonly used types
-pub use engine::{GameConfig, GameEngine, GameState, GameStats};
-pub use instruction::{Instruction, Parameter, ParameterType};
-pub use loader::{ChampionHeader, ChampionLoader};
-pub use memory::Memory;
+pub use assertion::{Assertion, AssertionOutcome};
+pub use breakpoint::{Breakpoint, BreakpointHit, WatchedWrite};
+pub use builder::GameEngineBuilder;
+pub use bundle::ResultsBundle;
+pub use coverage::ChampionCoverage;
+pub use decode::DecodedInstruction;
+pub use engine::{GameConfig, GameEngine, GameState, GameStats, MemoryDump, TickOutcome};
+pub use events::{GameEvent, GameObserver};
+pub use exectrace::{ExecutionTrace, TraceFilter, TraceFormat};
+pub use instruction::{Instruction, InstructionSpec, Parameter, ParameterType};
+pub use loader::{ChampionHeader, ChampionLoader, ChampionOptions, SkippedChampion};
+pub use memory::{DumpFormat, MemDelta, Memory, Placement};
+pub use metadata::ChampionMetadata;
+pub use mutator::ChampionMutator;
 pub use process::Process;
+#[cfg(feature = "event-protocol")]
+pub use recording::Recording;
+pub use rematch::RematchSeries;
+pub use resources::ResourceUsage;
+pub use rng::SeededRng;
+pub use rules::{LiveReset, Rules, RulesPreset};
+#[doc(hidden)]
 pub use scheduler::Scheduler;
+pub use snapshot::GameSnapshot;
+pub use tiebreak::{TieBreak, TieBreaker, TieBreakers};
+pub use tournament::{Match, MatchResult, Tournament};
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Champion data structure for loaded .cor files
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Champion {
-    /// Champion ID (1-4)
+    /// Champion ID, from 1 up to the arena's champion limit
     pub id: u8,
     /// Champion name from header
     pub name: String,
+    /// Display name chosen by the user, overriding the header name
+    #[serde(default)]
+    pub alias: Option<String>,
     /// Champion comment from header
     pub comment: String,
+    /// Author, version and build date from the trailer after the code
+    #[serde(default)]
+    pub metadata: ChampionMetadata,
+    /// The .cor file the champion was loaded from, if any
+    #[serde(default)]
+    pub source: Option<PathBuf>,
     /// Champion bytecode
     pub code: Vec<u8>,
     /// Loading address in memory
@@ -39,37 +124,100 @@ pub struct Champion {
     pub live_count: u32,
     /// Champion color for visualization
     pub color: ChampionColor,
+    /// Private storage for `ldp` and `stp`, kept across the rounds of a match;
+    /// empty without the `pspace` feature
+    #[serde(default)]
+    pub pspace: Vec<i32>,
 }
 
 /// Colors for champion visualization
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// The first four champions get the classic named colors; later champions
+/// get generated colors so any number of champions stay distinguishable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ChampionColor {
     Red,
     Blue,
     Green,
     Yellow,
+    /// Generated color for champions beyond the fourth
+    Rgb(u8, u8, u8),
 }
 
-impl Champion {
-    /// Create a new champion from bytecode
-    pub fn new(id: u8, name: String, comment: String, code: Vec<u8>, load_address: usize) -> Self {
-        let color = match id {
-            1 => ChampionColor::Red,
+impl ChampionColor {
+    /// Get the color for a champion ID
+    ///
+    /// IDs 1-4 map to the named colors. Later IDs step around the hue wheel
+    /// by the golden angle, starting at magenta, and alternate brightness so
+    /// neighbouring IDs differ even when their hues come close.
+    ///
+    /// # Arguments
+    /// * `id` - Champion ID
+    ///
+    /// # Returns
+    /// The champion's color
+    pub fn for_id(id: u8) -> Self {
+        match id {
+            0 | 1 => ChampionColor::Red,
             2 => ChampionColor::Blue,
             3 => ChampionColor::Green,
             4 => ChampionColor::Yellow,
-            _ => ChampionColor::Red,
-        };
+            _ => {
+                let step = u32::from(id - 5);
+                let hue = (300 + step * 137) % 360;
+                let value = if step.is_multiple_of(2) { 255 } else { 190 };
+                let (r, g, b) = hue_to_rgb(hue, value);
+                ChampionColor::Rgb(r, g, b)
+            }
+        }
+    }
+
+    /// Get the color's red, green and blue components
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            ChampionColor::Red => (255, 0, 0),
+            ChampionColor::Blue => (0, 0, 255),
+            ChampionColor::Green => (0, 255, 0),
+            ChampionColor::Yellow => (255, 255, 0),
+            ChampionColor::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+}
+
+/// Convert a fully saturated hue in degrees to RGB at the given brightness
+fn hue_to_rgb(hue: u32, value: u8) -> (u8, u8, u8) {
+    let value = u32::from(value);
+    let rising = (value * (hue % 60) / 60) as u8;
+    let falling = (value - value * (hue % 60) / 60) as u8;
+    let value = value as u8;
+    match hue / 60 {
+        0 => (value, rising, 0),
+        1 => (falling, value, 0),
+        2 => (0, value, rising),
+        3 => (0, falling, value),
+        4 => (rising, 0, value),
+        _ => (value, 0, falling),
+    }
+}
+
+impl Champion {
+    /// Create a new champion from bytecode
+    pub fn new(id: u8, name: String, comment: String, code: Vec<u8>, load_address: usize) -> Self {
+        let color = ChampionColor::for_id(id);
 
         Self {
             id,
             name,
+            alias: None,
             comment,
+            metadata: ChampionMetadata::default(),
+            source: None,
             code,
             load_address,
             process_count: 1, // Initially one process
             live_count: 0,
             color,
+            pspace: Vec::new(),
         }
     }
 
@@ -77,6 +225,14 @@ impl Champion {
     pub fn code_size(&self) -> usize {
         self.code.len()
     }
+
+    /// Get the name to show in the UI and reports
+    ///
+    /// # Returns
+    /// The alias if one was given, otherwise the header name
+    pub fn display_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
     
     /// Set the champion's color (for demo purposes)
     pub fn with_color(mut self, color: ChampionColor) -> Self {
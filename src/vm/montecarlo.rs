@@ -0,0 +1,246 @@
+/// Monte Carlo win rates of a matchup
+///
+/// One battle says little about a warrior: where it lands in memory can
+/// decide the result. [`run`] plays the same matchup many times, each battle
+/// with the next of the [`rematch_seeds`], and summarizes how often each
+/// champion wins, how often the battle is drawn and how long battles last.
+/// With random placement every seed puts the champions somewhere else.
+use crate::error::{CoreWarError, Result};
+use crate::vm::rematch::{MatchResult, play_battle, rematch_seeds};
+use crate::vm::{GameConfig, GameEngine};
+use std::fmt;
+
+/// z-score of the 95% confidence intervals in summaries
+const Z_95: f64 = 1.96;
+
+/// Results of a matchup played many times
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonteCarloSummary {
+    /// ID and display name of each champion
+    pub champions: Vec<(u8, String)>,
+    /// Every battle played, in order
+    pub battles: Vec<MatchResult>,
+}
+
+impl MonteCarloSummary {
+    /// Number of battles a champion won
+    pub fn wins(&self, champion_id: u8) -> usize {
+        self.battles
+            .iter()
+            .filter(|b| matches!(b.winner, Some((id, _)) if id == champion_id))
+            .count()
+    }
+
+    /// Number of drawn battles
+    pub fn draws(&self) -> usize {
+        self.battles.iter().filter(|b| b.winner.is_none()).count()
+    }
+
+    /// Share of battles a champion won, from 0 to 1
+    pub fn win_rate(&self, champion_id: u8) -> f64 {
+        self.rate(self.wins(champion_id))
+    }
+
+    /// Share of battles drawn, from 0 to 1
+    pub fn draw_rate(&self) -> f64 {
+        self.rate(self.draws())
+    }
+
+    /// Mean battle length in cycles
+    pub fn average_cycles(&self) -> f64 {
+        if self.battles.is_empty() {
+            return 0.0;
+        }
+        let total: u64 = self.battles.iter().map(|b| u64::from(b.cycles)).sum();
+        total as f64 / self.battles.len() as f64
+    }
+
+    /// Sample standard deviation of the battle length in cycles
+    pub fn cycles_std_dev(&self) -> f64 {
+        let n = self.battles.len();
+        if n < 2 {
+            return 0.0;
+        }
+        let mean = self.average_cycles();
+        let squares: f64 = self
+            .battles
+            .iter()
+            .map(|b| (f64::from(b.cycles) - mean).powi(2))
+            .sum();
+        (squares / (n - 1) as f64).sqrt()
+    }
+
+    /// 95% Wilson score interval of the true rate behind `count` of the battles
+    ///
+    /// # Returns
+    /// The lower and upper bounds, from 0 to 1
+    pub fn confidence_interval(&self, count: usize) -> (f64, f64) {
+        let n = self.battles.len() as f64;
+        if n == 0.0 {
+            return (0.0, 1.0);
+        }
+        let p = count as f64 / n;
+        let z2 = Z_95 * Z_95;
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+
+    /// Divide by the number of battles
+    fn rate(&self, count: usize) -> f64 {
+        if self.battles.is_empty() {
+            0.0
+        } else {
+            count as f64 / self.battles.len() as f64
+        }
+    }
+
+    /// Write one row of the outcome table
+    fn write_row(&self, f: &mut fmt::Formatter, label: &str, count: usize) -> fmt::Result {
+        let (low, high) = self.confidence_interval(count);
+        writeln!(
+            f,
+            "{:<24} {:>6} {:>7.1}%   {:>5.1}% - {:>5.1}%",
+            label,
+            count,
+            self.rate(count) * 100.0,
+            low * 100.0,
+            high * 100.0
+        )
+    }
+}
+
+impl fmt::Display for MonteCarloSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Battles: {}", self.battles.len())?;
+        writeln!(
+            f,
+            "{:<24} {:>6} {:>8}   {:>15}",
+            "Outcome", "Count", "Rate", "95% CI"
+        )?;
+        for (id, name) in &self.champions {
+            self.write_row(f, &format!("{} wins ({})", name, id), self.wins(*id))?;
+        }
+        self.write_row(f, "Draw", self.draws())?;
+
+        let min = self.battles.iter().map(|b| b.cycles).min().unwrap_or(0);
+        let max = self.battles.iter().map(|b| b.cycles).max().unwrap_or(0);
+        write!(
+            f,
+            "Battle length: {:.1} cycles on average (sd {:.1}, min {}, max {})",
+            self.average_cycles(),
+            self.cycles_std_dev(),
+            min,
+            max
+        )
+    }
+}
+
+/// Play a matchup many times and summarize the results
+///
+/// Battle `i` runs with the `i`th of the [`rematch_seeds`] of the
+/// configuration's seed, so the whole run replays exactly.
+///
+/// # Arguments
+/// * `config` - Configuration every battle runs with, apart from the seed
+/// * `repeats` - Number of battles to play
+/// * `load` - Loads the champions into each fresh engine
+///
+/// # Returns
+/// The summary, or an error if `repeats` is 0 or a battle fails
+pub fn run<F>(config: &GameConfig, repeats: u32, mut load: F) -> Result<MonteCarloSummary>
+where
+    F: FnMut(&mut GameEngine) -> Result<()>,
+{
+    if repeats == 0 {
+        return Err(CoreWarError::game_state(
+            "A Monte Carlo run needs at least 1 battle",
+        ));
+    }
+
+    let mut champions = Vec::new();
+    let mut battles = Vec::with_capacity(repeats as usize);
+    for seed in rematch_seeds(config.seed).take(repeats as usize) {
+        let (engine, result) = play_battle(config, seed, &mut load)?;
+        if champions.is_empty() {
+            champions = engine
+                .champions()
+                .iter()
+                .map(|c| (c.id, c.display_name().to_string()))
+                .collect();
+        }
+        battles.push(result);
+    }
+
+    Ok(MonteCarloSummary { champions, battles })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::Champion;
+
+    fn battle(cycles: u32, winner: Option<u8>) -> MatchResult {
+        MatchResult {
+            seed: 0,
+            cycles,
+            winner: winner.map(|id| (id, format!("C{}", id))),
+        }
+    }
+
+    #[test]
+    fn test_summary_statistics() {
+        let summary = MonteCarloSummary {
+            champions: vec![(1, "C1".to_string()), (2, "C2".to_string())],
+            battles: vec![
+                battle(100, Some(1)),
+                battle(200, Some(1)),
+                battle(300, Some(2)),
+                battle(400, None),
+            ],
+        };
+        assert_eq!(summary.wins(1), 2);
+        assert_eq!(summary.win_rate(2), 0.25);
+        assert_eq!(summary.draw_rate(), 0.25);
+        assert_eq!(summary.average_cycles(), 250.0);
+        assert!((summary.cycles_std_dev() - 129.1).abs() < 0.1);
+
+        let (low, high) = summary.confidence_interval(2);
+        assert!(low < 0.5 && high > 0.5);
+        assert!(low > 0.0 && high < 1.0);
+        assert_eq!(summary.confidence_interval(0).0, 0.0);
+
+        let text = summary.to_string();
+        assert!(text.starts_with("Battles: 4"));
+        assert!(text.contains("C1 wins (1)"));
+        assert!(text.ends_with("min 100, max 400)"));
+    }
+
+    #[test]
+    fn test_run_plays_every_seed() {
+        let config = GameConfig {
+            max_cycles: 200,
+            seed: 3,
+            ..GameConfig::default()
+        };
+        let load = |engine: &mut GameEngine| {
+            let mut bomber = vec![0x04; 60];
+            bomber[0] = 0x01;
+            engine.install_champions(vec![
+                Champion::new(1, "Bomber".to_string(), String::new(), bomber, 0),
+                Champion::new(2, "Liver".to_string(), String::new(), vec![0x01; 60], 3072),
+            ])
+        };
+        let summary = run(&config, 5, load).unwrap();
+
+        assert_eq!(summary.battles.len(), 5);
+        let seeds: Vec<u64> = summary.battles.iter().map(|b| b.seed).collect();
+        assert_eq!(seeds, rematch_seeds(3).take(5).collect::<Vec<_>>());
+        assert_eq!(summary.champions[0], (1, "Bomber".to_string()));
+        assert_eq!(
+            summary.wins(1) + summary.wins(2) + summary.draws(),
+            summary.battles.len()
+        );
+        assert!(run(&config, 0, load).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+/// Load-time transformations of champion code
+///
+/// A [`ChampionMutator`] rewrites a champion's bytecode before it is placed
+/// in memory. Mutators make it possible to test how robust a warrior is
+/// against corrupted copies of itself, or to run adversarial loading
+/// experiments. They draw randomness from a generator seeded with the
+/// battle's seed, so a mutated battle replays exactly.
+use crate::error::{CoreWarError, Result};
+use crate::vm::{Instruction, ParameterType, SeededRng};
+use std::fmt;
+
+/// `ld %0, r1`: seven bytes that only load a register
+pub const NOP: [u8; 7] = [0x02, 0x90, 0x00, 0x00, 0x00, 0x00, 0x01];
+
+/// A transformation applied to champion code at load time
+pub trait ChampionMutator: fmt::Debug + Send {
+    /// Short description for logs, e.g. `bitflip=3`
+    fn describe(&self) -> String;
+
+    /// Rewrite a champion's code
+    ///
+    /// # Arguments
+    /// * `code` - The champion's bytecode, changed in place
+    /// * `rng` - Generator to draw every random choice from
+    fn mutate(&self, code: &mut Vec<u8>, rng: &mut SeededRng);
+}
+
+/// Flip randomly chosen bits anywhere in the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitFlip {
+    /// Number of bits to flip; the same bit may be picked more than once
+    pub flips: usize,
+}
+
+impl ChampionMutator for BitFlip {
+    fn describe(&self) -> String {
+        format!("bitflip={}", self.flips)
+    }
+
+    fn mutate(&self, code: &mut Vec<u8>, rng: &mut SeededRng) {
+        if code.is_empty() {
+            return;
+        }
+        for _ in 0..self.flips {
+            let bit = rng.next_below(code.len() * 8);
+            code[bit / 8] ^= 1 << (bit % 8);
+        }
+    }
+}
+
+/// Insert [`NOP`] instructions at randomly chosen instruction boundaries
+///
+/// Everything after an inserted NOP shifts, so relative jumps and offsets
+/// that cross it are skewed, as they would be in a badly linked copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InjectNops {
+    /// Number of NOPs to insert
+    pub count: usize,
+}
+
+impl ChampionMutator for InjectNops {
+    fn describe(&self) -> String {
+        format!("nops={}", self.count)
+    }
+
+    fn mutate(&self, code: &mut Vec<u8>, rng: &mut SeededRng) {
+        let mut boundaries = decode(code).starts;
+        boundaries.push(code.len());
+        for _ in 0..self.count {
+            let at = boundaries[rng.next_below(boundaries.len())];
+            code.splice(at..at, NOP);
+            // Boundaries after the insertion moved; the NOP adds one of its own
+            for boundary in boundaries.iter_mut().filter(|b| **b > at) {
+                *boundary += NOP.len();
+            }
+            boundaries.push(at + NOP.len());
+        }
+    }
+}
+
+/// Replace every direct operand with a random value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RandomizeImmediates;
+
+impl ChampionMutator for RandomizeImmediates {
+    fn describe(&self) -> String {
+        "immediates".to_string()
+    }
+
+    fn mutate(&self, code: &mut Vec<u8>, rng: &mut SeededRng) {
+        for (offset, parameter_type, size) in decode(code).operands {
+            if parameter_type == ParameterType::Direct {
+                let value = rng.next_u64().to_le_bytes();
+                code[offset..offset + size].copy_from_slice(&value[..size]);
+            }
+        }
+    }
+}
+
+/// A mutator with the champion it is limited to, if any
+#[derive(Debug)]
+pub struct TargetedMutator {
+    /// Champion whose code is mutated, or None for every champion
+    pub champion_id: Option<u8>,
+    /// The transformation to apply
+    pub mutator: Box<dyn ChampionMutator>,
+}
+
+impl TargetedMutator {
+    /// Check whether this mutator applies to a champion
+    pub fn applies_to(&self, champion_id: u8) -> bool {
+        self.champion_id.is_none_or(|id| id == champion_id)
+    }
+}
+
+impl fmt::Display for TargetedMutator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mutator.describe())?;
+        if let Some(id) = self.champion_id {
+            write!(f, "@{}", id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a mutator from its command-line form
+///
+/// Accepted forms are `bitflip=N`, `nops=N` and `immediates`, optionally
+/// followed by `@ID` to mutate only that champion.
+///
+/// # Arguments
+/// * `spec` - Mutator text, e.g. `bitflip=3@2`
+///
+/// # Returns
+/// The mutator, or an error if the text is not a known mutator
+pub fn parse(spec: &str) -> Result<TargetedMutator> {
+    let invalid =
+        |reason: &str| CoreWarError::game_state(format!("Invalid mutator '{}': {}", spec, reason));
+
+    let (body, champion_id) = match spec.trim().rsplit_once('@') {
+        Some((body, id)) => {
+            let id = id
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|&id| id > 0)
+                .ok_or_else(|| invalid("champion must be a positive ID"))?;
+            (body.trim(), Some(id))
+        }
+        None => (spec.trim(), None),
+    };
+
+    let count = |value: Option<&str>| {
+        value
+            .ok_or_else(|| invalid("expected a count, e.g. bitflip=3"))?
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| invalid("count must be a non-negative integer"))
+    };
+
+    let (name, value) = match body.split_once('=') {
+        Some((name, value)) => (name.trim(), Some(value)),
+        None => (body, None),
+    };
+    let mutator: Box<dyn ChampionMutator> = match name.to_ascii_lowercase().as_str() {
+        "bitflip" => Box::new(BitFlip {
+            flips: count(value)?,
+        }),
+        "nops" => Box::new(InjectNops {
+            count: count(value)?,
+        }),
+        "immediates" if value.is_none() => Box::new(RandomizeImmediates),
+        "immediates" => return Err(invalid("immediates takes no value")),
+        _ => return Err(invalid("expected bitflip=N, nops=N or immediates")),
+    };
+
+    Ok(TargetedMutator {
+        champion_id,
+        mutator,
+    })
+}
+
+/// Instruction layout of a champion's code
+struct Decoded {
+    /// Offset of every instruction
+    starts: Vec<usize>,
+    /// Offset, type and size of every operand
+    operands: Vec<(usize, ParameterType, usize)>,
+}
+
+/// Walk the code as encoded by the assembler: opcode, parameter types if any, operands
+///
+/// Bytes that do not start a complete instruction are treated as data and
+/// skipped one at a time.
+fn decode(code: &[u8]) -> Decoded {
+    let mut decoded = Decoded {
+        starts: Vec::new(),
+        operands: Vec::new(),
+    };
+
+    let mut offset = 0;
+    while offset < code.len() {
+        let Ok(instruction) = Instruction::from_opcode(code[offset]) else {
+            offset += 1;
+            continue;
+        };
+        let types = if instruction.has_parameter_types() {
+            let Some(&types) = code.get(offset + 1) else {
+                break;
+            };
+            types
+        } else {
+            ParameterType::Direct.type_code() << 6
+        };
+
+        let mut operands = Vec::new();
+        let mut end = offset + instruction.header_size();
+        for i in 0..instruction.parameter_count() {
+            let parameter_type = ParameterType::from_type_code(types >> (6 - i * 2));
+            let size = instruction.parameter_size(parameter_type);
+            operands.push((end, parameter_type, size));
+            end += size;
+        }
+        if end > code.len() {
+            offset += 1;
+            continue;
+        }
+
+        decoded.starts.push(offset);
+        decoded.operands.extend(operands);
+        offset = end;
+    }
+
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `live %1; ld %5, r2; zjmp %-10`
+    fn program() -> Vec<u8> {
+        vec![
+            0x01, 0x00, 0x00, 0x00, 0x01, //
+            0x02, 0x90, 0x00, 0x00, 0x00, 0x05, 0x02, //
+            0x09, 0xFF, 0xF6,
+        ]
+    }
+
+    #[test]
+    fn test_decode_finds_instructions_and_operands() {
+        let decoded = decode(&program());
+        assert_eq!(decoded.starts, vec![0, 5, 12]);
+        assert_eq!(
+            decoded.operands,
+            vec![
+                (1, ParameterType::Direct, 4),
+                (7, ParameterType::Direct, 4),
+                (11, ParameterType::Register, 1),
+                (13, ParameterType::Direct, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bitflip_flips_bits() {
+        let original = program();
+        let mut code = original.clone();
+        BitFlip { flips: 1 }.mutate(&mut code, &mut SeededRng::new(3));
+
+        let changed: u32 = original
+            .iter()
+            .zip(&code)
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum();
+        assert_eq!(changed, 1);
+    }
+
+    #[test]
+    fn test_nops_are_inserted_between_instructions() {
+        let mut code = program();
+        InjectNops { count: 3 }.mutate(&mut code, &mut SeededRng::new(9));
+
+        assert_eq!(code.len(), program().len() + 3 * NOP.len());
+        let decoded = decode(&code);
+        assert_eq!(decoded.starts.len(), 6);
+        let nops = decoded
+            .starts
+            .iter()
+            .filter(|&&start| code[start..].starts_with(&NOP))
+            .count();
+        assert_eq!(nops, 3);
+    }
+
+    #[test]
+    fn test_immediates_change_only_direct_operands() {
+        let original = program();
+        let mut code = original.clone();
+        RandomizeImmediates.mutate(&mut code, &mut SeededRng::new(1));
+
+        let changed: Vec<usize> = (0..code.len())
+            .filter(|&i| code[i] != original[i])
+            .collect();
+        assert!(!changed.is_empty());
+        assert!(
+            changed
+                .iter()
+                .all(|i| (1..5).contains(i) || (7..11).contains(i) || [13, 14].contains(i))
+        );
+    }
+
+    #[test]
+    fn test_parse_mutators() {
+        let mutator = parse("bitflip=3@2").unwrap();
+        assert_eq!(mutator.champion_id, Some(2));
+        assert_eq!(mutator.to_string(), "bitflip=3@2");
+        assert!(!mutator.applies_to(1));
+        assert!(mutator.applies_to(2));
+
+        assert_eq!(parse("nops=1").unwrap().to_string(), "nops=1");
+        assert_eq!(parse("immediates").unwrap().to_string(), "immediates");
+
+        for invalid in [
+            "",
+            "bitflip",
+            "bitflip=x",
+            "nops=2@0",
+            "immediates=1",
+            "shuffle=2",
+        ] {
+            assert!(parse(invalid).is_err(), "{} should not parse", invalid);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+/// Newline-delimited JSON event output
+///
+/// For GUIs, web frontends and scripts that would rather read JSON than link
+/// the crate or decode the binary [`crate::vm::protocol`]: every published
+/// event becomes one JSON object on its own line, and the battle's results
+/// close the output.
+///
+/// Each event object has a `type` naming the event in snake_case
+/// (`memory_write`, `process_spawned`, `process_died`, `live_reported`,
+/// `cycle_completed`, `champion_eliminated`, `rewound`), the event's fields
+/// and the `cycle` it happened in, 0 for load time. The last line has type
+/// `result` and the fields of a [`BattleReport`].
+use crate::error::{CoreWarError, Result};
+use crate::vm::GameEvent;
+use crate::vm::bundle::BattleReport;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+/// Writes events and results as JSON lines
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    /// Create a writer; nothing is written until the first event
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the lines
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write an event as one line
+    ///
+    /// # Arguments
+    /// * `cycle` - Cycle the event happened in
+    /// * `event` - The event to write
+    pub fn write_event(&mut self, cycle: u32, event: &GameEvent) -> Result<()> {
+        self.write_line(event_object(cycle, event)?)
+    }
+
+    /// Write the battle's results as the closing line
+    ///
+    /// # Arguments
+    /// * `report` - Outcome of the finished battle
+    pub fn write_result(&mut self, report: &BattleReport) -> Result<()> {
+        let mut object = to_object(report)?;
+        object.insert("type".to_string(), "result".into());
+        self.write_line(object)
+    }
+
+    /// Flush written lines to the destination
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Get the destination back, without flushing
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Write a JSON object followed by a newline
+    fn write_line(&mut self, object: Map<String, Value>) -> Result<()> {
+        let mut line = serde_json::to_vec(&Value::Object(object))
+            .map_err(|e| CoreWarError::protocol(e.to_string()))?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> std::fmt::Debug for JsonLinesWriter<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonLinesWriter").finish_non_exhaustive()
+    }
+}
+
+/// Build the JSON object of an event, as the lines carry it
+///
+/// # Arguments
+/// * `cycle` - Cycle the event happened in
+/// * `event` - The event to convert
+pub fn event_object(cycle: u32, event: &GameEvent) -> Result<Map<String, Value>> {
+    let mut object = to_object(event)?;
+    object.entry("cycle").or_insert_with(|| cycle.into());
+    Ok(object)
+}
+
+/// Serialize a value that serde represents as a JSON object
+fn to_object<T: Serialize>(value: &T) -> Result<Map<String, Value>> {
+    match serde_json::to_value(value).map_err(|e| CoreWarError::protocol(e.to_string()))? {
+        Value::Object(object) => Ok(object),
+        other => Err(CoreWarError::protocol(format!(
+            "Expected a JSON object, got {}",
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::GameEngine;
+
+    #[test]
+    fn test_lines_are_stamped_objects() {
+        let mut writer = JsonLinesWriter::new(Vec::new());
+        writer
+            .write_event(
+                3,
+                &GameEvent::MemoryWrite {
+                    address: 12,
+                    value: 0xFF,
+                    champion_id: 1,
+                    process_id: 2,
+                    opcode: 0x01,
+                    pc: 11,
+                },
+            )
+            .unwrap();
+        writer
+            .write_event(3, &GameEvent::CycleCompleted { cycle: 3 })
+            .unwrap();
+
+        let mut engine = GameEngine::builder()
+            .max_cycles(5)
+            .champion_bytes("Liver", [0x01; 10])
+            .champion_bytes("Idle", [0x02; 10])
+            .build()
+            .unwrap();
+        engine.run_to_completion().unwrap();
+        writer.write_result(&BattleReport::of(&engine)).unwrap();
+
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], "memory_write");
+        assert_eq!(lines[0]["cycle"], 3);
+        assert_eq!(lines[0]["value"], 0xFF);
+        assert_eq!(lines[1]["type"], "cycle_completed");
+        assert_eq!(lines[1].as_object().unwrap().len(), 2);
+        assert_eq!(lines[2]["type"], "result");
+        assert_eq!(lines[2]["winner"], 1);
+        assert_eq!(lines[2]["champions"][0]["name"], "Liver");
+    }
+}
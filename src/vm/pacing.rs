@@ -0,0 +1,191 @@
+/// Pacing of battle execution for people watching it
+///
+/// Headless runs go as fast as the machine allows. When someone is watching,
+/// `speed` sets the pace instead: text mode throttles the engine to a target
+/// number of cycles per second, and the terminal UI runs a number of cycles
+/// per rendered frame, down to one cycle every few frames for slow motion.
+use crate::vm::clock::Instant;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+/// Cycles per second that one unit of text-mode speed stands for
+pub const CYCLES_PER_SPEED_UNIT: u32 = 100;
+
+/// Highest speed, in cycles per frame
+pub const MAX_SPEED: u32 = 1000;
+
+/// Most frames one cycle can be spread over in slow motion
+pub const MAX_SLOW_MOTION: u32 = 16;
+
+/// Sleeps shorter than this are deferred, since the OS cannot honor them
+const MIN_SLEEP: Duration = Duration::from_millis(1);
+
+/// Holds the engine to a target number of cycles per second
+#[derive(Debug, Clone, Copy)]
+pub struct Throttle {
+    /// Target rate
+    cycles_per_second: u32,
+    /// When the rate started being measured
+    origin: Instant,
+    /// Cycle the engine was at when the rate started being measured
+    origin_cycle: u32,
+}
+
+impl Throttle {
+    /// Create a throttle for a target rate
+    ///
+    /// # Arguments
+    /// * `cycles_per_second` - Target rate; 0 is treated as 1
+    pub fn new(cycles_per_second: u32) -> Self {
+        Self {
+            cycles_per_second: cycles_per_second.max(1),
+            origin: Instant::now(),
+            origin_cycle: 0,
+        }
+    }
+
+    /// Create the throttle text mode uses for a speed setting
+    ///
+    /// # Arguments
+    /// * `speed` - Speed setting; each unit adds [`CYCLES_PER_SPEED_UNIT`] cycles per second
+    pub fn for_speed(speed: u32) -> Self {
+        Self::new(speed.saturating_mul(CYCLES_PER_SPEED_UNIT))
+    }
+
+    /// Get the target rate
+    pub fn cycles_per_second(&self) -> u32 {
+        self.cycles_per_second
+    }
+
+    /// Start measuring the rate from a cycle, now
+    ///
+    /// # Arguments
+    /// * `cycle` - The engine's current cycle
+    pub fn restart(&mut self, cycle: u32) {
+        self.origin = Instant::now();
+        self.origin_cycle = cycle;
+    }
+
+    /// Time left until a cycle is due at the target rate
+    ///
+    /// # Arguments
+    /// * `cycle` - The cycle just completed
+    /// * `now` - The current time
+    pub fn delay(&self, cycle: u32, now: Instant) -> Duration {
+        let cycles = cycle.saturating_sub(self.origin_cycle);
+        let due = self.origin
+            + Duration::from_secs_f64(f64::from(cycles) / f64::from(self.cycles_per_second));
+        due.saturating_duration_since(now)
+    }
+
+    /// Sleep until a cycle is due
+    ///
+    /// Measuring restarts when the engine went back in time, after a rewind
+    /// or restore.
+    ///
+    /// # Arguments
+    /// * `cycle` - The cycle just completed
+    pub fn wait(&mut self, cycle: u32) {
+        if cycle < self.origin_cycle {
+            self.restart(cycle);
+            return;
+        }
+        let delay = self.delay(cycle, Instant::now());
+        if delay >= MIN_SLEEP {
+            thread::sleep(delay);
+        }
+    }
+}
+
+/// Cycles the terminal UI runs per rendered frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speed {
+    /// Run this many cycles every frame
+    Fast(u32),
+    /// Run one cycle every this many frames
+    Slow(u32),
+}
+
+impl Speed {
+    /// Create the speed for a cycles-per-frame setting, at least 1
+    pub fn per_frame(cycles: u32) -> Self {
+        Self::Fast(cycles.clamp(1, MAX_SPEED))
+    }
+
+    /// Double the speed, leaving slow motion on the way up
+    pub fn faster(self) -> Self {
+        match self {
+            Self::Slow(frames) if frames > 2 => Self::Slow(frames / 2),
+            Self::Slow(_) => Self::Fast(1),
+            Self::Fast(cycles) => Self::Fast((cycles * 2).min(MAX_SPEED)),
+        }
+    }
+
+    /// Halve the speed, entering slow motion below one cycle per frame
+    pub fn slower(self) -> Self {
+        match self {
+            Self::Fast(cycles) if cycles > 1 => Self::Fast(cycles / 2),
+            Self::Fast(_) => Self::Slow(2),
+            Self::Slow(frames) => Self::Slow((frames * 2).min(MAX_SLOW_MOTION)),
+        }
+    }
+
+    /// Cycles to run in a frame
+    ///
+    /// # Arguments
+    /// * `frame` - Number of the frame, counting from 0
+    pub fn cycles_in_frame(self, frame: u64) -> u32 {
+        match self {
+            Self::Fast(cycles) => cycles,
+            Self::Slow(frames) => u32::from(frame.is_multiple_of(u64::from(frames))),
+        }
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fast(cycles) => write!(f, "{}x", cycles),
+            Self::Slow(frames) => write!(f, "1/{}x", frames),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_delays_cycles_ahead_of_the_rate() {
+        let throttle = Throttle::for_speed(10);
+        assert_eq!(throttle.cycles_per_second(), 1000);
+
+        // 500 cycles at 1000 per second are due half a second in
+        let delay = throttle.delay(500, throttle.origin);
+        assert_eq!(delay, Duration::from_millis(500));
+        assert_eq!(
+            throttle.delay(500, throttle.origin + Duration::from_secs(1)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_speed_steps_through_slow_motion() {
+        let mut speed = Speed::per_frame(2);
+        speed = speed.slower();
+        assert_eq!(speed, Speed::Fast(1));
+        speed = speed.slower().slower();
+        assert_eq!(speed, Speed::Slow(4));
+        assert_eq!(speed.to_string(), "1/4x");
+        let cycles: Vec<u32> = (0..8).map(|frame| speed.cycles_in_frame(frame)).collect();
+        assert_eq!(cycles, [1, 0, 0, 0, 1, 0, 0, 0]);
+
+        assert_eq!(speed.faster().faster(), Speed::Fast(1));
+        assert_eq!(Speed::per_frame(5000).faster(), Speed::Fast(MAX_SPEED));
+        assert_eq!(
+            Speed::Slow(MAX_SLOW_MOTION).slower(),
+            Speed::Slow(MAX_SLOW_MOTION)
+        );
+    }
+}
@@ -4,12 +4,14 @@
 /// an executing program in the Core War virtual machine.
 use crate::error::{CoreWarError, Result};
 use crate::vm::ChampionColor;
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
 
 /// A process in the Core War virtual machine
 ///
 /// Each process represents an executing thread of a champion program.
 /// Processes can be created, forked, and terminated during execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Process {
     /// Process ID (unique identifier)
     pub id: u32,
@@ -21,8 +23,8 @@ pub struct Process {
     pub registers: [i32; 16],
     /// Carry flag for conditional operations
     pub carry: bool,
-    /// Number of cycles since last live instruction
-    pub live_counter: u32,
+    /// Cycle at which this process last executed a live instruction
+    pub last_live_cycle: Option<u32>,
     /// Whether this process is still alive
     pub alive: bool,
     /// Number of cycles to wait before next execution
@@ -51,7 +53,7 @@ impl Process {
             pc,
             registers: [0; 16],
             carry: false,
-            live_counter: 0,
+            last_live_cycle: None,
             alive: true,
             wait_cycles: 0,
             color,
@@ -95,8 +97,9 @@ impl Process {
     /// * `offset` - Number of bytes to advance (can be negative)
     /// * `memory_size` - Size of the memory for modulo arithmetic
     pub fn advance_pc(&mut self, offset: i32, memory_size: usize) {
-        let new_pc = (self.pc as i32 + offset) as usize;
-        self.pc = new_pc % memory_size;
+        // Wrap in signed 64 bits; casting a negative PC to usize depends on the target width
+        let new_pc = (self.pc as i64 + i64::from(offset)).rem_euclid(memory_size as i64);
+        self.pc = new_pc as usize;
         self.add_to_trail();
     }
 
@@ -120,13 +123,19 @@ impl Process {
     }
 
     /// Mark this process as alive (executed live instruction)
-    pub fn mark_alive(&mut self) {
-        self.live_counter = 0;
+    ///
+    /// # Arguments
+    /// * `cycle` - The absolute cycle at which the live instruction executed
+    pub fn mark_alive(&mut self, cycle: u32) {
+        self.last_live_cycle = Some(cycle);
     }
 
-    /// Increment the live counter
-    pub fn increment_live_counter(&mut self) {
-        self.live_counter += 1;
+    /// Check whether this process executed live after the given cycle
+    ///
+    /// # Arguments
+    /// * `cycle` - The cycle of the previous death check
+    pub fn lived_since(&self, cycle: u32) -> bool {
+        self.last_live_cycle.is_some_and(|live| live > cycle)
     }
 
     /// Kill this process
@@ -174,18 +183,32 @@ impl Process {
     /// Get a string representation of the process state for debugging
     pub fn debug_state(&self) -> String {
         format!(
-            "Process {} (Champion {}): PC={:04X}, Alive={}, Wait={}, Carry={}, LiveCounter={}",
+            "Process {} (Champion {}): PC={:04X}, Alive={}, Wait={}, Carry={}, LastLive={:?}",
             self.id,
             self.champion_id,
             self.pc,
             self.alive,
             self.wait_cycles,
             self.carry,
-            self.live_counter
+            self.last_live_cycle
         )
     }
 }
 
+impl Hash for Process {
+    // Visualization state (color, trail) is left out of the digest
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.champion_id.hash(state);
+        self.pc.hash(state);
+        self.registers.hash(state);
+        self.carry.hash(state);
+        self.last_live_cycle.hash(state);
+        self.alive.hash(state);
+        self.wait_cycles.hash(state);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,7 +221,7 @@ mod tests {
         assert_eq!(process.pc, 0x100);
         assert!(process.alive);
         assert_eq!(process.wait_cycles, 0);
-        assert_eq!(process.live_counter, 0);
+        assert_eq!(process.last_live_cycle, None);
         assert!(!process.carry);
     }
 
@@ -237,6 +260,10 @@ mod tests {
         process.advance_pc(900, memory_size);
         assert_eq!(process.pc, 25); // (125 + 900) % 1000 = 25
 
+        // Test negative wrapping past zero
+        process.advance_pc(-40, memory_size);
+        assert_eq!(process.pc, 985);
+
         // Test setting PC directly
         process.set_pc(500, memory_size);
         assert_eq!(process.pc, 500);
@@ -253,7 +280,7 @@ mod tests {
         // Test initial state
         assert!(process.is_ready());
         assert!(process.alive);
-        assert_eq!(process.live_counter, 0);
+        assert_eq!(process.last_live_cycle, None);
 
         // Test wait cycles
         process.set_wait_cycles(3);
@@ -269,12 +296,13 @@ mod tests {
         assert_eq!(process.wait_cycles, 0);
         assert!(process.is_ready());
 
-        // Test live counter
-        process.increment_live_counter();
-        assert_eq!(process.live_counter, 1);
+        // Test live tracking
+        assert!(!process.lived_since(0));
 
-        process.mark_alive();
-        assert_eq!(process.live_counter, 0);
+        process.mark_alive(5);
+        assert_eq!(process.last_live_cycle, Some(5));
+        assert!(process.lived_since(4));
+        assert!(!process.lived_since(5));
 
         // Test killing
         process.kill();
@@ -0,0 +1,260 @@
+/// Behavioural profiles of champions from battle telemetry
+///
+/// A champion's code says what it could do; its battles show what it does.
+/// This module plays a champion against a suite of opponents, follows every
+/// battle's events and condenses them into a profile: how much of its
+/// writing lands outside its own code, how fast it forks, how far apart its
+/// bombs fall and how well it survives once opponents write into it.
+use crate::error::{CoreWarError, Result};
+use crate::vm::{GameConfig, GameEngine, GameEvent};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Behaviour of one champion across battles
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChampionProfile {
+    /// Name of the profiled champion
+    pub name: String,
+    /// Battles played
+    pub battles: usize,
+    /// Battles won
+    pub wins: usize,
+    /// Sum of the battles' lengths in cycles
+    pub total_cycles: u64,
+    /// Bytes written by the champion's processes
+    pub writes: u64,
+    /// Bytes written outside the champion's own code
+    pub foreign_writes: u64,
+    /// Processes created by forks
+    pub forks: u64,
+    /// Sum of the distances between a process's consecutive foreign writes
+    pub stride_total: u64,
+    /// Number of distances in `stride_total`
+    pub strides: u64,
+    /// Battles in which an opponent wrote into the champion's code
+    pub attacked: usize,
+    /// Attacked battles the champion still had processes at the end of
+    pub survived_attacked: usize,
+}
+
+impl ChampionProfile {
+    /// Create an empty profile
+    ///
+    /// # Arguments
+    /// * `name` - Name of the profiled champion
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Add a finished battle to the profile
+    ///
+    /// # Arguments
+    /// * `engine` - Engine the battle ran on
+    /// * `champion_id` - ID of the profiled champion in the battle
+    /// * `events` - Events published while the battle ran
+    pub fn add_battle(&mut self, engine: &GameEngine, champion_id: u8, events: &[GameEvent]) {
+        let memory_size = engine.memory().size();
+        let Some(champion) = engine.champions().iter().find(|c| c.id == champion_id) else {
+            return;
+        };
+        let own_start = champion.load_address;
+        let own_len = champion.code.len();
+        let is_own = |address: usize| (address + memory_size - own_start) % memory_size < own_len;
+
+        let mut last_write: HashMap<u32, usize> = HashMap::new();
+        let mut attacked = false;
+        for event in events {
+            match *event {
+                GameEvent::MemoryWrite {
+                    address,
+                    champion_id: writer,
+                    process_id,
+                    ..
+                } if writer == champion_id => {
+                    self.writes += 1;
+                    if is_own(address) {
+                        continue;
+                    }
+                    self.foreign_writes += 1;
+                    if let Some(previous) = last_write.insert(process_id, address) {
+                        let distance = address.abs_diff(previous);
+                        self.stride_total += distance.min(memory_size - distance) as u64;
+                        self.strides += 1;
+                    }
+                }
+                GameEvent::MemoryWrite { address, .. } if is_own(address) => attacked = true,
+                GameEvent::ProcessSpawned {
+                    parent_id: Some(_),
+                    champion_id: owner,
+                    ..
+                } if owner == champion_id => self.forks += 1,
+                _ => {}
+            }
+        }
+
+        self.battles += 1;
+        self.total_cycles += u64::from(engine.state().cycle);
+        if engine.state().winner == Some(champion_id) {
+            self.wins += 1;
+        }
+        if attacked {
+            self.attacked += 1;
+            if champion.process_count > 0 {
+                self.survived_attacked += 1;
+            }
+        }
+    }
+
+    /// Share of the champion's writes that land outside its own code, from 0 to 1
+    pub fn aggression_index(&self) -> f64 {
+        ratio(self.foreign_writes, self.writes)
+    }
+
+    /// Forks per 1000 cycles
+    pub fn replication_rate(&self) -> f64 {
+        ratio(self.forks, self.total_cycles) * 1000.0
+    }
+
+    /// Average distance between a process's consecutive foreign writes, if any
+    pub fn average_bomb_stride(&self) -> Option<f64> {
+        (self.strides > 0).then(|| self.stride_total as f64 / self.strides as f64)
+    }
+
+    /// Fraction of attacked battles survived, if the champion was ever attacked
+    pub fn survivability(&self) -> Option<f64> {
+        (self.attacked > 0).then(|| ratio(self.survived_attacked as u64, self.attacked as u64))
+    }
+}
+
+impl fmt::Display for ChampionProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Champion: {}", self.name)?;
+        writeln!(f, "Battles: {} ({} won)", self.battles, self.wins)?;
+        writeln!(
+            f,
+            "Aggression index: {:.2} ({} of {} writes outside its code)",
+            self.aggression_index(),
+            self.foreign_writes,
+            self.writes
+        )?;
+        writeln!(
+            f,
+            "Replication rate: {:.1} forks per 1000 cycles",
+            self.replication_rate()
+        )?;
+        match self.average_bomb_stride() {
+            Some(stride) => writeln!(f, "Average bomb stride: {:.1}", stride)?,
+            None => writeln!(f, "Average bomb stride: n/a")?,
+        }
+        match self.survivability() {
+            Some(rate) => write!(
+                f,
+                "Survivability under attack: {:.1}% ({} of {} attacked battles)",
+                rate * 100.0,
+                self.survived_attacked,
+                self.attacked
+            ),
+            None => write!(f, "Survivability under attack: n/a (never attacked)"),
+        }
+    }
+}
+
+/// Profile a champion by playing it against every opponent of a suite
+///
+/// Each opponent gets one battle with the champion loaded first.
+///
+/// # Arguments
+/// * `champion` - The champion's .cor file
+/// * `suite` - Opponent .cor files
+/// * `config` - Configuration every battle runs with
+///
+/// # Returns
+/// The profile, or an error if the suite is empty or a battle fails
+pub fn profile(champion: &Path, suite: &[PathBuf], config: GameConfig) -> Result<ChampionProfile> {
+    if suite.is_empty() {
+        return Err(CoreWarError::game_state("The suite has no opponents"));
+    }
+
+    let mut profile: Option<ChampionProfile> = None;
+    for opponent in suite {
+        let mut engine = GameEngine::builder()
+            .config(config)
+            .champion_file(champion)
+            .champion_file(opponent)
+            .build()?;
+        let events = engine.subscribe_channel();
+        engine.run_to_completion()?;
+
+        let profile = profile
+            .get_or_insert_with(|| ChampionProfile::new(engine.champions()[0].display_name()));
+        let champion_id = engine.champions()[0].id;
+        profile.add_battle(&engine, champion_id, &events.try_iter().collect::<Vec<_>>());
+    }
+    Ok(profile.unwrap_or_default())
+}
+
+/// Divide, treating an empty set as 0
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(first: &[u8], second: &[u8]) -> (GameEngine, Vec<GameEvent>) {
+        let mut engine = GameEngine::builder()
+            .max_cycles(850)
+            .champion_bytes("First", first)
+            .champion_bytes("Second", second)
+            .build()
+            .unwrap();
+        let events = engine.subscribe_channel();
+        engine.run_to_completion().unwrap();
+        let events = events.try_iter().collect();
+        (engine, events)
+    }
+
+    #[test]
+    fn test_profile_of_a_bomber() {
+        // Writes 10 bytes ahead on every step, past the end of its 12 bytes
+        let mut bomber = vec![0x04; 12];
+        bomber[0] = 0x01;
+        let (engine, events) = play(&bomber, &[0x01; 100]);
+
+        let mut profile = ChampionProfile::new("First");
+        profile.add_battle(&engine, 1, &events);
+        assert_eq!(profile.battles, 1);
+        assert!(profile.writes > profile.foreign_writes);
+        assert!(profile.foreign_writes > 0);
+        assert!(profile.aggression_index() > 0.0 && profile.aggression_index() < 1.0);
+        assert_eq!(profile.average_bomb_stride(), Some(5.0));
+        assert_eq!(profile.replication_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_profile_counts_forks_and_attacks() {
+        let mut forker = vec![0x01; 100];
+        forker[5] = 0x0C;
+        let (engine, events) = play(&forker, &[0x04; 100]);
+
+        let mut profile = ChampionProfile::new("First");
+        profile.add_battle(&engine, 1, &events);
+        assert!(profile.forks > 0);
+        assert!(profile.replication_rate() > 0.0);
+
+        let mut writer = ChampionProfile::new("Second");
+        writer.add_battle(&engine, 2, &events);
+        assert_eq!(writer.survivability(), None);
+        assert!(writer.to_string().contains("never attacked"));
+        assert!(super::profile(Path::new("x.cor"), &[], GameConfig::default()).is_err());
+    }
+}
@@ -0,0 +1,389 @@
+/// Warrior projects
+///
+/// `corewar new NAME` creates a directory holding everything a new player
+/// needs: a template warrior, opponents to test it against (copies of the
+/// bundled [`crate::examples`]) and a [`FIGHT_FILE`] saying how to battle
+/// them. `corewar fight` in that directory then assembles the warrior and
+/// plays a [`Match`] against every opponent, so the edit, assemble and test
+/// loop needs no Makefile or scripts.
+///
+/// Paths in the fight file are relative to its directory. Warriors and
+/// opponents may be Redcode sources (`.s`) or assembled `.cor` files.
+/// [`FightConfig::new`] sets up the same fight for warriors named on the
+/// command line, with no project around them.
+use crate::assembler::Assembler;
+use crate::error::{CoreWarError, Result};
+use crate::examples;
+use crate::vm::GameConfig;
+use crate::vm::hill::MatchReport;
+use crate::vm::loader::ChampionLoader;
+use crate::vm::tournament::{DRAW_POINTS, Match, WIN_POINTS};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the file describing a project's test battles
+pub const FIGHT_FILE: &str = "fight.json";
+
+/// Bundled warriors a new project is tested against
+const OPPONENTS: [&str; 2] = ["imp", "dwarf"];
+
+/// Longest a round of a new fight lasts, in cycles
+const MAX_CYCLES: u32 = 20_000;
+
+/// How a project's warrior is battled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FightConfig {
+    /// The warrior under test
+    pub warrior: PathBuf,
+    /// Warriors it plays against, one match each
+    pub opponents: Vec<PathBuf>,
+    /// Rounds per match, rotating the placement order
+    #[serde(default = "default_rounds")]
+    pub rounds: u32,
+    /// Maximum number of cycles per round, 0 for unlimited
+    #[serde(default)]
+    pub max_cycles: u32,
+    /// Seed of the first round of every match
+    #[serde(default = "default_seed")]
+    pub seed: u64,
+}
+
+impl FightConfig {
+    /// Fight a warrior against opponents, with the default settings
+    ///
+    /// # Arguments
+    /// * `warrior` - The warrior under test
+    /// * `opponents` - Warriors it plays against
+    pub fn new(warrior: PathBuf, opponents: Vec<PathBuf>) -> Self {
+        Self {
+            warrior,
+            opponents,
+            rounds: default_rounds(),
+            max_cycles: MAX_CYCLES,
+            seed: default_seed(),
+        }
+    }
+
+    /// Read a project's fight file
+    ///
+    /// # Arguments
+    /// * `path` - The project's directory, or its fight file
+    ///
+    /// # Returns
+    /// The fight, with its warriors' paths made relative to the current
+    /// directory, or an error if the file cannot be read or is invalid
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = fight_file(path);
+        let root = file.parent().unwrap_or(Path::new("."));
+        let mut config: FightConfig = serde_json::from_str(&fs::read_to_string(&file)?)
+            .map_err(|e| CoreWarError::config(format!("Invalid {}: {}", file.display(), e)))?;
+        config.warrior = root.join(&config.warrior);
+        for opponent in &mut config.opponents {
+            *opponent = root.join(&*opponent);
+        }
+        Ok(config)
+    }
+
+    /// Get the files of every warrior in the fight, the warrior under test
+    /// first
+    pub fn sources(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.warrior.as_path()).chain(self.opponents.iter().map(PathBuf::as_path))
+    }
+
+    /// Get the settings each round is played with
+    pub fn game(&self) -> GameConfig {
+        GameConfig {
+            max_cycles: self.max_cycles,
+            seed: self.seed,
+            ..GameConfig::default()
+        }
+    }
+
+    /// Assemble or read every warrior in the fight
+    ///
+    /// # Returns
+    /// Each warrior's name and bytecode, the warrior under test first, or an
+    /// error if one does not assemble
+    pub fn champions(&self) -> Result<Vec<(String, Vec<u8>)>> {
+        self.sources().map(load).collect()
+    }
+
+    /// Play a match against each opponent
+    ///
+    /// # Returns
+    /// The results, or an error if the fight has no opponent or round, or a
+    /// warrior does not assemble
+    pub fn play(&self) -> Result<FightReport> {
+        if self.opponents.is_empty() || self.rounds == 0 {
+            return Err(CoreWarError::config(
+                "A fight needs at least one opponent and one round",
+            ));
+        }
+
+        let mut champions = self.champions()?.into_iter();
+        let (warrior, code) = champions.next().expect("the warrior is loaded first");
+        let mut matches = Vec::new();
+        for (name, opponent_code) in champions {
+            let result = Match::with_code(
+                vec![
+                    (warrior.clone(), code.clone()),
+                    (name.clone(), opponent_code),
+                ],
+                self.game(),
+            )
+            .best_of(self.rounds)
+            .play()?;
+            matches.push(MatchReport {
+                opponent: name,
+                wins: result.wins(0),
+                losses: result.wins(1),
+                draws: result.draws(),
+            });
+        }
+        Ok(FightReport { warrior, matches })
+    }
+}
+
+fn default_rounds() -> u32 {
+    4
+}
+
+fn default_seed() -> u64 {
+    crate::vm::rng::DEFAULT_SEED
+}
+
+/// Results of a project's test battles
+#[derive(Debug, Clone, PartialEq)]
+pub struct FightReport {
+    /// Name of the warrior under test
+    pub warrior: String,
+    /// Result of each match, in the fight file's order
+    pub matches: Vec<MatchReport>,
+}
+
+impl FightReport {
+    /// Points per 100 rounds over every match, as a hill scores them
+    pub fn score(&self) -> f64 {
+        let rounds: usize = self
+            .matches
+            .iter()
+            .map(|m| m.wins + m.losses + m.draws)
+            .sum();
+        let points: usize = self
+            .matches
+            .iter()
+            .map(|m| m.wins * WIN_POINTS as usize + m.draws * DRAW_POINTS as usize)
+            .sum();
+        if rounds == 0 {
+            0.0
+        } else {
+            points as f64 * 100.0 / rounds as f64
+        }
+    }
+}
+
+impl fmt::Display for FightReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for m in &self.matches {
+            writeln!(
+                f,
+                "{} vs {}: {}-{} ({} drawn)",
+                self.warrior, m.opponent, m.wins, m.losses, m.draws
+            )?;
+        }
+        write!(f, "Score: {:.1}", self.score())
+    }
+}
+
+/// Create a warrior project
+///
+/// # Arguments
+/// * `dir` - Directory to create; its last component names the warrior
+///
+/// # Returns
+/// The files written, or an error if the name is not usable or the
+/// directory exists and is not empty
+pub fn create(dir: &Path) -> Result<Vec<PathBuf>> {
+    let name = dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+        .ok_or_else(|| {
+            CoreWarError::config(format!(
+                "Invalid warrior name '{}': use letters, digits, '-' and '_'",
+                dir.display()
+            ))
+        })?;
+    if dir.exists() && fs::read_dir(dir)?.next().is_some() {
+        return Err(CoreWarError::config(format!(
+            "{} already exists and is not empty",
+            dir.display()
+        )));
+    }
+
+    let warrior = PathBuf::from(format!("{}.s", name));
+    let mut files = vec![(warrior.clone(), template(name))];
+    let mut opponents = Vec::new();
+    for opponent in OPPONENTS {
+        let example = examples::find(opponent)?;
+        let path = Path::new("opponents").join(example.file_name());
+        files.push((path.clone(), example.source.to_string()));
+        opponents.push(path);
+    }
+    let config = serde_json::to_string_pretty(&FightConfig::new(warrior, opponents))
+        .map_err(|e| CoreWarError::config(e.to_string()))?;
+    files.push((PathBuf::from(FIGHT_FILE), config + "\n"));
+
+    fs::create_dir_all(dir.join("opponents"))?;
+    let mut written = Vec::new();
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::write(&path, contents)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Find a project's fight file
+///
+/// # Arguments
+/// * `path` - The project's directory, or its fight file
+pub fn fight_file(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.join(FIGHT_FILE)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Battle a project's warrior against its opponents
+///
+/// # Arguments
+/// * `path` - The project's directory, or its fight file
+///
+/// # Returns
+/// The results, or an error if the fight file is invalid or a warrior does
+/// not assemble
+pub fn fight(path: &Path) -> Result<FightReport> {
+    FightConfig::open(path)?.play()
+}
+
+/// Load a warrior from a source or .cor file, as its name and bytecode
+fn load(path: &Path) -> Result<(String, Vec<u8>)> {
+    let cor = if path.extension().is_some_and(|ext| ext == "cor") {
+        fs::read(path)?
+    } else {
+        Assembler::new(false).assemble_path(path)?
+    };
+    let champion = ChampionLoader::new(false).load_champion_from_bytes(&cor, 1, None)?;
+    Ok((champion.display_name().to_string(), champion.code))
+}
+
+/// Source of a new project's warrior
+fn template(name: &str) -> String {
+    format!(
+        "; {name} - a new warrior
+;
+; Change the code below, then run `corewar fight` in this directory to play
+; it against the opponents listed in {fight}.
+.name \"{name}\"
+.comment \"A new warrior\"
+
+start:
+    live %1             ; report this warrior alive
+    fork %:start        ; start another process at the top
+    ld %0, r2           ; loading zero sets the carry...
+    zjmp %:start        ; ...so this always jumps back
+",
+        name = name,
+        fight = FIGHT_FILE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_project_fights_its_opponents() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("my-warrior");
+        let files = create(&project).unwrap();
+        assert_eq!(files.len(), 4);
+        assert!(project.join("my-warrior.s").is_file());
+        assert!(project.join("opponents/dwarf.s").is_file());
+
+        let report = fight(&project).unwrap();
+        assert_eq!(report.warrior, "my-warrior");
+        let opponents: Vec<&str> = report.matches.iter().map(|m| m.opponent.as_str()).collect();
+        assert_eq!(opponents, ["Imp", "Dwarf"]);
+        assert!(
+            report
+                .matches
+                .iter()
+                .all(|m| m.wins + m.losses + m.draws == 4)
+        );
+        assert!(
+            report
+                .to_string()
+                .ends_with(&format!("Score: {:.1}", report.score()))
+        );
+
+        // The fight file may be named directly, and the same seed replays
+        let again = fight(&project.join(FIGHT_FILE)).unwrap();
+        assert_eq!(again, report);
+    }
+
+    #[test]
+    fn test_create_refuses_bad_names_and_used_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(create(&dir.path().join("my warrior")).is_err());
+        let empty = dir.path().join("empty");
+        fs::create_dir(&empty).unwrap();
+        assert!(create(&empty).is_ok());
+        assert!(create(&empty).is_err());
+    }
+
+    #[test]
+    fn test_warriors_fight_without_a_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("loose");
+        create(&project).unwrap();
+        let opponents = vec![project.join("opponents/imp.s")];
+        let mut config = FightConfig::new(project.join("loose.s"), opponents);
+        config.rounds = 2;
+        assert_eq!(config.sources().count(), 2);
+        let names: Vec<String> = config
+            .champions()
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["loose", "Imp"]);
+        let played = &config.play().unwrap().matches[0];
+        assert_eq!(played.wins + played.losses + played.draws, 2);
+
+        config.opponents.clear();
+        assert!(config.play().is_err());
+    }
+
+    #[test]
+    fn test_fight_reports_invalid_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("broken");
+        create(&project).unwrap();
+        fs::write(project.join("broken.s"), ".name \"broken\"\n    mov 0, 1\n").unwrap();
+        assert!(fight(&project).is_err());
+
+        fs::write(project.join(FIGHT_FILE), "{\"warrior\": \"broken.s\"}").unwrap();
+        let error = fight(&project).unwrap_err().to_string();
+        assert!(error.contains("opponents"));
+    }
+}
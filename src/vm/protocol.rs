@@ -0,0 +1,566 @@
+/// Compact binary event stream for external visualizers
+///
+/// The engine can encode every [`GameEvent`] it publishes into a small
+/// little-endian byte stream, so visualizers written in any language can
+/// follow a battle from a pipe or a replay file without parsing JSON.
+///
+/// # Stream layout
+///
+/// A stream starts with a 9-byte header:
+///
+/// | Bytes | Field                          |
+/// |-------|--------------------------------|
+/// | 4     | Magic `CWEV`                   |
+/// | 1     | Protocol version (currently 1) |
+/// | 4     | Memory size in bytes (u32)     |
+///
+/// Records follow, each a one-byte tag and a fixed-size payload. Integers
+/// are little-endian; addresses, PCs and process IDs are u32, champion IDs,
+/// values and opcodes are u8.
+///
+/// | Tag    | Record              | Payload                                            |
+/// |--------|---------------------|----------------------------------------------------|
+/// | `0x01` | Cycle stamp         | cycle                                              |
+/// | `0x02` | Memory write        | address, value, champion, process, opcode, pc      |
+/// | `0x03` | Process spawned     | process, parent (`0xFFFFFFFF` for none), champion, pc |
+/// | `0x04` | Process died        | process, champion, pc                              |
+/// | `0x05` | Live reported       | process, champion                                  |
+/// | `0x06` | Cycle completed     | none                                               |
+/// | `0x07` | Champion eliminated | champion                                           |
+/// | `0x08` | Rewound             | none                                               |
+///
+/// A cycle stamp sets the cycle of every record after it, until the next
+/// stamp; records before the first stamp happened at load time, cycle 0.
+/// Stamps are only written when the cycle changes, so a cycle's memory
+/// deltas and process events cost no more than their own payloads.
+///
+/// Streams for spectators may be redacted with
+/// [`crate::vm::GameEngine::redact_events`]; their memory write records
+/// carry 0 as the value and opcode.
+use crate::error::{CoreWarError, Result};
+use crate::vm::GameEvent;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// First bytes of every event stream
+pub const MAGIC: [u8; 4] = *b"CWEV";
+
+/// Version of the stream layout written by [`EventEncoder`]
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Parent ID written for processes that have no parent
+const NO_PARENT: u32 = u32::MAX;
+
+const TAG_CYCLE: u8 = 0x01;
+const TAG_MEMORY_WRITE: u8 = 0x02;
+const TAG_PROCESS_SPAWNED: u8 = 0x03;
+const TAG_PROCESS_DIED: u8 = 0x04;
+const TAG_LIVE_REPORTED: u8 = 0x05;
+const TAG_CYCLE_COMPLETED: u8 = 0x06;
+const TAG_CHAMPION_ELIMINATED: u8 = 0x07;
+const TAG_REWOUND: u8 = 0x08;
+
+/// Header of an event stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamHeader {
+    /// Protocol version the stream was written with
+    pub version: u8,
+    /// Memory size of the battle, for sizing a visualizer's core view
+    pub memory_size: u32,
+}
+
+/// An event with the cycle it happened in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StampedEvent {
+    /// Cycle the event happened in, or 0 for load time
+    pub cycle: u32,
+    /// The event
+    pub event: GameEvent,
+}
+
+/// Writes events to a byte stream
+pub struct EventEncoder<W: Write> {
+    writer: W,
+    /// Cycle of the last stamp written
+    cycle: u32,
+    /// Reused record buffer
+    record: Vec<u8>,
+}
+
+impl<W: Write> EventEncoder<W> {
+    /// Start a stream by writing its header
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the stream
+    /// * `memory_size` - Memory size of the battle
+    ///
+    /// # Returns
+    /// The encoder, or an error if the header could not be written
+    pub fn new(mut writer: W, memory_size: usize) -> Result<Self> {
+        let mut header = MAGIC.to_vec();
+        header.push(PROTOCOL_VERSION);
+        header.extend_from_slice(&(memory_size as u32).to_le_bytes());
+        writer.write_all(&header)?;
+        Ok(Self {
+            writer,
+            cycle: 0,
+            record: Vec::with_capacity(16),
+        })
+    }
+
+    /// Write an event, preceded by a cycle stamp if the cycle changed
+    ///
+    /// # Arguments
+    /// * `cycle` - Cycle the event happened in
+    /// * `event` - The event to write
+    pub fn encode(&mut self, cycle: u32, event: &GameEvent) -> Result<()> {
+        self.record.clear();
+        if cycle != self.cycle {
+            self.record.push(TAG_CYCLE);
+            self.record.extend_from_slice(&cycle.to_le_bytes());
+            self.cycle = cycle;
+        }
+
+        let record = &mut self.record;
+        match *event {
+            GameEvent::MemoryWrite {
+                address,
+                value,
+                champion_id,
+                process_id,
+                opcode,
+                pc,
+            } => {
+                record.push(TAG_MEMORY_WRITE);
+                put_u32(record, address as u32);
+                record.extend_from_slice(&[value, champion_id]);
+                put_u32(record, process_id);
+                record.push(opcode);
+                put_u32(record, pc as u32);
+            }
+            GameEvent::ProcessSpawned {
+                process_id,
+                parent_id,
+                champion_id,
+                pc,
+            } => {
+                record.push(TAG_PROCESS_SPAWNED);
+                put_u32(record, process_id);
+                put_u32(record, parent_id.unwrap_or(NO_PARENT));
+                record.push(champion_id);
+                put_u32(record, pc as u32);
+            }
+            GameEvent::ProcessDied {
+                process_id,
+                champion_id,
+                pc,
+            } => {
+                record.push(TAG_PROCESS_DIED);
+                put_u32(record, process_id);
+                record.push(champion_id);
+                put_u32(record, pc as u32);
+            }
+            GameEvent::LiveReported {
+                process_id,
+                champion_id,
+                ..
+            } => {
+                record.push(TAG_LIVE_REPORTED);
+                put_u32(record, process_id);
+                record.push(champion_id);
+            }
+            GameEvent::CycleCompleted { .. } => record.push(TAG_CYCLE_COMPLETED),
+            GameEvent::ChampionEliminated { champion_id, .. } => {
+                record.extend_from_slice(&[TAG_CHAMPION_ELIMINATED, champion_id]);
+            }
+            GameEvent::Rewound { .. } => record.push(TAG_REWOUND),
+        }
+
+        self.writer.write_all(&self.record)?;
+        Ok(())
+    }
+
+    /// Flush buffered records to the destination
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Get a reference to the destination
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Get the destination back, without flushing
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> fmt::Debug for EventEncoder<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventEncoder")
+            .field("cycle", &self.cycle)
+            .finish()
+    }
+}
+
+/// Reads events back from a byte stream
+///
+/// Iterating yields every event with its cycle, ending cleanly at the end
+/// of the stream and with an error at a truncated or unknown record.
+pub struct EventDecoder<R: Read> {
+    reader: R,
+    header: StreamHeader,
+    /// Cycle of the last stamp read
+    cycle: u32,
+}
+
+impl<R: Read> EventDecoder<R> {
+    /// Open a stream by reading and checking its header
+    ///
+    /// # Arguments
+    /// * `reader` - Source of the stream
+    ///
+    /// # Returns
+    /// The decoder, or an error if the header is not a supported stream header
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut header = [0u8; 9];
+        read_exact(&mut reader, &mut header)?;
+        if header[..4] != MAGIC {
+            return Err(CoreWarError::protocol("Not an event stream (bad magic)"));
+        }
+        if header[4] != PROTOCOL_VERSION {
+            return Err(CoreWarError::protocol(format!(
+                "Unsupported event stream version {} (expected {})",
+                header[4], PROTOCOL_VERSION
+            )));
+        }
+        Ok(Self {
+            reader,
+            header: StreamHeader {
+                version: header[4],
+                memory_size: u32::from_le_bytes(header[5..9].try_into().unwrap()),
+            },
+            cycle: 0,
+        })
+    }
+
+    /// Get the stream header
+    pub fn header(&self) -> StreamHeader {
+        self.header
+    }
+
+    /// Read the next event, skipping over cycle stamps
+    fn next_event(&mut self) -> Result<Option<StampedEvent>> {
+        loop {
+            let mut tag = [0u8];
+            match self.reader.read(&mut tag) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+
+            let cycle = self.cycle;
+            let event = match tag[0] {
+                TAG_CYCLE => {
+                    self.cycle = self.u32()?;
+                    continue;
+                }
+                TAG_MEMORY_WRITE => GameEvent::MemoryWrite {
+                    address: self.u32()? as usize,
+                    value: self.u8()?,
+                    champion_id: self.u8()?,
+                    process_id: self.u32()?,
+                    opcode: self.u8()?,
+                    pc: self.u32()? as usize,
+                },
+                TAG_PROCESS_SPAWNED => GameEvent::ProcessSpawned {
+                    process_id: self.u32()?,
+                    parent_id: Some(self.u32()?).filter(|&id| id != NO_PARENT),
+                    champion_id: self.u8()?,
+                    pc: self.u32()? as usize,
+                },
+                TAG_PROCESS_DIED => GameEvent::ProcessDied {
+                    process_id: self.u32()?,
+                    champion_id: self.u8()?,
+                    pc: self.u32()? as usize,
+                },
+                TAG_LIVE_REPORTED => GameEvent::LiveReported {
+                    process_id: self.u32()?,
+                    champion_id: self.u8()?,
+                    cycle,
+                },
+                TAG_CYCLE_COMPLETED => GameEvent::CycleCompleted { cycle },
+                TAG_CHAMPION_ELIMINATED => GameEvent::ChampionEliminated {
+                    champion_id: self.u8()?,
+                    cycle,
+                },
+                TAG_REWOUND => GameEvent::Rewound { cycle },
+                tag => {
+                    return Err(CoreWarError::protocol(format!(
+                        "Unknown record tag 0x{:02X}",
+                        tag
+                    )));
+                }
+            };
+            return Ok(Some(StampedEvent { cycle, event }));
+        }
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        let mut bytes = [0u8];
+        read_exact(&mut self.reader, &mut bytes)?;
+        Ok(bytes[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut bytes = [0u8; 4];
+        read_exact(&mut self.reader, &mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+}
+
+impl<R: Read> Iterator for EventDecoder<R> {
+    type Item = Result<StampedEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+/// Decode a complete event stream held in memory
+///
+/// # Arguments
+/// * `bytes` - The stream, header included
+///
+/// # Returns
+/// The header and every event, or the first decoding error
+pub fn decode(bytes: &[u8]) -> Result<(StreamHeader, Vec<StampedEvent>)> {
+    let decoder = EventDecoder::new(bytes)?;
+    let header = decoder.header();
+    Ok((header, decoder.collect::<Result<Vec<_>>>()?))
+}
+
+/// Append a little-endian u32 to a record
+fn put_u32(record: &mut Vec<u8>, value: u32) {
+    record.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Fill a buffer, reporting a short read as a truncated stream
+fn read_exact<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<()> {
+    reader.read_exact(buffer).map_err(|e| {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            CoreWarError::protocol("Event stream ends in the middle of a record")
+        } else {
+            e.into()
+        }
+    })
+}
+
+/// Where an event stream is written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventTarget {
+    /// A replay file, created or truncated
+    File(PathBuf),
+    /// An open file descriptor inherited from the parent process, e.g. a pipe
+    Fd(i32),
+}
+
+impl EventTarget {
+    /// Open the target for writing
+    ///
+    /// # Returns
+    /// A buffered writer, or an error if the file cannot be created or the
+    /// descriptor is not open
+    pub fn open(&self) -> Result<Box<dyn Write + Send>> {
+        match self {
+            EventTarget::File(path) => Ok(Box::new(BufWriter::new(File::create(path)?))),
+            EventTarget::Fd(1) => Ok(Box::new(BufWriter::new(io::stdout()))),
+            EventTarget::Fd(2) => Ok(Box::new(BufWriter::new(io::stderr()))),
+            #[cfg(unix)]
+            EventTarget::Fd(fd) => {
+                use std::os::fd::FromRawFd;
+
+                // SAFETY: fcntl only queries the descriptor's flags
+                if unsafe { libc::fcntl(*fd, libc::F_GETFD) } == -1 {
+                    return Err(CoreWarError::protocol(format!(
+                        "File descriptor {} is not open: {}",
+                        fd,
+                        io::Error::last_os_error()
+                    )));
+                }
+                // SAFETY: the descriptor is open and nothing else in this
+                // process uses it, so the file may own and close it
+                let file = unsafe { File::from_raw_fd(*fd) };
+                Ok(Box::new(BufWriter::new(file)))
+            }
+            #[cfg(not(unix))]
+            EventTarget::Fd(fd) => Err(CoreWarError::protocol(format!(
+                "Writing to file descriptor {} is only supported on Unix",
+                fd
+            ))),
+        }
+    }
+}
+
+impl FromStr for EventTarget {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(fd) = s.strip_prefix("fd:") {
+            fd.parse::<i32>()
+                .ok()
+                .filter(|&fd| fd > 0)
+                .map(EventTarget::Fd)
+                .ok_or_else(|| {
+                    CoreWarError::protocol(format!(
+                        "Invalid file descriptor '{}' (expected a positive number)",
+                        fd
+                    ))
+                })
+        } else if let Some(path) = s.strip_prefix("file:").filter(|path| !path.is_empty()) {
+            Ok(EventTarget::File(PathBuf::from(path)))
+        } else {
+            Err(CoreWarError::protocol(format!(
+                "Unknown event target '{}' (expected fd:N or file:PATH)",
+                s
+            )))
+        }
+    }
+}
+
+impl fmt::Display for EventTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventTarget::File(path) => write!(f, "file:{}", path.display()),
+            EventTarget::Fd(fd) => write!(f, "fd:{}", fd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_events() -> Vec<StampedEvent> {
+        let stamped = |cycle, event| StampedEvent { cycle, event };
+        vec![
+            stamped(
+                0,
+                GameEvent::ProcessSpawned {
+                    process_id: 1,
+                    parent_id: None,
+                    champion_id: 1,
+                    pc: 0,
+                },
+            ),
+            stamped(
+                1,
+                GameEvent::MemoryWrite {
+                    address: 4095,
+                    value: 0xAA,
+                    champion_id: 1,
+                    process_id: 1,
+                    opcode: 0x03,
+                    pc: 10,
+                },
+            ),
+            stamped(
+                1,
+                GameEvent::LiveReported {
+                    process_id: 1,
+                    champion_id: 1,
+                    cycle: 1,
+                },
+            ),
+            stamped(1, GameEvent::CycleCompleted { cycle: 1 }),
+            stamped(
+                2,
+                GameEvent::ProcessSpawned {
+                    process_id: 2,
+                    parent_id: Some(1),
+                    champion_id: 1,
+                    pc: 100,
+                },
+            ),
+            stamped(
+                2,
+                GameEvent::ProcessDied {
+                    process_id: 2,
+                    champion_id: 1,
+                    pc: 105,
+                },
+            ),
+            stamped(
+                2,
+                GameEvent::ChampionEliminated {
+                    champion_id: 2,
+                    cycle: 2,
+                },
+            ),
+            stamped(2, GameEvent::CycleCompleted { cycle: 2 }),
+            stamped(1, GameEvent::Rewound { cycle: 1 }),
+        ]
+    }
+
+    fn encode_all(events: &[StampedEvent]) -> Vec<u8> {
+        let mut encoder = EventEncoder::new(Vec::new(), 4096).unwrap();
+        for stamped in events {
+            encoder.encode(stamped.cycle, &stamped.event).unwrap();
+        }
+        encoder.into_inner()
+    }
+
+    #[test]
+    fn test_events_round_trip() {
+        let events = sample_events();
+        let bytes = encode_all(&events);
+
+        assert_eq!(&bytes[..9], b"CWEV\x01\x00\x10\x00\x00");
+        // Header, three stamps and the records' tags and payloads
+        assert_eq!(
+            bytes.len(),
+            9 + 3 * 5 + 14 + 16 + 6 + 1 + 14 + 10 + 2 + 1 + 1
+        );
+
+        let (header, decoded) = decode(&bytes).unwrap();
+        assert_eq!(
+            header,
+            StreamHeader {
+                version: PROTOCOL_VERSION,
+                memory_size: 4096
+            }
+        );
+        assert_eq!(decoded, events);
+    }
+
+    #[test]
+    fn test_decoder_rejects_bad_streams() {
+        let bytes = encode_all(&sample_events());
+
+        assert!(decode(b"CWEX\x01\x00\x10\x00\x00").is_err());
+        assert!(decode(b"CWEV\x02\x00\x10\x00\x00").is_err());
+        assert!(decode(&bytes[..bytes.len() - 3]).is_err());
+        let mut unknown = bytes[..9].to_vec();
+        unknown.push(0x7F);
+        assert!(decode(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_parse_event_targets() {
+        assert_eq!("fd:3".parse::<EventTarget>().unwrap(), EventTarget::Fd(3));
+        assert_eq!(
+            "file:battle.cwev".parse::<EventTarget>().unwrap(),
+            EventTarget::File(PathBuf::from("battle.cwev"))
+        );
+        assert_eq!(EventTarget::Fd(3).to_string(), "fd:3");
+        for invalid in ["fd:", "fd:0", "fd:x", "file:", "battle.cwev"] {
+            assert!(invalid.parse::<EventTarget>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+/// Private storage (p-space) for champions
+///
+/// With the `pspace` feature and a nonzero [`Rules::pspace_size`], every
+/// champion owns that many cells of storage no other champion can reach.
+/// `ldp` loads a cell into a register and `stp` stores a register in a
+/// cell; cell numbers wrap around the p-space. The cells live on the
+/// [`Champion`], so snapshots and rewinds include them, and a
+/// [`Match`](crate::vm::Match) carries them from one round to the next. As
+/// in pMARS, cell 0 tells the champion how the previous round went:
+///
+/// | Cell 0 | Previous round                         |
+/// |--------|----------------------------------------|
+/// | -1     | None, this is the first round          |
+/// | 0      | Lost                                   |
+/// | 1      | Won                                    |
+/// | N      | Drawn with N champions still running   |
+use crate::error::{CoreWarError, Result};
+use crate::vm::decode::DecodedInstruction;
+use crate::vm::instruction::{Instruction, ParameterType};
+use crate::vm::{Champion, Memory, Process, Rules};
+use std::collections::BTreeSet;
+
+/// Cell 0 in the first round of a match
+pub const FIRST_ROUND: i32 = -1;
+
+/// Cell holding the result of the previous round
+pub const RESULT_CELL: usize = 0;
+
+/// Give a champion's p-space its configured size
+///
+/// Empty storage, as for a champion that has not played yet, starts out as
+/// the first round's. Otherwise the cells are kept, and dropped or added as
+/// zeros to match `size`.
+///
+/// # Arguments
+/// * `cells` - The champion's p-space
+/// * `size` - Number of cells the rules give each champion
+pub fn prepare(cells: &mut Vec<i32>, size: usize) {
+    if cells.is_empty() && size > 0 {
+        cells.push(FIRST_ROUND);
+    }
+    cells.resize(size, 0);
+}
+
+/// Get what cell 0 holds in the round after this one
+///
+/// # Arguments
+/// * `champion_id` - The champion the result is for
+/// * `winner` - The round's winner, or None for a draw
+/// * `running` - Champions that still had processes when the round ended
+///
+/// # Returns
+/// 1 for the winner, the number of champions still running for each of
+/// them in a draw, and 0 for everyone else
+pub fn round_result(champion_id: u8, winner: Option<u8>, running: &BTreeSet<u8>) -> i32 {
+    match winner {
+        Some(winner) if winner == champion_id => 1,
+        None if running.contains(&champion_id) => running.len() as i32,
+        _ => 0,
+    }
+}
+
+/// Load a cell
+///
+/// # Returns
+/// The cell numbered `index` modulo the p-space size, or 0 without p-space
+pub fn load(cells: &[i32], index: i32) -> i32 {
+    cell(cells, index).map_or(0, |cell| cells[cell])
+}
+
+/// Store a value in a cell
+///
+/// Does nothing without p-space.
+pub fn store(cells: &mut [i32], index: i32, value: i32) {
+    if let Some(cell) = cell(cells, index) {
+        cells[cell] = value;
+    }
+}
+
+/// Number the cell an index refers to
+fn cell(cells: &[i32], index: i32) -> Option<usize> {
+    (!cells.is_empty()).then(|| i64::from(index).rem_euclid(cells.len() as i64) as usize)
+}
+
+/// Execute `ldp` or `stp` for a process
+///
+/// The operands are decoded as the assembler encodes them: the index is a
+/// 4-byte direct value, a register, or the 4-byte value at
+/// PC + (offset % IDX_MOD). `ldp` sets the carry when it loads 0.
+///
+/// # Arguments
+/// * `decoded` - [`Instruction::Ldp`] or [`Instruction::Stp`], decoded at the PC
+/// * `process` - The executing process, whose PC is at the instruction
+/// * `memory` - Memory indirect operands are read from
+/// * `champions` - Champions, holding the p-space of the process's champion
+/// * `rules` - Arena rules, for IDX_MOD
+///
+/// # Returns
+/// The encoded size of the instruction, or an error if its operands are invalid
+pub(crate) fn execute(
+    decoded: &DecodedInstruction,
+    process: &mut Process,
+    memory: &Memory,
+    champions: &mut [Champion],
+    rules: &Rules,
+) -> Result<usize> {
+    let instruction = decoded
+        .instruction
+        .filter(|&instruction| matches!(instruction, Instruction::Ldp | Instruction::Stp))
+        .ok_or_else(|| {
+            CoreWarError::instruction(format!("Opcode 0x{:02X} is not ldp or stp", decoded.opcode))
+        })?;
+    let parameters: Vec<_> = decoded.parameters().collect();
+    // Each operand's register number, if it is a register, and its value
+    let mut operands = Vec::with_capacity(2);
+    for (i, accepted) in instruction.operand_types().iter().enumerate() {
+        let Some(&(parameter_type, raw)) = parameters.get(i).filter(|(t, _)| accepted.contains(t))
+        else {
+            return Err(CoreWarError::instruction(format!(
+                "Invalid operand types 0x{:02X} for {}",
+                decoded.types,
+                instruction.name()
+            )));
+        };
+
+        let register = raw as u8;
+        let value = match parameter_type {
+            ParameterType::Register => process.get_register(register)?,
+            ParameterType::Indirect => {
+                let target = (process.pc as i64 + i64::from(raw) % rules.idx_mod as i64)
+                    .rem_euclid(memory.size() as i64);
+                memory.read_word(target as usize) as i32
+            }
+            _ => raw,
+        };
+        operands.push((register, value));
+    }
+
+    let champion = champions
+        .iter_mut()
+        .find(|c| c.id == process.champion_id)
+        .ok_or_else(|| {
+            CoreWarError::game_state(format!("Process {} belongs to no champion", process.id))
+        })?;
+    if instruction == Instruction::Ldp {
+        let value = load(&champion.pspace, operands[0].1);
+        process.set_register(operands[1].0, value)?;
+        process.carry = value == 0;
+    } else {
+        store(&mut champion.pspace, operands[1].1, operands[0].1);
+    }
+    Ok(decoded.size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::Assembler;
+    use crate::vm::{GameConfig, Match, RulesPreset};
+
+    #[test]
+    fn test_cells_wrap_and_start_as_first_round() {
+        let mut cells = Vec::new();
+        prepare(&mut cells, 4);
+        assert_eq!(cells, [FIRST_ROUND, 0, 0, 0]);
+
+        store(&mut cells, 6, 42);
+        assert_eq!(load(&cells, 2), 42);
+        assert_eq!(load(&cells, -2), 42);
+        assert_eq!(load(&[], 2), 0);
+
+        // Storage from an earlier round is kept
+        prepare(&mut cells, 3);
+        assert_eq!(cells, [FIRST_ROUND, 0, 42]);
+        prepare(&mut cells, 0);
+        assert!(cells.is_empty());
+    }
+
+    #[test]
+    fn test_round_results() {
+        let running = BTreeSet::from([1, 3]);
+        assert_eq!(round_result(1, Some(1), &running), 1);
+        assert_eq!(round_result(3, Some(1), &running), 0);
+        assert_eq!(round_result(1, None, &running), 2);
+        assert_eq!(round_result(2, None, &running), 0);
+    }
+
+    #[test]
+    fn test_pspace_is_kept_across_rounds() {
+        // Copy cell 1 to cell 2 and cell 0 to cell 1, then outlast the forker
+        let source = ".name \"keeper\"\nldp %1, r2\nstp r2, %2\nldp %0, r3\nstp r3, %1\n";
+        let bytecode = Assembler::new(false).assemble_source(source).unwrap();
+        let mut keeper = bytecode[bytecode.len() - 28..].to_vec();
+        assert_eq!(
+            keeper[..14],
+            [0x11, 0x90, 0, 0, 0, 1, 2, 0x12, 0x60, 2, 0, 0, 0, 2]
+        );
+        let forker = [0x0C, 0x00, 0x00, 0x00, 0x00].to_vec();
+        keeper.extend(forker.repeat(2));
+
+        let config = GameConfig {
+            rules: RulesPreset::Pmars.rules(),
+            max_cycles: RulesPreset::Pmars.max_cycles(),
+            ..GameConfig::default()
+        };
+        let result = Match::with_code(
+            vec![
+                ("Keeper".to_string(), keeper),
+                ("Forker".to_string(), forker),
+            ],
+            config,
+        )
+        .best_of(2)
+        .play()
+        .unwrap();
+        assert_eq!(result.wins(0), 2);
+
+        // Round 2 copied round 1's result, which round 1 had copied the first-round marker over
+        let keeper = &result.pspace[0];
+        assert_eq!(keeper.len(), crate::constants::PMARS_PSPACE_SIZE);
+        assert_eq!(keeper[..3], [1, 1, FIRST_ROUND]);
+        assert!(result.pspace[1].iter().all(|&cell| cell == 0));
+    }
+}
@@ -0,0 +1,588 @@
+/// Battle recordings that replay without executing the champions
+///
+/// A recording holds the battle's configuration, a snapshot of its initial
+/// state and every event published after it, so a battle can be watched
+/// again from the file alone. Whenever the battle's state is replaced
+/// rather than evolved by events (a rewind, a restored snapshot, a poke in
+/// a what-if branch), a fresh snapshot follows, and a last one closes the
+/// recording with the final state.
+///
+/// # File layout
+///
+/// A recording starts with `CWRC` and a one-byte version (currently 1),
+/// followed by chunks of a one-byte kind, a u32 little-endian length and
+/// the payload:
+///
+/// | Kind   | Chunk    | Payload                                                |
+/// |--------|----------|--------------------------------------------------------|
+/// | `0x01` | Config   | [`GameConfig`] as JSON; always the first chunk         |
+/// | `0x02` | Keyframe | [`GameSnapshot`] as JSON; starts a new segment         |
+/// | `0x03` | Events   | An event stream (see [`crate::vm::protocol`]) continuing the segment |
+use crate::error::{CoreWarError, Result};
+use crate::vm::protocol::{self, EventEncoder, StampedEvent};
+use crate::vm::{GameConfig, GameEvent, GameSnapshot};
+use std::fs;
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+/// First bytes of every recording
+pub const MAGIC: [u8; 4] = *b"CWRC";
+
+/// Version of the recording layout written by [`Recorder`]
+pub const RECORDING_VERSION: u8 = 1;
+
+/// Replayed cycles between the snapshots kept for seeking backwards
+pub const REPLAY_CHECKPOINT_INTERVAL: u32 = 100;
+
+/// Size at which buffered events are written out as a chunk
+const CHUNK_BYTES: usize = 64 * 1024;
+
+const CHUNK_CONFIG: u8 = 0x01;
+const CHUNK_KEYFRAME: u8 = 0x02;
+const CHUNK_EVENTS: u8 = 0x03;
+
+/// A stretch of a battle: a starting state and the events that followed
+#[derive(Debug, Clone)]
+pub struct RecordedSegment {
+    /// State the segment starts from
+    pub keyframe: GameSnapshot,
+    /// Events published after the keyframe, in order
+    pub events: Vec<StampedEvent>,
+}
+
+/// A battle read back from a recording
+#[derive(Debug, Clone)]
+pub struct Recording {
+    /// Configuration the battle ran with
+    pub config: GameConfig,
+    /// Segments in the order they were recorded; never empty
+    pub segments: Vec<RecordedSegment>,
+}
+
+impl Recording {
+    /// Decode a recording held in memory
+    ///
+    /// # Arguments
+    /// * `bytes` - The recording, header included
+    ///
+    /// # Returns
+    /// The recording, or an error if it is malformed or from another version
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 5 || bytes[..4] != MAGIC {
+            return Err(CoreWarError::protocol("Not a battle recording (bad magic)"));
+        }
+        if bytes[4] != RECORDING_VERSION {
+            return Err(CoreWarError::protocol(format!(
+                "Unsupported recording version {} (expected {})",
+                bytes[4], RECORDING_VERSION
+            )));
+        }
+
+        let mut config = None;
+        let mut segments: Vec<RecordedSegment> = Vec::new();
+        let mut rest = &bytes[5..];
+        while !rest.is_empty() {
+            if rest.len() < 5 {
+                return Err(truncated());
+            }
+            let kind = rest[0];
+            let length = u32::from_le_bytes(rest[1..5].try_into().unwrap()) as usize;
+            let payload = rest.get(5..5 + length).ok_or_else(truncated)?;
+            rest = &rest[5 + length..];
+
+            match kind {
+                CHUNK_CONFIG if config.is_none() => {
+                    config = Some(
+                        serde_json::from_slice(payload)
+                            .map_err(|e| CoreWarError::protocol(format!("Bad config: {}", e)))?,
+                    );
+                }
+                CHUNK_KEYFRAME if config.is_some() => {
+                    let json = std::str::from_utf8(payload)
+                        .map_err(|e| CoreWarError::protocol(format!("Bad keyframe: {}", e)))?;
+                    segments.push(RecordedSegment {
+                        keyframe: GameSnapshot::from_json(json)?,
+                        events: Vec::new(),
+                    });
+                }
+                CHUNK_EVENTS => {
+                    let segment = segments.last_mut().ok_or_else(|| {
+                        CoreWarError::protocol("Recorded events come before any keyframe")
+                    })?;
+                    segment.events.extend(protocol::decode(payload)?.1);
+                }
+                CHUNK_CONFIG | CHUNK_KEYFRAME => {
+                    return Err(CoreWarError::protocol(
+                        "A recording must start with exactly one config chunk",
+                    ));
+                }
+                kind => {
+                    return Err(CoreWarError::protocol(format!(
+                        "Unknown recording chunk 0x{:02X}",
+                        kind
+                    )));
+                }
+            }
+        }
+
+        match config {
+            Some(config) if !segments.is_empty() => Ok(Self { config, segments }),
+            _ => Err(CoreWarError::protocol("Recording holds no battle state")),
+        }
+    }
+
+    /// Read a recording from a file
+    ///
+    /// # Arguments
+    /// * `path` - File written through [`crate::GameEngine::record`]
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    /// Get the cycle the recording ends at
+    pub fn final_cycle(&self) -> u32 {
+        let last = self.segments.last().expect("a recording has a segment");
+        last.events
+            .last()
+            .map_or(last.keyframe.cycle, |stamped| stamped.cycle)
+    }
+}
+
+/// Writes a battle recording as the battle runs
+pub struct Recorder<W: Write> {
+    writer: W,
+    /// Events since the last keyframe that were not written out yet
+    pending: Option<EventEncoder<Vec<u8>>>,
+    /// Memory size of the last keyframe, for the event stream headers
+    memory_size: usize,
+    /// Whether the battle's state changed in a way events cannot describe
+    keyframe_due: bool,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Start a recording by writing its header and the battle configuration
+    ///
+    /// A keyframe is due right away; events are dropped until it is written.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the recording
+    /// * `config` - Configuration of the recorded battle
+    ///
+    /// # Returns
+    /// The recorder, or an error if the header could not be written
+    pub fn new(mut writer: W, config: &GameConfig) -> Result<Self> {
+        let mut header = MAGIC.to_vec();
+        header.push(RECORDING_VERSION);
+        writer.write_all(&header)?;
+        let json = serde_json::to_vec(config).map_err(|e| CoreWarError::protocol(e.to_string()))?;
+        write_chunk(&mut writer, CHUNK_CONFIG, &json)?;
+
+        Ok(Self {
+            writer,
+            pending: None,
+            memory_size: config.rules.memory_size,
+            keyframe_due: true,
+        })
+    }
+
+    /// Check whether the next state must be written as a keyframe
+    pub fn keyframe_due(&self) -> bool {
+        self.keyframe_due
+    }
+
+    /// Note that the battle's state was replaced, so events no longer describe it
+    ///
+    /// Events are dropped until the next keyframe, which includes their effect.
+    pub fn invalidate(&mut self) {
+        self.keyframe_due = true;
+    }
+
+    /// Write the battle's state, starting a new segment
+    ///
+    /// # Arguments
+    /// * `snapshot` - The battle's current state
+    pub fn keyframe(&mut self, snapshot: &GameSnapshot) -> Result<()> {
+        self.write_pending()?;
+        write_chunk(
+            &mut self.writer,
+            CHUNK_KEYFRAME,
+            snapshot.to_json()?.as_bytes(),
+        )?;
+        self.memory_size = snapshot.rules.memory_size;
+        self.keyframe_due = false;
+        Ok(())
+    }
+
+    /// Add an event to the current segment
+    ///
+    /// # Arguments
+    /// * `cycle` - Cycle the event happened in
+    /// * `event` - The event
+    pub fn record(&mut self, cycle: u32, event: &GameEvent) -> Result<()> {
+        if self.keyframe_due {
+            return Ok(());
+        }
+        let encoder = match &mut self.pending {
+            Some(encoder) => encoder,
+            None => self
+                .pending
+                .insert(EventEncoder::new(Vec::new(), self.memory_size)?),
+        };
+        encoder.encode(cycle, event)?;
+        if encoder.get_ref().len() >= CHUNK_BYTES {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    /// Close the recording with the battle's final state and flush it
+    ///
+    /// # Arguments
+    /// * `snapshot` - The battle's final state
+    ///
+    /// # Returns
+    /// The destination, or an error if the recording could not be written
+    pub fn finish(mut self, snapshot: &GameSnapshot) -> Result<W> {
+        self.keyframe(snapshot)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Write buffered events out as a chunk
+    fn write_pending(&mut self) -> Result<()> {
+        if let Some(encoder) = self.pending.take() {
+            write_chunk(&mut self.writer, CHUNK_EVENTS, &encoder.into_inner())?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> std::fmt::Debug for Recorder<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recorder")
+            .field("keyframe_due", &self.keyframe_due)
+            .finish()
+    }
+}
+
+/// Replay state of an engine
+#[derive(Debug)]
+pub(crate) struct Replay {
+    /// Position in the recording
+    pub cursor: ReplayCursor,
+    /// Cycles the whole recording replays
+    pub length: u32,
+    /// States every [`REPLAY_CHECKPOINT_INTERVAL`] replayed cycles, with
+    /// the cursor there, oldest first
+    checkpoints: Vec<(GameSnapshot, ReplayCursor)>,
+}
+
+impl Replay {
+    /// Start replaying from the recording's first keyframe
+    ///
+    /// # Arguments
+    /// * `cursor` - Cursor returned by [`ReplayCursor::start`]
+    /// * `start` - The engine's state after applying the first keyframe
+    pub(crate) fn new(cursor: ReplayCursor, start: GameSnapshot) -> Self {
+        Self {
+            length: cursor.total_steps(),
+            checkpoints: vec![(start, cursor.clone())],
+            cursor,
+        }
+    }
+
+    /// Check whether the state after the current cycle should be kept
+    pub(crate) fn checkpoint_due(&self) -> bool {
+        let step = self.cursor.step();
+        step.is_multiple_of(REPLAY_CHECKPOINT_INTERVAL)
+            && self.checkpoints.last().is_none_or(|(_, c)| c.step() < step)
+    }
+
+    /// Keep the state after the current cycle to seek back to
+    ///
+    /// # Arguments
+    /// * `snapshot` - The engine's current state
+    pub(crate) fn checkpoint(&mut self, snapshot: GameSnapshot) {
+        self.checkpoints.push((snapshot, self.cursor.clone()));
+    }
+
+    /// Get the newest checkpoint at or before a number of replayed cycles
+    ///
+    /// # Arguments
+    /// * `step` - Number of replayed cycles to seek to
+    pub(crate) fn checkpoint_at_or_before(&self, step: u32) -> (GameSnapshot, ReplayCursor) {
+        self.checkpoints
+            .iter()
+            .rev()
+            .find(|(_, cursor)| cursor.step() <= step)
+            .cloned()
+            .expect("the replay's start is always kept")
+    }
+}
+
+/// Position of a replay in a recording
+///
+/// The recording is shared, so cursors are cheap to clone; the engine keeps
+/// one with every replay checkpoint to seek back to it.
+#[derive(Debug, Clone)]
+pub(crate) struct ReplayCursor {
+    recording: Arc<Recording>,
+    /// Index of the segment being replayed
+    segment: usize,
+    /// Index of the segment's next event to apply
+    event: usize,
+    /// Cycles replayed so far
+    step: u32,
+}
+
+/// Everything needed to replay one cycle
+#[derive(Debug)]
+pub(crate) struct ReplayStep {
+    /// State to jump to before the cycle, where a new segment starts
+    pub before: Option<GameSnapshot>,
+    /// The cycle replayed
+    pub cycle: u32,
+    /// The cycle's events
+    pub events: Vec<GameEvent>,
+    /// State to settle on after the cycle, where a segment starts at it
+    pub after: Option<GameSnapshot>,
+}
+
+/// Indices of one replayed cycle in the recording's segments
+struct StepIndices {
+    /// Segment whose keyframe is applied before the cycle
+    before: Option<usize>,
+    cycle: u32,
+    /// Segment the cycle's events belong to
+    segment: usize,
+    /// The cycle's events in that segment
+    events: Range<usize>,
+    /// Segment whose keyframe is applied after the cycle
+    after: Option<usize>,
+}
+
+impl ReplayCursor {
+    /// Start replaying a recording
+    ///
+    /// # Returns
+    /// The state to start from and the cursor after it
+    pub(crate) fn start(recording: Recording) -> (GameSnapshot, Self) {
+        let keyframe = recording.segments[0].keyframe.clone();
+        let cursor = Self {
+            recording: Arc::new(recording),
+            segment: 0,
+            event: 0,
+            step: 0,
+        };
+        (keyframe, cursor)
+    }
+
+    /// Move past the next cycle of the recording
+    ///
+    /// A segment whose events are used up gives way to the next one before
+    /// the cycle, so the battle jumps to its keyframe. A segment starting at
+    /// the cycle just replayed, such as the final state, takes over after it.
+    ///
+    /// # Arguments
+    /// * `cycle` - The cycle the battle is at
+    pub(crate) fn advance(&mut self, cycle: u32) -> ReplayStep {
+        let step = self.advance_indices(cycle);
+        let segments = &self.recording.segments;
+        ReplayStep {
+            before: step.before.map(|i| segments[i].keyframe.clone()),
+            cycle: step.cycle,
+            events: segments[step.segment].events[step.events]
+                .iter()
+                .map(|stamped| stamped.event.clone())
+                .collect(),
+            after: step.after.map(|i| segments[i].keyframe.clone()),
+        }
+    }
+
+    /// Check whether every recorded event and keyframe was replayed
+    pub(crate) fn is_finished(&self) -> bool {
+        self.event >= self.recording.segments[self.segment].events.len()
+            && self.segment + 1 >= self.recording.segments.len()
+    }
+
+    /// Get the number of cycles replayed so far
+    pub(crate) fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// Count the cycles the whole recording replays
+    pub(crate) fn total_steps(&self) -> u32 {
+        let mut cursor = Self {
+            recording: Arc::clone(&self.recording),
+            segment: 0,
+            event: 0,
+            step: 0,
+        };
+        let mut cycle = self.recording.segments[0].keyframe.cycle;
+        loop {
+            cycle = cursor.advance_indices(cycle).cycle;
+            if cursor.is_finished() {
+                return cursor.step;
+            }
+        }
+    }
+
+    /// Count the cycles until the replay reaches the next process death
+    ///
+    /// # Arguments
+    /// * `cycle` - The cycle the battle is at
+    ///
+    /// # Returns
+    /// The number of cycles to replay to include the next
+    /// [`GameEvent::ProcessDied`], or None if no process dies later
+    pub(crate) fn steps_to_next_death(&self, mut cycle: u32) -> Option<u32> {
+        let mut cursor = self.clone();
+        while !cursor.is_finished() {
+            let step = cursor.advance_indices(cycle);
+            cycle = step.cycle;
+            let died = cursor.recording.segments[step.segment].events[step.events]
+                .iter()
+                .any(|stamped| matches!(stamped.event, GameEvent::ProcessDied { .. }));
+            if died {
+                return Some(cursor.step - self.step);
+            }
+        }
+        None
+    }
+
+    /// Move past the next cycle without copying anything out
+    fn advance_indices(&mut self, cycle: u32) -> StepIndices {
+        let segments = &self.recording.segments;
+        let mut cycle = cycle;
+        let mut before = None;
+        if self.event >= segments[self.segment].events.len() && self.segment + 1 < segments.len() {
+            self.segment += 1;
+            self.event = 0;
+            before = Some(self.segment);
+            cycle = segments[self.segment].keyframe.cycle;
+        }
+
+        cycle += 1;
+        let segment = self.segment;
+        let first = self.event;
+        self.event += segments[segment].events[first..]
+            .iter()
+            .take_while(|stamped| stamped.cycle <= cycle)
+            .count();
+        let events = first..self.event;
+
+        let mut after = None;
+        if self.event >= segments[segment].events.len()
+            && segments
+                .get(segment + 1)
+                .is_some_and(|next| next.keyframe.cycle == cycle)
+        {
+            self.segment += 1;
+            self.event = 0;
+            after = Some(self.segment);
+        }
+
+        self.step += 1;
+        StepIndices {
+            before,
+            cycle,
+            segment,
+            events,
+            after,
+        }
+    }
+}
+
+/// Write a chunk with its kind and length
+fn write_chunk<W: Write>(writer: &mut W, kind: u8, payload: &[u8]) -> Result<()> {
+    writer.write_all(&[kind])?;
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)?;
+    Ok(())
+}
+
+/// Error for a recording that ends in the middle of a chunk
+fn truncated() -> CoreWarError {
+    CoreWarError::protocol("Recording ends in the middle of a chunk")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::GameEngine;
+
+    fn keyframe(cycle: u32) -> GameSnapshot {
+        let mut engine = GameEngine::builder()
+            .champion_bytes("Liver", [0x01; 10])
+            .build()
+            .unwrap();
+        engine.start().unwrap();
+        engine.run_cycles(cycle).unwrap();
+        engine.snapshot()
+    }
+
+    #[test]
+    fn test_recording_round_trips_segments() {
+        let config = GameConfig {
+            max_cycles: 77,
+            ..GameConfig::default()
+        };
+        let write = GameEvent::MemoryWrite {
+            address: 12,
+            value: 0xFF,
+            champion_id: 1,
+            process_id: 1,
+            opcode: 0x01,
+            pc: 11,
+        };
+
+        let mut recorder = Recorder::new(Vec::new(), &config).unwrap();
+        recorder.record(1, &write).unwrap();
+        recorder.keyframe(&keyframe(0)).unwrap();
+        recorder.record(1, &write).unwrap();
+        recorder
+            .record(1, &GameEvent::CycleCompleted { cycle: 1 })
+            .unwrap();
+        recorder.invalidate();
+        recorder.record(2, &write).unwrap();
+        let bytes = recorder.finish(&keyframe(1)).unwrap();
+
+        let recording = Recording::parse(&bytes).unwrap();
+        assert_eq!(recording.config.max_cycles, 77);
+        assert_eq!(recording.segments.len(), 2);
+        // Events before the first keyframe and after invalidating are dropped
+        let events: Vec<&GameEvent> = recording.segments[0]
+            .events
+            .iter()
+            .map(|stamped| &stamped.event)
+            .collect();
+        assert_eq!(
+            events,
+            vec![&write, &GameEvent::CycleCompleted { cycle: 1 }]
+        );
+        assert_eq!(recording.segments[1].keyframe.cycle, 1);
+        assert!(recording.segments[1].events.is_empty());
+        assert_eq!(recording.final_cycle(), 1);
+    }
+
+    #[test]
+    fn test_malformed_recordings_are_rejected() {
+        let mut recorder = Recorder::new(Vec::new(), &GameConfig::default()).unwrap();
+        recorder.keyframe(&keyframe(0)).unwrap();
+        let bytes = recorder.finish(&keyframe(0)).unwrap();
+        assert!(Recording::parse(&bytes).is_ok());
+
+        assert!(Recording::parse(b"CWEV\x01").is_err());
+        assert!(Recording::parse(&bytes[..bytes.len() - 1]).is_err());
+        let config_only = Recorder::new(Vec::new(), &GameConfig::default())
+            .unwrap()
+            .writer;
+        assert!(Recording::parse(&config_only).is_err());
+
+        let mut unknown = bytes.clone();
+        unknown.extend_from_slice(&[0x7F, 0, 0, 0, 0]);
+        assert!(Recording::parse(&unknown).is_err());
+    }
+}
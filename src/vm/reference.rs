@@ -0,0 +1,306 @@
+/// Built-in reference documentation for instructions and VM concepts
+///
+/// This module holds the structured reference table behind `corewar explain`
+/// and the terminal UI's process inspector, so every place that describes an
+/// instruction says the same thing.
+use crate::vm::{Instruction, Rules};
+
+/// Reference entry for one instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionReference {
+    /// The instruction described
+    pub instruction: Instruction,
+    /// One-line summary
+    pub summary: &'static str,
+    /// What the instruction does in detail
+    pub details: &'static str,
+}
+
+impl InstructionReference {
+    /// Look up the reference entry for an instruction
+    ///
+    /// # Arguments
+    /// * `instruction` - The instruction to describe
+    ///
+    /// # Returns
+    /// The reference entry
+    pub fn of(instruction: Instruction) -> Self {
+        let (summary, details) = match instruction {
+            Instruction::Live => (
+                "Report the champion alive",
+                "Marks the executing process as alive for the next death check and \
+                 reports its champion alive. The last champion reported alive wins.",
+            ),
+            Instruction::Ld => (
+                "Load a value into a register",
+                "Loads the first operand into the register given as the second operand. \
+                 Indirect operands read 4 bytes at PC + (offset % IDX_MOD).",
+            ),
+            Instruction::St => (
+                "Store a register",
+                "Writes the register in the first operand to the register or memory \
+                 address (PC + offset % IDX_MOD) given by the second operand.",
+            ),
+            Instruction::Add => (
+                "Add two registers",
+                "Stores the sum of the first two registers in the third register.",
+            ),
+            Instruction::Sub => (
+                "Subtract two registers",
+                "Stores the first register minus the second in the third register.",
+            ),
+            Instruction::And => (
+                "Bitwise AND",
+                "Stores the bitwise AND of the first two operands in the third register.",
+            ),
+            Instruction::Or => (
+                "Bitwise OR",
+                "Stores the bitwise OR of the first two operands in the third register.",
+            ),
+            Instruction::Xor => (
+                "Bitwise XOR",
+                "Stores the bitwise XOR of the first two operands in the third register.",
+            ),
+            Instruction::Zjmp => (
+                "Jump if carry is set",
+                "Moves the PC by the direct operand (% IDX_MOD) when the carry flag is \
+                 set; otherwise does nothing.",
+            ),
+            Instruction::Ldi => (
+                "Load from an indexed address",
+                "Adds the first two operands to form an offset, then loads 4 bytes from \
+                 PC + (offset % IDX_MOD) into the third register.",
+            ),
+            Instruction::Sti => (
+                "Store to an indexed address",
+                "Adds the second and third operands to form an offset, then writes the \
+                 first register to PC + (offset % IDX_MOD).",
+            ),
+            Instruction::Fork => (
+                "Create a new process",
+                "Creates a copy of the executing process at PC + (operand % IDX_MOD). \
+                 The child inherits registers and carry and runs from the next cycle.",
+            ),
+            Instruction::Lld => (
+                "Long load",
+                "Like ld, but indirect operands are not reduced by IDX_MOD.",
+            ),
+            Instruction::Lldi => (
+                "Long indexed load",
+                "Like ldi, but the final address is not reduced by IDX_MOD.",
+            ),
+            Instruction::Lfork => (
+                "Long fork",
+                "Like fork, but the child's address is not reduced by IDX_MOD.",
+            ),
+            Instruction::Aff => (
+                "Display a character",
+                "Prints the register value modulo 256 as an ASCII character.",
+            ),
+            #[cfg(feature = "pspace")]
+            Instruction::Ldp => (
+                "Load from private storage",
+                "Loads the p-space cell numbered by the first operand (modulo the \
+                 p-space size) into the register given as the second operand. Cell 0 \
+                 holds the result of the previous round of the match.",
+            ),
+            #[cfg(feature = "pspace")]
+            Instruction::Stp => (
+                "Store to private storage",
+                "Writes the register in the first operand to the p-space cell numbered \
+                 by the second operand (modulo the p-space size). The cells are kept \
+                 across the rounds of a match.",
+            ),
+        };
+
+        Self {
+            instruction,
+            summary,
+            details,
+        }
+    }
+
+    /// Describe the accepted operand types, e.g. `%N|N, rN`
+    pub fn operand_signature(&self) -> String {
+        self.instruction
+            .operand_types()
+            .iter()
+            .map(|types| {
+                types
+                    .iter()
+                    .map(|t| t.syntax())
+                    .collect::<Vec<_>>()
+                    .join("|")
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Render the full reference text
+    pub fn render(&self) -> String {
+        let instruction = self.instruction;
+        let carry = if instruction.sets_carry() {
+            "set when the result is zero, cleared otherwise"
+        } else {
+            "unchanged"
+        };
+        let addressing = if instruction.uses_long_addressing() {
+            "long (offsets are not reduced by IDX_MOD)"
+        } else {
+            "relative to PC, offsets reduced by IDX_MOD"
+        };
+
+        format!(
+            "{} - {}\n\n{}\n\nOpcode:     0x{:02X}\nOperands:   {}\nCycles:     {}\nCarry:      {}\nAddressing: {}\n",
+            instruction.name(),
+            self.summary,
+            self.details,
+            instruction.opcode(),
+            self.operand_signature(),
+            instruction.cycles(),
+            carry,
+            addressing
+        )
+    }
+}
+
+/// Reference entry for a VM concept
+#[derive(Debug, Clone, Copy)]
+pub struct ConceptReference {
+    /// Canonical name
+    pub name: &'static str,
+    /// Explanation text
+    pub text: &'static str,
+    /// Reads the standard value from the default rules, if the concept has one
+    pub default: Option<fn(&Rules) -> String>,
+}
+
+/// VM concepts that can be explained
+pub const CONCEPTS: &[ConceptReference] = &[
+    ConceptReference {
+        name: "MEMORY_SIZE",
+        text: "Size of the circular core in bytes. Every address wraps modulo this size.",
+        default: Some(|rules| rules.memory_size.to_string()),
+    },
+    ConceptReference {
+        name: "IDX_MOD",
+        text: "Relative offsets used by most instructions are reduced modulo IDX_MOD, \
+               limiting how far a process can reach from its PC. Long instructions \
+               (lld, lldi, lfork) skip this reduction.",
+        default: Some(|rules| rules.idx_mod.to_string()),
+    },
+    ConceptReference {
+        name: "CYCLE_TO_DIE",
+        text: "Length of a period between death checks. At each check, processes that \
+               have not executed live since the previous check are killed.",
+        default: Some(|rules| rules.cycle_to_die.to_string()),
+    },
+    ConceptReference {
+        name: "CYCLE_DELTA",
+        text: "Amount CYCLE_TO_DIE shrinks by when a period reports NBR_LIVE lives, or \
+               after MAX_CHECKS checks without a reduction. The battle ends when it \
+               reaches zero.",
+        default: Some(|rules| rules.cycle_delta.to_string()),
+    },
+    ConceptReference {
+        name: "NBR_LIVE",
+        text: "Number of live instructions in one period that triggers a CYCLE_TO_DIE \
+               reduction. See LIVE_RESET for when that period ends.",
+        default: Some(|rules| rules.nbr_live.to_string()),
+    },
+    ConceptReference {
+        name: "LIVE_RESET",
+        text: "When a period that reached NBR_LIVE lives ends. With at-check, the period \
+               runs its full CYCLE_TO_DIE cycles and the reduction happens at the \
+               scheduled death check; with immediate, the death check runs at the end \
+               of the cycle that reported the NBR_LIVE-th live.",
+        default: Some(|rules| rules.live_reset.to_string()),
+    },
+    ConceptReference {
+        name: "MAX_CHECKS",
+        text: "Number of consecutive death checks without a reduction after which \
+               CYCLE_TO_DIE is reduced anyway.",
+        default: Some(|rules| rules.max_checks.to_string()),
+    },
+    ConceptReference {
+        name: "CARRY",
+        text: "Per-process flag set by ld, lld, ldi, lldi, and, or and xor when their \
+               result is zero. zjmp only jumps when it is set.",
+        default: None,
+    },
+];
+
+impl ConceptReference {
+    /// Render the full reference text
+    pub fn render(&self) -> String {
+        let mut text = format!("{}\n\n{}\n", self.name, self.text);
+        if let Some(default) = self.default {
+            text.push_str(&format!("\nDefault: {}\n", default(&Rules::default())));
+        }
+        text
+    }
+}
+
+/// Every topic that can be explained
+pub fn topics() -> Vec<&'static str> {
+    Instruction::ALL
+        .iter()
+        .map(|i| i.name())
+        .chain(CONCEPTS.iter().map(|c| c.name))
+        .collect()
+}
+
+/// Look up the reference text for an instruction mnemonic or concept
+///
+/// # Arguments
+/// * `topic` - Mnemonic (e.g. `sti`) or concept name (e.g. `idx_mod`), case-insensitive
+///
+/// # Returns
+/// The reference text, or None if the topic is unknown
+pub fn explain(topic: &str) -> Option<String> {
+    let topic = topic.trim();
+
+    if let Some(instruction) = Instruction::ALL
+        .iter()
+        .find(|i| i.name().eq_ignore_ascii_case(topic))
+    {
+        return Some(InstructionReference::of(*instruction).render());
+    }
+
+    CONCEPTS
+        .iter()
+        .find(|c| {
+            c.name.eq_ignore_ascii_case(topic)
+                || c.name.replace('_', "-").eq_ignore_ascii_case(topic)
+        })
+        .map(ConceptReference::render)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_instruction() {
+        let text = explain("STI").unwrap();
+        assert!(text.starts_with("sti - "));
+        assert!(text.contains("Opcode:     0x0B"));
+        assert!(text.contains("Operands:   rN, rN|%N|N, %N|rN"));
+        assert!(text.contains("Cycles:     25"));
+    }
+
+    #[test]
+    fn test_explain_concept() {
+        let text = explain("idx_mod").unwrap();
+        assert!(text.contains("Default: 512"));
+        assert!(explain("cycle-to-die").is_some());
+        assert!(explain("nonsense").is_none());
+    }
+
+    #[test]
+    fn test_every_topic_is_explained() {
+        for topic in topics() {
+            assert!(explain(topic).is_some(), "{}", topic);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+/// Automatic rematches of drawn battles
+///
+/// Single-elimination tournaments need a decisive result from every match.
+/// [`play_until_decisive`] replays a drawn battle with fresh seeds until one
+/// of the battles has a winner or the rematch budget runs out. Rematch seeds
+/// are drawn from a generator seeded with the first battle's seed, so the
+/// whole series replays exactly.
+use crate::error::Result;
+use crate::vm::{GameConfig, GameEngine, SeededRng};
+use std::fmt;
+
+/// Result of one battle in a rematch series
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchResult {
+    /// Seed the battle ran with
+    pub seed: u64,
+    /// Cycles the battle lasted
+    pub cycles: u32,
+    /// Winning champion ID and display name, or None for a draw
+    pub winner: Option<(u8, String)>,
+}
+
+impl fmt::Display for MatchResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.winner {
+            Some((id, name)) => write!(
+                f,
+                "seed {}: Champion {} ({}) wins after {} cycles",
+                self.seed, id, name, self.cycles
+            ),
+            None => write!(f, "seed {}: draw after {} cycles", self.seed, self.cycles),
+        }
+    }
+}
+
+/// Battles played until one was decisive or the rematch budget ran out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RematchSeries {
+    /// Every battle played, in order; only the last can be decisive
+    pub matches: Vec<MatchResult>,
+    /// Maximum number of rematches that were allowed
+    pub rematch_limit: u32,
+}
+
+impl RematchSeries {
+    /// Get the result of the last battle, which decides the series
+    pub fn deciding_match(&self) -> &MatchResult {
+        self.matches
+            .last()
+            .expect("a rematch series always has a first match")
+    }
+
+    /// Get the series winner, or None if every battle was drawn
+    pub fn winner(&self) -> Option<u8> {
+        self.deciding_match().winner.as_ref().map(|(id, _)| *id)
+    }
+
+    /// Number of rematches played after the first battle
+    pub fn rematches(&self) -> usize {
+        self.matches.len() - 1
+    }
+}
+
+impl fmt::Display for RematchSeries {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, result) in self.matches.iter().enumerate() {
+            writeln!(f, "Match {}, {}", i + 1, result)?;
+        }
+        match &self.deciding_match().winner {
+            Some((id, name)) => write!(
+                f,
+                "Decided: Champion {} ({}) after {} rematches",
+                id,
+                name,
+                self.rematches()
+            ),
+            None => write!(
+                f,
+                "Undecided: all {} rematches were drawn",
+                self.rematch_limit
+            ),
+        }
+    }
+}
+
+/// Seeds of a rematch series: the first battle's seed, then derived seeds
+///
+/// # Arguments
+/// * `seed` - Seed of the first battle
+///
+/// # Returns
+/// An endless iterator of battle seeds
+pub fn rematch_seeds(seed: u64) -> impl Iterator<Item = u64> {
+    let mut rng = SeededRng::new(seed);
+    std::iter::once(seed).chain(std::iter::repeat_with(move || rng.next_u64()))
+}
+
+/// Play a battle, replaying it with new seeds while it ends in a draw
+///
+/// Each battle runs to completion with the configuration's seed replaced by
+/// the next of [`rematch_seeds`]; memory dumps and pausing are turned off.
+/// A battle that a tie-breaker decides counts as decisive.
+///
+/// # Arguments
+/// * `config` - Configuration of the first battle
+/// * `rematch_limit` - Maximum number of rematches after the first battle
+/// * `load` - Loads the champions into each fresh engine
+///
+/// # Returns
+/// The battles played, the last of which decides the series
+pub fn play_until_decisive<F>(
+    config: &GameConfig,
+    rematch_limit: u32,
+    mut load: F,
+) -> Result<RematchSeries>
+where
+    F: FnMut(&mut GameEngine) -> Result<()>,
+{
+    let mut matches = Vec::new();
+    for seed in rematch_seeds(config.seed).take(rematch_limit as usize + 1) {
+        let (_, result) = play_battle(config, seed, &mut load)?;
+        let decided = result.winner.is_some();
+        matches.push(result);
+        if decided {
+            break;
+        }
+    }
+
+    Ok(RematchSeries {
+        matches,
+        rematch_limit,
+    })
+}
+
+/// Play one battle to completion with a seed, without dumps or pausing
+///
+/// # Arguments
+/// * `config` - Configuration of the battle, apart from the seed
+/// * `seed` - Seed of the battle
+/// * `load` - Loads the champions into the fresh engine
+///
+/// # Returns
+/// The finished engine and the battle's result
+pub(crate) fn play_battle<F>(
+    config: &GameConfig,
+    seed: u64,
+    load: &mut F,
+) -> Result<(GameEngine, MatchResult)>
+where
+    F: FnMut(&mut GameEngine) -> Result<()>,
+{
+    let mut engine = GameEngine::new(GameConfig {
+        seed,
+        dump_cycles: 0,
+        start_paused: false,
+        ..*config
+    });
+    load(&mut engine)?;
+
+    let winner = engine.run_to_completion()?.map(|id| {
+        let name = engine
+            .champions()
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.display_name().to_string())
+            .unwrap_or_default();
+        (id, name)
+    });
+    let result = MatchResult {
+        seed,
+        cycles: engine.get_stats().cycle,
+        winner,
+    };
+    Ok((engine, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{Champion, TieBreaker, TieBreakers};
+
+    /// Two champions that never report alive, so every battle is drawn
+    /// unless a tie-breaker decides it; the first owns more memory
+    fn load_survivors(engine: &mut GameEngine) -> Result<()> {
+        engine.install_champions(vec![
+            Champion::new(1, "Big".to_string(), String::new(), vec![0x02; 300], 0),
+            Champion::new(2, "Small".to_string(), String::new(), vec![0x02; 260], 2048),
+        ])
+    }
+
+    fn config(tie_breakers: TieBreakers) -> GameConfig {
+        GameConfig {
+            max_cycles: 50,
+            seed: 7,
+            tie_breakers,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_rematch_seeds_start_with_the_first_seed() {
+        let seeds: Vec<u64> = rematch_seeds(7).take(4).collect();
+        assert_eq!(seeds[0], 7);
+        assert_eq!(seeds, rematch_seeds(7).take(4).collect::<Vec<_>>());
+        assert_ne!(seeds[1], seeds[2]);
+    }
+
+    #[test]
+    fn test_drawn_battles_are_replayed_up_to_the_limit() {
+        let series =
+            play_until_decisive(&config(TieBreakers::default()), 3, load_survivors).unwrap();
+
+        assert_eq!(series.matches.len(), 4);
+        assert_eq!(series.winner(), None);
+        let seeds: Vec<u64> = series.matches.iter().map(|m| m.seed).collect();
+        assert_eq!(seeds, rematch_seeds(7).take(4).collect::<Vec<_>>());
+        assert!(
+            series
+                .to_string()
+                .ends_with("Undecided: all 3 rematches were drawn")
+        );
+    }
+
+    #[test]
+    fn test_decisive_battle_ends_the_series() {
+        let tie_breakers = TieBreakers::new(&[TieBreaker::MemoryCoverage]).unwrap();
+        let series = play_until_decisive(&config(tie_breakers), 3, load_survivors).unwrap();
+
+        assert_eq!(series.matches.len(), 1);
+        assert_eq!(series.rematches(), 0);
+        assert_eq!(series.winner(), Some(1));
+        assert_eq!(series.deciding_match().winner, Some((1, "Big".to_string())));
+    }
+}
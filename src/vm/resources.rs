@@ -0,0 +1,233 @@
+/// Resource usage measurement for Core War battles
+///
+/// This module samples CPU time, peak resident set size and (with the
+/// `alloc-counter` feature) heap allocations so battles can report what they
+/// cost to run. CPU time is measured for the calling thread where the platform
+/// allows it, so battles running on separate threads do not count each other.
+/// Peak RSS and allocation counters are process-wide.
+use std::fmt;
+use std::time::Duration;
+
+/// Resources consumed by a battle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// CPU time spent (None if the platform cannot measure it)
+    pub cpu_time: Option<Duration>,
+    /// Peak resident set size of the process in bytes
+    pub peak_rss_bytes: Option<u64>,
+    /// Number of heap allocations (requires the `alloc-counter` feature)
+    pub allocations: Option<u64>,
+    /// Total bytes allocated (requires the `alloc-counter` feature)
+    pub allocated_bytes: Option<u64>,
+}
+
+impl fmt::Display for ResourceUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cpu_time {
+            Some(cpu_time) => writeln!(f, "CPU time: {:.3}s", cpu_time.as_secs_f64())?,
+            None => writeln!(f, "CPU time: unavailable")?,
+        }
+        match self.peak_rss_bytes {
+            Some(bytes) => writeln!(f, "Peak RSS: {:.1} MiB", bytes as f64 / (1024.0 * 1024.0))?,
+            None => writeln!(f, "Peak RSS: unavailable")?,
+        }
+        match (self.allocations, self.allocated_bytes) {
+            (Some(count), Some(bytes)) => write!(f, "Allocations: {} ({} bytes)", count, bytes),
+            _ => write!(
+                f,
+                "Allocations: not counted (enable the alloc-counter feature)"
+            ),
+        }
+    }
+}
+
+/// Point-in-time sample of the counters a battle is measured against
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResourceSnapshot {
+    cpu_time: Option<Duration>,
+    allocations: Option<u64>,
+    allocated_bytes: Option<u64>,
+}
+
+impl ResourceSnapshot {
+    /// Sample the current counters
+    pub fn capture() -> Self {
+        let (allocations, allocated_bytes) = allocation_counts();
+        Self {
+            cpu_time: sys::cpu_time(),
+            allocations,
+            allocated_bytes,
+        }
+    }
+
+    /// Measure the resources used since this snapshot was taken
+    ///
+    /// # Returns
+    /// The usage between this snapshot and now
+    pub fn usage_since(&self) -> ResourceUsage {
+        let now = Self::capture();
+        ResourceUsage {
+            cpu_time: now
+                .cpu_time
+                .zip(self.cpu_time)
+                .map(|(now, then)| now.saturating_sub(then)),
+            peak_rss_bytes: sys::peak_rss_bytes(),
+            allocations: now
+                .allocations
+                .zip(self.allocations)
+                .map(|(now, then)| now.saturating_sub(then)),
+            allocated_bytes: now
+                .allocated_bytes
+                .zip(self.allocated_bytes)
+                .map(|(now, then)| now.saturating_sub(then)),
+        }
+    }
+}
+
+#[cfg(feature = "alloc-counter")]
+fn allocation_counts() -> (Option<u64>, Option<u64>) {
+    use std::sync::atomic::Ordering;
+    (
+        Some(counting::ALLOCATIONS.load(Ordering::Relaxed)),
+        Some(counting::ALLOCATED_BYTES.load(Ordering::Relaxed)),
+    )
+}
+
+#[cfg(not(feature = "alloc-counter"))]
+fn allocation_counts() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+/// Global allocator that counts allocations on top of the system allocator
+#[cfg(feature = "alloc-counter")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    pub static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+    struct CountingAllocator;
+
+    fn record(size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            record(layout.size());
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            record(layout.size());
+            unsafe { System.alloc_zeroed(layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            record(new_size);
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::time::Duration;
+
+    #[cfg(target_os = "linux")]
+    const RUSAGE_SCOPE: libc::c_int = libc::RUSAGE_THREAD;
+    #[cfg(not(target_os = "linux"))]
+    const RUSAGE_SCOPE: libc::c_int = libc::RUSAGE_SELF;
+
+    fn rusage(who: libc::c_int) -> Option<libc::rusage> {
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+        // SAFETY: getrusage only writes into the provided buffer
+        let result = unsafe { libc::getrusage(who, usage.as_mut_ptr()) };
+        // SAFETY: a zero return means the buffer was fully initialized
+        (result == 0).then(|| unsafe { usage.assume_init() })
+    }
+
+    fn timeval_to_duration(time: libc::timeval) -> Duration {
+        Duration::new(time.tv_sec as u64, (time.tv_usec as u32) * 1000)
+    }
+
+    pub fn cpu_time() -> Option<Duration> {
+        rusage(RUSAGE_SCOPE)
+            .map(|usage| timeval_to_duration(usage.ru_utime) + timeval_to_duration(usage.ru_stime))
+    }
+
+    pub fn peak_rss_bytes() -> Option<u64> {
+        let max_rss = rusage(libc::RUSAGE_SELF)?.ru_maxrss as u64;
+        // Linux reports kilobytes, macOS reports bytes
+        if cfg!(target_os = "macos") {
+            Some(max_rss)
+        } else {
+            Some(max_rss * 1024)
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::time::Duration;
+
+    pub fn cpu_time() -> Option<Duration> {
+        None
+    }
+
+    pub fn peak_rss_bytes() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_since_snapshot() {
+        let snapshot = ResourceSnapshot::capture();
+
+        // Burn a little CPU so there is something to measure
+        let mut total = 0u64;
+        for i in 0..200_000u64 {
+            total = total.wrapping_add(i * i);
+        }
+        assert!(total > 0);
+
+        let usage = snapshot.usage_since();
+        if cfg!(unix) {
+            assert!(usage.cpu_time.is_some());
+            assert!(usage.peak_rss_bytes.unwrap() > 0);
+        }
+        assert_eq!(usage.allocations.is_some(), cfg!(feature = "alloc-counter"));
+    }
+
+    #[cfg(feature = "alloc-counter")]
+    #[test]
+    fn test_allocations_are_counted() {
+        let snapshot = ResourceSnapshot::capture();
+        let buffer = std::hint::black_box(vec![0u8; 4096]);
+        let usage = snapshot.usage_since();
+        drop(buffer);
+
+        assert!(usage.allocations.unwrap() >= 1);
+        assert!(usage.allocated_bytes.unwrap() >= 4096);
+    }
+
+    #[test]
+    fn test_display_without_measurements() {
+        let text = ResourceUsage::default().to_string();
+        assert!(text.contains("CPU time: unavailable"));
+        assert!(text.contains("alloc-counter"));
+    }
+}
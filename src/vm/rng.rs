@@ -0,0 +1,151 @@
+/// Deterministic randomness and state hashing for Core War battles
+///
+/// Battles must be reproducible: every nondeterministic choice draws from a
+/// [`SeededRng`] owned by the engine, and [`StateHasher`] produces digests
+/// that are stable across platforms and Rust releases so two runs can be
+/// compared by a single number.
+use serde::{Deserialize, Serialize};
+use std::hash::Hasher;
+
+/// Seed used when none is given
+pub const DEFAULT_SEED: u64 = 0;
+
+/// Small seeded pseudo-random number generator (SplitMix64)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Create a generator from a seed
+    ///
+    /// # Arguments
+    /// * `seed` - The seed; equal seeds produce equal sequences
+    ///
+    /// # Returns
+    /// A new SeededRng instance
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Get the next 64-bit value
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Get a value in `0..bound`
+    ///
+    /// # Arguments
+    /// * `bound` - Exclusive upper bound (must be positive)
+    ///
+    /// # Returns
+    /// A value below `bound`
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "bound must be positive");
+        // Multiply-shift keeps the bias negligible for arena-sized bounds
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+impl Default for SeededRng {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+/// Platform-independent 64-bit FNV-1a hasher used for state digests
+#[derive(Debug, Clone)]
+pub struct StateHasher {
+    hash: u64,
+}
+
+impl StateHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    /// Create a new hasher
+    pub fn new() -> Self {
+        Self {
+            hash: Self::OFFSET_BASIS,
+        }
+    }
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash ^= u64::from(byte);
+            self.hash = self.hash.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    // Fix integer encodings so digests do not depend on the target
+    fn write_usize(&mut self, value: usize) {
+        self.write(&(value as u64).to_le_bytes());
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = SeededRng::new(42);
+        let mut b = SeededRng::new(42);
+        let mut c = SeededRng::new(43);
+
+        let first: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        let second: Vec<u64> = (0..8).map(|_| b.next_u64()).collect();
+        let third: Vec<u64> = (0..8).map(|_| c.next_u64()).collect();
+
+        assert_eq!(first, second);
+        assert_ne!(first, third);
+    }
+
+    #[test]
+    fn test_next_below_in_range() {
+        let mut rng = SeededRng::new(7);
+        for _ in 0..1000 {
+            assert!(rng.next_below(6) < 6);
+        }
+    }
+
+    #[test]
+    fn test_state_hasher_is_stable() {
+        let mut hasher = StateHasher::new();
+        hasher.write(b"corewar");
+        // Known FNV-1a 64 value, so digests stay comparable across builds
+        assert_eq!(hasher.finish(), 0x2b69_e0fb_85f2_1c64);
+    }
+}
@@ -0,0 +1,393 @@
+/// Virtual machine rules for Core War battles
+///
+/// This module groups the arena parameters that used to be fixed constants so
+/// battles can run on non-standard arenas (smaller cores, faster death checks).
+/// The defaults match the values in [`crate::constants`]; [`RulesPreset`]
+/// names other sets, such as one matching pMARS.
+use crate::constants::{
+    CYCLE_DELTA, CYCLE_TO_DIE, IDX_MOD, MAX_CHAMPIONS, MAX_CHECKS, MEMORY_SIZE, NBR_LIVE,
+    PMARS_CORE_SIZE, PMARS_MAX_CYCLES, PMARS_MAX_PROCESSES,
+};
+use crate::error::{CoreWarError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// When a period that reached `NBR_LIVE` lives ends
+///
+/// Core War implementations disagree here: some only look at the live count
+/// at the scheduled death check, others start a new, shorter period as soon
+/// as the count is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LiveReset {
+    /// The period runs its full `CYCLE_TO_DIE` cycles and the reduction
+    /// happens at the scheduled death check
+    #[default]
+    AtCheck,
+    /// The death check runs at the end of the cycle that reported the
+    /// `NBR_LIVE`-th live, ending the period early
+    Immediate,
+}
+
+impl FromStr for LiveReset {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "at-check" => Ok(LiveReset::AtCheck),
+            "immediate" => Ok(LiveReset::Immediate),
+            _ => Err(CoreWarError::game_state(format!(
+                "Unknown live reset '{}' (expected at-check or immediate)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LiveReset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LiveReset::AtCheck => write!(f, "at-check"),
+            LiveReset::Immediate => write!(f, "immediate"),
+        }
+    }
+}
+
+/// Arena parameters used by memory, the scheduler and the champion loader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Rules {
+    /// Memory size in bytes
+    pub memory_size: usize,
+    /// Index modulo for memory addressing
+    pub idx_mod: usize,
+    /// Initial cycle to die value
+    pub cycle_to_die: u32,
+    /// Cycle reduction amount
+    pub cycle_delta: u32,
+    /// Number of lives required before cycle reduction
+    pub nbr_live: u32,
+    /// Whether reaching `nbr_live` ends the period right away
+    #[serde(default)]
+    pub live_reset: LiveReset,
+    /// Number of death checks without a reduction before cycle_to_die is reduced anyway
+    pub max_checks: u32,
+    /// Maximum number of champions
+    pub max_champions: usize,
+    /// Processes each champion may have; forks beyond it are dropped (0 = unlimited)
+    #[serde(default)]
+    pub max_processes: usize,
+    /// Whether death checks kill processes that did not report live
+    ///
+    /// Without them processes only die by faulting, and the battle runs
+    /// until one champion is left or the cycle limit is reached.
+    #[serde(default = "default_require_live")]
+    pub require_live: bool,
+    /// Cells of private storage each champion has for `ldp` and `stp` (0 = no p-space)
+    ///
+    /// Only the `pspace` feature has those instructions; without it the
+    /// setting is kept, so rules read and written by any build agree.
+    #[serde(default)]
+    pub pspace_size: usize,
+}
+
+/// Rules saved before `require_live` existed had death checks
+fn default_require_live() -> bool {
+    true
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            memory_size: MEMORY_SIZE,
+            idx_mod: IDX_MOD,
+            cycle_to_die: CYCLE_TO_DIE,
+            cycle_delta: CYCLE_DELTA,
+            nbr_live: NBR_LIVE,
+            live_reset: LiveReset::AtCheck,
+            max_checks: MAX_CHECKS,
+            max_champions: MAX_CHAMPIONS,
+            max_processes: 0,
+            require_live: true,
+            pspace_size: 0,
+        }
+    }
+}
+
+/// A named set of rules
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RulesPreset {
+    /// The standard arena of [`crate::constants`]
+    #[default]
+    Standard,
+    /// Settings matching pMARS, so results can be compared with it: an 8000
+    /// byte core without index limits, 8000 processes per warrior, no death
+    /// checks, a draw after 80000 cycles and 500 cells of p-space
+    Pmars,
+}
+
+impl RulesPreset {
+    /// Get the arena rules of this preset
+    pub fn rules(self) -> Rules {
+        match self {
+            RulesPreset::Standard => Rules::default(),
+            RulesPreset::Pmars => Rules {
+                memory_size: PMARS_CORE_SIZE,
+                // pMARS reads and writes anywhere in the core by default
+                idx_mod: PMARS_CORE_SIZE,
+                max_processes: PMARS_MAX_PROCESSES,
+                require_live: false,
+                pspace_size: crate::constants::PMARS_PSPACE_SIZE,
+                ..Rules::default()
+            },
+        }
+    }
+
+    /// Get the cycle limit of this preset
+    ///
+    /// # Returns
+    /// The number of cycles after which the battle is a draw (0 = unlimited)
+    pub fn max_cycles(self) -> u32 {
+        match self {
+            RulesPreset::Standard => 0,
+            RulesPreset::Pmars => PMARS_MAX_CYCLES,
+        }
+    }
+}
+
+impl FromStr for RulesPreset {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "standard" => Ok(RulesPreset::Standard),
+            "pmars" => Ok(RulesPreset::Pmars),
+            _ => Err(CoreWarError::game_state(format!(
+                "Unknown rules preset '{}' (expected standard or pmars)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for RulesPreset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RulesPreset::Standard => write!(f, "standard"),
+            RulesPreset::Pmars => write!(f, "pmars"),
+        }
+    }
+}
+
+impl Rules {
+    /// Set the memory size in bytes
+    pub fn with_memory_size(mut self, memory_size: usize) -> Self {
+        self.memory_size = memory_size;
+        self
+    }
+
+    /// Set the index modulo for memory addressing
+    pub fn with_idx_mod(mut self, idx_mod: usize) -> Self {
+        self.idx_mod = idx_mod;
+        self
+    }
+
+    /// Set the initial cycle to die value
+    pub fn with_cycle_to_die(mut self, cycle_to_die: u32) -> Self {
+        self.cycle_to_die = cycle_to_die;
+        self
+    }
+
+    /// Set the cycle reduction amount
+    pub fn with_cycle_delta(mut self, cycle_delta: u32) -> Self {
+        self.cycle_delta = cycle_delta;
+        self
+    }
+
+    /// Set the number of lives required before cycle reduction
+    pub fn with_nbr_live(mut self, nbr_live: u32) -> Self {
+        self.nbr_live = nbr_live;
+        self
+    }
+
+    /// Set whether reaching `nbr_live` ends the period right away
+    pub fn with_live_reset(mut self, live_reset: LiveReset) -> Self {
+        self.live_reset = live_reset;
+        self
+    }
+
+    /// Set the number of death checks without a reduction before cycle_to_die is reduced anyway
+    pub fn with_max_checks(mut self, max_checks: u32) -> Self {
+        self.max_checks = max_checks;
+        self
+    }
+
+    /// Set the maximum number of champions
+    pub fn with_max_champions(mut self, max_champions: usize) -> Self {
+        self.max_champions = max_champions;
+        self
+    }
+
+    /// Set the processes each champion may have (0 = unlimited)
+    pub fn with_max_processes(mut self, max_processes: usize) -> Self {
+        self.max_processes = max_processes;
+        self
+    }
+
+    /// Set whether death checks kill processes that did not report live
+    pub fn with_require_live(mut self, require_live: bool) -> Self {
+        self.require_live = require_live;
+        self
+    }
+
+    /// Set the cells of p-space each champion has (0 = no p-space)
+    pub fn with_pspace_size(mut self, pspace_size: usize) -> Self {
+        self.pspace_size = pspace_size;
+        self
+    }
+
+    /// Check that the rules describe a playable arena
+    ///
+    /// # Returns
+    /// `Ok(())` if the rules are consistent, error otherwise
+    pub fn validate(&self) -> Result<()> {
+        if self.memory_size == 0 {
+            return Err(CoreWarError::game_state("Memory size must be positive"));
+        }
+
+        if self.idx_mod == 0 || self.idx_mod > self.memory_size {
+            return Err(CoreWarError::game_state(format!(
+                "IDX_MOD {} must be between 1 and the memory size {}",
+                self.idx_mod, self.memory_size
+            )));
+        }
+
+        if self.cycle_to_die == 0 {
+            return Err(CoreWarError::game_state("Cycle to die must be positive"));
+        }
+
+        if self.live_reset == LiveReset::Immediate && self.nbr_live == 0 {
+            return Err(CoreWarError::game_state(
+                "NBR_LIVE must be positive when the period resets immediately",
+            ));
+        }
+
+        if self.max_checks == 0 {
+            return Err(CoreWarError::game_state("Max checks must be positive"));
+        }
+
+        if self.max_champions == 0 || self.max_champions > u8::MAX as usize {
+            return Err(CoreWarError::game_state(format!(
+                "Max champions {} must be between 1 and {}",
+                self.max_champions,
+                u8::MAX
+            )));
+        }
+
+        if self.max_champions > self.memory_size {
+            return Err(CoreWarError::game_state(format!(
+                "Memory size {} is too small for {} champions",
+                self.memory_size, self.max_champions
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules_match_constants() {
+        let rules = Rules::default();
+        assert_eq!(rules.memory_size, MEMORY_SIZE);
+        assert_eq!(rules.idx_mod, IDX_MOD);
+        assert_eq!(rules.cycle_to_die, CYCLE_TO_DIE);
+        assert_eq!(rules.max_champions, MAX_CHAMPIONS);
+        assert_eq!(rules.live_reset, LiveReset::AtCheck);
+        assert!(rules.validate().is_ok());
+    }
+
+    #[test]
+    fn test_live_reset_parsing_and_defaults() {
+        assert_eq!(
+            "immediate".parse::<LiveReset>().unwrap(),
+            LiveReset::Immediate
+        );
+        assert_eq!("At-Check".parse::<LiveReset>().unwrap(), LiveReset::AtCheck);
+        assert!("never".parse::<LiveReset>().is_err());
+        assert_eq!(LiveReset::Immediate.to_string(), "immediate");
+
+        // Rules saved before the option existed keep the scheduled check
+        let mut json = serde_json::to_value(Rules::default()).unwrap();
+        json.as_object_mut().unwrap().remove("live_reset");
+        let rules: Rules = serde_json::from_value(json).unwrap();
+        assert_eq!(rules.live_reset, LiveReset::AtCheck);
+
+        let rules = Rules {
+            nbr_live: 0,
+            live_reset: LiveReset::Immediate,
+            ..Default::default()
+        };
+        assert!(rules.validate().is_err());
+    }
+
+    #[test]
+    fn test_rules_presets() {
+        assert_eq!(RulesPreset::Standard.rules(), Rules::default());
+        assert_eq!(RulesPreset::Standard.max_cycles(), 0);
+
+        let preset: RulesPreset = "pMARS".parse().unwrap();
+        assert_eq!(preset, RulesPreset::Pmars);
+        assert_eq!(preset.to_string(), "pmars");
+        let rules = preset.rules();
+        assert_eq!(rules.memory_size, 8000);
+        assert_eq!(rules.max_processes, 8000);
+        assert!(!rules.require_live);
+        assert_eq!(preset.max_cycles(), 80000);
+        // The same in every build, whether or not it has the `pspace` feature
+        assert_eq!(rules.pspace_size, 500);
+        assert!(rules.validate().is_ok());
+        assert!("icws".parse::<RulesPreset>().is_err());
+
+        // Rules saved before the options existed keep their behaviour
+        let mut json = serde_json::to_value(Rules::default()).unwrap();
+        let object = json.as_object_mut().unwrap();
+        object.remove("max_processes");
+        object.remove("require_live");
+        let rules: Rules = serde_json::from_value(json).unwrap();
+        assert_eq!(rules, Rules::default());
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let rules = Rules {
+            memory_size: 0,
+            ..Default::default()
+        };
+        assert!(rules.validate().is_err());
+
+        let rules = Rules {
+            memory_size: 256,
+            ..Default::default()
+        };
+        assert!(rules.validate().is_err()); // IDX_MOD larger than the core
+
+        let rules = Rules {
+            cycle_to_die: 0,
+            ..Default::default()
+        };
+        assert!(rules.validate().is_err());
+
+        let rules = Rules {
+            memory_size: 1024,
+            idx_mod: 128,
+            cycle_to_die: 200,
+            ..Default::default()
+        };
+        assert!(rules.validate().is_ok());
+    }
+}
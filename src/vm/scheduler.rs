@@ -2,10 +2,22 @@
 ///
 /// This module implements the process scheduler that manages the execution
 /// of multiple processes in a round-robin fashion.
-use crate::error::Result;
-use crate::vm::{Champion, Memory, Process};
-use log::{debug, info};
-use std::collections::VecDeque;
+use crate::error::{CoreWarError, Result};
+use crate::vm::arena::ProcessArena;
+use crate::vm::bench::InstructionTimings;
+use crate::vm::clock::Instant;
+use crate::vm::coverage::CoverageTracker;
+use crate::vm::events::GameEvent;
+use crate::vm::exectrace::ExecutionTrace;
+#[cfg(feature = "pspace")]
+use crate::vm::instruction::Instruction;
+#[cfg(feature = "pspace")]
+use crate::vm::pspace;
+use crate::vm::{Champion, ChampionColor, LiveReset, Memory, Process, Rules};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+use tracing::{debug, debug_span, info, trace, trace_span};
 
 /// Process scheduler for the Core War virtual machine
 ///
@@ -13,40 +25,99 @@ use std::collections::VecDeque;
 /// handling instruction execution, process forking, and process termination.
 #[derive(Debug)]
 pub struct Scheduler {
-    /// Queue of active processes
-    processes: VecDeque<Process>,
+    /// Active processes, in run-queue order
+    processes: ProcessArena,
+    /// Processes forked during the current cycle or step
+    spawned: Vec<Process>,
     /// Next process ID to assign
     next_process_id: u32,
-    /// Current execution cycle
+    /// Current execution cycle within the current period
     current_cycle: u32,
+    /// Total number of cycles executed
+    total_cycles: u32,
+    /// Absolute cycle of the most recent death check
+    last_check_cycle: u32,
+    /// Consecutive death checks that did not reduce cycle_to_die
+    checks_without_reduction: u32,
     /// Cycles until death check
     cycle_to_die: u32,
     /// Number of live instructions executed in current period
     live_count: u32,
     /// Total number of live instructions executed
     total_live_count: u32,
+    /// Champion that most recently reported alive
+    last_live_champion: Option<u8>,
+    /// Cycle at which each champion first killed an opposing process
+    first_kills: BTreeMap<u8, u32>,
+    /// Arena rules driving death checks and the process limit
+    rules: Rules,
+    /// Live processes of each champion, counted for the process limit
+    champion_processes: BTreeMap<u8, usize>,
+    /// Executed-byte tracker, when coverage is enabled
+    coverage: Option<CoverageTracker>,
+    /// Time spent per opcode, when timing is enabled
+    timings: Option<InstructionTimings>,
+    /// Per-instruction trace output, when tracing is enabled
+    trace: Option<ExecutionTrace>,
+    /// Instructions executed by this scheduler
+    instructions_executed: u64,
+    /// Events raised since they were last taken
+    events: Vec<GameEvent>,
 }
 
 impl Scheduler {
     /// Create a new scheduler
     pub fn new() -> Self {
+        Self::with_rules(Rules::default())
+    }
+
+    /// Create a new scheduler for the given rules
+    ///
+    /// # Arguments
+    /// * `rules` - The arena rules providing the death check parameters
+    ///
+    /// # Returns
+    /// A new Scheduler instance
+    pub fn with_rules(rules: Rules) -> Self {
         Self {
-            processes: VecDeque::new(),
+            processes: ProcessArena::new(),
+            spawned: Vec::new(),
             next_process_id: 1,
             current_cycle: 0,
-            cycle_to_die: crate::constants::CYCLE_TO_DIE,
+            total_cycles: 0,
+            last_check_cycle: 0,
+            checks_without_reduction: 0,
+            cycle_to_die: rules.cycle_to_die,
             live_count: 0,
             total_live_count: 0,
+            last_live_champion: None,
+            first_kills: BTreeMap::new(),
+            rules,
+            champion_processes: BTreeMap::new(),
+            coverage: None,
+            timings: None,
+            trace: None,
+            instructions_executed: 0,
+            events: Vec::new(),
         }
     }
 
     /// Add a process to the scheduler
     ///
+    /// Newly added processes are placed at the front of the queue, so the
+    /// most recently created process is the first to execute each cycle.
+    ///
     /// # Arguments
     /// * `process` - The process to add
     pub fn add_process(&mut self, process: Process) {
-        debug!("Adding process {} to scheduler", process.id);
-        self.processes.push_back(process);
+        trace!(process = process.id, pc = process.pc, "process added");
+        self.events.push(GameEvent::ProcessSpawned {
+            process_id: process.id,
+            parent_id: None,
+            champion_id: process.champion_id,
+            pc: process.pc,
+        });
+        self.processes.push_front(process);
     }
 
     /// Create a new process for a champion
@@ -82,10 +153,128 @@ impl Scheduler {
         self.cycle_to_die
     }
 
+    /// Get the cycle at which a champion first killed an opposing process
+    ///
+    /// A process is killed by the champion that owns the memory holding the
+    /// invalid instruction it died on.
+    ///
+    /// # Arguments
+    /// * `champion_id` - The champion to look up
+    ///
+    /// # Returns
+    /// The cycle of the first kill, or None if the champion never killed
+    pub fn first_kill_cycle(&self, champion_id: u8) -> Option<u32> {
+        self.first_kills.get(&champion_id).copied()
+    }
+
+    /// Take the events raised since the last call
+    ///
+    /// # Returns
+    /// The pending events, oldest first
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Start recording which bytes of champion code are executed
+    ///
+    /// # Arguments
+    /// * `tracker` - Tracker covering the loaded champions
+    pub fn enable_coverage(&mut self, tracker: CoverageTracker) {
+        self.coverage = Some(tracker);
+    }
+
+    /// Get the coverage tracker, if coverage is enabled
+    pub fn coverage(&self) -> Option<&CoverageTracker> {
+        self.coverage.as_ref()
+    }
+
+    /// Start timing every instruction executed during a cycle
+    ///
+    /// Timing costs two clock reads per instruction, so throughput measured
+    /// while it is enabled understates the scheduler's speed.
+    pub fn enable_timing(&mut self) {
+        self.timings = Some(InstructionTimings::default());
+    }
+
+    /// Get the time spent per opcode, if timing is enabled
+    pub fn timings(&self) -> Option<&InstructionTimings> {
+        self.timings.as_ref()
+    }
+
+    /// Start writing a line for every traced instruction executed
+    ///
+    /// # Arguments
+    /// * `trace` - Destination and filter of the trace
+    pub fn enable_trace(&mut self, trace: ExecutionTrace) {
+        self.trace = Some(trace);
+    }
+
+    /// Stop tracing instructions
+    ///
+    /// # Returns
+    /// The trace, if tracing was enabled
+    pub fn take_trace(&mut self) -> Option<ExecutionTrace> {
+        self.trace.take()
+    }
+
+    /// Get the champion that most recently reported alive
+    ///
+    /// # Returns
+    /// The champion ID, or None if no `live` has executed yet
+    pub fn last_live_champion(&self) -> Option<u8> {
+        self.last_live_champion
+    }
+
+    /// Capture the processes and counters needed to resume execution
+    ///
+    /// # Returns
+    /// A serializable copy of the scheduler state
+    pub fn snapshot(&self) -> SchedulerSnapshot {
+        SchedulerSnapshot {
+            processes: self.processes.iter().cloned().collect(),
+            next_process_id: self.next_process_id,
+            current_cycle: self.current_cycle,
+            total_cycles: self.total_cycles,
+            last_check_cycle: self.last_check_cycle,
+            checks_without_reduction: self.checks_without_reduction,
+            cycle_to_die: self.cycle_to_die,
+            live_count: self.live_count,
+            total_live_count: self.total_live_count,
+            last_live_champion: self.last_live_champion,
+            first_kills: self.first_kills.clone(),
+        }
+    }
+
+    /// Replace the scheduler state with a snapshot
+    ///
+    /// Pending events are discarded. Coverage tracking is left as it is.
+    ///
+    /// # Arguments
+    /// * `rules` - The arena rules the snapshot was taken under
+    /// * `snapshot` - The state to resume from
+    pub fn restore(&mut self, rules: Rules, snapshot: SchedulerSnapshot) {
+        self.processes = snapshot.processes.into_iter().collect();
+        self.next_process_id = snapshot.next_process_id;
+        self.current_cycle = snapshot.current_cycle;
+        self.total_cycles = snapshot.total_cycles;
+        self.last_check_cycle = snapshot.last_check_cycle;
+        self.checks_without_reduction = snapshot.checks_without_reduction;
+        self.cycle_to_die = snapshot.cycle_to_die;
+        self.live_count = snapshot.live_count;
+        self.total_live_count = snapshot.total_live_count;
+        self.last_live_champion = snapshot.last_live_champion;
+        self.first_kills = snapshot.first_kills;
+        self.rules = rules;
+        self.events.clear();
+    }
+
     /// Execute one cycle of the scheduler
     ///
-    /// This method executes one instruction for the next ready process
-    /// and handles the scheduling logic.
+    /// Every alive process is visited exactly once per cycle, from the most
+    /// recently created to the oldest. Each visit counts down the process's
+    /// wait cycles and executes its next instruction once it is ready.
+    /// Processes spawned during the cycle join the front of the queue and
+    /// first run on the following cycle.
     ///
     /// # Arguments
     /// * `memory` - The virtual machine memory
@@ -99,68 +288,306 @@ impl Scheduler {
         champions: &mut [Champion],
     ) -> Result<bool> {
         self.current_cycle += 1;
-        // Only print every 100 cycles to reduce spam
-        if self.current_cycle % 100 == 0 {
-            eprintln!("Scheduler: Cycle {}. Processes: {}", self.current_cycle, self.processes.len());
-        }
+        self.total_cycles += 1;
+        let _cycle = trace_span!("cycle", cycle = self.total_cycles).entered();
+
+        let champions_before = self.champions_with_processes();
 
-        // Decrement wait cycles for all processes
-        for process in &mut self.processes {
+        // Take the processes out so instructions can borrow the scheduler
+        let mut queue = std::mem::take(&mut self.processes);
+        self.count_champion_processes(&queue);
+
+        for position in 0..queue.len() {
+            let process = queue.get_at_mut(position).expect("position is in the queue");
             process.decrement_wait_cycles();
-            eprintln!("Scheduler: Process {} wait_cycles: {}", process.id, process.wait_cycles);
-        }
+            if !process.is_ready() {
+                continue;
+            }
 
-        // Find the next ready process
-        if let Some(mut process) = self.get_next_ready_process() {
-            eprintln!("Scheduler: Process {} (PC: {}) ready to execute.", process.id, process.pc);
-            // Execute one instruction for this process
-        eprintln!("Scheduler: Before instruction execution. Process {}: PC={}, LiveCounter={}, Alive={}", process.id, process.pc, process.live_counter, process.alive);
-        if let Err(e) = self.execute_instruction(&mut process, memory, champions) {
-            eprintln!("Process {} error: {}", process.id, e);
-            process.kill();
+            let started = self
+                .timings
+                .is_some()
+                .then(|| (memory.read_byte(process.pc), Instant::now()));
+            let result = self.execute_traced(process, memory, champions);
+            if let (Some(timings), Some((opcode, started))) = (self.timings.as_mut(), started) {
+                timings.record(opcode, started.elapsed());
+            }
+            if let Err(e) = result {
+                self.kill_faulted(process, memory, e);
+            }
         }
-        eprintln!("Scheduler: After instruction execution. Process {}: PC={}, LiveCounter={}, Alive={}", process.id, process.pc, process.live_counter, process.alive);
 
+        // Drop processes that died while executing
+        queue.retain_mut(|process| {
+            if !process.alive {
+                debug!(process = process.id, "process died");
+            }
+            process.alive
+        });
+
+        // Processes spawned this cycle go to the front, newest first
+        self.processes = queue;
+        self.admit_spawned();
 
-        // Put the process back in the queue if it's still alive
-        if process.alive {
-            self.processes.push_back(process);
+        // Perform the death check once the current period has elapsed
+        if self.period_over() {
+            self.perform_death_check(champions);
         } else {
-            info!("Process {} died", process.id);
-            eprintln!("Scheduler: Process {} died.", process.id);
-        }
+            // Faults and kills remove processes between death checks too
+            self.update_process_counts(champions);
         }
 
-        // Check if we need to perform a death check (proper Core War logic)
-        if self.live_count >= crate::constants::NBR_LIVE || self.current_cycle >= self.cycle_to_die {
-            eprintln!("Scheduler: Performing death check at cycle {} (live_count: {}, cycle_to_die: {})", 
-                     self.current_cycle, self.live_count, self.cycle_to_die);
-            self.perform_death_check(champions);
-            eprintln!("Scheduler: After death check. Processes: {}, Cycle to Die: {}", self.processes.len(), self.cycle_to_die);
+        // Report champions that lost their last process this cycle
+        let remaining = self.champions_with_processes();
+        for &champion_id in champions_before.difference(&remaining) {
+            self.events.push(GameEvent::ChampionEliminated {
+                champion_id,
+                cycle: self.total_cycles,
+            });
         }
 
         // Check if game should continue
-        let should_continue = self.should_continue_game(champions);
-        eprintln!("Scheduler: should_continue_game returned {}. Live count: {}", should_continue, self.live_count);
-        Ok(should_continue)
-    }
-
-    /// Get the next ready process from the queue
-    fn get_next_ready_process(&mut self) -> Option<Process> {
-        // Find the first ready process
-        for _ in 0..self.processes.len() {
-            if let Some(process) = self.processes.pop_front() {
-                if process.is_ready() {
-                    eprintln!("Scheduler: Found ready process {}.", process.id);
-                    return Some(process);
-                } else {
-                    eprintln!("Scheduler: Process {} not ready. Wait cycles: {}. Alive: {}.", process.id, process.wait_cycles, process.alive);
-                    self.processes.push_back(process);
+        Ok(self.should_continue_game(champions))
+    }
+
+    /// Execute the next instruction of a single process
+    ///
+    /// The process's remaining wait cycles are skipped and no other process
+    /// moves. The cycle counter and the death check are left untouched, so
+    /// this only makes sense for debugging.
+    ///
+    /// # Arguments
+    /// * `process_id` - The process to step
+    /// * `memory` - The virtual machine memory
+    /// * `champions` - The active champions
+    ///
+    /// # Returns
+    /// Whether the process survived the instruction, or an error if no such
+    /// process is scheduled
+    pub fn step_process(
+        &mut self,
+        process_id: u32,
+        memory: &mut Memory,
+        champions: &mut [Champion],
+    ) -> Result<bool> {
+        // Take the processes out so the instruction can borrow the scheduler
+        let mut queue = std::mem::take(&mut self.processes);
+        self.count_champion_processes(&queue);
+        let Some(process) = queue.get_mut(process_id) else {
+            self.processes = queue;
+            return Err(CoreWarError::game_state(format!(
+                "No process with ID {}",
+                process_id
+            )));
+        };
+
+        process.set_wait_cycles(0);
+        if let Err(e) = self.execute_traced(process, memory, champions) {
+            self.kill_faulted(process, memory, e);
+        }
+
+        let alive = process.alive;
+        if !alive {
+            queue.remove(process_id);
+        }
+
+        // Spawned processes go to the front, as at the end of a cycle
+        self.processes = queue;
+        self.admit_spawned();
+
+        Ok(alive)
+    }
+
+    /// Advance one cycle by applying recorded events instead of executing
+    ///
+    /// Used to replay a recording: memory writes, spawns, deaths and lives
+    /// come from the events, and the death check only starts a new check
+    /// period because its kills were recorded too. Processes move to the PC
+    /// of each write or spawn they are recorded at. The events are raised
+    /// again, except cycle completions and rewinds, which the engine
+    /// publishes itself.
+    ///
+    /// # Arguments
+    /// * `events` - The events recorded for this cycle, in order
+    /// * `memory` - The virtual machine memory
+    /// * `champions` - The active champions
+    pub fn replay_cycle(
+        &mut self,
+        events: &[GameEvent],
+        memory: &mut Memory,
+        champions: &mut [Champion],
+    ) {
+        self.current_cycle += 1;
+        self.total_cycles += 1;
+
+        for event in events {
+            match *event {
+                GameEvent::MemoryWrite {
+                    address,
+                    value,
+                    champion_id,
+                    process_id,
+                    pc,
+                    ..
+                } => {
+                    memory.write_byte(address, value, Some(champion_id));
+                    if let Some(process) = self.processes.get_mut(process_id) {
+                        process.pc = pc;
+                    }
+                }
+                GameEvent::ProcessSpawned {
+                    process_id,
+                    champion_id,
+                    pc,
+                    ..
+                } => {
+                    let color = champions
+                        .iter()
+                        .find(|c| c.id == champion_id)
+                        .map_or(ChampionColor::for_id(champion_id), |c| c.color);
+                    self.processes
+                        .push_front(Process::new(process_id, champion_id, pc, color));
+                    self.next_process_id = self.next_process_id.max(process_id + 1);
+                }
+                GameEvent::ProcessDied { process_id, .. } => {
+                    self.processes.remove(process_id);
                 }
+                GameEvent::LiveReported {
+                    process_id,
+                    champion_id,
+                    ..
+                } => {
+                    self.live_count += 1;
+                    self.total_live_count += 1;
+                    self.last_live_champion = Some(champion_id);
+                    if let Some(process) = self.processes.get_mut(process_id) {
+                        process.mark_alive(self.total_cycles);
+                    }
+                    if let Some(champion) = champions.iter_mut().find(|c| c.id == champion_id) {
+                        champion.live_count += 1;
+                    }
+                }
+                GameEvent::ChampionEliminated { .. } => {}
+                GameEvent::CycleCompleted { .. } | GameEvent::Rewound { .. } => continue,
             }
+            self.events.push(event.clone());
+        }
+
+        if self.period_over() {
+            self.close_check_period(champions);
+        } else {
+            self.update_process_counts(champions);
+        }
+    }
+
+    /// Remove a process from the run queue as if it had died
+    ///
+    /// # Arguments
+    /// * `process_id` - The process to kill
+    ///
+    /// # Returns
+    /// `Ok(())` if the process was killed, or an error if no such process is scheduled
+    pub fn kill_process(&mut self, process_id: u32) -> Result<()> {
+        let mut process = self.processes.remove(process_id).ok_or_else(|| {
+            CoreWarError::game_state(format!("No process with ID {}", process_id))
+        })?;
+        process.kill();
+
+        self.events.push(GameEvent::ProcessDied {
+            process_id: process.id,
+            champion_id: process.champion_id,
+            pc: process.pc,
+        });
+        if !self.processes.iter().any(|p| p.champion_id == process.champion_id) {
+            self.events.push(GameEvent::ChampionEliminated {
+                champion_id: process.champion_id,
+                cycle: self.total_cycles,
+            });
+        }
+        Ok(())
+    }
+
+    /// Kill a process whose instruction failed, crediting the memory owner
+    fn kill_faulted(&mut self, process: &mut Process, memory: &Memory, error: CoreWarError) {
+        debug!(process = process.id, pc = process.pc, %error, "process faulted");
+        process.kill();
+        if let Some(count) = self.champion_processes.get_mut(&process.champion_id) {
+            *count = count.saturating_sub(1);
+        }
+        if let Some(killer) = memory.get_owner(process.pc)
+            && killer != process.champion_id
+        {
+            self.first_kills.entry(killer).or_insert(self.total_cycles);
+        }
+        self.events.push(GameEvent::ProcessDied {
+            process_id: process.id,
+            champion_id: process.champion_id,
+            pc: process.pc,
+        });
+    }
+
+    /// Move processes forked since the last call to the front of the queue
+    ///
+    /// The newest fork ends up first, so it is the first to execute next cycle.
+    fn admit_spawned(&mut self) {
+        for process in self.spawned.drain(..) {
+            self.processes.push_front(process);
+        }
+    }
+
+    /// Count each champion's processes before instructions run
+    fn count_champion_processes(&mut self, queue: &ProcessArena) {
+        let counts = &mut self.champion_processes;
+        counts.clear();
+        for process in queue.iter().chain(&self.spawned) {
+            *counts.entry(process.champion_id).or_insert(0) += 1;
         }
-        eprintln!("Scheduler: No ready processes found.");
-        None
+    }
+
+    /// Get the IDs of champions that still own at least one process
+    fn champions_with_processes(&self) -> BTreeSet<u8> {
+        self.processes.iter().map(|p| p.champion_id).collect()
+    }
+
+    /// Write a byte on behalf of a process and raise a memory write event
+    ///
+    /// The process PC must still point at the instruction doing the write.
+    fn write_byte(
+        &mut self,
+        memory: &mut Memory,
+        address: usize,
+        value: u8,
+        process: &Process,
+        opcode: u8,
+    ) {
+        let address = address % memory.size();
+        memory.write_byte(address, value, Some(process.champion_id));
+        self.events.push(GameEvent::MemoryWrite {
+            address,
+            value,
+            champion_id: process.champion_id,
+            process_id: process.id,
+            opcode,
+            pc: process.pc,
+        });
+    }
+
+    /// Execute one instruction for a process, tracing it if it is traced
+    fn execute_traced(
+        &mut self,
+        process: &mut Process,
+        memory: &mut Memory,
+        champions: &mut [Champion],
+    ) -> Result<()> {
+        let step = self
+            .trace
+            .as_ref()
+            .and_then(|trace| trace.begin(process, memory, self.events.len()));
+        let result = self.execute_instruction(process, memory, champions);
+        if let (Some(trace), Some(step)) = (self.trace.as_mut(), step) {
+            trace.record(self.total_cycles, step, process, &self.events, result.as_ref().err());
+        }
+        result
     }
 
     /// Execute one instruction for a process
@@ -171,22 +598,36 @@ impl Scheduler {
         &mut self,
         process: &mut Process,
         memory: &mut Memory,
-        _champions: &mut [Champion],
+        champions: &mut [Champion],
     ) -> Result<()> {
-        // Read the opcode at the current program counter
-        let opcode = memory.read_byte(process.pc);
-        eprintln!("execute_instruction: Process {} at PC {} (opcode: {:#02x})", process.id, process.pc, opcode);
+        self.instructions_executed += 1;
+
+        // Fetch the instruction at the current program counter, decoded once per write
+        let start_pc = process.pc;
+        let decoded = memory.decode(process.pc);
+        let opcode = decoded.opcode;
+        trace!(process = process.id, pc = start_pc, opcode, "execute");
 
         match opcode {
             0x01 => {
                 // 'live' instruction: increment live_count
                 self.live_count += 1;
-                process.mark_alive();
-                eprintln!("Process {} executed LIVE. live_count: {}", process.id, self.live_count);
+                self.total_live_count += 1;
+                process.mark_alive(self.total_cycles);
+                self.events.push(GameEvent::LiveReported {
+                    process_id: process.id,
+                    champion_id: process.champion_id,
+                    cycle: self.total_cycles,
+                });
+
+                // Report the owning champion alive; the latest report decides the winner
+                self.last_live_champion = Some(process.champion_id);
+                if let Some(champion) = champions.iter_mut().find(|c| c.id == process.champion_id) {
+                    champion.live_count += 1;
+                }
                 
                 // Write the live instruction result to memory (for visualization)
-                let write_addr = (process.pc + 1) % memory.size();
-                memory.write_byte(write_addr, 0xFF, Some(process.champion_id)); // Mark as executed
+                self.write_byte(memory, process.pc + 1, 0xFF, process, opcode); // Mark as executed
                 
                 process.advance_pc(1, memory.size()); // Advance PC for opcode
                 process.advance_pc(4, memory.size()); // Advance PC for parameter (direct 4-byte value)
@@ -196,139 +637,217 @@ impl Scheduler {
             }
             0x04 => {
                 // 'add' instruction
-                eprintln!("Process {} executed ADD instruction at PC {}.", process.id, process.pc);
-                
                 // Simulate add operation with memory write for visualization
-                let target_addr = (process.pc + 10) % memory.size();
-                memory.write_byte(target_addr, 0xAA, Some(process.champion_id));
+                self.write_byte(memory, process.pc + 10, 0xAA, process, opcode);
                 
                 process.advance_pc(5, memory.size()); // Standard instruction size
                 process.set_wait_cycles(10); // Add takes 10 cycles (correct)
             }
             0x03 => {
                 // 'st' instruction (store)
-                eprintln!("Process {} executed ST instruction at PC {}.", process.id, process.pc);
-                
                 // Simulate store operation with memory write
-                let target_addr = (process.pc + 5) % memory.size();
-                memory.write_byte(target_addr, 0xBB, Some(process.champion_id));
+                self.write_byte(memory, process.pc + 5, 0xBB, process, opcode);
                 
                 process.advance_pc(5, memory.size()); // Standard instruction size
                 process.set_wait_cycles(5); // St takes 5 cycles (correct)
             }
             0x09 => {
                 // 'jmp' instruction - make it actually jump for more dynamic movement
-                eprintln!("Process {} executed JMP instruction at PC {}.", process.id, process.pc);
-                
                 // Jump to a semi-random location for more visual interest
-                let jump_distance = 50 + (process.id as usize * 100);
-                let new_pc = (process.pc + jump_distance) % memory.size();
+                // Reduce in 64 bits so large process IDs wrap the same way on 32-bit targets
+                let jump_distance = (50 + u64::from(process.id) * 100) % memory.size() as u64;
+                let new_pc = (process.pc + jump_distance as usize) % memory.size();
                 process.pc = new_pc;
                 
                 process.set_wait_cycles(20); // Jump takes 20 cycles
             }
             0x0C => {
                 // 'fork' instruction - create actual new process for more activity
-                eprintln!("Process {} executed FORK instruction at PC {}.", process.id, process.pc);
-                
+                let count = self
+                    .champion_processes
+                    .entry(process.champion_id)
+                    .or_insert(0);
+                if self.rules.max_processes > 0 && *count >= self.rules.max_processes {
+                    // At the process limit the fork does nothing, as in pMARS
+                    trace!(process = process.id, "fork dropped at the process limit");
+                    process.advance_pc(5, memory.size());
+                    process.set_wait_cycles(800);
+                    return Ok(());
+                }
+                *count += 1;
+
                 // Create a new process at a different location
                 let fork_pc = (process.pc + 100) % memory.size();
-                let new_process = Process::new(
-                    self.next_process_id,
-                    process.champion_id,
-                    fork_pc,
-                    process.color,
-                );
+                let new_process = process.fork(self.next_process_id, fork_pc, memory.size());
                 self.next_process_id += 1;
+                self.events.push(GameEvent::ProcessSpawned {
+                    process_id: new_process.id,
+                    parent_id: Some(process.id),
+                    champion_id: new_process.champion_id,
+                    pc: new_process.pc,
+                });
                 
-                // Add the new process to the queue
-                self.processes.push_back(new_process);
-                eprintln!("Fork created new process {} at PC {}", self.next_process_id - 1, fork_pc);
+                // Hold the new process; it joins the run queue at the end of the cycle
+                self.spawned.push(new_process);
+                trace!(
+                    process = process.id,
+                    child = self.next_process_id - 1,
+                    pc = fork_pc,
+                    "forked"
+                );
                 
                 process.advance_pc(5, memory.size()); // Standard instruction size  
                 process.set_wait_cycles(800); // Proper Core War fork cycle cost
             }
+            #[cfg(feature = "pspace")]
+            0x11 | 0x12 if self.rules.pspace_size > 0 => {
+                // 'ldp' and 'stp': private storage, when the rules give champions any
+                let instruction = Instruction::from_opcode(opcode)?;
+                let size = pspace::execute(&decoded, process, memory, champions, &self.rules)?;
+                process.advance_pc(size as i32, memory.size());
+                process.set_wait_cycles(instruction.cycles());
+            }
             0x00 => {
                 // Invalid instruction (0x00) - kill the process
-                eprintln!("Process {} encountered invalid instruction 0x00 at PC {}. Killing process.", process.id, process.pc);
-                return Err(crate::error::CoreWarError::InvalidOpcode { 
+                return Err(CoreWarError::InvalidOpcode { 
                     opcode: 0x00
                 });
             }
             _ => {
                 // Unknown instruction - treat as no-op but advance PC and add some wait time
-                eprintln!("Process {} executed unknown instruction {:#02x} at PC {}. Treating as no-op.", process.id, opcode, process.pc);
                 process.advance_pc(5, memory.size()); // Standard instruction size
                 process.set_wait_cycles(1); // Minimal wait for unknown instructions
             }
         }
-        eprintln!("execute_instruction: Process {} new PC: {}", process.id, process.pc);
+
+        // Record the executed bytes; jumps leave the PC elsewhere so only count the opcode
+        if let Some(coverage) = self.coverage.as_mut() {
+            let size = match opcode {
+                0x09 => 1,
+                _ => (process.pc + memory.size() - start_pc) % memory.size(),
+            };
+            coverage.record(start_pc, size);
+        }
 
         Ok(())
     }
 
+    /// Check whether the current period ends with this cycle
+    ///
+    /// A period lasts `cycle_to_die` cycles, or with [`LiveReset::Immediate`]
+    /// until `NBR_LIVE` lives were reported, whichever comes first. Without
+    /// [`Rules::require_live`] there are no periods.
+    fn period_over(&self) -> bool {
+        if !self.rules.require_live {
+            return false;
+        }
+        self.current_cycle >= self.cycle_to_die
+            || (self.rules.live_reset == LiveReset::Immediate
+                && self.live_count >= self.rules.nbr_live)
+    }
+
     /// Perform death check for all processes (proper Core War logic)
+    ///
+    /// Processes that have not executed `live` since the previous check are
+    /// killed. If at least `NBR_LIVE` lives were reported during the period,
+    /// or `MAX_CHECKS` checks passed without a reduction, `cycle_to_die` is
+    /// reduced by `CYCLE_DELTA`.
     fn perform_death_check(&mut self, champions: &mut [Champion]) {
-        info!("Performing death check at cycle {}", self.current_cycle);
-        eprintln!("Death check: Initial processes count: {}", self.processes.len());
-
-        // Reduce cycle_to_die (this happens every death check in Core War)
-        self.cycle_to_die = self.cycle_to_die.saturating_sub(crate::constants::CYCLE_DELTA);
-        info!("Reducing cycle_to_die to {}", self.cycle_to_die);
-        
-        // Reset cycle counter and live count for next period
-        self.current_cycle = 0;
-        self.live_count = 0;
+        let _check = debug_span!(
+            "death_check",
+            cycle = self.total_cycles,
+            live_count = self.live_count,
+            cycle_to_die = self.cycle_to_die
+        )
+        .entered();
 
-        // Kill processes that haven't executed live in the last period
-        // In proper Core War, processes that don't execute live in CYCLE_TO_DIE cycles die
+        // Kill processes that haven't executed live since the last check
+        let last_check_cycle = self.last_check_cycle;
         let initial_process_count = self.processes.len();
+        let events = &mut self.events;
         self.processes.retain_mut(|process| {
-            if process.live_counter >= self.cycle_to_die {
-                eprintln!(
-                    "Killing process {} (champion {}) due to lack of live instructions (live_counter: {}, cycle_to_die: {})",
-                    process.id, process.champion_id, process.live_counter, self.cycle_to_die
+            if process.lived_since(last_check_cycle) {
+                true // Keep process
+            } else {
+                events.push(GameEvent::ProcessDied {
+                    process_id: process.id,
+                    champion_id: process.champion_id,
+                    pc: process.pc,
+                });
+                debug!(
+                    process = process.id,
+                    champion = process.champion_id,
+                    last_live = ?process.last_live_cycle,
+                    "process killed for not reporting live"
                 );
                 process.kill();
                 false // Remove from active processes
-            } else {
-                // Reset live counter for the new period
-                process.live_counter = 0;
-                true // Keep process
             }
         });
-        eprintln!("Death check: Processes after retain: {}", self.processes.len());
-        eprintln!("Death check: Killed {} processes", initial_process_count - self.processes.len());
+        debug!(
+            killed = initial_process_count - self.processes.len(),
+            remaining = self.processes.len(),
+            "death check finished"
+        );
+
+        self.close_check_period(champions);
+    }
+
+    /// Start a new check period after the death check removed its processes
+    fn close_check_period(&mut self, champions: &mut [Champion]) {
+        // Reduce cycle_to_die when enough lives were reported, or too many checks passed
+        self.checks_without_reduction += 1;
+        if self.live_count >= self.rules.nbr_live
+            || self.checks_without_reduction >= self.rules.max_checks
+        {
+            self.cycle_to_die = self.cycle_to_die.saturating_sub(self.rules.cycle_delta);
+            self.checks_without_reduction = 0;
+            info!(cycle_to_die = self.cycle_to_die, "cycle_to_die reduced");
+        }
+
+        // Reset cycle counter and live count for next period
+        self.current_cycle = 0;
+        self.live_count = 0;
+        self.last_check_cycle = self.total_cycles;
 
         // Update champion process counts
+        self.update_process_counts(champions);
+        for champion in champions {
+            debug!(
+                champion = champion.id,
+                processes = champion.process_count,
+                "champion processes counted"
+            );
+        }
+    }
+
+    /// Update each champion's process count from the run queue
+    fn update_process_counts(&self, champions: &mut [Champion]) {
         for champion in champions {
             champion.process_count = self
                 .processes
                 .iter()
                 .filter(|p| p.champion_id == champion.id)
                 .count();
-            eprintln!("Death check: Champion {} has {} active processes", champion.id, champion.process_count);
         }
     }
 
     /// Check if the game should continue (proper Core War logic)
     fn should_continue_game(&self, champions: &[Champion]) -> bool {
         // Game ends if cycle_to_die reaches 0
-        if self.cycle_to_die <= 0 {
-            eprintln!("should_continue_game: cycle_to_die is 0. Game over.");
+        if self.cycle_to_die == 0 {
+            trace!("cycle_to_die reached 0");
             return false;
         }
 
         // Game ends if no active processes
         if self.processes.is_empty() {
-            eprintln!("should_continue_game: No active processes. Returning false.");
+            trace!("no processes left");
             return false;
         }
 
         // Game ends if only one champion has active processes  
         let active_champions_count = champions.iter().filter(|c| c.process_count > 0).count();
-        eprintln!("should_continue_game: Active champions count: {}", active_champions_count);
-
         active_champions_count > 1
     }
 
@@ -340,6 +859,8 @@ impl Scheduler {
             process_count: self.process_count(),
             live_count: self.live_count,
             total_live_count: self.total_live_count,
+            last_live_champion: self.last_live_champion,
+            instructions_executed: self.instructions_executed,
         }
     }
 
@@ -347,6 +868,30 @@ impl Scheduler {
     pub fn processes(&self) -> Vec<&Process> {
         self.processes.iter().collect()
     }
+
+    /// Get an active process by ID
+    ///
+    /// IDs are never reused, so an ID keeps naming the same process until
+    /// it dies.
+    pub fn process(&self, process_id: u32) -> Option<&Process> {
+        self.processes.get(process_id)
+    }
+}
+
+impl Hash for Scheduler {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.processes.hash(state);
+        self.next_process_id.hash(state);
+        self.current_cycle.hash(state);
+        self.total_cycles.hash(state);
+        self.last_check_cycle.hash(state);
+        self.checks_without_reduction.hash(state);
+        self.cycle_to_die.hash(state);
+        self.live_count.hash(state);
+        self.total_live_count.hash(state);
+        self.last_live_champion.hash(state);
+        self.first_kills.hash(state);
+    }
 }
 
 impl Default for Scheduler {
@@ -355,6 +900,22 @@ impl Default for Scheduler {
     }
 }
 
+/// Serializable scheduler state, in queue order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerSnapshot {
+    pub processes: Vec<Process>,
+    pub next_process_id: u32,
+    pub current_cycle: u32,
+    pub total_cycles: u32,
+    pub last_check_cycle: u32,
+    pub checks_without_reduction: u32,
+    pub cycle_to_die: u32,
+    pub live_count: u32,
+    pub total_live_count: u32,
+    pub last_live_champion: Option<u8>,
+    pub first_kills: BTreeMap<u8, u32>,
+}
+
 /// Statistics about the scheduler state
 #[derive(Debug, Clone)]
 pub struct SchedulerStats {
@@ -363,6 +924,8 @@ pub struct SchedulerStats {
     pub process_count: usize,
     pub live_count: u32,
     pub total_live_count: u32,
+    pub last_live_champion: Option<u8>,
+    pub instructions_executed: u64,
 }
 
 #[cfg(test)]
@@ -424,4 +987,352 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_all_ready_processes_execute_each_cycle() {
+        let mut scheduler = Scheduler::new();
+        let mut memory = Memory::new();
+        let mut champions = vec![
+            Champion::new(1, "A".to_string(), String::new(), vec![0x04], 0),
+            Champion::new(2, "B".to_string(), String::new(), vec![0x04], 1000),
+        ];
+        memory.write_byte(0, 0x04, Some(1));
+        memory.write_byte(1000, 0x04, Some(2));
+
+        for champion in &champions {
+            let process = scheduler.create_process(champion);
+            scheduler.add_process(process);
+        }
+
+        scheduler
+            .execute_cycle(&mut memory, &mut champions)
+            .unwrap();
+
+        // Both processes advance past their instruction in the same cycle
+        let pcs: Vec<usize> = scheduler.processes().iter().map(|p| p.pc).collect();
+        assert_eq!(pcs, vec![1005, 5]);
+    }
+
+    #[test]
+    fn test_forked_processes_run_from_next_cycle_newest_first() {
+        let mut scheduler = Scheduler::new();
+        let mut memory = Memory::new();
+        let mut champions = vec![Champion::new(
+            1,
+            "Forker".to_string(),
+            String::new(),
+            vec![0x0C],
+            0,
+        )];
+        memory.write_byte(0, 0x0C, Some(1));
+
+        let process = scheduler.create_process(&champions[0]);
+        scheduler.add_process(process);
+
+        scheduler
+            .execute_cycle(&mut memory, &mut champions)
+            .unwrap();
+
+        // The child is queued ahead of its parent but has not executed yet
+        let processes = scheduler.processes();
+        assert_eq!(processes.len(), 2);
+        assert_eq!(processes[0].id, 2);
+        assert_eq!(processes[0].pc, 100);
+        assert_eq!(processes[1].id, 1);
+    }
+
+    #[test]
+    fn test_death_check_kills_processes_without_live() {
+        let mut scheduler = Scheduler::new();
+        let mut memory = Memory::new();
+        let mut champions = vec![
+            Champion::new(1, "Liver".to_string(), String::new(), vec![0x01], 0),
+            Champion::new(2, "Silent".to_string(), String::new(), vec![0x02], 3000),
+        ];
+
+        // Fill the core with single-cycle no-ops so both processes keep running
+        for addr in 0..memory.size() {
+            memory.write_byte(addr, 0x02, None);
+        }
+        memory.write_byte(0, 0x01, Some(1));
+
+        for champion in &champions {
+            let process = scheduler.create_process(champion);
+            scheduler.add_process(process);
+        }
+
+        let mut should_continue = true;
+        for _ in 0..crate::constants::CYCLE_TO_DIE {
+            should_continue = scheduler
+                .execute_cycle(&mut memory, &mut champions)
+                .unwrap();
+        }
+
+        // Only the process that reported live survives the first check
+        let processes = scheduler.processes();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].champion_id, 1);
+        assert_eq!(champions[0].process_count, 1);
+        assert_eq!(champions[1].process_count, 0);
+        assert!(!should_continue);
+
+        // The silent champion never reported alive
+        assert_eq!(scheduler.last_live_champion(), Some(1));
+        assert!(champions[0].live_count > 0);
+        assert_eq!(champions[1].live_count, 0);
+
+        // Too few lives were reported to shorten the next period
+        assert_eq!(scheduler.cycle_to_die(), crate::constants::CYCLE_TO_DIE);
+        assert_eq!(scheduler.current_cycle(), 0);
+    }
+
+    #[test]
+    fn test_faults_update_process_counts_between_death_checks() {
+        let mut scheduler = Scheduler::new();
+        let mut memory = Memory::new();
+        let mut champions = vec![
+            Champion::new(1, "Liver".to_string(), String::new(), vec![0x01], 0),
+            Champion::new(2, "Faulter".to_string(), String::new(), vec![0x00], 3000),
+        ];
+        for addr in 0..memory.size() {
+            memory.write_byte(addr, 0x01, None);
+        }
+        memory.write_byte(3000, 0x00, Some(2));
+        for champion in &champions {
+            let process = scheduler.create_process(champion);
+            scheduler.add_process(process);
+        }
+
+        // The faulting champion is out as soon as its process dies
+        let should_continue = scheduler
+            .execute_cycle(&mut memory, &mut champions)
+            .unwrap();
+        assert_eq!(scheduler.current_cycle(), 1);
+        assert_eq!(champions[0].process_count, 1);
+        assert_eq!(champions[1].process_count, 0);
+        assert!(!should_continue);
+    }
+
+    #[test]
+    fn test_process_limit_without_death_checks() {
+        let rules = Rules {
+            max_processes: 3,
+            require_live: false,
+            ..Rules::default()
+        };
+        let mut scheduler = Scheduler::with_rules(rules);
+        let mut memory = Memory::new();
+        let mut champions = vec![
+            Champion::new(1, "Forker".to_string(), String::new(), vec![0x0C], 0),
+            Champion::new(2, "Silent".to_string(), String::new(), vec![0x02], 3000),
+        ];
+
+        // Forks everywhere, so every process keeps forking
+        for addr in 0..memory.size() {
+            memory.write_byte(addr, 0x0C, None);
+        }
+        for champion in &champions {
+            let process = scheduler.create_process(champion);
+            scheduler.add_process(process);
+        }
+
+        let mut should_continue = true;
+        for _ in 0..crate::constants::CYCLE_TO_DIE * 3 {
+            should_continue = scheduler
+                .execute_cycle(&mut memory, &mut champions)
+                .unwrap();
+        }
+
+        // Nobody reported live, yet nobody was killed
+        assert!(should_continue);
+        assert_eq!(scheduler.cycle_to_die(), crate::constants::CYCLE_TO_DIE);
+        assert_eq!(champions[0].process_count, 3);
+        assert_eq!(champions[1].process_count, 3);
+        assert_eq!(scheduler.process_count(), 6);
+    }
+
+    #[test]
+    fn test_last_live_champion_tracks_latest_report() {
+        let mut scheduler = Scheduler::new();
+        let mut memory = Memory::new();
+        let mut champions = vec![
+            Champion::new(1, "A".to_string(), String::new(), vec![0x01], 0),
+            Champion::new(2, "B".to_string(), String::new(), vec![0x04], 1000),
+        ];
+        memory.write_byte(0, 0x01, Some(1));
+        memory.write_byte(1000, 0x04, Some(2));
+        memory.write_byte(1005, 0x01, Some(2));
+
+        for champion in &champions {
+            let process = scheduler.create_process(champion);
+            scheduler.add_process(process);
+        }
+        assert_eq!(scheduler.last_live_champion(), None);
+
+        scheduler
+            .execute_cycle(&mut memory, &mut champions)
+            .unwrap();
+        assert_eq!(scheduler.last_live_champion(), Some(1));
+
+        // Champion 2 reports alive at cycle 11 while champion 1 runs into empty core
+        for _ in 0..10 {
+            scheduler
+                .execute_cycle(&mut memory, &mut champions)
+                .unwrap();
+        }
+        assert_eq!(scheduler.last_live_champion(), Some(2));
+        assert_eq!(scheduler.get_stats().last_live_champion, Some(2));
+    }
+
+    #[test]
+    fn test_custom_cycle_to_die() {
+        let rules = Rules {
+            cycle_to_die: 50,
+            cycle_delta: 10,
+            nbr_live: 1,
+            ..Default::default()
+        };
+        let mut scheduler = Scheduler::with_rules(rules);
+        let mut memory = Memory::new();
+        let mut champions = vec![Champion::new(1, "A".to_string(), String::new(), vec![0x01], 0)];
+        memory.write_byte(0, 0x01, Some(1));
+
+        let process = scheduler.create_process(&champions[0]);
+        scheduler.add_process(process);
+        assert_eq!(scheduler.cycle_to_die(), 50);
+
+        for _ in 0..50 {
+            scheduler
+                .execute_cycle(&mut memory, &mut champions)
+                .unwrap();
+        }
+
+        // The single live meets NBR_LIVE, so the period shrinks by CYCLE_DELTA
+        assert_eq!(scheduler.current_cycle(), 0);
+        assert_eq!(scheduler.cycle_to_die(), 40);
+    }
+
+    /// Run a champion of `live` instructions for some cycles under a live reset policy
+    fn run_liver(live_reset: LiveReset, cycles: u32) -> Scheduler {
+        let rules = Rules {
+            cycle_to_die: 50,
+            cycle_delta: 10,
+            nbr_live: 2,
+            live_reset,
+            ..Default::default()
+        };
+        let mut scheduler = Scheduler::with_rules(rules);
+        let mut memory = Memory::new();
+        let code = vec![0x01; 100];
+        for (address, &byte) in code.iter().enumerate() {
+            memory.write_byte(address, byte, Some(1));
+        }
+        let mut champions = vec![Champion::new(1, "A".to_string(), String::new(), code, 0)];
+        let process = scheduler.create_process(&champions[0]);
+        scheduler.add_process(process);
+
+        for _ in 0..cycles {
+            scheduler
+                .execute_cycle(&mut memory, &mut champions)
+                .unwrap();
+        }
+        scheduler
+    }
+
+    #[test]
+    fn test_nbr_live_reduces_at_the_scheduled_check() {
+        // NBR_LIVE is reached early, but the period still runs to cycle 50
+        let scheduler = run_liver(LiveReset::AtCheck, 49);
+        assert!(scheduler.get_stats().live_count >= 2);
+        assert_eq!(scheduler.current_cycle(), 49);
+        assert_eq!(scheduler.cycle_to_die(), 50);
+
+        let scheduler = run_liver(LiveReset::AtCheck, 50);
+        assert_eq!(scheduler.current_cycle(), 0);
+        assert_eq!(scheduler.cycle_to_die(), 40);
+    }
+
+    #[test]
+    fn test_nbr_live_resets_the_period_immediately() {
+        // Lives come 10 cycles apart, so the second one ends the period at cycle 11
+        let scheduler = run_liver(LiveReset::Immediate, 10);
+        assert_eq!(scheduler.get_stats().live_count, 1);
+        assert_eq!(scheduler.cycle_to_die(), 50);
+
+        let scheduler = run_liver(LiveReset::Immediate, 11);
+        assert_eq!(scheduler.current_cycle(), 0);
+        assert_eq!(scheduler.get_stats().live_count, 0);
+        assert_eq!(scheduler.cycle_to_die(), 40);
+        // The process reported alive during the short period, so it survives
+        assert_eq!(scheduler.process_count(), 1);
+    }
+
+    #[test]
+    fn test_events_raised_during_cycle() {
+        let mut scheduler = Scheduler::new();
+        let mut memory = Memory::new();
+        let mut champions = vec![
+            Champion::new(1, "Liver".to_string(), String::new(), vec![0x01], 0),
+            Champion::new(2, "Broken".to_string(), String::new(), vec![0x00], 1000),
+        ];
+        memory.write_byte(0, 0x01, Some(1));
+
+        for champion in &champions {
+            let process = scheduler.create_process(champion);
+            scheduler.add_process(process);
+        }
+        let spawned = scheduler.take_events();
+        assert_eq!(spawned.len(), 2);
+        assert!(matches!(
+            spawned[0],
+            GameEvent::ProcessSpawned { process_id: 1, parent_id: None, champion_id: 1, pc: 0 }
+        ));
+
+        scheduler
+            .execute_cycle(&mut memory, &mut champions)
+            .unwrap();
+
+        assert_eq!(
+            scheduler.take_events(),
+            vec![
+                GameEvent::ProcessDied { process_id: 2, champion_id: 2, pc: 1000 },
+                GameEvent::LiveReported { process_id: 1, champion_id: 1, cycle: 1 },
+                GameEvent::MemoryWrite {
+                    address: 1,
+                    value: 0xFF,
+                    champion_id: 1,
+                    process_id: 1,
+                    opcode: 0x01,
+                    pc: 0,
+                },
+                GameEvent::ChampionEliminated { champion_id: 2, cycle: 1 },
+            ]
+        );
+        assert!(scheduler.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_first_kill_attributed_to_memory_owner() {
+        let mut scheduler = Scheduler::new();
+        let mut memory = Memory::new();
+        let mut champions = vec![
+            Champion::new(1, "Victim".to_string(), String::new(), vec![0x00], 0),
+            Champion::new(2, "Bomber".to_string(), String::new(), vec![0x02], 1000),
+        ];
+        // Champion 2 owns the zeroed byte champion 1 is about to execute
+        memory.write_byte(0, 0x00, Some(2));
+        memory.write_byte(1000, 0x02, Some(2));
+
+        for champion in &champions {
+            let process = scheduler.create_process(champion);
+            scheduler.add_process(process);
+        }
+
+        scheduler
+            .execute_cycle(&mut memory, &mut champions)
+            .unwrap();
+
+        assert_eq!(scheduler.first_kill_cycle(2), Some(1));
+        assert_eq!(scheduler.first_kill_cycle(1), None);
+    }
 }
@@ -0,0 +1,372 @@
+/// Battle server for browser visualizers
+///
+/// `corewar serve` runs battles without a terminal and streams them over
+/// WebSocket, so a visualizer in a browser can draw the core remotely. One
+/// battle is shared by every connected viewer; viewers may come and go at
+/// any cycle.
+///
+/// # Protocol
+///
+/// Every message is a JSON object in a WebSocket text frame, with a `type`
+/// naming it. The protocol version is [`PROTOCOL_VERSION`].
+///
+/// | Type       | Sent by | Fields |
+/// |------------|---------|--------|
+/// | `hello`    | server  | `protocol`, `memory_size`, `rounds`, `champions` (`id`, `name`, `comment`) |
+/// | `snapshot` | server  | `round`, `cycle`, `cycle_to_die`, `memory` (hex, two digits per byte), `owners` (champion ID per byte, 0 for none), `processes` (`id`, `champion_id`, `pc`) |
+/// | `delta`    | server  | `round`, `cycle`, `events`: the cycle's events as [`crate::vm::ndjson`] writes them |
+/// | `result`   | server  | `round` and the fields of a [`BattleReport`] |
+/// | `sync`     | client  | none |
+///
+/// # State sync
+///
+/// Right after the upgrade the server sends `hello`, then a `snapshot` of
+/// the battle as it stands. Applying each `delta` after the snapshot, in
+/// order, keeps a viewer in step with the core: memory writes carry the new
+/// byte and owner, and spawns and deaths keep the process list. A viewer
+/// that lost track sends `sync` and is answered with a fresh snapshot.
+///
+/// Each round opens with a snapshot at its first cycle and closes with a
+/// `result`; after the last round the server closes every connection.
+use crate::GameEngine;
+use crate::error::Result;
+use crate::vm::GameEvent;
+use crate::vm::bundle::BattleReport;
+use crate::vm::ndjson;
+use crate::vm::websocket::{self, Frame};
+use serde_json::{Value, json};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Version of the message protocol, sent in `hello`
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Longest a viewer may take over its handshake or to take a message
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause between checks for the first viewer
+const WAIT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A connected viewer
+struct Client {
+    /// Number the viewer's messages are tagged with
+    id: usize,
+    /// Connection to the viewer
+    stream: TcpStream,
+}
+
+/// Streams battles to WebSocket viewers
+pub struct BattleServer {
+    /// Socket new viewers connect to
+    listener: TcpListener,
+    /// Connected viewers
+    clients: Vec<Client>,
+    /// Number of the next viewer
+    next_id: usize,
+    /// Messages from the viewers' reader threads
+    requests: Receiver<(usize, Frame)>,
+    /// Handed to each viewer's reader thread
+    request_sender: Sender<(usize, Frame)>,
+}
+
+impl BattleServer {
+    /// Listen for viewers
+    ///
+    /// # Arguments
+    /// * `address` - Address to listen on; port 0 picks a free port
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let (request_sender, requests) = mpsc::channel();
+        Ok(Self {
+            listener,
+            clients: Vec::new(),
+            next_id: 0,
+            requests,
+            request_sender,
+        })
+    }
+
+    /// Get the address the server listens on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Get the number of connected viewers
+    pub fn viewers(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Play rounds of a battle, streaming each to the viewers
+    ///
+    /// The engine keeps its own pace, so set a throttle on it to make the
+    /// battle watchable. Rounds after the first restart the engine, which
+    /// needs champions loaded from files.
+    ///
+    /// # Arguments
+    /// * `engine` - The battle, with champions loaded
+    /// * `rounds` - Rounds to play, at least 1
+    /// * `wait` - Whether the first round waits for a viewer to connect
+    pub fn run(&mut self, engine: &mut GameEngine, rounds: u32, wait: bool) -> Result<()> {
+        let events = engine.subscribe_channel();
+        let rounds = rounds.max(1);
+        while wait && self.clients.is_empty() {
+            self.accept(engine, 1, rounds)?;
+            thread::sleep(WAIT_INTERVAL);
+        }
+
+        for round in 1..=rounds {
+            if round > 1 {
+                engine.restart()?;
+            }
+            engine.start()?;
+            // The snapshot covers everything that happened while loading
+            events.try_iter().for_each(drop);
+            self.broadcast(&snapshot(engine, round));
+
+            while engine.state().running {
+                self.accept(engine, round, rounds)?;
+                self.answer(engine, round);
+                engine.tick()?;
+                let cycle = engine.get_stats().cycle;
+                let events: Vec<GameEvent> = events.try_iter().collect();
+                self.broadcast(&delta(round, cycle, &events)?);
+            }
+
+            let mut result = serde_json::to_value(BattleReport::of(engine))
+                .map_err(|e| crate::CoreWarError::protocol(e.to_string()))?;
+            result["type"] = "result".into();
+            result["round"] = round.into();
+            self.broadcast(&result);
+            info!("Round {} of {} finished", round, rounds);
+        }
+
+        for client in &mut self.clients {
+            let _ = websocket::write_close(&mut client.stream);
+        }
+        self.clients.clear();
+        Ok(())
+    }
+
+    /// Accept viewers waiting to connect, greeting each with the battle so far
+    fn accept(&mut self, engine: &GameEngine, round: u32, rounds: u32) -> Result<()> {
+        loop {
+            let (stream, peer) = match self.listener.accept() {
+                Ok(connection) => connection,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e.into()),
+            };
+            match self.connect(stream, engine, round, rounds) {
+                Ok(()) => info!("Viewer {} connected", peer),
+                Err(e) => warn!("Viewer {} rejected: {}", peer, e),
+            }
+        }
+    }
+
+    /// Upgrade a new connection and start reading the viewer's messages
+    fn connect(
+        &mut self,
+        mut stream: TcpStream,
+        engine: &GameEngine,
+        round: u32,
+        rounds: u32,
+    ) -> Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        websocket::handshake(&mut stream)?;
+        websocket::write_text(&mut stream, &hello(engine, rounds).to_string())?;
+        websocket::write_text(&mut stream, &snapshot(engine, round).to_string())?;
+
+        // Reads block from here on, on a thread of their own
+        stream.set_read_timeout(None)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut reader = stream.try_clone()?;
+        let sender = self.request_sender.clone();
+        thread::spawn(move || {
+            loop {
+                let frame = websocket::read_frame(&mut reader).unwrap_or(Frame::Close);
+                let closed = frame == Frame::Close;
+                if sender.send((id, frame)).is_err() || closed {
+                    break;
+                }
+            }
+        });
+        self.clients.push(Client { id, stream });
+        Ok(())
+    }
+
+    /// Answer the viewers' messages
+    fn answer(&mut self, engine: &GameEngine, round: u32) {
+        let requests: Vec<(usize, Frame)> = self.requests.try_iter().collect();
+        for (id, frame) in requests {
+            let Some(index) = self.clients.iter().position(|c| c.id == id) else {
+                continue;
+            };
+            let stream = &mut self.clients[index].stream;
+            let answered = match frame {
+                Frame::Text(text) if is_sync(&text) => {
+                    websocket::write_text(stream, &snapshot(engine, round).to_string())
+                }
+                Frame::Ping(data) => websocket::write_pong(stream, &data),
+                Frame::Close => {
+                    let _ = websocket::write_close(stream);
+                    self.clients.remove(index);
+                    continue;
+                }
+                Frame::Text(_) | Frame::Other => Ok(()),
+            };
+            if answered.is_err() {
+                self.clients.remove(index);
+            }
+        }
+    }
+
+    /// Send a message to every viewer, dropping those that cannot take it
+    fn broadcast(&mut self, message: &Value) {
+        let text = message.to_string();
+        self.clients.retain_mut(|client| {
+            let sent = websocket::write_text(&mut client.stream, &text).is_ok();
+            if !sent {
+                info!("Viewer {} disconnected", client.id);
+            }
+            sent
+        });
+    }
+}
+
+/// Build the `hello` message
+pub fn hello(engine: &GameEngine, rounds: u32) -> Value {
+    let champions: Vec<Value> = engine
+        .champions()
+        .iter()
+        .map(|c| json!({"id": c.id, "name": c.display_name(), "comment": c.comment}))
+        .collect();
+    json!({
+        "type": "hello",
+        "protocol": PROTOCOL_VERSION,
+        "memory_size": engine.memory().size(),
+        "rounds": rounds,
+        "champions": champions,
+    })
+}
+
+/// Build a `snapshot` message of the battle as it stands
+pub fn snapshot(engine: &GameEngine, round: u32) -> Value {
+    let memory = engine.memory();
+    let hex: String = memory.data().iter().map(|b| format!("{:02x}", b)).collect();
+    let owners: Vec<u8> = memory.ownership().iter().map(|o| o.unwrap_or(0)).collect();
+    let processes: Vec<Value> = engine
+        .processes()
+        .iter()
+        .map(|p| json!({"id": p.id, "champion_id": p.champion_id, "pc": p.pc}))
+        .collect();
+    json!({
+        "type": "snapshot",
+        "round": round,
+        "cycle": engine.get_stats().cycle,
+        "cycle_to_die": engine.scheduler_stats().cycle_to_die,
+        "memory": hex,
+        "owners": owners,
+        "processes": processes,
+    })
+}
+
+/// Build the `delta` message of one cycle's events
+pub fn delta(round: u32, cycle: u32, events: &[GameEvent]) -> Result<Value> {
+    let events = events
+        .iter()
+        .map(|event| ndjson::event_object(cycle, event).map(Value::Object))
+        .collect::<Result<Vec<Value>>>()?;
+    Ok(json!({
+        "type": "delta",
+        "round": round,
+        "cycle": cycle,
+        "events": events,
+    }))
+}
+
+/// Check whether a viewer's message asks for a snapshot
+fn is_sync(text: &str) -> bool {
+    serde_json::from_str::<Value>(text).is_ok_and(|message| message["type"] == "sync")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn engine() -> GameEngine {
+        GameEngine::builder()
+            .max_cycles(3)
+            .champion_bytes("Liver", [0x01; 10])
+            .champion_bytes("Idle", [0x02; 10])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_messages() {
+        let mut engine = engine();
+        engine.start().unwrap();
+        let hello = hello(&engine, 2);
+        assert_eq!(hello["protocol"], PROTOCOL_VERSION);
+        assert_eq!(hello["champions"][1]["name"], "Idle");
+
+        let snapshot = snapshot(&engine, 1);
+        let size = engine.memory().size();
+        assert_eq!(snapshot["memory"].as_str().unwrap().len(), size * 2);
+        assert_eq!(snapshot["owners"][0], 1);
+        assert_eq!(snapshot["processes"].as_array().unwrap().len(), 2);
+
+        let delta = delta(1, 4, &[GameEvent::CycleCompleted { cycle: 4 }]).unwrap();
+        assert_eq!(delta["events"][0]["type"], "cycle_completed");
+        assert!(is_sync(r#"{"type": "sync"}"#));
+        assert!(!is_sync("sync"));
+    }
+
+    #[test]
+    fn test_viewer_follows_a_battle() {
+        let mut server = BattleServer::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        let viewer = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream
+                .write_all(
+                    b"GET / HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                      Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+                )
+                .unwrap();
+            let mut response = [0u8; 129];
+            std::io::Read::read_exact(&mut stream, &mut response).unwrap();
+            assert!(response.starts_with(b"HTTP/1.1 101"));
+
+            let mut messages = Vec::new();
+            while let Ok(Frame::Text(text)) = websocket::read_frame(&mut stream) {
+                messages.push(serde_json::from_str::<Value>(&text).unwrap());
+            }
+            messages
+        });
+
+        let mut engine = engine();
+        server.run(&mut engine, 1, true).unwrap();
+        let messages = viewer.join().unwrap();
+        let types: Vec<&str> = messages
+            .iter()
+            .map(|m| m["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "hello", "snapshot", "snapshot", "delta", "delta", "delta", "result"
+            ]
+        );
+        assert_eq!(messages[6]["winner"], 1);
+        assert_eq!(server.viewers(), 0);
+    }
+}
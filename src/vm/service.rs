@@ -0,0 +1,687 @@
+/// Battles as a service over HTTP
+///
+/// `corewar serve --http` keeps a library of warriors and runs battles
+/// between them on request, so a web site or bot can offer Core War without
+/// embedding the crate. Bodies and answers are JSON unless noted.
+///
+/// # Endpoints
+///
+/// | Request | Body | Answer |
+/// |---------|------|--------|
+/// | `POST /warriors?name=NAME` | Redcode source, or a .cor file | `201` with the warrior: `id`, `name`, `comment`, `size` |
+/// | `GET /warriors` | | Every warrior |
+/// | `POST /battles` | `warriors` (IDs), optional `seed` and `max_cycles` | `202` with the battle's status |
+/// | `GET /battles` | | Status of every battle |
+/// | `GET /battles/ID` | | Status: `id`, `status` (`running`, `finished` or `failed`), `warriors`, `seed`, `max_cycles`, `cycle`, then `result` or `error` |
+/// | `GET /battles/ID/result` | | The battle's [`BattleReport`]; `409` until it is over |
+/// | `GET /battles/ID/recording` | | The battle's [`crate::vm::recording`], as bytes; `409` until it is over |
+///
+/// A `name` for a warrior overrides the one in its source or header. Errors
+/// answer with a 4xx or 5xx status and a JSON object holding an `error`
+/// message.
+///
+/// # Limits
+///
+/// Each battle runs on a thread of its own, at most
+/// [`ServiceLimits::max_running`] at once; starting another answers `503`
+/// until one finishes. No battle runs past [`ServiceLimits::max_cycles`]:
+/// asking for more, or for no limit, gets the cap. Request bodies over
+/// [`ServiceLimits::max_body`] bytes are refused, and so are warriors past
+/// [`MAX_WARRIORS`]. Only the latest [`MAX_BATTLES`] finished battles are
+/// kept.
+use crate::assembler::Assembler;
+use crate::error::{CoreWarError, Result};
+use crate::vm::GameEngineBuilder;
+use crate::vm::bundle::BattleReport;
+use crate::vm::http::{self, Request, Response};
+use crate::vm::loader::{COR_MAGIC, ChampionLoader};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Most warriors the service keeps
+pub const MAX_WARRIORS: usize = 1000;
+
+/// Most finished battles the service keeps
+pub const MAX_BATTLES: usize = 1000;
+
+/// Cycles between updates of a running battle's progress
+const PROGRESS_INTERVAL: u32 = 1000;
+
+/// Longest a client may take to send its request or take the answer
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Limits that keep the service safe to expose
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceLimits {
+    /// Most cycles a battle runs
+    pub max_cycles: u32,
+    /// Most battles running at once
+    pub max_running: usize,
+    /// Largest request body, in bytes
+    pub max_body: usize,
+}
+
+impl Default for ServiceLimits {
+    fn default() -> Self {
+        Self {
+            max_cycles: 100_000,
+            max_running: 4,
+            max_body: 256 * 1024,
+        }
+    }
+}
+
+/// A warrior in the library
+struct Warrior {
+    /// Name shown in results
+    name: String,
+    /// Comment from the header
+    comment: String,
+    /// Contents of the warrior's .cor file
+    cor: Vec<u8>,
+    /// Size of the warrior's code
+    size: usize,
+}
+
+/// Where a battle is at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Running,
+    Finished,
+    Failed,
+}
+
+impl Status {
+    /// Get the status as the API names it
+    fn name(self) -> &'static str {
+        match self {
+            Status::Running => "running",
+            Status::Finished => "finished",
+            Status::Failed => "failed",
+        }
+    }
+}
+
+/// A battle the service started
+struct Battle {
+    /// Warriors fighting, in load order
+    warriors: Vec<u32>,
+    /// Seed of the battle
+    seed: u64,
+    /// Cycle limit of the battle
+    max_cycles: u32,
+    /// Where the battle is at
+    status: Status,
+    /// Last cycle reported by the battle's thread
+    cycle: u32,
+    /// Outcome, once finished
+    report: Option<BattleReport>,
+    /// Recording, once finished
+    recording: Vec<u8>,
+    /// Why the battle failed, if it did
+    error: Option<String>,
+}
+
+impl Battle {
+    /// Describe the battle for the API
+    fn status(&self, id: u32) -> Value {
+        let mut status = json!({
+            "id": id,
+            "status": self.status.name(),
+            "warriors": self.warriors,
+            "seed": self.seed,
+            "max_cycles": self.max_cycles,
+            "cycle": self.cycle,
+        });
+        if let Some(report) = &self.report {
+            status["result"] = json!(report);
+        }
+        if let Some(error) = &self.error {
+            status["error"] = error.as_str().into();
+        }
+        status
+    }
+}
+
+/// Warriors and battles, shared with the battles' threads
+#[derive(Default)]
+struct Library {
+    /// Warriors by ID
+    warriors: BTreeMap<u32, Warrior>,
+    /// Battles by ID
+    battles: BTreeMap<u32, Battle>,
+    /// ID of the next warrior
+    next_warrior: u32,
+    /// ID of the next battle
+    next_battle: u32,
+}
+
+impl Library {
+    /// Count the battles still running
+    fn running(&self) -> usize {
+        self.battles
+            .values()
+            .filter(|b| b.status == Status::Running)
+            .count()
+    }
+
+    /// Forget the oldest finished battles beyond the ones kept
+    fn prune(&mut self) {
+        let done: Vec<u32> = self
+            .battles
+            .iter()
+            .filter(|(_, b)| b.status != Status::Running)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in done.iter().take(done.len().saturating_sub(MAX_BATTLES)) {
+            self.battles.remove(id);
+        }
+    }
+}
+
+/// Runs battles for HTTP clients
+#[derive(Clone)]
+pub struct BattleService {
+    /// Warriors and battles
+    library: Arc<Mutex<Library>>,
+    /// Limits on the service
+    limits: ServiceLimits,
+}
+
+impl BattleService {
+    /// Create a service with no warriors
+    ///
+    /// # Arguments
+    /// * `limits` - Limits on battles and requests
+    pub fn new(limits: ServiceLimits) -> Self {
+        Self {
+            library: Arc::new(Mutex::new(Library {
+                next_warrior: 1,
+                next_battle: 1,
+                ..Library::default()
+            })),
+            limits,
+        }
+    }
+
+    /// Answer requests for as long as the listener accepts connections
+    ///
+    /// Requests are answered on [`http::WORKERS`] threads, so a slow client
+    /// holds up no one else; battles run on threads of their own.
+    pub fn serve(&self, listener: TcpListener) -> Result<()> {
+        http::serve_connections(listener, http::WORKERS, |stream| self.answer(stream));
+        Ok(())
+    }
+
+    /// Read a request from a connection and answer it
+    fn answer(&self, mut stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let response = match http::read_request(&mut stream, self.limits.max_body) {
+            Ok(request) => self.handle(&request),
+            Err(e @ CoreWarError::Io(_)) => return Err(e),
+            Err(e) => Response::error(400, e),
+        };
+        response.write_to(&mut stream)
+    }
+
+    /// Answer a request
+    ///
+    /// # Arguments
+    /// * `request` - A request from a client
+    ///
+    /// # Returns
+    /// The response to send back
+    pub fn handle(&self, request: &Request) -> Response {
+        let segments = request.segments();
+        let id = segments.get(1).map(|id| id.parse::<u32>());
+        match (request.method.as_str(), segments.as_slice(), id) {
+            ("POST", ["warriors"], _) => self.post_warrior(request),
+            ("GET", ["warriors"], _) => self.warriors(),
+            ("POST", ["battles"], _) => self.start_battle(request),
+            ("GET", ["battles"], _) => self.battles(),
+            ("GET", ["battles", _], Some(Ok(id))) => {
+                self.battle(id, |b| Response::json(200, &b.status(id)))
+            }
+            ("GET", ["battles", _, "result"], Some(Ok(id))) => {
+                self.finished_battle(id, |b| Response::json(200, &json!(b.report)))
+            }
+            ("GET", ["battles", _, "recording"], Some(Ok(id))) => self.recording(id),
+            ("GET" | "POST", ..) => {
+                Response::error(404, format!("No such resource: {}", request.path))
+            }
+            _ => Response::error(405, format!("Method {} is not allowed", request.method)),
+        }
+    }
+
+    /// Add a warrior from Redcode source or a .cor file in the body
+    fn post_warrior(&self, request: &Request) -> Response {
+        if self.lock().warriors.len() >= MAX_WARRIORS {
+            return Response::error(
+                503,
+                format!("The service holds its limit of {} warriors", MAX_WARRIORS),
+            );
+        }
+        match self.add_warrior(request.query("name"), &request.body) {
+            Ok(warrior) => Response::json(201, &warrior),
+            Err(e) => Response::error(422, e),
+        }
+    }
+
+    /// Check and store a warrior
+    ///
+    /// # Arguments
+    /// * `name` - Name overriding the warrior's own, if any
+    /// * `body` - Redcode source, or the contents of a .cor file
+    ///
+    /// # Returns
+    /// The warrior as the API describes it
+    pub fn add_warrior(&self, name: Option<&str>, body: &[u8]) -> Result<Value> {
+        let cor = upload_cor(body)?;
+        let champion = ChampionLoader::new(true).load_champion_from_bytes(&cor, 1, None)?;
+
+        let mut library = self.lock();
+        let id = library.next_warrior;
+        library.next_warrior += 1;
+        let warrior = Warrior {
+            name: name.map_or(champion.name, str::to_string),
+            comment: champion.comment,
+            size: champion.code.len(),
+            cor,
+        };
+        let description = describe(id, &warrior);
+        info!("Warrior {} ({}) added", id, warrior.name);
+        library.warriors.insert(id, warrior);
+        Ok(description)
+    }
+
+    /// List the warriors
+    fn warriors(&self) -> Response {
+        let library = self.lock();
+        let warriors: Vec<Value> = library
+            .warriors
+            .iter()
+            .map(|(&id, warrior)| describe(id, warrior))
+            .collect();
+        Response::json(200, &Value::from(warriors))
+    }
+
+    /// Start a battle on a thread of its own
+    fn start_battle(&self, request: &Request) -> Response {
+        let body: Value = match serde_json::from_slice(&request.body) {
+            Ok(body) => body,
+            Err(e) => return Response::error(400, format!("Invalid JSON: {}", e)),
+        };
+        let Some(warriors) = body["warriors"].as_array().and_then(|ids| {
+            ids.iter()
+                .map(|id| id.as_u64().and_then(|id| u32::try_from(id).ok()))
+                .collect::<Option<Vec<u32>>>()
+        }) else {
+            return Response::error(400, "`warriors` must be a list of warrior IDs");
+        };
+        let seed = body["seed"]
+            .as_u64()
+            .unwrap_or(crate::vm::rng::DEFAULT_SEED);
+        let max_cycles = match body["max_cycles"].as_u64() {
+            Some(cycles) if cycles > 0 => cycles.min(u64::from(self.limits.max_cycles)) as u32,
+            _ => self.limits.max_cycles,
+        };
+
+        let mut library = self.lock();
+        if library.running() >= self.limits.max_running {
+            return Response::error(
+                503,
+                format!(
+                    "{} battles are already running; try again later",
+                    self.limits.max_running
+                ),
+            );
+        }
+        let mut builder = GameEngineBuilder::new().seed(seed).max_cycles(max_cycles);
+        for id in &warriors {
+            match library.warriors.get(id) {
+                Some(warrior) => builder = builder.champion_cor(warrior.cor.as_slice()),
+                None => return Response::error(404, format!("No warrior {}", id)),
+            }
+        }
+        let names: Vec<String> = warriors
+            .iter()
+            .map(|id| library.warriors[id].name.clone())
+            .collect();
+
+        let id = library.next_battle;
+        library.next_battle += 1;
+        let battle = Battle {
+            warriors,
+            seed,
+            max_cycles,
+            status: Status::Running,
+            cycle: 0,
+            report: None,
+            recording: Vec::new(),
+            error: None,
+        };
+        let status = battle.status(id);
+        library.battles.insert(id, battle);
+        drop(library);
+
+        let service = self.clone();
+        thread::spawn(move || service.run_battle(id, builder, names));
+        Response::json(202, &status)
+    }
+
+    /// List the battles
+    fn battles(&self) -> Response {
+        let library = self.lock();
+        let battles: Vec<Value> = library
+            .battles
+            .iter()
+            .map(|(&id, battle)| battle.status(id))
+            .collect();
+        Response::json(200, &Value::from(battles))
+    }
+
+    /// Answer about a battle, or that there is no such battle
+    fn battle(&self, id: u32, answer: impl FnOnce(&Battle) -> Response) -> Response {
+        match self.lock().battles.get(&id) {
+            Some(battle) => answer(battle),
+            None => Response::error(404, format!("No battle {}", id)),
+        }
+    }
+
+    /// Answer about a battle that is over
+    fn finished_battle(&self, id: u32, answer: impl FnOnce(&Battle) -> Response) -> Response {
+        self.battle(id, |battle| match battle.status {
+            Status::Running => Response::error(409, format!("Battle {} is still running", id)),
+            Status::Failed => Response::error(
+                409,
+                format!(
+                    "Battle {} failed: {}",
+                    id,
+                    battle.error.as_deref().unwrap_or("unknown error")
+                ),
+            ),
+            Status::Finished => answer(battle),
+        })
+    }
+
+    /// Answer with a battle's recording
+    fn recording(&self, id: u32) -> Response {
+        if cfg!(feature = "event-protocol") {
+            self.finished_battle(id, |b| Response::bytes(200, b.recording.clone()))
+        } else {
+            Response::error(
+                404,
+                "Recordings need the event-protocol feature, which this build does not include",
+            )
+        }
+    }
+
+    /// Play a battle to its end, keeping its progress and outcome
+    fn run_battle(&self, id: u32, builder: GameEngineBuilder, names: Vec<String>) {
+        info!("Battle {} started: {}", id, names.join(" vs "));
+        let recording = SharedBuffer::default();
+        let outcome = (|| -> Result<BattleReport> {
+            let mut engine = builder.build()?;
+            #[cfg(feature = "event-protocol")]
+            engine.record(Box::new(recording.clone()))?;
+            while engine.tick()?.is_running() {
+                if engine.state().cycle.is_multiple_of(PROGRESS_INTERVAL)
+                    && let Some(battle) = self.lock().battles.get_mut(&id)
+                {
+                    battle.cycle = engine.state().cycle;
+                }
+            }
+            #[cfg(feature = "event-protocol")]
+            engine.finish_recording()?;
+            let mut report = BattleReport::of(&engine);
+            for (champion, name) in report.champions.iter_mut().zip(names) {
+                champion.name = name;
+            }
+            Ok(report)
+        })();
+
+        let mut library = self.lock();
+        if let Some(battle) = library.battles.get_mut(&id) {
+            match outcome {
+                Ok(report) => {
+                    info!("Battle {} finished after {} cycles", id, report.cycles);
+                    battle.cycle = report.cycles;
+                    battle.report = Some(report);
+                    battle.recording = recording.take();
+                    battle.status = Status::Finished;
+                }
+                Err(e) => {
+                    warn!("Battle {} failed: {}", id, e);
+                    battle.error = Some(e.to_string());
+                    battle.status = Status::Failed;
+                }
+            }
+        }
+        library.prune();
+    }
+
+    /// Lock the library, even if a battle's thread panicked while holding it
+    fn lock(&self) -> MutexGuard<'_, Library> {
+        self.library.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Describe a warrior for the API
+fn describe(id: u32, warrior: &Warrior) -> Value {
+    json!({
+        "id": id,
+        "name": warrior.name,
+        "comment": warrior.comment,
+        "size": warrior.size,
+    })
+}
+
+/// Get the .cor file of an uploaded warrior
+///
+/// Source is assembled without `.include`, which would let a client read
+/// the server's files.
+///
+/// # Arguments
+/// * `body` - Redcode source, or the contents of a .cor file
+///
+/// # Returns
+/// The .cor file, assembling the source if needed
+pub(crate) fn upload_cor(body: &[u8]) -> Result<Vec<u8>> {
+    if body.starts_with(&COR_MAGIC.to_le_bytes()) {
+        return Ok(body.to_vec());
+    }
+    let source = std::str::from_utf8(body)
+        .map_err(|_| CoreWarError::config("Redcode source must be UTF-8"))?;
+    Assembler::new(false)
+        .with_includes(false)
+        .assemble_string(source)
+}
+
+/// A recording destination the battle's thread and the service share
+#[derive(Debug, Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    /// Take the bytes written so far
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIVER: &str = ".name \"Liver\"\n.comment \"Reports live\"\nlive %1\n";
+
+    fn request(method: &str, path: &str, body: &[u8]) -> Request {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query
+                .split_once('=')
+                .map(|(name, value)| vec![(name.to_string(), value.to_string())])
+                .unwrap_or_default(),
+            headers: Vec::new(),
+            body: body.to_vec(),
+        }
+    }
+
+    fn body(response: &Response) -> Value {
+        serde_json::from_slice(&response.body).unwrap()
+    }
+
+    /// Poll a battle until it is over
+    fn wait_for(service: &BattleService, id: u32) -> Value {
+        loop {
+            let status = body(&service.handle(&request("GET", &format!("/battles/{}", id), b"")));
+            if status["status"] != "running" {
+                return status;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_warriors_are_submitted_and_listed() {
+        let service = BattleService::new(ServiceLimits::default());
+        let added = service.handle(&request("POST", "/warriors", LIVER.as_bytes()));
+        assert_eq!(added.status, 201);
+        assert_eq!(body(&added)["name"], "Liver");
+
+        let cor = Assembler::new(false).assemble_string(LIVER).unwrap();
+        let added = service.handle(&request("POST", "/warriors?name=Copy", &cor));
+        assert_eq!(
+            body(&added),
+            json!({"id": 2, "name": "Copy", "comment": "Reports live", "size": 5})
+        );
+
+        let invalid = service.handle(&request("POST", "/warriors", b"nonsense %"));
+        assert_eq!(invalid.status, 422);
+        let listed = body(&service.handle(&request("GET", "/warriors", b"")));
+        assert_eq!(listed.as_array().unwrap().len(), 2);
+
+        assert_eq!(service.handle(&request("GET", "/nothing", b"")).status, 404);
+        assert_eq!(
+            service.handle(&request("DELETE", "/warriors", b"")).status,
+            405
+        );
+    }
+
+    #[test]
+    fn test_uploads_cannot_include_server_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("secret.s");
+        std::fs::write(&secret, "server secret %\n").unwrap();
+        let source = format!(".include \"{}\"\nlive %1\n", secret.display());
+
+        let service = BattleService::new(ServiceLimits::default());
+        let refused = service.handle(&request("POST", "/warriors", source.as_bytes()));
+        assert_eq!(refused.status, 422);
+        let error = body(&refused)["error"].as_str().unwrap().to_string();
+        assert!(error.contains(".include is not allowed"), "{}", error);
+        assert!(!error.contains("server secret"), "{}", error);
+    }
+
+    #[test]
+    fn test_slow_clients_do_not_hold_up_others() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let service = BattleService::new(ServiceLimits::default());
+        thread::spawn(move || service.serve(listener));
+
+        // A client that never sends its request
+        let _idle = TcpStream::connect(&address).unwrap();
+        let started = std::time::Instant::now();
+        let max_body = ServiceLimits::default().max_body;
+        let (status, _) = http::send(&address, "GET", "/warriors", b"", max_body).unwrap();
+        assert_eq!(status, 200);
+        assert!(started.elapsed() < CLIENT_TIMEOUT);
+    }
+
+    #[test]
+    fn test_battles_run_within_the_limits() {
+        let limits = ServiceLimits {
+            max_cycles: 50,
+            max_running: 1,
+            ..ServiceLimits::default()
+        };
+        let service = BattleService::new(limits);
+        service.handle(&request("POST", "/warriors", LIVER.as_bytes()));
+        service.handle(&request("POST", "/warriors", LIVER.as_bytes()));
+
+        let missing = service.handle(&request("POST", "/battles", br#"{"warriors": [1, 9]}"#));
+        assert_eq!(missing.status, 404);
+        assert_eq!(
+            service.handle(&request("POST", "/battles", b"{}")).status,
+            400
+        );
+
+        let started = service.handle(&request(
+            "POST",
+            "/battles",
+            br#"{"warriors": [1, 2], "seed": 3, "max_cycles": 1000000}"#,
+        ));
+        assert_eq!(started.status, 202);
+        let started = body(&started);
+        assert_eq!(started["max_cycles"], 50);
+        let id = started["id"].as_u64().unwrap() as u32;
+
+        let status = wait_for(&service, id);
+        assert_eq!(status["status"], "finished");
+        assert!(status["cycle"].as_u64().unwrap() <= 50);
+        assert_eq!(status["result"]["winner"], 1);
+
+        let result = service.handle(&request("GET", &format!("/battles/{}/result", id), b""));
+        assert_eq!(body(&result)["champions"][1]["name"], "Liver");
+        let recording = service.handle(&request("GET", &format!("/battles/{}/recording", id), b""));
+        #[cfg(feature = "event-protocol")]
+        assert!(crate::vm::Recording::parse(&recording.body).is_ok());
+        #[cfg(not(feature = "event-protocol"))]
+        assert_eq!(recording.status, 404);
+        assert_eq!(
+            service.handle(&request("GET", "/battles/99", b"")).status,
+            404
+        );
+    }
+
+    #[test]
+    fn test_a_full_service_refuses_battles() {
+        let limits = ServiceLimits {
+            max_running: 0,
+            ..ServiceLimits::default()
+        };
+        let service = BattleService::new(limits);
+        service.handle(&request("POST", "/warriors", LIVER.as_bytes()));
+        let refused = service.handle(&request("POST", "/battles", br#"{"warriors": [1]}"#));
+        assert_eq!(refused.status, 503);
+        assert!(
+            body(&service.handle(&request("GET", "/battles", b"")))
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,74 @@
+/// Serializable snapshots of a running battle
+///
+/// A [`GameSnapshot`] captures everything needed to resume a battle on
+/// another engine: memory and ownership, every process, the scheduler
+/// counters, the champions and the random number generator. Snapshots are
+/// used to checkpoint long battles and to write regression fixtures.
+use crate::error::{CoreWarError, Result};
+use crate::vm::scheduler::SchedulerSnapshot;
+use crate::vm::{Champion, Rules, SeededRng, TieBreak};
+use serde::{Deserialize, Serialize};
+
+/// Snapshot format version, bumped whenever the layout changes
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Complete simulation state of a battle at a given cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct GameSnapshot {
+    /// Snapshot format version
+    pub version: u32,
+    /// Arena rules the battle runs under
+    pub rules: Rules,
+    /// Cycle the snapshot was taken at
+    pub cycle: u32,
+    /// Winner champion ID, if the battle was already decided
+    pub winner: Option<u8>,
+    /// How a drawn battle was decided, if a tie-breaker picked the winner
+    pub tie_break: Option<TieBreak>,
+    /// Memory contents
+    pub memory: Vec<u8>,
+    /// Owner of each memory location
+    pub ownership: Vec<Option<u8>>,
+    /// Processes and scheduler counters
+    pub scheduler: SchedulerSnapshot,
+    /// Loaded champions
+    pub champions: Vec<Champion>,
+    /// Random number generator state
+    pub rng: SeededRng,
+}
+
+impl GameSnapshot {
+    /// Encode the snapshot as JSON
+    ///
+    /// # Returns
+    /// The JSON text, or an error if encoding fails
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| CoreWarError::snapshot(e.to_string()))
+    }
+
+    /// Decode a snapshot from JSON
+    ///
+    /// # Arguments
+    /// * `json` - JSON produced by [`GameSnapshot::to_json`]
+    ///
+    /// # Returns
+    /// The snapshot, or an error if the JSON is invalid or from another version
+    pub fn from_json(json: &str) -> Result<Self> {
+        let snapshot: Self =
+            serde_json::from_str(json).map_err(|e| CoreWarError::snapshot(e.to_string()))?;
+        snapshot.check_version()?;
+        Ok(snapshot)
+    }
+
+    /// Check that the snapshot was written by this format version
+    pub fn check_version(&self) -> Result<()> {
+        if self.version != SNAPSHOT_VERSION {
+            return Err(CoreWarError::snapshot(format!(
+                "Unsupported snapshot version {} (expected {})",
+                self.version, SNAPSHOT_VERSION
+            )));
+        }
+        Ok(())
+    }
+}
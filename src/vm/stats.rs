@@ -0,0 +1,388 @@
+/// Long-term statistics across stored battles
+///
+/// Every battle run with `--bundle` leaves a [`ResultsBundle`] behind.
+/// This module aggregates a set of bundles into per-champion and
+/// head-to-head statistics: win rates, how often a champion survives to the
+/// end and how long its battles last. Champions are picked by name, display
+/// name or a prefix of their code's SHA-256, so a renamed champion is still
+/// found by its hash and a reassembled one counts separately.
+use crate::error::{CoreWarError, Result};
+use crate::vm::ResultsBundle;
+use crate::vm::bundle::BundledChampion;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// Shortest SHA-256 prefix accepted as a champion selector
+pub const MIN_HASH_PREFIX: usize = 8;
+
+/// Results of one champion across battles
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChampionStats {
+    /// Name of the champion in the first battle found
+    pub name: String,
+    /// Battles the champion took part in
+    pub battles: usize,
+    /// Battles the champion won
+    pub wins: usize,
+    /// Battles that ended in a draw
+    pub draws: usize,
+    /// Battles the champion still had processes at the end of
+    pub survived: usize,
+    /// Sum of the battles' lengths in cycles
+    pub total_cycles: u64,
+}
+
+impl ChampionStats {
+    /// Aggregate a champion's results
+    ///
+    /// # Arguments
+    /// * `bundles` - Stored battles
+    /// * `selector` - Name, display name or SHA-256 prefix of the champion
+    ///
+    /// # Returns
+    /// The statistics, or an error if the champion is in none of the battles
+    pub fn collect(bundles: &[ResultsBundle], selector: &str) -> Result<Self> {
+        let mut stats: Option<Self> = None;
+        for bundle in bundles {
+            let Some(champion) = find(bundle, selector, None) else {
+                continue;
+            };
+            let stats = stats.get_or_insert_with(|| Self {
+                name: display_name(champion),
+                battles: 0,
+                wins: 0,
+                draws: 0,
+                survived: 0,
+                total_cycles: 0,
+            });
+            stats.battles += 1;
+            stats.total_cycles += u64::from(bundle.report.cycles);
+            match bundle.report.winner {
+                Some(id) if id == champion.id => stats.wins += 1,
+                Some(_) => {}
+                None => stats.draws += 1,
+            }
+            if survived(bundle, champion.id) {
+                stats.survived += 1;
+            }
+        }
+        stats.ok_or_else(|| {
+            CoreWarError::game_state(format!("No stored battle with champion '{}'", selector))
+        })
+    }
+
+    /// Battles the champion lost
+    pub fn losses(&self) -> usize {
+        self.battles - self.wins - self.draws
+    }
+
+    /// Fraction of battles won, from 0 to 1
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.wins, self.battles)
+    }
+
+    /// Average battle length in cycles
+    pub fn average_cycles(&self) -> f64 {
+        self.total_cycles as f64 / self.battles as f64
+    }
+}
+
+impl fmt::Display for ChampionStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Champion: {}", self.name)?;
+        writeln!(f, "Battles: {}", self.battles)?;
+        writeln!(
+            f,
+            "Wins: {} ({:.1}%), losses: {}, draws: {}",
+            self.wins,
+            self.win_rate() * 100.0,
+            self.losses(),
+            self.draws
+        )?;
+        writeln!(
+            f,
+            "Survived to the end: {} ({:.1}%)",
+            self.survived,
+            ratio(self.survived, self.battles) * 100.0
+        )?;
+        write!(
+            f,
+            "Average battle length: {:.1} cycles",
+            self.average_cycles()
+        )
+    }
+}
+
+/// Head-to-head results of two champions across battles
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchupStats {
+    /// Name of the first champion in the first battle found
+    pub first: String,
+    /// Name of the second champion in the first battle found
+    pub second: String,
+    /// Battles both champions took part in
+    pub battles: usize,
+    /// Battles the first champion won
+    pub first_wins: usize,
+    /// Battles the second champion won
+    pub second_wins: usize,
+    /// Battles that ended in a draw
+    pub draws: usize,
+    /// Sum of the battles' lengths in cycles
+    pub total_cycles: u64,
+}
+
+impl MatchupStats {
+    /// Aggregate the battles two champions fought against each other
+    ///
+    /// Battles with other champions count too; a third champion's win is
+    /// neither side's.
+    ///
+    /// # Arguments
+    /// * `bundles` - Stored battles
+    /// * `first` - Name, display name or SHA-256 prefix of one champion
+    /// * `second` - Selector of the other champion
+    ///
+    /// # Returns
+    /// The statistics, or an error if the champions never met
+    pub fn collect(bundles: &[ResultsBundle], first: &str, second: &str) -> Result<Self> {
+        let mut stats: Option<Self> = None;
+        for bundle in bundles {
+            let Some(a) = find(bundle, first, None) else {
+                continue;
+            };
+            let Some(b) = find(bundle, second, Some(a.id)) else {
+                continue;
+            };
+            let stats = stats.get_or_insert_with(|| Self {
+                first: display_name(a),
+                second: display_name(b),
+                battles: 0,
+                first_wins: 0,
+                second_wins: 0,
+                draws: 0,
+                total_cycles: 0,
+            });
+            stats.battles += 1;
+            stats.total_cycles += u64::from(bundle.report.cycles);
+            match bundle.report.winner {
+                Some(id) if id == a.id => stats.first_wins += 1,
+                Some(id) if id == b.id => stats.second_wins += 1,
+                Some(_) => {}
+                None => stats.draws += 1,
+            }
+        }
+        stats.ok_or_else(|| {
+            CoreWarError::game_state(format!(
+                "No stored battle between '{}' and '{}'",
+                first, second
+            ))
+        })
+    }
+
+    /// Average battle length in cycles
+    pub fn average_cycles(&self) -> f64 {
+        self.total_cycles as f64 / self.battles as f64
+    }
+}
+
+impl fmt::Display for MatchupStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Matchup: {} vs {}", self.first, self.second)?;
+        writeln!(f, "Battles: {}", self.battles)?;
+        writeln!(
+            f,
+            "{} wins: {} ({:.1}%)",
+            self.first,
+            self.first_wins,
+            ratio(self.first_wins, self.battles) * 100.0
+        )?;
+        writeln!(
+            f,
+            "{} wins: {} ({:.1}%)",
+            self.second,
+            self.second_wins,
+            ratio(self.second_wins, self.battles) * 100.0
+        )?;
+        writeln!(f, "Draws: {}", self.draws)?;
+        write!(
+            f,
+            "Average battle length: {:.1} cycles",
+            self.average_cycles()
+        )
+    }
+}
+
+/// Read stored battles from bundle files and directories of them
+///
+/// Directories are searched for `.json` files, not recursively.
+///
+/// # Arguments
+/// * `paths` - Bundle files written with `corewar run --bundle`, or
+///   directories holding them
+///
+/// # Returns
+/// Every bundle, or an error naming the first file that cannot be read
+pub fn read_bundles(paths: &[PathBuf]) -> Result<Vec<ResultsBundle>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<_>>()?;
+            entries.retain(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "json"));
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    files
+        .iter()
+        .map(|file| {
+            ResultsBundle::read(file)
+                .map_err(|e| CoreWarError::bundle(format!("Cannot read {}: {}", file.display(), e)))
+        })
+        .collect()
+}
+
+/// Check whether a selector picks a champion
+///
+/// Names and display names match ignoring case; a SHA-256 prefix needs
+/// at least [`MIN_HASH_PREFIX`] hex digits.
+fn matches(champion: &BundledChampion, selector: &str) -> bool {
+    let selector = selector.trim();
+    champion.name.eq_ignore_ascii_case(selector)
+        || champion
+            .alias
+            .as_deref()
+            .is_some_and(|alias| alias.eq_ignore_ascii_case(selector))
+        || (selector.len() >= MIN_HASH_PREFIX
+            && champion.sha256.starts_with(&selector.to_ascii_lowercase()))
+}
+
+/// Find the first champion of a battle picked by a selector
+///
+/// # Arguments
+/// * `bundle` - The battle
+/// * `selector` - Name, display name or SHA-256 prefix
+/// * `except` - Champion ID to skip, so a mirror match finds both sides
+fn find<'a>(
+    bundle: &'a ResultsBundle,
+    selector: &str,
+    except: Option<u8>,
+) -> Option<&'a BundledChampion> {
+    bundle
+        .champions
+        .iter()
+        .find(|c| Some(c.id) != except && matches(c, selector))
+}
+
+/// Name a champion as reports show it
+fn display_name(champion: &BundledChampion) -> String {
+    champion
+        .alias
+        .clone()
+        .unwrap_or_else(|| champion.name.clone())
+}
+
+/// Check whether a champion had processes left when a battle ended
+fn survived(bundle: &ResultsBundle, champion_id: u8) -> bool {
+    bundle
+        .report
+        .champions
+        .iter()
+        .any(|c| c.id == champion_id && c.process_count > 0)
+}
+
+/// Divide, treating an empty set as 0
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::GameEngine;
+
+    const LIVER: [u8; 100] = [0x01; 100];
+    const IDLE: [u8; 100] = [0x02; 100];
+
+    fn battle(first: (&str, [u8; 100]), second: (&str, [u8; 100])) -> ResultsBundle {
+        // Short enough that the idle champions never run off their code
+        let mut engine = GameEngine::builder()
+            .max_cycles(10)
+            .champion_bytes(first.0, first.1)
+            .champion_bytes(second.0, second.1)
+            .build()
+            .unwrap();
+        engine.run_to_completion().unwrap();
+        ResultsBundle::record(&engine).unwrap()
+    }
+
+    fn history() -> Vec<ResultsBundle> {
+        vec![
+            battle(("Liver", LIVER), ("Idle", IDLE)),
+            battle(("Idle", IDLE), ("Liver", LIVER)),
+            battle(("Idle", IDLE), ("Other", [0x02; 100])),
+        ]
+    }
+
+    #[test]
+    fn test_champion_stats_by_name_and_hash() {
+        let bundles = history();
+
+        let liver = ChampionStats::collect(&bundles, "liver").unwrap();
+        assert_eq!((liver.battles, liver.wins, liver.draws), (2, 2, 0));
+        assert_eq!(liver.win_rate(), 1.0);
+        assert_eq!(liver.average_cycles(), 10.0);
+
+        let idle = ChampionStats::collect(&bundles, "Idle").unwrap();
+        assert_eq!((idle.battles, idle.wins, idle.draws), (3, 0, 1));
+        assert_eq!(idle.losses(), 2);
+        assert!(
+            idle.to_string()
+                .contains("Wins: 0 (0.0%), losses: 2, draws: 1")
+        );
+
+        let hash = &bundles[0].champions[0].sha256;
+        assert_eq!(
+            ChampionStats::collect(&bundles, &hash[..MIN_HASH_PREFIX]).unwrap(),
+            liver
+        );
+        assert!(ChampionStats::collect(&bundles, &hash[..4]).is_err());
+        assert!(ChampionStats::collect(&bundles, "Nobody").is_err());
+    }
+
+    #[test]
+    fn test_matchup_stats_count_both_sides() {
+        let bundles = history();
+
+        let matchup = MatchupStats::collect(&bundles, "Idle", "Liver").unwrap();
+        assert_eq!(matchup.battles, 2);
+        assert_eq!((matchup.first_wins, matchup.second_wins), (0, 2));
+        assert_eq!(matchup.first, "Idle");
+        assert!(MatchupStats::collect(&bundles, "Liver", "Other").is_err());
+    }
+
+    #[test]
+    fn test_read_bundles_from_a_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        for (i, bundle) in history().iter().enumerate() {
+            bundle
+                .write(dir.path().join(format!("{}.json", i)))
+                .unwrap();
+        }
+        fs::write(dir.path().join("notes.txt"), "not a bundle").unwrap();
+
+        let bundles = read_bundles(&[dir.path().to_path_buf()]).unwrap();
+        assert_eq!(bundles.len(), 3);
+        assert!(read_bundles(&[dir.path().join("notes.txt")]).is_err());
+    }
+}
@@ -0,0 +1,362 @@
+/// Scheduler and memory stress testing
+///
+/// This module synthesizes fork-heavy workloads with extreme process counts
+/// and runs them directly on the scheduler and memory, measuring throughput
+/// and checking structural invariants after every cycle.
+use crate::error::{CoreWarError, Result};
+use crate::vm::rng::{DEFAULT_SEED, SeededRng};
+use crate::vm::{Champion, GameEvent, Memory, Rules, Scheduler};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Opcodes placed at instruction slots, weighted towards fork
+const WORKLOAD_OPCODES: [u8; 10] = [
+    0x0C, 0x0C, 0x0C, // fork
+    0x01, 0x01, // live
+    0x03, // st
+    0x04, // add
+    0x09, // zjmp
+    0x02, 0x02, // no-op for the current executor
+];
+
+/// Filler between instruction slots; any non-zero byte keeps processes alive
+const FILLER: u8 = 0x02;
+
+/// Stress test parameters
+#[derive(Debug, Clone, Copy)]
+pub struct StressConfig {
+    /// Number of processes seeded at the start
+    pub processes: usize,
+    /// Number of cycles to run
+    pub cycles: u32,
+    /// Number of synthetic champions sharing the processes
+    pub champions: usize,
+    /// Process count at which the run stops early (0 = unlimited)
+    pub max_processes: usize,
+    /// Seed for the synthesized code and starting positions
+    pub seed: u64,
+    /// Arena rules
+    pub rules: Rules,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        Self {
+            processes: 1000,
+            cycles: 1000,
+            champions: 4,
+            max_processes: 1_000_000,
+            seed: DEFAULT_SEED,
+            rules: Rules::default(),
+        }
+    }
+}
+
+/// Why a stress run stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StressStop {
+    /// Every requested cycle ran
+    Completed,
+    /// The scheduler ended the battle
+    BattleOver,
+    /// The process count passed the configured limit
+    ProcessLimit,
+}
+
+/// Measurements from a stress run
+#[derive(Debug, Clone)]
+pub struct StressReport {
+    /// Configuration the run used
+    pub config: StressConfig,
+    /// Cycles executed
+    pub cycles_run: u32,
+    /// Why the run stopped
+    pub stop: StressStop,
+    /// Highest process count seen after a cycle
+    pub peak_processes: usize,
+    /// Process count after the last cycle
+    pub final_processes: usize,
+    /// Processes created by forks
+    pub spawned: u64,
+    /// Processes that died
+    pub died: u64,
+    /// Sum over cycles of the processes scheduled in that cycle
+    pub process_cycles: u64,
+    /// Wall-clock time spent executing cycles
+    pub elapsed: Duration,
+}
+
+impl StressReport {
+    /// Cycles executed per second
+    pub fn cycles_per_second(&self) -> f64 {
+        per_second(u64::from(self.cycles_run), self.elapsed)
+    }
+
+    /// Process visits per second, the scheduler's real workload
+    pub fn process_cycles_per_second(&self) -> f64 {
+        per_second(self.process_cycles, self.elapsed)
+    }
+}
+
+impl fmt::Display for StressReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stop = match self.stop {
+            StressStop::Completed => "completed",
+            StressStop::BattleOver => "battle over",
+            StressStop::ProcessLimit => "process limit reached",
+        };
+        writeln!(f, "Champions: {}", self.config.champions)?;
+        writeln!(
+            f,
+            "Cycles: {} of {} ({})",
+            self.cycles_run, self.config.cycles, stop
+        )?;
+        writeln!(
+            f,
+            "Processes: {} seeded, {} peak, {} final",
+            self.config.processes, self.peak_processes, self.final_processes
+        )?;
+        writeln!(f, "Forks: {}, deaths: {}", self.spawned, self.died)?;
+        writeln!(f, "Elapsed: {:.3}s", self.elapsed.as_secs_f64())?;
+        writeln!(
+            f,
+            "Throughput: {:.1} cycles/s, {:.0} process-cycles/s",
+            self.cycles_per_second(),
+            self.process_cycles_per_second()
+        )?;
+        write!(f, "Invariants: held after every cycle")
+    }
+}
+
+/// Run a stress test
+///
+/// # Arguments
+/// * `config` - Workload size and arena
+///
+/// # Returns
+/// The measurements, or an error if the workload is invalid or an invariant broke
+pub fn run(config: &StressConfig) -> Result<StressReport> {
+    config.rules.validate()?;
+    if config.champions == 0 || config.champions > usize::from(u8::MAX) {
+        return Err(CoreWarError::game_state(format!(
+            "Stress champions must be between 1 and {}, got {}",
+            u8::MAX,
+            config.champions
+        )));
+    }
+    if config.processes < config.champions {
+        return Err(CoreWarError::game_state(format!(
+            "Need at least one process per champion ({} processes for {} champions)",
+            config.processes, config.champions
+        )));
+    }
+
+    let mut rng = SeededRng::new(config.seed);
+    let mut memory = Memory::with_rules(&config.rules);
+    let mut champions = synthesize_champions(config, &mut rng, &mut memory)?;
+    let mut scheduler = Scheduler::with_rules(config.rules);
+
+    for i in 0..config.processes {
+        let champion = &champions[i % champions.len()];
+        let mut process = scheduler.create_process(champion);
+        process.set_pc(rng.next_below(memory.size()), memory.size());
+        scheduler.add_process(process);
+    }
+    scheduler.take_events();
+
+    let mut report = StressReport {
+        config: *config,
+        cycles_run: 0,
+        stop: StressStop::Completed,
+        peak_processes: config.processes,
+        final_processes: config.processes,
+        spawned: 0,
+        died: 0,
+        process_cycles: 0,
+        elapsed: Duration::ZERO,
+    };
+
+    for cycle in 1..=config.cycles {
+        report.process_cycles += scheduler.process_count() as u64;
+
+        let started = Instant::now();
+        let running = scheduler.execute_cycle(&mut memory, &mut champions)?;
+        report.elapsed += started.elapsed();
+
+        for event in scheduler.take_events() {
+            match event {
+                GameEvent::ProcessSpawned { .. } => report.spawned += 1,
+                GameEvent::ProcessDied { .. } => report.died += 1,
+                _ => {}
+            }
+        }
+
+        report.cycles_run = cycle;
+        report.final_processes = scheduler.process_count();
+        report.peak_processes = report.peak_processes.max(report.final_processes);
+
+        check_invariants(&scheduler, &memory, &champions, &report)
+            .map_err(|e| CoreWarError::game_state(format!("Cycle {}: {}", cycle, e)))?;
+
+        if !running {
+            report.stop = StressStop::BattleOver;
+            break;
+        }
+        if config.max_processes > 0 && report.final_processes > config.max_processes {
+            report.stop = StressStop::ProcessLimit;
+            break;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Fill memory with one fork-heavy champion per equal region
+fn synthesize_champions(
+    config: &StressConfig,
+    rng: &mut SeededRng,
+    memory: &mut Memory,
+) -> Result<Vec<Champion>> {
+    let region = memory.size() / config.champions;
+
+    (0..config.champions)
+        .map(|i| {
+            let id = (i + 1) as u8;
+            let start = i * region;
+            // The last champion also covers any remainder of the core
+            let end = if i + 1 == config.champions {
+                memory.size()
+            } else {
+                start + region
+            };
+            let code: Vec<u8> = (start..end)
+                .map(|address| match address % 5 {
+                    0 => WORKLOAD_OPCODES[rng.next_below(WORKLOAD_OPCODES.len())],
+                    _ => FILLER,
+                })
+                .collect();
+
+            memory.load_code(start, &code, id)?;
+            Ok(Champion::new(
+                id,
+                format!("stress-{}", id),
+                "Synthesized fork-heavy workload".to_string(),
+                code,
+                start,
+            ))
+        })
+        .collect()
+}
+
+/// Check the structural invariants the scheduler and memory must keep
+fn check_invariants(
+    scheduler: &Scheduler,
+    memory: &Memory,
+    champions: &[Champion],
+    report: &StressReport,
+) -> std::result::Result<(), String> {
+    let processes = scheduler.processes();
+    let champion_ids: HashSet<u8> = champions.iter().map(|c| c.id).collect();
+    let mut process_ids = HashSet::with_capacity(processes.len());
+
+    for process in &processes {
+        if !process_ids.insert(process.id) {
+            return Err(format!("process ID {} is scheduled twice", process.id));
+        }
+        if !process.alive {
+            return Err(format!("dead process {} is still scheduled", process.id));
+        }
+        if process.pc >= memory.size() {
+            return Err(format!(
+                "process {} has PC {} outside memory",
+                process.id, process.pc
+            ));
+        }
+        if !champion_ids.contains(&process.champion_id) {
+            return Err(format!(
+                "process {} belongs to unknown champion {}",
+                process.id, process.champion_id
+            ));
+        }
+    }
+
+    if memory.size() != report.config.rules.memory_size {
+        return Err(format!("memory size changed to {}", memory.size()));
+    }
+    if let Some(owner) = memory
+        .ownership()
+        .iter()
+        .flatten()
+        .find(|owner| !champion_ids.contains(owner))
+    {
+        return Err(format!("memory owned by unknown champion {}", owner));
+    }
+
+    let expected = report.config.processes as u64 + report.spawned - report.died;
+    if processes.len() as u64 != expected {
+        return Err(format!(
+            "{} processes scheduled but {} seeded + {} forked - {} died",
+            processes.len(),
+            report.config.processes,
+            report.spawned,
+            report.died
+        ));
+    }
+
+    Ok(())
+}
+
+/// Rate of `count` over `elapsed`
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        count as f64 / seconds
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stress_run_keeps_invariants() {
+        let config = StressConfig {
+            processes: 200,
+            cycles: 300,
+            ..Default::default()
+        };
+        let report = run(&config).unwrap();
+
+        assert_eq!(report.stop, StressStop::Completed);
+        assert_eq!(report.cycles_run, 300);
+        assert!(report.spawned > 0);
+        assert!(report.peak_processes > 200);
+        assert_eq!(
+            report.final_processes as u64,
+            200 + report.spawned - report.died
+        );
+    }
+
+    #[test]
+    fn test_stress_process_limit_and_validation() {
+        let config = StressConfig {
+            processes: 100,
+            cycles: 5000,
+            max_processes: 150,
+            ..Default::default()
+        };
+        let report = run(&config).unwrap();
+        assert_eq!(report.stop, StressStop::ProcessLimit);
+        assert!(report.final_processes > 150);
+
+        let config = StressConfig {
+            processes: 2,
+            champions: 4,
+            ..Default::default()
+        };
+        assert!(run(&config).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+/// Draw tie-breakers
+///
+/// When no champion wins outright, tournaments still need a deterministic
+/// ranking. Tie-breakers are applied in the configured order, each one
+/// narrowing the set of leading champions until a single one remains.
+use crate::error::{CoreWarError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A rule used to separate champions in a drawn battle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TieBreaker {
+    /// Most memory bytes owned at the end of the battle
+    MemoryCoverage,
+    /// Most `live` instructions executed
+    TotalLives,
+    /// Most processes still alive
+    ProcessesAlive,
+    /// Earliest cycle at which the champion killed an opposing process
+    EarliestFirstKill,
+}
+
+impl TieBreaker {
+    /// Get the name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MemoryCoverage => "coverage",
+            Self::TotalLives => "lives",
+            Self::ProcessesAlive => "processes",
+            Self::EarliestFirstKill => "first-kill",
+        }
+    }
+}
+
+impl FromStr for TieBreaker {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "coverage" => Ok(Self::MemoryCoverage),
+            "lives" => Ok(Self::TotalLives),
+            "processes" => Ok(Self::ProcessesAlive),
+            "first-kill" => Ok(Self::EarliestFirstKill),
+            other => Err(CoreWarError::game_state(format!(
+                "Unknown tie-breaker '{}' (expected coverage, lives, processes or first-kill)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for TieBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Ordered list of tie-breakers applied to drawn battles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TieBreakers {
+    order: [Option<TieBreaker>; 4],
+}
+
+impl TieBreakers {
+    /// Create a tie-breaker list
+    ///
+    /// # Arguments
+    /// * `order` - Tie-breakers in the order they are applied
+    ///
+    /// # Returns
+    /// The list, or an error if a tie-breaker repeats
+    pub fn new(order: &[TieBreaker]) -> Result<Self> {
+        let mut list = Self::default();
+        for (i, &tie_breaker) in order.iter().enumerate() {
+            if order[..i].contains(&tie_breaker) {
+                return Err(CoreWarError::game_state(format!(
+                    "Tie-breaker '{}' listed more than once",
+                    tie_breaker
+                )));
+            }
+            list.order[i] = Some(tie_breaker);
+        }
+        Ok(list)
+    }
+
+    /// Iterate over the tie-breakers in order
+    pub fn iter(&self) -> impl Iterator<Item = TieBreaker> + '_ {
+        self.order.iter().flatten().copied()
+    }
+
+    /// Check whether no tie-breakers are configured
+    pub fn is_empty(&self) -> bool {
+        self.order[0].is_none()
+    }
+}
+
+impl FromStr for TieBreakers {
+    type Err = CoreWarError;
+
+    /// Parse a comma-separated list such as `coverage,lives`
+    fn from_str(s: &str) -> Result<Self> {
+        let order = s
+            .split(',')
+            .filter(|part| !part.trim().is_empty())
+            .map(TieBreaker::from_str)
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(&order)
+    }
+}
+
+/// End-of-battle measurements for one champion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TieBreakScores {
+    /// Champion ID
+    pub champion_id: u8,
+    /// Memory bytes owned
+    pub memory_owned: usize,
+    /// Total `live` instructions executed
+    pub total_lives: u32,
+    /// Processes still alive
+    pub processes_alive: usize,
+    /// Cycle of the champion's first kill
+    pub first_kill_cycle: Option<u32>,
+}
+
+impl TieBreakScores {
+    /// Score under a tie-breaker, where higher ranks better
+    fn score(&self, tie_breaker: TieBreaker) -> i64 {
+        match tie_breaker {
+            TieBreaker::MemoryCoverage => self.memory_owned as i64,
+            TieBreaker::TotalLives => i64::from(self.total_lives),
+            TieBreaker::ProcessesAlive => self.processes_alive as i64,
+            TieBreaker::EarliestFirstKill => self
+                .first_kill_cycle
+                .map(|cycle| -i64::from(cycle))
+                .unwrap_or(i64::MIN),
+        }
+    }
+}
+
+/// How a drawn battle was decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TieBreak {
+    /// The tie-breaker that separated the champions
+    pub decided_by: TieBreaker,
+    /// The champion ranked first
+    pub winner: u8,
+}
+
+/// Rank drawn champions using the configured tie-breakers
+///
+/// # Arguments
+/// * `tie_breakers` - Tie-breakers in the order they are applied
+/// * `scores` - Measurements for each champion in the draw
+///
+/// # Returns
+/// The deciding tie-breaker and winner, or None if the champions stay tied
+pub fn break_tie(tie_breakers: &TieBreakers, scores: &[TieBreakScores]) -> Option<TieBreak> {
+    let mut leaders: Vec<&TieBreakScores> = scores.iter().collect();
+
+    for tie_breaker in tie_breakers.iter() {
+        let best = leaders.iter().map(|s| s.score(tie_breaker)).max()?;
+        leaders.retain(|s| s.score(tie_breaker) == best);
+
+        if let [winner] = leaders.as_slice() {
+            return Some(TieBreak {
+                decided_by: tie_breaker,
+                winner: winner.champion_id,
+            });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scores(champion_id: u8, memory_owned: usize, total_lives: u32) -> TieBreakScores {
+        TieBreakScores {
+            champion_id,
+            memory_owned,
+            total_lives,
+            processes_alive: 0,
+            first_kill_cycle: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_tie_breakers() {
+        let list: TieBreakers = "lives, coverage".parse().unwrap();
+        let order: Vec<TieBreaker> = list.iter().collect();
+        assert_eq!(
+            order,
+            vec![TieBreaker::TotalLives, TieBreaker::MemoryCoverage]
+        );
+
+        assert!("lives,lives".parse::<TieBreakers>().is_err());
+        assert!("luck".parse::<TieBreakers>().is_err());
+        assert!("".parse::<TieBreakers>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tie_breakers_applied_in_order() {
+        let all = [scores(1, 100, 5), scores(2, 100, 7), scores(3, 50, 9)];
+
+        // Coverage keeps 1 and 2, lives then picks 2
+        let list = TieBreakers::new(&[TieBreaker::MemoryCoverage, TieBreaker::TotalLives]).unwrap();
+        assert_eq!(
+            break_tie(&list, &all),
+            Some(TieBreak {
+                decided_by: TieBreaker::TotalLives,
+                winner: 2
+            })
+        );
+
+        // Lives alone picks 3
+        let list = TieBreakers::new(&[TieBreaker::TotalLives]).unwrap();
+        assert_eq!(break_tie(&list, &all).unwrap().winner, 3);
+
+        // Coverage alone leaves a tie
+        let list = TieBreakers::new(&[TieBreaker::MemoryCoverage]).unwrap();
+        assert_eq!(break_tie(&list, &all), None);
+    }
+
+    #[test]
+    fn test_earliest_first_kill() {
+        let mut a = scores(1, 0, 0);
+        let mut b = scores(2, 0, 0);
+        let c = scores(3, 0, 0);
+        a.first_kill_cycle = Some(300);
+        b.first_kill_cycle = Some(120);
+
+        let list = TieBreakers::new(&[TieBreaker::EarliestFirstKill]).unwrap();
+        assert_eq!(break_tie(&list, &[a, b, c]).unwrap().winner, 2);
+    }
+}
@@ -0,0 +1,546 @@
+/// Round-robin tournaments between many champions
+///
+/// A tournament plays every pairing of its entrants once, and optionally
+/// every 3- or 4-way melee too. Each of these is a [`Match`] of one or more
+/// rounds with rotated placement, every round on its own engine. Results are scored
+/// like a league: a win earns [`WIN_POINTS`], a draw [`DRAW_POINTS`] to every
+/// champion still in the arena, and a loss nothing. The standings can be
+/// printed as a table or exported as CSV or JSON.
+use crate::error::{CoreWarError, Result};
+use crate::vm::builder::ChampionSource;
+#[cfg(feature = "pspace")]
+use crate::vm::pspace;
+use crate::vm::rematch::rematch_seeds;
+use crate::vm::{GameConfig, GameEngine};
+use serde::Serialize;
+#[cfg(feature = "pspace")]
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Points for winning a match
+pub const WIN_POINTS: u32 = 3;
+
+/// Points for a match that ends in a draw
+pub const DRAW_POINTS: u32 = 1;
+
+/// Output format of the standings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StandingsFormat {
+    /// Aligned columns for the terminal
+    #[default]
+    Table,
+    /// `rank,name,file,played,wins,losses,draws,points` rows with a header
+    Csv,
+    /// The standings and every match's result
+    Json,
+}
+
+impl FromStr for StandingsFormat {
+    type Err = CoreWarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "table" => Ok(StandingsFormat::Table),
+            "csv" => Ok(StandingsFormat::Csv),
+            "json" => Ok(StandingsFormat::Json),
+            _ => Err(CoreWarError::game_state(format!(
+                "Unknown standings format '{}' (expected table, csv or json)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for StandingsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StandingsFormat::Table => write!(f, "table"),
+            StandingsFormat::Csv => write!(f, "csv"),
+            StandingsFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// One champion's line in the standings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Standing {
+    /// Champion name
+    pub name: String,
+    /// The champion's .cor file
+    pub file: PathBuf,
+    /// Matches played
+    pub played: usize,
+    /// Matches won
+    pub wins: usize,
+    /// Matches another champion won
+    pub losses: usize,
+    /// Matches that ended in a draw
+    pub draws: usize,
+    /// League points
+    pub points: u32,
+}
+
+/// Champions playing one or more rounds against each other
+///
+/// Each round rotates the load order by one place, so with spread placement
+/// every champion starts from every address and turn position in turn, and
+/// runs with the next of [`rematch_seeds`], so random placement scatters the
+/// champions differently every round.
+#[derive(Debug, Clone)]
+pub struct Match {
+    /// The champions, in the first round's load order
+    champions: Vec<ChampionSource>,
+    /// Configuration of the first round
+    config: GameConfig,
+    /// Number of rounds
+    rounds: u32,
+}
+
+/// Result of one round of a match
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoundResult {
+    /// Entrant indices in the round's load order
+    pub order: Vec<usize>,
+    /// Seed the round ran with
+    pub seed: u64,
+    /// Index of the winning entrant, or `None` for a draw
+    pub winner: Option<usize>,
+    /// Length of the round in cycles
+    pub cycles: u32,
+}
+
+/// Results of every round of a match
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MatchResult {
+    /// Names of the entrants
+    pub entrants: Vec<String>,
+    /// Every round, in order
+    pub rounds: Vec<RoundResult>,
+    /// Each entrant's p-space after the last round, with cell 0 holding its
+    /// result; empty without the `pspace` feature
+    pub pspace: Vec<Vec<i32>>,
+}
+
+impl Match {
+    /// Create a single-round match
+    ///
+    /// # Arguments
+    /// * `files` - The champions' .cor files
+    /// * `config` - Configuration of the first round
+    pub fn new(files: Vec<PathBuf>, config: GameConfig) -> Self {
+        Self {
+            champions: files.into_iter().map(ChampionSource::File).collect(),
+            config,
+            rounds: 1,
+        }
+    }
+
+    /// Create a single-round match between champions given as bytecode
+    ///
+    /// # Arguments
+    /// * `champions` - Each champion's name and code
+    /// * `config` - Configuration of the first round
+    pub fn with_code(champions: Vec<(String, Vec<u8>)>, config: GameConfig) -> Self {
+        Self {
+            champions: champions
+                .into_iter()
+                .map(|(name, code)| ChampionSource::Bytes { name, code })
+                .collect(),
+            config,
+            rounds: 1,
+        }
+    }
+
+    /// Play the match over a number of rounds
+    ///
+    /// # Arguments
+    /// * `rounds` - Number of rounds; the entrant that wins the most takes the match
+    pub fn best_of(mut self, rounds: u32) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Play every round
+    ///
+    /// # Returns
+    /// The rounds' results, or an error if there are no rounds or a round
+    /// cannot be played
+    pub fn play(&self) -> Result<MatchResult> {
+        if self.rounds == 0 {
+            return Err(CoreWarError::game_state("A match needs at least 1 round"));
+        }
+
+        let mut entrants: Vec<String> = Vec::new();
+        let mut rounds = Vec::new();
+        // Each entrant's p-space, empty until its first round
+        #[cfg_attr(not(feature = "pspace"), allow(unused_mut))]
+        let mut storage = vec![Vec::new(); self.champions.len()];
+        for (round, seed) in (0..self.rounds).zip(rematch_seeds(self.config.seed)) {
+            let mut order: Vec<usize> = (0..self.champions.len()).collect();
+            order.rotate_left(round as usize % self.champions.len().max(1));
+
+            let mut builder = GameEngine::builder().config(GameConfig {
+                seed,
+                ..self.config
+            });
+            for &index in &order {
+                builder = builder.champion_source(self.champions[index].clone());
+                #[cfg(feature = "pspace")]
+                if !storage[index].is_empty() {
+                    builder = builder.pspace(std::mem::take(&mut storage[index]));
+                }
+            }
+            let mut engine = builder.build()?;
+            let winner = engine.run_to_completion()?;
+
+            let champions = engine.champions();
+            #[cfg(feature = "pspace")]
+            if self.config.rules.pspace_size > 0 {
+                let running: BTreeSet<u8> =
+                    engine.processes().iter().map(|p| p.champion_id).collect();
+                for (&index, champion) in order.iter().zip(champions) {
+                    let mut cells = champion.pspace.clone();
+                    cells[pspace::RESULT_CELL] =
+                        pspace::round_result(champion.id, winner, &running);
+                    storage[index] = cells;
+                }
+            }
+            if entrants.is_empty() {
+                entrants = vec![String::new(); order.len()];
+                for (&index, champion) in order.iter().zip(champions) {
+                    entrants[index] = champion.display_name().to_string();
+                }
+            }
+            rounds.push(RoundResult {
+                winner: winner.and_then(|id| {
+                    champions
+                        .iter()
+                        .position(|c| c.id == id)
+                        .map(|position| order[position])
+                }),
+                order,
+                seed,
+                cycles: engine.state().cycle,
+            });
+        }
+        Ok(MatchResult {
+            entrants,
+            rounds,
+            pspace: storage,
+        })
+    }
+}
+
+impl MatchResult {
+    /// Rounds an entrant won
+    ///
+    /// # Arguments
+    /// * `entrant` - Index of the entrant
+    pub fn wins(&self, entrant: usize) -> usize {
+        self.rounds
+            .iter()
+            .filter(|r| r.winner == Some(entrant))
+            .count()
+    }
+
+    /// Rounds that ended in a draw
+    pub fn draws(&self) -> usize {
+        self.rounds.iter().filter(|r| r.winner.is_none()).count()
+    }
+
+    /// The entrant that won the most rounds, or `None` if that is a tie
+    pub fn winner(&self) -> Option<usize> {
+        let wins: Vec<usize> = (0..self.entrants.len()).map(|i| self.wins(i)).collect();
+        let best = *wins.iter().max()?;
+        let mut leaders = (0..wins.len()).filter(|&i| wins[i] == best);
+        match (leaders.next(), leaders.next()) {
+            (Some(leader), None) if best > 0 => Some(leader),
+            _ => None,
+        }
+    }
+
+    /// Total length of the rounds in cycles
+    pub fn cycles(&self) -> u64 {
+        self.rounds.iter().map(|r| u64::from(r.cycles)).sum()
+    }
+}
+
+/// Results of a finished tournament
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tournament {
+    /// Standings, best first
+    pub standings: Vec<Standing>,
+    /// Every match played, in order
+    pub matches: Vec<MatchResult>,
+}
+
+impl Tournament {
+    /// Play a tournament
+    ///
+    /// # Arguments
+    /// * `files` - Entrants' .cor files
+    /// * `melee_sizes` - Sizes of melees to play besides the pairings, e.g. 3 and 4
+    /// * `rounds` - Rounds per match, see [`Match::best_of`]
+    /// * `config` - Configuration every match runs with
+    ///
+    /// # Returns
+    /// The results, or an error if there are too few entrants, a melee size is
+    /// out of range or a match cannot be played
+    pub fn run(
+        files: &[PathBuf],
+        melee_sizes: &[usize],
+        rounds: u32,
+        config: GameConfig,
+    ) -> Result<Self> {
+        if files.len() < 2 {
+            return Err(CoreWarError::game_state(
+                "A tournament needs at least 2 champions",
+            ));
+        }
+        let mut sizes = vec![2];
+        for &size in melee_sizes {
+            if size < 3 || size > files.len() || size > config.rules.max_champions {
+                return Err(CoreWarError::game_state(format!(
+                    "Melee size {} must be from 3 to {}",
+                    size,
+                    files.len().min(config.rules.max_champions)
+                )));
+            }
+            if !sizes.contains(&size) {
+                sizes.push(size);
+            }
+        }
+
+        let mut standings: Vec<Standing> = files.iter().map(|file| Standing::new(file)).collect();
+        let mut matches = Vec::new();
+        for size in sizes {
+            for entrants in combinations(files.len(), size) {
+                let result =
+                    Match::new(entrants.iter().map(|&i| files[i].clone()).collect(), config)
+                        .best_of(rounds)
+                        .play()?;
+                score(&result, &entrants, &mut standings);
+                matches.push(result);
+            }
+        }
+
+        standings.sort_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then(b.wins.cmp(&a.wins))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(Self { standings, matches })
+    }
+
+    /// Render the standings
+    ///
+    /// # Arguments
+    /// * `format` - Output format
+    pub fn render(&self, format: StandingsFormat) -> Result<String> {
+        match format {
+            StandingsFormat::Table => Ok(self.to_string()),
+            StandingsFormat::Csv => {
+                let mut out = String::from("rank,name,file,played,wins,losses,draws,points\n");
+                for (rank, s) in self.standings.iter().enumerate() {
+                    out.push_str(&format!(
+                        "{},{},{},{},{},{},{},{}\n",
+                        rank + 1,
+                        s.name.replace(',', " "),
+                        s.file.display().to_string().replace(',', " "),
+                        s.played,
+                        s.wins,
+                        s.losses,
+                        s.draws,
+                        s.points
+                    ));
+                }
+                Ok(out)
+            }
+            StandingsFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| CoreWarError::game_state(e.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .standings
+            .iter()
+            .map(|s| s.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Champion".len());
+        writeln!(
+            f,
+            "{:>4}  {:<width$}  {:>6}  {:>4}  {:>6}  {:>5}  {:>6}",
+            "Rank", "Champion", "Played", "Wins", "Losses", "Draws", "Points"
+        )?;
+        for (rank, s) in self.standings.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<width$}  {:>6}  {:>4}  {:>6}  {:>5}  {:>6}",
+                rank + 1,
+                s.name,
+                s.played,
+                s.wins,
+                s.losses,
+                s.draws,
+                s.points
+            )?;
+        }
+        write!(f, "{} matches played", self.matches.len())
+    }
+}
+
+impl Standing {
+    /// Start a champion's standing, named after its file until it is loaded
+    fn new(file: &Path) -> Self {
+        Self {
+            name: file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            file: file.to_path_buf(),
+            played: 0,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            points: 0,
+        }
+    }
+}
+
+/// Add a match's result to the standings
+///
+/// # Arguments
+/// * `result` - The match's result
+/// * `entrants` - Indices into `standings` of the match's entrants
+/// * `standings` - Standings by file index
+fn score(result: &MatchResult, entrants: &[usize], standings: &mut [Standing]) {
+    let winner = result.winner();
+    for (i, &index) in entrants.iter().enumerate() {
+        let standing = &mut standings[index];
+        standing.name = result.entrants[i].clone();
+        standing.played += 1;
+        match winner {
+            Some(w) if w == i => {
+                standing.wins += 1;
+                standing.points += WIN_POINTS;
+            }
+            Some(_) => standing.losses += 1,
+            None => {
+                standing.draws += 1;
+                standing.points += DRAW_POINTS;
+            }
+        }
+    }
+}
+
+/// Every way to choose `k` of `n` indices, in lexicographic order
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut all = Vec::new();
+    let mut current: Vec<usize> = (0..k).collect();
+    if k == 0 || k > n {
+        return all;
+    }
+    loop {
+        all.push(current.clone());
+        // Find the rightmost index that can still move right
+        let Some(i) = (0..k).rev().find(|&i| current[i] < n - k + i) else {
+            return all;
+        };
+        current[i] += 1;
+        for j in i + 1..k {
+            current[j] = current[j - 1] + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::loader::write_champion;
+    use std::fs;
+
+    /// Write a champion file the loader accepts
+    fn champion(dir: &Path, name: &str, code: &[u8]) -> PathBuf {
+        let path = dir.join(format!("{}.cor", name.to_lowercase()));
+        let mut bytes = Vec::new();
+        write_champion(&mut bytes, name, "", code).unwrap();
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(
+            combinations(4, 3),
+            vec![vec![0, 1, 2], vec![0, 1, 3], vec![0, 2, 3], vec![1, 2, 3]]
+        );
+        assert_eq!(combinations(3, 2).len(), 3);
+        assert!(combinations(2, 3).is_empty());
+    }
+
+    #[test]
+    fn test_round_robin_standings() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = vec![
+            champion(dir.path(), "Idle", &[0x02; 100]),
+            champion(dir.path(), "Liver", &[0x01; 100]),
+            champion(dir.path(), "Dead", &[0x00; 100]),
+        ];
+        let config = GameConfig {
+            max_cycles: 50,
+            ..GameConfig::default()
+        };
+
+        let tournament = Tournament::run(&files, &[3], 1, config).unwrap();
+        assert_eq!(tournament.matches.len(), 4);
+        let liver = &tournament.standings[0];
+        assert_eq!(liver.name, "Liver");
+        assert_eq!((liver.played, liver.wins), (3, 3));
+        assert_eq!(liver.points, 3 * WIN_POINTS);
+        assert!(
+            tournament
+                .standings
+                .iter()
+                .all(|s| s.played == 3 && s.wins + s.losses + s.draws == 3)
+        );
+
+        let csv = tournament.render(StandingsFormat::Csv).unwrap();
+        assert!(csv.lines().nth(1).unwrap().starts_with("1,Liver,"));
+        assert!(tournament.to_string().contains("4 matches played"));
+
+        assert!(Tournament::run(&files[..1], &[], 1, config).is_err());
+        assert!(Tournament::run(&files, &[4], 1, config).is_err());
+    }
+
+    #[test]
+    fn test_best_of_rotates_the_load_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = vec![
+            champion(dir.path(), "Liver", &[0x01; 100]),
+            champion(dir.path(), "Idle", &[0x02; 100]),
+        ];
+        let config = GameConfig {
+            max_cycles: 50,
+            ..GameConfig::default()
+        };
+
+        let result = Match::new(files.clone(), config).best_of(3).play().unwrap();
+        assert_eq!(result.entrants, vec!["Liver", "Idle"]);
+        assert_eq!(result.rounds.len(), 3);
+        assert_eq!(result.rounds[0].order, vec![0, 1]);
+        assert_eq!(result.rounds[1].order, vec![1, 0]);
+        assert_eq!(result.rounds[0].seed, config.seed);
+        assert_ne!(result.rounds[1].seed, config.seed);
+        assert_eq!((result.wins(0), result.wins(1), result.draws()), (3, 0, 0));
+        assert_eq!(result.winner(), Some(0));
+
+        assert!(Match::new(files, config).best_of(0).play().is_err());
+    }
+}
@@ -0,0 +1,280 @@
+/// Checks of .cor files, for warrior CI pipelines
+///
+/// The loader accepts anything it can read a champion from. Validation is
+/// stricter: it looks at every byte of the file and reports each problem it
+/// finds, sorted into the classes of [`Failure`]. `corewar validate` exits
+/// with the code of the first class that failed:
+///
+/// | Exit code | Failure                                                |
+/// |-----------|--------------------------------------------------------|
+/// | 0         | None, the file is valid                                |
+/// | 1         | The file could not be read                             |
+/// | 2         | Wrong magic number                                     |
+/// | 3         | Header fields out of bounds                            |
+/// | 4         | Code size in the header does not match the file        |
+/// | 5         | Code that does not decode to instructions              |
+/// | 6         | Code larger than [`CHAMP_MAX_SIZE`]                    |
+use crate::assembler::disassembler;
+use crate::constants::CHAMP_MAX_SIZE;
+use crate::vm::loader::{COMMENT_LENGTH, COR_MAGIC, HEADER_SIZE, PROG_NAME_LENGTH};
+use crate::vm::metadata;
+use std::fmt;
+
+/// Offset of the name field in the header
+const NAME_OFFSET: usize = 4;
+
+/// Offset of the code size field in the header
+const CODE_SIZE_OFFSET: usize = NAME_OFFSET + PROG_NAME_LENGTH + 4;
+
+/// Offset of the comment field in the header
+const COMMENT_OFFSET: usize = CODE_SIZE_OFFSET + 4;
+
+/// Class of a problem with a .cor file, in the order they are checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Failure {
+    /// The file does not start with [`COR_MAGIC`]
+    Magic,
+    /// The file is shorter than the header, or a header field is malformed
+    Header,
+    /// The code size in the header does not match the bytes that follow
+    CodeSize,
+    /// Bytes of the code do not decode to an instruction
+    Decode,
+    /// The code is larger than a champion may be
+    TooLarge,
+}
+
+impl Failure {
+    /// Get the exit code `corewar validate` reports the failure with
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Failure::Magic => 2,
+            Failure::Header => 3,
+            Failure::CodeSize => 4,
+            Failure::Decode => 5,
+            Failure::TooLarge => 6,
+        }
+    }
+}
+
+/// One problem found in a .cor file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Class of the problem
+    pub failure: Failure,
+    /// What is wrong
+    pub message: String,
+}
+
+impl Problem {
+    fn new(failure: Failure, message: impl Into<String>) -> Self {
+        Self {
+            failure,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Validate the contents of a .cor file
+///
+/// A wrong magic number or a file shorter than the header stops the checks
+/// there, and code cut short by the end of the file is not decoded;
+/// otherwise every check runs. Bytes after the code are accepted only as a
+/// metadata trailer (see [`crate::vm::metadata`]).
+///
+/// # Arguments
+/// * `bytes` - The whole file
+///
+/// # Returns
+/// Every problem found, in the order of [`Failure`]; empty if the file is valid
+pub fn validate(bytes: &[u8]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let magic = bytes
+        .first_chunk::<4>()
+        .map(|magic| u32::from_le_bytes(*magic));
+    if magic != Some(COR_MAGIC) {
+        let found = magic.map_or_else(|| "nothing".to_string(), |m| format!("0x{:x}", m));
+        problems.push(Problem::new(
+            Failure::Magic,
+            format!("Magic number is {}, not 0x{:x}", found, COR_MAGIC),
+        ));
+        return problems;
+    }
+    if bytes.len() < HEADER_SIZE {
+        problems.push(Problem::new(
+            Failure::Header,
+            format!(
+                "File is {} bytes, shorter than the {}-byte header",
+                bytes.len(),
+                HEADER_SIZE
+            ),
+        ));
+        return problems;
+    }
+
+    let name = &bytes[NAME_OFFSET..NAME_OFFSET + PROG_NAME_LENGTH];
+    check_string("Name", name, &mut problems);
+    let comment = &bytes[COMMENT_OFFSET..COMMENT_OFFSET + COMMENT_LENGTH];
+    check_string("Comment", comment, &mut problems);
+    let paddings = [
+        NAME_OFFSET + PROG_NAME_LENGTH,
+        COMMENT_OFFSET + COMMENT_LENGTH,
+    ];
+    for offset in paddings {
+        if bytes[offset..offset + 4].iter().any(|&b| b != 0) {
+            problems.push(Problem::new(
+                Failure::Header,
+                format!("Padding at byte {} of the header is not zero", offset),
+            ));
+        }
+    }
+
+    let code_size = &bytes[CODE_SIZE_OFFSET..CODE_SIZE_OFFSET + 4];
+    let code_size = u32::from_le_bytes(code_size.try_into().unwrap()) as usize;
+    let rest = &bytes[HEADER_SIZE..];
+    let code = rest.get(..code_size).unwrap_or_default();
+    if code_size > rest.len() {
+        problems.push(Problem::new(
+            Failure::CodeSize,
+            format!(
+                "Header gives {} bytes of code, but the file holds {}",
+                code_size,
+                rest.len()
+            ),
+        ));
+    } else {
+        let trailer = &rest[code_size..];
+        if !trailer.is_empty() && metadata::trailer_length(trailer) != Some(trailer.len()) {
+            problems.push(Problem::new(
+                Failure::CodeSize,
+                format!(
+                    "{} bytes follow the {} bytes of code the header gives",
+                    trailer.len(),
+                    code_size
+                ),
+            ));
+        }
+    }
+
+    let undecodable: Vec<usize> = disassembler::decode_all(code)
+        .into_iter()
+        .filter_map(|decoded| decoded.err())
+        .collect();
+    if let Some(first) = undecodable.first() {
+        problems.push(Problem::new(
+            Failure::Decode,
+            format!(
+                "{} bytes of code do not decode to an instruction, the first at offset {}",
+                undecodable.len(),
+                first
+            ),
+        ));
+    }
+
+    if code_size > CHAMP_MAX_SIZE {
+        problems.push(Problem::new(
+            Failure::TooLarge,
+            format!(
+                "Code is {} bytes, more than the {} a champion may have",
+                code_size, CHAMP_MAX_SIZE
+            ),
+        ));
+    }
+
+    problems
+}
+
+/// Check a zero-terminated header string
+fn check_string(field: &str, bytes: &[u8], problems: &mut Vec<Problem>) {
+    let Some(end) = bytes.iter().position(|&b| b == 0) else {
+        problems.push(Problem::new(
+            Failure::Header,
+            format!(
+                "{} fills its {} bytes with no terminating zero",
+                field,
+                bytes.len()
+            ),
+        ));
+        return;
+    };
+    if std::str::from_utf8(&bytes[..end]).is_err() {
+        problems.push(Problem::new(
+            Failure::Header,
+            format!("{} is not valid UTF-8", field),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::Assembler;
+
+    fn assembled(source: &str) -> Vec<u8> {
+        Assembler::new(false).assemble_source(source).unwrap()
+    }
+
+    fn failures(bytes: &[u8]) -> Vec<Failure> {
+        validate(bytes).iter().map(|p| p.failure).collect()
+    }
+
+    #[test]
+    fn test_assembled_champions_are_valid() {
+        assert!(validate(&assembled(".name \"imp\"\nlive %1\nzjmp %-6\n")).is_empty());
+        let with_metadata = assembled(".name \"imp\"\n.author \"Alice\"\nlive %1\n");
+        assert!(validate(&with_metadata).is_empty());
+    }
+
+    #[test]
+    fn test_header_problems() {
+        let valid = assembled(".name \"imp\"\nlive %1\n");
+        assert_eq!(failures(&valid[..2]), [Failure::Magic]);
+        let mut wrong_magic = valid.clone();
+        wrong_magic[0] = 0;
+        assert_eq!(failures(&wrong_magic), [Failure::Magic]);
+        assert_eq!(failures(&valid[..100]), [Failure::Header]);
+
+        let mut unterminated = valid.clone();
+        unterminated[NAME_OFFSET..][..PROG_NAME_LENGTH].fill(b'x');
+        unterminated[COMMENT_OFFSET + COMMENT_LENGTH] = 1;
+        let problems = validate(&unterminated);
+        assert_eq!(problems.len(), 2);
+        assert_eq!(
+            problems[0].message,
+            "Name fills its 128 bytes with no terminating zero"
+        );
+        assert_eq!(
+            problems[1].message,
+            "Padding at byte 268 of the header is not zero"
+        );
+    }
+
+    #[test]
+    fn test_code_problems() {
+        let valid = assembled(".name \"imp\"\nlive %1\n");
+        assert_eq!(failures(&valid[..valid.len() - 1]), [Failure::CodeSize]);
+        let mut trailing = valid.clone();
+        trailing.extend_from_slice(b"junk");
+        assert_eq!(failures(&trailing), [Failure::CodeSize]);
+
+        let mut garbled = valid.clone();
+        garbled[HEADER_SIZE] = 0xFF;
+        let problems = validate(&garbled);
+        assert_eq!(problems[0].failure, Failure::Decode);
+        assert!(problems[0].message.ends_with("the first at offset 0"));
+
+        // An oversized champion made of valid instructions
+        let mut large = Vec::new();
+        let code = valid[HEADER_SIZE..].repeat(CHAMP_MAX_SIZE / 5 + 1);
+        crate::vm::loader::write_champion(&mut large, "big", "", &code).unwrap();
+        assert_eq!(failures(&large), [Failure::TooLarge]);
+        assert_eq!(Failure::TooLarge.exit_code(), 6);
+    }
+}
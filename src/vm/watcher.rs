@@ -0,0 +1,139 @@
+/// Source file watching
+///
+/// `corewar fight --watch` replays a fight whenever one of its warriors is
+/// saved. [`SourceWatcher`] notices those saves by polling each file's
+/// modification time and length, which works on every platform and for
+/// the handful of small files a fight names, without a notification API.
+///
+/// Editors that save by writing a new file and renaming it over the old
+/// one are seen as a change too, and a file that disappears for a moment
+/// is reported again once it is back.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Time between two looks at the files while waiting for a change
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// What a file looked like when last polled, `None` while it is missing
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Watches files for changes
+#[derive(Debug, Clone)]
+pub struct SourceWatcher {
+    /// Each watched file and how it looked when last polled
+    files: Vec<(PathBuf, Stamp)>,
+}
+
+impl SourceWatcher {
+    /// Start watching files as they are now
+    ///
+    /// # Arguments
+    /// * `paths` - Files to watch; they need not exist yet
+    pub fn new<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut files: Vec<(PathBuf, Stamp)> = Vec::new();
+        for path in paths {
+            let path = path.as_ref().to_path_buf();
+            if !files.iter().any(|(watched, _)| *watched == path) {
+                let stamp = stamp(&path);
+                files.push((path, stamp));
+            }
+        }
+        Self { files }
+    }
+
+    /// Get the watched files
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Look for files that changed since the last look
+    ///
+    /// # Returns
+    /// The files that were modified, created or removed, in the order they
+    /// were given
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, last) in &mut self.files {
+            let now = stamp(path);
+            if now != *last {
+                *last = now;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    /// Wait until a file changes
+    ///
+    /// Waits a further [`POLL_INTERVAL`] once something changed, so an
+    /// editor writing several files, or one file in several steps, is seen
+    /// as a single change.
+    ///
+    /// # Returns
+    /// The files that changed
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        loop {
+            let mut changed = self.changed();
+            if !changed.is_empty() {
+                thread::sleep(POLL_INTERVAL);
+                for path in self.changed() {
+                    if !changed.contains(&path) {
+                        changed.push(path);
+                    }
+                }
+                return changed;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Look at a file
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_are_reported_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let warrior = dir.path().join("warrior.s");
+        let other = dir.path().join("other.s");
+        fs::write(&warrior, "live %1\n").unwrap();
+        fs::write(&other, "live %1\n").unwrap();
+
+        let mut watcher = SourceWatcher::new([&warrior, &other, &warrior]);
+        assert_eq!(watcher.paths().count(), 2);
+        assert!(watcher.changed().is_empty());
+
+        // A longer file is a change even within the clock's resolution
+        fs::write(&other, "live %1\nlive %2\n").unwrap();
+        assert_eq!(watcher.changed(), [other]);
+        assert!(watcher.changed().is_empty());
+
+        fs::remove_file(&warrior).unwrap();
+        assert_eq!(watcher.changed(), std::slice::from_ref(&warrior));
+        fs::write(&warrior, "live %1\n").unwrap();
+        assert_eq!(watcher.wait(), [warrior]);
+    }
+
+    #[test]
+    fn test_missing_files_are_watched_for_creation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("later.s");
+        let mut watcher = SourceWatcher::new([&path]);
+        assert!(watcher.changed().is_empty());
+        fs::write(&path, "").unwrap();
+        assert_eq!(watcher.changed(), [path]);
+    }
+}
@@ -0,0 +1,263 @@
+/// Minimal WebSocket (RFC 6455) server side
+///
+/// Just enough of the protocol for [`crate::vm::serve`] to push battle
+/// messages to browsers: the HTTP upgrade handshake, unfragmented text
+/// frames to the client, and reading the client's masked text, ping and
+/// close frames.
+use crate::error::{CoreWarError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sha1::{Digest, Sha1};
+use std::io::{BufRead, BufReader, Read, Write};
+
+/// Suffix the client's key is hashed with to accept the handshake
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Longest request header the handshake reads, to bound a hostile client
+const MAX_REQUEST: usize = 8192;
+
+/// Largest frame payload read from a client; clients only send short messages
+const MAX_PAYLOAD: u64 = 1 << 16;
+
+/// Frame opcodes
+const TEXT: u8 = 0x1;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xA;
+
+/// A message read from a client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// A text message
+    Text(String),
+    /// A ping, to be answered with a pong carrying the same data
+    Ping(Vec<u8>),
+    /// The client is closing the connection
+    Close,
+    /// A binary, pong or continuation frame, which the server ignores
+    Other,
+}
+
+/// Accept a WebSocket upgrade request
+///
+/// Reads the client's HTTP request and answers with `101 Switching
+/// Protocols`, or with `400 Bad Request` and an error if it is not a
+/// WebSocket upgrade.
+///
+/// # Arguments
+/// * `stream` - Connection from the client, before anything was read
+pub fn handshake<S: Read + Write>(stream: &mut S) -> Result<()> {
+    let mut key = None;
+    let mut upgrade = false;
+    let mut read = 0;
+    // Clients send nothing more until they are answered, so the reader
+    // cannot buffer away the first frame
+    let mut reader = BufReader::new(&mut *stream);
+    loop {
+        let mut line = String::new();
+        let n = reader.read_line(&mut line)?;
+        read += n;
+        if n == 0 || read > MAX_REQUEST {
+            return Err(CoreWarError::protocol("Incomplete WebSocket handshake"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "upgrade" => upgrade = value.eq_ignore_ascii_case("websocket"),
+                "sec-websocket-key" => key = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+    drop(reader);
+
+    let Some(key) = key.filter(|_| upgrade) else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+        return Err(CoreWarError::protocol("Not a WebSocket upgrade request"));
+    };
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(&key)
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Get the `Sec-WebSocket-Accept` value answering a client's key
+pub fn accept_key(key: &str) -> String {
+    STANDARD.encode(Sha1::digest(format!("{}{}", key, ACCEPT_GUID)))
+}
+
+/// Send a text message
+pub fn write_text<W: Write>(writer: &mut W, text: &str) -> Result<()> {
+    write_frame(writer, TEXT, text.as_bytes())
+}
+
+/// Answer a ping
+pub fn write_pong<W: Write>(writer: &mut W, data: &[u8]) -> Result<()> {
+    write_frame(writer, PONG, data)
+}
+
+/// Tell the client the connection is closing
+pub fn write_close<W: Write>(writer: &mut W) -> Result<()> {
+    write_frame(writer, CLOSE, &[])
+}
+
+/// Read the next frame from a client
+///
+/// Client frames are masked, as the protocol requires; fragmented messages
+/// are not supported and come back as [`Frame::Other`].
+pub fn read_frame<R: Read>(reader: &mut R) -> Result<Frame> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head)?;
+    let opcode = head[0] & 0x0F;
+    let masked = head[1] & 0x80 != 0;
+    let length = match head[1] & 0x7F {
+        126 => {
+            let mut bytes = [0u8; 2];
+            reader.read_exact(&mut bytes)?;
+            u64::from(u16::from_be_bytes(bytes))
+        }
+        127 => {
+            let mut bytes = [0u8; 8];
+            reader.read_exact(&mut bytes)?;
+            u64::from_be_bytes(bytes)
+        }
+        length => u64::from(length),
+    };
+    if length > MAX_PAYLOAD {
+        return Err(CoreWarError::protocol(format!(
+            "WebSocket frame of {} bytes is too large",
+            length
+        )));
+    }
+    let mut mask = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0u8; length as usize];
+    reader.read_exact(&mut payload)?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+
+    Ok(match opcode {
+        TEXT => Frame::Text(
+            String::from_utf8(payload)
+                .map_err(|_| CoreWarError::protocol("WebSocket text is not UTF-8"))?,
+        ),
+        PING => Frame::Ping(payload),
+        CLOSE => Frame::Close,
+        _ => Frame::Other,
+    })
+}
+
+/// Write one unfragmented, unmasked frame, as servers send them
+fn write_frame<W: Write>(writer: &mut W, opcode: u8, payload: &[u8]) -> Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend((length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend((length as u64).to_be_bytes());
+        }
+    }
+    frame.extend(payload);
+    writer.write_all(&frame)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// A connection that reads a scripted request and records the answer
+    struct Connection {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for Connection {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Connection {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_handshake() {
+        // The example from RFC 6455
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+
+        let request = "GET /battle HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                       Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                       Sec-WebSocket-Version: 13\r\n\r\n";
+        let mut connection = Connection {
+            input: Cursor::new(request.as_bytes().to_vec()),
+            output: Vec::new(),
+        };
+        handshake(&mut connection).unwrap();
+        let response = String::from_utf8(connection.output).unwrap();
+        assert!(response.starts_with("HTTP/1.1 101"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+
+        let mut connection = Connection {
+            input: Cursor::new(b"GET / HTTP/1.1\r\n\r\n".to_vec()),
+            output: Vec::new(),
+        };
+        assert!(handshake(&mut connection).is_err());
+        assert!(connection.output.starts_with(b"HTTP/1.1 400"));
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut output = Vec::new();
+        write_text(&mut output, "hi").unwrap();
+        assert_eq!(output, [0x81, 2, b'h', b'i']);
+        let mut output = Vec::new();
+        write_text(&mut output, &"x".repeat(300)).unwrap();
+        assert_eq!(output[..4], [0x81, 126, 0x01, 0x2C]);
+
+        // A masked "Hello" from the RFC, then a masked close
+        let input = [
+            0x81, 0x85, 0x37, 0xFA, 0x21, 0x3D, 0x7F, 0x9F, 0x4D, 0x51, 0x58, 0x88, 0x80, 0x00,
+            0x00, 0x00, 0x00,
+        ];
+        let mut reader = Cursor::new(input.to_vec());
+        assert_eq!(
+            read_frame(&mut reader).unwrap(),
+            Frame::Text("Hello".to_string())
+        );
+        assert_eq!(read_frame(&mut reader).unwrap(), Frame::Close);
+        assert!(read_frame(&mut reader).is_err());
+
+        let mut reader = Cursor::new(vec![0x89, 0x01, 0x07]);
+        assert_eq!(read_frame(&mut reader).unwrap(), Frame::Ping(vec![7]));
+    }
+}
@@ -0,0 +1,272 @@
+/// WebAssembly API for an in-browser playground
+///
+/// Built with the `wasm` feature and without the default `tui` feature, the
+/// crate compiles to `wasm32-unknown-unknown`, and `wasm-bindgen` exposes
+/// the assembler and a battle to JavaScript:
+///
+/// ```text
+/// cargo build --lib --release --target wasm32-unknown-unknown \
+///     --no-default-features --features wasm
+/// wasm-bindgen --target web target/wasm32-unknown-unknown/release/corewar.wasm --out-dir pkg
+/// ```
+///
+/// A page assembles Redcode with [`assemble`], loads the bytes into a
+/// [`Playground`] and calls [`Playground::tick`] from its animation loop,
+/// reading memory, owners and processes back to draw the core. Errors reach
+/// JavaScript as thrown `Error`s. Battles in the browser run unthrottled,
+/// since the page paces them.
+use crate::assembler::Assembler;
+use crate::vm::{GameEngine, GameEngineBuilder};
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+/// Assemble Redcode source into the contents of a .cor file
+///
+/// # Arguments
+/// * `source` - Redcode source
+///
+/// # Returns
+/// Header followed by code, ready for [`Playground::load`]
+#[wasm_bindgen]
+pub fn assemble(source: &str) -> Result<Vec<u8>, JsError> {
+    Ok(Assembler::new(false).assemble_string(source)?)
+}
+
+/// A battle driven from JavaScript
+#[wasm_bindgen]
+pub struct Playground {
+    /// Contents of the loaded .cor files, in load order
+    champions: Vec<Vec<u8>>,
+    /// Seed of the battle
+    seed: u64,
+    /// Maximum cycles of the battle, 0 for unlimited
+    max_cycles: u32,
+    /// The battle, once a champion is loaded
+    engine: Option<GameEngine>,
+}
+
+impl Default for Playground {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl Playground {
+    /// Create a playground with no champions
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            champions: Vec::new(),
+            seed: crate::vm::rng::DEFAULT_SEED,
+            max_cycles: 0,
+            engine: None,
+        }
+    }
+
+    /// Set the seed and maximum cycles, restarting the battle
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the battle
+    /// * `max_cycles` - Maximum cycles of the battle, 0 for unlimited
+    pub fn configure(&mut self, seed: u64, max_cycles: u32) -> Result<(), JsError> {
+        self.seed = seed;
+        self.max_cycles = max_cycles;
+        self.reset()
+    }
+
+    /// Add a champion and restart the battle with it
+    ///
+    /// # Arguments
+    /// * `cor` - Contents of a .cor file, as [`assemble`] returns them
+    ///
+    /// # Returns
+    /// The champion's ID
+    pub fn load(&mut self, cor: &[u8]) -> Result<u8, JsError> {
+        self.champions.push(cor.to_vec());
+        if let Err(e) = self.reset() {
+            self.champions.pop();
+            return Err(e);
+        }
+        Ok(self.champions.len() as u8)
+    }
+
+    /// Remove every champion
+    pub fn clear(&mut self) {
+        self.champions.clear();
+        self.engine = None;
+    }
+
+    /// Start the battle over from its first cycle
+    pub fn reset(&mut self) -> Result<(), JsError> {
+        if self.champions.is_empty() {
+            self.engine = None;
+            return Ok(());
+        }
+        let builder = self.champions.iter().fold(
+            GameEngineBuilder::new()
+                .seed(self.seed)
+                .max_cycles(self.max_cycles),
+            |builder, cor| builder.champion_cor(cor.as_slice()),
+        );
+        self.engine = Some(builder.build()?);
+        Ok(())
+    }
+
+    /// Run cycles of the battle
+    ///
+    /// # Arguments
+    /// * `cycles` - Cycles to run; fewer run if the battle ends first
+    ///
+    /// # Returns
+    /// Whether the battle is still running
+    pub fn tick(&mut self, cycles: u32) -> Result<bool, JsError> {
+        let Some(engine) = self.engine.as_mut() else {
+            return Ok(false);
+        };
+        if !engine.state().running {
+            return Ok(false);
+        }
+        for _ in 0..cycles {
+            if !engine.tick()?.is_running() {
+                break;
+            }
+        }
+        Ok(engine.state().running)
+    }
+
+    /// Get the current cycle
+    pub fn cycle(&self) -> u32 {
+        self.engine
+            .as_ref()
+            .map_or(0, |engine| engine.state().cycle)
+    }
+
+    /// Check whether the battle is still running
+    pub fn running(&self) -> bool {
+        self.engine
+            .as_ref()
+            .is_some_and(|engine| engine.state().running)
+    }
+
+    /// Get the ID of the winner, once the battle is over and was won
+    pub fn winner(&self) -> Option<u8> {
+        self.engine
+            .as_ref()
+            .and_then(|engine| engine.state().winner)
+    }
+
+    /// Get the number of bytes in memory
+    pub fn memory_size(&self) -> usize {
+        self.engine
+            .as_ref()
+            .map_or(0, |engine| engine.memory().size())
+    }
+
+    /// Get a copy of memory
+    pub fn memory(&self) -> Vec<u8> {
+        self.engine
+            .as_ref()
+            .map_or_else(Vec::new, |engine| engine.memory().data().to_vec())
+    }
+
+    /// Get the ID of the champion owning each byte, 0 for none
+    pub fn owners(&self) -> Vec<u8> {
+        self.engine.as_ref().map_or_else(Vec::new, |engine| {
+            engine
+                .memory()
+                .ownership()
+                .iter()
+                .map(|owner| owner.unwrap_or(0))
+                .collect()
+        })
+    }
+
+    /// Get the live processes as a JSON array
+    ///
+    /// Each process has its `id`, `champion_id`, `pc`, `carry`, `registers`
+    /// and the `wait_cycles` left before its next instruction.
+    pub fn processes(&self) -> String {
+        let processes: Vec<_> = self.engine.as_ref().map_or_else(Vec::new, |engine| {
+            engine
+                .processes()
+                .iter()
+                .map(|p| {
+                    json!({
+                        "id": p.id,
+                        "champion_id": p.champion_id,
+                        "pc": p.pc,
+                        "carry": p.carry,
+                        "registers": p.registers,
+                        "wait_cycles": p.wait_cycles,
+                    })
+                })
+                .collect()
+        });
+        serde_json::Value::from(processes).to_string()
+    }
+
+    /// Get the loaded champions as a JSON array
+    ///
+    /// Each champion has its `id`, `name`, `comment`, `size`, the
+    /// `process_count` it has left and its `live_count`.
+    pub fn champions(&self) -> String {
+        let champions: Vec<_> = self.engine.as_ref().map_or_else(Vec::new, |engine| {
+            engine
+                .champions()
+                .iter()
+                .map(|c| {
+                    json!({
+                        "id": c.id,
+                        "name": c.display_name(),
+                        "comment": c.comment,
+                        "size": c.code_size(),
+                        "process_count": c.process_count,
+                        "live_count": c.live_count,
+                    })
+                })
+                .collect()
+        });
+        serde_json::Value::from(champions).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIVER: &str = ".name \"Liver\"\n.comment \"Reports live once\"\nlive %1\n";
+
+    #[test]
+    fn test_playground_runs_an_assembled_battle() {
+        let mut playground = Playground::new();
+        assert!(!playground.running());
+        assert!(playground.memory().is_empty());
+        assert!(!playground.tick(10).unwrap());
+
+        let cor = assemble(LIVER).unwrap();
+        playground.configure(3, 20).unwrap();
+        assert_eq!(playground.load(&cor).unwrap(), 1);
+        assert_eq!(playground.load(&cor).unwrap(), 2);
+        assert!(playground.running());
+        assert_eq!(playground.memory().len(), playground.memory_size());
+        assert_eq!(playground.owners()[0], 1);
+
+        let processes: serde_json::Value = serde_json::from_str(&playground.processes()).unwrap();
+        assert_eq!(processes.as_array().unwrap().len(), 2);
+        let champions: serde_json::Value = serde_json::from_str(&playground.champions()).unwrap();
+        assert_eq!(champions[0]["name"], "Liver");
+
+        assert!(playground.tick(5).unwrap());
+        assert_eq!(playground.cycle(), 5);
+        assert!(!playground.tick(100).unwrap());
+        assert!(!playground.running());
+        assert!(playground.cycle() <= 20);
+        assert_eq!(playground.winner(), Some(1));
+
+        playground.reset().unwrap();
+        assert_eq!(playground.cycle(), 0);
+        playground.clear();
+        assert!(!playground.running());
+    }
+}
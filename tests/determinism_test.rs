@@ -0,0 +1,74 @@
+use corewar::GameEngine;
+
+/// State digests of the reference battle at fixed cycles
+///
+/// These values must be identical on every platform and pointer width; CI
+/// runs this test on Linux, macOS, Windows and a 32-bit target. Only update
+/// them when a change to the simulation is intended, and say so in the commit.
+const EXPECTED_DIGESTS: [(u32, u64); 6] = [
+    (1, 0x5c4e_5890_1fcc_f10d),
+    (10, 0xb1d4_644d_bd74_4685),
+    (100, 0x22b6_455d_e85f_5ccc),
+    (1000, 0xc1b3_ce58_502f_2e98),
+    (2000, 0x3afb_0d2d_db23_db3f),
+    (5000, 0x4b26_6b17_417d_9060),
+];
+
+/// Fill a quarter of the default core with a repeated instruction pattern
+fn pattern(slots: &[u8]) -> Vec<u8> {
+    slots
+        .iter()
+        .flat_map(|&opcode| [opcode, 0x02, 0x02, 0x02, 0x02])
+        .cycle()
+        .take(1536)
+        .collect()
+}
+
+/// Four champions filling the whole core with forks, jumps, writes and lives
+fn reference_battle() -> GameEngine {
+    GameEngine::builder()
+        .seed(0xC0DE)
+        .max_cycles(5000)
+        .champion_bytes("Forker", pattern(&[0x0C, 0x01, 0x03]))
+        .champion_bytes("Bomber", pattern(&[0x01, 0x03, 0x04]))
+        .champion_bytes("Runner", pattern(&[0x09, 0x01]))
+        .champion_bytes("Mixed", pattern(&[0x01, 0x0C, 0x09, 0x04]))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_reference_battle_digests_are_platform_independent() {
+    let mut engine = reference_battle();
+
+    for (cycle, expected) in EXPECTED_DIGESTS {
+        engine.run_cycles(cycle - engine.state().cycle).unwrap();
+        assert_eq!(engine.state().cycle, cycle);
+        assert_eq!(
+            engine.state_digest(),
+            expected,
+            "state digest diverged at cycle {}",
+            cycle
+        );
+    }
+
+    assert_eq!(engine.determine_winner().unwrap(), Some(4));
+}
+
+#[test]
+fn test_snapshot_round_trip_keeps_digest() {
+    let mut engine = reference_battle();
+    engine.run_cycles(1000).unwrap();
+
+    let json = engine.snapshot().to_json().unwrap();
+    let mut restored = reference_battle();
+    restored
+        .restore(corewar::vm::GameSnapshot::from_json(&json).unwrap())
+        .unwrap();
+    assert_eq!(restored.state_digest(), engine.state_digest());
+
+    engine.run_cycles(1000).unwrap();
+    restored.run_cycles(1000).unwrap();
+    assert_eq!(restored.state_digest(), EXPECTED_DIGESTS[4].1);
+    assert_eq!(engine.state_digest(), EXPECTED_DIGESTS[4].1);
+}
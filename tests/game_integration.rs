@@ -1,39 +1,18 @@
+use corewar::examples;
+use corewar::vm::assertion::{Assertion, run_with_assertions};
+use corewar::vm::loader::{HEADER_SIZE, write_champion};
 use corewar::{GameConfig, GameEngine};
 use std::io::Write;
 use tempfile::NamedTempFile;
 
-/// Create a simple test champion that just executes live instructions
+/// Create a test champion running the bundled imp, which reports live first
 fn create_live_champion(name: &str) -> NamedTempFile {
     let mut file = NamedTempFile::new().unwrap();
 
-    // Write header
-    let magic = 0xea83f3u32;
-    file.write_all(&magic.to_le_bytes()).unwrap();
-
-    // Write name (128 bytes)
-    let mut name_bytes = [0u8; 128];
-    let name_src = name.as_bytes();
-    name_bytes[..name_src.len()].copy_from_slice(name_src);
-    file.write_all(&name_bytes).unwrap();
-
-    // Padding
-    file.write_all(&[0u8; 4]).unwrap();
-
-    // Code: live %1 (simple instruction)
-    let code = vec![0x01, 0x40, 0x01, 0x00]; // live %1 in bytecode
-    file.write_all(&(code.len() as u32).to_le_bytes()).unwrap();
-
-    // Comment
+    let cor = examples::find("imp").unwrap().assemble().unwrap();
+    let code = &cor[HEADER_SIZE..];
     let comment = format!("{} - test champion", name);
-    let mut comment_bytes = [0u8; 128];
-    comment_bytes[..comment.len().min(127)].copy_from_slice(comment.as_bytes());
-    file.write_all(&comment_bytes).unwrap();
-
-    // Final padding
-    file.write_all(&[0u8; 4]).unwrap();
-
-    // Write code
-    file.write_all(&code).unwrap();
+    write_champion(&mut file, name, &comment, code).unwrap();
 
     file.flush().unwrap();
     file
@@ -41,10 +20,7 @@ fn create_live_champion(name: &str) -> NamedTempFile {
 
 #[test]
 fn test_game_flow_single_champion() {
-    let config = GameConfig {
-        max_cycles: 100,
-        ..Default::default()
-    };
+    let config = GameConfig::default().with_max_cycles(100);
     let mut engine = GameEngine::new(config);
 
     let champion = create_live_champion("TestChamp");
@@ -59,12 +35,8 @@ fn test_game_flow_single_champion() {
 }
 
 #[test]
-#[ignore]
-fn test_game_flow_multiple_champions_draw() {
-    let config = GameConfig {
-        max_cycles: 100,
-        ..Default::default()
-    };
+fn test_game_flow_multiple_champions_last_live_wins() {
+    let config = GameConfig::default().with_max_cycles(100);
     let mut engine = GameEngine::new(config);
 
     let champion1 = create_live_champion("ChampA");
@@ -73,17 +45,17 @@ fn test_game_flow_multiple_champions_draw() {
     engine.start().unwrap();
 
     let winner = engine.run_to_completion().unwrap();
-    assert!(winner.is_none()); // Should be a draw if both are alive after max_cycles
-    assert!(!engine.get_stats().running);
+    // Both champions stay alive, so the last one to report live wins
+    let stats = engine.get_stats();
+    assert!(winner.is_some());
+    assert_eq!(winner, stats.last_live_champion);
+    assert_eq!(stats.winner, winner);
+    assert!(!stats.running);
 }
 
 #[test]
-#[ignore]
 fn test_game_flow_one_champion_dies() {
-    let config = GameConfig {
-        max_cycles: 1000, // Enough cycles for one to die
-        ..Default::default()
-    };
+    let config = GameConfig::default().with_max_cycles(1000); // Enough cycles for one to die
     let mut engine = GameEngine::new(config);
 
     let champion1 = create_live_champion("ChampX");
@@ -92,9 +64,39 @@ fn test_game_flow_one_champion_dies() {
     engine.start().unwrap();
 
     let winner = engine.run_to_completion().unwrap();
-    // With only live instructions, it should be a draw unless max_cycles is very high
-    // or one champion is explicitly killed by the game logic.
-    // For this test, we expect a draw if no explicit kill mechanism is used.
-    assert!(winner.is_none());
+    // The winner is decided by the last live report, not by surviving processes
+    assert_eq!(winner, engine.get_stats().last_live_champion);
+    assert!(winner.is_some());
     assert!(!engine.get_stats().running);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_battle_assertions() {
+    let config = GameConfig::default().with_max_cycles(100);
+    let mut engine = GameEngine::new(config);
+
+    let champion = create_live_champion("TestChamp");
+    engine.load_champions(&[champion.path()], None).unwrap();
+
+    let assertions: Vec<Assertion> = [
+        "winner==1",
+        "cycle<5000",
+        "mem[0]==0x01@1",
+        "processes>5",
+        "cycle>0@1000",
+    ]
+    .iter()
+    .map(|expr| Assertion::parse(expr).unwrap())
+    .collect();
+
+    let outcomes = run_with_assertions(&mut engine, &assertions).unwrap();
+    assert_eq!(outcomes.len(), 5);
+    assert!(outcomes[0].passed);
+    assert!(outcomes[1].passed);
+    assert!(outcomes[2].passed);
+    assert!(!outcomes[3].passed);
+
+    // The battle ends long before cycle 1000, so the check never ran
+    assert!(!outcomes[4].passed);
+    assert_eq!(outcomes[4].actual, None);
+}
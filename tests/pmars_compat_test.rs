@@ -0,0 +1,105 @@
+//! Matchups whose outcome pMARS defines, run under `RulesPreset::Pmars`
+//!
+//! pMARS has no `live`: processes die only by executing data, a warrior
+//! wins by outlasting the others, and warriors still running at the cycle
+//! limit draw. Each matchup is also run under the standard rules where they
+//! disagree, so a change that silently drops the preset shows up here.
+use corewar::constants::{CYCLE_TO_DIE, PMARS_CORE_SIZE, PMARS_MAX_CYCLES};
+use corewar::vm::{GameEngineBuilder, RulesPreset};
+use corewar::{GameEngine, Rules};
+
+/// A single data byte: the warrior dies on its first instruction
+const DAT: &[u8] = &[0x00];
+
+/// `live %1`, then data
+const LIVE: &[u8] = &[0x01, 0x00, 0x00, 0x00, 0x01];
+
+/// Warrior that reports live `lives` times, 10 cycles apart, before reaching data
+fn liver(lives: usize) -> Vec<u8> {
+    LIVE.repeat(lives)
+}
+
+/// Warrior that never reports live and runs `forks` forks of 800 cycles
+/// each before reaching data; its children land on data and die at once
+fn forker(forks: usize) -> Vec<u8> {
+    [0x0C, 0x00, 0x00, 0x00, 0x00].repeat(forks)
+}
+
+/// Run a matchup to the end
+fn battle(preset: RulesPreset, warriors: &[(&str, &[u8])]) -> GameEngine {
+    let mut builder = GameEngineBuilder::new().preset(preset);
+    for (name, code) in warriors {
+        builder = builder.champion_bytes(*name, code.to_vec());
+    }
+    let mut engine = builder.build().unwrap();
+    engine.run_to_completion().unwrap();
+    engine
+}
+
+#[test]
+fn test_preset_matches_pmars_arena() {
+    let engine = GameEngineBuilder::new()
+        .preset(RulesPreset::Pmars)
+        .champion_bytes("Imp", LIVE)
+        .build()
+        .unwrap();
+    assert_eq!(engine.config().max_cycles, PMARS_MAX_CYCLES);
+    assert_eq!(engine.memory().size(), PMARS_CORE_SIZE);
+    assert_eq!(engine.config().rules, RulesPreset::Pmars.rules());
+    assert_ne!(engine.config().rules, Rules::default());
+}
+
+#[test]
+fn test_dat_loses_on_the_first_cycle() {
+    for warriors in [
+        [("Dat", DAT), ("Live", LIVE)],
+        [("Live", LIVE), ("Dat", DAT)],
+    ] {
+        let mut engine = battle(RulesPreset::Pmars, &warriors);
+        let live_id = if warriors[0].0 == "Live" { 1 } else { 2 };
+        assert_eq!(engine.determine_winner().unwrap(), Some(live_id));
+        assert_eq!(engine.get_stats().cycle, 1);
+    }
+}
+
+#[test]
+fn test_warriors_need_not_report_live() {
+    let short = forker(3);
+    let long = forker(4);
+    let warriors: [(&str, &[u8]); 2] = [("Short", &short), ("Long", &long)];
+
+    // pMARS: the warrior that runs longer outlasts the other and wins
+    let mut engine = battle(RulesPreset::Pmars, &warriors);
+    assert_eq!(engine.determine_winner().unwrap(), Some(2));
+    assert!(engine.get_stats().cycle > 3 * 800);
+    assert!(engine.get_stats().cycle < 4 * 800);
+
+    // Standard rules: neither reported live, so both die at the first check
+    let mut engine = battle(RulesPreset::Standard, &warriors);
+    assert_eq!(engine.determine_winner().unwrap(), None);
+    assert_eq!(engine.get_stats().cycle, CYCLE_TO_DIE);
+}
+
+#[test]
+fn test_warriors_that_die_together_draw() {
+    let code = forker(2);
+    let mut engine = battle(RulesPreset::Pmars, &[("A", &code), ("B", &code)]);
+    assert_eq!(engine.determine_winner().unwrap(), None);
+    assert!(engine.processes().is_empty());
+}
+
+#[test]
+fn test_survivors_at_the_cycle_limit_draw() {
+    // Both warriors still run at the (shortened) limit; reporting live does not help
+    let mut engine = GameEngineBuilder::new()
+        .preset(RulesPreset::Pmars)
+        .max_cycles(500)
+        .champion_bytes("Live", liver(60))
+        .champion_bytes("Forker", forker(1))
+        .build()
+        .unwrap();
+    engine.run_to_completion().unwrap();
+
+    assert_eq!(engine.get_stats().cycle, 500);
+    assert_eq!(engine.determine_winner().unwrap(), None);
+}
@@ -0,0 +1,65 @@
+use corewar::prelude::*;
+use std::sync::{Arc, Mutex};
+
+/// Counts events the way a downstream visualizer would observe them
+struct Counter(Arc<Mutex<usize>>);
+
+impl GameObserver for Counter {
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::CycleCompleted { .. } = event {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+}
+
+/// A battle built, observed and inspected through the prelude alone
+#[test]
+fn test_prelude_runs_a_battle() -> Result<()> {
+    let builder: GameEngineBuilder = GameEngine::builder()
+        .config(GameConfig::default().with_max_cycles(20))
+        .rules(Rules::default().with_max_processes(64))
+        .placement(Placement::Spread)
+        .champion_bytes("Liver", [0x01; 100])
+        .champion_bytes("Idle", [0x02; 100]);
+    let mut engine = builder.build()?;
+
+    let cycles = Arc::new(Mutex::new(0));
+    engine.subscribe(Box::new(Counter(cycles.clone())));
+    assert_eq!(engine.tick()?, TickOutcome::Running);
+    let winner = engine.run_to_completion()?;
+
+    let state: &GameState = engine.state();
+    let stats: GameStats = engine.get_stats();
+    let champions: &[Champion] = engine.champions();
+    assert_eq!(*cycles.lock().unwrap(), state.cycle as usize);
+    assert_eq!(winner, Some(champions[0].id));
+    assert_eq!(stats.winner, winner);
+
+    let snapshot: GameSnapshot = engine.snapshot();
+    assert_eq!(snapshot.cycle, state.cycle);
+    Ok(())
+}
+
+/// The assembler and its errors are part of the prelude too
+#[test]
+fn test_prelude_assembles() {
+    let assembler = Assembler::new(false);
+    let error: CoreWarError = assembler.assemble_string("start: bogus %1\n").unwrap_err();
+    assert!(!error.to_string().is_empty());
+}
+
+/// Structs with public fields are built with their `with_*` methods
+#[test]
+fn test_prelude_structs_build_with_setters() {
+    let rules = Rules::default().with_memory_size(4096).with_idx_mod(256);
+    assert_eq!((rules.memory_size, rules.idx_mod), (4096, 256));
+    assert!(rules.validate().is_ok());
+
+    let config = GameConfig::default().with_rules(rules).with_seed(7);
+    assert_eq!(config.rules, rules);
+    assert_eq!(config.seed, 7);
+
+    let options = ChampionOptions::default().with_number(Some(3));
+    assert_eq!(options.number, Some(3));
+    assert_eq!(options.alias, None);
+}
@@ -1,5 +1,6 @@
 use proptest::prelude::*;
 use corewar::assembler::Assembler;
+use corewar::vm::loader::write_champion;
 use corewar::vm::{GameConfig, GameEngine};
 use tempfile::NamedTempFile;
 use std::io::Write;
@@ -7,20 +8,8 @@ use std::io::Write;
 // Helper to create a dummy champion file for VM tests
 fn create_dummy_champion(name: &str, code: &[u8]) -> NamedTempFile {
     let mut file = NamedTempFile::new().unwrap();
-    let magic = 0xea83f3u32;
-    file.write_all(&magic.to_le_bytes()).unwrap();
-    let mut name_bytes = [0u8; 128];
-    let name_src = name.as_bytes();
-    name_bytes[..name_src.len()].copy_from_slice(name_src);
-    file.write_all(&name_bytes).unwrap();
-    file.write_all(&[0u8; 4]).unwrap();
-    file.write_all(&(code.len() as u32).to_le_bytes()).unwrap();
     let comment = format!("{} - dummy champion", name);
-    let mut comment_bytes = [0u8; 128];
-    comment_bytes[..comment.len().min(127)].copy_from_slice(comment.as_bytes());
-    file.write_all(&comment_bytes).unwrap();
-    file.write_all(&[0u8; 4]).unwrap();
-    file.write_all(code).unwrap();
+    write_champion(&mut file, name, &comment, code).unwrap();
     file.flush().unwrap();
     file
 }
@@ -30,12 +19,12 @@ proptest! {
     #[test]
     fn prop_assemble_disassemble_simple_instruction(opcode in 0x01u8..=0x10) {
         let program_str = format!(
-            ".name \"TestChamp\"\n.comment \"A test champion\"\n\nlive {}\n",
+            ".name \"TestChamp\"\n.comment \"A test champion\"\n\nlive %{}\n",
             opcode
         );
         let assembler = Assembler::new(false);
         let bytecode = assembler.assemble_source(&program_str).unwrap();
-        prop_assert!(bytecode.len() > 0);
+        prop_assert!(!bytecode.is_empty());
     }
 }
 
@@ -52,22 +41,19 @@ proptest! {
 
         let handle = thread::spawn(move || {
             let result = (|| -> Result<(), TestCaseError> {
-                let config = GameConfig {
-                    max_cycles,
-                    ..Default::default()
-                };
+                let config = GameConfig::default().with_max_cycles(max_cycles);
                 let mut engine = GameEngine::new(config);
 
                 // Create two different champions for a proper Core War battle
-                let champion1_file = create_dummy_champion("Champion1", &[0x01, 0x40, 0x01, 0x00]); // live %1
-                let champion2_file = create_dummy_champion("Champion2", &[0x01, 0x40, 0x02, 0x00]); // live %2
+                let champion1_file = create_dummy_champion("Champion1", &[0x01, 0x00, 0x00, 0x00, 0x01]); // live %1
+                let champion2_file = create_dummy_champion("Champion2", &[0x01, 0x00, 0x00, 0x00, 0x02]); // live %2
                 
                 engine.load_champions(&[champion1_file.path(), champion2_file.path()], None).unwrap();
                 engine.start().unwrap();
 
                 // Manually tick the engine for a reasonable number of cycles
                 let mut ticks = 0;
-                while engine.tick().unwrap() && ticks < max_cycles * 2 {
+                while engine.tick().unwrap().is_running() && ticks < max_cycles * 2 {
                     ticks += 1;
                 }
 
@@ -86,6 +72,54 @@ proptest! {
             Err(mpsc::RecvTimeoutError::Disconnected) => panic!("Test thread disconnected"),
         }
 
-        let _ = handle.join().unwrap();
+        handle.join().unwrap();
+    }
+}
+
+/// Generate one instruction as Redcode, with parameters in the range its encoding holds
+fn arb_instruction() -> impl Strategy<Value = String> {
+    use corewar::vm::instruction::Instruction;
+
+    let parameters = prop::collection::vec((1u8..=3, any::<i32>()), 3);
+    (prop::sample::select(Instruction::ALL.to_vec()), parameters).prop_map(
+        |(instruction, parameters)| {
+            let parameters: Vec<String> = parameters
+                .iter()
+                .take(instruction.parameter_count())
+                .map(|&(type_code, value)| {
+                    // Without a parameter types byte the parameter is always direct
+                    let type_code = if instruction.has_parameter_types() {
+                        type_code
+                    } else {
+                        2
+                    };
+                    match type_code {
+                        1 => format!("r{}", value.rem_euclid(16) + 1),
+                        2 if instruction.uses_index_parameters() => format!("%{}", value as i16),
+                        2 => format!("%{}", value),
+                        _ => (value as i16).to_string(),
+                    }
+                })
+                .collect();
+            format!("{} {}", instruction.name(), parameters.join(", "))
+        },
+    )
+}
+
+// Property: Disassembling assembled code and assembling it again yields the same bytes
+proptest! {
+    #[test]
+    fn prop_disassemble_round_trip(instructions in prop::collection::vec(arb_instruction(), 1..20)) {
+        use corewar::assembler::disassembler::disassemble;
+
+        let program_str = format!(
+            ".name \"RoundTrip\"\n.comment \"A test champion\"\n\n{}\n",
+            instructions.join("\n")
+        );
+        let assembler = Assembler::new(false);
+        let bytecode = assembler.assemble_source(&program_str).unwrap();
+
+        let source = disassemble(&bytecode).unwrap();
+        prop_assert_eq!(assembler.assemble_source(&source).unwrap(), bytecode, "{}", source);
     }
 }
@@ -1,6 +1,5 @@
 use corewar::assembler::Assembler;
-
-const COR_MAGIC: u32 = 0xea83f3;
+use corewar::vm::loader::COR_MAGIC;
 
 #[test]
 fn test_assemble_simple_champion() {
@@ -18,7 +17,7 @@ fn test_assemble_simple_champion() {
 
     // Header size is 4 (magic) + 128 (name) + 4 (padding) + 4 (size) + 128 (comment) + 4 (padding) = 272
     let header_size = 272;
-    let instruction_size = 1 + 1 + 2 + 1 + 1 + 2; // live %1 (opcode + param_types + 2 bytes) + zjmp %:start (opcode + param_types + 2 bytes)
+    let instruction_size = 1 + 4 + 1 + 2; // live %1 (opcode + 4 bytes) + zjmp %:start (opcode + 2 bytes)
     assert_eq!(bytecode.len(), header_size + instruction_size);
 
     // Check magic number (stored in little-endian)
@@ -31,14 +30,265 @@ fn test_assemble_simple_champion() {
     assert_eq!(name_str, "simple");
 
     // Check bytecode for live %1
+    // Parameters are big-endian, and live and zjmp have no parameter types byte
     assert_eq!(bytecode[header_size], 0x01); // live opcode
-    assert_eq!(bytecode[header_size + 1], 0b10000000); // Parameter type (direct)
-    let live_param = u16::from_le_bytes([bytecode[header_size + 2], bytecode[header_size + 3]]);
+    let live_param = u32::from_be_bytes(bytecode[header_size + 1..header_size + 5].try_into().unwrap());
     assert_eq!(live_param, 1);
 
     // Check bytecode for zjmp %:start
-    assert_eq!(bytecode[header_size + 4], 0x09); // zjmp opcode
-    assert_eq!(bytecode[header_size + 5], 0b10000000); // Parameter type (direct)
-    let zjmp_param = u16::from_le_bytes([bytecode[header_size + 6], bytecode[header_size + 7]]);
-    assert_eq!(zjmp_param, 0); // Jumps to the start
+    assert_eq!(bytecode[header_size + 5], 0x09); // zjmp opcode
+    let zjmp_param = i16::from_be_bytes([bytecode[header_size + 6], bytecode[header_size + 7]]);
+    assert_eq!(zjmp_param, -5); // Jumps back to the start
+}
+
+#[test]
+fn test_assemble_parameter_expressions() {
+    let source = r#"
+        .name "exprs"
+        .comment ""
+
+    start:
+        ld %(2*8), r1
+        zjmp %:end+4
+        st r1, end-start
+    end:
+        live %1
+    "#;
+
+    let bytecode = Assembler::new(false).assemble_source(source).unwrap();
+    let code = &bytecode[272..];
+
+    // ld %(2*8), r1: a 4-byte direct value
+    assert_eq!(i32::from_be_bytes(code[2..6].try_into().unwrap()), 16);
+    // zjmp %:end+4 at 7: end is at 7 + 3 + 5
+    assert_eq!(i16::from_be_bytes([code[8], code[9]]), 8 + 4);
+    // st r1, end-start: the distance between the labels
+    assert_eq!(code[10], 0x03);
+    assert_eq!(code[11], 0b0111_0000);
+    assert_eq!(i16::from_be_bytes([code[13], code[14]]), 15);
+}
+
+#[test]
+fn test_assemble_macros() {
+    let with_macro = r#"
+        .name "macro"
+        .comment ""
+    .macro bomb offset, value
+        ld %value, r2
+        st r2, offset
+    .endm
+    start:
+        bomb 64, 0
+        bomb :start, 7
+        zjmp %:start
+    "#;
+    let by_hand = r#"
+        .name "macro"
+        .comment ""
+    start:
+        ld %0, r2
+        st r2, 64
+        ld %7, r2
+        st r2, :start
+        zjmp %:start
+    "#;
+
+    let assembler = Assembler::new(false);
+    assert_eq!(
+        assembler.assemble_source(with_macro).unwrap(),
+        assembler.assemble_source(by_hand).unwrap()
+    );
+
+    // Errors in expanded code point at the invocation
+    let error = assembler
+        .assemble_source(&with_macro.replace("bomb :start, 7", "bomb :nowhere, 7"))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("Undefined label 'nowhere'\n  --> <source>:10:9"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_assemble_file_with_include() {
+    let directory = tempfile::tempdir().unwrap();
+    let lib = directory.path().join("lib");
+    std::fs::create_dir(&lib).unwrap();
+    std::fs::write(
+        lib.join("common.s"),
+        ".equ STEP 8\n.macro keep\n    live %1\n.endm\n",
+    )
+    .unwrap();
+    let main = directory.path().join("warrior.s");
+    std::fs::write(
+        &main,
+        ".name \"inc\"\n.include \"lib/common.s\"\nstart: keep\n    zjmp %:start\n    st r1, STEP\n",
+    )
+    .unwrap();
+
+    let assembler = Assembler::new(false);
+    let included = assembler.assemble_file(&main, None).unwrap();
+    let inline = assembler
+        .assemble_source(".name \"inc\"\nstart: live %1\nzjmp %:start\nst r1, 8\n")
+        .unwrap();
+    assert_eq!(included, inline);
+
+    // Errors inside the included file name it
+    std::fs::write(
+        lib.join("common.s"),
+        ".equ STEP 8\n.macro keep\n    live %1\n.endm\n    live %:nowhere\n",
+    )
+    .unwrap();
+    let error = assembler.assemble_file(&main, None).unwrap_err().to_string();
+    assert!(
+        error.contains("Undefined label 'nowhere'\n  --> "),
+        "{}",
+        error
+    );
+    assert!(
+        error.ends_with("common.s:5:10\n   |\n 5 |     live %:nowhere\n   |          ^"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_assemble_reports_every_error() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("broken.s");
+    std::fs::write(
+        &path,
+        ".name \"broken\"\nstart: live %1\n    mov 0, 1\n    ld %1, r17\n\tzjmp %:nowhere\n    st r1, $\n",
+    )
+    .unwrap();
+
+    let error = Assembler::new(false)
+        .assemble_file(&path, None)
+        .unwrap_err()
+        .to_string();
+    let reports: Vec<&str> = error.split("\n\n").collect();
+    assert_eq!(reports[0], "Assembler error: 4 errors");
+    assert_eq!(reports.len(), 5, "{}", error);
+
+    // In source order within each stage, each with its line
+    assert!(reports[1].starts_with("Unexpected character '$'\n  --> "));
+    assert!(reports[1].ends_with("broken.s:6:12\n   |\n 6 |     st r1, $\n   |            ^"));
+    assert!(reports[2].starts_with("Unknown instruction 'mov'\n"));
+    assert!(reports[2].ends_with(" 3 |     mov 0, 1\n   |     ^"));
+    assert!(reports[3].starts_with("Register out of range: r17\n"));
+    assert!(reports[3].ends_with("broken.s:4:12\n   |\n 4 |     ld %1, r17\n   |            ^"));
+    assert!(reports[4].starts_with("Undefined label 'nowhere'\n"));
+    assert!(reports[4].ends_with(" 5 | \tzjmp %:nowhere\n   | \t     ^"));
+}
+
+#[test]
+fn test_warnings_and_deny_warnings() {
+    let source = ".name \"w\"\nstart: live %1\nidle: ld %0, r2\nzjmp %:start\n";
+
+    let assembler = Assembler::new(false);
+    let bytecode = assembler.assemble_source(source).unwrap();
+    let warnings = assembler.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].to_string(),
+        "warning: Label 'idle' is never used\n  --> <source>:3\n   |\n 3 | idle: ld %0, r2"
+    );
+
+    let strict = Assembler::new(false).with_deny_warnings(true);
+    let error = strict.assemble_source(source).unwrap_err().to_string();
+    assert!(error.contains("Label 'idle' is never used"), "{}", error);
+    assert_eq!(
+        strict
+            .assemble_source(&source.replace("idle: ", ""))
+            .unwrap(),
+        bytecode
+    );
+    assert!(strict.warnings().is_empty());
+}
+
+#[test]
+fn test_listing_of_an_included_file() {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::write(directory.path().join("keep.s"), "keep: live %1\n").unwrap();
+    let main = directory.path().join("warrior.s");
+    std::fs::write(
+        &main,
+        ".name \"l\"\nstart: ld %0, r2\n.include \"keep.s\"\nzjmp %:start\n",
+    )
+    .unwrap();
+
+    let assembler = Assembler::new(false);
+    assert!(assembler.listing().is_none());
+    assembler.assemble_file(&main, None).unwrap();
+    let listing = assembler.listing().unwrap();
+    assert_eq!(
+        listing.symbols(),
+        [("start".to_string(), 0), ("keep".to_string(), 7)]
+    );
+    assert_eq!(listing.code_size(), 7 + 5 + 3);
+
+    let text = listing.to_string();
+    let main_at = text.find("warrior.s\n").unwrap();
+    let included_at = text.find("keep.s\n").unwrap();
+    assert!(main_at < included_at, "{}", text);
+    assert!(text.contains("    4  000c  09 ff f4"), "{}", text);
+    assert!(text.contains("    1  0007  01 00 00 00 01"), "{}", text);
+    assert!(text.ends_with("Code size: 15 bytes\n"));
+
+    // A failed assembly leaves no listing behind
+    std::fs::write(&main, ".name \"l\"\nzjmp %:nowhere\n").unwrap();
+    assert!(assembler.assemble_file(&main, None).is_err());
+    assert!(assembler.listing().is_none());
+}
+
+#[test]
+fn test_assembly_in_memory_writes_no_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("imp.s");
+    let source = ".name \"imp\"\nstart: live %1\nzjmp %:start\n";
+    std::fs::write(&path, source).unwrap();
+
+    let assembler = Assembler::new(false);
+    let from_string = assembler.assemble_string(source).unwrap();
+    assert_eq!(assembler.assemble_path(&path).unwrap(), from_string);
+    assert_eq!(assembler.assemble_source(source).unwrap(), from_string);
+
+    let files: Vec<_> = std::fs::read_dir(directory.path()).unwrap().collect();
+    assert_eq!(files.len(), 1);
+}
+
+#[test]
+fn test_load_files_load_like_cor_files() {
+    use corewar::ChampionLoader;
+    use corewar::assembler::loadfile;
+
+    let directory = tempfile::tempdir().unwrap();
+    let source = ".name \"imp\"\n.comment \"exchanged\"\nstart: live %1\nzjmp %:start\n";
+    let bytecode = Assembler::new(false).assemble_source(source).unwrap();
+    let load_file = directory.path().join("imp.red");
+    std::fs::write(&load_file, loadfile::export(&bytecode).unwrap()).unwrap();
+    let cor_file = directory.path().join("imp.cor");
+    std::fs::write(&cor_file, &bytecode).unwrap();
+
+    let loader = ChampionLoader::new(true);
+    let from_load_file = loader.load_champion(&load_file, 1, None).unwrap();
+    let from_cor_file = loader.load_champion(&cor_file, 1, None).unwrap();
+    assert_eq!(from_load_file.name, "imp");
+    assert_eq!(from_load_file.comment, "exchanged");
+    assert_eq!(from_load_file.code, from_cor_file.code);
+
+    // Errors point into the load file
+    std::fs::write(&load_file, ";redcode-94\n;name imp\nSPL 0\n").unwrap();
+    let error = loader
+        .load_champion(&load_file, 1, None)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("ICWS'94 opcode 'SPL' is not supported"),
+        "{}",
+        error
+    );
+    assert!(error.contains("imp.red:3:1"), "{}", error);
 }
@@ -13,14 +13,14 @@ fn test_visual_effects_working() {
     
     // Create some test data
     let _memory = Memory::new();
-    let _champions = vec![
+    let _champions = [
         Champion::new(1, "TestChamp1".to_string(), "Test 1".to_string(), vec![0x01], 0)
             .with_color(ChampionColor::Red),
         Champion::new(2, "TestChamp2".to_string(), "Test 2".to_string(), vec![0x02], 100)
             .with_color(ChampionColor::Blue),
     ];
     
-    let processes = vec![
+    let processes = [
         Process::new(1, 1, 0, ChampionColor::Red),
         Process::new(2, 2, 100, ChampionColor::Blue),
     ];
@@ -2,9 +2,10 @@
 ///
 /// This test verifies that the advanced memory grid correctly integrates
 /// with the real Core War VM and processes battle events.
-
 use corewar::{GameConfig, GameEngine};
 use corewar::ui::app::App;
+use corewar::vm::loader::write_champion;
+use corewar::vm::pacing::Speed;
 use std::fs::File;
 use std::io::Write;
 use tempfile::TempDir;
@@ -23,13 +24,12 @@ fn test_advanced_memory_integration() {
     create_test_cor_file(&champ2_path, "TestChamp2", "Test champion 2", &[0x04, 0x80, 0x04, 0x00]);
     
     // Create a game engine with test configuration
-    let config = GameConfig {
-        max_cycles: 100,
-        dump_cycles: 0,
-        speed: 1,
-        verbose: false,
-        start_paused: false,
-    };
+    let config = GameConfig::default()
+        .with_max_cycles(100)
+        .with_dump_cycles(0)
+        .with_speed(1)
+        .with_verbose(false)
+        .with_start_paused(false);
     
     let mut engine = GameEngine::new(config);
     
@@ -43,11 +43,12 @@ fn test_advanced_memory_integration() {
     
     // Verify initial state
     assert!(!app.paused);
-    assert_eq!(app.speed, 1);
+    assert_eq!(app.speed, Speed::Fast(1));
     
     // Run several VM ticks through the app
     for i in 0..10 {
-        app.update().expect(&format!("Update {} should succeed", i));
+        app.update()
+            .unwrap_or_else(|e| panic!("Update {} should succeed: {}", i, e));
     }
     
     // The test passes if we got here without panicking - 
@@ -87,34 +88,6 @@ fn test_app_memory_tracking() {
 /// Create a test .cor file with proper format
 fn create_test_cor_file(path: &std::path::Path, name: &str, comment: &str, code: &[u8]) {
     let mut file = File::create(path).unwrap();
-    
-    // Write Core War .cor file header
-    let magic: u32 = 0xea83f3;
-    file.write_all(&magic.to_le_bytes()).unwrap(); // Magic
-    
-    // Write name (128 bytes)
-    let mut name_bytes = [0u8; 128];
-    let name_src = name.as_bytes();
-    name_bytes[..name_src.len()].copy_from_slice(name_src);
-    file.write_all(&name_bytes).unwrap();
-    
-    // Write padding (4 bytes)
-    file.write_all(&[0u8; 4]).unwrap();
-    
-    // Write code size
-    file.write_all(&(code.len() as u32).to_le_bytes()).unwrap();
-    
-    // Write comment (128 bytes)
-    let mut comment_bytes = [0u8; 128];
-    let comment_src = comment.as_bytes();
-    comment_bytes[..comment_src.len()].copy_from_slice(comment_src);
-    file.write_all(&comment_bytes).unwrap();
-    
-    // Write final padding (4 bytes)
-    file.write_all(&[0u8; 4]).unwrap();
-    
-    // Write code
-    file.write_all(code).unwrap();
-    
+    write_champion(&mut file, name, comment, code).unwrap();
     file.flush().unwrap();
 }
\ No newline at end of file